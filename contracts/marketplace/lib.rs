@@ -6,16 +6,80 @@ pub mod marketplace {
     use ink::{
         codegen::{EmitEvent, Env},
         env::DefaultEnvironment,
+        prelude::vec::Vec,
         EnvAccess,
     };
     use openbrush::{
-        contracts::{ownable::*, psp34::Id, reentrancy_guard::*},
+        contracts::{
+            access_control::{self, AccessControl},
+            ownable::*,
+            pausable::{self, Pausable},
+            psp34::{Id, PSP34Receiver, PSP34ReceiverError},
+            reentrancy_guard::*,
+        },
         modifiers,
         traits::{Storage, String},
     };
     use pallet_marketplace::{
-        impls::marketplace::{marketplace_sale::MarketplaceSaleEvents, types::MarketplaceError, *},
-        traits::marketplace::*,
+        helpers::events::{Compact, EVENT_SCHEMA_VERSION},
+        impls::marketplace::{
+            batch::DEFAULT_MAX_BATCH_SIZE,
+            marketplace_auctions::MarketplaceAuctionsEvents,
+            marketplace_circuit_breaker::MarketplaceCircuitBreakerEvents,
+            marketplace_coupons::MarketplaceCouponsEvents,
+            marketplace_credits::MarketplaceCreditsEvents,
+            marketplace_curation::MarketplaceCurationEvents,
+            marketplace_custody::{
+                when_not_taking_custody, Internal as CustodyInternal, MarketplaceCustodyEvents,
+            },
+            marketplace_disputes::MarketplaceDisputesEvents,
+            marketplace_editions::MarketplaceEditionsEvents,
+            marketplace_escrow::MarketplaceEscrowEvents,
+            marketplace_flash_sales::MarketplaceFlashSalesEvents,
+            marketplace_fractions::MarketplaceFractionsEvents,
+            marketplace_governance::MarketplaceGovernanceEvents,
+            marketplace_group_purchase::MarketplaceGroupPurchaseEvents,
+            marketplace_installments::MarketplaceInstallmentsEvents,
+            marketplace_lending::MarketplaceLendingEvents,
+            marketplace_limit_orders::MarketplaceLimitOrdersEvents,
+            marketplace_migration::MarketplaceMigrationEvents,
+            marketplace_moderation::MarketplaceModerationEvents,
+            marketplace_multisig::MarketplaceMultisigEvents,
+            marketplace_mystery_boxes::MarketplaceMysteryBoxesEvents,
+            marketplace_nested_listings::MarketplaceNestedListingsEvents,
+            marketplace_orders::MarketplaceOrdersEvents,
+            marketplace_partners::MarketplacePartnersEvents,
+            marketplace_physical_sales::MarketplacePhysicalSalesEvents,
+            marketplace_raffles::MarketplaceRafflesEvents,
+            marketplace_recovery::MarketplaceRecoveryEvents,
+            marketplace_referrals::MarketplaceReferralsEvents,
+            marketplace_rentals::MarketplaceRentalsEvents,
+            marketplace_sale::MarketplaceSaleEvents,
+            marketplace_staking::MarketplaceStakingEvents,
+            marketplace_subscriptions::MarketplaceSubscriptionsEvents,
+            marketplace_timelock::MarketplaceTimelockEvents,
+            marketplace_trade::MarketplaceTradeEvents,
+            marketplace_wash_trading::MarketplaceWashTradingEvents,
+            marketplace_xcm::MarketplaceXcmEvents,
+            types::{
+                AdminOperation, Appended, Coupon, CouponDiscount, CouponFunding, Currency,
+                DeliveryOutcome, DisputeSubject, EditionSide, FeatureId, FlashSale, HeldAsset,
+                MarketplaceError, NftContractType, ParameterChange, ProposalStatus, RateLimit,
+                Subscription, SubscriptionPlan, WashTrade, DEFAULT_MAX_FEE, STORAGE_VERSION,
+                UPGRADER,
+            },
+            *,
+        },
+        traits::{
+            accounting::*, admin::*, auctions::*, circuit_breaker::*, claims::*, coupons::*,
+            credits::*, curation::*, custody::*, deposit::*, disputes::*, editions::*, escrow::*,
+            factory::*, flash_sales::*, fractions::*, governance::*, group_purchase::*,
+            installments::*, inventory::*, lending::*, limit_orders::*, marketplace::*,
+            migration::*, moderation::*, multisig::*, mystery_boxes::*, nested_listings::*,
+            offer::*, orders::*, partners::*, physical_sales::*, raffles::*, recovery::*,
+            referrals::*, rentals::*, snapshots::*, staking::*, subscriptions::*, timelock::*,
+            trade::*, wash_trading::*, xcm::*,
+        },
     };
 
     // MarketplaceContract contract storage
@@ -28,266 +92,10178 @@ pub mod marketplace {
         guard: reentrancy_guard::Data,
         #[storage_field]
         marketplace: types::Data,
+        // added after the first deployment, so they follow the fields of its root cell
+        pause: Appended<pausable::Data>,
+        access: Appended<access_control::Data>,
     }
 
-    /// Event emitted when token is listed or unlisted
+    impl Storage<pausable::Data> for MarketplaceContract {
+        fn get(&self) -> &pausable::Data {
+            &self.pause
+        }
+
+        fn get_mut(&mut self) -> &mut pausable::Data {
+            &mut self.pause
+        }
+    }
+
+    impl Storage<access_control::Data> for MarketplaceContract {
+        fn get(&self) -> &access_control::Data {
+            &self.access
+        }
+
+        fn get_mut(&mut self) -> &mut access_control::Data {
+            &mut self.access
+        }
+    }
+
+    /// Event emitted when token is listed
     #[ink(event)]
     pub struct TokenListed {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        price: Option<Compact<Balance>>,
+    }
+
+    /// Event emitted when token is unlisted
+    #[ink(event)]
+    pub struct TokenUnlisted {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    /// Event emitted when a listed token is relisted by its owner
+    #[ink(event)]
+    pub struct ListingUpdated {
+        version: u8,
         #[ink(topic)]
         contract: AccountId,
         #[ink(topic)]
         id: Id,
+        old_price: Compact<Balance>,
+        new_price: Compact<Balance>,
+        currency: Currency,
+    }
+
+    /// Event emitted when buyers of a listing are restricted to holders of a collection
+    #[ink(event)]
+    pub struct ListingGateUpdated {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
         #[ink(topic)]
-        price: Option<Balance>,
+        id: Id,
+        gate_collection: Option<AccountId>,
     }
 
     /// Event emitted when deposit for offer
     #[ink(event)]
     pub struct Deposit {
+        version: u8,
         #[ink(topic)]
         account_id: AccountId,
         #[ink(topic)]
+        currency: Currency,
+        #[ink(topic)]
         amount: Balance,
+        new_balance: Compact<Balance>,
     }
 
     /// Event emitted when withdraw for offer
     #[ink(event)]
     pub struct Withdraw {
+        version: u8,
         #[ink(topic)]
         account_id: AccountId,
         #[ink(topic)]
+        currency: Currency,
+        #[ink(topic)]
         amount: Balance,
+        new_balance: Compact<Balance>,
+    }
+
+    /// Event emitted when deposit is transferred to another account
+    #[ink(event)]
+    pub struct DepositTransferred {
+        version: u8,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when deposit owner approves a spender
+    #[ink(event)]
+    pub struct SpenderApproved {
+        version: u8,
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        allowance: Compact<Balance>,
     }
 
     /// Event emitted when make offer
     #[ink(event)]
     pub struct MakeOffer {
+        version: u8,
         #[ink(topic)]
         offer_id: u128,
         #[ink(topic)]
         contract: AccountId,
-        #[ink(topic)]
         id: Option<Id>,
+        #[ink(topic)]
         bidder_id: AccountId,
-        price_per_item: Balance,
+        price_per_item: Compact<Balance>,
         quantity: u64,
         extra: String,
     }
 
     #[ink(event)]
     pub struct CancelOffer {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+    }
+
+    /// Event emitted when an offer no longer covered by the bidder's deposit is removed.
+    #[ink(event)]
+    pub struct OfferAutoCancelled {
+        version: u8,
         #[ink(topic)]
         offer_id: u128,
+        #[ink(topic)]
+        bidder: AccountId,
     }
 
     #[ink(event)]
     pub struct AcceptOffer {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+    }
+
+    /// Event emitted when an offer bond is forfeited to the fee recipient.
+    #[ink(event)]
+    pub struct OfferBondForfeited {
+        version: u8,
         #[ink(topic)]
         offer_id: u128,
+        amount: Compact<Balance>,
     }
 
-    /// Event emitted when a token is bought
+    /// Event emitted when a token is bought, with the split of the sale price
     #[ink(event)]
     pub struct TokenBought {
+        version: u8,
+        sale_id: Compact<u128>,
         #[ink(topic)]
         contract: AccountId,
-        #[ink(topic)]
         id: Id,
+        price: Compact<Balance>,
         #[ink(topic)]
-        price: Balance,
-        from: AccountId,
-        to: AccountId,
+        seller: AccountId,
+        #[ink(topic)]
+        buyer: AccountId,
+        marketplace_fee: Compact<Balance>,
+        royalty_receiver: AccountId,
+        royalty: Compact<Balance>,
+        currency: Currency,
     }
 
     /// Event emitted when a NFT contract is registered to the marketplace.
     #[ink(event)]
     pub struct CollectionRegistered {
+        version: u8,
         #[ink(topic)]
         contract: AccountId,
     }
 
-    impl MarketplaceContract {
-        #[ink(constructor)]
-        pub fn new(market_fee_recipient: AccountId) -> Self {
-            let mut instance = Self::default();
-            instance.marketplace.fee = 100; // 1%
-            instance.marketplace.max_fee = 1000; // 10%
-            instance.marketplace.market_fee_recipient = Option::Some(market_fee_recipient);
+    /// Event emitted when royalty of a registered collection is changed.
+    #[ink(event)]
+    pub struct CollectionRoyaltyUpdated {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        old_royalty: Option<(AccountId, u16)>,
+        new_royalty: Option<(AccountId, u16)>,
+    }
 
-            let caller = instance.env().caller();
-            instance._init_with_owner(caller);
-            instance
-        }
+    /// Event emitted when metadata of a registered collection is changed.
+    #[ink(event)]
+    pub struct CollectionMetadataUpdated {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        old_metadata: Option<String>,
+        new_metadata: String,
+    }
 
-        #[ink(message)]
-        #[modifiers(only_owner)]
-        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), MarketplaceError> {
-            ink::env::set_code_hash(&code_hash).unwrap_or_else(|err| {
-                panic!(
-                    "Failed to `set_code_hash` to {:?} due to {:?}",
-                    code_hash, err
-                )
-            });
-            ink::env::debug_println!("Switched code hash to {:?}.", code_hash);
-            Ok(())
-        }
+    /// Event emitted when a registered collection is verified or unverified.
+    #[ink(event)]
+    pub struct CollectionVerificationUpdated {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        old_verified: bool,
+        new_verified: bool,
     }
 
-    impl MarketplaceSaleEvents for MarketplaceContract {
-        fn emit_token_listed_event(
-            &self,
-            contract: AccountId,
-            token_id: Id,
-            price: Option<Balance>,
-        ) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                TokenListed,
-            >(
-                self.env(),
-                TokenListed {
-                    contract,
-                    id: token_id,
-                    price,
-                },
-            );
-        }
+    /// Event emitted when a NFT contract is removed from the marketplace.
+    #[ink(event)]
+    pub struct CollectionUnregistered {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        royalty: Option<(AccountId, u16)>,
+        verified: bool,
+    }
 
-        fn emit_token_bought_event(
-            &self,
-            contract: AccountId,
-            token_id: Id,
-            price: Balance,
-            from: AccountId,
-            to: AccountId,
-        ) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                TokenBought,
-            >(
-                self.env(),
-                TokenBought {
-                    contract,
-                    id: token_id,
-                    price,
-                    from,
-                    to,
-                },
-            );
-        }
+    /// Event emitted when the marketplace factory creates a new NFT contract.
+    #[ink(event)]
+    pub struct FactoryInstantiated {
+        version: u8,
+        #[ink(topic)]
+        creator: AccountId,
+        #[ink(topic)]
+        contract: AccountId,
+        contract_type: NftContractType,
+        code_hash: Hash,
+    }
 
-        fn emit_collection_registered_event(&self, contract: AccountId) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                CollectionRegistered,
-            >(self.env(), CollectionRegistered { contract })
-        }
+    /// Event emitted when a purchase of an escrowed item is made.
+    #[ink(event)]
+    pub struct EscrowOpened {
+        version: u8,
+        #[ink(topic)]
+        escrow_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        buyer: AccountId,
+        release_time: u64,
+    }
 
-        fn emit_deposit_event(&self, account_id: AccountId, amount: Balance) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                Deposit,
-            >(self.env(), Deposit { account_id, amount })
-        }
+    /// Event emitted when escrowed proceeds are released to the seller.
+    #[ink(event)]
+    pub struct EscrowReleased {
+        version: u8,
+        #[ink(topic)]
+        escrow_id: u128,
+    }
 
-        fn emit_withdraw_event(&self, account_id: AccountId, amount: Balance) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                Withdraw,
-            >(self.env(), Withdraw { account_id, amount })
-        }
+    /// Event emitted when the buyer disputes an escrowed purchase.
+    #[ink(event)]
+    pub struct EscrowDisputed {
+        version: u8,
+        #[ink(topic)]
+        escrow_id: u128,
+    }
 
-        fn emit_make_offer_event(
-            &self,
-            bidder_id: AccountId,
-            contract: AccountId,
-            token_id: Option<Id>,
-            quantity: u64,
-            price_per_item: u128,
-            extra: String,
-            offer_id: u128,
-        ) {
-            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
-                MakeOffer,
-            >(
-                self.env(),
-                MakeOffer {
-                    offer_id,
-                    contract,
-                    id: token_id,
-                    bidder_id,
-                    price_per_item,
-                    quantity,
-                    extra,
+    /// Event emitted when the arbiter resolves a dispute.
+    #[ink(event)]
+    pub struct DisputeResolved {
+        version: u8,
+        #[ink(topic)]
+        escrow_id: u128,
+        refund_buyer: bool,
+    }
+
+    /// Event emitted when an item is bought in installments.
+    #[ink(event)]
+    pub struct InstallmentPlanCreated {
+        version: u8,
+        #[ink(topic)]
+        plan_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        buyer: AccountId,
+        installments: u8,
+    }
+
+    /// Event emitted when an installment is paid.
+    #[ink(event)]
+    pub struct InstallmentPaid {
+        version: u8,
+        #[ink(topic)]
+        plan_id: u128,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when an overdue payment plan is closed and the NFT returned to the seller.
+    #[ink(event)]
+    pub struct InstallmentPlanDefaulted {
+        version: u8,
+        #[ink(topic)]
+        plan_id: u128,
+    }
+
+    /// Event emitted when a group purchase vault is created.
+    #[ink(event)]
+    pub struct GroupPurchaseCreated {
+        version: u8,
+        #[ink(topic)]
+        group_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        target: Compact<Balance>,
+    }
+
+    /// Event emitted when an account contributes to a group purchase.
+    #[ink(event)]
+    pub struct Contributed {
+        version: u8,
+        #[ink(topic)]
+        group_id: u128,
+        #[ink(topic)]
+        contributor: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a group purchase buys its NFT.
+    #[ink(event)]
+    pub struct GroupPurchaseExecuted {
+        version: u8,
+        #[ink(topic)]
+        group_id: u128,
+    }
+
+    /// Event emitted when a contribution to a group purchase is refunded.
+    #[ink(event)]
+    pub struct ContributionRefunded {
+        version: u8,
+        #[ink(topic)]
+        group_id: u128,
+        #[ink(topic)]
+        contributor: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a trade is proposed.
+    #[ink(event)]
+    pub struct TradeProposed {
+        version: u8,
+        #[ink(topic)]
+        trade_id: u128,
+        #[ink(topic)]
+        maker: AccountId,
+        #[ink(topic)]
+        taker: Option<AccountId>,
+    }
+
+    /// Event emitted when a trade is accepted.
+    #[ink(event)]
+    pub struct TradeAccepted {
+        version: u8,
+        #[ink(topic)]
+        trade_id: u128,
+        #[ink(topic)]
+        taker: AccountId,
+    }
+
+    /// Event emitted when a trade is cancelled.
+    #[ink(event)]
+    pub struct TradeCancelled {
+        version: u8,
+        #[ink(topic)]
+        trade_id: u128,
+    }
+
+    /// Event emitted when credit is granted to an account.
+    #[ink(event)]
+    pub struct CreditGranted {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+        expires_at: u64,
+    }
+
+    /// Event emitted when credit is spent on a purchase.
+    #[ink(event)]
+    pub struct CreditSpent {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when expired credit is reclaimed.
+    #[ink(event)]
+    pub struct CreditExpired {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a parameter change is queued.
+    #[ink(event)]
+    pub struct ParameterChangeQueued {
+        version: u8,
+        #[ink(topic)]
+        change_id: u128,
+        change: ParameterChange,
+        eta: u64,
+    }
+
+    /// Event emitted when a queued parameter change is executed.
+    #[ink(event)]
+    pub struct ParameterChangeExecuted {
+        version: u8,
+        #[ink(topic)]
+        change_id: u128,
+    }
+
+    /// Event emitted when a queued parameter change is cancelled.
+    #[ink(event)]
+    pub struct ParameterChangeCancelled {
+        version: u8,
+        #[ink(topic)]
+        change_id: u128,
+    }
+
+    /// Event emitted when the marketplace code is upgraded.
+    #[ink(event)]
+    pub struct Upgraded {
+        version: u8,
+        #[ink(topic)]
+        code_hash: Hash,
+    }
+
+    /// Event emitted when storage is migrated to a new version.
+    #[ink(event)]
+    pub struct StorageMigrated {
+        version: u8,
+        #[ink(topic)]
+        version: u32,
+    }
+
+    /// Event emitted when an account is added to or removed from the blacklist.
+    #[ink(event)]
+    pub struct BlacklistUpdated {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        blacklisted: bool,
+    }
+
+    /// Event emitted when a moderator removes a listing.
+    #[ink(event)]
+    pub struct ListingRemovedByModerator {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        #[ink(topic)]
+        moderator: AccountId,
+        reason: String,
+    }
+
+    /// Event emitted when signers of admin operations are changed.
+    #[ink(event)]
+    pub struct SignersUpdated {
+        version: u8,
+        signers: Vec<AccountId>,
+        threshold: u8,
+    }
+
+    /// Event emitted when an admin operation is proposed.
+    #[ink(event)]
+    pub struct AdminOperationProposed {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+        #[ink(topic)]
+        proposer: AccountId,
+        operation: AdminOperation,
+    }
+
+    /// Event emitted when a signer confirms an admin operation.
+    #[ink(event)]
+    pub struct AdminOperationConfirmed {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+        #[ink(topic)]
+        signer: AccountId,
+    }
+
+    /// Event emitted when a confirmed admin operation is executed.
+    #[ink(event)]
+    pub struct AdminOperationExecuted {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+    }
+
+    /// Event emitted when return of a held asset to its owners is queued.
+    #[ink(event)]
+    pub struct RecoveryQueued {
+        version: u8,
+        #[ink(topic)]
+        recovery_id: u128,
+        asset: HeldAsset,
+        eta: u64,
+    }
+
+    /// Event emitted when a held asset is returned to its owners.
+    #[ink(event)]
+    pub struct RecoveryExecuted {
+        version: u8,
+        #[ink(topic)]
+        recovery_id: u128,
+        asset: HeldAsset,
+    }
+
+    /// Event emitted when a queued recovery is cancelled.
+    #[ink(event)]
+    pub struct RecoveryCancelled {
+        version: u8,
+        #[ink(topic)]
+        recovery_id: u128,
+    }
+
+    /// Event emitted when a repeated sale between the same accounts is flagged.
+    #[ink(event)]
+    pub struct WashTradeFlagged {
+        version: u8,
+        #[ink(topic)]
+        wash_trade_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        seller: AccountId,
+        buyer: AccountId,
+        excluded: bool,
+    }
+
+    /// Event emitted when a moderator clears a wash trade flag.
+    #[ink(event)]
+    pub struct WashTradeCleared {
+        version: u8,
+        #[ink(topic)]
+        wash_trade_id: u128,
+        moderator: AccountId,
+    }
+
+    /// Event emitted when a NFT is listed for rent.
+    #[ink(event)]
+    pub struct RentalListed {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        owner: AccountId,
+        daily_price: Compact<Balance>,
+        collateral: Compact<Balance>,
+        custodial: bool,
+        purchase_price: Option<Compact<Balance>>,
+    }
+
+    /// Event emitted when a NFT is removed from rental listings.
+    #[ink(event)]
+    pub struct RentalListingCancelled {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+    }
+
+    /// Event emitted when a NFT is rented.
+    #[ink(event)]
+    pub struct RentalStarted {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        renter: AccountId,
+        end_time: u64,
+    }
+
+    /// Event emitted when a rental term is extended.
+    #[ink(event)]
+    pub struct RentalExtended {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+        end_time: u64,
+    }
+
+    /// Event emitted when a rented NFT is returned to its owner and collateral refunded.
+    #[ink(event)]
+    pub struct RentalEnded {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+        refund: Compact<Balance>,
+    }
+
+    /// Event emitted when paid rent reaches the purchase price and the NFT is transferred to
+    /// the renter.
+    #[ink(event)]
+    pub struct RentToOwnCompleted {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+        #[ink(topic)]
+        renter: AccountId,
+    }
+
+    /// Event emitted when the owner claims collateral of a rental that was not returned or
+    /// terminates a streamed rental the renter no longer pays for.
+    #[ink(event)]
+    pub struct RentalDefaulted {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+    }
+
+    /// Event emitted when streamed rent is paid from the renter's deposit.
+    #[ink(event)]
+    pub struct RentStreamSettled {
+        version: u8,
+        #[ink(topic)]
+        rental_id: u128,
+        amount: Compact<Balance>,
+        settled_until: u64,
+    }
+
+    /// Event emitted when a loan against NFT is offered.
+    #[ink(event)]
+    pub struct LoanOfferMade {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        lender: AccountId,
+        principal: Compact<Balance>,
+        interest: Compact<Balance>,
+        duration: u64,
+    }
+
+    /// Event emitted when a loan offer is cancelled.
+    #[ink(event)]
+    pub struct LoanOfferCancelled {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+    }
+
+    /// Event emitted when a standing loan offer is posted for a collection.
+    #[ink(event)]
+    pub struct StandingLoanOfferMade {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        lender: AccountId,
+        max_principal: Compact<Balance>,
+        apr: u16,
+        duration: u64,
+    }
+
+    /// Event emitted when a standing loan offer is cancelled.
+    #[ink(event)]
+    pub struct StandingLoanOfferCancelled {
+        version: u8,
+        #[ink(topic)]
+        offer_id: u128,
+    }
+
+    /// Event emitted when a loan offer is accepted and the NFT taken as collateral.
+    #[ink(event)]
+    pub struct LoanStarted {
+        version: u8,
+        #[ink(topic)]
+        loan_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        borrower: AccountId,
+        lender: AccountId,
+        due: u64,
+    }
+
+    /// Event emitted when a loan is repaid and the NFT returned to the borrower.
+    #[ink(event)]
+    pub struct LoanRepaid {
+        version: u8,
+        #[ink(topic)]
+        loan_id: u128,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when the lender claims NFT of a loan that was not repaid.
+    #[ink(event)]
+    pub struct LoanDefaulted {
+        version: u8,
+        #[ink(topic)]
+        loan_id: u128,
+    }
+
+    /// Event emitted when NFT is bought with a signed order.
+    #[ink(event)]
+    pub struct SignedOrderFulfilled {
+        version: u8,
+        #[ink(topic)]
+        seller: AccountId,
+        nonce: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+    }
+
+    /// Event emitted when the seller cancels a signed order.
+    #[ink(event)]
+    pub struct SignedOrderCancelled {
+        version: u8,
+        #[ink(topic)]
+        seller: AccountId,
+        nonce: u64,
+    }
+
+    /// Event emitted when the seller cancels all signed orders with a lower nonce.
+    #[ink(event)]
+    pub struct SignedOrdersCancelled {
+        version: u8,
+        #[ink(topic)]
+        seller: AccountId,
+        min_nonce: u64,
+    }
+
+    /// Event emitted when a maker order for units of an edition is posted.
+    #[ink(event)]
+    pub struct EditionOrderMade {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        token_id: Id,
+        #[ink(topic)]
+        maker: AccountId,
+        side: EditionSide,
+        price: Compact<Balance>,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when an edition order is cancelled.
+    #[ink(event)]
+    pub struct EditionOrderCancelled {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+    }
+
+    /// Event emitted when units of an edition order are bought or sold by a taker.
+    #[ink(event)]
+    pub struct EditionOrderFilled {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+        #[ink(topic)]
+        taker: AccountId,
+        price: Compact<Balance>,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a standing order to buy tokens of a collection is placed.
+    #[ink(event)]
+    pub struct LimitOrderPlaced {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        buyer: AccountId,
+        quantity: u64,
+        price: Compact<Balance>,
+    }
+
+    /// Event emitted when a limit order is cancelled.
+    #[ink(event)]
+    pub struct LimitOrderCancelled {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+    }
+
+    /// Event emitted when a token is sold into a limit order.
+    #[ink(event)]
+    pub struct LimitOrderFilled {
+        version: u8,
+        #[ink(topic)]
+        order_id: u128,
+        #[ink(topic)]
+        seller: AccountId,
+        token_id: Id,
+    }
+
+    /// Event emitted when NFT is raffled.
+    #[ink(event)]
+    pub struct RaffleCreated {
+        version: u8,
+        #[ink(topic)]
+        raffle_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        token_id: Id,
+        #[ink(topic)]
+        owner: AccountId,
+        ticket_price: Compact<Balance>,
+        max_tickets: u32,
+        deadline: u64,
+    }
+
+    /// Event emitted when raffle tickets are bought.
+    #[ink(event)]
+    pub struct RaffleTicketsBought {
+        version: u8,
+        #[ink(topic)]
+        raffle_id: u128,
+        #[ink(topic)]
+        buyer: AccountId,
+        tickets: u32,
+    }
+
+    /// Event emitted when the winner of a raffle is drawn.
+    #[ink(event)]
+    pub struct RaffleDrawn {
+        version: u8,
+        #[ink(topic)]
+        raffle_id: u128,
+        #[ink(topic)]
+        winner: AccountId,
+    }
+
+    /// Event emitted when a raffle ends without selling the minimum number of tickets.
+    #[ink(event)]
+    pub struct RaffleFailed {
+        version: u8,
+        #[ink(topic)]
+        raffle_id: u128,
+    }
+
+    /// Event emitted when tickets of a failed raffle are refunded.
+    #[ink(event)]
+    pub struct RaffleRefunded {
+        version: u8,
+        #[ink(topic)]
+        raffle_id: u128,
+        #[ink(topic)]
+        buyer: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when NFT is auctioned.
+    #[ink(event)]
+    pub struct AuctionCreated {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        token_id: Id,
+        #[ink(topic)]
+        seller: AccountId,
+        reserve_price: Compact<Balance>,
+        start_time: u64,
+        end_time: u64,
+    }
+
+    /// Event emitted when a bid is placed on an auction.
+    #[ink(event)]
+    pub struct AuctionBid {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when an ended auction is settled. There is no winner if there were no
+    /// bids.
+    #[ink(event)]
+    pub struct AuctionSettled {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+    }
+
+    /// Event emitted when an auction is cancelled by its seller.
+    #[ink(event)]
+    pub struct AuctionCancelled {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+    }
+
+    /// Event emitted when a mystery box of NFTs is created.
+    #[ink(event)]
+    pub struct MysteryBoxCreated {
+        version: u8,
+        #[ink(topic)]
+        box_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        creator: AccountId,
+        price: Compact<Balance>,
+        tokens: u32,
+    }
+
+    /// Event emitted when a draw from a mystery box is bought.
+    #[ink(event)]
+    pub struct MysteryBoxBought {
+        version: u8,
+        #[ink(topic)]
+        purchase_id: u128,
+        #[ink(topic)]
+        box_id: u128,
+        #[ink(topic)]
+        buyer: AccountId,
+    }
+
+    /// Event emitted when the NFT of a mystery box purchase is drawn.
+    #[ink(event)]
+    pub struct MysteryBoxRevealed {
+        version: u8,
+        #[ink(topic)]
+        purchase_id: u128,
+        #[ink(topic)]
+        buyer: AccountId,
+        token_id: Id,
+    }
+
+    /// Event emitted when a mystery box is closed.
+    #[ink(event)]
+    pub struct MysteryBoxClosed {
+        version: u8,
+        #[ink(topic)]
+        box_id: u128,
+    }
+
+    /// Event emitted when NFT is fractionalized into shares.
+    #[ink(event)]
+    pub struct Fractionalized {
+        version: u8,
+        #[ink(topic)]
+        vault_id: u128,
+        #[ink(topic)]
+        contract: AccountId,
+        token_id: Id,
+        #[ink(topic)]
+        share_token: AccountId,
+        total_shares: Compact<Balance>,
+    }
+
+    /// Event emitted when shares of a vault are bought.
+    #[ink(event)]
+    pub struct FractionSharesBought {
+        version: u8,
+        #[ink(topic)]
+        vault_id: u128,
+        #[ink(topic)]
+        buyer: AccountId,
+        shares: Compact<Balance>,
+    }
+
+    /// Event emitted when a bid to buy the NFT of a vault out is placed.
+    #[ink(event)]
+    pub struct BuyoutBid {
+        version: u8,
+        #[ink(topic)]
+        vault_id: u128,
+        #[ink(topic)]
+        bidder: AccountId,
+        bid: Compact<Balance>,
+        end_time: u64,
+    }
+
+    /// Event emitted when the NFT of a vault is transferred to the buyout winner.
+    #[ink(event)]
+    pub struct BuyoutEnded {
+        version: u8,
+        #[ink(topic)]
+        vault_id: u128,
+        #[ink(topic)]
+        winner: AccountId,
+        bid: Compact<Balance>,
+    }
+
+    /// Event emitted when shares of a bought out vault are redeemed.
+    #[ink(event)]
+    pub struct FractionSharesRedeemed {
+        version: u8,
+        #[ink(topic)]
+        vault_id: u128,
+        #[ink(topic)]
+        holder: AccountId,
+        shares: Compact<Balance>,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when NFT is bought through XCM and held for the beneficiary.
+    #[ink(event)]
+    pub struct XcmPurchaseReserved {
+        version: u8,
+        #[ink(topic)]
+        reservation_id: u128,
+        #[ink(topic)]
+        origin: AccountId,
+        #[ink(topic)]
+        beneficiary: AccountId,
+    }
+
+    /// Event emitted when NFT reserved through XCM is claimed.
+    #[ink(event)]
+    pub struct XcmReservationClaimed {
+        version: u8,
+        #[ink(topic)]
+        reservation_id: u128,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// Event emitted when children nested in a listed RMRK token are recorded.
+    #[ink(event)]
+    pub struct ListingChildrenRecorded {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        token_id: Id,
+        children: Vec<(AccountId, Id)>,
+    }
+
+    /// Event emitted when a NFT is received into custody.
+    #[ink(event)]
+    pub struct TokenReceived {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        token_id: Id,
+        #[ink(topic)]
+        from: AccountId,
+    }
+
+    /// Event emitted when a dispute is opened.
+    #[ink(event)]
+    pub struct DisputeOpened {
+        version: u8,
+        #[ink(topic)]
+        dispute_id: u128,
+        subject: DisputeSubject,
+        #[ink(topic)]
+        claimant: AccountId,
+    }
+
+    /// Event emitted when an arbiter rules on a dispute.
+    #[ink(event)]
+    pub struct DisputeRuled {
+        version: u8,
+        #[ink(topic)]
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    }
+
+    /// Event emitted when a NFT backed by a physical item is listed.
+    #[ink(event)]
+    pub struct PhysicalItemListed {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        token_id: Id,
+        delivery_period: u64,
+        partial_refund: u16,
+    }
+
+    /// Event emitted when the buyer confirms delivery of a physical-backed purchase.
+    #[ink(event)]
+    pub struct DeliveryConfirmed {
+        version: u8,
+        #[ink(topic)]
+        escrow_id: u128,
+    }
+
+    /// Event emitted when a partner frontend is registered.
+    #[ink(event)]
+    pub struct PartnerRegistered {
+        version: u8,
+        #[ink(topic)]
+        partner_id: u32,
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when a partner earns a share of the marketplace fee.
+    #[ink(event)]
+    pub struct PartnerFeeAccrued {
+        version: u8,
+        #[ink(topic)]
+        partner_id: u32,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a partner claims its earnings.
+    #[ink(event)]
+    pub struct PartnerEarningsClaimed {
+        version: u8,
+        #[ink(topic)]
+        partner_id: u32,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when an account registers a referral code.
+    #[ink(event)]
+    pub struct ReferralCodeRegistered {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        code: String,
+    }
+
+    /// Event emitted when a referrer earns a share of the marketplace fee.
+    #[ink(event)]
+    pub struct ReferralFeeAccrued {
+        version: u8,
+        #[ink(topic)]
+        referrer: AccountId,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a referrer claims its earnings.
+    #[ink(event)]
+    pub struct ReferralEarningsClaimed {
+        version: u8,
+        #[ink(topic)]
+        referrer: AccountId,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a curator features a listing.
+    #[ink(event)]
+    pub struct ListingFeatured {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+        #[ink(topic)]
+        curator: AccountId,
+    }
+
+    /// Event emitted when a listing is no longer featured.
+    #[ink(event)]
+    pub struct ListingUnfeatured {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        id: Id,
+    }
+
+    /// Event emitted when a curator features an auction.
+    #[ink(event)]
+    pub struct AuctionFeatured {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+        #[ink(topic)]
+        curator: AccountId,
+    }
+
+    /// Event emitted when an auction is no longer featured.
+    #[ink(event)]
+    pub struct AuctionUnfeatured {
+        version: u8,
+        #[ink(topic)]
+        auction_id: u128,
+    }
+
+    /// Event emitted when a curator sets its auction calendar.
+    #[ink(event)]
+    pub struct AuctionCalendarSet {
+        version: u8,
+        #[ink(topic)]
+        curator: AccountId,
+        auction_ids: Vec<u128>,
+    }
+
+    /// Event emitted when a curator earns a share of the marketplace fee.
+    #[ink(event)]
+    pub struct CuratorFeeAccrued {
+        version: u8,
+        #[ink(topic)]
+        curator: AccountId,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a curator claims its earnings.
+    #[ink(event)]
+    pub struct CuratorEarningsClaimed {
+        version: u8,
+        #[ink(topic)]
+        curator: AccountId,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a discount coupon is issued.
+    #[ink(event)]
+    pub struct CouponCreated {
+        version: u8,
+        #[ink(topic)]
+        code_hash: [u8; 32],
+        #[ink(topic)]
+        issuer: AccountId,
+        contract: Option<AccountId>,
+        discount: CouponDiscount,
+        funding: CouponFunding,
+        pool: Compact<Balance>,
+        max_uses: u32,
+        expires_at: u64,
+    }
+
+    /// Event emitted when a discount coupon is cancelled.
+    #[ink(event)]
+    pub struct CouponCancelled {
+        version: u8,
+        #[ink(topic)]
+        code_hash: [u8; 32],
+        refund: Compact<Balance>,
+    }
+
+    /// Event emitted when a buyer redeems a discount coupon.
+    #[ink(event)]
+    pub struct CouponRedeemed {
+        version: u8,
+        #[ink(topic)]
+        code_hash: [u8; 32],
+        #[ink(topic)]
+        buyer: AccountId,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        discount: Compact<Balance>,
+    }
+
+    /// Event emitted when a seller subscription tier is set or removed.
+    #[ink(event)]
+    pub struct SubscriptionPlanUpdated {
+        version: u8,
+        #[ink(topic)]
+        tier: u32,
+        plan: Option<SubscriptionPlan>,
+    }
+
+    /// Event emitted when a seller subscribes to a tier or extends the subscription.
+    #[ink(event)]
+    pub struct Subscribed {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        tier: u32,
+        expires_at: u64,
+        currency: Currency,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a rate limit of the circuit breaker is updated.
+    #[ink(event)]
+    pub struct RateLimitUpdated {
+        version: u8,
+        #[ink(topic)]
+        contract: Option<AccountId>,
+        rate_limit: Option<RateLimit>,
+    }
+
+    /// Event emitted when a sale exceeds a rate limit and trading of its collection is paused.
+    #[ink(event)]
+    pub struct CircuitBreakerTripped {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        /// Collection of the exceeded rate limit, `None` for the global rate limit.
+        limited: Option<AccountId>,
+        sales: u32,
+        volume: Compact<Balance>,
+    }
+
+    /// Event emitted when trading of a collection paused by the circuit breaker is resumed.
+    #[ink(event)]
+    pub struct CollectionResumed {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+    }
+
+    /// Event emitted when a flash sale is scheduled.
+    #[ink(event)]
+    pub struct FlashSaleScheduled {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        seller: Option<AccountId>,
+        start: u64,
+        end: u64,
+        discount: u16,
+    }
+
+    /// Event emitted when a flash sale is cancelled.
+    #[ink(event)]
+    pub struct FlashSaleCancelled {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        seller: Option<AccountId>,
+    }
+
+    /// Event emitted when governance tokens are staked.
+    #[ink(event)]
+    pub struct Staked {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when governance tokens are unstaked.
+    #[ink(event)]
+    pub struct Unstaked {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a staker claims its share of marketplace fees.
+    #[ink(event)]
+    pub struct StakingRewardsClaimed {
+        version: u8,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Compact<Balance>,
+    }
+
+    /// Event emitted when a staker proposes a parameter change.
+    #[ink(event)]
+    pub struct ProposalCreated {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+        #[ink(topic)]
+        proposer: AccountId,
+        change: ParameterChange,
+    }
+
+    /// Event emitted when a staker votes on a proposal.
+    #[ink(event)]
+    pub struct VoteCast {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+        #[ink(topic)]
+        voter: AccountId,
+        support: bool,
+        weight: Compact<Balance>,
+    }
+
+    /// Event emitted when votes of a proposal are counted.
+    #[ink(event)]
+    pub struct ProposalFinalized {
+        version: u8,
+        #[ink(topic)]
+        proposal_id: u128,
+        status: ProposalStatus,
+    }
+
+    impl MarketplaceContract {
+        #[ink(constructor)]
+        pub fn new(market_fee_recipient: AccountId) -> Self {
+            let mut instance = Self::default();
+            instance.marketplace.fee = 100; // 1%
+            instance.marketplace.max_fee = DEFAULT_MAX_FEE; // 10%
+            instance.marketplace.v1.max_batch_size = DEFAULT_MAX_BATCH_SIZE;
+            instance.marketplace.market_fee_recipient = Option::Some(market_fee_recipient);
+            instance.marketplace.v1.storage_version = STORAGE_VERSION;
+
+            let caller = instance.env().caller();
+            instance._init_with_owner(caller);
+            access_control::Internal::_init_with_admin(&mut instance, caller);
+            instance
+        }
+
+        /// Kept for existing tooling, see `upgrade`.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), MarketplaceError> {
+            self.upgrade(Hash::from(code_hash))
+        }
+
+        /// Switches the marketplace code keeping its storage, listings and deposits.
+        /// Only the owner and accounts with the `UPGRADER` role can upgrade.
+        #[ink(message)]
+        #[modifiers(when_not_taking_custody)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), MarketplaceError> {
+            let caller = self.env().caller();
+            if caller != self.owner() && !self.has_role(UPGRADER, caller) {
+                return Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole,
+                ))
+            }
+            marketplace_multisig::Internal::ensure_no_multisig(self)?;
+
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| MarketplaceError::UpgradeFailed)?;
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Upgraded,
+            >(
+                self.env(),
+                Upgraded {
+                    version: EVENT_SCHEMA_VERSION,
+                    code_hash,
+                },
+            );
+            Ok(())
+        }
+
+        /// Gets version of the marketplace code.
+        #[ink(message)]
+        pub fn get_code_version(&self) -> String {
+            String::from(env!("CARGO_PKG_VERSION"))
+        }
+
+        /// Gets major, minor and patch version of the marketplace code.
+        #[ink(message)]
+        pub fn marketplace_version(&self) -> (u16, u16, u16) {
+            (
+                env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap_or_default(),
+                env!("CARGO_PKG_VERSION_MINOR").parse().unwrap_or_default(),
+                env!("CARGO_PKG_VERSION_PATCH").parse().unwrap_or_default(),
+            )
+        }
+
+        /// Checks if the marketplace code supports a feature.
+        #[ink(message)]
+        pub fn supports_feature(&self, feature: FeatureId) -> bool {
+            match feature {
+                FeatureId::XcmPurchases => cfg!(feature = "xcm"),
+                FeatureId::Erc721Xvm => cfg!(feature = "xvm"),
+                FeatureId::AssetPayments => cfg!(feature = "pallet-assets"),
+                _ => true,
+            }
+        }
+    }
+
+    impl MarketplaceSaleEvents for MarketplaceContract {
+        fn emit_token_listed_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            price: Option<Balance>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TokenListed,
+            >(
+                self.env(),
+                TokenListed {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    price: price.map(Compact),
+                },
+            );
+        }
+
+        fn emit_token_unlisted_event(&self, contract: AccountId, token_id: Id) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TokenUnlisted,
+            >(
+                self.env(),
+                TokenUnlisted {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                },
+            );
+        }
+
+        fn emit_listing_updated_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            old_price: Balance,
+            new_price: Balance,
+            currency: Currency,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingUpdated,
+            >(
+                self.env(),
+                ListingUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    old_price: old_price.into(),
+                    new_price: new_price.into(),
+                    currency,
+                },
+            );
+        }
+
+        fn emit_listing_gate_updated_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            gate_collection: Option<AccountId>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingGateUpdated,
+            >(
+                self.env(),
+                ListingGateUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    gate_collection,
+                },
+            );
+        }
+
+        fn emit_token_bought_event(
+            &self,
+            sale_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            price: Balance,
+            seller: AccountId,
+            buyer: AccountId,
+            marketplace_fee: Balance,
+            royalty_receiver: AccountId,
+            royalty: Balance,
+            currency: Currency,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TokenBought,
+            >(
+                self.env(),
+                TokenBought {
+                    version: EVENT_SCHEMA_VERSION,
+                    sale_id: sale_id.into(),
+                    contract,
+                    id: token_id,
+                    price: price.into(),
+                    seller,
+                    buyer,
+                    marketplace_fee: marketplace_fee.into(),
+                    royalty_receiver,
+                    royalty: royalty.into(),
+                    currency,
+                },
+            );
+        }
+
+        fn emit_collection_registered_event(&self, contract: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionRegistered,
+            >(
+                self.env(),
+                CollectionRegistered {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                },
+            )
+        }
+
+        fn emit_collection_royalty_updated_event(
+            &self,
+            contract: AccountId,
+            old_royalty: Option<(AccountId, u16)>,
+            new_royalty: Option<(AccountId, u16)>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionRoyaltyUpdated,
+            >(
+                self.env(),
+                CollectionRoyaltyUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    old_royalty,
+                    new_royalty,
+                },
+            )
+        }
+
+        fn emit_collection_metadata_updated_event(
+            &self,
+            contract: AccountId,
+            old_metadata: Option<String>,
+            new_metadata: String,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionMetadataUpdated,
+            >(
+                self.env(),
+                CollectionMetadataUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    old_metadata,
+                    new_metadata,
+                },
+            )
+        }
+
+        fn emit_collection_verification_updated_event(
+            &self,
+            contract: AccountId,
+            old_verified: bool,
+            new_verified: bool,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionVerificationUpdated,
+            >(
+                self.env(),
+                CollectionVerificationUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    old_verified,
+                    new_verified,
+                },
+            )
+        }
+
+        fn emit_collection_unregistered_event(
+            &self,
+            contract: AccountId,
+            royalty: Option<(AccountId, u16)>,
+            verified: bool,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionUnregistered,
+            >(
+                self.env(),
+                CollectionUnregistered {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    royalty,
+                    verified,
+                },
+            )
+        }
+
+        fn emit_factory_instantiated_event(
+            &self,
+            creator: AccountId,
+            contract: AccountId,
+            contract_type: NftContractType,
+            code_hash: Hash,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FactoryInstantiated,
+            >(
+                self.env(),
+                FactoryInstantiated {
+                    version: EVENT_SCHEMA_VERSION,
+                    creator,
+                    contract,
+                    contract_type,
+                    code_hash,
+                },
+            )
+        }
+
+        fn emit_deposit_event(
+            &self,
+            account_id: AccountId,
+            currency: Currency,
+            amount: Balance,
+            new_balance: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Deposit,
+            >(
+                self.env(),
+                Deposit {
+                    version: EVENT_SCHEMA_VERSION,
+                    account_id,
+                    currency,
+                    amount,
+                    new_balance: new_balance.into(),
+                },
+            )
+        }
+
+        fn emit_withdraw_event(
+            &self,
+            account_id: AccountId,
+            currency: Currency,
+            amount: Balance,
+            new_balance: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Withdraw,
+            >(
+                self.env(),
+                Withdraw {
+                    version: EVENT_SCHEMA_VERSION,
+                    account_id,
+                    currency,
+                    amount,
+                    new_balance: new_balance.into(),
+                },
+            )
+        }
+
+        fn emit_deposit_transferred_event(&self, from: AccountId, to: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DepositTransferred,
+            >(
+                self.env(),
+                DepositTransferred {
+                    version: EVENT_SCHEMA_VERSION,
+                    from,
+                    to,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_spender_approved_event(
+            &self,
+            owner: AccountId,
+            operator: AccountId,
+            allowance: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SpenderApproved,
+            >(
+                self.env(),
+                SpenderApproved {
+                    version: EVENT_SCHEMA_VERSION,
+                    owner,
+                    operator,
+                    allowance: allowance.into(),
+                },
+            )
+        }
+
+        fn emit_make_offer_event(
+            &self,
+            bidder_id: AccountId,
+            contract: AccountId,
+            token_id: Option<Id>,
+            quantity: u64,
+            price_per_item: u128,
+            extra: String,
+            offer_id: u128,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                MakeOffer,
+            >(
+                self.env(),
+                MakeOffer {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                    contract,
+                    id: token_id,
+                    bidder_id,
+                    price_per_item: price_per_item.into(),
+                    quantity,
+                    extra,
+                },
+            )
+        }
+
+        fn emit_cancel_offer_event(&self, offer_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CancelOffer,
+            >(
+                self.env(),
+                CancelOffer {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                },
+            )
+        }
+
+        fn emit_offer_auto_cancelled_event(&self, offer_id: u128, bidder: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                OfferAutoCancelled,
+            >(
+                self.env(),
+                OfferAutoCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                    bidder,
+                },
+            )
+        }
+
+        fn emit_accept_offer_event(&self, offer_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AcceptOffer,
+            >(
+                self.env(),
+                AcceptOffer {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                },
+            )
+        }
+
+        fn emit_offer_bond_forfeited_event(&self, offer_id: u128, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                OfferBondForfeited,
+            >(
+                self.env(),
+                OfferBondForfeited {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceEscrowEvents for MarketplaceContract {
+        fn emit_escrow_opened_event(
+            &self,
+            escrow_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            buyer: AccountId,
+            release_time: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EscrowOpened,
+            >(
+                self.env(),
+                EscrowOpened {
+                    version: EVENT_SCHEMA_VERSION,
+                    escrow_id,
+                    contract,
+                    id: token_id,
+                    buyer,
+                    release_time,
+                },
+            )
+        }
+
+        fn emit_escrow_released_event(&self, escrow_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EscrowReleased,
+            >(
+                self.env(),
+                EscrowReleased {
+                    version: EVENT_SCHEMA_VERSION,
+                    escrow_id,
+                },
+            )
+        }
+
+        fn emit_escrow_disputed_event(&self, escrow_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EscrowDisputed,
+            >(
+                self.env(),
+                EscrowDisputed {
+                    version: EVENT_SCHEMA_VERSION,
+                    escrow_id,
+                },
+            )
+        }
+
+        fn emit_dispute_resolved_event(&self, escrow_id: u128, refund_buyer: bool) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DisputeResolved,
+            >(
+                self.env(),
+                DisputeResolved {
+                    version: EVENT_SCHEMA_VERSION,
+                    escrow_id,
+                    refund_buyer,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceInstallmentsEvents for MarketplaceContract {
+        fn emit_installment_plan_created_event(
+            &self,
+            plan_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            buyer: AccountId,
+            installments: u8,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                InstallmentPlanCreated,
+            >(
+                self.env(),
+                InstallmentPlanCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    plan_id,
+                    contract,
+                    id: token_id,
+                    buyer,
+                    installments,
+                },
+            )
+        }
+
+        fn emit_installment_paid_event(&self, plan_id: u128, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                InstallmentPaid,
+            >(
+                self.env(),
+                InstallmentPaid {
+                    version: EVENT_SCHEMA_VERSION,
+                    plan_id,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_installment_plan_defaulted_event(&self, plan_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                InstallmentPlanDefaulted,
+            >(
+                self.env(),
+                InstallmentPlanDefaulted {
+                    version: EVENT_SCHEMA_VERSION,
+                    plan_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceGroupPurchaseEvents for MarketplaceContract {
+        fn emit_group_purchase_created_event(
+            &self,
+            group_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            target: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                GroupPurchaseCreated,
+            >(
+                self.env(),
+                GroupPurchaseCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    group_id,
+                    contract,
+                    id: token_id,
+                    target: target.into(),
+                },
+            )
+        }
+
+        fn emit_contributed_event(&self, group_id: u128, contributor: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Contributed,
+            >(
+                self.env(),
+                Contributed {
+                    version: EVENT_SCHEMA_VERSION,
+                    group_id,
+                    contributor,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_group_purchase_executed_event(&self, group_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                GroupPurchaseExecuted,
+            >(
+                self.env(),
+                GroupPurchaseExecuted {
+                    version: EVENT_SCHEMA_VERSION,
+                    group_id,
+                },
+            )
+        }
+
+        fn emit_contribution_refunded_event(
+            &self,
+            group_id: u128,
+            contributor: AccountId,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ContributionRefunded,
+            >(
+                self.env(),
+                ContributionRefunded {
+                    version: EVENT_SCHEMA_VERSION,
+                    group_id,
+                    contributor,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceTradeEvents for MarketplaceContract {
+        fn emit_trade_proposed_event(
+            &self,
+            trade_id: u128,
+            maker: AccountId,
+            taker: Option<AccountId>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TradeProposed,
+            >(
+                self.env(),
+                TradeProposed {
+                    version: EVENT_SCHEMA_VERSION,
+                    trade_id,
+                    maker,
+                    taker,
+                },
+            )
+        }
+
+        fn emit_trade_accepted_event(&self, trade_id: u128, taker: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TradeAccepted,
+            >(
+                self.env(),
+                TradeAccepted {
+                    version: EVENT_SCHEMA_VERSION,
+                    trade_id,
+                    taker,
+                },
+            )
+        }
+
+        fn emit_trade_cancelled_event(&self, trade_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TradeCancelled,
+            >(
+                self.env(),
+                TradeCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    trade_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceCreditsEvents for MarketplaceContract {
+        fn emit_credit_granted_event(&self, account: AccountId, amount: Balance, expires_at: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CreditGranted,
+            >(
+                self.env(),
+                CreditGranted {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                    expires_at,
+                },
+            )
+        }
+
+        fn emit_credit_spent_event(&self, account: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CreditSpent,
+            >(
+                self.env(),
+                CreditSpent {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_credit_expired_event(&self, account: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CreditExpired,
+            >(
+                self.env(),
+                CreditExpired {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceTimelockEvents for MarketplaceContract {
+        fn emit_parameter_change_queued_event(
+            &self,
+            change_id: u128,
+            change: ParameterChange,
+            eta: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ParameterChangeQueued,
+            >(
+                self.env(),
+                ParameterChangeQueued {
+                    version: EVENT_SCHEMA_VERSION,
+                    change_id,
+                    change,
+                    eta,
+                },
+            )
+        }
+
+        fn emit_parameter_change_executed_event(&self, change_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ParameterChangeExecuted,
+            >(
+                self.env(),
+                ParameterChangeExecuted {
+                    version: EVENT_SCHEMA_VERSION,
+                    change_id,
+                },
+            )
+        }
+
+        fn emit_parameter_change_cancelled_event(&self, change_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ParameterChangeCancelled,
+            >(
+                self.env(),
+                ParameterChangeCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    change_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceMigrationEvents for MarketplaceContract {
+        fn emit_storage_migrated_event(&self, version: u32) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                StorageMigrated,
+            >(
+                self.env(),
+                StorageMigrated {
+                    version: EVENT_SCHEMA_VERSION,
+                    version,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceModerationEvents for MarketplaceContract {
+        fn emit_blacklist_updated_event(&self, account: AccountId, blacklisted: bool) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BlacklistUpdated,
+            >(
+                self.env(),
+                BlacklistUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    blacklisted,
+                },
+            )
+        }
+
+        fn emit_listing_removed_by_moderator_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            moderator: AccountId,
+            reason: String,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingRemovedByModerator,
+            >(
+                self.env(),
+                ListingRemovedByModerator {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    moderator,
+                    reason,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceMultisigEvents for MarketplaceContract {
+        fn emit_signers_updated_event(&self, signers: Vec<AccountId>, threshold: u8) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SignersUpdated,
+            >(
+                self.env(),
+                SignersUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    signers,
+                    threshold,
+                },
+            )
+        }
+
+        fn emit_admin_operation_proposed_event(
+            &self,
+            proposal_id: u128,
+            proposer: AccountId,
+            operation: AdminOperation,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AdminOperationProposed,
+            >(
+                self.env(),
+                AdminOperationProposed {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                    proposer,
+                    operation,
+                },
+            )
+        }
+
+        fn emit_admin_operation_confirmed_event(&self, proposal_id: u128, signer: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AdminOperationConfirmed,
+            >(
+                self.env(),
+                AdminOperationConfirmed {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                    signer,
+                },
+            )
+        }
+
+        fn emit_admin_operation_executed_event(&self, proposal_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AdminOperationExecuted,
+            >(
+                self.env(),
+                AdminOperationExecuted {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceRecoveryEvents for MarketplaceContract {
+        fn emit_recovery_queued_event(&self, recovery_id: u128, asset: HeldAsset, eta: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RecoveryQueued,
+            >(
+                self.env(),
+                RecoveryQueued {
+                    version: EVENT_SCHEMA_VERSION,
+                    recovery_id,
+                    asset,
+                    eta,
+                },
+            )
+        }
+
+        fn emit_recovery_executed_event(&self, recovery_id: u128, asset: HeldAsset) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RecoveryExecuted,
+            >(
+                self.env(),
+                RecoveryExecuted {
+                    version: EVENT_SCHEMA_VERSION,
+                    recovery_id,
+                    asset,
+                },
+            )
+        }
+
+        fn emit_recovery_cancelled_event(&self, recovery_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RecoveryCancelled,
+            >(
+                self.env(),
+                RecoveryCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    recovery_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceWashTradingEvents for MarketplaceContract {
+        fn emit_wash_trade_flagged_event(&self, wash_trade_id: u128, wash_trade: &WashTrade) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                WashTradeFlagged,
+            >(
+                self.env(),
+                WashTradeFlagged {
+                    version: EVENT_SCHEMA_VERSION,
+                    wash_trade_id,
+                    contract: wash_trade.contract_address,
+                    id: wash_trade.token_id.clone(),
+                    seller: wash_trade.seller,
+                    buyer: wash_trade.buyer,
+                    excluded: wash_trade.excluded,
+                },
+            )
+        }
+
+        fn emit_wash_trade_cleared_event(&self, wash_trade_id: u128, moderator: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                WashTradeCleared,
+            >(
+                self.env(),
+                WashTradeCleared {
+                    version: EVENT_SCHEMA_VERSION,
+                    wash_trade_id,
+                    moderator,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceRentalsEvents for MarketplaceContract {
+        fn emit_rental_listed_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            owner: AccountId,
+            daily_price: Balance,
+            collateral: Balance,
+            custodial: bool,
+            purchase_price: Option<Balance>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalListed,
+            >(
+                self.env(),
+                RentalListed {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    owner,
+                    daily_price: daily_price.into(),
+                    collateral: collateral.into(),
+                    custodial,
+                    purchase_price: purchase_price.map(Compact),
+                },
+            )
+        }
+
+        fn emit_rental_listing_cancelled_event(&self, contract: AccountId, token_id: Id) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalListingCancelled,
+            >(
+                self.env(),
+                RentalListingCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                },
+            )
+        }
+
+        fn emit_rental_started_event(
+            &self,
+            rental_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            renter: AccountId,
+            end_time: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalStarted,
+            >(
+                self.env(),
+                RentalStarted {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                    contract,
+                    id: token_id,
+                    renter,
+                    end_time,
+                },
+            )
+        }
+
+        fn emit_rental_extended_event(&self, rental_id: u128, end_time: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalExtended,
+            >(
+                self.env(),
+                RentalExtended {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                    end_time,
+                },
+            )
+        }
+
+        fn emit_rental_ended_event(&self, rental_id: u128, refund: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalEnded,
+            >(
+                self.env(),
+                RentalEnded {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                    refund: refund.into(),
+                },
+            )
+        }
+
+        fn emit_rent_to_own_completed_event(&self, rental_id: u128, renter: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentToOwnCompleted,
+            >(
+                self.env(),
+                RentToOwnCompleted {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                    renter,
+                },
+            )
+        }
+
+        fn emit_rental_defaulted_event(&self, rental_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentalDefaulted,
+            >(
+                self.env(),
+                RentalDefaulted {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                },
+            )
+        }
+
+        fn emit_rent_stream_settled_event(
+            &self,
+            rental_id: u128,
+            amount: Balance,
+            settled_until: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RentStreamSettled,
+            >(
+                self.env(),
+                RentStreamSettled {
+                    version: EVENT_SCHEMA_VERSION,
+                    rental_id,
+                    amount: amount.into(),
+                    settled_until,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceLendingEvents for MarketplaceContract {
+        fn emit_loan_offer_made_event(
+            &self,
+            offer_id: u128,
+            contract: AccountId,
+            lender: AccountId,
+            principal: Balance,
+            interest: Balance,
+            duration: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LoanOfferMade,
+            >(
+                self.env(),
+                LoanOfferMade {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                    contract,
+                    lender,
+                    principal: principal.into(),
+                    interest: interest.into(),
+                    duration,
+                },
+            )
+        }
+
+        fn emit_loan_offer_cancelled_event(&self, offer_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LoanOfferCancelled,
+            >(
+                self.env(),
+                LoanOfferCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                },
+            )
+        }
+
+        fn emit_standing_loan_offer_made_event(
+            &self,
+            offer_id: u128,
+            contract: AccountId,
+            lender: AccountId,
+            max_principal: Balance,
+            apr: u16,
+            duration: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                StandingLoanOfferMade,
+            >(
+                self.env(),
+                StandingLoanOfferMade {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                    contract,
+                    lender,
+                    max_principal: max_principal.into(),
+                    apr,
+                    duration,
+                },
+            )
+        }
+
+        fn emit_standing_loan_offer_cancelled_event(&self, offer_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                StandingLoanOfferCancelled,
+            >(
+                self.env(),
+                StandingLoanOfferCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    offer_id,
+                },
+            )
+        }
+
+        fn emit_loan_started_event(
+            &self,
+            loan_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            borrower: AccountId,
+            lender: AccountId,
+            due: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LoanStarted,
+            >(
+                self.env(),
+                LoanStarted {
+                    version: EVENT_SCHEMA_VERSION,
+                    loan_id,
+                    contract,
+                    id: token_id,
+                    borrower,
+                    lender,
+                    due,
+                },
+            )
+        }
+
+        fn emit_loan_repaid_event(&self, loan_id: u128, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LoanRepaid,
+            >(
+                self.env(),
+                LoanRepaid {
+                    version: EVENT_SCHEMA_VERSION,
+                    loan_id,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_loan_defaulted_event(&self, loan_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LoanDefaulted,
+            >(
+                self.env(),
+                LoanDefaulted {
+                    version: EVENT_SCHEMA_VERSION,
+                    loan_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceOrdersEvents for MarketplaceContract {
+        fn emit_signed_order_fulfilled_event(
+            &self,
+            seller: AccountId,
+            nonce: u64,
+            buyer: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SignedOrderFulfilled,
+            >(
+                self.env(),
+                SignedOrderFulfilled {
+                    version: EVENT_SCHEMA_VERSION,
+                    seller,
+                    nonce,
+                    buyer,
+                },
+            )
+        }
+
+        fn emit_signed_order_cancelled_event(&self, seller: AccountId, nonce: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SignedOrderCancelled,
+            >(
+                self.env(),
+                SignedOrderCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    seller,
+                    nonce,
+                },
+            )
+        }
+
+        fn emit_signed_orders_cancelled_event(&self, seller: AccountId, min_nonce: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SignedOrdersCancelled,
+            >(
+                self.env(),
+                SignedOrdersCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    seller,
+                    min_nonce,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceEditionsEvents for MarketplaceContract {
+        fn emit_edition_order_made_event(
+            &self,
+            order_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            maker: AccountId,
+            side: EditionSide,
+            price: Balance,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EditionOrderMade,
+            >(
+                self.env(),
+                EditionOrderMade {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                    contract,
+                    token_id,
+                    maker,
+                    side,
+                    price: price.into(),
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_edition_order_cancelled_event(&self, order_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EditionOrderCancelled,
+            >(
+                self.env(),
+                EditionOrderCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                },
+            )
+        }
+
+        fn emit_edition_order_filled_event(
+            &self,
+            order_id: u128,
+            taker: AccountId,
+            price: Balance,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                EditionOrderFilled,
+            >(
+                self.env(),
+                EditionOrderFilled {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                    taker,
+                    price: price.into(),
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceLimitOrdersEvents for MarketplaceContract {
+        fn emit_limit_order_placed_event(
+            &self,
+            order_id: u128,
+            contract: AccountId,
+            buyer: AccountId,
+            quantity: u64,
+            price: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LimitOrderPlaced,
+            >(
+                self.env(),
+                LimitOrderPlaced {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                    contract,
+                    buyer,
+                    quantity,
+                    price: price.into(),
+                },
+            )
+        }
+
+        fn emit_limit_order_cancelled_event(&self, order_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LimitOrderCancelled,
+            >(
+                self.env(),
+                LimitOrderCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                },
+            )
+        }
+
+        fn emit_limit_order_filled_event(&self, order_id: u128, seller: AccountId, token_id: Id) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                LimitOrderFilled,
+            >(
+                self.env(),
+                LimitOrderFilled {
+                    version: EVENT_SCHEMA_VERSION,
+                    order_id,
+                    seller,
+                    token_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceAuctionsEvents for MarketplaceContract {
+        fn emit_auction_created_event(
+            &self,
+            auction_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            seller: AccountId,
+            reserve_price: Balance,
+            start_time: u64,
+            end_time: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionCreated,
+            >(
+                self.env(),
+                AuctionCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                    contract,
+                    token_id,
+                    seller,
+                    reserve_price: reserve_price.into(),
+                    start_time,
+                    end_time,
+                },
+            )
+        }
+
+        fn emit_auction_bid_event(&self, auction_id: u128, bidder: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionBid,
+            >(
+                self.env(),
+                AuctionBid {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                    bidder,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_auction_settled_event(&self, auction_id: u128, winner: Option<AccountId>) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionSettled,
+            >(
+                self.env(),
+                AuctionSettled {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                    winner,
+                },
+            )
+        }
+
+        fn emit_auction_cancelled_event(&self, auction_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionCancelled,
+            >(
+                self.env(),
+                AuctionCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceRafflesEvents for MarketplaceContract {
+        fn emit_raffle_created_event(
+            &self,
+            raffle_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            owner: AccountId,
+            ticket_price: Balance,
+            max_tickets: u32,
+            deadline: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RaffleCreated,
+            >(
+                self.env(),
+                RaffleCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    raffle_id,
+                    contract,
+                    token_id,
+                    owner,
+                    ticket_price: ticket_price.into(),
+                    max_tickets,
+                    deadline,
+                },
+            )
+        }
+
+        fn emit_raffle_tickets_bought_event(
+            &self,
+            raffle_id: u128,
+            buyer: AccountId,
+            tickets: u32,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RaffleTicketsBought,
+            >(
+                self.env(),
+                RaffleTicketsBought {
+                    version: EVENT_SCHEMA_VERSION,
+                    raffle_id,
+                    buyer,
+                    tickets,
+                },
+            )
+        }
+
+        fn emit_raffle_drawn_event(&self, raffle_id: u128, winner: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RaffleDrawn,
+            >(
+                self.env(),
+                RaffleDrawn {
+                    version: EVENT_SCHEMA_VERSION,
+                    raffle_id,
+                    winner,
+                },
+            )
+        }
+
+        fn emit_raffle_failed_event(&self, raffle_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RaffleFailed,
+            >(
+                self.env(),
+                RaffleFailed {
+                    version: EVENT_SCHEMA_VERSION,
+                    raffle_id,
+                },
+            )
+        }
+
+        fn emit_raffle_refunded_event(&self, raffle_id: u128, buyer: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RaffleRefunded,
+            >(
+                self.env(),
+                RaffleRefunded {
+                    version: EVENT_SCHEMA_VERSION,
+                    raffle_id,
+                    buyer,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceMysteryBoxesEvents for MarketplaceContract {
+        fn emit_mystery_box_created_event(
+            &self,
+            box_id: u128,
+            contract: AccountId,
+            creator: AccountId,
+            price: Balance,
+            tokens: u32,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                MysteryBoxCreated,
+            >(
+                self.env(),
+                MysteryBoxCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    box_id,
+                    contract,
+                    creator,
+                    price: price.into(),
+                    tokens,
+                },
+            )
+        }
+
+        fn emit_mystery_box_bought_event(&self, purchase_id: u128, box_id: u128, buyer: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                MysteryBoxBought,
+            >(
+                self.env(),
+                MysteryBoxBought {
+                    version: EVENT_SCHEMA_VERSION,
+                    purchase_id,
+                    box_id,
+                    buyer,
+                },
+            )
+        }
+
+        fn emit_mystery_box_revealed_event(
+            &self,
+            purchase_id: u128,
+            buyer: AccountId,
+            token_id: Id,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                MysteryBoxRevealed,
+            >(
+                self.env(),
+                MysteryBoxRevealed {
+                    version: EVENT_SCHEMA_VERSION,
+                    purchase_id,
+                    buyer,
+                    token_id,
+                },
+            )
+        }
+
+        fn emit_mystery_box_closed_event(&self, box_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                MysteryBoxClosed,
+            >(
+                self.env(),
+                MysteryBoxClosed {
+                    version: EVENT_SCHEMA_VERSION,
+                    box_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceFractionsEvents for MarketplaceContract {
+        fn emit_fractionalized_event(
+            &self,
+            vault_id: u128,
+            contract: AccountId,
+            token_id: Id,
+            share_token: AccountId,
+            total_shares: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Fractionalized,
+            >(
+                self.env(),
+                Fractionalized {
+                    version: EVENT_SCHEMA_VERSION,
+                    vault_id,
+                    contract,
+                    token_id,
+                    share_token,
+                    total_shares: total_shares.into(),
+                },
+            )
+        }
+
+        fn emit_fraction_shares_bought_event(
+            &self,
+            vault_id: u128,
+            buyer: AccountId,
+            shares: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FractionSharesBought,
+            >(
+                self.env(),
+                FractionSharesBought {
+                    version: EVENT_SCHEMA_VERSION,
+                    vault_id,
+                    buyer,
+                    shares: shares.into(),
+                },
+            )
+        }
+
+        fn emit_buyout_bid_event(
+            &self,
+            vault_id: u128,
+            bidder: AccountId,
+            bid: Balance,
+            end_time: u64,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BuyoutBid,
+            >(
+                self.env(),
+                BuyoutBid {
+                    version: EVENT_SCHEMA_VERSION,
+                    vault_id,
+                    bidder,
+                    bid: bid.into(),
+                    end_time,
+                },
+            )
+        }
+
+        fn emit_buyout_ended_event(&self, vault_id: u128, winner: AccountId, bid: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BuyoutEnded,
+            >(
+                self.env(),
+                BuyoutEnded {
+                    version: EVENT_SCHEMA_VERSION,
+                    vault_id,
+                    winner,
+                    bid: bid.into(),
+                },
+            )
+        }
+
+        fn emit_fraction_shares_redeemed_event(
+            &self,
+            vault_id: u128,
+            holder: AccountId,
+            shares: Balance,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FractionSharesRedeemed,
+            >(
+                self.env(),
+                FractionSharesRedeemed {
+                    version: EVENT_SCHEMA_VERSION,
+                    vault_id,
+                    holder,
+                    shares: shares.into(),
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceXcmEvents for MarketplaceContract {
+        fn emit_xcm_purchase_reserved_event(
+            &self,
+            reservation_id: u128,
+            origin: AccountId,
+            beneficiary: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                XcmPurchaseReserved,
+            >(
+                self.env(),
+                XcmPurchaseReserved {
+                    version: EVENT_SCHEMA_VERSION,
+                    reservation_id,
+                    origin,
+                    beneficiary,
+                },
+            )
+        }
+
+        fn emit_xcm_reservation_claimed_event(&self, reservation_id: u128, to: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                XcmReservationClaimed,
+            >(
+                self.env(),
+                XcmReservationClaimed {
+                    version: EVENT_SCHEMA_VERSION,
+                    reservation_id,
+                    to,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceNestedListingsEvents for MarketplaceContract {
+        fn emit_listing_children_recorded_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            children: Vec<(AccountId, Id)>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingChildrenRecorded,
+            >(
+                self.env(),
+                ListingChildrenRecorded {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    token_id,
+                    children,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceCustodyEvents for MarketplaceContract {
+        fn emit_token_received_event(&self, contract: AccountId, token_id: Id, from: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TokenReceived,
+            >(
+                self.env(),
+                TokenReceived {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    token_id,
+                    from,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceDisputesEvents for MarketplaceContract {
+        fn emit_dispute_opened_event(
+            &self,
+            dispute_id: u128,
+            subject: DisputeSubject,
+            claimant: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DisputeOpened,
+            >(
+                self.env(),
+                DisputeOpened {
+                    version: EVENT_SCHEMA_VERSION,
+                    dispute_id,
+                    subject,
+                    claimant,
+                },
+            )
+        }
+
+        fn emit_dispute_ruled_event(
+            &self,
+            dispute_id: u128,
+            claimant_wins: bool,
+            rationale: String,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DisputeRuled,
+            >(
+                self.env(),
+                DisputeRuled {
+                    version: EVENT_SCHEMA_VERSION,
+                    dispute_id,
+                    claimant_wins,
+                    rationale,
+                },
+            )
+        }
+    }
+
+    impl MarketplacePhysicalSalesEvents for MarketplaceContract {
+        fn emit_physical_item_listed_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            delivery_period: u64,
+            partial_refund: u16,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                PhysicalItemListed,
+            >(
+                self.env(),
+                PhysicalItemListed {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    token_id,
+                    delivery_period,
+                    partial_refund,
+                },
+            )
+        }
+
+        fn emit_delivery_confirmed_event(&self, escrow_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DeliveryConfirmed,
+            >(
+                self.env(),
+                DeliveryConfirmed {
+                    version: EVENT_SCHEMA_VERSION,
+                    escrow_id,
+                },
+            )
+        }
+    }
+
+    impl MarketplacePartnersEvents for MarketplaceContract {
+        fn emit_partner_registered_event(&self, partner_id: u32, account: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                PartnerRegistered,
+            >(
+                self.env(),
+                PartnerRegistered {
+                    version: EVENT_SCHEMA_VERSION,
+                    partner_id,
+                    account,
+                },
+            )
+        }
+
+        fn emit_partner_fee_accrued_event(
+            &self,
+            partner_id: u32,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                PartnerFeeAccrued,
+            >(
+                self.env(),
+                PartnerFeeAccrued {
+                    version: EVENT_SCHEMA_VERSION,
+                    partner_id,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_partner_earnings_claimed_event(
+            &self,
+            partner_id: u32,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                PartnerEarningsClaimed,
+            >(
+                self.env(),
+                PartnerEarningsClaimed {
+                    version: EVENT_SCHEMA_VERSION,
+                    partner_id,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceReferralsEvents for MarketplaceContract {
+        fn emit_referral_code_registered_event(&self, account: AccountId, code: String) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ReferralCodeRegistered,
+            >(
+                self.env(),
+                ReferralCodeRegistered {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    code,
+                },
+            )
+        }
+
+        fn emit_referral_fee_accrued_event(
+            &self,
+            referrer: AccountId,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ReferralFeeAccrued,
+            >(
+                self.env(),
+                ReferralFeeAccrued {
+                    version: EVENT_SCHEMA_VERSION,
+                    referrer,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_referral_earnings_claimed_event(
+            &self,
+            referrer: AccountId,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ReferralEarningsClaimed,
+            >(
+                self.env(),
+                ReferralEarningsClaimed {
+                    version: EVENT_SCHEMA_VERSION,
+                    referrer,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceCurationEvents for MarketplaceContract {
+        fn emit_listing_featured_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            curator: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingFeatured,
+            >(
+                self.env(),
+                ListingFeatured {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                    curator,
+                },
+            )
+        }
+
+        fn emit_listing_unfeatured_event(&self, contract: AccountId, token_id: Id) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingUnfeatured,
+            >(
+                self.env(),
+                ListingUnfeatured {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    id: token_id,
+                },
+            )
+        }
+
+        fn emit_auction_featured_event(&self, auction_id: u128, curator: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionFeatured,
+            >(
+                self.env(),
+                AuctionFeatured {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                    curator,
+                },
+            )
+        }
+
+        fn emit_auction_unfeatured_event(&self, auction_id: u128) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionUnfeatured,
+            >(
+                self.env(),
+                AuctionUnfeatured {
+                    version: EVENT_SCHEMA_VERSION,
+                    auction_id,
+                },
+            )
+        }
+
+        fn emit_auction_calendar_set_event(&self, curator: AccountId, auction_ids: Vec<u128>) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionCalendarSet,
+            >(
+                self.env(),
+                AuctionCalendarSet {
+                    version: EVENT_SCHEMA_VERSION,
+                    curator,
+                    auction_ids,
+                },
+            )
+        }
+
+        fn emit_curator_fee_accrued_event(
+            &self,
+            curator: AccountId,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CuratorFeeAccrued,
+            >(
+                self.env(),
+                CuratorFeeAccrued {
+                    version: EVENT_SCHEMA_VERSION,
+                    curator,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_curator_earnings_claimed_event(
+            &self,
+            curator: AccountId,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CuratorEarningsClaimed,
+            >(
+                self.env(),
+                CuratorEarningsClaimed {
+                    version: EVENT_SCHEMA_VERSION,
+                    curator,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceCouponsEvents for MarketplaceContract {
+        fn emit_coupon_created_event(
+            &self,
+            code_hash: [u8; 32],
+            issuer: AccountId,
+            coupon: Coupon,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CouponCreated,
+            >(
+                self.env(),
+                CouponCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    code_hash,
+                    issuer,
+                    contract: coupon.contract_address,
+                    discount: coupon.discount,
+                    funding: coupon.funding,
+                    pool: coupon.pool.into(),
+                    max_uses: coupon.max_uses,
+                    expires_at: coupon.expires_at,
+                },
+            )
+        }
+
+        fn emit_coupon_cancelled_event(&self, code_hash: [u8; 32], refund: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CouponCancelled,
+            >(
+                self.env(),
+                CouponCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    code_hash,
+                    refund: refund.into(),
+                },
+            )
+        }
+
+        fn emit_coupon_redeemed_event(
+            &self,
+            code_hash: [u8; 32],
+            buyer: AccountId,
+            contract: AccountId,
+            id: Id,
+            discount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CouponRedeemed,
+            >(
+                self.env(),
+                CouponRedeemed {
+                    version: EVENT_SCHEMA_VERSION,
+                    code_hash,
+                    buyer,
+                    contract,
+                    id,
+                    discount: discount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceFlashSalesEvents for MarketplaceContract {
+        fn emit_flash_sale_scheduled_event(
+            &self,
+            contract: AccountId,
+            seller: Option<AccountId>,
+            flash_sale: FlashSale,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FlashSaleScheduled,
+            >(
+                self.env(),
+                FlashSaleScheduled {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    seller,
+                    start: flash_sale.start,
+                    end: flash_sale.end,
+                    discount: flash_sale.discount,
+                },
+            )
+        }
+
+        fn emit_flash_sale_cancelled_event(&self, contract: AccountId, seller: Option<AccountId>) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FlashSaleCancelled,
+            >(
+                self.env(),
+                FlashSaleCancelled {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    seller,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceCircuitBreakerEvents for MarketplaceContract {
+        fn emit_rate_limit_updated_event(
+            &self,
+            contract: Option<AccountId>,
+            rate_limit: Option<RateLimit>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                RateLimitUpdated,
+            >(
+                self.env(),
+                RateLimitUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    rate_limit,
+                },
+            )
+        }
+
+        fn emit_circuit_breaker_tripped_event(
+            &self,
+            contract: AccountId,
+            limited: Option<AccountId>,
+            sales: u32,
+            volume: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CircuitBreakerTripped,
+            >(
+                self.env(),
+                CircuitBreakerTripped {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                    limited,
+                    sales,
+                    volume: volume.into(),
+                },
+            )
+        }
+
+        fn emit_collection_resumed_event(&self, contract: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionResumed,
+            >(
+                self.env(),
+                CollectionResumed {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceSubscriptionsEvents for MarketplaceContract {
+        fn emit_subscription_plan_updated_event(&self, tier: u32, plan: Option<SubscriptionPlan>) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SubscriptionPlanUpdated,
+            >(
+                self.env(),
+                SubscriptionPlanUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    tier,
+                    plan,
+                },
+            )
+        }
+
+        fn emit_subscribed_event(
+            &self,
+            account: AccountId,
+            tier: u32,
+            expires_at: u64,
+            currency: Currency,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Subscribed,
+            >(
+                self.env(),
+                Subscribed {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    tier,
+                    expires_at,
+                    currency,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceStakingEvents for MarketplaceContract {
+        fn emit_staked_event(&self, account: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Staked,
+            >(
+                self.env(),
+                Staked {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_unstaked_event(&self, account: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                Unstaked,
+            >(
+                self.env(),
+                Unstaked {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                },
+            )
+        }
+
+        fn emit_staking_rewards_claimed_event(&self, account: AccountId, amount: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                StakingRewardsClaimed,
+            >(
+                self.env(),
+                StakingRewardsClaimed {
+                    version: EVENT_SCHEMA_VERSION,
+                    account,
+                    amount: amount.into(),
+                },
+            )
+        }
+    }
+
+    impl MarketplaceGovernanceEvents for MarketplaceContract {
+        fn emit_proposal_created_event(
+            &self,
+            proposal_id: u128,
+            proposer: AccountId,
+            change: ParameterChange,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ProposalCreated,
+            >(
+                self.env(),
+                ProposalCreated {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                    proposer,
+                    change,
+                },
+            )
+        }
+
+        fn emit_vote_cast_event(
+            &self,
+            proposal_id: u128,
+            voter: AccountId,
+            support: bool,
+            weight: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                VoteCast,
+            >(
+                self.env(),
+                VoteCast {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                    voter,
+                    support,
+                    weight: weight.into(),
+                },
+            )
+        }
+
+        fn emit_proposal_finalized_event(&self, proposal_id: u128, status: ProposalStatus) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ProposalFinalized,
+            >(
+                self.env(),
+                ProposalFinalized {
+                    version: EVENT_SCHEMA_VERSION,
+                    proposal_id,
+                    status,
+                },
+            )
+        }
+    }
+
+    impl MarketplaceSale for MarketplaceContract {}
+
+    impl MarketplaceOffer for MarketplaceContract {}
+
+    impl MarketplaceDeposit for MarketplaceContract {}
+
+    impl MarketplaceAdmin for MarketplaceContract {}
+
+    impl MarketplaceFactory for MarketplaceContract {}
+
+    impl MarketplaceEscrow for MarketplaceContract {}
+
+    impl MarketplaceInstallments for MarketplaceContract {}
+
+    impl MarketplaceGroupPurchase for MarketplaceContract {}
+
+    impl MarketplaceTrade for MarketplaceContract {}
+
+    impl MarketplaceCredits for MarketplaceContract {}
+
+    impl MarketplaceTimelock for MarketplaceContract {}
+
+    impl MarketplaceMigration for MarketplaceContract {}
+
+    impl MarketplaceModeration for MarketplaceContract {}
+
+    impl MarketplaceRecovery for MarketplaceContract {}
+
+    impl MarketplaceAccounting for MarketplaceContract {}
+
+    impl MarketplaceMultisig for MarketplaceContract {}
+
+    impl MarketplaceWashTrading for MarketplaceContract {}
+
+    impl MarketplaceRentals for MarketplaceContract {}
+
+    impl MarketplaceLending for MarketplaceContract {}
+
+    impl MarketplaceOrders for MarketplaceContract {}
+
+    impl MarketplaceEditions for MarketplaceContract {}
+
+    impl MarketplaceLimitOrders for MarketplaceContract {}
+
+    impl MarketplaceAuctions for MarketplaceContract {}
+
+    impl MarketplaceRaffles for MarketplaceContract {}
+
+    impl MarketplaceMysteryBoxes for MarketplaceContract {}
+
+    impl MarketplaceFractions for MarketplaceContract {}
+
+    impl MarketplaceXcm for MarketplaceContract {}
+
+    impl MarketplaceNestedListings for MarketplaceContract {}
+
+    impl MarketplaceCustody for MarketplaceContract {}
+
+    impl MarketplaceDisputes for MarketplaceContract {}
+
+    impl MarketplacePhysicalSales for MarketplaceContract {}
+
+    impl MarketplacePartners for MarketplaceContract {}
+
+    impl MarketplaceReferrals for MarketplaceContract {}
+
+    impl MarketplaceCuration for MarketplaceContract {}
+
+    impl MarketplaceCoupons for MarketplaceContract {}
+
+    impl MarketplaceFlashSales for MarketplaceContract {}
+
+    impl MarketplaceSnapshots for MarketplaceContract {}
+
+    impl MarketplaceInventory for MarketplaceContract {}
+
+    impl MarketplaceCircuitBreaker for MarketplaceContract {}
+
+    impl MarketplaceSubscriptions for MarketplaceContract {}
+
+    impl MarketplaceStaking for MarketplaceContract {}
+
+    impl MarketplaceClaims for MarketplaceContract {}
+
+    impl MarketplaceGovernance for MarketplaceContract {}
+
+    impl AccessControl for MarketplaceContract {
+        /// Grants a role. Once signers are set roles are granted through them.
+        #[ink(message)]
+        fn grant_role(
+            &mut self,
+            role: access_control::RoleType,
+            account: AccountId,
+        ) -> Result<(), access_control::AccessControlError> {
+            if marketplace_multisig::Internal::ensure_no_multisig(self).is_err()
+                || self.marketplace.custody_transfer.get().unwrap_or_default()
+            {
+                return Err(access_control::AccessControlError::InvalidCaller)
+            }
+            let role_admin = self.get_role_admin(role);
+            access_control::Internal::_check_role(self, role_admin, self.env().caller())?;
+            if self.has_role(role, account) {
+                return Err(access_control::AccessControlError::RoleRedundant)
+            }
+
+            access_control::Internal::_setup_role(self, role, account);
+            Ok(())
+        }
+
+        /// Revokes a role. Once signers are set roles are revoked through them.
+        #[ink(message)]
+        fn revoke_role(
+            &mut self,
+            role: access_control::RoleType,
+            account: AccountId,
+        ) -> Result<(), access_control::AccessControlError> {
+            if marketplace_multisig::Internal::ensure_no_multisig(self).is_err()
+                || self.marketplace.custody_transfer.get().unwrap_or_default()
+            {
+                return Err(access_control::AccessControlError::InvalidCaller)
+            }
+            let role_admin = self.get_role_admin(role);
+            access_control::Internal::_check_role(self, role_admin, self.env().caller())?;
+            access_control::Internal::_check_role(self, role, account)?;
+
+            access_control::Internal::_do_revoke_role(self, role, account);
+            Ok(())
+        }
+    }
+
+    impl Pausable for MarketplaceContract {}
+
+    impl PSP34Receiver for MarketplaceContract {
+        /// Accepts only NFTs the marketplace takes into custody itself and records the
+        /// account they were received from.
+        #[ink(message)]
+        fn before_received(
+            &mut self,
+            operator: AccountId,
+            from: AccountId,
+            id: Id,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP34ReceiverError> {
+            self.receive_token(operator, from, id).map_err(|_| {
+                PSP34ReceiverError::TransferRejected(String::from(
+                    "Marketplace accepts only tokens it takes into custody",
+                ))
+            })
+        }
+    }
+
+    // ***************************** Tests *******************************
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::marketplace::MarketplaceContract;
+        use ink::{env::test, storage::traits::StorageKey};
+        use openbrush::contracts::psp34::{Id, PSP34Error};
+        use pallet_marketplace::impls::marketplace::{
+            batch::next_batch,
+            contract_calls::mock,
+            marketplace_disputes::Internal as DisputesInternal,
+            marketplace_fractions::{Internal as FractionsInternal, BUYOUT_DURATION},
+            marketplace_lending::{Internal as LendingInternal, YEAR},
+            marketplace_mystery_boxes::Internal as MysteryBoxesInternal,
+            marketplace_nested_listings::Internal as NestedListingsInternal,
+            marketplace_partners::Internal as PartnersInternal,
+            marketplace_raffles::{Internal as RafflesInternal, RAFFLE_REVEAL_PERIOD},
+            marketplace_rentals::{Internal as RentalsInternal, DAY},
+            marketplace_sale::Internal,
+            marketplace_staking::{Internal as StakingInternal, EPOCH_LENGTH},
+            marketplace_wash_trading::Internal as WashTradingInternal,
+            types::{
+                price_bucket, token_key, BalanceSheet, BatchProgress, Buyout, ClaimableBalances,
+                Credit, CurrencyMetadata, DepositBreakdown, Earnings, EditionSide, EscrowItem,
+                FractionVault, GroupPurchase, InventoryToken, Item, Loan, MarketplaceError,
+                MarketplaceStats, MysteryBox, NftContractType, OfferItem, Partner,
+                PendingParameterChange, PendingRecovery, PhysicalTerms, ProposalStatus, Raffle,
+                RaffleEntry, RaffleStatus, RegisteredCollection, Rental, SaleAttribution,
+                SignedOrder, Stake, Trade, TradeAsset, XcmReservation, ARBITER, CREDIT_ISSUER,
+                CURATOR, MODERATOR, NATIVE_UNIT, PRICE_BUCKET_WORDS,
+            },
+        };
+        use std::{cell::RefCell, rc::Rc};
+
+        #[ink::test]
+        fn new_works() {
+            let marketplace = init_contract();
+            assert_eq!(marketplace.get_marketplace_fee(), 100);
+            assert_eq!(marketplace.get_max_fee(), 1000);
+            assert_eq!(marketplace.get_fee_recipient(), Some(fee_recipient()));
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.pause().is_ok());
+            assert!(marketplace.paused());
+            assert_eq!(
+                marketplace.deposit(),
+                Err(MarketplaceError::PausableError(
+                    pausable::PausableError::Paused
+                ))
+            );
+            assert!(marketplace.unpause().is_ok());
+            assert!(!marketplace.paused());
+            assert!(marketplace.deposit().is_ok());
+        }
+
+        #[ink::test]
+        fn pause_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.pause(),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn upgrade_fails_if_not_upgrader() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.upgrade(Hash::default()),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn get_code_version_works() {
+            let marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.get_code_version(),
+                String::from(env!("CARGO_PKG_VERSION"))
+            );
+        }
+
+        #[ink::test]
+        fn marketplace_version_works() {
+            let marketplace = init_contract();
+
+            let (major, minor, patch) = marketplace.marketplace_version();
+            assert_eq!(
+                format!("{}.{}.{}", major, minor, patch),
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+
+        #[ink::test]
+        fn supports_feature_works() {
+            let marketplace = init_contract();
+
+            assert!(marketplace.supports_feature(FeatureId::Psp22Payments));
+            assert!(marketplace.supports_feature(FeatureId::Auctions));
+            assert!(!marketplace.supports_feature(FeatureId::XcmPurchases));
+            assert!(!marketplace.supports_feature(FeatureId::Erc721Xvm));
+            assert_eq!(
+                marketplace.supports_feature(FeatureId::AssetPayments),
+                cfg!(feature = "pallet-assets")
+            );
+            assert!(marketplace.supports_feature(FeatureId::RmrkNesting));
+        }
+
+        #[ink::test]
+        fn migrate_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(marketplace.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(marketplace.migrate(10), Ok(true));
+        }
+
+        #[ink::test]
+        fn migrate_from_first_deployment_works() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            // root cell as written by the first deployment
+            #[derive(scale::Encode)]
+            struct RootV0 {
+                owner: AccountId,
+                ownable_reserved: Option<()>,
+                guard_status: u8,
+                guard_reserved: Option<()>,
+                fee: u16,
+                max_fee: u16,
+                market_fee_recipient: Option<AccountId>,
+                nonce: u64,
+                last_offer_id: u128,
+            }
+            ink::env::set_contract_storage(
+                &<MarketplaceContract as StorageKey>::KEY,
+                &RootV0 {
+                    owner: accounts.alice,
+                    ownable_reserved: None,
+                    guard_status: 0,
+                    guard_reserved: None,
+                    fee: 150,
+                    max_fee: 500,
+                    market_fee_recipient: Some(fee_recipient()),
+                    nonce: 3,
+                    last_offer_id: 2,
+                },
+            );
+            let mut marketplace = load_contract();
+            assert_eq!(marketplace.get_storage_version(), 0);
+            assert_eq!(marketplace.get_marketplace_fee(), 150);
+            assert_eq!(marketplace.get_max_fee(), 500);
+            assert_eq!(marketplace.get_fee_recipient(), Some(fee_recipient()));
+            assert_eq!(marketplace.owner(), accounts.alice);
+            assert!(!marketplace.paused());
+
+            let long_id = Id::Bytes(vec![1; 40]);
+            for (offer_id, token_id) in [(1, Id::U128(1)), (2, long_id.clone())] {
+                marketplace.marketplace.offer_items.insert(
+                    &offer_id,
+                    &OfferItem {
+                        bidder_id: accounts.bob,
+                        contract_address: contract_address(),
+                        token_id: Some(token_id.clone()),
+                        quantity: 1,
+                        price_per_item: 100,
+                        extra: String::new(),
+                    },
+                );
+                marketplace
+                    .marketplace
+                    .offer_items_per_contract_token_id
+                    .insert(&(contract_address(), Some(token_id)), &vec![offer_id]);
+            }
+            // listings of the first deployment are keyed by the token id as is
+            marketplace.marketplace.items.insert(
+                &(contract_address(), long_id.clone()),
+                &Item {
+                    owner: accounts.charlie,
+                    price: 1000,
+                },
+            );
+
+            assert_eq!(marketplace.migrate(1), Ok(false));
+            assert_eq!(marketplace.get_storage_version(), 0);
+            assert_eq!(marketplace.migrate(1), Ok(true));
+            assert_eq!(marketplace.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(marketplace.migrate(1), Ok(true));
+
+            assert_eq!(marketplace.get_marketplace_fee(), 150);
+            assert_eq!(marketplace.get_max_fee(), 500);
+            assert_eq!(
+                marketplace.marketplace.v1.max_batch_size,
+                DEFAULT_MAX_BATCH_SIZE
+            );
+            assert!(marketplace.has_role(access_control::DEFAULT_ADMIN_ROLE, accounts.alice));
+            assert!(!marketplace.paused());
+            assert_eq!(
+                marketplace.get_offer_for_token(contract_address(), Some(Id::U128(1))),
+                Ok(vec![1])
+            );
+            assert_eq!(
+                marketplace.get_offer_for_token(contract_address(), Some(long_id.clone())),
+                Ok(vec![2])
+            );
+            assert_eq!(
+                marketplace.get_account_activity(accounts.bob).offers_made,
+                vec![1, 2]
+            );
+            assert_eq!(marketplace.get_marketplace_stats().active_offers, 2);
+            assert!(marketplace
+                .marketplace
+                .offer_items_per_contract_token_id
+                .get(&(contract_address(), Some(Id::U128(1))))
+                .is_none());
+
+            let listings = vec![(contract_address(), long_id.clone())];
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.migrate_listings(listings.clone()),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.migrate_listings(listings.clone()).is_ok());
+            assert!(marketplace.migrate_listings(listings).is_ok());
+            assert_eq!(
+                marketplace.get_price(contract_address(), long_id.clone()),
+                Some(1000)
+            );
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(1000));
+            let listings = marketplace.get_listings_by_account(accounts.charlie, 0, 10);
+            assert_eq!(listings.len(), 1);
+            assert_eq!(listings[0].1, long_id);
+            assert_eq!(marketplace.get_marketplace_stats().active_listings, 1);
+        }
+
+        #[ink::test]
+        fn token_key_works() {
+            assert_eq!(token_key(&Id::U128(1)), Id::U128(1));
+            assert_eq!(token_key(&Id::Bytes(vec![1; 32])), Id::Bytes(vec![1; 32]));
+
+            let key = token_key(&Id::Bytes(vec![1; 40]));
+            match key {
+                Id::Bytes(bytes) => {
+                    assert_eq!(bytes.len(), 33);
+                    assert_eq!(bytes[0], 0xff);
+                }
+                _ => panic!("expected byte key"),
+            }
+        }
+
+        #[ink::test]
+        fn blacklist_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.add_to_blacklist(accounts.bob).is_ok());
+            assert!(marketplace.is_blacklisted(accounts.bob));
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.list(contract_address(), Id::U128(1), 100),
+                Err(MarketplaceError::Blacklisted)
+            );
+            assert_eq!(
+                marketplace.make_offer(contract_address(), None, 1, 100, String::from("")),
+                Err(MarketplaceError::Blacklisted)
+            );
+            assert_eq!(marketplace.withdraw(0), Err(MarketplaceError::Blacklisted));
+            set_sender(accounts.alice);
+            assert!(marketplace.remove_from_blacklist(accounts.bob).is_ok());
+            assert!(!marketplace.is_blacklisted(accounts.bob));
+        }
+
+        #[ink::test]
+        fn add_to_blacklist_fails_if_not_moderator() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.add_to_blacklist(accounts.charlie),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.grant_role(MODERATOR, accounts.bob).is_ok());
+            set_sender(accounts.bob);
+            assert!(marketplace.add_to_blacklist(accounts.charlie).is_ok());
+        }
+
+        #[ink::test]
+        fn admin_unlist_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.charlie,
+                    price: 100,
+                },
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.admin_unlist(contract_address(), Id::U128(1), String::from("scam")),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+
+            set_sender(accounts.alice);
+            assert!(marketplace.grant_role(MODERATOR, accounts.bob).is_ok());
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .admin_unlist(contract_address(), Id::U128(1), String::from("scam"))
+                .is_ok());
+            assert_eq!(marketplace.get_price(contract_address(), Id::U128(1)), None);
+            assert_eq!(
+                marketplace.admin_unlist(contract_address(), Id::U128(1), String::from("scam")),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn admin_operations_require_signer_confirmations() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert_eq!(
+                marketplace.set_signers(vec![accounts.alice, accounts.bob], 3),
+                Err(MarketplaceError::InvalidThreshold)
+            );
+            assert!(marketplace
+                .set_signers(vec![accounts.alice, accounts.bob], 2)
+                .is_ok());
+            assert_eq!(marketplace.get_signer_threshold(), 2);
+
+            assert_eq!(
+                marketplace.set_marketplace_fee(120),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.add_to_blacklist(accounts.charlie),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.propose_admin_operation(AdminOperation::ParameterChange(
+                    ParameterChange::MarketplaceFee(120)
+                )),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.execute_admin_operation(1),
+                Err(MarketplaceError::NotEnoughConfirmations)
+            );
+            assert_eq!(
+                marketplace.confirm_admin_operation(1),
+                Err(MarketplaceError::AlreadyConfirmed)
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.confirm_admin_operation(1),
+                Err(MarketplaceError::NotSigner)
+            );
+            set_sender(accounts.bob);
+            assert!(marketplace.confirm_admin_operation(1).is_ok());
+            assert!(marketplace.execute_admin_operation(1).is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 120);
+            assert_eq!(
+                marketplace.execute_admin_operation(1),
+                Err(MarketplaceError::AdminProposalExecuted)
+            );
+        }
+
+        #[ink::test]
+        fn owner_cannot_bypass_signers() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_timelock_delay(1000).is_ok());
+            assert!(marketplace.set_fee_recipient(accounts.bob).is_ok());
+            assert!(marketplace
+                .set_signers(vec![accounts.alice, accounts.bob], 2)
+                .is_ok());
+
+            assert_eq!(marketplace.pause(), Err(MarketplaceError::MultisigRequired));
+            assert_eq!(
+                marketplace.unpause(),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.set_price_oracle(accounts.charlie, String::from("ASTR/USD"), 60_000),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.set_compliance_registry(Some(accounts.charlie)),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.cancel_parameter_change(1),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.grant_role(UPGRADER, accounts.charlie),
+                Err(access_control::AccessControlError::InvalidCaller)
+            );
+            assert_eq!(
+                marketplace.revoke_role(0, accounts.alice),
+                Err(access_control::AccessControlError::InvalidCaller)
+            );
+            assert_eq!(
+                marketplace.upgrade(Hash::from([1; 32])),
+                Err(MarketplaceError::MultisigRequired)
+            );
+
+            assert_eq!(
+                marketplace
+                    .propose_admin_operation(AdminOperation::GrantRole(UPGRADER, accounts.charlie)),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.propose_admin_operation(AdminOperation::CancelParameterChange(1)),
+                Ok(2)
+            );
+            assert_eq!(
+                marketplace.propose_admin_operation(AdminOperation::Pause),
+                Ok(3)
+            );
+            set_sender(accounts.bob);
+            for proposal_id in 1..=3 {
+                assert!(marketplace.confirm_admin_operation(proposal_id).is_ok());
+                assert!(marketplace.execute_admin_operation(proposal_id).is_ok());
+            }
+            assert!(marketplace.has_role(UPGRADER, accounts.charlie));
+            assert_eq!(marketplace.get_parameter_change(1), None);
+            assert!(marketplace.paused());
+        }
+
+        #[ink::test]
+        fn settlement_and_withdrawal_cannot_be_reentered() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let reentrancy_error = Err(MarketplaceError::ReentrancyError(
+                ReentrancyGuardError::ReentrantCall,
+            ));
+            // Simulates a malicious PSP34/PSP22 hook calling back while a guarded call runs.
+            marketplace.guard.status = 1;
+
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1)),
+                reentrancy_error
+            );
+            assert_eq!(
+                marketplace.buy_with_psp22(contract_address(), Id::U128(1), currency_address()),
+                reentrancy_error
+            );
+            assert_eq!(
+                marketplace.buy_with_psp22_permit(
+                    contract_address(),
+                    Id::U128(1),
+                    currency_address(),
+                    0,
+                    [0; 65]
+                ),
+                reentrancy_error
+            );
+            assert_eq!(marketplace.accept_offer(1, Id::U128(1)), reentrancy_error);
+            assert_eq!(marketplace.fulfill_offer(1, Id::U128(1)), reentrancy_error);
+            assert_eq!(marketplace.withdraw(0), reentrancy_error);
+            assert_eq!(marketplace.withdraw_all(), reentrancy_error);
+            assert_eq!(marketplace.withdraw_to(accounts.bob, 0), reentrancy_error);
+            assert_eq!(
+                marketplace.withdraw_psp22(currency_address(), 0),
+                reentrancy_error
+            );
+            assert_eq!(
+                marketplace.transfer_deposit(accounts.bob, 0),
+                reentrancy_error
+            );
+        }
+
+        #[ink::test]
+        fn collection_hook_cannot_reenter_settlement() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            // The collection calls back into the marketplace while the NFT is transferred
+            // to the buyer.
+            let reentrant_results = Rc::new(RefCell::new(Vec::new()));
+            let results = reentrant_results.clone();
+            mock::set_transfer_hook(move || {
+                // the guard status was flushed to storage by the outer call
+                let mut marketplace = load_contract();
+                results.borrow_mut().push(marketplace.withdraw(0));
+                results
+                    .borrow_mut()
+                    .push(marketplace.buy(contract_address(), Id::U64(1)));
+            });
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert!(marketplace.buy(contract_address(), Id::U64(1)).is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(
+                *reentrant_results.borrow(),
+                vec![
+                    Err(MarketplaceError::ReentrancyError(
+                        ReentrancyGuardError::ReentrantCall
+                    )),
+                    Err(MarketplaceError::ReentrancyError(
+                        ReentrancyGuardError::ReentrantCall
+                    )),
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn collection_hook_can_only_call_before_received_during_custody_transfer() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    true
+                )
+                .is_ok());
+
+            // The collection calls back into the marketplace while the NFT is taken into
+            // custody for the escrow.
+            let reentrant_results = Rc::new(RefCell::new(Vec::new()));
+            let results = reentrant_results.clone();
+            mock::set_transfer_hook(move || {
+                let mut marketplace = load_contract();
+                set_sender(contract_address());
+                results
+                    .borrow_mut()
+                    .push(marketplace.set_receive_wrapped_native(false));
+                results.borrow_mut().push(marketplace.withdraw(0));
+                assert!(marketplace
+                    .before_received(
+                        test::callee::<Environment>(),
+                        default_accounts().bob,
+                        Id::U64(1),
+                        Vec::new()
+                    )
+                    .is_ok());
+                set_sender(default_accounts().charlie);
+            });
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert!(marketplace.buy(contract_address(), Id::U64(1)).is_ok());
+            assert_eq!(
+                *reentrant_results.borrow(),
+                vec![
+                    Err(MarketplaceError::CustodyTransferInProgress),
+                    Err(MarketplaceError::CustodyTransferInProgress),
+                ]
+            );
+            assert_eq!(
+                marketplace.get_custody_depositor(contract_address(), Id::U64(1)),
+                Some(accounts.bob)
+            );
+            assert!(!marketplace
+                .marketplace
+                .custody_transfer
+                .get()
+                .unwrap_or_default());
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.set_marketplace_fee(120).is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 120);
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_is_timelocked() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(marketplace.set_timelock_delay(1000), Ok(None));
+            assert_eq!(marketplace.get_timelock_delay(), 1000);
+            assert_eq!(marketplace.set_marketplace_fee(120), Ok(Some(1)));
+            assert_eq!(marketplace.get_marketplace_fee(), 100);
+            assert_eq!(
+                marketplace.get_parameter_change(1),
+                Some(PendingParameterChange {
+                    change: ParameterChange::MarketplaceFee(120),
+                    eta: 1000
+                })
+            );
+            assert_eq!(
+                marketplace.execute_parameter_change(1),
+                Err(MarketplaceError::TimelockNotExpired)
+            );
+            test::set_block_timestamp::<Environment>(1000);
+            assert!(marketplace.execute_parameter_change(1).is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 120);
+            assert_eq!(marketplace.get_parameter_change(1), None);
+        }
+
+        #[ink::test]
+        fn cancel_parameter_change_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.set_timelock_delay(1000).is_ok());
+            assert!(marketplace.set_fee_recipient(accounts.bob).is_ok());
+            assert!(marketplace.cancel_parameter_change(1).is_ok());
+            assert_eq!(
+                marketplace.execute_parameter_change(1),
+                Err(MarketplaceError::ParameterChangeNotFound)
+            );
+            assert_eq!(marketplace.get_fee_recipient(), Some(fee_recipient()));
+        }
+
+        #[ink::test]
+        fn set_max_fee_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.set_max_fee(2000).is_ok());
+            assert_eq!(marketplace.get_max_fee(), 2000);
+            assert_eq!(
+                marketplace.set_max_fee(10_001),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn set_max_fee_fails_if_lower_than_marketplace_fee() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.set_max_fee(99),
+                Err(MarketplaceError::FeeTooHigh { max: 99, got: 100 })
+            );
+            assert!(marketplace.set_timelock_delay(1000).is_ok());
+            assert_eq!(marketplace.set_max_fee(150), Ok(Some(1)));
+            assert_eq!(marketplace.set_marketplace_fee(200), Ok(Some(2)));
+
+            // the fee raised while the max fee change was queued is checked again
+            test::set_block_timestamp::<Environment>(1000);
+            assert!(marketplace.execute_parameter_change(2).is_ok());
+            assert_eq!(
+                marketplace.execute_parameter_change(1),
+                Err(MarketplaceError::FeeTooHigh { max: 150, got: 200 })
+            );
+            assert_eq!(marketplace.get_max_fee(), 1000);
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_marketplace_fee(120),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_fails_if_fee_too_high() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.set_marketplace_fee(1001),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 1_000,
+                    got: 1_001,
+                })
+            );
+            assert!(marketplace.set_marketplace_fee(1000).is_ok());
+        }
+
+        #[ink::test]
+        fn set_fee_recipient_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.set_fee_recipient(accounts.bob).is_ok());
+            assert_eq!(marketplace.get_fee_recipient(), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn settle_sale_fails_if_fee_recipient_not_set() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.market_fee_recipient = None;
+
+            assert_eq!(marketplace.get_fee_recipient(), None);
+            assert_eq!(
+                marketplace.settle_sale(
+                    contract_address(),
+                    Id::U64(1),
+                    accounts.bob,
+                    accounts.alice,
+                    100,
+                    Currency::Native,
+                    SaleAttribution::default()
+                ),
+                Err(MarketplaceError::FeeRecipientNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn set_fee_recipient_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.set_fee_recipient(accounts.bob),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn buy_fails_if_unlisted_token() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn add_supported_currency_works() {
+            let mut marketplace = init_contract();
+
+            assert!(!marketplace.is_supported_currency(currency_address()));
+            assert!(marketplace
+                .add_supported_currency(currency_address(), currency_metadata())
+                .is_ok());
+            assert!(marketplace.is_supported_currency(currency_address()));
+            assert_eq!(
+                marketplace.get_supported_currencies(),
+                vec![currency_address()]
+            );
+            assert_eq!(
+                marketplace.get_currency_metadata(currency_address()),
+                Some(currency_metadata())
+            );
+            assert!(marketplace
+                .remove_supported_currency(currency_address())
+                .is_ok());
+            assert!(!marketplace.is_supported_currency(currency_address()));
+            assert!(marketplace.get_supported_currencies().is_empty());
+        }
+
+        #[ink::test]
+        fn add_supported_currency_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.add_supported_currency(currency_address(), currency_metadata()),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_psp22_fails_if_currency_not_supported() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.buy_with_psp22(contract_address(), Id::U64(1), currency_address()),
+                Err(MarketplaceError::CurrencyNotSupported)
+            );
+        }
+
+        #[ink::test]
+        fn add_supported_asset_works() {
+            let mut marketplace = init_contract();
+
+            assert!(!marketplace.is_supported_asset(1));
+            assert!(marketplace.add_supported_asset(1).is_ok());
+            assert!(marketplace.add_supported_asset(1).is_ok());
+            assert!(marketplace.is_supported_asset(1));
+            assert_eq!(marketplace.get_supported_assets(), vec![1]);
+            assert!(marketplace.remove_supported_asset(1).is_ok());
+            assert!(!marketplace.is_supported_asset(1));
+            assert!(marketplace.get_supported_assets().is_empty());
+        }
+
+        #[ink::test]
+        fn buy_with_asset_fails_if_asset_not_supported() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.buy_with_asset(contract_address(), Id::U64(1), 1),
+                Err(MarketplaceError::CurrencyNotSupported)
+            );
+        }
+
+        #[ink::test]
+        fn set_wrapped_native_works() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(marketplace.get_wrapped_native(), None);
+            assert!(marketplace.set_wrapped_native(currency_address()).is_ok());
+            assert_eq!(marketplace.get_wrapped_native(), Some(currency_address()));
+            assert!(marketplace.is_supported_currency(currency_address()));
+        }
+
+        #[ink::test]
+        fn set_receive_wrapped_native_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.set_receive_wrapped_native(true),
+                Err(MarketplaceError::WrappedNativeNotSet)
+            );
+            assert!(marketplace.set_wrapped_native(currency_address()).is_ok());
+            assert!(marketplace.set_receive_wrapped_native(true).is_ok());
+            assert!(marketplace.is_receiving_wrapped_native(accounts.alice));
+            assert!(marketplace.set_receive_wrapped_native(false).is_ok());
+            assert!(!marketplace.is_receiving_wrapped_native(accounts.alice));
+        }
+
+        #[ink::test]
+        fn set_price_oracle_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace
+                .set_price_oracle(currency_address(), String::from("ASTR/USD"), 60_000)
+                .is_ok());
+            assert_eq!(marketplace.get_price_oracle(), Some(currency_address()));
+        }
+
+        #[ink::test]
+        fn set_compliance_registry_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace
+                .set_compliance_registry(Some(currency_address()))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_compliance_registry(),
+                Some(currency_address())
+            );
+            assert!(marketplace.set_compliance_registry(None).is_ok());
+            assert_eq!(marketplace.get_compliance_registry(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_compliance_registry(Some(currency_address())),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_royalty_registry_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace
+                .set_royalty_registry(Some(currency_address()))
+                .is_ok());
+            assert_eq!(marketplace.get_royalty_registry(), Some(currency_address()));
+            assert!(marketplace.set_royalty_registry(None).is_ok());
+            assert_eq!(marketplace.get_royalty_registry(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_royalty_registry(Some(currency_address())),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_royalty_registry_is_timelocked() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.set_timelock_delay(1000).is_ok());
+            assert!(marketplace
+                .set_royalty_registry(Some(currency_address()))
+                .is_ok());
+            assert_eq!(marketplace.get_royalty_registry(), None);
+            assert_eq!(
+                marketplace.get_parameter_change(1),
+                Some(PendingParameterChange {
+                    change: ParameterChange::RoyaltyRegistry(Some(currency_address())),
+                    eta: 1000
+                })
+            );
+            test::set_block_timestamp::<Environment>(1000);
+            assert!(marketplace.execute_parameter_change(1).is_ok());
+            assert_eq!(marketplace.get_royalty_registry(), Some(currency_address()));
+
+            assert!(marketplace
+                .set_signers(vec![accounts.alice, accounts.bob], 2)
+                .is_ok());
+            assert_eq!(
+                marketplace.set_royalty_registry(None),
+                Err(MarketplaceError::MultisigRequired)
+            );
+            assert_eq!(
+                marketplace.set_timelock_delay(0),
+                Err(MarketplaceError::MultisigRequired)
+            );
+        }
+
+        #[ink::test]
+        fn set_price_oracle_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.set_price_oracle(currency_address(), String::from("ASTR/USD"), 60_000),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn list_with_usd_price_fails_if_oracle_not_set() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.list_with_usd_price(contract_address(), Id::U64(1), 100),
+                Err(MarketplaceError::OracleNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_psp22_permit_fails_if_deadline_passed() {
+            let mut marketplace = init_contract();
+            assert!(marketplace
+                .add_supported_currency(currency_address(), currency_metadata())
+                .is_ok());
+            test::set_block_timestamp::<Environment>(1000);
+
+            assert_eq!(
+                marketplace.buy_with_psp22_permit(
+                    contract_address(),
+                    Id::U128(1),
+                    currency_address(),
+                    999,
+                    [0; 65]
+                ),
+                Err(MarketplaceError::PermitExpired)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_deposit_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            assert!(marketplace.transfer_deposit(accounts.bob, 40).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 60);
+            assert_eq!(marketplace.get_deposit(accounts.bob), 40);
+            assert_eq!(
+                marketplace.transfer_deposit(accounts.bob, 61),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+        }
+
+        #[ink::test]
+        fn deposit_fails_on_overflow() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            test::set_value_transferred::<Environment>(Balance::MAX);
+            assert!(marketplace.deposit().is_ok());
+
+            test::set_value_transferred::<Environment>(1);
+            assert_eq!(
+                marketplace.deposit(),
+                Err(MarketplaceError::ArithmeticOverflow)
+            );
+            assert_eq!(marketplace.get_deposit(accounts.alice), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn make_offer_fails_on_overflow() {
+            let mut marketplace = init_contract();
+            test::set_value_transferred::<Environment>(Balance::MAX);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_offer(
+                    contract_address(),
+                    Some(Id::U64(1)),
+                    2,
+                    Balance::MAX,
+                    String::from("")
+                ),
+                Err(MarketplaceError::ArithmeticOverflow)
+            );
+        }
+
+        #[ink::test]
+        fn make_offer_returns_offer_id() {
+            let mut marketplace = init_contract();
+            test::set_value_transferred::<Environment>(1000);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_offer(contract_address(), None, 1, 100, String::from("")),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.make_offer_with_currency(
+                    contract_address(),
+                    Some(Id::U64(1)),
+                    1,
+                    100,
+                    Currency::Native,
+                    String::from("")
+                ),
+                Ok(2)
+            );
+        }
+
+        #[ink::test]
+        fn make_offer_with_currency_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let currency = Currency::Psp22(currency_address());
+            assert!(marketplace
+                .add_supported_currency(currency_address(), currency_metadata())
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.make_offer_with_currency(
+                    contract_address(),
+                    None,
+                    1,
+                    100,
+                    Currency::Asset(1),
+                    String::from("")
+                ),
+                Err(MarketplaceError::CurrencyNotSupported)
+            );
+            // native deposit does not fund offers in other currencies
+            test::set_value_transferred::<Environment>(1000);
+            assert!(marketplace.deposit().is_ok());
+            assert_eq!(
+                marketplace.make_offer_with_currency(
+                    contract_address(),
+                    None,
+                    1,
+                    100,
+                    currency,
+                    String::from("")
+                ),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+
+            marketplace.set_currency_deposit(accounts.bob, currency, 100);
+            assert!(marketplace
+                .make_offer_with_currency(
+                    contract_address(),
+                    None,
+                    1,
+                    100,
+                    currency,
+                    String::from("")
+                )
+                .is_ok());
+            assert_eq!(marketplace.get_offer_currency(1), Some(currency));
+            assert!(marketplace.get_offer_active(1));
+            marketplace.set_currency_deposit(accounts.bob, currency, 99);
+            assert!(!marketplace.get_offer_active(1));
+
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(marketplace.get_offer_currency(1), None);
+            assert!(!marketplace.marketplace.offer_currencies.contains(&1));
+        }
+
+        #[ink::test]
+        fn buy_with_deposit_of_does_not_spend_owner_credit() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.charlie);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.charlie);
+            mock::approve(
+                contract_address(),
+                accounts.charlie,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    100,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            set_sender(accounts.alice);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.grant_credit(accounts.alice, 1000).is_ok());
+            test::set_value_transferred::<Environment>(1000);
+            assert!(marketplace.deposit().is_ok());
+            test::set_value_transferred::<Environment>(0);
+            assert!(marketplace.approve_spender(accounts.bob, 100).is_ok());
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 10_000);
+            assert!(marketplace
+                .buy_with_deposit_of(accounts.alice, contract_address(), Id::U64(1))
+                .is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.alice)
+            );
+            assert_eq!(marketplace.get_deposit(accounts.alice), 900);
+            assert_eq!(
+                marketplace
+                    .get_credit(accounts.alice)
+                    .map(|credit| credit.amount),
+                Some(100)
+            );
+            assert_eq!(
+                marketplace.get_spender_allowance(accounts.alice, accounts.bob),
+                0
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_deposit_of_requires_native_listing() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let key = (contract_address(), Id::U128(1));
+            marketplace.marketplace.items.insert(
+                &key,
+                &Item {
+                    owner: accounts.charlie,
+                    price: 100,
+                },
+            );
+            marketplace
+                .marketplace
+                .listing_currencies
+                .insert(&key, &Currency::Psp22(currency_address()));
+            assert!(marketplace.approve_spender(accounts.bob, 100).is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.buy_with_deposit_of(accounts.alice, contract_address(), Id::U128(1)),
+                Err(MarketplaceError::CurrencyMismatch)
+            );
+            assert_eq!(
+                marketplace.get_spender_allowance(accounts.alice, accounts.bob),
+                100
+            );
+        }
+
+        #[ink::test]
+        fn get_royalty_fails_on_overflow() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    Some(accounts.bob),
+                    Some(1000),
+                    NftContractType::Psp34
+                )
+                .is_ok());
+
+            assert_eq!(
+                marketplace.get_royalty(contract_address(), &Id::U64(1), Balance::MAX / 1000),
+                Ok((accounts.bob, Balance::MAX / 1000 / 10))
+            );
+            assert_eq!(
+                marketplace.get_royalty(contract_address(), &Id::U64(1), Balance::MAX),
+                Err(MarketplaceError::ArithmeticOverflow)
+            );
+        }
+
+        #[ink::test]
+        fn offer_bond_is_refunded_after_bond_period() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            assert!(marketplace.set_offer_bond_period(2000).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_offer(contract_address(), None, 1, 91, String::from("")),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 90, String::from(""))
+                .is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 90);
+            assert!(marketplace.get_offer_active(1));
+
+            test::set_block_timestamp::<Environment>(2000);
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 100);
+            assert_eq!(marketplace.get_deposit(fee_recipient()), 0);
+        }
+
+        #[ink::test]
+        fn cancel_offer_clears_storage() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(
+                    contract_address(),
+                    Some(Id::U64(1)),
+                    1,
+                    90,
+                    String::from("")
+                )
+                .is_ok());
+
+            assert!(marketplace.cancel_offer(1).is_ok());
+            let data = &marketplace.marketplace;
+            assert!(!data.offer_items.contains(&1));
+            assert!(!data.offer_created_at.contains(&1));
+            assert!(!data.offer_bonds.contains(&1));
+            assert_eq!(
+                data.token_offer_index
+                    .count(&(contract_address(), Some(Id::U64(1)))),
+                0
+            );
+            assert_eq!(data.account_offer_index.count(&accounts.alice), 0);
+        }
+
+        #[ink::test]
+        fn deposit_breakdown_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            assert!(marketplace.set_offer_bond_period(2000).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+
+            assert_eq!(
+                marketplace.get_deposit_breakdown(accounts.alice),
+                DepositBreakdown {
+                    total: 90,
+                    locked: 50,
+                    available: 40,
+                    bonds: 10,
+                }
+            );
+            assert_eq!(
+                marketplace.get_deposit_breakdown(accounts.alice).total,
+                marketplace.get_deposit(accounts.alice)
+            );
+
+            test::set_block_timestamp::<Environment>(2000);
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(
+                marketplace.get_deposit_breakdown(accounts.alice),
+                DepositBreakdown {
+                    total: 100,
+                    locked: 0,
+                    available: 100,
+                    bonds: 0,
+                }
+            );
+            assert_eq!(
+                marketplace.get_deposit_breakdown(accounts.alice).total,
+                marketplace.get_deposit(accounts.alice)
+            );
+        }
+
+        #[ink::test]
+        fn offer_bond_is_forfeited_if_cancelled_early() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            assert!(marketplace.set_offer_bond_period(2000).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 90);
+            assert_eq!(marketplace.get_deposit(fee_recipient()), 10);
+        }
+
+        #[ink::test]
+        fn cancel_offer_emits_event() {
+            let mut marketplace = init_contract();
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+            assert_eq!(2, ink::env::test::recorded_events().count());
+
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(3, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn cancel_offer_fails_before_min_offer_duration() {
+            let mut marketplace = init_contract();
+            assert!(marketplace.set_min_offer_duration(100).is_ok());
+            assert_eq!(marketplace.get_min_offer_duration(), 100);
+            test::set_block_timestamp::<Environment>(1000);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+
+            test::set_block_timestamp::<Environment>(1099);
+            assert_eq!(
+                marketplace.cancel_offer(1),
+                Err(MarketplaceError::OfferTooRecent)
+            );
+            test::set_block_timestamp::<Environment>(1100);
+            assert!(marketplace.cancel_offer(1).is_ok());
+        }
+
+        #[ink::test]
+        fn remove_unfunded_offer_fails_for_bidder_before_min_offer_duration() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_min_offer_duration(100).is_ok());
+            test::set_block_timestamp::<Environment>(1000);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+            marketplace.set_currency_deposit(accounts.alice, Currency::Native, 49);
+
+            test::set_block_timestamp::<Environment>(1099);
+            assert_eq!(
+                marketplace.remove_unfunded_offer(1),
+                Err(MarketplaceError::OfferTooRecent)
+            );
+            test::set_block_timestamp::<Environment>(1100);
+            assert!(marketplace.remove_unfunded_offer(1).is_ok());
+        }
+
+        #[ink::test]
+        fn remove_unfunded_offer_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 90, String::from(""))
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.remove_unfunded_offer(1),
+                Err(MarketplaceError::OfferFunded)
+            );
+            // spending the deposit elsewhere leaves the offer uncovered
+            marketplace.set_currency_deposit(accounts.alice, Currency::Native, 89);
+            assert!(marketplace.remove_unfunded_offer(1).is_ok());
+            assert!(!marketplace.get_offer_active(1));
+            // bond goes to the treasury whoever removes the offer
+            assert_eq!(marketplace.get_deposit(fee_recipient()), 10);
+            assert_eq!(marketplace.get_deposit(accounts.alice), 89);
+            assert_eq!(
+                marketplace.remove_unfunded_offer(1),
+                Err(MarketplaceError::OfferDoesNotExist)
+            );
+        }
+
+        #[ink::test]
+        fn balance_sheet_tracks_deposits_and_bonds() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+
+            assert_eq!(
+                marketplace.get_balance_sheet(),
+                BalanceSheet {
+                    deposits: 90,
+                    locked: 10,
+                    escrowed: 0,
+                    accrued_fees: 0,
+                }
+            );
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert!(marketplace.withdraw_to(accounts.alice, 30).is_ok());
+            assert_eq!(
+                marketplace.get_balance_sheet(),
+                BalanceSheet {
+                    deposits: 70,
+                    locked: 0,
+                    escrowed: 0,
+                    accrued_fees: 0,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn balance_sheet_tracks_accrued_fees() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let contract = test::callee::<Environment>();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_referral_fee_share(5000).is_ok());
+            set_sender(accounts.django);
+            assert!(marketplace
+                .register_referral_code(String::from("django"))
+                .is_ok());
+
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(contract_address(), accounts.bob, contract, Some(Id::U64(1)));
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(contract, 1000);
+            assert!(marketplace
+                .buy_with_referral(contract_address(), Id::U64(1), String::from("django"))
+                .is_ok());
+
+            let sheet = marketplace.get_balance_sheet();
+            assert_eq!(
+                sheet,
+                BalanceSheet {
+                    deposits: 0,
+                    locked: 0,
+                    escrowed: 0,
+                    accrued_fees: 5,
+                }
+            );
+            // only the referrer share stays in the contract
+            assert_eq!(
+                sheet.deposits + sheet.locked + sheet.escrowed + sheet.accrued_fees,
+                test::get_account_balance::<Environment>(contract).unwrap()
+            );
+            assert!(marketplace.solvency_check());
+
+            set_sender(accounts.django);
+            test::set_value_transferred::<Environment>(0);
+            assert!(marketplace
+                .claim_referral_earnings(Currency::Native)
+                .is_ok());
+            assert_eq!(marketplace.get_balance_sheet(), BalanceSheet::default());
+            assert_eq!(
+                test::get_account_balance::<Environment>(contract).unwrap(),
+                0
+            );
+        }
+
+        #[ink::test]
+        fn solvency_check_works() {
+            let mut marketplace = init_contract();
+            let contract = test::callee::<Environment>();
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            test::set_account_balance::<Environment>(contract, 100);
+            assert!(marketplace.solvency_check());
+            test::set_account_balance::<Environment>(contract, 99);
+            assert!(!marketplace.solvency_check());
+        }
+
+        #[ink::test]
+        fn withdraw_all_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_offer_bond(10).is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+
+            assert!(marketplace.withdraw_to(accounts.bob, 30).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 60);
+            assert_eq!(
+                marketplace.withdraw_to(accounts.bob, 11),
+                Err(MarketplaceError::DepositLockedByOffer)
+            );
+            assert_eq!(
+                marketplace.transfer_deposit(accounts.bob, 11),
+                Err(MarketplaceError::DepositLockedByOffer)
+            );
+            // the open offer stays covered
+            assert!(marketplace.withdraw_all().is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 50);
+            assert!(marketplace.get_offer_active(1));
+
+            // the bond is returned once the offer is cancelled
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert!(marketplace.withdraw_all().is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 0);
+        }
+
+        #[ink::test]
+        fn approve_spender_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.approve_spender(accounts.bob, 100).is_ok());
+            assert_eq!(
+                marketplace.get_spender_allowance(accounts.alice, accounts.bob),
+                100
+            );
+            assert_eq!(
+                marketplace.get_spender_allowance(accounts.bob, accounts.alice),
+                0
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_deposit_of_fails_if_not_listed() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.buy_with_deposit_of(accounts.alice, contract_address(), Id::U128(1)),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn deposit_psp22_fails_if_currency_not_supported() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.deposit_psp22(currency_address(), 100),
+                Err(MarketplaceError::CurrencyNotSupported)
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_psp22_fails_if_balance_insufficient() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.withdraw_psp22(currency_address(), 100),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            assert_eq!(
+                marketplace.get_psp22_deposit(fee_recipient(), currency_address()),
+                0
+            );
+        }
+
+        #[ink::test]
+        fn register_contract_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    Some(fee_recipient()),
+                    Some(999),
+                    NftContractType::Psp34
+                )
+                .is_ok());
+            let contract = marketplace
+                .get_registered_collection(contract_address())
+                .unwrap();
+            assert_eq!(contract.royalty.unwrap().0, fee_recipient());
+            assert_eq!(contract.royalty.unwrap().1, 999);
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn register_fails_if_fee_too_high() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.register(
+                    contract_address(),
+                    Some(fee_recipient()),
+                    Some(1001),
+                    NftContractType::Psp34
+                ),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 1_000,
+                    got: 1_001,
+                })
+            );
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    Some(fee_recipient()),
+                    Some(999),
+                    NftContractType::Psp34
+                )
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn register_fails_if_contract_already_registered() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    Some(fee_recipient()),
+                    Some(999),
+                    NftContractType::Psp34
+                )
+                .is_ok());
+            assert_eq!(
+                marketplace.register(
+                    contract_address(),
+                    Some(fee_recipient()),
+                    Some(999),
+                    NftContractType::Psp34
+                ),
+                Err(MarketplaceError::ContractAlreadyRegistered)
+            );
+        }
+
+        #[ink::test]
+        fn update_registered_collection_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+
+            assert!(marketplace
+                .set_collection_royalty(contract_address(), Some(accounts.bob), Some(500))
+                .is_ok());
+            assert_eq!(
+                marketplace.set_collection_royalty(contract_address(), None, Some(500)),
+                Err(MarketplaceError::RoyaltyReceiverNotSet)
+            );
+            assert!(marketplace
+                .set_collection_metadata(contract_address(), String::from("ipfs://meta"))
+                .is_ok());
+            assert!(marketplace
+                .set_collection_verified(contract_address(), true)
+                .is_ok());
+
+            let collection = marketplace
+                .get_registered_collection(contract_address())
+                .unwrap();
+            assert_eq!(collection.royalty, Some((accounts.bob, 500)));
+            assert!(marketplace.is_collection_verified(contract_address()));
+            assert_eq!(
+                marketplace.get_collection_metadata(contract_address()),
+                Some(String::from("ipfs://meta"))
+            );
+            assert_eq!(4, ink::env::test::recorded_events().count());
+
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .set_collection_verified(contract_address(), false)
+                .is_err());
+        }
+
+        #[ink::test]
+        fn unregister_works() {
+            let mut marketplace = init_contract();
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+            assert!(marketplace
+                .set_collection_metadata(contract_address(), String::from("ipfs://meta"))
+                .is_ok());
+
+            assert!(marketplace.unregister(contract_address()).is_ok());
+            assert!(marketplace
+                .get_registered_collection(contract_address())
+                .is_none());
+            assert_eq!(
+                marketplace.get_collection_metadata(contract_address()),
+                None
+            );
+            assert_eq!(
+                marketplace.unregister(contract_address()),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn set_nft_contract_hash_works() {
+            let mut marketplace = init_contract();
+            let hash = Hash::try_from([1; 32]).unwrap();
+            let hash2 = Hash::try_from([2; 32]).unwrap();
+
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::Rmrk, hash)
+                .is_ok());
+            assert_eq!(
+                marketplace.nft_contract_hash(NftContractType::Rmrk),
+                Ok(hash)
+            );
+
+            // Check also if owner is able to update hash.
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::Rmrk, hash2)
+                .is_ok());
+            assert_eq!(
+                marketplace.nft_contract_hash(NftContractType::Rmrk),
+                Ok(hash2)
+            );
+        }
+
+        #[ink::test]
+        fn get_nft_contract_types_works() {
+            let mut marketplace = init_contract();
+            let hash = Hash::try_from([1; 32]).unwrap();
+            let hash2 = Hash::try_from([2; 32]).unwrap();
+            assert!(marketplace.get_nft_contract_types().is_empty());
+
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::NFTSeries, hash)
+                .is_ok());
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::Psp34, hash2)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_nft_contract_types(),
+                vec![
+                    (NftContractType::Psp34, hash2),
+                    (NftContractType::NFTSeries, hash)
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn nft_contract_hash_fails_if_not_set() {
+            let marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.nft_contract_hash(NftContractType::Rmrk),
+                Err(MarketplaceError::NftContractHashNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn missing_offer_fails_with_error() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.cancel_offer(1),
+                Err(MarketplaceError::OfferDoesNotExist)
+            );
+            assert_eq!(
+                marketplace.accept_offer(1, Id::U64(1)),
+                Err(MarketplaceError::OfferDoesNotExist)
+            );
+        }
+
+        #[ink::test]
+        fn register_fails_if_royalty_receiver_not_set() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.register(contract_address(), None, Some(100), NftContractType::Psp34),
+                Err(MarketplaceError::RoyaltyReceiverNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn set_equip_slots_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(
+                marketplace.set_equip_slots(contract_address(), vec![1]),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+            assert_eq!(
+                marketplace.set_equip_slots(contract_address(), vec![1]),
+                Err(MarketplaceError::NotRmrkCollection)
+            );
+            assert_eq!(
+                marketplace.check_nested_token(contract_address(), &Id::U64(1)),
+                Ok(())
+            );
+
+            let rmrk_address = AccountId::from([0x4; 32]);
+            assert!(marketplace
+                .register(rmrk_address, None, None, NftContractType::Rmrk)
+                .is_ok());
+            assert!(marketplace
+                .set_equip_slots(rmrk_address, vec![1, 2])
+                .is_ok());
+            assert_eq!(marketplace.get_equip_slots(rmrk_address), vec![1, 2]);
+            assert!(marketplace
+                .set_equip_slots(rmrk_address, Vec::new())
+                .is_ok());
+            assert_eq!(marketplace.get_equip_slots(rmrk_address), Vec::<u32>::new());
+        }
+
+        #[ink::test]
+        fn get_listing_children_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(
+                marketplace.get_listing_children(contract_address(), Id::U64(1)),
+                Vec::new()
+            );
+
+            let children = vec![(contract_address(), Id::U64(2))];
+            marketplace
+                .marketplace
+                .listing_children
+                .insert(&(contract_address(), Id::U64(1)), &children);
+            assert_eq!(
+                marketplace.get_listing_children(contract_address(), Id::U64(1)),
+                children
+            );
+        }
+
+        #[ink::test]
+        fn register_erc721_xvm_fails_without_xvm_feature() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.register(
+                    evm_contract_address(),
+                    None,
+                    None,
+                    NftContractType::Erc721Xvm
+                ),
+                Err(MarketplaceError::XvmNotSupported)
+            );
+        }
+
+        #[ink::test]
+        fn set_collection_royalty_fails_for_erc721_xvm_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.registered_collections.insert(
+                &evm_contract_address(),
+                &RegisteredCollection {
+                    royalty: None,
+                    contract_type: NftContractType::Erc721Xvm,
+                },
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_collection_royalty(
+                    evm_contract_address(),
+                    Some(accounts.bob),
+                    Some(500)
+                ),
+                Err(MarketplaceError::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn set_nft_contract_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let hash = Hash::try_from([1; 32]).unwrap();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.set_nft_contract_hash(NftContractType::Rmrk, hash),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn create_collection_fails_if_royalty_too_high() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.create_collection(
+                    String::from("Collection"),
+                    String::from("COL"),
+                    Some(1001)
+                ),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 1_000,
+                    got: 1_001,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn create_collection_fails_if_contract_hash_not_set() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.create_collection(
+                    String::from("Collection"),
+                    String::from("COL"),
+                    Some(500)
+                ),
+                Err(MarketplaceError::NftContractHashNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn record_earnings_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            // Secondary sale only pays royalty to the creator.
+            marketplace.record_earnings(contract_address(), accounts.bob, 900, accounts.alice, 100);
+            // Primary sale by the creator.
+            marketplace.record_earnings(
+                contract_address(),
+                accounts.alice,
+                900,
+                accounts.alice,
+                100,
+            );
+
+            let expected = Earnings {
+                primary_sales: 900,
+                royalties: 200,
+            };
+            assert_eq!(marketplace.get_creator_earnings(accounts.alice), expected);
+            assert_eq!(
+                marketplace.get_collection_earnings(contract_address()),
+                expected
+            );
+            assert_eq!(
+                marketplace.get_creator_earnings(accounts.bob),
+                Earnings::default()
+            );
+        }
+
+        #[ink::test]
+        fn batch_queries_work() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U64(1)),
+                &Item {
+                    owner: accounts.charlie,
+                    price: 100,
+                },
+            );
+            let tokens = vec![
+                (contract_address(), Id::U64(1)),
+                (contract_address(), Id::U64(2)),
+            ];
+
+            let listings = marketplace.get_listings(tokens.clone());
+            assert_eq!(listings.len(), 2);
+            assert_eq!(
+                listings[0].as_ref().map(|item| item.owner),
+                Some(accounts.charlie)
+            );
+            assert!(listings[1].is_none());
+            assert_eq!(marketplace.get_prices(tokens), vec![Some(100), None]);
+
+            test::set_value_transferred::<Environment>(50);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+            assert_eq!(marketplace.get_offers_active(vec![1, 2]), vec![true, false]);
+        }
+
+        #[ink::test]
+        fn floor_price_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let listings = [
+                (Id::U64(1), 300, Currency::Native),
+                (Id::U64(2), 100, Currency::Native),
+                (Id::U64(3), 50, Currency::Psp22(currency_address())),
+            ];
+            for (token_id, price, currency) in listings {
+                let key = (contract_address(), token_id.clone());
+                marketplace.marketplace.items.insert(
+                    &key,
+                    &Item {
+                        owner: accounts.charlie,
+                        price,
+                    },
+                );
+                if currency == Currency::Native {
+                    marketplace.update_listing_prices(
+                        contract_address(),
+                        &token_id,
+                        None,
+                        Some(price),
+                    );
+                } else {
+                    marketplace
+                        .marketplace
+                        .listing_currencies
+                        .insert(&key, &currency);
+                }
+            }
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(100));
+
+            // price update
+            marketplace.update_listing_prices(
+                contract_address(),
+                &Id::U64(2),
+                Some(100),
+                Some(400),
+            );
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(300));
+
+            marketplace.remove_item(contract_address(), &Id::U64(1));
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(400));
+            assert_eq!(marketplace.get_floor_price(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn price_bucket_keeps_price_order() {
+            let prices = [0, 1, 15, 16, 17, 31, 32, 1000, 1001, NATIVE_UNIT, u128::MAX];
+            for pair in prices.windows(2) {
+                assert!(price_bucket(pair[0]) <= price_bucket(pair[1]));
+            }
+            assert_eq!(price_bucket(15), 15);
+            assert_ne!(price_bucket(16), price_bucket(31));
+            assert!((price_bucket(u128::MAX) as usize) < PRICE_BUCKET_WORDS * 128);
+        }
+
+        #[ink::test]
+        fn floor_price_spans_price_buckets() {
+            let mut marketplace = init_contract();
+            for (id, price) in [(1, NATIVE_UNIT), (2, 5), (3, 7), (4, 1 << 100)] {
+                marketplace.update_listing_prices(
+                    contract_address(),
+                    &Id::U64(id),
+                    None,
+                    Some(price),
+                );
+            }
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(5));
+
+            marketplace.update_listing_prices(contract_address(), &Id::U64(2), Some(5), None);
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(7));
+            marketplace.update_listing_prices(contract_address(), &Id::U64(3), Some(7), None);
+            assert_eq!(
+                marketplace.get_floor_price(contract_address()),
+                Some(NATIVE_UNIT)
+            );
+            marketplace.update_listing_prices(
+                contract_address(),
+                &Id::U64(1),
+                Some(NATIVE_UNIT),
+                None,
+            );
+            assert_eq!(
+                marketplace.get_floor_price(contract_address()),
+                Some(1 << 100)
+            );
+            marketplace.update_listing_prices(
+                contract_address(),
+                &Id::U64(4),
+                Some(1 << 100),
+                None,
+            );
+            assert_eq!(marketplace.get_floor_price(contract_address()), None);
+        }
+
+        #[ink::test]
+        fn account_activity_works() {
+            let mut marketplace = init_contract();
+            test::set_value_transferred::<Environment>(200);
+            assert!(marketplace.deposit().is_ok());
+            let accounts = default_accounts();
+            let item = Item {
+                owner: accounts.bob,
+                price: 100,
+            };
+            marketplace
+                .marketplace
+                .items
+                .insert(&(contract_address(), Id::U64(1)), &item);
+            marketplace.update_listing_indexes(accounts.bob, contract_address(), &Id::U64(1), true);
+
+            assert!(marketplace
+                .make_offer(
+                    contract_address(),
+                    Some(Id::U64(1)),
+                    1,
+                    50,
+                    String::from("")
+                )
+                .is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 60, String::from(""))
+                .is_ok());
+
+            let activity = marketplace.get_account_activity(accounts.alice);
+            assert_eq!(activity.offers_made, vec![1, 2]);
+            assert!(activity.listings.is_empty());
+
+            let activity = marketplace.get_account_activity(accounts.bob);
+            assert_eq!(activity.listings, vec![(contract_address(), Id::U64(1))]);
+            assert_eq!(activity.offers_received, vec![1, 2]);
+
+            assert!(marketplace.cancel_offer(1).is_ok());
+            marketplace.remove_item(contract_address(), &Id::U64(1));
+            assert_eq!(
+                marketplace.get_account_activity(accounts.alice).offers_made,
+                vec![2]
+            );
+            assert!(marketplace
+                .get_account_activity(accounts.bob)
+                .listings
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn listings_by_collection_and_account_work() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            for token_id in 1..=3 {
+                marketplace.marketplace.items.insert(
+                    &(contract_address(), Id::U64(token_id)),
+                    &Item {
+                        owner: accounts.bob,
+                        price: token_id as u128 * 100,
+                    },
+                );
+                marketplace.update_listing_indexes(
+                    accounts.bob,
+                    contract_address(),
+                    &Id::U64(token_id),
+                    true,
+                );
+            }
+            marketplace.remove_item(contract_address(), &Id::U64(1));
+
+            assert_eq!(
+                marketplace
+                    .get_listings_by_collection(contract_address(), 0, 10)
+                    .into_iter()
+                    .map(|(token_id, item)| (token_id, item.price))
+                    .collect::<Vec<_>>(),
+                vec![(Id::U64(3), 300), (Id::U64(2), 200)]
+            );
+            let listings = marketplace.get_listings_by_account(accounts.bob, 1, 10);
+            assert_eq!(listings.len(), 1);
+            assert_eq!(listings[0].0, contract_address());
+            assert_eq!(listings[0].1, Id::U64(2));
+            assert!(marketplace
+                .get_listings_by_account(accounts.alice, 0, 10)
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn listings_by_collection_and_account_return_long_ids() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace
+                .add_supported_currency(currency_address(), currency_metadata())
+                .is_ok());
+            let long_id = Id::Bytes(vec![1; 40]);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), long_id.clone(), accounts.bob);
+            assert!(marketplace
+                .list_with_psp22(contract_address(), long_id.clone(), 100, currency_address())
+                .is_ok());
+
+            assert_eq!(
+                marketplace
+                    .get_listings_by_collection(contract_address(), 0, 10)
+                    .into_iter()
+                    .map(|(token_id, item)| (token_id, item.price))
+                    .collect::<Vec<_>>(),
+                vec![(long_id.clone(), 100)]
+            );
+            let listings = marketplace.get_listings_by_account(accounts.bob, 0, 10);
+            assert_eq!(listings.len(), 1);
+            assert_eq!(listings[0].1, long_id);
+            assert_eq!(
+                marketplace.get_account_activity(accounts.bob).listings,
+                vec![(contract_address(), long_id.clone())]
+            );
+
+            assert!(marketplace
+                .unlist(contract_address(), long_id.clone())
+                .is_ok());
+            assert!(marketplace
+                .marketplace
+                .hashed_token_ids
+                .get(&(contract_address(), token_key(&long_id)))
+                .is_none());
+        }
+
+        #[ink::test]
+        fn remove_item_clears_storage() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let key = (contract_address(), Id::U64(1));
+            let item = Item {
+                owner: accounts.bob,
+                price: 100,
+            };
+            marketplace.marketplace.items.insert(&key, &item);
+            marketplace.update_listing_prices(contract_address(), &Id::U64(1), None, Some(100));
+            marketplace.update_listing_indexes(accounts.bob, contract_address(), &Id::U64(1), true);
+            marketplace
+                .marketplace
+                .listing_children
+                .insert(&key, &vec![(contract_address(), Id::U64(2))]);
+            marketplace.marketplace.physical_listings.insert(
+                &key,
+                &PhysicalTerms {
+                    delivery_period: 100,
+                    partial_refund: 1000,
+                },
+            );
+            marketplace.marketplace.usd_listings.insert(&key, &());
+            marketplace.marketplace.escrowed_listings.insert(&key, &());
+
+            marketplace.remove_item(contract_address(), &Id::U64(1));
+            let data = &marketplace.marketplace;
+            assert!(!data.items.contains(&key));
+            assert!(!data.floor_prices.contains(&contract_address()));
+            assert!(!data.listing_price_bitmaps.contains(&contract_address()));
+            assert!(!data.listing_children.contains(&key));
+            assert!(!data.physical_listings.contains(&key));
+            assert!(!data.usd_listings.contains(&key));
+            assert!(!data.escrowed_listings.contains(&key));
+            assert_eq!(data.account_listing_index.count(&accounts.bob), 0);
+            assert_eq!(data.collection_listing_index.count(&contract_address()), 0);
+        }
+
+        #[ink::test]
+        fn check_listing_valid_fails_for_unlisted_token() {
+            let marketplace = init_contract();
+            assert_eq!(
+                marketplace.check_listing_valid(contract_address(), Id::U64(1)),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn marketplace_stats_works() {
+            let mut marketplace = init_contract();
+            test::set_value_transferred::<Environment>(200);
+            assert!(marketplace.deposit().is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 50, String::from(""))
+                .is_ok());
+            assert!(marketplace
+                .make_offer(contract_address(), None, 1, 60, String::from(""))
+                .is_ok());
+            assert!(marketplace.cancel_offer(1).is_ok());
+
+            marketplace.record_sale_stats(1000, 10, Currency::Native);
+            marketplace.record_sale_stats(500, 5, Currency::Psp22(currency_address()));
+
+            assert_eq!(
+                marketplace.get_marketplace_stats(),
+                MarketplaceStats {
+                    total_volume: 1000,
+                    total_sales: 2,
+                    active_listings: 0,
+                    active_offers: 1,
+                    total_fees: 10,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn repeated_sales_are_flagged_as_wash_trades() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let token_id = Id::U64(1);
+            assert!(marketplace.set_wash_trade_window(1000).is_ok());
+            assert!(marketplace.set_exclude_wash_trades(true).is_ok());
+
+            test::set_block_timestamp::<Environment>(1000);
+            assert!(!marketplace.track_sale(
+                1,
+                contract_address(),
+                &token_id,
+                accounts.bob,
+                accounts.charlie,
+                900,
+                accounts.alice,
+                100,
+                Currency::Native
+            ));
+            test::set_block_timestamp::<Environment>(1500);
+            assert!(marketplace.track_sale(
+                2,
+                contract_address(),
+                &token_id,
+                accounts.charlie,
+                accounts.bob,
+                900,
+                accounts.alice,
+                100,
+                Currency::Native
+            ));
+            assert_eq!(
+                marketplace
+                    .get_wash_trade(1)
+                    .map(|trade| (trade.sale_id, trade.excluded)),
+                Some((2, true))
+            );
+
+            // sale after the window is not flagged
+            test::set_block_timestamp::<Environment>(2500);
+            assert!(!marketplace.track_sale(
+                3,
+                contract_address(),
+                &token_id,
+                accounts.bob,
+                accounts.charlie,
+                900,
+                accounts.alice,
+                100,
+                Currency::Native
+            ));
+            assert_eq!(marketplace.get_wash_trade(2), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.clear_wash_trade(1),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.clear_wash_trade(1).is_ok());
+            assert_eq!(marketplace.get_wash_trade(1), None);
+            assert_eq!(
+                marketplace.get_creator_earnings(accounts.alice).royalties,
+                100
+            );
+            assert_eq!(
+                marketplace.clear_wash_trade(1),
+                Err(MarketplaceError::WashTradeNotFound)
+            );
+
+            // sales in other currencies are not counted in earnings
+            assert!(marketplace.track_sale(
+                4,
+                contract_address(),
+                &token_id,
+                accounts.charlie,
+                accounts.bob,
+                900,
+                accounts.alice,
+                100,
+                Currency::Psp22(currency_address())
+            ));
+            assert_eq!(
+                marketplace.get_wash_trade(2).map(|trade| trade.excluded),
+                Some(false)
+            );
+            assert!(marketplace.clear_wash_trade(2).is_ok());
+            assert_eq!(
+                marketplace.get_creator_earnings(accounts.alice).royalties,
+                100
+            );
+        }
+
+        #[ink::test]
+        fn set_escrow_period_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.set_escrow_period(86_400_000).is_ok());
+            assert_eq!(marketplace.get_escrow_period(), 86_400_000);
+        }
+
+        #[ink::test]
+        fn set_escrow_period_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.set_escrow_period(86_400_000),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn dispute_fails_if_escrow_not_found() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.dispute(1),
+                Err(MarketplaceError::EscrowNotFound)
+            );
+            assert_eq!(
+                marketplace.release_escrow(1),
+                Err(MarketplaceError::EscrowNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn resolve_dispute_fails_if_not_arbiter() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.resolve_dispute(1, true),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            assert!(marketplace.grant_role(ARBITER, accounts.alice).is_ok());
+            assert_eq!(
+                marketplace.resolve_dispute(1, true),
+                Err(MarketplaceError::EscrowNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn set_rental_dispute_window_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.set_rental_dispute_window(1000).is_ok());
+            assert_eq!(marketplace.get_rental_dispute_window(), 1000);
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_rental_dispute_window(0),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn open_dispute_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+
+            set_sender(accounts.bob);
+            assert_eq!(marketplace.open_dispute(DisputeSubject::Escrow(1)), Ok(1));
+            assert_eq!(
+                marketplace.get_open_dispute(DisputeSubject::Escrow(1)),
+                Some(1)
+            );
+            let dispute = marketplace.get_dispute(1).unwrap();
+            assert_eq!(dispute.claimant, accounts.bob);
+            assert_eq!(dispute.respondent, accounts.charlie);
+            assert_eq!(dispute.ruling, None);
+            assert!(marketplace.get_escrow(1).unwrap().disputed);
+            assert_eq!(
+                marketplace.check_not_disputed(contract_address(), &Id::U128(1)),
+                Err(MarketplaceError::TokenDisputed)
+            );
+            assert_eq!(
+                marketplace.release_escrow(1),
+                Err(MarketplaceError::EscrowDisputed)
+            );
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.open_dispute(DisputeSubject::Escrow(1)),
+                Err(MarketplaceError::EscrowDisputed)
+            );
+        }
+
+        #[ink::test]
+        fn open_dispute_fails_if_not_party() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.open_dispute(DisputeSubject::Escrow(1)),
+                Err(MarketplaceError::NotDisputeParty)
+            );
+            assert_eq!(
+                marketplace.open_dispute(DisputeSubject::Rental(1)),
+                Err(MarketplaceError::RentalNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn open_dispute_fails_if_window_closed() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+
+            test::set_block_timestamp::<Environment>(1000);
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.open_dispute(DisputeSubject::Escrow(1)),
+                Err(MarketplaceError::DisputeWindowClosed)
+            );
+        }
+
+        #[ink::test]
+        fn rule_on_dispute_fails_if_not_arbiter() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.rule_on_dispute(1, true, String::from("")),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.grant_role(ARBITER, accounts.alice).is_ok());
+            assert_eq!(
+                marketplace.rule_on_dispute(1, true, String::from("")),
+                Err(MarketplaceError::DisputeNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn list_physical_item_fails_with_invalid_terms() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.list_physical_item(contract_address(), Id::U128(1), 100, 0, 0),
+                Err(MarketplaceError::InvalidDeliveryPeriod)
+            );
+            assert_eq!(
+                marketplace.list_physical_item(contract_address(), Id::U128(1), 100, 1000, 10_001),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001
+                })
+            );
+            assert_eq!(
+                marketplace.get_physical_terms(contract_address(), Id::U128(1)),
+                None
+            );
+        }
+
+        #[ink::test]
+        fn confirm_delivery_fails_if_not_physical_sale() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.confirm_delivery(1),
+                Err(MarketplaceError::NotPhysicalSale)
+            );
+            assert_eq!(
+                marketplace.confirm_delivery(2),
+                Err(MarketplaceError::EscrowNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn confirm_delivery_fails_if_not_buyer() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+            marketplace.marketplace.physical_escrows.insert(
+                &1,
+                &PhysicalTerms {
+                    delivery_period: 1000,
+                    partial_refund: 5000,
+                },
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.confirm_delivery(1),
+                Err(MarketplaceError::NotBuyer)
+            );
+            assert_eq!(
+                marketplace.get_escrow_physical_terms(1),
+                Some(PhysicalTerms {
+                    delivery_period: 1000,
+                    partial_refund: 5000,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn rule_on_delivery_fails_if_not_physical_sale() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .escrows
+                .insert(&1, &escrow_item(accounts.bob, accounts.charlie));
+            assert!(marketplace.grant_role(ARBITER, accounts.alice).is_ok());
+            set_sender(accounts.charlie);
+            assert_eq!(marketplace.open_dispute(DisputeSubject::Escrow(1)), Ok(1));
+
+            set_sender(accounts.alice);
+            assert_eq!(
+                marketplace.rule_on_delivery(1, DeliveryOutcome::NotDelivered, String::from("")),
+                Err(MarketplaceError::NotPhysicalSale)
+            );
+            assert_eq!(marketplace.get_dispute(1).unwrap().ruling, None);
+        }
+
+        #[ink::test]
+        fn register_partner_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.register_partner(accounts.bob, 2000, Some(500), String::from("Bob")),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.get_partner(1),
+                Some(Partner {
+                    account: accounts.bob,
+                    fee_share: 2000,
+                    fee_override: Some(500),
+                    metadata: String::from("Bob"),
+                    active: true,
+                })
+            );
+            assert!(marketplace
+                .update_partner(1, 3000, None, String::from("Bob's"))
+                .is_ok());
+            assert!(marketplace.set_partner_active(1, false).is_ok());
+            let partner = marketplace.get_partner(1).unwrap();
+            assert_eq!(partner.fee_share, 3000);
+            assert_eq!(partner.fee_override, None);
+            assert!(!partner.active);
+        }
+
+        #[ink::test]
+        fn register_partner_fails_with_invalid_fees() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.register_partner(accounts.bob, 10_001, None, String::from("")),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001
+                })
+            );
+            assert_eq!(
+                marketplace.register_partner(accounts.bob, 0, Some(1001), String::from("")),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 1000,
+                    got: 1001
+                })
+            );
+            assert_eq!(
+                marketplace.update_partner(1, 0, None, String::from("")),
+                Err(MarketplaceError::PartnerNotFound)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.register_partner(accounts.bob, 0, None, String::from("")),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn buy_via_partner_fails_if_partner_inactive() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.buy_via_partner(contract_address(), Id::U128(1), 1),
+                Err(MarketplaceError::PartnerNotFound)
+            );
+            assert!(marketplace
+                .register_partner(accounts.bob, 2000, None, String::from(""))
+                .is_ok());
+            assert!(marketplace.set_partner_active(1, false).is_ok());
+            assert_eq!(
+                marketplace.buy_with_deposit_via_partner(contract_address(), Id::U128(1), 1),
+                Err(MarketplaceError::PartnerInactive)
+            );
+        }
+
+        #[ink::test]
+        fn buy_via_partner_pays_partner_when_escrow_is_released() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace
+                .register_partner(accounts.django, 2000, None, String::from(""))
+                .is_ok());
+
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    true
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert!(marketplace
+                .buy_via_partner(contract_address(), Id::U64(1), 1)
+                .is_ok());
+            assert_eq!(marketplace.get_partner_earnings(1, Currency::Native), 0);
+
+            test::set_value_transferred::<Environment>(0);
+            assert!(marketplace.release_escrow(1).is_ok());
+            // partner earns 20% of the 1% marketplace fee
+            assert_eq!(marketplace.get_partner_earnings(1, Currency::Native), 2);
+        }
+
+        #[ink::test]
+        fn accrue_partner_fee_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .register_partner(accounts.bob, 2000, None, String::from(""))
+                .is_ok());
+            let partner = marketplace.get_partner(1).unwrap();
+
+            assert_eq!(
+                marketplace.accrue_partner_fee(1, &partner, 1000, Currency::Native),
+                200
+            );
+            assert_eq!(marketplace.get_partner_earnings(1, Currency::Native), 200);
+            assert_eq!(marketplace.get_balance_sheet().locked, 200);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.claim_partner_earnings(1, Currency::Native),
+                Err(MarketplaceError::NotPartner)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.claim_partner_earnings(1, Currency::Psp22(currency_address())),
+                Err(MarketplaceError::NoPartnerEarnings)
+            );
+        }
+
+        #[ink::test]
+        fn set_staking_reward_share_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.set_staking_reward_share(2500).is_ok());
+            assert_eq!(marketplace.get_staking_reward_share(), 2500);
+            assert_eq!(
+                marketplace.set_staking_reward_share(10_001),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn stake_fails_if_not_configured() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.stake(100),
+                Err(MarketplaceError::StakingNotConfigured)
+            );
+            assert_eq!(
+                marketplace.unstake(100),
+                Err(MarketplaceError::StakingNotConfigured)
+            );
+        }
+
+        #[ink::test]
+        fn set_staking_token_fails_with_stakes_outstanding() {
+            let mut marketplace = init_contract();
+            assert!(marketplace.set_staking_token(currency_address()).is_ok());
+            marketplace.marketplace.v1.pending_staked = 100;
+
+            assert_eq!(
+                marketplace.set_staking_token(contract_address()),
+                Err(MarketplaceError::StakesOutstanding)
+            );
+            assert_eq!(marketplace.get_staking_token(), Some(currency_address()));
+        }
+
+        #[ink::test]
+        fn staking_rewards_are_distributed_per_epoch() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_staking_token(currency_address()).is_ok());
+            assert!(marketplace.set_staking_reward_share(5000).is_ok());
+            marketplace.marketplace.stakes.insert(
+                &accounts.alice,
+                &Stake {
+                    pending: 1000,
+                    pending_epoch: 1,
+                    ..Default::default()
+                },
+            );
+            marketplace.marketplace.v1.pending_staked = 1000;
+
+            // stake becomes active in the next epoch
+            test::set_block_timestamp::<Environment>(EPOCH_LENGTH);
+            marketplace.advance_epoch();
+            assert_eq!(marketplace.get_staking_epoch(), (1, EPOCH_LENGTH));
+            assert_eq!(marketplace.get_total_staked(), 1000);
+
+            assert_eq!(marketplace.accrue_staking_fee(1000, Currency::Native), 500);
+            assert_eq!(
+                marketplace.accrue_staking_fee(1000, Currency::Psp22(currency_address())),
+                0
+            );
+            assert_eq!(marketplace.get_balance_sheet().locked, 500);
+            assert_eq!(marketplace.update_stake(accounts.alice).rewards, 0);
+
+            test::set_block_timestamp::<Environment>(2 * EPOCH_LENGTH);
+            marketplace.advance_epoch();
+            let stake = marketplace.update_stake(accounts.alice);
+            assert_eq!(stake.amount, 1000);
+            assert_eq!(stake.rewards, 500);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.claim_rewards(),
+                Err(MarketplaceError::NoStakingRewards)
+            );
+        }
+
+        #[ink::test]
+        fn set_curated_mode_works() {
+            let mut marketplace = init_contract();
+
+            assert!(!marketplace.is_curated_mode());
+            assert!(marketplace.set_curated_mode(true).is_ok());
+            assert!(marketplace.is_curated_mode());
+        }
+
+        #[ink::test]
+        fn set_governance_parameters_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace
+                .set_governance_parameters(500, 5000, 100)
+                .is_ok());
+            assert_eq!(marketplace.get_governance_parameters(), (500, 5000, 100));
+            assert_eq!(
+                marketplace.set_governance_parameters(500, 10_000, 100),
+                Err(MarketplaceError::InvalidGovernanceParameters)
+            );
+        }
+
+        #[ink::test]
+        fn propose_fails_if_not_staker() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.propose(ParameterChange::CuratedMode(true)),
+                Err(MarketplaceError::GovernanceNotConfigured)
+            );
+            assert!(marketplace
+                .set_governance_parameters(500, 5000, 100)
+                .is_ok());
+            assert_eq!(
+                marketplace.propose(ParameterChange::CuratedMode(true)),
+                Err(MarketplaceError::NotStaker)
+            );
+        }
+
+        #[ink::test]
+        fn passed_proposal_applies_change() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_staking_token(currency_address()).is_ok());
+            assert!(marketplace
+                .set_governance_parameters(500, 5000, 100)
+                .is_ok());
+            marketplace.marketplace.stakes.insert(
+                &accounts.alice,
+                &Stake {
+                    amount: 1000,
+                    ..Default::default()
+                },
+            );
+            marketplace.marketplace.v1.total_staked = 1000;
+
+            assert_eq!(
+                marketplace.propose(ParameterChange::MarketplaceFee(200)),
+                Ok(1)
+            );
+            assert!(marketplace.vote(1, true).is_ok());
+            assert!(marketplace.has_voted(1, accounts.alice));
+            assert_eq!(
+                marketplace.vote(1, true),
+                Err(MarketplaceError::AlreadyVoted)
+            );
+            assert_eq!(
+                marketplace.unstake(1000),
+                Err(MarketplaceError::StakeLocked)
+            );
+            assert_eq!(
+                marketplace.finalize_proposal(1),
+                Err(MarketplaceError::VotingNotEnded)
+            );
+
+            test::set_block_timestamp::<Environment>(100);
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.vote(1, false),
+                Err(MarketplaceError::VotingClosed)
+            );
+            assert!(marketplace.finalize_proposal(1).is_ok());
+            let proposal = marketplace.get_proposal(1).unwrap();
+            assert_eq!(proposal.votes_for, 1000);
+            assert_eq!(proposal.status, ProposalStatus::Passed);
+            assert_eq!(marketplace.get_marketplace_fee(), 200);
+            assert_eq!(
+                marketplace.finalize_proposal(1),
+                Err(MarketplaceError::ProposalNotActive)
+            );
+        }
+
+        #[ink::test]
+        fn passed_proposal_with_invalid_change_is_finalized() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_staking_token(currency_address()).is_ok());
+            assert!(marketplace
+                .set_governance_parameters(500, 5000, 100)
+                .is_ok());
+            marketplace.marketplace.stakes.insert(
+                &accounts.alice,
+                &Stake {
+                    amount: 1000,
+                    ..Default::default()
+                },
+            );
+            marketplace.marketplace.v1.total_staked = 1000;
+
+            assert_eq!(
+                marketplace.propose(ParameterChange::MarketplaceFee(2000)),
+                Ok(1)
+            );
+            assert!(marketplace.vote(1, true).is_ok());
+            test::set_block_timestamp::<Environment>(100);
+            assert!(marketplace.finalize_proposal(1).is_ok());
+            assert_eq!(
+                marketplace.get_proposal(1).unwrap().status,
+                ProposalStatus::Invalid
+            );
+            assert_eq!(marketplace.get_marketplace_fee(), 100);
+        }
+
+        #[ink::test]
+        fn register_checks_mocked_collection_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            mock::set_collection_owner(contract_address(), accounts.bob);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.register(contract_address(), None, None, NftContractType::Psp34),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn list_item_checks_mocked_token_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    100,
+                    Currency::Native,
+                    false,
+                    false
+                ),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.list_item(
+                    contract_address(),
+                    Id::U64(2),
+                    100,
+                    Currency::Native,
+                    false,
+                    false
+                ),
+                Err(MarketplaceError::TokenDoesNotExist)
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    100,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U64(1)),
+                Some(100)
+            );
+        }
+
+        #[ink::test]
+        fn buy_item_transfers_mocked_token() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            for token_id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(token_id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+            // only the first token is approved for the marketplace to move
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert_eq!(
+                marketplace.buy_item(contract_address(), Id::U64(2), SaleAttribution::default()),
+                Err(MarketplaceError::PSP34Error(PSP34Error::NotApproved))
+            );
+
+            let seller_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(marketplace.get_price(contract_address(), Id::U64(1)), None);
+            // seller is paid the price less the 1% marketplace fee
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                seller_balance + 990
+            );
+        }
+
+        #[ink::test]
+        fn sell_into_order_settles_from_order_value() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(3000);
+            assert!(marketplace.deposit().is_ok());
+            test::set_value_transferred::<Environment>(0);
+            assert_eq!(
+                marketplace.place_limit_order(contract_address(), 3, 1000),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            assert_eq!(
+                marketplace.place_limit_order(contract_address(), 2, 1000),
+                Ok(1)
+            );
+            assert_eq!(marketplace.get_deposit(accounts.charlie), 1000);
+            assert_eq!(marketplace.get_limit_orders(contract_address()), vec![1]);
+
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 3000);
+            set_sender(accounts.bob);
+            let seller_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace.sell_into_order(1, Id::U64(1)).is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.charlie)
+            );
+            // seller is paid the order price less the 1% marketplace fee
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                seller_balance + 990
+            );
+            assert_eq!(
+                marketplace.get_limit_order(1).map(|order| order.quantity),
+                Some(1)
+            );
+            assert_eq!(
+                marketplace.cancel_limit_order(1),
+                Err(MarketplaceError::NotOwner)
+            );
+
+            set_sender(accounts.charlie);
+            assert!(marketplace.cancel_limit_order(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.charlie), 2000);
+            assert_eq!(marketplace.get_limit_order(1), None);
+            assert!(marketplace.get_limit_orders(contract_address()).is_empty());
+        }
+
+        #[ink::test]
+        fn sweep_floor_buys_cheapest_listings_within_budget() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            for token_id in 1..=3 {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(token_id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(token_id),
+                        100 * token_id as u128,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.sweep_floor(contract_address(), DEFAULT_MAX_BATCH_SIZE + 1, 1000),
+                Err(MarketplaceError::BatchTooLarge)
+            );
+            test::set_value_transferred::<Environment>(400);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            let buyer_balance = test::get_account_balance::<Environment>(accounts.charlie).unwrap();
+            assert_eq!(marketplace.sweep_floor(contract_address(), 3, 350), Ok(2));
+            for token_id in 1..=2 {
+                assert_eq!(
+                    mock::owner_of(contract_address(), &Id::U64(token_id)),
+                    Some(accounts.charlie)
+                );
+            }
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(300));
+            // unspent value is refunded
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.charlie).unwrap(),
+                buyer_balance + 100
+            );
+        }
+
+        #[ink::test]
+        fn sweep_floor_buys_cheapest_listing_within_price_bucket() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            for (token_id, price) in [(1, 103), (2, 100), (3, 2000)] {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(token_id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(token_id),
+                        price,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+            assert_eq!(price_bucket(100), price_bucket(103));
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            assert_eq!(marketplace.sweep_floor(contract_address(), 1, 1000), Ok(1));
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(2)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(marketplace.get_floor_price(contract_address()), Some(103));
+        }
+
+        #[ink::test]
+        fn sweep_floor_counts_skipped_listings_against_batch_size() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            for token_id in 1..=3 {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(token_id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(token_id),
+                        100 * token_id as u128,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+            // the cheapest listings went stale
+            for token_id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.django);
+            }
+
+            set_sender(accounts.alice);
+            assert!(marketplace.set_max_batch_size(2).is_ok());
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(300);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            assert_eq!(marketplace.sweep_floor(contract_address(), 1, 300), Ok(0));
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(3)),
+                Some(accounts.bob)
+            );
+
+            set_sender(accounts.alice);
+            assert!(marketplace.set_max_batch_size(3).is_ok());
+            set_sender(accounts.charlie);
+            assert_eq!(marketplace.sweep_floor(contract_address(), 1, 300), Ok(1));
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(3)),
+                Some(accounts.charlie)
+            );
+        }
+
+        #[ink::test]
+        fn register_referral_code_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            for code in ["", "bob-code", "a1234567890123456"] {
+                assert_eq!(
+                    marketplace.register_referral_code(String::from(code)),
+                    Err(MarketplaceError::InvalidReferralCode)
+                );
+            }
+            assert!(marketplace
+                .register_referral_code(String::from("bob1"))
+                .is_ok());
+            assert!(marketplace
+                .register_referral_code(String::from("bob2"))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_referral_code(accounts.bob),
+                Some(String::from("bob2"))
+            );
+            // previous code is released
+            assert_eq!(
+                marketplace.get_referral_code_owner(String::from("bob1")),
+                None
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.register_referral_code(String::from("bob2")),
+                Err(MarketplaceError::ReferralCodeTaken)
+            );
+            assert!(marketplace
+                .register_referral_code(String::from("bob1"))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_referral_code_owner(String::from("bob1")),
+                Some(accounts.charlie)
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_referral_pays_referrer_fee_share() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_referral_fee_share(5000).is_ok());
+            set_sender(accounts.django);
+            assert!(marketplace
+                .register_referral_code(String::from("django"))
+                .is_ok());
+
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert_eq!(
+                marketplace.buy_with_referral(contract_address(), Id::U64(1), String::from("eve")),
+                Err(MarketplaceError::ReferralCodeNotFound)
+            );
+            assert!(marketplace
+                .buy_with_referral(contract_address(), Id::U64(1), String::from("django"))
+                .is_ok());
+            // referrer earns half of the 1% marketplace fee
+            assert_eq!(
+                marketplace.get_referral_earnings(accounts.django, Currency::Native),
+                5
+            );
+
+            set_sender(accounts.django);
+            let referrer_balance =
+                test::get_account_balance::<Environment>(accounts.django).unwrap();
+            assert!(marketplace
+                .claim_referral_earnings(Currency::Native)
+                .is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.django).unwrap(),
+                referrer_balance + 5
+            );
+            assert_eq!(
+                marketplace.claim_referral_earnings(Currency::Native),
+                Err(MarketplaceError::NoReferralEarnings)
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_referral_pays_referrer_when_escrow_is_released() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_referral_fee_share(5000).is_ok());
+            set_sender(accounts.django);
+            assert!(marketplace
+                .register_referral_code(String::from("django"))
+                .is_ok());
+
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    true
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert!(marketplace
+                .buy_with_referral(contract_address(), Id::U64(1), String::from("django"))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_referral_earnings(accounts.django, Currency::Native),
+                0
+            );
+
+            test::set_value_transferred::<Environment>(0);
+            assert!(marketplace.release_escrow(1).is_ok());
+            assert_eq!(
+                marketplace.get_referral_earnings(accounts.django, Currency::Native),
+                5
+            );
+        }
+
+        #[ink::test]
+        fn feature_listing_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.feature_listing(contract_address(), Id::U64(1)),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+
+            set_sender(accounts.alice);
+            assert!(marketplace.grant_role(CURATOR, accounts.charlie).is_ok());
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.feature_listing(contract_address(), Id::U64(2)),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+            assert!(marketplace
+                .feature_listing(contract_address(), Id::U64(1))
+                .is_ok());
+            assert_eq!(
+                marketplace.feature_listing(contract_address(), Id::U64(1)),
+                Err(MarketplaceError::ListingAlreadyFeatured)
+            );
+            assert_eq!(
+                marketplace.get_listing_curator(contract_address(), Id::U64(1)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(
+                marketplace.get_featured_listings(contract_address(), 0, 10),
+                vec![Id::U64(1)]
+            );
+
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.unfeature_listing(contract_address(), Id::U64(1)),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            set_sender(accounts.charlie);
+            assert!(marketplace
+                .unfeature_listing(contract_address(), Id::U64(1))
+                .is_ok());
+            assert_eq!(
+                marketplace.unfeature_listing(contract_address(), Id::U64(1)),
+                Err(MarketplaceError::ListingNotFeatured)
+            );
+            assert!(marketplace
+                .get_featured_listings(contract_address(), 0, 10)
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn buy_featured_listing_pays_curator_fee_share() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_curator_fee_share(5000).is_ok());
+            assert!(marketplace.grant_role(CURATOR, accounts.django).is_ok());
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+            set_sender(accounts.django);
+            assert!(marketplace
+                .feature_listing(contract_address(), Id::U64(1))
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            // curator earns half of the 1% marketplace fee and the sold listing is unfeatured
+            assert_eq!(
+                marketplace.get_curator_earnings(accounts.django, Currency::Native),
+                5
+            );
+            assert_eq!(
+                marketplace.get_listing_curator(contract_address(), Id::U64(1)),
+                None
+            );
+
+            set_sender(accounts.django);
+            let curator_balance =
+                test::get_account_balance::<Environment>(accounts.django).unwrap();
+            assert!(marketplace.claim_curator_earnings(Currency::Native).is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.django).unwrap(),
+                curator_balance + 5
+            );
+            assert_eq!(
+                marketplace.claim_curator_earnings(Currency::Native),
+                Err(MarketplaceError::NoCuratorEarnings)
+            );
+        }
+
+        #[ink::test]
+        fn auction_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let contract = test::callee::<Environment>();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            assert_eq!(
+                marketplace.create_auction(contract_address(), Id::U64(1), 100, 10, 1000),
+                Err(MarketplaceError::TokenNotApproved)
+            );
+            mock::approve(contract_address(), accounts.bob, contract, Some(Id::U64(1)));
+            assert_eq!(
+                marketplace.create_auction(contract_address(), Id::U64(1), 100, 1000, 10),
+                Err(MarketplaceError::InvalidAuction)
+            );
+            assert_eq!(
+                marketplace.create_auction(contract_address(), Id::U64(1), 100, 10, 1000),
+                Ok(1)
+            );
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(contract)
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.place_bid(1),
+                Err(MarketplaceError::AuctionNotStarted)
+            );
+            test::set_block_timestamp::<Environment>(10);
+            test::set_value_transferred::<Environment>(99);
+            assert_eq!(marketplace.place_bid(1), Err(MarketplaceError::BidTooLow));
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.place_bid(1).is_ok());
+
+            set_sender(accounts.django);
+            assert_eq!(marketplace.place_bid(1), Err(MarketplaceError::BidTooLow));
+            test::set_value_transferred::<Environment>(1000);
+            assert!(marketplace.place_bid(1).is_ok());
+            // outbid bid is returned to the deposit of its bidder
+            assert_eq!(marketplace.get_deposit(accounts.charlie), 100);
+            assert_eq!(marketplace.get_balance_sheet().locked, 1000);
+            assert_eq!(
+                marketplace.get_auction(1).unwrap().highest_bid,
+                Some((accounts.django, 1000))
+            );
+
+            set_sender(accounts.bob);
+            test::set_value_transferred::<Environment>(0);
+            assert_eq!(
+                marketplace.cancel_auction(1),
+                Err(MarketplaceError::AuctionHasBids)
+            );
+            assert_eq!(
+                marketplace.settle_auction(1),
+                Err(MarketplaceError::AuctionNotEnded)
+            );
+
+            test::set_block_timestamp::<Environment>(1001);
+            set_sender(accounts.django);
+            test::set_value_transferred::<Environment>(2000);
+            assert_eq!(
+                marketplace.place_bid(1),
+                Err(MarketplaceError::AuctionEnded)
+            );
+            test::set_value_transferred::<Environment>(0);
+            test::set_account_balance::<Environment>(contract, 1100);
+            let seller_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace.settle_auction(1).is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.django)
+            );
+            // seller is paid the highest bid less the 1% marketplace fee
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                seller_balance + 990
+            );
+            assert_eq!(marketplace.get_balance_sheet().locked, 0);
+            assert_eq!(marketplace.get_auction(1), None);
+            assert_eq!(
+                marketplace.settle_auction(1),
+                Err(MarketplaceError::AuctionNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn auction_without_bids_returns_nft() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let contract = test::callee::<Environment>();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            for token_id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    contract,
+                    Some(Id::U64(token_id)),
+                );
+                assert_eq!(
+                    marketplace.create_auction(contract_address(), Id::U64(token_id), 100, 0, 1000),
+                    Ok(token_id as u128)
+                );
+            }
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.cancel_auction(1),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert!(marketplace.cancel_auction(1).is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(1)),
+                Some(accounts.bob)
+            );
+
+            test::set_block_timestamp::<Environment>(1001);
+            set_sender(accounts.charlie);
+            assert!(marketplace.settle_auction(2).is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(2)),
+                Some(accounts.bob)
+            );
+        }
+
+        #[ink::test]
+        fn featured_auction_pays_curator_fee_share() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let contract = test::callee::<Environment>();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_curator_fee_share(5000).is_ok());
+            assert!(marketplace.grant_role(CURATOR, accounts.django).is_ok());
+            set_sender(accounts.bob);
+            for (token_id, start_time) in [(1, 500), (2, 0)] {
+                mock::set_owner(contract_address(), Id::U64(token_id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    contract,
+                    Some(Id::U64(token_id)),
+                );
+                assert!(marketplace
+                    .create_auction(contract_address(), Id::U64(token_id), 100, start_time, 1000)
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.feature_auction(1),
+                Err(MarketplaceError::AccessControlError(
+                    access_control::AccessControlError::MissingRole
+                ))
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.feature_auction(3),
+                Err(MarketplaceError::AuctionNotFound)
+            );
+            assert!(marketplace.feature_auction(1).is_ok());
+            assert_eq!(
+                marketplace.feature_auction(1),
+                Err(MarketplaceError::AuctionAlreadyFeatured)
+            );
+            assert_eq!(
+                marketplace.set_auction_calendar(vec![1, 2]),
+                Err(MarketplaceError::AuctionNotFeatured)
+            );
+            assert!(marketplace.feature_auction(2).is_ok());
+            assert_eq!(marketplace.get_auction_curator(2), Some(accounts.django));
+            assert!(marketplace.set_auction_calendar(vec![1, 2]).is_ok());
+            // calendar is ordered by start time
+            assert_eq!(
+                marketplace
+                    .get_auction_calendar(accounts.django)
+                    .into_iter()
+                    .map(|(auction_id, _)| auction_id)
+                    .collect::<Vec<_>>(),
+                vec![2, 1]
+            );
+            assert!(marketplace.unfeature_auction(1).is_ok());
+            assert_eq!(
+                marketplace.unfeature_auction(1),
+                Err(MarketplaceError::AuctionNotFeatured)
+            );
+            assert_eq!(marketplace.get_auction_calendar(accounts.django).len(), 1);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            assert!(marketplace.place_bid(2).is_ok());
+            test::set_value_transferred::<Environment>(0);
+            test::set_block_timestamp::<Environment>(1001);
+            test::set_account_balance::<Environment>(contract, 1000);
+            assert!(marketplace.settle_auction(2).is_ok());
+            // curator earns half of the 1% marketplace fee and the settled auction leaves the
+            // calendar
+            assert_eq!(
+                marketplace.get_curator_earnings(accounts.django, Currency::Native),
+                5
+            );
+            assert_eq!(marketplace.get_auction_curator(2), None);
+            assert!(marketplace.get_auction_calendar(accounts.django).is_empty());
+        }
+
+        #[ink::test]
+        fn claim_all_releases_escrows_and_refunds_contributions() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let data = &mut marketplace.marketplace;
+            for (escrow_id, disputed) in [(1, false), (2, true)] {
+                data.escrows.insert(
+                    &escrow_id,
+                    &EscrowItem {
+                        contract_address: contract_address(),
+                        token_id: Id::U128(escrow_id),
+                        seller: accounts.django,
+                        buyer: accounts.bob,
+                        price: 100,
+                        currency: Currency::Native,
+                        release_time: 1000,
+                        disputed,
+                        attribution: SaleAttribution::default(),
+                    },
+                );
+            }
+            data.v1.last_escrow_id = 2;
+            for (group_id, completed) in [(1, false), (2, true)] {
+                data.group_purchases.insert(
+                    &group_id,
+                    &GroupPurchase {
+                        contract_address: contract_address(),
+                        token_id: Id::U128(group_id),
+                        target: 100,
+                        raised: 30,
+                        expires_at: 500,
+                        completed,
+                    },
+                );
+                data.group_contributions
+                    .insert(&(group_id, accounts.django), &30);
+            }
+            data.v1.last_group_purchase_id = 2;
+
+            // dispute window of the escrow is still open
+            assert_eq!(
+                marketplace.get_claimable(accounts.django),
+                ClaimableBalances {
+                    group_refunds: vec![(1, 30)],
+                    ..Default::default()
+                }
+            );
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(
+                marketplace.get_claimable(accounts.django),
+                ClaimableBalances {
+                    escrow_proceeds: vec![(1, Currency::Native, 100)],
+                    group_refunds: vec![(1, 30)],
+                    ..Default::default()
+                }
+            );
+            assert_eq!(
+                marketplace.get_claimable(accounts.bob),
+                ClaimableBalances::default()
+            );
+
+            // NFT transfers are not mocked off-chain
+            marketplace.marketplace.escrows.remove(&1);
+            set_sender(accounts.django);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            let balance = test::get_account_balance::<Environment>(accounts.django).unwrap();
+            assert!(marketplace.claim_all().is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.django).unwrap(),
+                balance + 30
+            );
+            assert_eq!(marketplace.get_group_purchase(1).unwrap().raised, 0);
+            assert_eq!(
+                marketplace.claim_all(),
+                Err(MarketplaceError::NothingToClaim)
+            );
+        }
+
+        #[ink::test]
+        fn claim_all_pays_partner_referral_and_staking_earnings() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let psp22 = Currency::Psp22(currency_address());
+            assert!(marketplace
+                .register_partner(accounts.django, 1000, None, String::from(""))
+                .is_ok());
+            assert!(marketplace
+                .add_supported_currency(currency_address(), currency_metadata())
+                .is_ok());
+            let data = &mut marketplace.marketplace;
+            data.partner_earnings.insert(&(1, Currency::Native), &10);
+            data.referral_earnings
+                .insert(&(accounts.django, Currency::Native), &5);
+            data.referral_earnings.insert(&(accounts.django, psp22), &3);
+            data.curator_earnings
+                .insert(&(accounts.django, Currency::Native), &4);
+            data.stakes.insert(
+                &accounts.django,
+                &Stake {
+                    rewards: 7,
+                    ..Default::default()
+                },
+            );
+
+            assert_eq!(
+                marketplace.get_claimable(accounts.django),
+                ClaimableBalances {
+                    partner_earnings: vec![(1, Currency::Native, 10)],
+                    referral_earnings: vec![(Currency::Native, 5), (psp22, 3)],
+                    curator_earnings: vec![(Currency::Native, 4)],
+                    staking_rewards: 7,
+                    ..Default::default()
+                }
+            );
+            assert_eq!(
+                marketplace.get_claimable(accounts.bob),
+                ClaimableBalances::default()
+            );
+
+            // PSP22 transfers are not mocked off-chain
+            marketplace
+                .marketplace
+                .referral_earnings
+                .remove(&(accounts.django, psp22));
+            set_sender(accounts.django);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            let balance = test::get_account_balance::<Environment>(accounts.django).unwrap();
+            assert!(marketplace.claim_all().is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.django).unwrap(),
+                balance + 26
+            );
+            assert_eq!(
+                marketplace.get_claimable(accounts.django),
+                ClaimableBalances::default()
+            );
+            assert_eq!(
+                marketplace.claim_all(),
+                Err(MarketplaceError::NothingToClaim)
+            );
+        }
+
+        #[ink::test]
+        fn buy_with_referral_fails_with_own_code() {
+            let mut marketplace = init_contract();
+            set_sender(default_accounts().charlie);
+            assert!(marketplace
+                .register_referral_code(String::from("charlie"))
+                .is_ok());
+            assert_eq!(
+                marketplace.buy_with_referral(
+                    contract_address(),
+                    Id::U64(1),
+                    String::from("charlie")
+                ),
+                Err(MarketplaceError::SelfReferral)
+            );
+        }
+
+        #[ink::test]
+        fn gated_listing_requires_gate_token() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let gate_collection = AccountId::from([0x5; 32]);
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(1)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.set_listing_gate(contract_address(), Id::U64(1), Some(gate_collection)),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .set_listing_gate(contract_address(), Id::U64(1), Some(gate_collection))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_listing_gate(contract_address(), Id::U64(1)),
+                Some(gate_collection)
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert_eq!(
+                marketplace.buy_item(contract_address(), Id::U64(1), SaleAttribution::default()),
+                Err(MarketplaceError::GateTokenRequired)
+            );
+            mock::set_owner(gate_collection, Id::U64(7), accounts.charlie);
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            assert_eq!(
+                marketplace.get_listing_gate(contract_address(), Id::U64(1)),
+                None
+            );
+        }
+
+        #[ink::test]
+        fn create_coupon_checks_issuer_and_terms() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            mock::set_collection_owner(contract_address(), accounts.bob);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.create_coupon(
+                    [1; 32],
+                    None,
+                    CouponDiscount::Percent(1000),
+                    CouponFunding::Seller,
+                    1,
+                    100
+                ),
+                Err(MarketplaceError::NotOwner)
+            );
+            assert_eq!(
+                marketplace.create_coupon(
+                    [1; 32],
+                    Some(contract_address()),
+                    CouponDiscount::Percent(10_001),
+                    CouponFunding::Seller,
+                    1,
+                    100
+                ),
+                Err(MarketplaceError::InvalidCoupon)
+            );
+            assert_eq!(
+                marketplace.create_coupon(
+                    [1; 32],
+                    Some(contract_address()),
+                    CouponDiscount::Flat(100),
+                    CouponFunding::Pool,
+                    1,
+                    100
+                ),
+                Err(MarketplaceError::InvalidCoupon)
+            );
+            assert!(marketplace
+                .create_coupon(
+                    [1; 32],
+                    Some(contract_address()),
+                    CouponDiscount::Percent(1000),
+                    CouponFunding::Seller,
+                    1,
+                    100
+                )
+                .is_ok());
+            assert_eq!(
+                marketplace.create_coupon(
+                    [1; 32],
+                    Some(contract_address()),
+                    CouponDiscount::Percent(1000),
+                    CouponFunding::Seller,
+                    1,
+                    100
+                ),
+                Err(MarketplaceError::CouponExists)
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.cancel_coupon([1; 32]),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert!(marketplace.cancel_coupon([1; 32]).is_ok());
+            assert_eq!(marketplace.get_coupon([1; 32]), None);
+        }
+
+        #[ink::test]
+        fn buy_with_seller_funded_coupon_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            let code_hash = coupon_hash(b"SALE10");
+            assert!(marketplace
+                .create_coupon(
+                    code_hash,
+                    None,
+                    CouponDiscount::Percent(1000),
+                    CouponFunding::Seller,
+                    2,
+                    100
+                )
+                .is_ok());
+            for (id, owner) in [(1, accounts.bob), (2, accounts.alice)] {
+                set_sender(owner);
+                mock::set_owner(contract_address(), Id::U64(id), owner);
+                mock::approve(
+                    contract_address(),
+                    owner,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(900);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            // seller funded coupons only apply to listings of the issuer
+            assert_eq!(
+                marketplace.buy_with_coupon(contract_address(), Id::U64(1), String::from("SALE10")),
+                Err(MarketplaceError::CouponNotApplicable)
+            );
+            assert_eq!(
+                marketplace.buy_with_coupon(contract_address(), Id::U64(2), String::from("SALE5")),
+                Err(MarketplaceError::CouponNotFound)
+            );
+            test::set_value_transferred::<Environment>(899);
+            assert_eq!(
+                marketplace.buy_with_coupon(contract_address(), Id::U64(2), String::from("SALE10")),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 900,
+                    received: 899
+                })
+            );
+            test::set_value_transferred::<Environment>(900);
+            let alice_balance = test::get_account_balance::<Environment>(accounts.alice).unwrap();
+            assert!(marketplace
+                .buy_with_coupon(contract_address(), Id::U64(2), String::from("SALE10"))
+                .is_ok());
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U64(2)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.alice).unwrap(),
+                alice_balance + 891
+            );
+            assert_eq!(marketplace.get_coupon(code_hash).unwrap().uses, 1);
+        }
+
+        #[ink::test]
+        fn buy_with_pool_funded_coupon_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            let code_hash = coupon_hash(b"DROP");
+            test::set_value_transferred::<Environment>(300);
+            assert!(marketplace
+                .create_coupon(
+                    code_hash,
+                    Some(contract_address()),
+                    CouponDiscount::Flat(100),
+                    CouponFunding::Pool,
+                    5,
+                    100
+                )
+                .is_ok());
+
+            set_sender(accounts.bob);
+            for id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(id)),
+                );
+                test::set_value_transferred::<Environment>(0);
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(900);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            let bob_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace
+                .buy_with_coupon(contract_address(), Id::U64(1), String::from("DROP"))
+                .is_ok());
+            // seller receives the full price less the marketplace fee
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                bob_balance + 990
+            );
+            assert_eq!(marketplace.get_coupon(code_hash).unwrap().pool, 200);
+            assert!(marketplace.is_coupon_redeemed(code_hash, accounts.charlie));
+            assert_eq!(
+                marketplace.buy_with_coupon(contract_address(), Id::U64(2), String::from("DROP")),
+                Err(MarketplaceError::CouponAlreadyRedeemed)
+            );
+
+            test::set_block_timestamp::<Environment>(100);
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.buy_with_coupon(contract_address(), Id::U64(2), String::from("DROP")),
+                Err(MarketplaceError::CouponExpired)
+            );
+        }
+
+        #[ink::test]
+        fn schedule_flash_sale_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert_eq!(
+                marketplace.schedule_flash_sale(contract_address(), true, 10, 20, 2000),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+            register_mocked_collection(&mut marketplace);
+            mock::set_collection_owner(contract_address(), accounts.bob);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.schedule_flash_sale(contract_address(), true, 10, 20, 2000),
+                Err(MarketplaceError::NotOwner)
+            );
+            for (start, end, discount) in [(10, 20, 0), (10, 20, 10_000), (20, 10, 2000)] {
+                assert_eq!(
+                    marketplace.schedule_flash_sale(
+                        contract_address(),
+                        false,
+                        start,
+                        end,
+                        discount
+                    ),
+                    Err(MarketplaceError::InvalidFlashSale)
+                );
+            }
+            assert!(marketplace
+                .schedule_flash_sale(contract_address(), false, 10, 20, 2000)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_flash_sale(contract_address(), Some(accounts.charlie)),
+                Some(FlashSale {
+                    start: 10,
+                    end: 20,
+                    discount: 2000
+                })
+            );
+
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .schedule_flash_sale(contract_address(), true, 10, 20, 1000)
+                .is_ok());
+            assert!(marketplace
+                .get_flash_sale(contract_address(), None)
+                .is_some());
+            assert!(marketplace
+                .cancel_flash_sale(contract_address(), true)
+                .is_ok());
+            assert_eq!(
+                marketplace.cancel_flash_sale(contract_address(), true),
+                Err(MarketplaceError::FlashSaleNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn flash_sale_discounts_listings_during_window() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace
+                .schedule_flash_sale(contract_address(), true, 10, 20, 1000)
+                .is_ok());
+
+            set_sender(accounts.bob);
+            for id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+            // seller's own sale applies when it is bigger than the collection sale
+            assert!(marketplace
+                .schedule_flash_sale(contract_address(), false, 15, 20, 2000)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U64(1)),
+                Some(1000)
+            );
+
+            test::set_block_timestamp::<Environment>(10);
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U64(1)),
+                Some(900)
+            );
+            test::set_block_timestamp::<Environment>(15);
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U64(1)),
+                Some(800)
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(800);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            let bob_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                bob_balance + 792
+            );
+
+            test::set_block_timestamp::<Environment>(20);
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U64(2)),
+                Some(1000)
+            );
+            assert_eq!(
+                marketplace.buy_item(contract_address(), Id::U64(2), SaleAttribution::default()),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 1000,
+                    received: 800
+                })
+            );
+        }
+
+        #[ink::test]
+        fn snapshot_holders_pages_through_tokens() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert_eq!(
+                marketplace.snapshot_holders(contract_address(), 0, 2),
+                Err(MarketplaceError::CollectionNotEnumerable)
+            );
+
+            let series = AccountId::from([0x5; 32]);
+            mock::set_collection_owner(series, accounts.alice);
+            assert!(marketplace
+                .register(series, None, None, NftContractType::NFTSeries)
+                .is_ok());
+            for (id, owner) in [(1, accounts.bob), (2, accounts.charlie), (3, accounts.bob)] {
+                mock::set_owner(series, Id::U64(id), owner);
+            }
+
+            assert_eq!(
+                marketplace.snapshot_holders(series, 0, 2),
+                Ok((
+                    vec![(Id::U64(1), accounts.bob), (Id::U64(2), accounts.charlie)],
+                    BatchProgress {
+                        processed: 2,
+                        next: Some(2)
+                    }
+                ))
+            );
+            assert_eq!(
+                marketplace.snapshot_holders(series, 2, 2),
+                Ok((
+                    vec![(Id::U64(3), accounts.bob)],
+                    BatchProgress {
+                        processed: 1,
+                        next: None
+                    }
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn get_owned_tokens_annotates_listings() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_max_batch_size(2).is_ok());
+            for id in 1..=3 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+            }
+            mock::set_owner(contract_address(), Id::U64(4), accounts.charlie);
+
+            set_sender(accounts.bob);
+            mock::approve(
+                contract_address(),
+                accounts.bob,
+                test::callee::<Environment>(),
+                Some(Id::U64(2)),
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(2),
+                    1000,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+
+            assert_eq!(
+                marketplace.get_owned_tokens(contract_address(), accounts.bob, 0),
+                Ok(vec![
+                    InventoryToken {
+                        token_id: Id::U64(1),
+                        listing: None,
+                        approved: false,
+                    },
+                    InventoryToken {
+                        token_id: Id::U64(2),
+                        listing: Some((1000, Currency::Native)),
+                        approved: true,
+                    },
+                ])
+            );
+            assert_eq!(
+                marketplace.get_owned_tokens(contract_address(), accounts.bob, 1),
+                Ok(vec![InventoryToken {
+                    token_id: Id::U64(3),
+                    listing: None,
+                    approved: false,
+                }])
+            );
+            assert_eq!(
+                marketplace.get_owned_tokens(contract_address(), accounts.bob, 2),
+                Ok(vec![])
+            );
+            assert_eq!(
+                marketplace.get_owned_tokens(AccountId::from([0x5; 32]), accounts.bob, 0),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn set_rate_limit_works() {
+            let mut marketplace = init_contract();
+            let rate_limit = RateLimit {
+                max_sales_per_block: Some(2),
+                max_volume_per_hour: None,
+            };
+            assert_eq!(
+                marketplace.set_rate_limit(
+                    None,
+                    Some(RateLimit {
+                        max_sales_per_block: Some(0),
+                        max_volume_per_hour: None,
+                    })
+                ),
+                Err(MarketplaceError::InvalidRateLimit)
+            );
+            assert!(marketplace
+                .set_rate_limit(Some(contract_address()), Some(rate_limit.clone()))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_rate_limit(Some(contract_address())),
+                Some(rate_limit)
+            );
+            assert_eq!(marketplace.get_rate_limit(None), None);
+            assert!(marketplace
+                .set_rate_limit(Some(contract_address()), None)
+                .is_ok());
+            assert_eq!(marketplace.get_rate_limit(Some(contract_address())), None);
+            assert_eq!(
+                marketplace.resume_collection(contract_address()),
+                Err(MarketplaceError::CollectionNotPaused)
+            );
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                marketplace.set_rate_limit(None, None),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn circuit_breaker_pauses_collection_over_volume() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace
+                .set_rate_limit(
+                    None,
+                    Some(RateLimit {
+                        max_sales_per_block: None,
+                        max_volume_per_hour: Some(1500),
+                    })
+                )
+                .is_ok());
+
+            set_sender(accounts.bob);
+            for id in 1..=3 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 5000);
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            assert!(!marketplace.is_collection_paused(contract_address()));
+            // sale over the limit completes and pauses the collection
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(2), SaleAttribution::default())
+                .is_ok());
+            assert!(marketplace.is_collection_paused(contract_address()));
+            assert_eq!(
+                marketplace.buy_item(contract_address(), Id::U64(3), SaleAttribution::default()),
+                Err(MarketplaceError::CollectionPaused)
+            );
+
+            set_sender(accounts.alice);
+            assert!(marketplace.resume_collection(contract_address()).is_ok());
+            // volume is counted per hour
+            test::set_block_timestamp::<Environment>(3_600_000);
+            set_sender(accounts.charlie);
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(3), SaleAttribution::default())
+                .is_ok());
+            assert!(!marketplace.is_collection_paused(contract_address()));
+        }
+
+        #[ink::test]
+        fn floor_guard_requires_confirmation_below_floor() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_floor_guard(Some(10_001)),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001
+                })
+            );
+            assert!(marketplace.set_floor_guard(Some(2000)).is_ok());
+            assert_eq!(marketplace.get_floor_guard(accounts.bob), Some(2000));
+            for id in 1..=5 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+            }
+            // without a floor there is nothing to compare with
+            assert!(marketplace
+                .list(contract_address(), Id::U64(1), 1000)
+                .is_ok());
+
+            assert_eq!(
+                marketplace.list(contract_address(), Id::U64(2), 799),
+                Err(MarketplaceError::PriceBelowFloor { floor: 1000 })
+            );
+            assert!(marketplace
+                .list(contract_address(), Id::U64(2), 800)
+                .is_ok());
+            assert!(marketplace
+                .list_with_confirmation(contract_address(), Id::U64(3), 100, true)
+                .is_ok());
+            assert_eq!(
+                marketplace.list(contract_address(), Id::U64(5), 79),
+                Err(MarketplaceError::PriceBelowFloor { floor: 100 })
+            );
+
+            assert!(marketplace.set_floor_guard(None).is_ok());
+            assert!(marketplace.list(contract_address(), Id::U64(4), 10).is_ok());
+        }
+
+        #[ink::test]
+        fn set_subscription_plan_works() {
+            let mut marketplace = init_contract();
+            let plan = SubscriptionPlan {
+                fee: 50,
+                price: 100,
+                currency: Currency::Native,
+                period: 1000,
+            };
+            assert_eq!(
+                marketplace.set_subscription_plan(
+                    1,
+                    Some(SubscriptionPlan {
+                        fee: 1001,
+                        ..plan.clone()
+                    })
+                ),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 1000,
+                    got: 1001
+                })
+            );
+            for invalid in [
+                SubscriptionPlan {
+                    price: 0,
+                    ..plan.clone()
+                },
+                SubscriptionPlan {
+                    period: 0,
+                    ..plan.clone()
+                },
+                SubscriptionPlan {
+                    currency: Currency::Psp22(AccountId::from([0x5; 32])),
+                    ..plan.clone()
+                },
+            ] {
+                assert_eq!(
+                    marketplace.set_subscription_plan(1, Some(invalid)),
+                    Err(MarketplaceError::InvalidSubscriptionPlan)
+                );
+            }
+            assert!(marketplace
+                .set_subscription_plan(1, Some(plan.clone()))
+                .is_ok());
+            assert_eq!(marketplace.get_subscription_plan(1), Some(plan));
+            assert!(marketplace.set_subscription_plan(1, None).is_ok());
+            assert_eq!(marketplace.get_subscription_plan(1), None);
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                marketplace.set_subscription_plan(1, None),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn subscription_reduces_seller_fee_until_expiry() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            for tier in 1..=2 {
+                assert!(marketplace
+                    .set_subscription_plan(
+                        tier,
+                        Some(SubscriptionPlan {
+                            fee: 50,
+                            price: 100,
+                            currency: Currency::Native,
+                            period: 1000,
+                        })
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.bob);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            assert_eq!(
+                marketplace.subscribe(3, 1),
+                Err(MarketplaceError::SubscriptionPlanNotFound)
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.subscribe(1, 2),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 200,
+                    received: 100
+                })
+            );
+            assert!(marketplace.subscribe(1, 1).is_ok());
+            // renewing extends the subscription
+            assert!(marketplace.subscribe(1, 1).is_ok());
+            assert_eq!(
+                marketplace.get_subscription(accounts.bob),
+                Some(Subscription {
+                    tier: 1,
+                    expires_at: 2000
+                })
+            );
+            assert_eq!(
+                marketplace.subscribe(2, 1),
+                Err(MarketplaceError::SubscriptionActive)
+            );
+
+            for id in 1..=2 {
+                mock::set_owner(contract_address(), Id::U64(id), accounts.bob);
+                mock::approve(
+                    contract_address(),
+                    accounts.bob,
+                    test::callee::<Environment>(),
+                    Some(Id::U64(id)),
+                );
+                assert!(marketplace
+                    .list_item(
+                        contract_address(),
+                        Id::U64(id),
+                        1000,
+                        Currency::Native,
+                        false,
+                        false
+                    )
+                    .is_ok());
+            }
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(1000);
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 2000);
+            let bob_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(1), SaleAttribution::default())
+                .is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                bob_balance + 995
+            );
+
+            test::set_block_timestamp::<Environment>(2000);
+            assert_eq!(marketplace.get_subscription(accounts.bob), None);
+            assert!(marketplace
+                .buy_item(contract_address(), Id::U64(2), SaleAttribution::default())
+                .is_ok());
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                bob_balance + 995 + 990
+            );
+        }
+
+        #[ink::test]
+        fn set_max_batch_size_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(marketplace.get_max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+            assert_eq!(
+                marketplace.set_max_batch_size(0),
+                Err(MarketplaceError::InvalidBatchSize)
+            );
+            assert!(marketplace.set_max_batch_size(2).is_ok());
+            assert_eq!(marketplace.get_max_batch_size(), 2);
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                marketplace.set_max_batch_size(10),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_tick_size_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(marketplace.get_tick_size(), 0);
+            assert!(marketplace.set_tick_size(10).is_ok());
+            assert_eq!(marketplace.get_tick_size(), 10);
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                marketplace.set_tick_size(1),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn list_item_checks_tick_size() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_mocked_collection(&mut marketplace);
+            assert!(marketplace.set_tick_size(10).is_ok());
+            mock::set_owner(contract_address(), Id::U64(1), accounts.bob);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    105,
+                    Currency::Native,
+                    false,
+                    false
+                ),
+                Err(MarketplaceError::PriceNotOnTick { tick_size: 10 })
+            );
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    110,
+                    Currency::Native,
+                    false,
+                    false
+                )
+                .is_ok());
+            // tick size only applies to native prices
+            assert!(marketplace
+                .list_item(
+                    contract_address(),
+                    Id::U64(1),
+                    105,
+                    Currency::Psp22(accounts.django),
+                    false,
+                    false
+                )
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn make_offer_checks_tick_size() {
+            let mut marketplace = init_contract();
+            assert!(marketplace.set_tick_size(10).is_ok());
+
+            set_sender(default_accounts().bob);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), None, 1, 105, String::from("")),
+                Err(MarketplaceError::PriceNotOnTick { tick_size: 10 })
+            );
+        }
+
+        #[ink::test]
+        fn next_batch_returns_continuation_cursor() {
+            let (range, progress) = next_batch(5, 0, 2).unwrap();
+            assert_eq!(range, 0..2);
+            assert_eq!(
+                progress,
+                BatchProgress {
+                    processed: 2,
+                    next: Some(2)
+                }
+            );
+            let (range, progress) = next_batch(5, 4, 2).unwrap();
+            assert_eq!(range, 4..5);
+            assert_eq!(
+                progress,
+                BatchProgress {
+                    processed: 1,
+                    next: None
+                }
+            );
+            assert_eq!(next_batch(5, 5, 2).unwrap().1.processed, 0);
+            assert_eq!(
+                next_batch(5, 6, 2),
+                Err(MarketplaceError::InvalidBatchCursor)
+            );
+        }
+
+        #[ink::test]
+        fn batches_are_bounded_by_max_batch_size() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_max_batch_size(2).is_ok());
+
+            let tokens = (1..=3)
+                .map(|token_id| (contract_address(), Id::U64(token_id)))
+                .collect::<Vec<_>>();
+            assert_eq!(marketplace.get_prices(tokens.clone()).len(), 2);
+            assert_eq!(marketplace.get_listings(tokens).len(), 2);
+            assert_eq!(marketplace.get_offers_active(vec![1, 2, 3]).len(), 2);
+            assert_eq!(
+                marketplace.create_mystery_box(
+                    contract_address(),
+                    vec![Id::U64(1), Id::U64(2), Id::U64(3)],
+                    100
+                ),
+                Err(MarketplaceError::BatchTooLarge)
+            );
+            assert_eq!(
+                marketplace.propose_trade(
+                    Some(accounts.bob),
+                    vec![
+                        TradeAsset::Psp34(contract_address(), Id::U64(1)),
+                        TradeAsset::Psp34(contract_address(), Id::U64(2)),
+                    ],
+                    vec![TradeAsset::Native(100)]
+                ),
+                Err(MarketplaceError::BatchTooLarge)
+            );
+        }
+
+        #[ink::test]
+        fn events_carry_schema_version_and_compact_prices() {
+            let marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.emit_token_listed_event(accounts.charlie, Id::U8(1), Some(100));
+
+            let event = ink::env::test::recorded_events().last().unwrap();
+            assert_eq!(event.data[1], EVENT_SCHEMA_VERSION);
+            match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                Ok(Event::TokenListed(TokenListed { version, price, .. })) => {
+                    assert_eq!(version, EVENT_SCHEMA_VERSION);
+                    assert_eq!(price, Some(Compact(100)));
+                }
+                _ => panic!("expected TokenListed event"),
+            }
+        }
+
+        #[ink::test]
+        fn set_installment_penalty_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.set_installment_penalty(1000).is_ok());
+            assert_eq!(marketplace.get_installment_penalty(), 1000);
+            assert_eq!(
+                marketplace.set_installment_penalty(10_001),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: 10_000,
+                    got: 10_001,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn rental_fails_if_not_found() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.rent(contract_address(), Id::U128(1), 1),
+                Err(MarketplaceError::RentalNotListed)
+            );
+            assert_eq!(
+                marketplace.cancel_rental_listing(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::RentalNotListed)
+            );
+            assert_eq!(
+                marketplace.return_rental(1),
+                Err(MarketplaceError::RentalNotFound)
+            );
+            assert_eq!(
+                marketplace.claim_rental_collateral(1),
+                Err(MarketplaceError::RentalNotFound)
+            );
+            assert_eq!(
+                marketplace.end_custodial_rental(1),
+                Err(MarketplaceError::RentalNotFound)
+            );
+            assert_eq!(
+                marketplace.extend_rental(1, 1),
+                Err(MarketplaceError::RentalNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn user_of_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 10,
+                max_days: 7,
+                collateral: 0,
+                early_return_refund: 0,
+                held_rent: 0,
+                end_time: 1000,
+                custodial: true,
+                purchase_price: None,
+                paid: 0,
+                settled_until: None,
+            };
+            marketplace.marketplace.rentals.insert(&1, &rental);
+            marketplace
+                .marketplace
+                .rented_tokens
+                .insert(&(contract_address(), Id::U128(1)), &1);
+
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(
+                marketplace.user_of(contract_address(), Id::U128(1)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(marketplace.user_of(contract_address(), Id::U128(2)), None);
+            assert_eq!(
+                marketplace.claim_rental_collateral(1),
+                Err(MarketplaceError::CustodialRental)
+            );
+            assert_eq!(
+                marketplace.end_custodial_rental(1),
+                Err(MarketplaceError::RentalNotEnded)
+            );
+
+            test::set_block_timestamp::<Environment>(1001);
+            assert_eq!(marketplace.user_of(contract_address(), Id::U128(1)), None);
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.extend_rental(1, 1),
+                Err(MarketplaceError::RentalEnded)
+            );
+        }
+
+        #[ink::test]
+        fn rental_price_works() {
+            let marketplace = init_contract();
+
+            assert_eq!(marketplace.rental_price(10, 7, 7), Ok(70));
+            assert_eq!(
+                marketplace.rental_price(10, 7, 0),
+                Err(MarketplaceError::InvalidRentalDuration)
+            );
+            assert_eq!(
+                marketplace.rental_price(10, 7, 8),
+                Err(MarketplaceError::InvalidRentalDuration)
+            );
+        }
+
+        #[ink::test]
+        fn get_active_rentals_by_account_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = |token_id| Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(token_id),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 10,
+                max_days: 7,
+                collateral: 0,
+                early_return_refund: 0,
+                held_rent: 0,
+                end_time: 1000,
+                custodial: true,
+                purchase_price: None,
+                paid: 0,
+                settled_until: None,
+            };
+            for rental_id in 1..=3 {
+                marketplace
+                    .marketplace
+                    .rentals
+                    .insert(&rental_id, &rental(rental_id));
+                marketplace.update_account_rentals(accounts.bob, rental_id, true);
+                marketplace.update_account_rentals(accounts.charlie, rental_id, true);
+            }
+            marketplace.remove_rental(2, &rental(2));
+
+            assert_eq!(
+                marketplace.get_active_rentals_by_account(accounts.charlie, 0, 10),
+                vec![(1, rental(1)), (3, rental(3))]
+            );
+            assert_eq!(
+                marketplace.get_active_rentals_by_account(accounts.bob, 1, 1),
+                vec![(3, rental(3))]
+            );
+            assert_eq!(marketplace.get_rental(2), None);
+            assert!(marketplace
+                .get_active_rentals_by_account(accounts.alice, 0, 10)
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn remove_rental_clears_storage() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 10,
+                max_days: 7,
+                collateral: 0,
+                early_return_refund: 0,
+                held_rent: 0,
+                end_time: 1000,
+                custodial: true,
+                purchase_price: None,
+                paid: 0,
+                settled_until: None,
+            };
+            marketplace.marketplace.rentals.insert(&1, &rental);
+            marketplace
+                .marketplace
+                .rented_tokens
+                .insert(&(contract_address(), Id::U128(1)), &1);
+            marketplace.update_account_rentals(accounts.bob, 1, true);
+            marketplace.update_account_rentals(accounts.charlie, 1, true);
+
+            marketplace.remove_rental(1, &rental);
+            let data = &marketplace.marketplace;
+            assert!(!data.rentals.contains(&1));
+            assert!(!data
+                .rented_tokens
+                .contains(&(contract_address(), Id::U128(1))));
+            assert_eq!(data.account_rental_index.count(&accounts.bob), 0);
+            assert_eq!(data.account_rental_index.count(&accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn rent_due_works() {
+            let marketplace = init_contract();
+
+            assert_eq!(marketplace.rent_due(100, None, 1000), 100);
+            assert_eq!(marketplace.rent_due(100, Some(1000), 800), 100);
+            assert_eq!(marketplace.rent_due(100, Some(1000), 950), 50);
+            assert_eq!(marketplace.rent_due(100, Some(1000), 1000), 0);
+        }
+
+        #[ink::test]
+        fn rental_refund_works() {
+            let marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 100,
+                max_days: 7,
+                collateral: 0,
+                early_return_refund: 5000,
+                held_rent: 350,
+                end_time: 7 * DAY,
+                custodial: true,
+                purchase_price: None,
+                paid: 0,
+                settled_until: None,
+            };
+
+            assert_eq!(marketplace.rental_refund(&rental), 350);
+            // partially used day is not refunded
+            test::set_block_timestamp::<Environment>(DAY + 1);
+            assert_eq!(marketplace.rental_refund(&rental), 250);
+            test::set_block_timestamp::<Environment>(7 * DAY);
+            assert_eq!(marketplace.rental_refund(&rental), 0);
+        }
+
+        #[ink::test]
+        fn claim_rental_collateral_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 10,
+                max_days: 7,
+                collateral: 100,
+                early_return_refund: 0,
+                held_rent: 0,
+                end_time: 1000,
+                custodial: false,
+                purchase_price: None,
+                paid: 0,
+                settled_until: None,
+            };
+            marketplace.marketplace.rentals.insert(&1, &rental);
+            marketplace.marketplace.balance_sheet.set(&BalanceSheet {
+                locked: 100,
+                ..Default::default()
+            });
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 100);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.claim_rental_collateral(1),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(
+                marketplace.claim_rental_collateral(1),
+                Err(MarketplaceError::RentalNotEnded)
+            );
+
+            test::set_block_timestamp::<Environment>(1001);
+            assert!(marketplace.claim_rental_collateral(1).is_ok());
+            assert_eq!(marketplace.get_rental(1), None);
+            assert_eq!(marketplace.get_balance_sheet().locked, 0);
+        }
+
+        #[ink::test]
+        fn rent_stream_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let rental = Rental {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                owner: accounts.bob,
+                renter: accounts.charlie,
+                daily_price: 100,
+                max_days: 0,
+                collateral: 0,
+                early_return_refund: 0,
+                held_rent: 0,
+                end_time: u64::MAX,
+                custodial: true,
+                purchase_price: None,
+                paid: 0,
+                settled_until: Some(0),
+            };
+            marketplace.marketplace.rentals.insert(&1, &rental);
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+            assert_eq!(
+                marketplace.extend_rental(1, 1),
+                Err(MarketplaceError::StreamedRental)
+            );
+
+            test::set_block_timestamp::<Environment>(DAY / 2);
+            assert!(marketplace.settle_rent_stream(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.charlie), 50);
+            assert_eq!(marketplace.get_deposit(accounts.bob), 50);
+            assert_eq!(
+                marketplace.get_rental(1).unwrap().settled_until,
+                Some(DAY / 2)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.terminate_rental(1),
+                Err(MarketplaceError::RentStreamFunded)
+            );
+
+            // deposit runs dry half a day later
+            test::set_block_timestamp::<Environment>(3 * DAY);
+            assert!(marketplace.settle_rent_stream(1).is_ok());
+            let rental = marketplace.get_rental(1).unwrap();
+            assert_eq!(rental.settled_until, Some(DAY));
+            assert_eq!(rental.paid, 100);
+            assert_eq!(marketplace.get_deposit(accounts.charlie), 0);
+            assert_eq!(marketplace.get_deposit(accounts.bob), 100);
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.terminate_rental(1),
+                Err(MarketplaceError::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn loan_offer_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_loan_offer(contract_address(), None, 101, 10, 1000),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            assert!(marketplace
+                .make_loan_offer(contract_address(), None, 100, 10, 1000)
+                .is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 0);
+            assert_eq!(marketplace.get_balance_sheet().locked, 100);
+            assert_eq!(
+                marketplace.get_loan_offer(1).map(|offer| offer.principal),
+                Some(100)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.cancel_loan_offer(1),
+                Err(MarketplaceError::NotLender)
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.cancel_loan_offer(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 100);
+            assert_eq!(marketplace.get_balance_sheet().locked, 0);
+            assert_eq!(marketplace.get_loan_offer(1), None);
+        }
+
+        #[ink::test]
+        fn standing_loan_offer_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_standing_loan_offer(contract_address(), 0, 1000, YEAR),
+                Err(MarketplaceError::InvalidPrincipal)
+            );
+            assert!(marketplace
+                .make_standing_loan_offer(contract_address(), 80, 1000, YEAR)
+                .is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 20);
+            assert_eq!(
+                marketplace.accept_standing_loan_offer(1, Id::U128(1), 81),
+                Err(MarketplaceError::InvalidPrincipal)
+            );
+
+            assert!(marketplace.cancel_standing_loan_offer(1).is_ok());
+            assert_eq!(marketplace.get_deposit(accounts.alice), 100);
+            assert_eq!(marketplace.get_standing_loan_offer(1), None);
+        }
+
+        #[ink::test]
+        fn get_loans_by_collection_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let loan = |token_id| Loan {
+                contract_address: contract_address(),
+                token_id: Id::U128(token_id),
+                borrower: accounts.bob,
+                lender: accounts.charlie,
+                principal: 100,
+                interest: 10,
+                due: 1000,
+            };
+            for loan_id in 1..=3 {
+                marketplace
+                    .marketplace
+                    .loans
+                    .insert(&loan_id, &loan(loan_id));
+            }
+            for loan_id in 1..=3 {
+                marketplace
+                    .marketplace
+                    .collection_loan_index
+                    .insert(&contract_address(), &loan_id);
+            }
+            marketplace.remove_loan(1, &loan(1));
+
+            assert_eq!(
+                marketplace.get_loans_by_collection(contract_address(), 0, 1),
+                vec![(3, loan(3))]
+            );
+            assert_eq!(
+                marketplace.get_loans_by_collection(contract_address(), 1, 10),
+                vec![(2, loan(2))]
+            );
+            assert!(marketplace
+                .get_loans_by_collection(accounts.alice, 0, 10)
+                .is_empty());
+        }
+
+        #[ink::test]
+        fn loan_interest_works() {
+            let marketplace = init_contract();
+
+            assert_eq!(marketplace.loan_interest(1000, 1000, YEAR), 100);
+            assert_eq!(marketplace.loan_interest(1000, 1000, YEAR / 2), 50);
+            assert_eq!(marketplace.loan_interest(1000, 0, YEAR), 0);
+        }
+
+        #[ink::test]
+        fn loan_fails_if_not_found() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.make_loan_offer(contract_address(), None, 0, 0, 0),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+            assert_eq!(
+                marketplace.accept_loan_offer(1, Id::U128(1)),
+                Err(MarketplaceError::LoanOfferNotFound)
+            );
+            assert_eq!(
+                marketplace.repay_loan(1),
+                Err(MarketplaceError::LoanNotFound)
+            );
+            assert_eq!(
+                marketplace.claim_defaulted_loan(1),
+                Err(MarketplaceError::LoanNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn repay_loan_fails_if_overdue() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let loan = Loan {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                borrower: accounts.bob,
+                lender: accounts.charlie,
+                principal: 100,
+                interest: 10,
+                due: 1000,
+            };
+            marketplace.marketplace.loans.insert(&1, &loan);
+
+            set_sender(accounts.bob);
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(
+                marketplace.repay_loan(1),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            assert_eq!(
+                marketplace.claim_defaulted_loan(1),
+                Err(MarketplaceError::NotLender)
+            );
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.claim_defaulted_loan(1),
+                Err(MarketplaceError::LoanNotOverdue)
+            );
+
+            set_sender(accounts.bob);
+            test::set_block_timestamp::<Environment>(1001);
+            assert_eq!(
+                marketplace.repay_loan(1),
+                Err(MarketplaceError::LoanOverdue)
+            );
+        }
+
+        #[ink::test]
+        fn fulfill_signed_order_fails_if_invalid() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let order = SignedOrder {
+                seller: accounts.bob,
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                price: 100,
+                expiry: 1000,
+                nonce: 1,
+            };
+
+            set_sender(accounts.charlie);
+            test::set_block_timestamp::<Environment>(1001);
+            assert_eq!(
+                marketplace.fulfill_signed_order(order.clone(), [0; 65]),
+                Err(MarketplaceError::OrderExpired)
+            );
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(
+                marketplace.fulfill_signed_order(order, [0; 65]),
+                Err(MarketplaceError::InvalidSignature)
+            );
+        }
+
+        #[ink::test]
+        fn cancel_signed_order_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let order = SignedOrder {
+                seller: accounts.bob,
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                price: 100,
+                expiry: 1000,
+                nonce: 1,
+            };
+            let hash = marketplace.get_order_hash(order.clone());
+            assert_ne!(
+                marketplace.get_order_hash(SignedOrder { nonce: 2, ..order }),
+                hash
+            );
+
+            set_sender(accounts.bob);
+            assert!(marketplace.cancel_signed_order(1).is_ok());
+            assert_eq!(
+                marketplace.cancel_signed_order(1),
+                Err(MarketplaceError::OrderNonceUsed)
+            );
+            assert!(marketplace.is_order_nonce_used(accounts.bob, 1));
+            assert!(!marketplace.is_order_nonce_used(accounts.bob, 2));
+            assert!(!marketplace.is_order_nonce_used(accounts.charlie, 1));
+
+            assert!(marketplace.cancel_signed_orders(5).is_ok());
+            assert_eq!(marketplace.get_min_order_nonce(accounts.bob), 5);
+            assert!(marketplace.is_order_nonce_used(accounts.bob, 4));
+            assert!(!marketplace.is_order_nonce_used(accounts.bob, 5));
+            assert_eq!(
+                marketplace.cancel_signed_orders(5),
+                Err(MarketplaceError::OrderNonceUsed)
+            );
+        }
+
+        #[ink::test]
+        fn edition_bid_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let token_id = Id::U128(1);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.deposit().is_ok());
+
+            assert_eq!(
+                marketplace.make_edition_order(
+                    contract_address(),
+                    token_id.clone(),
+                    EditionSide::Bid,
+                    0,
+                    1
+                ),
+                Err(MarketplaceError::InvalidEditionOrder)
+            );
+            assert_eq!(
+                marketplace.make_edition_order(
+                    contract_address(),
+                    token_id.clone(),
+                    EditionSide::Bid,
+                    10,
+                    11
+                ),
+                Err(MarketplaceError::BalanceInsufficient)
+            );
+            for (price, amount) in [(10, 3), (5, 4), (10, 2)] {
+                assert!(marketplace
+                    .make_edition_order(
+                        contract_address(),
+                        token_id.clone(),
+                        EditionSide::Bid,
+                        price,
+                        amount
+                    )
+                    .is_ok());
+            }
+            assert_eq!(marketplace.get_deposit(accounts.alice), 30);
+            assert_eq!(
+                marketplace.get_edition_levels(
+                    contract_address(),
+                    token_id.clone(),
+                    EditionSide::Bid
+                ),
+                vec![(5, 4), (10, 5)]
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.cancel_edition_order(1),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.alice);
+            assert!(marketplace.cancel_edition_order(1).is_ok());
+            assert_eq!(marketplace.get_edition_order(1), None);
+            assert_eq!(marketplace.get_deposit(accounts.alice), 60);
+            assert_eq!(
+                marketplace.get_edition_levels(
+                    contract_address(),
+                    token_id.clone(),
+                    EditionSide::Bid
+                ),
+                vec![(5, 4), (10, 2)]
+            );
+            assert!(marketplace.cancel_edition_order(2).is_ok());
+            assert_eq!(
+                marketplace.get_edition_levels(contract_address(), token_id, EditionSide::Bid),
+                vec![(10, 2)]
+            );
+            assert_eq!(
+                marketplace.cancel_edition_order(2),
+                Err(MarketplaceError::EditionOrderNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn buy_raffle_tickets_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .raffles
+                .insert(&1, &raffle(accounts.bob, RaffleStatus::Open));
+
+            assert_eq!(
+                marketplace.buy_raffle_tickets(2, 1),
+                Err(MarketplaceError::RaffleNotFound)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.buy_raffle_tickets(1, 1),
+                Err(MarketplaceError::AlreadyOwner)
+            );
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(10);
+            assert_eq!(
+                marketplace.buy_raffle_tickets(1, 2),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 20,
+                    received: 10,
+                })
+            );
+            assert!(marketplace.buy_raffle_tickets(1, 1).is_ok());
+            set_sender(accounts.django);
+            test::set_value_transferred::<Environment>(40);
+            assert!(marketplace.buy_raffle_tickets(1, 4).is_ok());
+            assert_eq!(
+                marketplace.buy_raffle_tickets(1, 1),
+                Err(MarketplaceError::RaffleSoldOut)
+            );
+
+            let raffle = marketplace.get_raffle(1).unwrap();
+            assert_eq!(raffle.tickets_sold, 5);
+            assert_ne!(raffle.seed, [0; 32]);
+            assert_eq!(raffle.entries, 2);
+            assert_eq!(
+                marketplace.get_raffle_entries(1, 0, 10),
+                vec![(accounts.charlie, 1), (accounts.django, 4)]
+            );
+            assert_eq!(
+                marketplace.get_raffle_entries(1, 1, 10),
+                vec![(accounts.django, 4)]
+            );
+            assert_eq!(marketplace.get_raffle_tickets(1, accounts.django), 4);
+            assert_eq!(marketplace.get_balance_sheet().escrowed, 50);
+
+            test::set_block_timestamp::<Environment>(1001);
+            assert_eq!(
+                marketplace.buy_raffle_tickets(1, 1),
+                Err(MarketplaceError::RaffleEnded)
+            );
+        }
+
+        #[ink::test]
+        fn raffle_winner_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let mut open_raffle = raffle(accounts.bob, RaffleStatus::Open);
+            for (index, (buyer, tickets, first_ticket)) in [
+                (accounts.charlie, 1, 0),
+                (accounts.django, 4, 1),
+                (accounts.charlie, 2, 5),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                marketplace.marketplace.raffle_entries.insert(
+                    &(1, index as u32),
+                    &RaffleEntry {
+                        buyer,
+                        tickets,
+                        first_ticket,
+                    },
+                );
+            }
+            open_raffle.entries = 3;
+            let random = |ticket: u8| {
+                let mut random = [0; 32];
+                random[0] = ticket;
+                random
+            };
+
+            assert_eq!(marketplace.raffle_winner(1, &open_raffle, random(0)), None);
+            open_raffle.tickets_sold = 7;
+            assert_eq!(
+                marketplace.raffle_winner(1, &open_raffle, random(0)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(
+                marketplace.raffle_winner(1, &open_raffle, random(1)),
+                Some(accounts.django)
+            );
+            assert_eq!(
+                marketplace.raffle_winner(1, &open_raffle, random(4)),
+                Some(accounts.django)
+            );
+            assert_eq!(
+                marketplace.raffle_winner(1, &open_raffle, random(6)),
+                Some(accounts.charlie)
+            );
+            assert_eq!(
+                marketplace.raffle_winner(1, &open_raffle, random(7)),
+                Some(accounts.charlie)
+            );
+        }
+
+        #[ink::test]
+        fn draw_raffle_fails_without_secret() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let mut open_raffle = raffle(accounts.bob, RaffleStatus::Open);
+            open_raffle.tickets_sold = 2;
+            marketplace.marketplace.raffles.insert(&1, &open_raffle);
+
+            assert_eq!(
+                marketplace.draw_raffle(1, None),
+                Err(MarketplaceError::RaffleNotEnded)
+            );
+            test::set_block_timestamp::<Environment>(1001);
+            assert_eq!(
+                marketplace.draw_raffle(1, None),
+                Err(MarketplaceError::RaffleMinimumNotMet)
+            );
+            open_raffle.tickets_sold = 3;
+            marketplace.marketplace.raffles.insert(&1, &open_raffle);
+            assert_eq!(
+                marketplace.end_failed_raffle(1),
+                Err(MarketplaceError::RaffleMinimumMet)
+            );
+            assert_eq!(
+                marketplace.draw_raffle(1, None),
+                Err(MarketplaceError::RaffleRevealPending)
+            );
+            assert_eq!(
+                marketplace.draw_raffle(1, Some([1; 32])),
+                Err(MarketplaceError::InvalidRaffleSecret)
+            );
+        }
+
+        #[ink::test]
+        fn raffle_fails_if_not_drawn_within_reveal_period() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let mut open_raffle = raffle(accounts.bob, RaffleStatus::Open);
+            open_raffle.tickets_sold = 3;
+            marketplace.marketplace.raffles.insert(&1, &open_raffle);
+            mock::set_owner(
+                contract_address(),
+                Id::U128(1),
+                test::callee::<Environment>(),
+            );
+
+            test::set_block_timestamp::<Environment>(1000 + RAFFLE_REVEAL_PERIOD);
+            assert_eq!(
+                marketplace.end_failed_raffle(1),
+                Err(MarketplaceError::RaffleMinimumMet)
+            );
+            test::set_block_timestamp::<Environment>(1001 + RAFFLE_REVEAL_PERIOD);
+            assert_eq!(
+                marketplace.draw_raffle(1, None),
+                Err(MarketplaceError::RaffleRevealExpired)
+            );
+            // anyone can end it, buyers are refunded and the owner gets no proceeds
+            set_sender(accounts.charlie);
+            assert!(marketplace.end_failed_raffle(1).is_ok());
+            assert_eq!(
+                marketplace.get_raffle(1).unwrap().status,
+                RaffleStatus::Failed
+            );
+            assert_eq!(
+                mock::owner_of(contract_address(), &Id::U128(1)),
+                Some(accounts.bob)
+            );
+        }
+
+        #[ink::test]
+        fn claim_raffle_refund_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .raffles
+                .insert(&1, &raffle(accounts.bob, RaffleStatus::Failed));
+            for (buyer, tickets) in [(accounts.charlie, 2), (accounts.django, 1)] {
+                marketplace
+                    .marketplace
+                    .raffle_tickets
+                    .insert(&(1, buyer), &tickets);
+            }
+            marketplace.marketplace.balance_sheet.set(&BalanceSheet {
+                escrowed: 30,
+                ..Default::default()
+            });
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 30);
+
+            set_sender(accounts.eve);
+            assert_eq!(
+                marketplace.claim_raffle_refund(1),
+                Err(MarketplaceError::NoRaffleTickets)
+            );
+            set_sender(accounts.charlie);
+            assert!(marketplace.claim_raffle_refund(1).is_ok());
+            assert_eq!(marketplace.get_raffle_tickets(1, accounts.charlie), 0);
+            assert_eq!(marketplace.get_raffle_tickets(1, accounts.django), 1);
+            assert_eq!(marketplace.get_balance_sheet().escrowed, 10);
+            assert_eq!(
+                marketplace.claim_raffle_refund(1),
+                Err(MarketplaceError::NoRaffleTickets)
+            );
+        }
+
+        #[ink::test]
+        fn buy_mystery_box_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .mystery_boxes
+                .insert(&1, &mystery_box(accounts.bob));
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.buy_mystery_box(1, [1; 32]),
+                Err(MarketplaceError::AlreadyOwner)
+            );
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(10);
+            assert_eq!(
+                marketplace.buy_mystery_box(1, [1; 32]),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 100,
+                    received: 10,
+                })
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.buy_mystery_box(1, [1; 32]).is_ok());
+            set_sender(accounts.django);
+            assert!(marketplace.buy_mystery_box(1, [2; 32]).is_ok());
+            assert_eq!(
+                marketplace.buy_mystery_box(1, [3; 32]),
+                Err(MarketplaceError::MysteryBoxSoldOut)
+            );
+
+            let mystery_box = marketplace.get_mystery_box(1).unwrap();
+            assert_eq!(mystery_box.pending, 2);
+            assert_ne!(mystery_box.seed, [0; 32]);
+            let purchase = marketplace.get_mystery_box_purchase(1).unwrap();
+            assert_eq!(purchase.buyer, accounts.charlie);
+            assert_eq!(purchase.commitment, [1; 32]);
+        }
+
+        #[ink::test]
+        fn reveal_mystery_box_fails_if_invalid() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .mystery_boxes
+                .insert(&1, &mystery_box(accounts.bob));
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.buy_mystery_box(1, [1; 32]).is_ok());
+
+            assert_eq!(
+                marketplace.reveal_mystery_box(1, Some([1; 32])),
+                Err(MarketplaceError::MysteryBoxRevealTooEarly)
+            );
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.reveal_mystery_box(1, Some([1; 32])),
+                Err(MarketplaceError::InvalidMysteryBoxSecret)
+            );
+            assert_eq!(
+                marketplace.reveal_mystery_box(2, None),
+                Err(MarketplaceError::MysteryBoxPurchaseNotFound)
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.reveal_mystery_box(1, None),
+                Err(MarketplaceError::MysteryBoxRevealPending)
+            );
+
+            assert_eq!(
+                marketplace.close_mystery_box(1),
+                Err(MarketplaceError::NotOwner)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.close_mystery_box(1),
+                Err(MarketplaceError::MysteryBoxPurchasesPending)
+            );
+        }
+
+        #[ink::test]
+        fn mystery_box_draw_works() {
+            let marketplace = init_contract();
+            let mut random = [0; 32];
+            random[0] = 7;
+            assert_eq!(marketplace.mystery_box_draw(random, 3), 1);
+            assert_eq!(marketplace.mystery_box_draw(random, 8), 7);
+            assert_eq!(marketplace.mystery_box_draw(random, 1), 0);
+        }
+
+        #[ink::test]
+        fn bid_buyout_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace
+                .marketplace
+                .fraction_vaults
+                .insert(&1, &fraction_vault(accounts.bob));
+            test::set_account_balance::<Environment>(test::callee::<Environment>(), 1000);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.end_buyout(1),
+                Err(MarketplaceError::BuyoutNotStarted)
+            );
+            test::set_value_transferred::<Environment>(99);
+            assert_eq!(
+                marketplace.bid_buyout(1),
+                Err(MarketplaceError::BuyoutBidTooLow)
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace.bid_buyout(1).is_ok());
+            assert_eq!(marketplace.get_balance_sheet().escrowed, 100);
+
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.bid_buyout(1),
+                Err(MarketplaceError::BuyoutBidTooLow)
+            );
+            test::set_value_transferred::<Environment>(150);
+            assert!(marketplace.bid_buyout(1).is_ok());
+            assert_eq!(marketplace.get_balance_sheet().escrowed, 150);
+            assert_eq!(
+                marketplace.get_fraction_vault(1).unwrap().buyout,
+                Some(Buyout {
+                    bidder: accounts.django,
+                    bid: 150,
+                    end_time: BUYOUT_DURATION,
+                })
+            );
+            assert_eq!(
+                marketplace.end_buyout(1),
+                Err(MarketplaceError::BuyoutNotEnded)
+            );
+
+            test::set_block_timestamp::<Environment>(BUYOUT_DURATION + 1);
+            test::set_value_transferred::<Environment>(200);
+            assert_eq!(
+                marketplace.bid_buyout(1),
+                Err(MarketplaceError::BuyoutEnded)
+            );
+            assert_eq!(
+                marketplace.redeem_fraction_shares(1, 10),
+                Err(MarketplaceError::VaultNotBoughtOut)
+            );
+        }
+
+        #[ink::test]
+        fn buy_fraction_shares_fails_if_invalid() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let mut vault = fraction_vault(accounts.bob);
+            marketplace.marketplace.fraction_vaults.insert(&1, &vault);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.buy_fraction_shares(1, 51),
+                Err(MarketplaceError::FractionSharesSoldOut)
+            );
+            test::set_value_transferred::<Environment>(10);
+            assert_eq!(
+                marketplace.buy_fraction_shares(1, 10),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 20,
+                    received: 10,
+                })
+            );
+            vault.bought_out = true;
+            marketplace.marketplace.fraction_vaults.insert(&1, &vault);
+            assert_eq!(
+                marketplace.buy_fraction_shares(1, 10),
+                Err(MarketplaceError::VaultBoughtOut)
+            );
+        }
+
+        #[ink::test]
+        fn redeem_value_works() {
+            let marketplace = init_contract();
+            let accounts = default_accounts();
+            let mut vault = fraction_vault(accounts.bob);
+            assert_eq!(marketplace.redeem_value(&vault, 10), Ok(0));
+
+            vault.buyout = Some(Buyout {
+                bidder: accounts.charlie,
+                bid: 1000,
+                end_time: 0,
+            });
+            assert_eq!(marketplace.redeem_value(&vault, 10), Ok(100));
+            assert_eq!(marketplace.redeem_value(&vault, 3), Ok(30));
+            assert_eq!(marketplace.redeem_value(&vault, 100), Ok(1000));
+        }
+
+        #[ink::test]
+        fn set_xcm_origin_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.set_xcm_origin(accounts.eve, true).is_ok());
+            assert!(marketplace.is_xcm_origin(accounts.eve));
+            assert!(marketplace.set_xcm_origin(accounts.eve, false).is_ok());
+            assert!(!marketplace.is_xcm_origin(accounts.eve));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_xcm_origin(accounts.eve, true),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn buy_via_xcm_fails_without_xcm_feature() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace.set_xcm_origin(accounts.eve, true).is_ok());
+
+            set_sender(accounts.eve);
+            assert_eq!(
+                marketplace.buy_via_xcm(contract_address(), Id::U128(1), accounts.bob),
+                Err(MarketplaceError::XcmNotSupported)
+            );
+        }
+
+        #[ink::test]
+        fn claim_xcm_reservation_fails_if_not_origin_or_beneficiary() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert_eq!(
+                marketplace.claim_xcm_reservation(1, accounts.bob),
+                Err(MarketplaceError::XcmReservationNotFound)
+            );
+
+            marketplace.marketplace.xcm_reservations.insert(
+                &1,
+                &XcmReservation {
+                    origin: accounts.eve,
+                    beneficiary: accounts.bob,
+                    contract_address: contract_address(),
+                    token_id: Id::U128(1),
                 },
-            )
+            );
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.claim_xcm_reservation(1, accounts.charlie),
+                Err(MarketplaceError::NotOwner)
+            );
         }
-    }
 
-    impl MarketplaceSale for MarketplaceContract {}
+        #[ink::test]
+        fn buy_in_installments_fails_if_single_installment() {
+            let mut marketplace = init_contract();
 
-    // ***************************** Tests *******************************
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use crate::marketplace::MarketplaceContract;
-        use ink::env::test;
-        use openbrush::contracts::psp34::Id;
-        use pallet_marketplace::impls::marketplace::types::{MarketplaceError, NftContractType};
+            assert_eq!(
+                marketplace.buy_in_installments(contract_address(), Id::U128(1), 1),
+                Err(MarketplaceError::InvalidInstallments)
+            );
+        }
 
         #[ink::test]
-        fn new_works() {
-            let marketplace = init_contract();
-            assert_eq!(marketplace.get_marketplace_fee(), 100);
-            assert_eq!(marketplace.get_max_fee(), 1000);
-            assert_eq!(marketplace.get_fee_recipient(), fee_recipient());
+        fn pay_installment_fails_if_plan_not_found() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.pay_installment(1),
+                Err(MarketplaceError::InstallmentPlanNotFound)
+            );
+            assert_eq!(
+                marketplace.claim_defaulted_installments(1),
+                Err(MarketplaceError::InstallmentPlanNotFound)
+            );
         }
 
         #[ink::test]
-        fn set_marketplace_fee_works() {
+        fn create_group_purchase_fails_if_not_listed() {
             let mut marketplace = init_contract();
 
-            assert!(marketplace.set_marketplace_fee(120).is_ok());
-            assert_eq!(marketplace.get_marketplace_fee(), 120);
+            assert_eq!(
+                marketplace.create_group_purchase(contract_address(), Id::U128(1), 1000),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
         }
 
         #[ink::test]
-        fn set_marketplace_fee_fails_if_not_owner() {
+        fn contribute_fails_if_group_purchase_not_found() {
             let mut marketplace = init_contract();
 
+            assert_eq!(
+                marketplace.contribute(1),
+                Err(MarketplaceError::GroupPurchaseNotFound)
+            );
+            assert_eq!(marketplace.get_contribution(1, default_accounts().bob), 0);
+            assert_eq!(marketplace.get_group_share(1, default_accounts().bob), 0);
+        }
+
+        #[ink::test]
+        fn propose_trade_works() {
+            let mut marketplace = init_contract();
             let accounts = default_accounts();
-            set_sender(accounts.bob);
+            let offered = vec![TradeAsset::Psp34(contract_address(), Id::U128(1))];
+            let requested = vec![
+                TradeAsset::Psp22(currency_address(), 50),
+                TradeAsset::Native(100),
+            ];
+
             assert_eq!(
-                marketplace.set_marketplace_fee(120),
-                Err(MarketplaceError::OwnableError(
-                    OwnableError::CallerIsNotOwner
-                ))
+                marketplace.propose_trade(Some(accounts.bob), offered.clone(), requested.clone()),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.get_trade(1),
+                Some(Trade {
+                    maker: accounts.alice,
+                    taker: Some(accounts.bob),
+                    offered,
+                    requested,
+                })
+            );
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.accept_trade(1),
+                Err(MarketplaceError::NotTradeTaker)
             );
+            assert_eq!(marketplace.cancel_trade(1), Err(MarketplaceError::NotOwner));
+            set_sender(accounts.alice);
+            assert!(marketplace.cancel_trade(1).is_ok());
+            assert_eq!(marketplace.get_trade(1), None);
         }
 
         #[ink::test]
-        fn set_marketplace_fee_fails_if_fee_too_high() {
+        fn recovery_works() {
             let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let offered = vec![TradeAsset::Psp34(contract_address(), Id::U128(1))];
+            let requested = vec![TradeAsset::Native(100)];
+            assert!(marketplace
+                .propose_trade(Some(accounts.bob), offered, requested)
+                .is_ok());
 
             assert_eq!(
-                marketplace.set_marketplace_fee(1001),
-                Err(MarketplaceError::FeeTooHigh)
+                marketplace.queue_recovery(HeldAsset::Escrow(1)),
+                Err(MarketplaceError::EscrowNotFound)
+            );
+            assert_eq!(marketplace.queue_recovery(HeldAsset::Trade(1)), Ok(1));
+            assert_eq!(
+                marketplace.get_recovery(1),
+                Some(PendingRecovery {
+                    asset: HeldAsset::Trade(1),
+                    eta: 0,
+                })
+            );
+            assert!(marketplace.execute_recovery(1).is_ok());
+            assert_eq!(marketplace.get_trade(1), None);
+            assert_eq!(marketplace.get_recovery(1), None);
+            assert_eq!(
+                marketplace.execute_recovery(1),
+                Err(MarketplaceError::RecoveryNotFound)
             );
-            assert!(marketplace.set_marketplace_fee(1000).is_ok());
         }
 
         #[ink::test]
-        fn set_fee_recipient_works() {
+        fn recovery_is_timelocked() {
             let mut marketplace = init_contract();
             let accounts = default_accounts();
+            let offered = vec![TradeAsset::Psp34(contract_address(), Id::U128(1))];
+            let requested = vec![TradeAsset::Native(100)];
+            assert!(marketplace
+                .propose_trade(Some(accounts.bob), offered, requested)
+                .is_ok());
+            assert!(marketplace.set_timelock_delay(1000).is_ok());
 
-            assert!(marketplace.set_fee_recipient(accounts.bob).is_ok());
-            assert_eq!(marketplace.get_fee_recipient(), accounts.bob);
+            assert_eq!(marketplace.queue_recovery(HeldAsset::Trade(1)), Ok(1));
+            assert_eq!(
+                marketplace.execute_recovery(1),
+                Err(MarketplaceError::TimelockNotExpired)
+            );
+            assert!(marketplace.cancel_recovery(1).is_ok());
+            assert_eq!(marketplace.get_recovery(1), None);
+            assert!(marketplace.get_trade(1).is_some());
         }
 
         #[ink::test]
-        fn set_fee_recipient_fails_if_not_owner() {
+        fn queue_recovery_fails_if_not_owner() {
             let mut marketplace = init_contract();
             let accounts = default_accounts();
             set_sender(accounts.bob);
 
             assert_eq!(
-                marketplace.set_fee_recipient(accounts.bob),
+                marketplace.queue_recovery(HeldAsset::Trade(1)),
                 Err(MarketplaceError::OwnableError(
                     OwnableError::CallerIsNotOwner
                 ))
@@ -295,111 +10271,121 @@ pub mod marketplace {
         }
 
         #[ink::test]
-        fn buy_fails_if_unlisted_token() {
+        fn propose_trade_fails_if_one_side_empty() {
             let mut marketplace = init_contract();
 
             assert_eq!(
-                marketplace.buy(contract_address(), Id::U128(1)),
-                Err(MarketplaceError::ItemNotListedForSale)
+                marketplace.propose_trade(
+                    None,
+                    vec![TradeAsset::Psp34(contract_address(), Id::U128(1))],
+                    Vec::new()
+                ),
+                Err(MarketplaceError::EmptyTrade)
             );
         }
 
         #[ink::test]
-        fn register_contract_works() {
+        fn propose_trade_fails_if_native_value_mismatch() {
             let mut marketplace = init_contract();
 
-            assert!(marketplace
-                .register(
-                    contract_address(),
-                    Some(fee_recipient()),
-                    Some(999),
-                    NftContractType::Psp34
-                )
-                .is_ok());
-            let contract = marketplace
-                .get_registered_collection(contract_address())
-                .unwrap();
-            assert_eq!(contract.royalty.unwrap().0, fee_recipient());
-            assert_eq!(contract.royalty.unwrap().1, 999);
-            assert_eq!(1, ink::env::test::recorded_events().count());
+            assert_eq!(
+                marketplace.propose_trade(
+                    None,
+                    vec![TradeAsset::Native(100)],
+                    vec![TradeAsset::Psp34(contract_address(), Id::U128(1))]
+                ),
+                Err(MarketplaceError::BadBuyValue {
+                    expected: 100,
+                    received: 0,
+                })
+            );
         }
 
         #[ink::test]
-        fn register_fails_if_fee_too_high() {
+        fn grant_credit_works() {
             let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            test::set_value_transferred::<Environment>(100);
 
+            assert!(marketplace.grant_credit(accounts.bob, 1000).is_ok());
             assert_eq!(
-                marketplace.register(
-                    contract_address(),
-                    Some(fee_recipient()),
-                    Some(1001),
-                    NftContractType::Psp34
-                ),
-                Err(MarketplaceError::FeeTooHigh)
+                marketplace.get_credit(accounts.bob),
+                Some(Credit {
+                    amount: 100,
+                    expires_at: 1000
+                })
             );
-            assert!(marketplace
-                .register(
-                    contract_address(),
-                    Some(fee_recipient()),
-                    Some(999),
-                    NftContractType::Psp34
-                )
-                .is_ok());
+            assert_eq!(
+                marketplace.reclaim_expired_credit(accounts.bob),
+                Err(MarketplaceError::CreditNotExpired)
+            );
+            test::set_block_timestamp::<Environment>(1000);
+            assert_eq!(marketplace.get_credit(accounts.bob), None);
         }
 
         #[ink::test]
-        fn register_fails_if_contract_already_registered() {
+        fn grant_credit_fails_if_not_issuer() {
             let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
 
-            assert!(marketplace
-                .register(
-                    contract_address(),
-                    Some(fee_recipient()),
-                    Some(999),
-                    NftContractType::Psp34
-                )
-                .is_ok());
             assert_eq!(
-                marketplace.register(
-                    contract_address(),
-                    Some(fee_recipient()),
-                    Some(999),
-                    NftContractType::Psp34
-                ),
-                Err(MarketplaceError::ContractAlreadyRegistered)
+                marketplace.grant_credit(accounts.charlie, 1000),
+                Err(MarketplaceError::NotCreditIssuer)
             );
+            set_sender(accounts.alice);
+            assert!(marketplace.grant_role(CREDIT_ISSUER, accounts.bob).is_ok());
+            set_sender(accounts.bob);
+            assert!(marketplace.grant_credit(accounts.charlie, 1000).is_ok());
         }
 
         #[ink::test]
-        fn set_nft_contract_hash_works() {
+        fn before_received_records_depositor() {
             let mut marketplace = init_contract();
-            let hash = Hash::try_from([1; 32]).unwrap();
-            let hash2 = Hash::try_from([2; 32]).unwrap();
-
-            assert!(marketplace
-                .set_nft_contract_hash(NftContractType::Rmrk, hash)
-                .is_ok());
-            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash);
+            let accounts = default_accounts();
+            set_sender(contract_address());
+            // as while the marketplace transfers the token into custody
+            marketplace.marketplace.custody_transfer.set(&true);
 
-            // Check also if owner is able to update hash.
             assert!(marketplace
-                .set_nft_contract_hash(NftContractType::Rmrk, hash2)
+                .before_received(
+                    test::callee::<Environment>(),
+                    accounts.bob,
+                    Id::U128(1),
+                    Vec::new()
+                )
                 .is_ok());
-            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash2);
+            assert_eq!(
+                marketplace.get_custody_depositor(contract_address(), Id::U128(1)),
+                Some(accounts.bob)
+            );
+            assert_eq!(
+                marketplace.get_custody_depositor(contract_address(), Id::U128(2)),
+                None
+            );
         }
 
         #[ink::test]
-        fn set_nft_contract_fails_if_not_owner() {
+        fn before_received_rejects_unsolicited_transfer() {
             let mut marketplace = init_contract();
-            let hash = Hash::try_from([1; 32]).unwrap();
             let accounts = default_accounts();
-            set_sender(accounts.bob);
+            set_sender(contract_address());
 
+            assert!(marketplace
+                .before_received(accounts.bob, accounts.bob, Id::U128(1), Vec::new())
+                .is_err());
+            // the marketplace operating a transfer it didn't start
+            assert!(marketplace
+                .before_received(
+                    test::callee::<Environment>(),
+                    accounts.bob,
+                    Id::U128(1),
+                    Vec::new()
+                )
+                .is_err());
             assert_eq!(
-                marketplace.set_nft_contract_hash(NftContractType::Rmrk, hash),
-                Err(MarketplaceError::OwnableError(
-                    OwnableError::CallerIsNotOwner
-                ))
+                marketplace.get_custody_depositor(contract_address(), Id::U128(1)),
+                None
             );
         }
 
@@ -407,6 +10393,21 @@ pub mod marketplace {
             MarketplaceContract::new(fee_recipient())
         }
 
+        /// Loads the contract from storage like a message dispatched to it.
+        fn load_contract() -> MarketplaceContract {
+            ink::env::get_contract_storage(&<MarketplaceContract as StorageKey>::KEY)
+                .unwrap()
+                .unwrap()
+        }
+
+        fn register_mocked_collection(marketplace: &mut MarketplaceContract) {
+            mock::set_collection_owner(contract_address(), default_accounts().alice);
+            set_sender(default_accounts().alice);
+            assert!(marketplace
+                .register(contract_address(), None, None, NftContractType::Psp34)
+                .is_ok());
+        }
+
         fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
             test::default_accounts::<Environment>()
         }
@@ -422,5 +10423,624 @@ pub mod marketplace {
         fn contract_address() -> AccountId {
             AccountId::from([0x2; 32])
         }
+
+        fn coupon_hash(code: &[u8]) -> [u8; 32] {
+            let mut hash = [0; 32];
+            ink::env::hash_bytes::<ink::env::hash::Blake2x256>(code, &mut hash);
+            hash
+        }
+
+        fn evm_contract_address() -> AccountId {
+            let mut address = [0; 32];
+            address[..20].copy_from_slice(&[0x3; 20]);
+            AccountId::from(address)
+        }
+
+        fn currency_address() -> AccountId {
+            AccountId::from([0x3; 32])
+        }
+
+        fn currency_metadata() -> CurrencyMetadata {
+            CurrencyMetadata {
+                symbol: String::from("USDT"),
+                decimals: 6,
+            }
+        }
+
+        fn escrow_item(seller: AccountId, buyer: AccountId) -> EscrowItem {
+            EscrowItem {
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                seller,
+                buyer,
+                price: 100,
+                currency: Currency::Native,
+                release_time: 1000,
+                disputed: false,
+                attribution: SaleAttribution::default(),
+            }
+        }
+
+        fn fraction_vault(owner: AccountId) -> FractionVault {
+            FractionVault {
+                owner,
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                share_token: currency_address(),
+                total_shares: 100,
+                shares_for_sale: 50,
+                share_price: 2,
+                reserve_price: 100,
+                buyout: None,
+                bought_out: false,
+            }
+        }
+
+        fn mystery_box(creator: AccountId) -> MysteryBox {
+            MysteryBox {
+                creator,
+                contract_address: contract_address(),
+                price: 100,
+                tokens: vec![Id::U128(1), Id::U128(2)],
+                pending: 0,
+                seed: [0; 32],
+            }
+        }
+
+        fn raffle(owner: AccountId, status: RaffleStatus) -> Raffle {
+            Raffle {
+                owner,
+                contract_address: contract_address(),
+                token_id: Id::U128(1),
+                ticket_price: 10,
+                max_tickets: 5,
+                min_tickets: 3,
+                deadline: 1000,
+                commitment: [1; 32],
+                seed: [0; 32],
+                tickets_sold: 0,
+                entries: 0,
+                status,
+            }
+        }
+    }
+
+    // ************************** E2E Tests ******************************
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::{build_message, AccountKeyring};
+        use openbrush::contracts::psp34::{psp34_external::PSP34, Id};
+        use pallet_marketplace::traits::{
+            custody::marketplacecustody_external::MarketplaceCustody as _,
+            deposit::marketplacedeposit_external::MarketplaceDeposit as _,
+            escrow::marketplaceescrow_external::MarketplaceEscrow as _,
+            factory::marketplacefactory_external::MarketplaceFactory as _,
+            marketplace::marketplacesale_external::MarketplaceSale as _,
+            offer::marketplaceoffer_external::MarketplaceOffer as _,
+        };
+        use test_psp34::test_psp34::TestPsp34Ref;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const PRICE: Balance = 1_000_000_000_000;
+        const ROYALTY: u16 = 500;
+
+        #[ink_e2e::test(additional_contracts = "../test_psp34/Cargo.toml")]
+        async fn buy_pays_seller_royalty_and_fee(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let fee_recipient = ink_e2e::account_id(AccountKeyring::Ferdie);
+            let royalty_receiver = ink_e2e::account_id(AccountKeyring::Eve);
+            let bob = ink_e2e::account_id(AccountKeyring::Bob);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let marketplace = client
+                .instantiate(
+                    "marketplace",
+                    &ink_e2e::alice(),
+                    MarketplaceContractRef::new(fee_recipient),
+                    0,
+                    None,
+                )
+                .await
+                .expect("marketplace instantiate failed")
+                .account_id;
+            // the collection mints token 1 to its deployer
+            let psp34 = client
+                .instantiate("test_psp34", &ink_e2e::bob(), TestPsp34Ref::new(), 0, None)
+                .await
+                .expect("psp34 instantiate failed")
+                .account_id;
+
+            let register = build_message::<MarketplaceContractRef>(marketplace.clone()).call(|m| {
+                m.register(
+                    psp34,
+                    Some(royalty_receiver),
+                    Some(ROYALTY),
+                    NftContractType::Psp34,
+                )
+            });
+            client
+                .call(&ink_e2e::alice(), register, 0, None)
+                .await
+                .expect("register failed");
+            let approve = build_message::<TestPsp34Ref>(psp34.clone())
+                .call(|c| c.approve(marketplace, Some(Id::U8(1)), true));
+            client
+                .call(&ink_e2e::bob(), approve, 0, None)
+                .await
+                .expect("approve failed");
+            let list = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.list(psp34, Id::U8(1), PRICE));
+            client
+                .call(&ink_e2e::bob(), list, 0, None)
+                .await
+                .expect("list failed");
+
+            let seller_balance = client.balance(bob).await?;
+            let royalty_balance = client.balance(royalty_receiver).await?;
+            let fee_balance = client.balance(fee_recipient).await?;
+            let buy = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.buy(psp34, Id::U8(1)));
+            client
+                .call(&ink_e2e::charlie(), buy, PRICE, None)
+                .await
+                .expect("buy failed");
+
+            let owner_of =
+                build_message::<TestPsp34Ref>(psp34.clone()).call(|c| c.owner_of(Id::U8(1)));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+                    .await
+                    .return_value(),
+                Some(charlie)
+            );
+            // 1% marketplace fee and 5% royalty are taken from the price
+            assert_eq!(
+                client.balance(bob).await?,
+                seller_balance + PRICE * 94 / 100
+            );
+            assert_eq!(
+                client.balance(royalty_receiver).await?,
+                royalty_balance + PRICE * 5 / 100
+            );
+            assert_eq!(
+                client.balance(fee_recipient).await?,
+                fee_balance + PRICE / 100
+            );
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../test_psp34/Cargo.toml")]
+        async fn accepted_offer_settles_from_deposit(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let fee_recipient = ink_e2e::account_id(AccountKeyring::Ferdie);
+            let royalty_receiver = ink_e2e::account_id(AccountKeyring::Eve);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let marketplace = client
+                .instantiate(
+                    "marketplace",
+                    &ink_e2e::alice(),
+                    MarketplaceContractRef::new(fee_recipient),
+                    0,
+                    None,
+                )
+                .await
+                .expect("marketplace instantiate failed")
+                .account_id;
+            let psp34 = client
+                .instantiate("test_psp34", &ink_e2e::bob(), TestPsp34Ref::new(), 0, None)
+                .await
+                .expect("psp34 instantiate failed")
+                .account_id;
+            let register = build_message::<MarketplaceContractRef>(marketplace.clone()).call(|m| {
+                m.register(
+                    psp34,
+                    Some(royalty_receiver),
+                    Some(ROYALTY),
+                    NftContractType::Psp34,
+                )
+            });
+            client
+                .call(&ink_e2e::alice(), register, 0, None)
+                .await
+                .expect("register failed");
+
+            let deposit =
+                build_message::<MarketplaceContractRef>(marketplace.clone()).call(|m| m.deposit());
+            client
+                .call(&ink_e2e::charlie(), deposit, 2 * PRICE, None)
+                .await
+                .expect("deposit failed");
+            let make_offer = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.make_offer(psp34, Some(Id::U8(1)), 1, PRICE, String::new()));
+            let offer_id = client
+                .call(&ink_e2e::charlie(), make_offer, 0, None)
+                .await
+                .expect("make offer failed")
+                .return_value()
+                .expect("make offer returned error");
+
+            // accepting fails until the marketplace may move the token
+            let accept = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.accept_offer(offer_id, Id::U8(1)));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::bob(), &accept, 0, None)
+                    .await
+                    .return_value(),
+                Err(MarketplaceError::TokenNotApproved)
+            );
+            let approve = build_message::<TestPsp34Ref>(psp34.clone())
+                .call(|c| c.approve(marketplace, Some(Id::U8(1)), true));
+            client
+                .call(&ink_e2e::bob(), approve, 0, None)
+                .await
+                .expect("approve failed");
+            let royalty_balance = client.balance(royalty_receiver).await?;
+            client
+                .call(&ink_e2e::bob(), accept, 0, None)
+                .await
+                .expect("accept offer failed");
+
+            let owner_of =
+                build_message::<TestPsp34Ref>(psp34.clone()).call(|c| c.owner_of(Id::U8(1)));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+                    .await
+                    .return_value(),
+                Some(charlie)
+            );
+            assert_eq!(
+                client.balance(royalty_receiver).await?,
+                royalty_balance + PRICE * 5 / 100
+            );
+            let get_deposit = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.get_deposit(charlie));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::charlie(), &get_deposit, 0, None)
+                    .await
+                    .return_value(),
+                PRICE
+            );
+
+            // the rest of the deposit can be withdrawn
+            let withdraw = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.withdraw(PRICE));
+            client
+                .call(&ink_e2e::charlie(), withdraw, 0, None)
+                .await
+                .expect("withdraw failed");
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::charlie(), &get_deposit, 0, None)
+                    .await
+                    .return_value(),
+                0
+            );
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../test_psp34/Cargo.toml")]
+        async fn escrowed_sale_takes_token_into_custody(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let fee_recipient = ink_e2e::account_id(AccountKeyring::Ferdie);
+            let bob = ink_e2e::account_id(AccountKeyring::Bob);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let marketplace = client
+                .instantiate(
+                    "marketplace",
+                    &ink_e2e::alice(),
+                    MarketplaceContractRef::new(fee_recipient),
+                    0,
+                    None,
+                )
+                .await
+                .expect("marketplace instantiate failed")
+                .account_id;
+            let psp34 = client
+                .instantiate("test_psp34", &ink_e2e::bob(), TestPsp34Ref::new(), 0, None)
+                .await
+                .expect("psp34 instantiate failed")
+                .account_id;
+            let register = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.register(psp34, None, None, NftContractType::Psp34));
+            client
+                .call(&ink_e2e::alice(), register, 0, None)
+                .await
+                .expect("register failed");
+            let approve = build_message::<TestPsp34Ref>(psp34.clone())
+                .call(|c| c.approve(marketplace, Some(Id::U8(1)), true));
+            client
+                .call(&ink_e2e::bob(), approve, 0, None)
+                .await
+                .expect("approve failed");
+            let list = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.list_with_escrow(psp34, Id::U8(1), PRICE));
+            client
+                .call(&ink_e2e::bob(), list, 0, None)
+                .await
+                .expect("list failed");
+
+            // the collection calls back into the marketplace while moving the token
+            let buy = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.buy(psp34, Id::U8(1)));
+            client
+                .call(&ink_e2e::charlie(), buy, PRICE, None)
+                .await
+                .expect("buy failed");
+
+            let owner_of =
+                build_message::<TestPsp34Ref>(psp34.clone()).call(|c| c.owner_of(Id::U8(1)));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+                    .await
+                    .return_value(),
+                Some(marketplace)
+            );
+            let get_depositor = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.get_custody_depositor(psp34, Id::U8(1)));
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &get_depositor, 0, None)
+                    .await
+                    .return_value(),
+                Some(bob)
+            );
+
+            let release = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.release_escrow(1));
+            client
+                .call(&ink_e2e::charlie(), release, 0, None)
+                .await
+                .expect("release escrow failed");
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+                    .await
+                    .return_value(),
+                Some(charlie)
+            );
+            assert_eq!(
+                client
+                    .call_dry_run(&ink_e2e::alice(), &get_depositor, 0, None)
+                    .await
+                    .return_value(),
+                None
+            );
+            Ok(())
+        }
+    }
+
+    // ************************* E2E Benchmarks **************************
+    #[cfg(all(test, feature = "e2e-bench"))]
+    mod e2e_benchmarks {
+        use super::*;
+        use ink_e2e::{build_message, AccountKeyring};
+        use openbrush::contracts::psp34::{
+            extensions::mintable::psp34mintable_external::PSP34Mintable, psp34_external::PSP34, Id,
+        };
+        use pallet_marketplace::traits::{
+            deposit::marketplacedeposit_external::MarketplaceDeposit as _,
+            factory::marketplacefactory_external::MarketplaceFactory as _,
+            marketplace::marketplacesale_external::MarketplaceSale as _,
+            offer::marketplaceoffer_external::MarketplaceOffer as _,
+        };
+        use std::{fmt::Write, fs, path::PathBuf};
+        use test_psp34::test_psp34::TestPsp34Ref;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const PRICE: Balance = 1_000_000_000_000;
+        /// Numbers of listings and offers that messages are measured with.
+        const SIZES: [u64; 3] = [1, 10, 50];
+
+        /// Dry-runs a message and appends its required weight to the report.
+        macro_rules! measure {
+            ($client:ident, $report:ident, $signer:expr, $name:expr, $message:expr, $value:expr, $size:expr) => {{
+                let weight = $client
+                    .call_dry_run($signer, &$message, $value, None)
+                    .await
+                    .exec_result
+                    .gas_required;
+                writeln!(
+                    $report,
+                    "{},{},{},{}",
+                    $name,
+                    $size,
+                    weight.ref_time(),
+                    weight.proof_size()
+                )?;
+            }};
+        }
+
+        /// Measures weight of hot messages and enumeration queries against a live node and writes
+        /// them as CSV to `WEIGHT_REPORT`, `target/weights/marketplace.csv` by default.
+        #[ink_e2e::test(additional_contracts = "../test_psp34/Cargo.toml")]
+        async fn benchmark_weights(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mut report = String::from("message,size,ref_time,proof_size\n");
+            let bob = ink_e2e::account_id(AccountKeyring::Bob);
+            let charlie = ink_e2e::account_id(AccountKeyring::Charlie);
+            let marketplace = client
+                .instantiate(
+                    "marketplace",
+                    &ink_e2e::alice(),
+                    MarketplaceContractRef::new(ink_e2e::account_id(AccountKeyring::Ferdie)),
+                    0,
+                    None,
+                )
+                .await
+                .expect("marketplace instantiate failed")
+                .account_id;
+            let psp34 = client
+                .instantiate("test_psp34", &ink_e2e::bob(), TestPsp34Ref::new(), 0, None)
+                .await
+                .expect("psp34 instantiate failed")
+                .account_id;
+            let register = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.register(psp34, None, None, NftContractType::Psp34));
+            client
+                .call(&ink_e2e::alice(), register, 0, None)
+                .await
+                .expect("register failed");
+            let approve = build_message::<TestPsp34Ref>(psp34.clone())
+                .call(|c| c.approve(marketplace, None, true));
+            client
+                .call(&ink_e2e::bob(), approve, 0, None)
+                .await
+                .expect("approve failed");
+            let deposit =
+                build_message::<MarketplaceContractRef>(marketplace.clone()).call(|m| m.deposit());
+            client
+                .call(
+                    &ink_e2e::charlie(),
+                    deposit,
+                    PRICE * SIZES[2] as Balance,
+                    None,
+                )
+                .await
+                .expect("deposit failed");
+
+            // listings and offers grow one token at a time and are measured at each size
+            let mut offer_ids = Vec::new();
+            for token_id in 1..=SIZES[2] {
+                let mint = build_message::<TestPsp34Ref>(psp34.clone())
+                    .call(|c| c.mint(bob, Id::U64(token_id)));
+                client
+                    .call(&ink_e2e::bob(), mint, 0, None)
+                    .await
+                    .expect("mint failed");
+                let list = build_message::<MarketplaceContractRef>(marketplace.clone())
+                    .call(|m| m.list(psp34, Id::U64(token_id), PRICE));
+                let make_offer =
+                    build_message::<MarketplaceContractRef>(marketplace.clone()).call(|m| {
+                        m.make_offer(psp34, Some(Id::U64(token_id)), 1, PRICE, String::new())
+                    });
+                if SIZES.contains(&token_id) {
+                    measure!(client, report, &ink_e2e::bob(), "list", list, 0, token_id);
+                    measure!(
+                        client,
+                        report,
+                        &ink_e2e::charlie(),
+                        "make_offer",
+                        make_offer,
+                        0,
+                        token_id
+                    );
+                }
+                client
+                    .call(&ink_e2e::bob(), list, 0, None)
+                    .await
+                    .expect("list failed");
+                offer_ids.push(
+                    client
+                        .call(&ink_e2e::charlie(), make_offer, 0, None)
+                        .await
+                        .expect("make offer failed")
+                        .return_value()
+                        .expect("make offer returned error"),
+                );
+                if !SIZES.contains(&token_id) {
+                    continue
+                }
+
+                let size = token_id as u32;
+                let tokens = (1..=token_id)
+                    .map(|token_id| (psp34, Id::U64(token_id)))
+                    .collect::<Vec<_>>();
+                let by_collection = build_message::<MarketplaceContractRef>(marketplace.clone())
+                    .call(|m| m.get_listings_by_collection(psp34, 0, size));
+                measure!(
+                    client,
+                    report,
+                    &ink_e2e::alice(),
+                    "get_listings_by_collection",
+                    by_collection,
+                    0,
+                    size
+                );
+                let by_account = build_message::<MarketplaceContractRef>(marketplace.clone())
+                    .call(|m| m.get_listings_by_account(bob, 0, size));
+                measure!(
+                    client,
+                    report,
+                    &ink_e2e::alice(),
+                    "get_listings_by_account",
+                    by_account,
+                    0,
+                    size
+                );
+                let get_prices = build_message::<MarketplaceContractRef>(marketplace.clone())
+                    .call(|m| m.get_prices(tokens.clone()));
+                measure!(
+                    client,
+                    report,
+                    &ink_e2e::alice(),
+                    "get_prices",
+                    get_prices,
+                    0,
+                    size
+                );
+                let get_offers_active =
+                    build_message::<MarketplaceContractRef>(marketplace.clone())
+                        .call(|m| m.get_offers_active(offer_ids.clone()));
+                measure!(
+                    client,
+                    report,
+                    &ink_e2e::alice(),
+                    "get_offers_active",
+                    get_offers_active,
+                    0,
+                    size
+                );
+            }
+
+            let buy = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.buy(psp34, Id::U64(1)));
+            measure!(client, report, &ink_e2e::charlie(), "buy", buy, PRICE, 1);
+            let accept = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.accept_offer(offer_ids[1], Id::U64(2)));
+            measure!(
+                client,
+                report,
+                &ink_e2e::bob(),
+                "accept_offer",
+                accept,
+                0,
+                1
+            );
+            let unlist = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.unlist(psp34, Id::U64(3)));
+            measure!(client, report, &ink_e2e::bob(), "unlist", unlist, 0, 1);
+            let get_deposit = build_message::<MarketplaceContractRef>(marketplace.clone())
+                .call(|m| m.get_deposit(charlie));
+            measure!(
+                client,
+                report,
+                &ink_e2e::alice(),
+                "get_deposit",
+                get_deposit,
+                0,
+                1
+            );
+
+            let path = std::env::var("WEIGHT_REPORT")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| {
+                    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                        .join("../../target/weights/marketplace.csv")
+                });
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, &report)?;
+            Ok(())
+        }
     }
 }
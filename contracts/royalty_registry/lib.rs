@@ -0,0 +1,146 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod royalty_registry {
+    use ink::{
+        codegen::{EmitEvent, Env},
+        env::DefaultEnvironment,
+        EnvAccess,
+    };
+    use openbrush::{contracts::ownable::*, traits::Storage};
+    use pallet_marketplace::{
+        helpers::events::EVENT_SCHEMA_VERSION,
+        impls::royalty_registry::{royalty_registry::RoyaltyRegistryEvents, types, *},
+        traits::royalty_registry::*,
+    };
+
+    // RoyaltyRegistryContract contract storage
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct RoyaltyRegistryContract {
+        #[storage_field]
+        ownable: ownable::Data,
+        #[storage_field]
+        registry: types::Data,
+    }
+
+    /// Event emitted when royalty of a collection is set.
+    #[ink(event)]
+    pub struct RoyaltySet {
+        version: u8,
+        #[ink(topic)]
+        contract: AccountId,
+        royalty: Option<(AccountId, u16)>,
+    }
+
+    impl RoyaltyRegistryContract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            let mut instance = Self::default();
+            let caller = instance.env().caller();
+            instance._init_with_owner(caller);
+            instance
+        }
+    }
+
+    impl RoyaltyRegistryEvents for RoyaltyRegistryContract {
+        fn emit_royalty_set_event(
+            &self,
+            contract_address: AccountId,
+            royalty: Option<(AccountId, u16)>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<RoyaltyRegistryContract>>::emit_event::<
+                RoyaltySet,
+            >(
+                self.env(),
+                RoyaltySet {
+                    version: EVENT_SCHEMA_VERSION,
+                    contract: contract_address,
+                    royalty,
+                },
+            )
+        }
+    }
+
+    impl RoyaltyRegistry for RoyaltyRegistryContract {}
+
+    impl Ownable for RoyaltyRegistryContract {}
+
+    // ***************************** Tests *******************************
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+        use pallet_marketplace::impls::{
+            marketplace::types::MarketplaceError, royalty_registry::types::MAX_ROYALTY,
+        };
+
+        #[ink::test]
+        fn set_royalty_works_for_writer() {
+            let mut registry = init_contract();
+            let accounts = default_accounts();
+            assert!(registry.set_writer(accounts.bob, true).is_ok());
+            assert!(registry.is_writer(accounts.bob));
+
+            set_sender(accounts.bob);
+            assert!(registry
+                .set_royalty(contract_address(), Some((accounts.charlie, 500)))
+                .is_ok());
+            assert_eq!(
+                registry.get_royalty(contract_address()),
+                Some((accounts.charlie, 500))
+            );
+            assert_eq!(1, test::recorded_events().count());
+
+            assert!(registry.set_royalty(contract_address(), None).is_ok());
+            assert_eq!(registry.get_royalty(contract_address()), None);
+        }
+
+        #[ink::test]
+        fn set_royalty_fails_if_too_high() {
+            let mut registry = init_contract();
+            let accounts = default_accounts();
+            assert!(registry.set_writer(accounts.alice, true).is_ok());
+
+            assert_eq!(
+                registry.set_royalty(contract_address(), Some((accounts.charlie, 10_001))),
+                Err(MarketplaceError::FeeTooHigh {
+                    max: MAX_ROYALTY,
+                    got: 10_001,
+                })
+            );
+        }
+
+        #[ink::test]
+        fn set_writer_fails_if_not_owner() {
+            let mut registry = init_contract();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                registry.set_writer(accounts.bob, true),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+            assert!(!registry.is_writer(accounts.bob));
+        }
+
+        fn init_contract() -> RoyaltyRegistryContract {
+            RoyaltyRegistryContract::new()
+        }
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<Environment>()
+        }
+
+        fn set_sender(sender: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
+        }
+
+        fn contract_address() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+/// Plain PSP34 collection used by marketplace integration tests.
+#[openbrush::contract]
+pub mod test_psp34 {
+    use openbrush::{
+        contracts::psp34::extensions::{burnable::*, metadata::*, mintable::*},
+        traits::Storage,
+    };
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct TestPsp34 {
+        #[storage_field]
+        psp34: psp34::Data,
+        #[storage_field]
+        metadata: metadata::Data,
+    }
+
+    impl PSP34 for TestPsp34 {}
+
+    impl PSP34Burnable for TestPsp34 {}
+
+    impl PSP34Mintable for TestPsp34 {}
+
+    impl PSP34Metadata for TestPsp34 {}
+
+    impl TestPsp34 {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            let mut instance = Self::default();
+            let caller = instance.env().caller();
+            instance
+                ._mint_to(caller, Id::U8(1))
+                .expect("Can't mint token");
+            instance
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Encoding shared by the events of marketplace contracts.
+//!
+//! Every event starts with a `version` field holding [`EVENT_SCHEMA_VERSION`]. Indexers read it
+//! before the other fields to pick the layout to decode, so later versions can add or change
+//! fields without breaking decoding of events emitted before. The version is bumped whenever
+//! the fields of any event change.
+//!
+//! Prices, amounts and numeric ids that are not topics are SCALE compact encoded. A `Balance`
+//! takes 16 bytes in its fixed width encoding but as few as 1 byte compact encoded, which keeps
+//! event storage cheap as events carry more fields. Topics keep their fixed width encoding as
+//! they are hashed to 32 bytes anyway.
+
+pub use scale::Compact;
+
+/// Schema version of the fields of emitted events.
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
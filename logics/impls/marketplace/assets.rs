@@ -0,0 +1,94 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Transfers of pallet-assets assets (e.g. bridged USDC) through the runtime chain extension.
+//!
+//! The chain extension is only called when the `pallet-assets` feature is enabled, otherwise
+//! every transfer fails with `MarketplaceError::AssetsNotSupported` so the contract can be
+//! deployed on chains without the extension.
+
+use crate::impls::marketplace::types::{AssetId, MarketplaceError};
+use openbrush::traits::{AccountId, Balance};
+
+/// Function id of pallet-assets `transfer` (extension 2, function 2 on Astar).
+pub const TRANSFER_FUNC_ID: u32 = 0x0002_0002;
+/// Function id of pallet-assets `transfer_approved` (extension 2, function 9 on Astar).
+pub const TRANSFER_APPROVED_FUNC_ID: u32 = 0x0002_0009;
+
+/// Non-zero status code returned by the chain extension.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AssetsStatusCode(pub u32);
+
+impl ink::env::chain_extension::FromStatusCode for AssetsStatusCode {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            code => Err(AssetsStatusCode(code)),
+        }
+    }
+}
+
+/// Transfers `amount` of asset owned by the marketplace to `to`.
+#[cfg(feature = "pallet-assets")]
+pub fn transfer(asset_id: AssetId, to: AccountId, amount: Balance) -> Result<(), MarketplaceError> {
+    ink::env::chain_extension::ChainExtensionMethod::build(TRANSFER_FUNC_ID)
+        .input::<(AssetId, AccountId, Balance)>()
+        .output::<(), false>()
+        .handle_error_code::<AssetsStatusCode>()
+        .call(&(asset_id, to, amount))
+        .map_err(|_| MarketplaceError::AssetTransferFailed)
+}
+
+/// Transfers `amount` of asset from `owner` to `to`, the marketplace must be approved
+/// by `owner` through pallet-assets `approve_transfer`.
+#[cfg(feature = "pallet-assets")]
+pub fn transfer_approved(
+    asset_id: AssetId,
+    owner: AccountId,
+    to: AccountId,
+    amount: Balance,
+) -> Result<(), MarketplaceError> {
+    ink::env::chain_extension::ChainExtensionMethod::build(TRANSFER_APPROVED_FUNC_ID)
+        .input::<(AssetId, AccountId, AccountId, Balance)>()
+        .output::<(), false>()
+        .handle_error_code::<AssetsStatusCode>()
+        .call(&(asset_id, owner, to, amount))
+        .map_err(|_| MarketplaceError::AssetTransferFailed)
+}
+
+#[cfg(not(feature = "pallet-assets"))]
+pub fn transfer(
+    _asset_id: AssetId,
+    _to: AccountId,
+    _amount: Balance,
+) -> Result<(), MarketplaceError> {
+    Err(MarketplaceError::AssetsNotSupported)
+}
+
+#[cfg(not(feature = "pallet-assets"))]
+pub fn transfer_approved(
+    _asset_id: AssetId,
+    _owner: AccountId,
+    _to: AccountId,
+    _amount: Balance,
+) -> Result<(), MarketplaceError> {
+    Err(MarketplaceError::AssetsNotSupported)
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Bounded iteration shared by batch messages.
+//!
+//! A batch message handles at most `max_batch_size` items per call. Messages that can make
+//! partial progress take a cursor and return [`BatchProgress`] with the cursor to continue
+//! from, so large inputs are spread over several calls instead of exceeding block limits.
+//! Messages that must handle their input at once reject inputs over the limit.
+
+use super::types::{BatchProgress, MarketplaceError};
+use crate::ensure;
+use core::ops::Range;
+
+/// Max batch size set at deployment.
+pub const DEFAULT_MAX_BATCH_SIZE: u32 = 50;
+
+/// Gets range of the `len` batch items to handle in this call, starting at `cursor`, and the
+/// progress made once they are handled.
+pub fn next_batch(
+    len: usize,
+    cursor: u32,
+    max_batch_size: u32,
+) -> Result<(Range<usize>, BatchProgress), MarketplaceError> {
+    let start = cursor as usize;
+    ensure!(start <= len, MarketplaceError::InvalidBatchCursor);
+    let end = len.min(start.saturating_add(max_batch_size as usize));
+    let progress = BatchProgress {
+        processed: (end - start) as u32,
+        next: (end < len).then_some(end as u32),
+    };
+    Ok((start..end, progress))
+}
+
+/// Checks that a batch handled in a single call does not exceed the max batch size.
+pub fn check_batch_size(len: usize, max_batch_size: u32) -> Result<(), MarketplaceError> {
+    ensure!(
+        len <= max_batch_size as usize,
+        MarketplaceError::BatchTooLarge
+    );
+    Ok(())
+}
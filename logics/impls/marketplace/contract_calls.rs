@@ -0,0 +1,293 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Cross-contract calls into PSP34 collections.
+//!
+//! Business logic reaches NFT contracts only through these functions. With the
+//! `test-mocks` feature they are served from an in-memory ledger instead, so
+//! listing, offer and settlement flows can be unit-tested off-chain.
+
+#[cfg(not(feature = "test-mocks"))]
+use ink::{
+    env::{CallFlags, DefaultEnvironment},
+    prelude::vec::Vec,
+};
+#[cfg(not(feature = "test-mocks"))]
+use openbrush::contracts::{
+    ownable::*,
+    psp34::{extensions::enumerable::*, *},
+};
+use openbrush::{
+    contracts::psp34::{Id, PSP34Error},
+    traits::AccountId,
+};
+
+#[cfg(not(feature = "test-mocks"))]
+pub fn owner_of(contract_address: AccountId, token_id: &Id) -> Option<AccountId> {
+    PSP34Ref::owner_of(&contract_address, token_id.clone())
+}
+
+#[cfg(not(feature = "test-mocks"))]
+pub fn balance_of(contract_address: AccountId, owner: AccountId) -> u32 {
+    PSP34Ref::balance_of(&contract_address, owner)
+}
+
+#[cfg(not(feature = "test-mocks"))]
+pub fn total_supply(contract_address: AccountId) -> u128 {
+    PSP34Ref::total_supply(&contract_address)
+}
+
+/// Gets token at `index` of a PSP34Enumerable collection.
+#[cfg(not(feature = "test-mocks"))]
+pub fn token_by_index(contract_address: AccountId, index: u128) -> Option<Id> {
+    PSP34EnumerableRef::token_by_index(&contract_address, index).ok()
+}
+
+/// Gets token at `index` of tokens owned by `owner` in a PSP34Enumerable collection.
+#[cfg(not(feature = "test-mocks"))]
+pub fn owners_token_by_index(
+    contract_address: AccountId,
+    owner: AccountId,
+    index: u128,
+) -> Option<Id> {
+    PSP34EnumerableRef::owners_token_by_index(&contract_address, owner, index).ok()
+}
+
+#[cfg(not(feature = "test-mocks"))]
+pub fn allowance(
+    contract_address: AccountId,
+    owner: AccountId,
+    operator: AccountId,
+    token_id: &Id,
+) -> bool {
+    PSP34Ref::allowance(&contract_address, owner, operator, Some(token_id.clone()))
+}
+
+/// Transfers a NFT held or approved to the marketplace. Transfers into custody let the
+/// collection call back `PSP34Receiver::before_received` on the marketplace, callers set
+/// `custody_transfer` so no other message can be re-entered.
+#[cfg(not(feature = "test-mocks"))]
+pub fn transfer(
+    contract_address: AccountId,
+    to: AccountId,
+    token_id: Id,
+) -> Result<(), PSP34Error> {
+    let into_custody = to == ink::env::account_id::<DefaultEnvironment>();
+    PSP34Ref::transfer_builder(&contract_address, to, token_id, Vec::new())
+        .call_flags(CallFlags::default().set_allow_reentry(into_custody))
+        .invoke()
+}
+
+#[cfg(not(feature = "test-mocks"))]
+pub fn collection_owner(contract_address: AccountId) -> AccountId {
+    OwnableRef::owner(&contract_address)
+}
+
+#[cfg(feature = "test-mocks")]
+pub use mock::{
+    allowance, balance_of, collection_owner, owner_of, owners_token_by_index, token_by_index,
+    total_supply, transfer,
+};
+
+/// In-memory PSP34 and Ownable backend used by off-chain tests.
+///
+/// State is thread local, and every `#[ink::test]` runs on its own thread, so
+/// tests start from an empty ledger.
+#[cfg(feature = "test-mocks")]
+pub mod mock {
+    use super::*;
+    use std::{boxed::Box, cell::RefCell, vec::Vec};
+
+    #[derive(Default)]
+    struct Ledger {
+        owners: Vec<(AccountId, Id, AccountId)>,
+        approvals: Vec<(AccountId, AccountId, AccountId, Option<Id>)>,
+        collection_owners: Vec<(AccountId, AccountId)>,
+    }
+
+    std::thread_local! {
+        static LEDGER: RefCell<Ledger> = RefCell::new(Ledger::default());
+        static TRANSFER_HOOK: RefCell<Option<Box<dyn FnMut()>>> = RefCell::new(None);
+    }
+
+    /// Runs `hook` after every transfer, like a collection calling back into the marketplace.
+    pub fn set_transfer_hook(hook: impl FnMut() + 'static) {
+        TRANSFER_HOOK.with(|slot| *slot.borrow_mut() = Some(Box::new(hook)))
+    }
+
+    /// Mints or moves `token_id` of `contract_address` to `owner`.
+    pub fn set_owner(contract_address: AccountId, token_id: Id, owner: AccountId) {
+        LEDGER.with(|ledger| {
+            let mut ledger = ledger.borrow_mut();
+            ledger
+                .owners
+                .retain(|(contract, id, _)| *contract != contract_address || *id != token_id);
+            ledger.owners.push((contract_address, token_id, owner));
+        })
+    }
+
+    /// Lets `operator` transfer `token_id` of `owner`, or all of its tokens when `token_id` is `None`.
+    pub fn approve(
+        contract_address: AccountId,
+        owner: AccountId,
+        operator: AccountId,
+        token_id: Option<Id>,
+    ) {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow_mut()
+                .approvals
+                .push((contract_address, owner, operator, token_id))
+        })
+    }
+
+    /// Sets the Ownable owner of `contract_address`.
+    pub fn set_collection_owner(contract_address: AccountId, owner: AccountId) {
+        LEDGER.with(|ledger| {
+            let mut ledger = ledger.borrow_mut();
+            ledger
+                .collection_owners
+                .retain(|(contract, _)| *contract != contract_address);
+            ledger.collection_owners.push((contract_address, owner));
+        })
+    }
+
+    pub fn owner_of(contract_address: AccountId, token_id: &Id) -> Option<AccountId> {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .owners
+                .iter()
+                .find(|(contract, id, _)| *contract == contract_address && id == token_id)
+                .map(|(_, _, owner)| *owner)
+        })
+    }
+
+    pub fn balance_of(contract_address: AccountId, owner: AccountId) -> u32 {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .owners
+                .iter()
+                .filter(|(contract, _, account)| *contract == contract_address && *account == owner)
+                .count() as u32
+        })
+    }
+
+    pub fn total_supply(contract_address: AccountId) -> u128 {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .owners
+                .iter()
+                .filter(|(contract, _, _)| *contract == contract_address)
+                .count() as u128
+        })
+    }
+
+    /// Enumerates tokens of `contract_address` in the order they were last set.
+    pub fn token_by_index(contract_address: AccountId, index: u128) -> Option<Id> {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .owners
+                .iter()
+                .filter(|(contract, _, _)| *contract == contract_address)
+                .nth(index as usize)
+                .map(|(_, id, _)| id.clone())
+        })
+    }
+
+    pub fn owners_token_by_index(
+        contract_address: AccountId,
+        owner: AccountId,
+        index: u128,
+    ) -> Option<Id> {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .owners
+                .iter()
+                .filter(|(contract, _, account)| *contract == contract_address && *account == owner)
+                .nth(index as usize)
+                .map(|(_, id, _)| id.clone())
+        })
+    }
+
+    pub fn allowance(
+        contract_address: AccountId,
+        owner: AccountId,
+        operator: AccountId,
+        token_id: &Id,
+    ) -> bool {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .approvals
+                .iter()
+                .any(|(contract, from, to, id)| {
+                    *contract == contract_address
+                        && *from == owner
+                        && *to == operator
+                        && id.as_ref().map_or(true, |id| id == token_id)
+                })
+        })
+    }
+
+    /// Transfers on behalf of the calling contract, which must own or be approved for the token.
+    pub fn transfer(
+        contract_address: AccountId,
+        to: AccountId,
+        token_id: Id,
+    ) -> Result<(), PSP34Error> {
+        let operator = ink::env::account_id::<ink::env::DefaultEnvironment>();
+        let owner = owner_of(contract_address, &token_id).ok_or(PSP34Error::TokenNotExists)?;
+        if owner != operator && !allowance(contract_address, owner, operator, &token_id) {
+            return Err(PSP34Error::NotApproved)
+        }
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow_mut()
+                .approvals
+                .retain(|(contract, _, _, id)| {
+                    *contract != contract_address || id.as_ref() != Some(&token_id)
+                })
+        });
+        set_owner(contract_address, token_id, to);
+        if let Some(mut hook) = TRANSFER_HOOK.with(|slot| slot.borrow_mut().take()) {
+            hook();
+            TRANSFER_HOOK.with(|slot| *slot.borrow_mut() = Some(hook));
+        }
+        Ok(())
+    }
+
+    pub fn collection_owner(contract_address: AccountId) -> AccountId {
+        LEDGER.with(|ledger| {
+            ledger
+                .borrow()
+                .collection_owners
+                .iter()
+                .find(|(contract, _)| *contract == contract_address)
+                .map(|(_, owner)| *owner)
+                .unwrap_or_default()
+        })
+    }
+}
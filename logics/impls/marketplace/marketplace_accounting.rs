@@ -0,0 +1,133 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use crate::{
+    impls::marketplace::types::{BalanceSheet, Data},
+    traits::accounting::MarketplaceAccounting,
+};
+use openbrush::traits::{Balance, Storage};
+
+pub trait Internal {
+    /// Records a change of the total native deposits.
+    fn update_deposits(&mut self, old_amount: Balance, new_amount: Balance);
+
+    /// Records funds locked by the marketplace.
+    fn lock_funds(&mut self, amount: Balance);
+
+    /// Records locked funds paid out or returned to deposits.
+    fn unlock_funds(&mut self, amount: Balance);
+
+    /// Records sale proceeds taken into escrow.
+    fn escrow_funds(&mut self, amount: Balance);
+
+    /// Records escrowed proceeds paid out.
+    fn release_escrowed_funds(&mut self, amount: Balance);
+
+    /// Records marketplace fee shares accrued to partners, referrers, curators and stakers.
+    fn accrue_fees(&mut self, amount: Balance);
+
+    /// Records accrued fee shares paid out.
+    fn pay_accrued_fees(&mut self, amount: Balance);
+}
+
+impl<T> MarketplaceAccounting for T
+where
+    T: Storage<Data>,
+{
+    /// Gets native currency the marketplace owes to its users.
+    default fn get_balance_sheet(&self) -> BalanceSheet {
+        self.data::<Data>().balance_sheet.get().unwrap_or_default()
+    }
+
+    /// Checks that the marketplace native balance covers all its liabilities.
+    default fn solvency_check(&self) -> bool {
+        let sheet = self.get_balance_sheet();
+        sheet
+            .deposits
+            .checked_add(sheet.locked)
+            .and_then(|liabilities| liabilities.checked_add(sheet.escrowed))
+            .and_then(|liabilities| liabilities.checked_add(sheet.accrued_fees))
+            .map_or(false, |liabilities| Self::env().balance() >= liabilities)
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn update_deposits(&mut self, old_amount: Balance, new_amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.deposits = sheet
+                .deposits
+                .saturating_sub(old_amount)
+                .saturating_add(new_amount)
+        });
+    }
+
+    default fn lock_funds(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.locked = sheet.locked.saturating_add(amount)
+        });
+    }
+
+    default fn unlock_funds(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.locked = sheet.locked.saturating_sub(amount)
+        });
+    }
+
+    default fn escrow_funds(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.escrowed = sheet.escrowed.saturating_add(amount)
+        });
+    }
+
+    default fn release_escrowed_funds(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.escrowed = sheet.escrowed.saturating_sub(amount)
+        });
+    }
+
+    default fn accrue_fees(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.accrued_fees = sheet.accrued_fees.saturating_add(amount)
+        });
+    }
+
+    default fn pay_accrued_fees(&mut self, amount: Balance) {
+        update_balance_sheet(self, |sheet| {
+            sheet.accrued_fees = sheet.accrued_fees.saturating_sub(amount)
+        });
+    }
+}
+
+fn update_balance_sheet<T>(instance: &mut T, update: impl FnOnce(&mut BalanceSheet))
+where
+    T: Storage<Data>,
+{
+    let mut sheet = instance
+        .data::<Data>()
+        .balance_sheet
+        .get()
+        .unwrap_or_default();
+    update(&mut sheet);
+    instance.data::<Data>().balance_sheet.set(&sheet);
+}
@@ -0,0 +1,303 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    assets,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_multisig::Internal as MultisigInternal,
+    marketplace_sale::Internal as SaleInternal,
+    marketplace_timelock::Internal as TimelockInternal,
+    types::{AssetId, CurrencyMetadata, ParameterChange},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::admin::MarketplaceAdmin,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{access_control, ownable::*, pausable::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+impl<T> MarketplaceAdmin for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>
+        + Storage<access_control::Data>,
+{
+    /// Halts trading on the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn pause(&mut self) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        pausable::Internal::_pause(self)
+    }
+
+    /// Resumes trading on the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn unpause(&mut self) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        pausable::Internal::_unpause(self)
+    }
+
+    /// Sets price oracle, quoted pair and max age (in milliseconds) of an accepted price.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_price_oracle(
+        &mut self,
+        price_oracle: AccountId,
+        pair: String,
+        max_price_age: u64,
+    ) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.data::<Data>().v1.price_oracle = Some(price_oracle);
+        self.data::<Data>().oracle_pair.set(&pair);
+        self.data::<Data>().v1.max_price_age = max_price_age;
+
+        Ok(())
+    }
+
+    /// Gets price oracle.
+    default fn get_price_oracle(&self) -> Option<AccountId> {
+        self.data::<Data>().v1.price_oracle
+    }
+
+    /// Sets compliance registry.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_compliance_registry(
+        &mut self,
+        registry: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.data::<Data>().v1.compliance_registry = registry;
+        Ok(())
+    }
+
+    /// Gets compliance registry.
+    default fn get_compliance_registry(&self) -> Option<AccountId> {
+        self.data::<Data>().v1.compliance_registry
+    }
+
+    /// Sets royalty registry.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_royalty_registry(
+        &mut self,
+        registry: Option<AccountId>,
+    ) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::RoyaltyRegistry(registry))
+    }
+
+    /// Gets royalty registry.
+    default fn get_royalty_registry(&self) -> Option<AccountId> {
+        self.data::<Data>().v1.royalty_registry
+    }
+
+    /// Sets the marketplace fee.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_marketplace_fee(&mut self, fee: u16) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::MarketplaceFee(fee))
+    }
+
+    /// Gets the marketplace fee.
+    default fn get_marketplace_fee(&self) -> u16 {
+        self.data::<Data>().fee
+    }
+
+    /// Sets max fee that can be applied to an item price.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_max_fee(&mut self, max_fee: u16) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::MaxFee(max_fee))
+    }
+
+    /// Gets max fee that can be applied to an item price.
+    default fn get_max_fee(&self) -> u16 {
+        self.data::<Data>().max_fee
+    }
+
+    /// Sets max number of items a batch message handles in one call.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_max_batch_size(&mut self, max_batch_size: u32) -> Result<(), MarketplaceError> {
+        ensure!(max_batch_size > 0, MarketplaceError::InvalidBatchSize);
+        self.data::<Data>().v1.max_batch_size = max_batch_size;
+        Ok(())
+    }
+
+    /// Gets max number of items a batch message handles in one call.
+    default fn get_max_batch_size(&self) -> u32 {
+        self.data::<Data>().v1.max_batch_size
+    }
+
+    /// Sets tick size native prices must be a multiple of.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_tick_size(&mut self, tick_size: Balance) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.tick_size = tick_size;
+        Ok(())
+    }
+
+    /// Gets tick size native prices must be a multiple of.
+    default fn get_tick_size(&self) -> Balance {
+        self.data::<Data>().v1.tick_size
+    }
+
+    /// Gets the marketplace fee recipient.
+    default fn get_fee_recipient(&self) -> Option<AccountId> {
+        self.data::<Data>().market_fee_recipient
+    }
+
+    /// Sets the marketplace fee recipient.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_fee_recipient(
+        &mut self,
+        fee_recipient: AccountId,
+    ) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::FeeRecipient(fee_recipient))
+    }
+
+    /// Sets curated mode.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_curated_mode(
+        &mut self,
+        curated: bool,
+    ) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::CuratedMode(curated))
+    }
+
+    /// Checks if only verified collections can be listed.
+    default fn is_curated_mode(&self) -> bool {
+        self.data::<Data>().v1.curated
+    }
+
+    /// Adds a PSP22 token to the currencies accepted by the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn add_supported_currency(
+        &mut self,
+        currency: AccountId,
+        metadata: CurrencyMetadata,
+    ) -> Result<(), MarketplaceError> {
+        self.insert_supported_currency(currency, metadata);
+
+        Ok(())
+    }
+
+    /// Removes a PSP22 token from the currencies accepted by the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn remove_supported_currency(
+        &mut self,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        self.remove_currency(currency);
+
+        Ok(())
+    }
+
+    /// Checks if a PSP22 token is accepted by the marketplace.
+    default fn is_supported_currency(&self, currency: AccountId) -> bool {
+        self.data::<Data>().supported_currencies.contains(&currency)
+    }
+
+    /// Gets PSP22 tokens accepted by the marketplace.
+    default fn get_supported_currencies(&self) -> Vec<AccountId> {
+        self.data::<Data>()
+            .supported_currency_list
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// Gets metadata of a PSP22 token accepted by the marketplace.
+    default fn get_currency_metadata(&self, currency: AccountId) -> Option<CurrencyMetadata> {
+        self.data::<Data>().supported_currencies.get(&currency)
+    }
+
+    /// Adds a pallet-assets asset to the currencies accepted by the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn add_supported_asset(&mut self, asset_id: AssetId) -> Result<(), MarketplaceError> {
+        if !self.data::<Data>().supported_assets.contains(&asset_id) {
+            let mut assets = self
+                .data::<Data>()
+                .supported_asset_list
+                .get()
+                .unwrap_or_default();
+            assets.push(asset_id);
+            self.data::<Data>().supported_asset_list.set(&assets);
+        }
+        self.data::<Data>().supported_assets.insert(&asset_id, &());
+
+        Ok(())
+    }
+
+    /// Removes a pallet-assets asset from the currencies accepted by the marketplace.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn remove_supported_asset(
+        &mut self,
+        asset_id: AssetId,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().supported_assets.remove(&asset_id);
+        let mut assets = self
+            .data::<Data>()
+            .supported_asset_list
+            .get()
+            .unwrap_or_default();
+        assets.retain(|supported| *supported != asset_id);
+        self.data::<Data>().supported_asset_list.set(&assets);
+
+        Ok(())
+    }
+
+    /// Checks if a pallet-assets asset is accepted by the marketplace.
+    default fn is_supported_asset(&self, asset_id: AssetId) -> bool {
+        self.data::<Data>().supported_assets.contains(&asset_id)
+    }
+
+    /// Gets pallet-assets assets accepted by the marketplace.
+    default fn get_supported_assets(&self) -> Vec<AssetId> {
+        self.data::<Data>()
+            .supported_asset_list
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// Sets the wrapped native token (e.g. WASTR) and accepts it as a currency.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_wrapped_native(
+        &mut self,
+        wrapped_native: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.wrapped_native = Some(wrapped_native);
+        let metadata = self
+            .get_currency_metadata(wrapped_native)
+            .unwrap_or_default();
+        self.insert_supported_currency(wrapped_native, metadata);
+
+        Ok(())
+    }
+
+    /// Gets the wrapped native token.
+    default fn get_wrapped_native(&self) -> Option<AccountId> {
+        self.data::<Data>().v1.wrapped_native
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! English auctions.
+//!
+//! The auctioned NFT is held in custody and bids are locked in the marketplace. A higher bid
+//! returns the previous one to the deposit of its bidder, so a bidder that can't receive funds
+//! does not block the auction. Once the auction ends anyone can settle it, selling the NFT to
+//! the highest bidder like a listing, with the curator of a featured auction sharing the
+//! marketplace fee.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_curation::Internal as CurationInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Auction, Currency, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::auctions::MarketplaceAuctions,
+};
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Removes an auction and stops featuring it.
+    fn remove_auction(&mut self, auction_id: u128);
+}
+
+pub trait MarketplaceAuctionsEvents {
+    fn emit_auction_created_event(
+        &self,
+        auction_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        seller: AccountId,
+        reserve_price: Balance,
+        start_time: u64,
+        end_time: u64,
+    );
+    fn emit_auction_bid_event(&self, auction_id: u128, bidder: AccountId, amount: Balance);
+    fn emit_auction_settled_event(&self, auction_id: u128, winner: Option<AccountId>);
+    fn emit_auction_cancelled_event(&self, auction_id: u128);
+}
+
+impl<T> MarketplaceAuctions for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Auctions caller's NFT.
+    #[modifiers(when_not_taking_custody)]
+    default fn create_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<u128, MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_token_allowance(contract_address, &token_id)?;
+        self.check_not_disputed(contract_address, &token_id)?;
+        ensure!(
+            start_time < end_time && end_time > Self::env().block_timestamp(),
+            MarketplaceError::InvalidAuction
+        );
+
+        self.take_custody(contract_address, token_id.clone())?;
+        let auction_id = self.data::<Data>().v1.last_auction_id + 1;
+        self.data::<Data>().auctions.insert(
+            &auction_id,
+            &Auction {
+                seller: caller,
+                contract_address,
+                token_id: token_id.clone(),
+                reserve_price,
+                start_time,
+                end_time,
+                highest_bid: None,
+            },
+        );
+        self.data::<Data>().v1.last_auction_id = auction_id;
+
+        self.emit_auction_created_event(
+            auction_id,
+            contract_address,
+            token_id,
+            caller,
+            reserve_price,
+            start_time,
+            end_time,
+        );
+        Ok(auction_id)
+    }
+
+    /// Bids on an auction.
+    #[modifiers(when_not_taking_custody)]
+    default fn place_bid(&mut self, auction_id: u128) -> Result<(), MarketplaceError> {
+        let mut auction = self
+            .get_auction(auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(caller != auction.seller, MarketplaceError::AlreadyOwner);
+        let now = Self::env().block_timestamp();
+        ensure!(
+            now >= auction.start_time,
+            MarketplaceError::AuctionNotStarted
+        );
+        ensure!(now <= auction.end_time, MarketplaceError::AuctionEnded);
+        let amount = Self::env().transferred_value();
+        ensure!(
+            amount >= auction.reserve_price
+                && auction
+                    .highest_bid
+                    .map_or(true, |(_, highest)| amount > highest),
+            MarketplaceError::BidTooLow
+        );
+
+        self.lock_funds(amount);
+        if let Some((bidder, highest)) = auction.highest_bid {
+            self.unlock_funds(highest);
+            let deposit = self
+                .get_deposit_internal(bidder)
+                .checked_add(highest)
+                .ok_or(MarketplaceError::ArithmeticOverflow)?;
+            self.set_currency_deposit(bidder, Currency::Native, deposit);
+        }
+        auction.highest_bid = Some((caller, amount));
+        self.data::<Data>().auctions.insert(&auction_id, &auction);
+
+        self.emit_auction_bid_event(auction_id, caller, amount);
+        Ok(())
+    }
+
+    /// Settles an ended auction.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn settle_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError> {
+        let auction = self
+            .get_auction(auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() > auction.end_time,
+            MarketplaceError::AuctionNotEnded
+        );
+
+        let curator = self.data::<Data>().auction_curators.get(&auction_id);
+        self.remove_auction(auction_id);
+        let winner = match auction.highest_bid {
+            Some((bidder, amount)) => {
+                self.unlock_funds(amount);
+                self.settle_sale(
+                    auction.contract_address,
+                    auction.token_id,
+                    auction.seller,
+                    bidder,
+                    amount,
+                    Currency::Native,
+                    SaleAttribution {
+                        curator,
+                        ..Default::default()
+                    },
+                )?;
+                Some(bidder)
+            }
+            None => {
+                self.release_custody(auction.contract_address, auction.seller, auction.token_id)?;
+                None
+            }
+        };
+
+        self.emit_auction_settled_event(auction_id, winner);
+        Ok(())
+    }
+
+    /// Cancels an auction without bids.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn cancel_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError> {
+        let auction = self
+            .get_auction(auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            Self::env().caller() == auction.seller,
+            MarketplaceError::NotOwner
+        );
+        ensure!(
+            auction.highest_bid.is_none(),
+            MarketplaceError::AuctionHasBids
+        );
+
+        self.remove_auction(auction_id);
+        self.release_custody(auction.contract_address, auction.seller, auction.token_id)?;
+
+        self.emit_auction_cancelled_event(auction_id);
+        Ok(())
+    }
+
+    /// Gets auction.
+    default fn get_auction(&self, auction_id: u128) -> Option<Auction> {
+        self.data::<Data>().auctions.get(&auction_id)
+    }
+}
+
+impl<T> MarketplaceAuctionsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_auction_created_event(
+        &self,
+        _auction_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _seller: AccountId,
+        _reserve_price: Balance,
+        _start_time: u64,
+        _end_time: u64,
+    ) {
+    }
+
+    default fn emit_auction_bid_event(
+        &self,
+        _auction_id: u128,
+        _bidder: AccountId,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_auction_settled_event(&self, _auction_id: u128, _winner: Option<AccountId>) {}
+
+    default fn emit_auction_cancelled_event(&self, _auction_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn remove_auction(&mut self, auction_id: u128) {
+        self.data::<Data>().auctions.remove(&auction_id);
+        self.remove_featured_auction(auction_id);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Circuit breaker on abnormal trading volume.
+//!
+//! Sales are counted per block and native currency volume per hour, globally and per
+//! collection. Failed messages revert their state, so a sale cannot both fail and pause its
+//! collection. Instead the sale exceeding a limit completes and pauses trading of its
+//! collection. Further sales of the collection fail until the owner resumes it.
+
+use super::marketplace_custody::when_not_taking_custody;
+use crate::{
+    ensure,
+    impls::marketplace::types::{Currency, Data, MarketplaceError, RateLimit, SaleVolume},
+    traits::circuit_breaker::MarketplaceCircuitBreaker,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Length of the window sale volume is limited in (in milliseconds).
+pub const HOUR: u64 = 3_600_000;
+
+pub trait Internal {
+    /// Fails if trading of a collection is paused by the circuit breaker.
+    fn check_collection_not_paused(
+        &self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Counts a sale against the rate limits and pauses trading of the collection if a limit
+    /// is exceeded.
+    fn record_sale_volume(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        currency: Currency,
+    );
+
+    /// Counts a sale against a rate limit. Returns the updated volume if the limit is exceeded.
+    fn count_sale(
+        &mut self,
+        limited: Option<AccountId>,
+        rate_limit: &RateLimit,
+        price: Balance,
+        currency: Currency,
+    ) -> Option<SaleVolume>;
+}
+
+pub trait MarketplaceCircuitBreakerEvents {
+    fn emit_rate_limit_updated_event(
+        &self,
+        contract: Option<AccountId>,
+        rate_limit: Option<RateLimit>,
+    );
+    fn emit_circuit_breaker_tripped_event(
+        &self,
+        contract: AccountId,
+        limited: Option<AccountId>,
+        sales: u32,
+        volume: Balance,
+    );
+    fn emit_collection_resumed_event(&self, contract: AccountId);
+}
+
+impl<T> MarketplaceCircuitBreaker for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_rate_limit(
+        &mut self,
+        contract_address: Option<AccountId>,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<(), MarketplaceError> {
+        match &rate_limit {
+            Some(limit) => {
+                ensure!(
+                    limit.max_sales_per_block != Some(0) && limit.max_volume_per_hour != Some(0),
+                    MarketplaceError::InvalidRateLimit
+                );
+                self.data::<Data>()
+                    .rate_limits
+                    .insert(&contract_address, limit);
+            }
+            None => self.data::<Data>().rate_limits.remove(&contract_address),
+        }
+        self.data::<Data>().sale_volumes.remove(&contract_address);
+
+        self.emit_rate_limit_updated_event(contract_address, rate_limit);
+        Ok(())
+    }
+
+    default fn get_rate_limit(&self, contract_address: Option<AccountId>) -> Option<RateLimit> {
+        self.data::<Data>().rate_limits.get(&contract_address)
+    }
+
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn resume_collection(
+        &mut self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_collection_paused(contract_address),
+            MarketplaceError::CollectionNotPaused
+        );
+
+        self.data::<Data>()
+            .paused_collections
+            .remove(&contract_address);
+
+        self.emit_collection_resumed_event(contract_address);
+        Ok(())
+    }
+
+    default fn is_collection_paused(&self, contract_address: AccountId) -> bool {
+        self.data::<Data>()
+            .paused_collections
+            .contains(&contract_address)
+    }
+}
+
+impl<T> MarketplaceCircuitBreakerEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_rate_limit_updated_event(
+        &self,
+        _contract: Option<AccountId>,
+        _rate_limit: Option<RateLimit>,
+    ) {
+    }
+
+    default fn emit_circuit_breaker_tripped_event(
+        &self,
+        _contract: AccountId,
+        _limited: Option<AccountId>,
+        _sales: u32,
+        _volume: Balance,
+    ) {
+    }
+
+    default fn emit_collection_resumed_event(&self, _contract: AccountId) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_collection_not_paused(
+        &self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self
+                .data::<Data>()
+                .paused_collections
+                .contains(&contract_address),
+            MarketplaceError::CollectionPaused
+        );
+        Ok(())
+    }
+
+    default fn record_sale_volume(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        currency: Currency,
+    ) {
+        for limited in [Some(contract_address), None] {
+            let rate_limit = match self.data::<Data>().rate_limits.get(&limited) {
+                Some(rate_limit) => rate_limit,
+                None => continue,
+            };
+            if let Some(volume) = self.count_sale(limited, &rate_limit, price, currency) {
+                if !self
+                    .data::<Data>()
+                    .paused_collections
+                    .contains(&contract_address)
+                {
+                    self.data::<Data>()
+                        .paused_collections
+                        .insert(&contract_address, &());
+                    self.emit_circuit_breaker_tripped_event(
+                        contract_address,
+                        limited,
+                        volume.block_sales,
+                        volume.hour_volume,
+                    );
+                }
+            }
+        }
+    }
+
+    default fn count_sale(
+        &mut self,
+        limited: Option<AccountId>,
+        rate_limit: &RateLimit,
+        price: Balance,
+        currency: Currency,
+    ) -> Option<SaleVolume> {
+        let block = Self::env().block_number();
+        let hour = Self::env().block_timestamp() / HOUR;
+        let mut volume = self
+            .data::<Data>()
+            .sale_volumes
+            .get(&limited)
+            .unwrap_or_default();
+        if volume.block != block {
+            volume.block = block;
+            volume.block_sales = 0;
+        }
+        if volume.hour != hour {
+            volume.hour = hour;
+            volume.hour_volume = 0;
+        }
+        volume.block_sales += 1;
+        // volume in other currencies is not comparable with the limit
+        if currency == Currency::Native {
+            volume.hour_volume = volume.hour_volume.saturating_add(price);
+        }
+        self.data::<Data>().sale_volumes.insert(&limited, &volume);
+
+        let exceeded = rate_limit
+            .max_sales_per_block
+            .map_or(false, |max| volume.block_sales > max)
+            || rate_limit
+                .max_volume_per_hour
+                .map_or(false, |max| volume.hour_volume > max);
+        exceeded.then_some(volume)
+    }
+}
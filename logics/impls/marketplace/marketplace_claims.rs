@@ -0,0 +1,208 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Claims across pull-payment features.
+//!
+//! Escrowed sale proceeds, group purchase refunds, partner, curator and referral earnings and
+//! staking rewards are held by the marketplace and claimed with their own messages. This module
+//! sums them up for an account and claims them all at once. Escrows and group purchases are
+//! looked up by id. Earnings are looked up in native currency and the supported PSP22 tokens
+//! and assets, earnings in a currency no longer supported are claimed with the feature's
+//! message.
+
+use super::{
+    marketplace_curation::Internal as CurationInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_escrow::{Internal as EscrowInternal, MarketplaceEscrowEvents},
+    marketplace_group_purchase::Internal as GroupPurchaseInternal,
+    marketplace_partners::Internal as PartnersInternal,
+    marketplace_referrals::Internal as ReferralsInternal,
+    marketplace_staking::Internal as StakingInternal,
+    types::{ClaimableBalances, Currency},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::claims::MarketplaceClaims,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::reentrancy_guard::*,
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Gets currencies earnings are looked up in.
+    fn claimable_currencies(&self) -> Vec<Currency>;
+}
+
+impl<T> MarketplaceClaims for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    default fn get_claimable(&self, account: AccountId) -> ClaimableBalances {
+        let currencies = self.claimable_currencies();
+        let data = self.data::<Data>();
+        let now = Self::env().block_timestamp();
+
+        let mut escrow_proceeds = Vec::new();
+        for escrow_id in 1..=data.v1.last_escrow_id {
+            let escrow = match data.escrows.get(&escrow_id) {
+                Some(escrow)
+                    if escrow.seller == account
+                        && !escrow.disputed
+                        && now >= escrow.release_time =>
+                {
+                    escrow
+                }
+                _ => continue,
+            };
+            escrow_proceeds.push((escrow_id, escrow.currency, escrow.price));
+        }
+
+        let mut group_refunds = Vec::new();
+        for group_id in 1..=data.v1.last_group_purchase_id {
+            let amount = data
+                .group_contributions
+                .get(&(group_id, account))
+                .unwrap_or_default();
+            if amount == 0 {
+                continue
+            }
+            match data.group_purchases.get(&group_id) {
+                Some(group) if self.is_group_refundable(&group) => {}
+                _ => continue,
+            }
+            group_refunds.push((group_id, amount));
+        }
+
+        // partners are registered by the owner, so there are few of them
+        let mut partner_earnings = Vec::new();
+        for partner_id in 1..=data.v1.last_partner_id {
+            match data.partners.get(&partner_id) {
+                Some(partner) if partner.account == account => {}
+                _ => continue,
+            }
+            for currency in currencies.iter() {
+                let amount = data
+                    .partner_earnings
+                    .get(&(partner_id, *currency))
+                    .unwrap_or_default();
+                if amount > 0 {
+                    partner_earnings.push((partner_id, *currency, amount));
+                }
+            }
+        }
+
+        let referral_earnings = currencies
+            .iter()
+            .filter_map(|currency| {
+                data.referral_earnings
+                    .get(&(account, *currency))
+                    .filter(|amount| *amount > 0)
+                    .map(|amount| (*currency, amount))
+            })
+            .collect();
+        let curator_earnings = currencies
+            .iter()
+            .filter_map(|currency| {
+                data.curator_earnings
+                    .get(&(account, *currency))
+                    .filter(|amount| *amount > 0)
+                    .map(|amount| (*currency, amount))
+            })
+            .collect();
+
+        ClaimableBalances {
+            escrow_proceeds,
+            group_refunds,
+            partner_earnings,
+            referral_earnings,
+            curator_earnings,
+            staking_rewards: self.update_stake(account).rewards,
+        }
+    }
+
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_all(&mut self) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let claimable = self.get_claimable(caller);
+
+        for (escrow_id, ..) in claimable.escrow_proceeds.iter() {
+            if let Some(escrow) = self.data::<Data>().escrows.get(escrow_id) {
+                self.close_escrow(*escrow_id, escrow, false)?;
+                self.emit_escrow_released_event(*escrow_id);
+            }
+        }
+        for (group_id, _) in claimable.group_refunds.iter() {
+            if let Some(group) = self.data::<Data>().group_purchases.get(group_id) {
+                self.refund_group_contribution(*group_id, group, caller)?;
+            }
+        }
+        for (partner_id, currency, amount) in claimable.partner_earnings.iter() {
+            self.pay_partner_earnings(*partner_id, caller, *currency, *amount)?;
+        }
+        for (currency, amount) in claimable.referral_earnings.iter() {
+            self.pay_referral_earnings(caller, *currency, *amount)?;
+        }
+        for (currency, amount) in claimable.curator_earnings.iter() {
+            self.pay_curator_earnings(caller, *currency, *amount)?;
+        }
+        let staking_rewards = self.pay_staking_rewards(caller)?;
+
+        ensure!(
+            !claimable.escrow_proceeds.is_empty()
+                || !claimable.group_refunds.is_empty()
+                || !claimable.partner_earnings.is_empty()
+                || !claimable.referral_earnings.is_empty()
+                || !claimable.curator_earnings.is_empty()
+                || staking_rewards > 0,
+            MarketplaceError::NothingToClaim
+        );
+        Ok(())
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn claimable_currencies(&self) -> Vec<Currency> {
+        let data = self.data::<Data>();
+        let mut currencies = Vec::from([Currency::Native]);
+        currencies.extend(
+            data.supported_currency_list
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Currency::Psp22),
+        );
+        currencies.extend(
+            data.supported_asset_list
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Currency::Asset),
+        );
+        currencies
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Discount coupons.
+//!
+//! Coupons are stored under the Blake2x256 hash of their code, so the code itself is only
+//! revealed by the buyer redeeming it. Seller funded coupons lower the price the issuer
+//! receives for their own listings. Pool funded coupons keep the seller whole and pay the
+//! discount from funds locked by the issuer when the coupon is created.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_flash_sales::Internal as FlashSalesInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Coupon, CouponDiscount, CouponFunding, Currency, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::coupons::MarketplaceCoupons,
+};
+use ink::env::hash::Blake2x256;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Gets discount of a coupon for a price.
+    fn coupon_discount(&self, coupon: &Coupon, price: Balance) -> Balance;
+}
+
+pub trait MarketplaceCouponsEvents {
+    fn emit_coupon_created_event(&self, code_hash: [u8; 32], issuer: AccountId, coupon: Coupon);
+    fn emit_coupon_cancelled_event(&self, code_hash: [u8; 32], refund: Balance);
+    fn emit_coupon_redeemed_event(
+        &self,
+        code_hash: [u8; 32],
+        buyer: AccountId,
+        contract: AccountId,
+        id: Id,
+        discount: Balance,
+    );
+}
+
+impl<T> MarketplaceCoupons for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    #[modifiers(when_not_taking_custody)]
+    default fn create_coupon(
+        &mut self,
+        code_hash: [u8; 32],
+        contract_address: Option<AccountId>,
+        discount: CouponDiscount,
+        funding: CouponFunding,
+        max_uses: u32,
+        expires_at: u64,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        match contract_address {
+            Some(contract_address) => {
+                self.check_collection_owner(self.owner(), contract_address)?
+            }
+            None => ensure!(caller == self.owner(), MarketplaceError::NotOwner),
+        }
+        ensure!(
+            !self.data::<Data>().coupons.contains(&code_hash),
+            MarketplaceError::CouponExists
+        );
+
+        let pool = Self::env().transferred_value();
+        ensure!(
+            match discount {
+                CouponDiscount::Percent(bps) => bps > 0 && bps <= 10_000,
+                CouponDiscount::Flat(amount) => amount > 0,
+            } && max_uses > 0
+                && expires_at > Self::env().block_timestamp()
+                && (pool > 0) == (funding == CouponFunding::Pool),
+            MarketplaceError::InvalidCoupon
+        );
+
+        let coupon = Coupon {
+            issuer: caller,
+            contract_address,
+            discount,
+            funding,
+            pool,
+            max_uses,
+            uses: 0,
+            expires_at,
+        };
+        self.data::<Data>().coupons.insert(&code_hash, &coupon);
+        self.lock_funds(pool);
+
+        self.emit_coupon_created_event(code_hash, caller, coupon);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn cancel_coupon(&mut self, code_hash: [u8; 32]) -> Result<(), MarketplaceError> {
+        let coupon = self
+            .data::<Data>()
+            .coupons
+            .get(&code_hash)
+            .ok_or(MarketplaceError::CouponNotFound)?;
+        let caller = Self::env().caller();
+        ensure!(
+            caller == coupon.issuer || caller == self.owner(),
+            MarketplaceError::NotOwner
+        );
+
+        self.data::<Data>().coupons.remove(&code_hash);
+        if coupon.pool > 0 {
+            self.unlock_funds(coupon.pool);
+            self.transfer_funds(
+                Currency::Native,
+                coupon.issuer,
+                coupon.pool,
+                MarketplaceError::TransferToOwnerFailed,
+            )?;
+        }
+
+        self.emit_coupon_cancelled_event(code_hash, coupon.pool);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_coupon(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        code: String,
+    ) -> Result<(), MarketplaceError> {
+        let code_hash = Self::env().hash_bytes::<Blake2x256>(&code);
+        let mut coupon = self
+            .data::<Data>()
+            .coupons
+            .get(&code_hash)
+            .ok_or(MarketplaceError::CouponNotFound)?;
+        ensure!(
+            coupon.expires_at > Self::env().block_timestamp(),
+            MarketplaceError::CouponExpired
+        );
+        ensure!(
+            coupon.uses < coupon.max_uses,
+            MarketplaceError::CouponExhausted
+        );
+        let caller = Self::env().caller();
+        ensure!(
+            !self
+                .data::<Data>()
+                .coupon_redemptions
+                .contains(&(code_hash, caller)),
+            MarketplaceError::CouponAlreadyRedeemed
+        );
+        ensure!(
+            coupon.contract_address.is_none() || coupon.contract_address == Some(contract_address),
+            MarketplaceError::CouponNotApplicable
+        );
+
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+        ensure!(
+            coupon.funding == CouponFunding::Pool || token_owner == coupon.issuer,
+            MarketplaceError::CouponNotApplicable
+        );
+
+        let listing_price = self.flash_sale_price(contract_address, &item);
+        let discount = self.coupon_discount(&coupon, listing_price);
+        let value = Self::env().transferred_value();
+        self.check_price(value, listing_price - discount)?;
+
+        let price = if coupon.funding == CouponFunding::Pool {
+            ensure!(coupon.pool >= discount, MarketplaceError::CouponExhausted);
+            coupon.pool -= discount;
+            self.unlock_funds(discount);
+            value + discount
+        } else {
+            value
+        };
+        coupon.uses += 1;
+        self.data::<Data>().coupons.insert(&code_hash, &coupon);
+        self.data::<Data>()
+            .coupon_redemptions
+            .insert(&(code_hash, caller), &());
+
+        self.settle_purchase(
+            contract_address,
+            token_id.clone(),
+            token_owner,
+            caller,
+            price,
+            SaleAttribution::default(),
+        )?;
+
+        self.emit_coupon_redeemed_event(code_hash, caller, contract_address, token_id, discount);
+        Ok(())
+    }
+
+    default fn get_coupon(&self, code_hash: [u8; 32]) -> Option<Coupon> {
+        self.data::<Data>().coupons.get(&code_hash)
+    }
+
+    default fn is_coupon_redeemed(&self, code_hash: [u8; 32], account: AccountId) -> bool {
+        self.data::<Data>()
+            .coupon_redemptions
+            .contains(&(code_hash, account))
+    }
+}
+
+impl<T> MarketplaceCouponsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_coupon_created_event(
+        &self,
+        _code_hash: [u8; 32],
+        _issuer: AccountId,
+        _coupon: Coupon,
+    ) {
+    }
+
+    default fn emit_coupon_cancelled_event(&self, _code_hash: [u8; 32], _refund: Balance) {}
+
+    default fn emit_coupon_redeemed_event(
+        &self,
+        _code_hash: [u8; 32],
+        _buyer: AccountId,
+        _contract: AccountId,
+        _id: Id,
+        _discount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn coupon_discount(&self, coupon: &Coupon, price: Balance) -> Balance {
+        let discount = match coupon.discount {
+            CouponDiscount::Percent(bps) => price.saturating_mul(bps as u128) / 10_000,
+            CouponDiscount::Flat(amount) => amount,
+        };
+        discount.min(price)
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Credit, Currency, CREDIT_ISSUER},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::credits::MarketplaceCredits,
+};
+use openbrush::{
+    contracts::{access_control::*, ownable::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Spends up to `amount` of account credit and returns the spent part.
+    fn consume_credit(&mut self, account: AccountId, amount: Balance) -> Balance;
+}
+
+pub trait MarketplaceCreditsEvents {
+    fn emit_credit_granted_event(&self, account: AccountId, amount: Balance, expires_at: u64);
+    fn emit_credit_spent_event(&self, account: AccountId, amount: Balance);
+    fn emit_credit_expired_event(&self, account: AccountId, amount: Balance);
+}
+
+impl<T> MarketplaceCredits for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<access_control::Data>,
+{
+    /// Grants transferred value as credit spendable on marketplace purchases.
+    #[modifiers(when_not_taking_custody)]
+    default fn grant_credit(
+        &mut self,
+        account: AccountId,
+        expires_at: u64,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            caller == self.owner() || self.has_role(CREDIT_ISSUER, caller),
+            MarketplaceError::NotCreditIssuer
+        );
+        ensure!(
+            expires_at > Self::env().block_timestamp(),
+            MarketplaceError::InvalidCreditExpiry
+        );
+
+        let amount = Self::env().transferred_value();
+        let mut credit = self
+            .data::<Data>()
+            .credits
+            .get(&account)
+            .unwrap_or_default();
+        ensure!(
+            credit.amount == 0 || credit.expires_at > Self::env().block_timestamp(),
+            MarketplaceError::CreditExpired
+        );
+        credit.amount = credit
+            .amount
+            .checked_add(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        credit.expires_at = credit.expires_at.max(expires_at);
+        self.data::<Data>().credits.insert(&account, &credit);
+        self.lock_funds(amount);
+
+        self.emit_credit_granted_event(account, amount, expires_at);
+        Ok(())
+    }
+
+    /// Moves expired credit of an account to the marketplace fee recipient.
+    #[modifiers(when_not_taking_custody)]
+    default fn reclaim_expired_credit(
+        &mut self,
+        account: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let credit = self
+            .data::<Data>()
+            .credits
+            .get(&account)
+            .unwrap_or_default();
+        ensure!(
+            credit.amount > 0 && credit.expires_at <= Self::env().block_timestamp(),
+            MarketplaceError::CreditNotExpired
+        );
+
+        self.data::<Data>().credits.remove(&account);
+        self.unlock_funds(credit.amount);
+        self.transfer_funds(
+            Currency::Native,
+            self.fee_recipient()?,
+            credit.amount,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+
+        self.emit_credit_expired_event(account, credit.amount);
+        Ok(())
+    }
+
+    /// Gets credit of an account.
+    default fn get_credit(&self, account: AccountId) -> Option<Credit> {
+        self.data::<Data>()
+            .credits
+            .get(&account)
+            .filter(|credit| credit.expires_at > Self::env().block_timestamp())
+    }
+}
+
+impl<T> MarketplaceCreditsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_credit_granted_event(
+        &self,
+        _account: AccountId,
+        _amount: Balance,
+        _expires_at: u64,
+    ) {
+    }
+
+    default fn emit_credit_spent_event(&self, _account: AccountId, _amount: Balance) {}
+
+    default fn emit_credit_expired_event(&self, _account: AccountId, _amount: Balance) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn consume_credit(&mut self, account: AccountId, amount: Balance) -> Balance {
+        let mut credit = match self.data::<Data>().credits.get(&account) {
+            Some(credit) if credit.expires_at > Self::env().block_timestamp() => credit,
+            _ => return 0,
+        };
+
+        let spent = credit.amount.min(amount);
+        if spent == 0 {
+            return 0
+        }
+        credit.amount -= spent;
+        self.unlock_funds(spent);
+        if credit.amount == 0 {
+            self.data::<Data>().credits.remove(&account);
+        } else {
+            self.data::<Data>().credits.insert(&account, &credit);
+        }
+
+        self.emit_credit_spent_event(account, spent);
+        spent
+    }
+}
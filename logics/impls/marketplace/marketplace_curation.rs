@@ -0,0 +1,445 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Curated listings and auctions.
+//!
+//! Curators feature listings and auctions for frontends to show in curated sales programs, and
+//! schedule featured auctions in their auction calendar. When a featured listing sells or a
+//! featured auction is settled with a bid, the curator earns a share of the marketplace fee,
+//! taken after the partner share and before the referral share, so seller proceeds are
+//! unchanged. Featuring ends when the listing or auction is removed. Earnings accrue in the
+//! marketplace and are claimed per currency.
+
+use super::{
+    batch::check_batch_size,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Auction, Currency, CURATOR},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::curation::MarketplaceCuration,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{access_control::*, ownable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Stops featuring a listing. Returns the curator that featured it.
+    fn remove_featured_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Option<AccountId>;
+
+    /// Stops featuring an auction. Returns the curator that featured it.
+    fn remove_featured_auction(&mut self, auction_id: u128) -> Option<AccountId>;
+
+    /// Accrues curator's share of the marketplace fee. Returns the share.
+    fn accrue_curator_fee(
+        &mut self,
+        curator: AccountId,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance;
+
+    /// Pays `amount` of curator earnings of an account in a currency.
+    fn pay_curator_earnings(
+        &mut self,
+        curator: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceCurationEvents {
+    fn emit_listing_featured_event(&self, contract: AccountId, token_id: Id, curator: AccountId);
+    fn emit_listing_unfeatured_event(&self, contract: AccountId, token_id: Id);
+    fn emit_auction_featured_event(&self, auction_id: u128, curator: AccountId);
+    fn emit_auction_unfeatured_event(&self, auction_id: u128);
+    fn emit_auction_calendar_set_event(&self, curator: AccountId, auction_ids: Vec<u128>);
+    fn emit_curator_fee_accrued_event(
+        &self,
+        curator: AccountId,
+        currency: Currency,
+        amount: Balance,
+    );
+    fn emit_curator_earnings_claimed_event(
+        &self,
+        curator: AccountId,
+        currency: Currency,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceCuration for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<access_control::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Features a listing.
+    #[modifiers(when_not_taking_custody)]
+    default fn feature_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            caller == self.owner() || self.has_role(CURATOR, caller),
+            MarketplaceError::AccessControlError(AccessControlError::MissingRole)
+        );
+        let key = (contract_address, token_key(&token_id));
+        ensure!(
+            self.data::<Data>().items.contains(&key),
+            MarketplaceError::ItemNotListedForSale
+        );
+        ensure!(
+            !self.data::<Data>().featured_listings.contains(&key),
+            MarketplaceError::ListingAlreadyFeatured
+        );
+
+        self.data::<Data>().featured_listings.insert(&key, &caller);
+        self.data::<Data>()
+            .featured_listing_index
+            .insert(&contract_address, &token_id);
+
+        self.emit_listing_featured_event(contract_address, token_id, caller);
+        Ok(())
+    }
+
+    /// Stops featuring a listing.
+    #[modifiers(when_not_taking_custody)]
+    default fn unfeature_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let curator = self
+            .get_listing_curator(contract_address, token_id.clone())
+            .ok_or(MarketplaceError::ListingNotFeatured)?;
+        ensure!(
+            caller == curator || caller == self.owner(),
+            MarketplaceError::AccessControlError(AccessControlError::MissingRole)
+        );
+
+        self.remove_featured_listing(contract_address, &token_id);
+
+        self.emit_listing_unfeatured_event(contract_address, token_id);
+        Ok(())
+    }
+
+    /// Gets curator of a featured listing.
+    default fn get_listing_curator(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<AccountId> {
+        self.data::<Data>()
+            .featured_listings
+            .get(&(contract_address, token_key(&token_id)))
+    }
+
+    /// Gets featured listings of a collection.
+    default fn get_featured_listings(
+        &self,
+        contract_address: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<Id> {
+        let listings = &self.data::<Data>().featured_listing_index;
+        (from as u128..listings.count(&contract_address))
+            .take(limit.min(self.data::<Data>().v1.max_batch_size) as usize)
+            .filter_map(|index| listings.get_value(&contract_address, &index))
+            .collect()
+    }
+
+    /// Features an auction.
+    #[modifiers(when_not_taking_custody)]
+    default fn feature_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            caller == self.owner() || self.has_role(CURATOR, caller),
+            MarketplaceError::AccessControlError(AccessControlError::MissingRole)
+        );
+        ensure!(
+            self.data::<Data>().auctions.contains(&auction_id),
+            MarketplaceError::AuctionNotFound
+        );
+        ensure!(
+            !self.data::<Data>().auction_curators.contains(&auction_id),
+            MarketplaceError::AuctionAlreadyFeatured
+        );
+
+        self.data::<Data>()
+            .auction_curators
+            .insert(&auction_id, &caller);
+
+        self.emit_auction_featured_event(auction_id, caller);
+        Ok(())
+    }
+
+    /// Stops featuring an auction.
+    #[modifiers(when_not_taking_custody)]
+    default fn unfeature_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let curator = self
+            .get_auction_curator(auction_id)
+            .ok_or(MarketplaceError::AuctionNotFeatured)?;
+        ensure!(
+            caller == curator || caller == self.owner(),
+            MarketplaceError::AccessControlError(AccessControlError::MissingRole)
+        );
+
+        self.remove_featured_auction(auction_id);
+
+        self.emit_auction_unfeatured_event(auction_id);
+        Ok(())
+    }
+
+    /// Gets curator of a featured auction.
+    default fn get_auction_curator(&self, auction_id: u128) -> Option<AccountId> {
+        self.data::<Data>().auction_curators.get(&auction_id)
+    }
+
+    /// Sets the auction calendar of the caller.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_auction_calendar(
+        &mut self,
+        auction_ids: Vec<u128>,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        check_batch_size(auction_ids.len(), self.data::<Data>().v1.max_batch_size)?;
+        let mut calendar = Vec::with_capacity(auction_ids.len());
+        for auction_id in auction_ids {
+            ensure!(
+                self.get_auction_curator(auction_id) == Some(caller),
+                MarketplaceError::AuctionNotFeatured
+            );
+            let auction = self
+                .data::<Data>()
+                .auctions
+                .get(&auction_id)
+                .ok_or(MarketplaceError::AuctionNotFound)?;
+            calendar.push((auction.start_time, auction_id));
+        }
+        calendar.sort_unstable();
+        calendar.dedup();
+        let auction_ids: Vec<u128> = calendar
+            .into_iter()
+            .map(|(_, auction_id)| auction_id)
+            .collect();
+
+        if auction_ids.is_empty() {
+            self.data::<Data>().auction_calendars.remove(&caller);
+        } else {
+            self.data::<Data>()
+                .auction_calendars
+                .insert(&caller, &auction_ids);
+        }
+
+        self.emit_auction_calendar_set_event(caller, auction_ids);
+        Ok(())
+    }
+
+    /// Gets auction calendar of a curator.
+    default fn get_auction_calendar(&self, curator: AccountId) -> Vec<(u128, Auction)> {
+        self.data::<Data>()
+            .auction_calendars
+            .get(&curator)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|auction_id| self.get_auction_curator(*auction_id) == Some(curator))
+            .filter_map(|auction_id| {
+                self.data::<Data>()
+                    .auctions
+                    .get(&auction_id)
+                    .map(|auction| (auction_id, auction))
+            })
+            .collect()
+    }
+
+    /// Sets share of the marketplace fee paid to curators.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_curator_fee_share(&mut self, fee_share: u16) -> Result<(), MarketplaceError> {
+        self.check_fee(fee_share, 10_000)?;
+        self.data::<Data>().curator_fee_share.set(&fee_share);
+        Ok(())
+    }
+
+    /// Gets share of the marketplace fee paid to curators.
+    default fn get_curator_fee_share(&self) -> u16 {
+        self.data::<Data>()
+            .curator_fee_share
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// Pays curator earnings of the caller in a currency.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_curator_earnings(
+        &mut self,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let amount = self.get_curator_earnings(caller, currency);
+        ensure!(amount > 0, MarketplaceError::NoCuratorEarnings);
+
+        self.pay_curator_earnings(caller, currency, amount)
+    }
+
+    /// Gets unclaimed curator earnings of an account in a currency.
+    default fn get_curator_earnings(&self, account: AccountId, currency: Currency) -> Balance {
+        self.data::<Data>()
+            .curator_earnings
+            .get(&(account, currency))
+            .unwrap_or_default()
+    }
+}
+
+impl<T> MarketplaceCurationEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_listing_featured_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _curator: AccountId,
+    ) {
+    }
+
+    default fn emit_listing_unfeatured_event(&self, _contract: AccountId, _token_id: Id) {}
+
+    default fn emit_auction_featured_event(&self, _auction_id: u128, _curator: AccountId) {}
+
+    default fn emit_auction_unfeatured_event(&self, _auction_id: u128) {}
+
+    default fn emit_auction_calendar_set_event(
+        &self,
+        _curator: AccountId,
+        _auction_ids: Vec<u128>,
+    ) {
+    }
+
+    default fn emit_curator_fee_accrued_event(
+        &self,
+        _curator: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_curator_earnings_claimed_event(
+        &self,
+        _curator: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn remove_featured_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Option<AccountId> {
+        let key = (contract_address, token_key(token_id));
+        let curator = self.data::<Data>().featured_listings.get(&key)?;
+        self.data::<Data>().featured_listings.remove(&key);
+        self.data::<Data>()
+            .featured_listing_index
+            .remove_value(&contract_address, token_id);
+        Some(curator)
+    }
+
+    default fn remove_featured_auction(&mut self, auction_id: u128) -> Option<AccountId> {
+        let curator = self.data::<Data>().auction_curators.get(&auction_id)?;
+        self.data::<Data>().auction_curators.remove(&auction_id);
+        Some(curator)
+    }
+
+    default fn accrue_curator_fee(
+        &mut self,
+        curator: AccountId,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance {
+        let fee_share = self
+            .data::<Data>()
+            .curator_fee_share
+            .get()
+            .unwrap_or_default();
+        let amount = marketplace_fee.saturating_mul(fee_share as u128) / 10_000;
+        if amount == 0 {
+            return 0
+        }
+
+        let earnings = self
+            .data::<Data>()
+            .curator_earnings
+            .get(&(curator, currency))
+            .unwrap_or_default();
+        self.data::<Data>()
+            .curator_earnings
+            .insert(&(curator, currency), &earnings.saturating_add(amount));
+        if currency == Currency::Native {
+            self.accrue_fees(amount);
+        }
+        self.emit_curator_fee_accrued_event(curator, currency, amount);
+        amount
+    }
+
+    default fn pay_curator_earnings(
+        &mut self,
+        curator: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>()
+            .curator_earnings
+            .remove(&(curator, currency));
+        if currency == Currency::Native {
+            self.pay_accrued_fees(amount);
+        }
+        self.transfer_funds(
+            currency,
+            curator,
+            amount,
+            MarketplaceError::TransferToCuratorFailed,
+        )?;
+
+        self.emit_curator_earnings_claimed_event(curator, currency, amount);
+        Ok(())
+    }
+}
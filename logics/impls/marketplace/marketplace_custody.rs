@@ -0,0 +1,155 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Ledger of NFTs held in custody by the marketplace.
+//!
+//! NFTs are received through `PSP34Receiver::before_received`. The marketplace only accepts
+//! transfers it makes itself and attributes each received token to the account it was
+//! transferred from. Tokens leaving custody have to be released through `release_custody`
+//! so the ledger stays in sync.
+//!
+//! The collection may call back into the marketplace while a token is transferred into
+//! custody. Every other state changing message is guarded by `when_not_taking_custody`, so
+//! `before_received` is the only one the collection can re-enter.
+
+use super::{contract_calls, types::token_key};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::custody::MarketplaceCustody,
+};
+use openbrush::{
+    contracts::psp34::*,
+    traits::{AccountId, Storage},
+};
+
+/// Fails while a NFT is transferred into custody.
+#[openbrush::modifier_definition]
+pub fn when_not_taking_custody<T, F, R, E>(instance: &mut T, body: F) -> Result<R, E>
+where
+    T: Storage<Data>,
+    F: FnOnce(&mut T) -> Result<R, E>,
+    E: From<MarketplaceError>,
+{
+    ensure!(
+        !instance
+            .data::<Data>()
+            .custody_transfer
+            .get()
+            .unwrap_or_default(),
+        MarketplaceError::CustodyTransferInProgress
+    );
+    body(instance)
+}
+
+pub trait Internal {
+    /// Records a NFT received from `from`. Rejects transfers not made by the marketplace.
+    fn receive_token(
+        &mut self,
+        operator: AccountId,
+        from: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers a NFT out of custody and removes it from the ledger.
+    fn release_custody(
+        &mut self,
+        contract_address: AccountId,
+        to: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceCustodyEvents {
+    fn emit_token_received_event(&self, contract_address: AccountId, token_id: Id, from: AccountId);
+}
+
+impl<T> MarketplaceCustody for T
+where
+    T: Storage<Data>,
+{
+    /// Gets account that deposited a NFT held in custody.
+    default fn get_custody_depositor(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<AccountId> {
+        self.data::<Data>()
+            .custody_depositors
+            .get(&(contract_address, token_key(&token_id)))
+    }
+}
+
+impl<T> MarketplaceCustodyEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_token_received_event(
+        &self,
+        _contract_address: AccountId,
+        _token_id: Id,
+        _from: AccountId,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn receive_token(
+        &mut self,
+        operator: AccountId,
+        from: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            operator == Self::env().account_id()
+                && self
+                    .data::<Data>()
+                    .custody_transfer
+                    .get()
+                    .unwrap_or_default(),
+            MarketplaceError::UnsolicitedTransfer
+        );
+        // Called back during the marketplace's own transfer, so only the ledger mapping may
+        // be written here. Root storage is flushed by the outer call and would be overwritten.
+        let contract_address = Self::env().caller();
+        self.data::<Data>()
+            .custody_depositors
+            .insert(&(contract_address, token_key(&token_id)), &from);
+        self.emit_token_received_event(contract_address, token_id, from);
+        Ok(())
+    }
+
+    default fn release_custody(
+        &mut self,
+        contract_address: AccountId,
+        to: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>()
+            .custody_depositors
+            .remove(&(contract_address, token_key(&token_id)));
+        contract_calls::transfer(contract_address, to, token_id)?;
+        Ok(())
+    }
+}
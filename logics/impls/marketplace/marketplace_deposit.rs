@@ -0,0 +1,266 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    assets,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::{Internal as SaleInternal, MarketplaceSaleEvents},
+    types::{AssetId, Currency, DepositBreakdown},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::{admin::MarketplaceAdmin, deposit::MarketplaceDeposit},
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp22::PSP22Ref, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+impl<T> MarketplaceDeposit for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Sets if caller receives proceeds of native sales in wrapped native token.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_receive_wrapped_native(
+        &mut self,
+        enabled: bool,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        if enabled {
+            ensure!(
+                self.data::<Data>().v1.wrapped_native.is_some(),
+                MarketplaceError::WrappedNativeNotSet
+            );
+            self.data::<Data>()
+                .receive_wrapped_native
+                .insert(&caller, &());
+        } else {
+            self.data::<Data>().receive_wrapped_native.remove(&caller);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if account receives proceeds of native sales in wrapped native token.
+    default fn is_receiving_wrapped_native(&self, account: AccountId) -> bool {
+        self.data::<Data>()
+            .receive_wrapped_native
+            .contains(&account)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn deposit(&mut self) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let value = Self::env().transferred_value();
+
+        let current_balance = self.get_currency_deposit(caller, Currency::Native);
+        let new_balance = current_balance
+            .checked_add(value)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        self.set_currency_deposit(caller, Currency::Native, new_balance);
+
+        self.emit_deposit_event(caller, Currency::Native, value, new_balance);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn withdraw(&mut self, amount: Balance) -> Result<(), MarketplaceError> {
+        self.withdraw_currency(Currency::Native, Self::env().caller(), amount)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn withdraw_all(&mut self) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let amount = self
+            .get_deposit_internal(caller)
+            .saturating_sub(self.get_offer_locked_deposit(caller, Currency::Native));
+        self.withdraw_currency(Currency::Native, caller, amount)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn withdraw_to(
+        &mut self,
+        recipient: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.withdraw_currency(Currency::Native, recipient, amount)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn deposit_psp22(
+        &mut self,
+        currency: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_supported_currency(currency),
+            MarketplaceError::CurrencyNotSupported
+        );
+        let caller = Self::env().caller();
+        let new_balance = self
+            .get_currency_deposit(caller, Currency::Psp22(currency))
+            .checked_add(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+
+        PSP22Ref::transfer_from(
+            &currency,
+            caller,
+            Self::env().account_id(),
+            amount,
+            Vec::new(),
+        )
+        .map_err(|_| MarketplaceError::TransferFromDepositorFailed)?;
+
+        let currency = Currency::Psp22(currency);
+        self.set_currency_deposit(caller, currency, new_balance);
+
+        self.emit_deposit_event(caller, currency, amount, new_balance);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn withdraw_psp22(
+        &mut self,
+        currency: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.withdraw_currency(Currency::Psp22(currency), Self::env().caller(), amount)
+    }
+
+    default fn get_psp22_deposit(&self, account_id: AccountId, currency: AccountId) -> Balance {
+        self.get_currency_deposit(account_id, Currency::Psp22(currency))
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn deposit_asset(
+        &mut self,
+        asset_id: AssetId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_supported_asset(asset_id),
+            MarketplaceError::CurrencyNotSupported
+        );
+        let caller = Self::env().caller();
+        let currency = Currency::Asset(asset_id);
+        let new_balance = self
+            .get_currency_deposit(caller, currency)
+            .checked_add(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+
+        assets::transfer_approved(asset_id, caller, Self::env().account_id(), amount)?;
+
+        self.set_currency_deposit(caller, currency, new_balance);
+
+        self.emit_deposit_event(caller, currency, amount, new_balance);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn withdraw_asset(
+        &mut self,
+        asset_id: AssetId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.withdraw_currency(Currency::Asset(asset_id), Self::env().caller(), amount)
+    }
+
+    default fn get_asset_deposit(&self, account_id: AccountId, asset_id: AssetId) -> Balance {
+        self.get_currency_deposit(account_id, Currency::Asset(asset_id))
+    }
+
+    default fn get_deposit(&self, account_id: AccountId) -> Balance {
+        self.get_deposit_internal(account_id)
+    }
+
+    default fn get_deposit_breakdown(&self, account_id: AccountId) -> DepositBreakdown {
+        let total = self.get_deposit_internal(account_id);
+        let locked = self
+            .get_offer_locked_deposit(account_id, Currency::Native)
+            .min(total);
+
+        DepositBreakdown {
+            total,
+            locked,
+            available: total - locked,
+            bonds: self.get_locked_deposit(account_id),
+        }
+    }
+
+    #[modifiers(when_not_taking_custody)]
+    default fn approve_spender(
+        &mut self,
+        operator: AccountId,
+        allowance: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.data::<Data>()
+            .deposit_allowances
+            .insert(&(caller, operator), &allowance);
+
+        self.emit_spender_approved_event(caller, operator, allowance);
+        Ok(())
+    }
+
+    default fn get_spender_allowance(&self, owner: AccountId, operator: AccountId) -> Balance {
+        self.data::<Data>()
+            .deposit_allowances
+            .get(&(owner, operator))
+            .unwrap_or(0)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn transfer_deposit(
+        &mut self,
+        to: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(to)?;
+        let current_balance = self.get_deposit_internal(caller);
+        ensure!(
+            current_balance >= amount,
+            MarketplaceError::BalanceInsufficient
+        );
+        ensure!(
+            current_balance - amount >= self.get_offer_locked_deposit(caller, Currency::Native),
+            MarketplaceError::DepositLockedByOffer
+        );
+
+        self.set_currency_deposit(caller, Currency::Native, current_balance - amount);
+        let to_balance = self
+            .get_deposit_internal(to)
+            .checked_add(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        self.set_currency_deposit(to, Currency::Native, to_balance);
+
+        self.emit_deposit_transferred_event(caller, to, amount);
+        Ok(())
+    }
+}
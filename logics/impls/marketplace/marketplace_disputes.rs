@@ -0,0 +1,325 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Disputes of escrowed sales and rentals.
+//!
+//! Either party of the trade can open a dispute within its dispute window: before the
+//! release time of an escrow, or before the end of a rental plus the rental dispute window.
+//! The disputed NFT cannot be listed, sold or rented out until an arbiter rules on the
+//! dispute. The ruling and its rationale are kept with the dispute, and the trade is settled
+//! when the ruling is made.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_escrow::{Internal as EscrowInternal, MarketplaceEscrowEvents},
+    marketplace_rentals::{Internal as RentalsInternal, MarketplaceRentalsEvents},
+    types::{token_key, Dispute, DisputeRuling, DisputeSubject, ARBITER},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::disputes::MarketplaceDisputes,
+};
+use openbrush::{
+    contracts::{access_control::*, ownable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Storage, String},
+};
+
+pub trait Internal {
+    /// Fails if trading of the NFT is frozen by an open dispute.
+    fn check_not_disputed(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Opens a dispute on behalf of a party of the trade.
+    fn open_dispute_for(
+        &mut self,
+        subject: DisputeSubject,
+        claimant: AccountId,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Records the ruling and unfreezes the NFT. Returns the resolved dispute.
+    fn record_ruling(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<Dispute, MarketplaceError>;
+
+    /// Records the ruling, unfreezes the NFT and settles the disputed trade.
+    fn rule(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceDisputesEvents {
+    fn emit_dispute_opened_event(
+        &self,
+        dispute_id: u128,
+        subject: DisputeSubject,
+        claimant: AccountId,
+    );
+    fn emit_dispute_ruled_event(&self, dispute_id: u128, claimant_wins: bool, rationale: String);
+}
+
+impl<T> MarketplaceDisputes for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<reentrancy_guard::Data>
+        + Storage<access_control::Data>,
+{
+    /// Sets how long (in milliseconds) after its end a rental can be disputed.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_rental_dispute_window(&mut self, window: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.rental_dispute_window = window;
+        Ok(())
+    }
+
+    /// Gets rental dispute window in milliseconds.
+    default fn get_rental_dispute_window(&self) -> u64 {
+        self.data::<Data>().v1.rental_dispute_window
+    }
+
+    /// Opens a dispute of an escrowed sale or a rental.
+    #[modifiers(when_not_taking_custody)]
+    default fn open_dispute(&mut self, subject: DisputeSubject) -> Result<u128, MarketplaceError> {
+        self.open_dispute_for(subject, Self::env().caller())
+    }
+
+    /// Resolves a dispute and settles the disputed trade.
+    #[modifiers(when_not_taking_custody, only_role(ARBITER), non_reentrant)]
+    default fn rule_on_dispute(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<(), MarketplaceError> {
+        self.rule(dispute_id, claimant_wins, rationale)
+    }
+
+    /// Gets dispute.
+    default fn get_dispute(&self, dispute_id: u128) -> Option<Dispute> {
+        self.data::<Data>().disputes.get(&dispute_id)
+    }
+
+    /// Gets id of the open dispute of an escrowed sale or a rental.
+    default fn get_open_dispute(&self, subject: DisputeSubject) -> Option<u128> {
+        self.data::<Data>().subject_disputes.get(&subject)
+    }
+}
+
+impl<T> MarketplaceDisputesEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_dispute_opened_event(
+        &self,
+        _dispute_id: u128,
+        _subject: DisputeSubject,
+        _claimant: AccountId,
+    ) {
+    }
+
+    default fn emit_dispute_ruled_event(
+        &self,
+        _dispute_id: u128,
+        _claimant_wins: bool,
+        _rationale: String,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_not_disputed(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self
+                .data::<Data>()
+                .disputed_tokens
+                .contains(&(contract_address, token_key(token_id))),
+            MarketplaceError::TokenDisputed
+        );
+        Ok(())
+    }
+
+    default fn open_dispute_for(
+        &mut self,
+        subject: DisputeSubject,
+        claimant: AccountId,
+    ) -> Result<u128, MarketplaceError> {
+        let now = Self::env().block_timestamp();
+        let (contract_address, token_id, respondent) = match subject {
+            DisputeSubject::Escrow(escrow_id) => {
+                let mut escrow = self
+                    .data::<Data>()
+                    .escrows
+                    .get(&escrow_id)
+                    .ok_or(MarketplaceError::EscrowNotFound)?;
+                let respondent = if claimant == escrow.buyer {
+                    escrow.seller
+                } else if claimant == escrow.seller {
+                    escrow.buyer
+                } else {
+                    return Err(MarketplaceError::NotDisputeParty)
+                };
+                ensure!(!escrow.disputed, MarketplaceError::EscrowDisputed);
+                ensure!(
+                    now < escrow.release_time,
+                    MarketplaceError::DisputeWindowClosed
+                );
+
+                escrow.disputed = true;
+                self.data::<Data>().escrows.insert(&escrow_id, &escrow);
+                self.emit_escrow_disputed_event(escrow_id);
+                (escrow.contract_address, escrow.token_id, respondent)
+            }
+            DisputeSubject::Rental(rental_id) => {
+                let rental = self
+                    .data::<Data>()
+                    .rentals
+                    .get(&rental_id)
+                    .ok_or(MarketplaceError::RentalNotFound)?;
+                let respondent = if claimant == rental.renter {
+                    rental.owner
+                } else if claimant == rental.owner {
+                    rental.renter
+                } else {
+                    return Err(MarketplaceError::NotDisputeParty)
+                };
+                self.check_not_disputed(rental.contract_address, &rental.token_id)?;
+                ensure!(
+                    now <= rental
+                        .end_time
+                        .saturating_add(self.data::<Data>().v1.rental_dispute_window),
+                    MarketplaceError::DisputeWindowClosed
+                );
+                (rental.contract_address, rental.token_id, respondent)
+            }
+        };
+
+        let dispute_id = self.data::<Data>().v1.last_dispute_id + 1;
+        self.data::<Data>()
+            .disputed_tokens
+            .insert(&(contract_address, token_key(&token_id)), &dispute_id);
+        self.data::<Data>()
+            .subject_disputes
+            .insert(&subject, &dispute_id);
+        self.data::<Data>().disputes.insert(
+            &dispute_id,
+            &Dispute {
+                subject,
+                contract_address,
+                token_id,
+                claimant,
+                respondent,
+                opened_at: now,
+                ruling: None,
+            },
+        );
+        self.data::<Data>().v1.last_dispute_id = dispute_id;
+
+        self.emit_dispute_opened_event(dispute_id, subject, claimant);
+        Ok(dispute_id)
+    }
+
+    default fn record_ruling(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<Dispute, MarketplaceError> {
+        let mut dispute = self
+            .data::<Data>()
+            .disputes
+            .get(&dispute_id)
+            .ok_or(MarketplaceError::DisputeNotFound)?;
+        ensure!(
+            dispute.ruling.is_none(),
+            MarketplaceError::DisputeAlreadyResolved
+        );
+
+        dispute.ruling = Some(DisputeRuling {
+            claimant_wins,
+            rationale: rationale.clone(),
+            resolved_at: Self::env().block_timestamp(),
+        });
+        self.data::<Data>().disputes.insert(&dispute_id, &dispute);
+        self.data::<Data>()
+            .subject_disputes
+            .remove(&dispute.subject);
+        // the NFT has to be unfrozen before the trade is settled
+        self.data::<Data>()
+            .disputed_tokens
+            .remove(&(dispute.contract_address, token_key(&dispute.token_id)));
+
+        self.emit_dispute_ruled_event(dispute_id, claimant_wins, rationale);
+        Ok(dispute)
+    }
+
+    default fn rule(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<(), MarketplaceError> {
+        let dispute = self.record_ruling(dispute_id, claimant_wins, rationale)?;
+        match dispute.subject {
+            DisputeSubject::Escrow(escrow_id) => {
+                let escrow = self
+                    .data::<Data>()
+                    .escrows
+                    .get(&escrow_id)
+                    .ok_or(MarketplaceError::EscrowNotFound)?;
+                let refund_buyer = claimant_wins == (dispute.claimant == escrow.buyer);
+                self.close_escrow(escrow_id, escrow, refund_buyer)?;
+                self.emit_dispute_resolved_event(escrow_id, refund_buyer);
+            }
+            DisputeSubject::Rental(rental_id) => {
+                let rental = self
+                    .data::<Data>()
+                    .rentals
+                    .get(&rental_id)
+                    .ok_or(MarketplaceError::RentalNotFound)?;
+                if claimant_wins == (dispute.claimant == rental.renter) {
+                    let refund = self.close_rental(rental_id, rental)?;
+                    self.emit_rental_ended_event(rental_id, refund);
+                } else {
+                    self.forfeit_collateral(rental_id, rental)?;
+                    self.emit_rental_defaulted_event(rental_id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,461 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_lending::Internal as LendingInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, EditionLevel, EditionOrder, EditionSide},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::editions::MarketplaceEditions,
+};
+use ink::prelude::{vec, vec::Vec};
+use openbrush::{
+    contracts::{psp34::Id, psp37::PSP37Ref, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets value of units at a unit price.
+    fn edition_value(&self, price: Balance, amount: Balance) -> Result<Balance, MarketplaceError>;
+
+    /// Adds order to the price level of its edition side.
+    fn add_edition_order(&mut self, order_id: u128, order: &EditionOrder);
+
+    /// Takes filled or cancelled units of an order off its price level and stores the order
+    /// or removes it once no units are left.
+    fn reduce_edition_order(&mut self, order_id: u128, order: EditionOrder, amount: Balance);
+
+    /// Pays value of sold units to the seller less the marketplace fee.
+    fn pay_edition_seller(
+        &mut self,
+        seller: AccountId,
+        value: Balance,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceEditionsEvents {
+    fn emit_edition_order_made_event(
+        &self,
+        order_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        maker: AccountId,
+        side: EditionSide,
+        price: Balance,
+        amount: Balance,
+    );
+    fn emit_edition_order_cancelled_event(&self, order_id: u128);
+    fn emit_edition_order_filled_event(
+        &self,
+        order_id: u128,
+        taker: AccountId,
+        price: Balance,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceEditions for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Posts a maker order for units of an edition.
+    #[modifiers(when_not_taking_custody)]
+    default fn make_edition_order(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        side: EditionSide,
+        price: Balance,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(
+            price > 0 && amount > 0,
+            MarketplaceError::InvalidEditionOrder
+        );
+
+        match side {
+            EditionSide::Ask => {
+                let marketplace = Self::env().account_id();
+                ensure!(
+                    PSP37Ref::balance_of(&contract_address, caller, Some(token_id.clone()))
+                        >= amount,
+                    MarketplaceError::NotOwner
+                );
+                ensure!(
+                    PSP37Ref::allowance(
+                        &contract_address,
+                        caller,
+                        marketplace,
+                        Some(token_id.clone())
+                    ) >= amount,
+                    MarketplaceError::TokenNotApproved
+                );
+            }
+            EditionSide::Bid => {
+                let value = self.edition_value(price, amount)?;
+                let deposit = self.get_deposit_internal(caller);
+                ensure!(deposit >= value, MarketplaceError::BalanceInsufficient);
+                self.set_currency_deposit(caller, Currency::Native, deposit - value);
+                self.lock_funds(value);
+            }
+        }
+
+        let order_id = self.data::<Data>().v1.last_edition_order_id + 1;
+        let order = EditionOrder {
+            maker: caller,
+            contract_address,
+            token_id: token_id.clone(),
+            side,
+            price,
+            amount,
+        };
+        self.data::<Data>().edition_orders.insert(&order_id, &order);
+        self.add_edition_order(order_id, &order);
+        self.data::<Data>().v1.last_edition_order_id = order_id;
+
+        self.emit_edition_order_made_event(
+            order_id,
+            contract_address,
+            token_id,
+            caller,
+            side,
+            price,
+            amount,
+        );
+        Ok(())
+    }
+
+    /// Cancels edition order.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_edition_order(&mut self, order_id: u128) -> Result<(), MarketplaceError> {
+        let order = self
+            .data::<Data>()
+            .edition_orders
+            .get(&order_id)
+            .ok_or(MarketplaceError::EditionOrderNotFound)?;
+        ensure!(
+            Self::env().caller() == order.maker,
+            MarketplaceError::NotOwner
+        );
+
+        if order.side == EditionSide::Bid {
+            let value = self.edition_value(order.price, order.amount)?;
+            self.unlock_funds(value);
+            self.credit_deposit(order.maker, value)?;
+        }
+        let amount = order.amount;
+        self.reduce_edition_order(order_id, order, amount);
+
+        self.emit_edition_order_cancelled_event(order_id);
+        Ok(())
+    }
+
+    /// Buys units of an edition from the lowest asks.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn buy_editions(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        amount: Balance,
+        max_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        let received = Self::env().transferred_value();
+        let levels = self
+            .data::<Data>()
+            .edition_levels
+            .get(&(contract_address, token_key(&token_id), EditionSide::Ask))
+            .unwrap_or_default();
+
+        let mut remaining = amount;
+        let mut cost: Balance = 0;
+        for level in levels {
+            if remaining == 0 || level.price > max_price {
+                break
+            }
+            for order_id in level.order_ids {
+                if remaining == 0 {
+                    break
+                }
+                let order = match self.data::<Data>().edition_orders.get(&order_id) {
+                    Some(order) => order,
+                    None => continue,
+                };
+                let filled = remaining.min(order.amount);
+                if PSP37Ref::transfer_from(
+                    &contract_address,
+                    order.maker,
+                    caller,
+                    token_id.clone(),
+                    filled,
+                    Vec::new(),
+                )
+                .is_err()
+                {
+                    // maker no longer holds or approved the units
+                    let amount = order.amount;
+                    self.reduce_edition_order(order_id, order, amount);
+                    self.emit_edition_order_cancelled_event(order_id);
+                    continue
+                }
+
+                let value = self.edition_value(level.price, filled)?;
+                cost = cost
+                    .checked_add(value)
+                    .ok_or(MarketplaceError::ArithmeticOverflow)?;
+                ensure!(
+                    received >= cost,
+                    MarketplaceError::BadBuyValue {
+                        expected: cost,
+                        received,
+                    }
+                );
+                self.pay_edition_seller(order.maker, value)?;
+                remaining -= filled;
+                self.reduce_edition_order(order_id, order, filled);
+                self.emit_edition_order_filled_event(order_id, caller, level.price, filled);
+            }
+        }
+
+        if received > cost {
+            self.transfer_funds(
+                Currency::Native,
+                caller,
+                received - cost,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Sells units of an edition to the highest bids.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn sell_editions(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        amount: Balance,
+        min_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        let levels = self
+            .data::<Data>()
+            .edition_levels
+            .get(&(contract_address, token_key(&token_id), EditionSide::Bid))
+            .unwrap_or_default();
+
+        let mut remaining = amount;
+        for level in levels.into_iter().rev() {
+            if remaining == 0 || level.price < min_price {
+                break
+            }
+            for order_id in level.order_ids {
+                if remaining == 0 {
+                    break
+                }
+                let order = match self.data::<Data>().edition_orders.get(&order_id) {
+                    Some(order) => order,
+                    None => continue,
+                };
+                let filled = remaining.min(order.amount);
+                PSP37Ref::transfer_from(
+                    &contract_address,
+                    caller,
+                    order.maker,
+                    token_id.clone(),
+                    filled,
+                    Vec::new(),
+                )
+                .map_err(|_| MarketplaceError::EditionTransferFailed)?;
+
+                let value = self.edition_value(level.price, filled)?;
+                self.unlock_funds(value);
+                self.pay_edition_seller(caller, value)?;
+                remaining -= filled;
+                self.reduce_edition_order(order_id, order, filled);
+                self.emit_edition_order_filled_event(order_id, caller, level.price, filled);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets edition order.
+    default fn get_edition_order(&self, order_id: u128) -> Option<EditionOrder> {
+        self.data::<Data>().edition_orders.get(&order_id)
+    }
+
+    /// Gets price levels of an edition side.
+    default fn get_edition_levels(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        side: EditionSide,
+    ) -> Vec<(Balance, Balance)> {
+        self.data::<Data>()
+            .edition_levels
+            .get(&(contract_address, token_key(&token_id), side))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|level| (level.price, level.amount))
+            .collect()
+    }
+}
+
+impl<T> MarketplaceEditionsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_edition_order_made_event(
+        &self,
+        _order_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _maker: AccountId,
+        _side: EditionSide,
+        _price: Balance,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_edition_order_cancelled_event(&self, _order_id: u128) {}
+
+    default fn emit_edition_order_filled_event(
+        &self,
+        _order_id: u128,
+        _taker: AccountId,
+        _price: Balance,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn edition_value(
+        &self,
+        price: Balance,
+        amount: Balance,
+    ) -> Result<Balance, MarketplaceError> {
+        price
+            .checked_mul(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)
+    }
+
+    default fn add_edition_order(&mut self, order_id: u128, order: &EditionOrder) {
+        let key = (
+            order.contract_address,
+            token_key(&order.token_id),
+            order.side,
+        );
+        let mut levels = self
+            .data::<Data>()
+            .edition_levels
+            .get(&key)
+            .unwrap_or_default();
+        match levels.binary_search_by(|level| level.price.cmp(&order.price)) {
+            Ok(index) => {
+                let level = &mut levels[index];
+                level.amount = level.amount.saturating_add(order.amount);
+                level.order_ids.push(order_id);
+            }
+            Err(index) => levels.insert(
+                index,
+                EditionLevel {
+                    price: order.price,
+                    amount: order.amount,
+                    order_ids: vec![order_id],
+                },
+            ),
+        }
+        self.data::<Data>().edition_levels.insert(&key, &levels);
+    }
+
+    default fn reduce_edition_order(
+        &mut self,
+        order_id: u128,
+        mut order: EditionOrder,
+        amount: Balance,
+    ) {
+        let key = (
+            order.contract_address,
+            token_key(&order.token_id),
+            order.side,
+        );
+        let mut levels = self
+            .data::<Data>()
+            .edition_levels
+            .get(&key)
+            .unwrap_or_default();
+        order.amount = order.amount.saturating_sub(amount);
+        if let Ok(index) = levels.binary_search_by(|level| level.price.cmp(&order.price)) {
+            let level = &mut levels[index];
+            level.amount = level.amount.saturating_sub(amount);
+            if order.amount == 0 {
+                level.order_ids.retain(|id| *id != order_id);
+            }
+            if level.order_ids.is_empty() {
+                levels.remove(index);
+            }
+        }
+        self.data::<Data>().edition_levels.insert(&key, &levels);
+
+        if order.amount == 0 {
+            self.data::<Data>().edition_orders.remove(&order_id);
+        } else {
+            self.data::<Data>().edition_orders.insert(&order_id, &order);
+        }
+    }
+
+    default fn pay_edition_seller(
+        &mut self,
+        seller: AccountId,
+        value: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let marketplace_fee = value
+            .checked_mul(self.data::<Data>().fee as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+        self.transfer_funds(
+            Currency::Native,
+            self.fee_recipient()?,
+            marketplace_fee,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+        self.transfer_funds(
+            Currency::Native,
+            seller,
+            value - marketplace_fee,
+            MarketplaceError::TransferToOwnerFailed,
+        )
+    }
+}
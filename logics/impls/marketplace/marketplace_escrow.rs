@@ -0,0 +1,297 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_disputes::Internal as DisputesInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, DisputeSubject, EscrowItem, SaleAttribution, ARBITER},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::escrow::MarketplaceEscrow,
+};
+use openbrush::{
+    contracts::{access_control::*, ownable::*, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Takes custody of the sold NFT and holds the payment until the escrow is closed.
+    fn open_escrow(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        seller: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Currency,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Either settles the sale or refunds the buyer and returns the NFT to the seller.
+    fn close_escrow(
+        &mut self,
+        escrow_id: u128,
+        escrow: EscrowItem,
+        refund_buyer: bool,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceEscrowEvents {
+    fn emit_escrow_opened_event(
+        &self,
+        escrow_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+        release_time: u64,
+    );
+    fn emit_escrow_released_event(&self, escrow_id: u128);
+    fn emit_escrow_disputed_event(&self, escrow_id: u128);
+    fn emit_dispute_resolved_event(&self, escrow_id: u128, refund_buyer: bool);
+}
+
+impl<T> MarketplaceEscrow for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<reentrancy_guard::Data>
+        + Storage<access_control::Data>,
+{
+    /// Sets how long (in milliseconds) escrowed sale proceeds are held before release.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_escrow_period(&mut self, period: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.escrow_period = period;
+        Ok(())
+    }
+
+    /// Gets escrow period in milliseconds.
+    default fn get_escrow_period(&self) -> u64 {
+        self.data::<Data>().v1.escrow_period
+    }
+
+    /// Creates a NFT item sale whose proceeds are held in escrow after purchase.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_with_escrow(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.list_item(
+            contract_address,
+            token_id,
+            price,
+            Currency::Native,
+            false,
+            true,
+        )
+    }
+
+    /// Releases escrowed proceeds to the seller and the NFT to the buyer.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn release_escrow(&mut self, escrow_id: u128) -> Result<(), MarketplaceError> {
+        let escrow = self
+            .data::<Data>()
+            .escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::EscrowNotFound)?;
+        ensure!(!escrow.disputed, MarketplaceError::EscrowDisputed);
+        ensure!(
+            Self::env().caller() == escrow.buyer
+                || Self::env().block_timestamp() >= escrow.release_time,
+            MarketplaceError::DisputeWindowOpen
+        );
+
+        self.close_escrow(escrow_id, escrow, false)?;
+        self.emit_escrow_released_event(escrow_id);
+        Ok(())
+    }
+
+    /// Disputes an escrowed purchase.
+    #[modifiers(when_not_taking_custody)]
+    default fn dispute(&mut self, escrow_id: u128) -> Result<(), MarketplaceError> {
+        let escrow = self
+            .data::<Data>()
+            .escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::EscrowNotFound)?;
+        ensure!(
+            Self::env().caller() == escrow.buyer,
+            MarketplaceError::NotBuyer
+        );
+
+        self.open_dispute_for(DisputeSubject::Escrow(escrow_id), escrow.buyer)?;
+        Ok(())
+    }
+
+    /// Resolves a disputed escrow.
+    #[modifiers(when_not_taking_custody, only_role(ARBITER), non_reentrant)]
+    default fn resolve_dispute(
+        &mut self,
+        escrow_id: u128,
+        refund_buyer: bool,
+    ) -> Result<(), MarketplaceError> {
+        let escrow = self
+            .data::<Data>()
+            .escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::EscrowNotFound)?;
+        ensure!(escrow.disputed, MarketplaceError::EscrowNotDisputed);
+
+        let subject = DisputeSubject::Escrow(escrow_id);
+        if let Some(dispute_id) = self.data::<Data>().subject_disputes.get(&subject) {
+            let claimant_is_buyer = self
+                .data::<Data>()
+                .disputes
+                .get(&dispute_id)
+                .map_or(true, |dispute| dispute.claimant == escrow.buyer);
+            return self.rule(dispute_id, refund_buyer == claimant_is_buyer, String::new())
+        }
+
+        // escrow disputed before disputes were recorded
+        self.close_escrow(escrow_id, escrow, refund_buyer)?;
+        self.emit_dispute_resolved_event(escrow_id, refund_buyer);
+        Ok(())
+    }
+
+    /// Gets escrowed purchase.
+    default fn get_escrow(&self, escrow_id: u128) -> Option<EscrowItem> {
+        self.data::<Data>().escrows.get(&escrow_id)
+    }
+}
+
+impl<T> MarketplaceEscrowEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_escrow_opened_event(
+        &self,
+        _escrow_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _buyer: AccountId,
+        _release_time: u64,
+    ) {
+    }
+
+    default fn emit_escrow_released_event(&self, _escrow_id: u128) {}
+
+    default fn emit_escrow_disputed_event(&self, _escrow_id: u128) {}
+
+    default fn emit_dispute_resolved_event(&self, _escrow_id: u128, _refund_buyer: bool) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn open_escrow(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        seller: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Currency,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError> {
+        self.check_not_blacklisted(seller)?;
+        self.check_not_blacklisted(buyer)?;
+        // delivery terms are removed with the listing when the NFT is taken into custody
+        let physical_terms = self
+            .data::<Data>()
+            .physical_listings
+            .get(&(contract_address, token_key(&token_id)));
+        self.take_custody(contract_address, token_id.clone())?;
+
+        let escrow_id = self.data::<Data>().v1.last_escrow_id + 1;
+        let release_time = Self::env().block_timestamp().saturating_add(
+            physical_terms.map_or(self.data::<Data>().v1.escrow_period, |terms| {
+                terms.delivery_period
+            }),
+        );
+        self.data::<Data>().escrows.insert(
+            &escrow_id,
+            &EscrowItem {
+                contract_address,
+                token_id: token_id.clone(),
+                seller,
+                buyer,
+                price,
+                currency,
+                release_time,
+                disputed: false,
+                attribution,
+            },
+        );
+        self.data::<Data>().v1.last_escrow_id = escrow_id;
+        if let Some(terms) = physical_terms {
+            self.data::<Data>()
+                .physical_escrows
+                .insert(&escrow_id, &terms);
+        }
+        if currency == Currency::Native {
+            self.escrow_funds(price);
+        }
+
+        self.emit_escrow_opened_event(escrow_id, contract_address, token_id, buyer, release_time);
+        Ok(())
+    }
+
+    default fn close_escrow(
+        &mut self,
+        escrow_id: u128,
+        escrow: EscrowItem,
+        refund_buyer: bool,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().escrows.remove(&escrow_id);
+        self.data::<Data>().physical_escrows.remove(&escrow_id);
+        if escrow.currency == Currency::Native {
+            self.release_escrowed_funds(escrow.price);
+        }
+
+        if !refund_buyer {
+            return self.settle_sale(
+                escrow.contract_address,
+                escrow.token_id,
+                escrow.seller,
+                escrow.buyer,
+                escrow.price,
+                escrow.currency,
+                escrow.attribution,
+            )
+        }
+
+        self.release_custody(escrow.contract_address, escrow.seller, escrow.token_id)?;
+        self.transfer_funds(
+            escrow.currency,
+            escrow.buyer,
+            escrow.price,
+            MarketplaceError::RefundFailed,
+        )
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::{Internal as SaleInternal, MarketplaceSaleEvents},
+    types::{NftContractType, RegisteredCollection},
+    xvm,
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::{factory::MarketplaceFactory, royalty_registry::RoyaltyRegistryRef},
+};
+use ink::{prelude::vec::Vec, ToAccountId};
+use nft::nft::NFTContractRef;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Hash, Storage, String},
+};
+
+impl<T> MarketplaceFactory for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Sets a hash of a Shiden34 contract to be instantiated by factory call.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_nft_contract_hash(
+        &mut self,
+        contract_type: NftContractType,
+        contract_hash: Hash,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>()
+            .nft_contract_hash
+            .insert(&contract_type, &contract_hash);
+        Ok(())
+    }
+
+    /// Gets a NFT contract hash.
+    default fn nft_contract_hash(
+        &self,
+        contract_type: NftContractType,
+    ) -> Result<Hash, MarketplaceError> {
+        self.get_nft_contract_hash(&contract_type)
+    }
+
+    /// Gets all configured NFT contract hashes.
+    default fn get_nft_contract_types(&self) -> Vec<(NftContractType, Hash)> {
+        [
+            NftContractType::Psp34,
+            NftContractType::Rmrk,
+            NftContractType::NFTSeries,
+        ]
+        .into_iter()
+        .filter_map(|contract_type| {
+            self.data::<Data>()
+                .nft_contract_hash
+                .get(&contract_type)
+                .map(|hash| (contract_type, hash))
+        })
+        .collect()
+    }
+
+    /// Registers NFT collection to the marketplace.
+    #[modifiers(when_not_taking_custody)]
+    default fn register(
+        &mut self,
+        contract_address: AccountId,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+        contract_type: NftContractType,
+    ) -> Result<(), MarketplaceError> {
+        if contract_type == NftContractType::Erc721Xvm {
+            ensure!(cfg!(feature = "xvm"), MarketplaceError::XvmNotSupported);
+            // EVM collections have no ink! owner to check against
+            ensure!(
+                xvm::is_evm_address(&contract_address),
+                MarketplaceError::InvalidEvmAddress
+            );
+            ensure!(
+                Self::env().caller() == self.data::<ownable::Data>().owner,
+                MarketplaceError::NotOwner
+            );
+        } else {
+            self.check_collection_owner(self.data::<ownable::Data>().owner, contract_address)?;
+        }
+        ensure!(
+            !self
+                .data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::ContractAlreadyRegistered
+        );
+
+        let royalty = self.collection_royalty(royalty_receiver, royalty)?;
+        self.data::<Data>().registered_collections.insert(
+            &contract_address,
+            &RegisteredCollection {
+                royalty,
+                contract_type,
+            },
+        );
+        self.emit_collection_registered_event(contract_address);
+        Ok(())
+    }
+
+    /// Updates royalty of a registered collection.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_collection_royalty(
+        &mut self,
+        contract_address: AccountId,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+    ) -> Result<(), MarketplaceError> {
+        self.check_collection_owner(self.data::<ownable::Data>().owner, contract_address)?;
+        let mut collection = self.registered_collection(contract_address)?;
+
+        let old_royalty = collection.royalty;
+        collection.royalty = self.collection_royalty(royalty_receiver, royalty)?;
+        self.data::<Data>()
+            .registered_collections
+            .insert(&contract_address, &collection);
+        if let Some(registry) = self.data::<Data>().v1.royalty_registry {
+            RoyaltyRegistryRef::set_royalty(&registry, contract_address, collection.royalty)?;
+        }
+
+        self.emit_collection_royalty_updated_event(
+            contract_address,
+            old_royalty,
+            collection.royalty,
+        );
+        Ok(())
+    }
+
+    /// Sets metadata of a registered collection.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_collection_metadata(
+        &mut self,
+        contract_address: AccountId,
+        metadata: String,
+    ) -> Result<(), MarketplaceError> {
+        self.check_collection_owner(self.data::<ownable::Data>().owner, contract_address)?;
+        self.registered_collection(contract_address)?;
+
+        let old_metadata = self
+            .data::<Data>()
+            .collection_metadata
+            .get(&contract_address);
+        self.data::<Data>()
+            .collection_metadata
+            .insert(&contract_address, &metadata);
+
+        self.emit_collection_metadata_updated_event(contract_address, old_metadata, metadata);
+        Ok(())
+    }
+
+    /// Gets metadata of a registered collection.
+    default fn get_collection_metadata(&self, contract_address: AccountId) -> Option<String> {
+        self.data::<Data>()
+            .collection_metadata
+            .get(&contract_address)
+    }
+
+    /// Marks a registered collection as verified or unverified.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_collection_verified(
+        &mut self,
+        contract_address: AccountId,
+        verified: bool,
+    ) -> Result<(), MarketplaceError> {
+        self.registered_collection(contract_address)?;
+
+        let old_verified = self.is_collection_verified(contract_address);
+        if verified {
+            self.data::<Data>()
+                .verified_collections
+                .insert(&contract_address, &());
+        } else {
+            self.data::<Data>()
+                .verified_collections
+                .remove(&contract_address);
+        }
+
+        self.emit_collection_verification_updated_event(contract_address, old_verified, verified);
+        Ok(())
+    }
+
+    /// Checks if a registered collection is verified.
+    default fn is_collection_verified(&self, contract_address: AccountId) -> bool {
+        self.data::<Data>()
+            .verified_collections
+            .contains(&contract_address)
+    }
+
+    /// Removes NFT collection from the marketplace.
+    #[modifiers(when_not_taking_custody)]
+    default fn unregister(&mut self, contract_address: AccountId) -> Result<(), MarketplaceError> {
+        self.check_collection_owner(self.data::<ownable::Data>().owner, contract_address)?;
+        let collection = self.registered_collection(contract_address)?;
+        let verified = self.is_collection_verified(contract_address);
+
+        self.data::<Data>()
+            .registered_collections
+            .remove(&contract_address);
+        self.data::<Data>()
+            .verified_collections
+            .remove(&contract_address);
+        self.data::<Data>()
+            .collection_metadata
+            .remove(&contract_address);
+
+        self.emit_collection_unregistered_event(contract_address, collection.royalty, verified);
+        Ok(())
+    }
+
+    /// Creates a new NFT Series collection and registers it to the marketplace.
+    #[modifiers(when_not_taking_custody)]
+    default fn create_collection(
+        &mut self,
+        name: String,
+        symbol: String,
+        royalty: Option<u16>,
+    ) -> Result<AccountId, MarketplaceError> {
+        let caller = Self::env().caller();
+        if let Some(royalty) = royalty {
+            let max_fee = self.data::<Data>().max_fee;
+            self.check_fee(royalty, max_fee)?;
+        }
+        let code_hash = self.get_nft_contract_hash(&NftContractType::NFTSeries)?;
+
+        let nonce = self.data::<Data>().nonce;
+        let fee_recipient = self.fee_recipient()?;
+        let fee = self.data::<Data>().fee;
+        let contract = NFTContractRef::new(name, symbol, fee_recipient, fee)
+            .code_hash(code_hash)
+            .endowment(0)
+            .salt_bytes(nonce.to_le_bytes())
+            .try_instantiate()
+            .map_err(|_| MarketplaceError::ContractInstantiationFailed)?
+            .map_err(|_| MarketplaceError::ContractInstantiationFailed)?;
+        self.data::<Data>().nonce = nonce + 1;
+
+        let contract_address = contract.to_account_id();
+        OwnableRef::transfer_ownership(&contract_address, caller)
+            .map_err(|_| MarketplaceError::ContractInstantiationFailed)?;
+        self.emit_factory_instantiated_event(
+            caller,
+            contract_address,
+            NftContractType::NFTSeries,
+            code_hash,
+        );
+
+        self.data::<Data>().registered_collections.insert(
+            &contract_address,
+            &RegisteredCollection {
+                royalty: royalty.map(|royalty| (caller, royalty)),
+                contract_type: NftContractType::NFTSeries,
+            },
+        );
+        self.data::<Data>()
+            .verified_collections
+            .insert(&contract_address, &());
+        self.emit_collection_registered_event(contract_address);
+        Ok(contract_address)
+    }
+
+    /// Gets registered collection.
+    default fn get_registered_collection(
+        &self,
+        contract_address: AccountId,
+    ) -> Option<RegisteredCollection> {
+        self.data::<Data>()
+            .registered_collections
+            .get(&contract_address)
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Flash sales.
+//!
+//! A flash sale discounts listings of a collection, or of one seller in a collection, for a
+//! time window. Listings keep their stored price; buy methods and price queries apply the
+//! biggest discount active at the time of the call through `flash_sale_price`.
+
+use super::{
+    marketplace_custody::when_not_taking_custody, marketplace_sale::Internal as SaleInternal,
+    types::Item,
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, FlashSale, MarketplaceError},
+    traits::flash_sales::MarketplaceFlashSales,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets price of a listing with the discount of active flash sales applied.
+    fn flash_sale_price(&self, contract_address: AccountId, item: &Item) -> Balance;
+}
+
+pub trait MarketplaceFlashSalesEvents {
+    fn emit_flash_sale_scheduled_event(
+        &self,
+        contract: AccountId,
+        seller: Option<AccountId>,
+        flash_sale: FlashSale,
+    );
+    fn emit_flash_sale_cancelled_event(&self, contract: AccountId, seller: Option<AccountId>);
+}
+
+impl<T> MarketplaceFlashSales for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn schedule_flash_sale(
+        &mut self,
+        contract_address: AccountId,
+        collection_wide: bool,
+        start: u64,
+        end: u64,
+        discount: u16,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::NotRegisteredContract
+        );
+        let seller = if collection_wide {
+            self.check_collection_owner(self.owner(), contract_address)?;
+            None
+        } else {
+            Some(Self::env().caller())
+        };
+        ensure!(
+            discount > 0 && discount < 10_000 && start < end && end > Self::env().block_timestamp(),
+            MarketplaceError::InvalidFlashSale
+        );
+
+        let flash_sale = FlashSale {
+            start,
+            end,
+            discount,
+        };
+        self.data::<Data>()
+            .flash_sales
+            .insert(&(contract_address, seller), &flash_sale);
+
+        self.emit_flash_sale_scheduled_event(contract_address, seller, flash_sale);
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_flash_sale(
+        &mut self,
+        contract_address: AccountId,
+        collection_wide: bool,
+    ) -> Result<(), MarketplaceError> {
+        let seller = if collection_wide {
+            self.check_collection_owner(self.owner(), contract_address)?;
+            None
+        } else {
+            Some(Self::env().caller())
+        };
+        ensure!(
+            self.data::<Data>()
+                .flash_sales
+                .contains(&(contract_address, seller)),
+            MarketplaceError::FlashSaleNotFound
+        );
+
+        self.data::<Data>()
+            .flash_sales
+            .remove(&(contract_address, seller));
+
+        self.emit_flash_sale_cancelled_event(contract_address, seller);
+        Ok(())
+    }
+
+    default fn get_flash_sale(
+        &self,
+        contract_address: AccountId,
+        seller: Option<AccountId>,
+    ) -> Option<FlashSale> {
+        self.data::<Data>()
+            .flash_sales
+            .get(&(contract_address, seller))
+    }
+}
+
+impl<T> MarketplaceFlashSalesEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_flash_sale_scheduled_event(
+        &self,
+        _contract: AccountId,
+        _seller: Option<AccountId>,
+        _flash_sale: FlashSale,
+    ) {
+    }
+
+    default fn emit_flash_sale_cancelled_event(
+        &self,
+        _contract: AccountId,
+        _seller: Option<AccountId>,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn flash_sale_price(&self, contract_address: AccountId, item: &Item) -> Balance {
+        let now = Self::env().block_timestamp();
+        let discount = [None, Some(item.owner)]
+            .into_iter()
+            .filter_map(|seller| {
+                self.data::<Data>()
+                    .flash_sales
+                    .get(&(contract_address, seller))
+            })
+            .filter(|flash_sale| flash_sale.start <= now && now < flash_sale.end)
+            .map(|flash_sale| flash_sale.discount)
+            .max()
+            .unwrap_or_default();
+
+        item.price - item.price.saturating_mul(discount as u128) / 10_000
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Buyout, Currency, FractionVault},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::fractions::MarketplaceFractions,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{psp22::PSP22Ref, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Time (in milliseconds) a buyout can be outbid after the first bid.
+pub const BUYOUT_DURATION: u64 = 604_800_000;
+
+pub trait Internal {
+    /// Gets part of the winning bid redeemed by shares of a vault.
+    fn redeem_value(
+        &self,
+        vault: &FractionVault,
+        shares: Balance,
+    ) -> Result<Balance, MarketplaceError>;
+
+    /// Pays value of sold shares to the vault owner less the marketplace fee.
+    fn pay_vault_owner(&mut self, owner: AccountId, value: Balance)
+        -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceFractionsEvents {
+    fn emit_fractionalized_event(
+        &self,
+        vault_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        share_token: AccountId,
+        total_shares: Balance,
+    );
+    fn emit_fraction_shares_bought_event(&self, vault_id: u128, buyer: AccountId, shares: Balance);
+    fn emit_buyout_bid_event(&self, vault_id: u128, bidder: AccountId, bid: Balance, end_time: u64);
+    fn emit_buyout_ended_event(&self, vault_id: u128, winner: AccountId, bid: Balance);
+    fn emit_fraction_shares_redeemed_event(
+        &self,
+        vault_id: u128,
+        holder: AccountId,
+        shares: Balance,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceFractions for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Fractionalizes caller's NFT.
+    #[modifiers(when_not_taking_custody)]
+    default fn fractionalize(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        share_token: AccountId,
+        shares_for_sale: Balance,
+        share_price: Balance,
+        reserve_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_token_allowance(contract_address, &token_id)?;
+        let total_shares = PSP22Ref::total_supply(&share_token);
+        ensure!(
+            total_shares > 0
+                && shares_for_sale <= total_shares
+                && PSP22Ref::balance_of(&share_token, caller) == total_shares,
+            MarketplaceError::InvalidFractionShares
+        );
+
+        self.take_custody(contract_address, token_id.clone())?;
+        if shares_for_sale > 0 {
+            PSP22Ref::transfer_from(
+                &share_token,
+                caller,
+                Self::env().account_id(),
+                shares_for_sale,
+                Vec::new(),
+            )
+            .map_err(|_| MarketplaceError::ShareTransferFailed)?;
+        }
+
+        let vault_id = self.data::<Data>().v1.last_fraction_vault_id + 1;
+        let vault = FractionVault {
+            owner: caller,
+            contract_address,
+            token_id: token_id.clone(),
+            share_token,
+            total_shares,
+            shares_for_sale,
+            share_price,
+            reserve_price,
+            buyout: None,
+            bought_out: false,
+        };
+        self.data::<Data>()
+            .fraction_vaults
+            .insert(&vault_id, &vault);
+        self.data::<Data>().v1.last_fraction_vault_id = vault_id;
+
+        self.emit_fractionalized_event(
+            vault_id,
+            contract_address,
+            token_id,
+            share_token,
+            total_shares,
+        );
+        Ok(())
+    }
+
+    /// Buys shares of a vault.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn buy_fraction_shares(
+        &mut self,
+        vault_id: u128,
+        shares: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let mut vault = self
+            .data::<Data>()
+            .fraction_vaults
+            .get(&vault_id)
+            .ok_or(MarketplaceError::FractionVaultNotFound)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(!vault.bought_out, MarketplaceError::VaultBoughtOut);
+        ensure!(
+            shares > 0 && shares <= vault.shares_for_sale,
+            MarketplaceError::FractionSharesSoldOut
+        );
+        let expected = vault
+            .share_price
+            .checked_mul(shares)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == expected,
+            MarketplaceError::BadBuyValue { expected, received }
+        );
+
+        vault.shares_for_sale -= shares;
+        self.data::<Data>()
+            .fraction_vaults
+            .insert(&vault_id, &vault);
+        self.transfer_funds(
+            Currency::Psp22(vault.share_token),
+            caller,
+            shares,
+            MarketplaceError::ShareTransferFailed,
+        )?;
+        self.pay_vault_owner(vault.owner, received)?;
+
+        self.emit_fraction_shares_bought_event(vault_id, caller, shares);
+        Ok(())
+    }
+
+    /// Bids to buy the NFT of a vault out.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn bid_buyout(&mut self, vault_id: u128) -> Result<(), MarketplaceError> {
+        let mut vault = self
+            .data::<Data>()
+            .fraction_vaults
+            .get(&vault_id)
+            .ok_or(MarketplaceError::FractionVaultNotFound)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(!vault.bought_out, MarketplaceError::VaultBoughtOut);
+        let bid = Self::env().transferred_value();
+        self.check_tick_size(bid)?;
+        let now = Self::env().block_timestamp();
+
+        let end_time = match vault.buyout {
+            Some(buyout) => {
+                ensure!(now <= buyout.end_time, MarketplaceError::BuyoutEnded);
+                ensure!(bid > buyout.bid, MarketplaceError::BuyoutBidTooLow);
+                self.release_escrowed_funds(buyout.bid);
+                self.transfer_funds(
+                    Currency::Native,
+                    buyout.bidder,
+                    buyout.bid,
+                    MarketplaceError::RefundFailed,
+                )?;
+                buyout.end_time
+            }
+            None => {
+                ensure!(
+                    bid > 0 && bid >= vault.reserve_price,
+                    MarketplaceError::BuyoutBidTooLow
+                );
+                now.saturating_add(BUYOUT_DURATION)
+            }
+        };
+        self.escrow_funds(bid);
+        vault.buyout = Some(Buyout {
+            bidder: caller,
+            bid,
+            end_time,
+        });
+        self.data::<Data>()
+            .fraction_vaults
+            .insert(&vault_id, &vault);
+
+        self.emit_buyout_bid_event(vault_id, caller, bid, end_time);
+        Ok(())
+    }
+
+    /// Transfers the NFT of a vault to the buyout winner.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn end_buyout(&mut self, vault_id: u128) -> Result<(), MarketplaceError> {
+        let mut vault = self
+            .data::<Data>()
+            .fraction_vaults
+            .get(&vault_id)
+            .ok_or(MarketplaceError::FractionVaultNotFound)?;
+        ensure!(!vault.bought_out, MarketplaceError::VaultBoughtOut);
+        let buyout = vault
+            .buyout
+            .clone()
+            .ok_or(MarketplaceError::BuyoutNotStarted)?;
+        ensure!(
+            Self::env().block_timestamp() > buyout.end_time,
+            MarketplaceError::BuyoutNotEnded
+        );
+
+        // shares still for sale belong to the vault owner
+        let unsold_value = self.redeem_value(&vault, vault.shares_for_sale)?;
+        vault.shares_for_sale = 0;
+        vault.bought_out = true;
+        self.data::<Data>()
+            .fraction_vaults
+            .insert(&vault_id, &vault);
+        self.release_custody(
+            vault.contract_address,
+            buyout.bidder,
+            vault.token_id.clone(),
+        )?;
+        if unsold_value > 0 {
+            self.release_escrowed_funds(unsold_value);
+            self.transfer_proceeds(Currency::Native, vault.owner, unsold_value)?;
+        }
+
+        self.emit_buyout_ended_event(vault_id, buyout.bidder, buyout.bid);
+        Ok(())
+    }
+
+    /// Redeems caller's shares of a bought out vault.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn redeem_fraction_shares(
+        &mut self,
+        vault_id: u128,
+        shares: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let vault = self
+            .data::<Data>()
+            .fraction_vaults
+            .get(&vault_id)
+            .ok_or(MarketplaceError::FractionVaultNotFound)?;
+        ensure!(vault.bought_out, MarketplaceError::VaultNotBoughtOut);
+        let caller = Self::env().caller();
+        let amount = self.redeem_value(&vault, shares)?;
+
+        PSP22Ref::transfer_from(
+            &vault.share_token,
+            caller,
+            Self::env().account_id(),
+            shares,
+            Vec::new(),
+        )
+        .map_err(|_| MarketplaceError::ShareTransferFailed)?;
+        self.release_escrowed_funds(amount);
+        self.transfer_funds(
+            Currency::Native,
+            caller,
+            amount,
+            MarketplaceError::TransferToOwnerFailed,
+        )?;
+
+        self.emit_fraction_shares_redeemed_event(vault_id, caller, shares, amount);
+        Ok(())
+    }
+
+    /// Gets fraction vault.
+    default fn get_fraction_vault(&self, vault_id: u128) -> Option<FractionVault> {
+        self.data::<Data>().fraction_vaults.get(&vault_id)
+    }
+}
+
+impl<T> MarketplaceFractionsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_fractionalized_event(
+        &self,
+        _vault_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _share_token: AccountId,
+        _total_shares: Balance,
+    ) {
+    }
+
+    default fn emit_fraction_shares_bought_event(
+        &self,
+        _vault_id: u128,
+        _buyer: AccountId,
+        _shares: Balance,
+    ) {
+    }
+
+    default fn emit_buyout_bid_event(
+        &self,
+        _vault_id: u128,
+        _bidder: AccountId,
+        _bid: Balance,
+        _end_time: u64,
+    ) {
+    }
+
+    default fn emit_buyout_ended_event(&self, _vault_id: u128, _winner: AccountId, _bid: Balance) {}
+
+    default fn emit_fraction_shares_redeemed_event(
+        &self,
+        _vault_id: u128,
+        _holder: AccountId,
+        _shares: Balance,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn redeem_value(
+        &self,
+        vault: &FractionVault,
+        shares: Balance,
+    ) -> Result<Balance, MarketplaceError> {
+        let bid = vault.buyout.as_ref().map_or(0, |buyout| buyout.bid);
+        Ok(bid
+            .checked_mul(shares)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / vault.total_shares)
+    }
+
+    default fn pay_vault_owner(
+        &mut self,
+        owner: AccountId,
+        value: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let marketplace_fee = value
+            .checked_mul(self.data::<Data>().fee as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+        self.transfer_funds(
+            Currency::Native,
+            self.fee_recipient()?,
+            marketplace_fee,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+        self.transfer_proceeds(Currency::Native, owner, value - marketplace_fee)
+    }
+}
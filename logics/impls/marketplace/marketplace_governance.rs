@@ -0,0 +1,263 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! On-chain parameter governance.
+//!
+//! Stakers of the governance token propose parameter changes and vote on them with their
+//! active stake. Passed proposals go through the same timelock as owner parameter changes.
+//! Voters cannot unstake until voting ends, so the same tokens cannot vote twice from
+//! different accounts.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_staking::Internal as StakingInternal,
+    marketplace_timelock::Internal as TimelockInternal,
+    types::{ParameterChange, Proposal, ProposalStatus},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::governance::MarketplaceGovernance,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets voting weight of an account, its stake earning rewards.
+    fn voting_weight(&mut self, account: AccountId) -> Balance;
+}
+
+pub trait MarketplaceGovernanceEvents {
+    fn emit_proposal_created_event(
+        &self,
+        proposal_id: u128,
+        proposer: AccountId,
+        change: ParameterChange,
+    );
+    fn emit_vote_cast_event(
+        &self,
+        proposal_id: u128,
+        voter: AccountId,
+        support: bool,
+        weight: Balance,
+    );
+    fn emit_proposal_finalized_event(&self, proposal_id: u128, status: ProposalStatus);
+}
+
+impl<T> MarketplaceGovernance for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<pausable::Data>,
+{
+    /// Sets governance quorum, threshold and voting period.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_governance_parameters(
+        &mut self,
+        quorum: Balance,
+        threshold: u16,
+        voting_period: u64,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            threshold < 10_000,
+            MarketplaceError::InvalidGovernanceParameters
+        );
+
+        self.data::<Data>().governance_quorum.set(&quorum);
+        self.data::<Data>().governance_threshold.set(&threshold);
+        self.data::<Data>().voting_period.set(&voting_period);
+        Ok(())
+    }
+
+    /// Gets governance quorum, threshold and voting period.
+    default fn get_governance_parameters(&self) -> (Balance, u16, u64) {
+        let data = self.data::<Data>();
+        (
+            data.governance_quorum.get().unwrap_or_default(),
+            data.governance_threshold.get().unwrap_or_default(),
+            data.voting_period.get().unwrap_or_default(),
+        )
+    }
+
+    /// Proposes a parameter change.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn propose(&mut self, change: ParameterChange) -> Result<u128, MarketplaceError> {
+        let voting_period = self.data::<Data>().voting_period.get().unwrap_or_default();
+        ensure!(voting_period > 0, MarketplaceError::GovernanceNotConfigured);
+        let proposer = Self::env().caller();
+        ensure!(
+            self.voting_weight(proposer) > 0,
+            MarketplaceError::NotStaker
+        );
+
+        let proposal_id = self.data::<Data>().v1.last_proposal_id + 1;
+        self.data::<Data>().proposals.insert(
+            &proposal_id,
+            &Proposal {
+                proposer,
+                change: change.clone(),
+                votes_for: 0,
+                votes_against: 0,
+                end_time: Self::env().block_timestamp().saturating_add(voting_period),
+                status: ProposalStatus::Active,
+            },
+        );
+        self.data::<Data>().v1.last_proposal_id = proposal_id;
+
+        self.emit_proposal_created_event(proposal_id, proposer, change);
+        Ok(proposal_id)
+    }
+
+    /// Votes on a proposal with the active stake of the caller.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn vote(&mut self, proposal_id: u128, support: bool) -> Result<(), MarketplaceError> {
+        let mut proposal = self
+            .data::<Data>()
+            .proposals
+            .get(&proposal_id)
+            .ok_or(MarketplaceError::ProposalNotFound)?;
+        ensure!(
+            proposal.status == ProposalStatus::Active,
+            MarketplaceError::ProposalNotActive
+        );
+        ensure!(
+            Self::env().block_timestamp() < proposal.end_time,
+            MarketplaceError::VotingClosed
+        );
+        let voter = Self::env().caller();
+        ensure!(
+            !self.has_voted(proposal_id, voter),
+            MarketplaceError::AlreadyVoted
+        );
+        let weight = self.voting_weight(voter);
+        ensure!(weight > 0, MarketplaceError::NotStaker);
+
+        if support {
+            proposal.votes_for = proposal.votes_for.saturating_add(weight);
+        } else {
+            proposal.votes_against = proposal.votes_against.saturating_add(weight);
+        }
+        self.data::<Data>()
+            .proposals
+            .insert(&proposal_id, &proposal);
+        self.data::<Data>()
+            .proposal_votes
+            .insert(&(proposal_id, voter), &());
+        let locked_until = self
+            .data::<Data>()
+            .vote_locks
+            .get(&voter)
+            .unwrap_or_default()
+            .max(proposal.end_time);
+        self.data::<Data>().vote_locks.insert(&voter, &locked_until);
+
+        self.emit_vote_cast_event(proposal_id, voter, support, weight);
+        Ok(())
+    }
+
+    /// Counts votes of a proposal once voting ended.
+    #[modifiers(when_not_taking_custody)]
+    default fn finalize_proposal(&mut self, proposal_id: u128) -> Result<(), MarketplaceError> {
+        let mut proposal = self
+            .data::<Data>()
+            .proposals
+            .get(&proposal_id)
+            .ok_or(MarketplaceError::ProposalNotFound)?;
+        ensure!(
+            proposal.status == ProposalStatus::Active,
+            MarketplaceError::ProposalNotActive
+        );
+        ensure!(
+            Self::env().block_timestamp() >= proposal.end_time,
+            MarketplaceError::VotingNotEnded
+        );
+
+        let votes = proposal.votes_for.saturating_add(proposal.votes_against);
+        let (quorum, threshold, _) = self.get_governance_parameters();
+        let passed = votes > 0
+            && votes >= quorum
+            && proposal.votes_for.saturating_mul(10_000) > votes.saturating_mul(threshold as u128);
+
+        proposal.status = if !passed {
+            ProposalStatus::Rejected
+        } else if self.queue_parameter_change(proposal.change.clone()).is_ok() {
+            ProposalStatus::Passed
+        } else {
+            // finalized anyway so the proposal does not stay active
+            ProposalStatus::Invalid
+        };
+        self.data::<Data>()
+            .proposals
+            .insert(&proposal_id, &proposal);
+
+        self.emit_proposal_finalized_event(proposal_id, proposal.status);
+        Ok(())
+    }
+
+    /// Gets proposal.
+    default fn get_proposal(&self, proposal_id: u128) -> Option<Proposal> {
+        self.data::<Data>().proposals.get(&proposal_id)
+    }
+
+    /// Checks if an account voted on a proposal.
+    default fn has_voted(&self, proposal_id: u128, account: AccountId) -> bool {
+        self.data::<Data>()
+            .proposal_votes
+            .contains(&(proposal_id, account))
+    }
+}
+
+impl<T> MarketplaceGovernanceEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_proposal_created_event(
+        &self,
+        _proposal_id: u128,
+        _proposer: AccountId,
+        _change: ParameterChange,
+    ) {
+    }
+
+    default fn emit_vote_cast_event(
+        &self,
+        _proposal_id: u128,
+        _voter: AccountId,
+        _support: bool,
+        _weight: Balance,
+    ) {
+    }
+
+    default fn emit_proposal_finalized_event(&self, _proposal_id: u128, _status: ProposalStatus) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn voting_weight(&mut self, account: AccountId) -> Balance {
+        self.advance_epoch();
+        let stake = self.update_stake(account);
+        self.data::<Data>().stakes.insert(&account, &stake);
+        stake.amount
+    }
+}
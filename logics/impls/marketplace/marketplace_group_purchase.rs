@@ -0,0 +1,335 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    contract_calls,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, GroupPurchase, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::group_purchase::MarketplaceGroupPurchase,
+};
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Checks if the NFT is still listed for native currency at the group purchase target.
+    fn is_group_listing_available(&self, group: &GroupPurchase) -> bool;
+
+    /// Checks if contributions to a group purchase can be refunded, which is when the group
+    /// purchase expired or its listing changed before it was executed.
+    fn is_group_refundable(&self, group: &GroupPurchase) -> bool;
+
+    /// Refunds contribution of `contributor` to a refundable group purchase.
+    fn refund_group_contribution(
+        &mut self,
+        group_id: u128,
+        group: GroupPurchase,
+        contributor: AccountId,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceGroupPurchaseEvents {
+    fn emit_group_purchase_created_event(
+        &self,
+        group_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        target: Balance,
+    );
+    fn emit_contributed_event(&self, group_id: u128, contributor: AccountId, amount: Balance);
+    fn emit_group_purchase_executed_event(&self, group_id: u128);
+    fn emit_contribution_refunded_event(
+        &self,
+        group_id: u128,
+        contributor: AccountId,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceGroupPurchase for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Creates a vault pooling native currency toward buying a listed NFT.
+    #[modifiers(when_not_taking_custody)]
+    default fn create_group_purchase(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        expires_at: u64,
+    ) -> Result<u128, MarketplaceError> {
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && !self.is_escrowed_listing(contract_address, &token_id)
+                && self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
+        ensure!(
+            expires_at > Self::env().block_timestamp(),
+            MarketplaceError::GroupPurchaseClosed
+        );
+        // a group of contributors cannot prove it holds the gate token
+        ensure!(
+            !self
+                .data::<Data>()
+                .listing_gates
+                .contains(&(contract_address, token_key(&token_id))),
+            MarketplaceError::GateTokenRequired
+        );
+
+        let group_id = self.data::<Data>().v1.last_group_purchase_id + 1;
+        self.data::<Data>().group_purchases.insert(
+            &group_id,
+            &GroupPurchase {
+                contract_address,
+                token_id: token_id.clone(),
+                target: item.price,
+                raised: 0,
+                expires_at,
+                completed: false,
+            },
+        );
+        self.data::<Data>().v1.last_group_purchase_id = group_id;
+
+        self.emit_group_purchase_created_event(group_id, contract_address, token_id, item.price);
+        Ok(group_id)
+    }
+
+    /// Contributes transferred value to a group purchase.
+    #[modifiers(when_not_taking_custody)]
+    default fn contribute(&mut self, group_id: u128) -> Result<(), MarketplaceError> {
+        let mut group = self
+            .data::<Data>()
+            .group_purchases
+            .get(&group_id)
+            .ok_or(MarketplaceError::GroupPurchaseNotFound)?;
+        ensure!(
+            !group.completed && Self::env().block_timestamp() < group.expires_at,
+            MarketplaceError::GroupPurchaseClosed
+        );
+
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        let value = Self::env().transferred_value();
+        let raised = group
+            .raised
+            .checked_add(value)
+            .filter(|raised| *raised <= group.target)
+            .ok_or(MarketplaceError::ContributionExceedsTarget)?;
+        group.raised = raised;
+        self.data::<Data>()
+            .group_purchases
+            .insert(&group_id, &group);
+
+        let contribution = self.get_contribution(group_id, caller);
+        self.data::<Data>()
+            .group_contributions
+            .insert(&(group_id, caller), &(contribution + value));
+        self.lock_funds(value);
+
+        self.emit_contributed_event(group_id, caller, value);
+        Ok(())
+    }
+
+    /// Buys the NFT once a group purchase raised its target.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn execute_group_purchase(&mut self, group_id: u128) -> Result<(), MarketplaceError> {
+        let mut group = self
+            .data::<Data>()
+            .group_purchases
+            .get(&group_id)
+            .ok_or(MarketplaceError::GroupPurchaseNotFound)?;
+        ensure!(!group.completed, MarketplaceError::GroupPurchaseClosed);
+        ensure!(
+            group.raised == group.target,
+            MarketplaceError::TargetNotReached
+        );
+        ensure!(
+            self.is_group_listing_available(&group),
+            MarketplaceError::ItemNotListedForSale
+        );
+
+        let token_owner = contract_calls::owner_of(group.contract_address, &group.token_id)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        group.completed = true;
+        self.data::<Data>()
+            .group_purchases
+            .insert(&group_id, &group);
+        self.remove_item(group.contract_address, &group.token_id);
+        self.unlock_funds(group.target);
+
+        self.settle_sale(
+            group.contract_address,
+            group.token_id,
+            token_owner,
+            Self::env().account_id(),
+            group.target,
+            Currency::Native,
+            SaleAttribution::default(),
+        )?;
+        self.emit_group_purchase_executed_event(group_id);
+        Ok(())
+    }
+
+    /// Refunds caller's contribution if a group purchase expired or its listing changed.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn refund_contribution(&mut self, group_id: u128) -> Result<(), MarketplaceError> {
+        let group = self
+            .data::<Data>()
+            .group_purchases
+            .get(&group_id)
+            .ok_or(MarketplaceError::GroupPurchaseNotFound)?;
+        ensure!(
+            self.is_group_refundable(&group),
+            MarketplaceError::RefundNotAvailable
+        );
+
+        self.refund_group_contribution(group_id, group, Self::env().caller())
+    }
+
+    /// Gets group purchase.
+    default fn get_group_purchase(&self, group_id: u128) -> Option<GroupPurchase> {
+        self.data::<Data>().group_purchases.get(&group_id)
+    }
+
+    /// Gets account contribution to a group purchase.
+    default fn get_contribution(&self, group_id: u128, account_id: AccountId) -> Balance {
+        self.data::<Data>()
+            .group_contributions
+            .get(&(group_id, account_id))
+            .unwrap_or(0)
+    }
+
+    /// Gets account share (in basis points) of a group purchase.
+    default fn get_group_share(&self, group_id: u128, account_id: AccountId) -> u16 {
+        match self.data::<Data>().group_purchases.get(&group_id) {
+            Some(group) if group.raised > 0 => {
+                (self.get_contribution(group_id, account_id) * 10_000 / group.raised) as u16
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<T> MarketplaceGroupPurchaseEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_group_purchase_created_event(
+        &self,
+        _group_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _target: Balance,
+    ) {
+    }
+
+    default fn emit_contributed_event(
+        &self,
+        _group_id: u128,
+        _contributor: AccountId,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_group_purchase_executed_event(&self, _group_id: u128) {}
+
+    default fn emit_contribution_refunded_event(
+        &self,
+        _group_id: u128,
+        _contributor: AccountId,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn is_group_listing_available(&self, group: &GroupPurchase) -> bool {
+        match self
+            .data::<Data>()
+            .items
+            .get(&(group.contract_address, token_key(&group.token_id)))
+        {
+            Some(item) => {
+                !self.is_usd_listing(group.contract_address, &group.token_id)
+                    && !self.is_escrowed_listing(group.contract_address, &group.token_id)
+                    && self.listing_currency(group.contract_address, &group.token_id)
+                        == Currency::Native
+                    && item.price == group.target
+            }
+            None => false,
+        }
+    }
+
+    default fn is_group_refundable(&self, group: &GroupPurchase) -> bool {
+        !group.completed
+            && (Self::env().block_timestamp() >= group.expires_at
+                || !self.is_group_listing_available(group))
+    }
+
+    default fn refund_group_contribution(
+        &mut self,
+        group_id: u128,
+        mut group: GroupPurchase,
+        contributor: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let contribution = self
+            .data::<Data>()
+            .group_contributions
+            .get(&(group_id, contributor))
+            .unwrap_or(0);
+        ensure!(contribution > 0, MarketplaceError::BalanceInsufficient);
+
+        group.raised -= contribution;
+        self.data::<Data>()
+            .group_purchases
+            .insert(&group_id, &group);
+        self.data::<Data>()
+            .group_contributions
+            .remove(&(group_id, contributor));
+        self.unlock_funds(contribution);
+        self.transfer_funds(
+            Currency::Native,
+            contributor,
+            contribution,
+            MarketplaceError::RefundFailed,
+        )?;
+
+        self.emit_contribution_refunded_event(group_id, contributor, contribution);
+        Ok(())
+    }
+}
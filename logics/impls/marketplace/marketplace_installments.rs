@@ -0,0 +1,294 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    contract_calls,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_flash_sales::Internal as FlashSalesInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, InstallmentPlan, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::installments::MarketplaceInstallments,
+};
+use openbrush::{
+    contracts::{ownable::*, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets amount of the next installment. The last one covers the rounding remainder.
+    fn next_installment_amount(&self, plan: &InstallmentPlan) -> Balance;
+}
+
+pub trait MarketplaceInstallmentsEvents {
+    fn emit_installment_plan_created_event(
+        &self,
+        plan_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+        installments: u8,
+    );
+    fn emit_installment_paid_event(&self, plan_id: u128, amount: Balance);
+    fn emit_installment_plan_defaulted_event(&self, plan_id: u128);
+}
+
+impl<T> MarketplaceInstallments for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Sets share (in basis points) of paid installments the seller keeps on default.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_installment_penalty(&mut self, penalty: u16) -> Result<(), MarketplaceError> {
+        self.check_fee(penalty, 10_000)?;
+        self.data::<Data>().v1.installment_penalty = penalty;
+        Ok(())
+    }
+
+    /// Gets installment default penalty in basis points.
+    default fn get_installment_penalty(&self) -> u16 {
+        self.data::<Data>().v1.installment_penalty
+    }
+
+    /// Sets time (in milliseconds) the buyer has to pay each installment.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_installment_interval(&mut self, interval: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.installment_interval = interval;
+        Ok(())
+    }
+
+    /// Gets installment interval in milliseconds.
+    default fn get_installment_interval(&self) -> u64 {
+        self.data::<Data>().v1.installment_interval
+    }
+
+    /// Buys NFT item listed for native currency in installments.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn buy_in_installments(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        installments: u8,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(installments > 1, MarketplaceError::InvalidInstallments);
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && !self.is_escrowed_listing(contract_address, &token_id)
+                && self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
+
+        let seller = contract_calls::owner_of(contract_address, &token_id)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let caller = Self::env().caller();
+        ensure!(seller != caller, MarketplaceError::AlreadyOwner);
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(seller)?;
+        self.check_listing_gate(contract_address, &token_id, caller)?;
+
+        let mut plan = InstallmentPlan {
+            contract_address,
+            token_id: token_id.clone(),
+            seller,
+            buyer: caller,
+            price: self.flash_sale_price(contract_address, &item),
+            paid: 0,
+            installments,
+            installments_paid: 0,
+            next_due: 0,
+        };
+        let amount = self.next_installment_amount(&plan);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == amount,
+            MarketplaceError::BadBuyValue {
+                expected: amount,
+                received,
+            }
+        );
+        self.take_custody(contract_address, token_id.clone())?;
+        self.lock_funds(amount);
+        plan.paid = amount;
+        plan.installments_paid = 1;
+        plan.next_due = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().v1.installment_interval);
+
+        let plan_id = self.data::<Data>().v1.last_installment_plan_id + 1;
+        self.data::<Data>()
+            .installment_plans
+            .insert(&plan_id, &plan);
+        self.data::<Data>().v1.last_installment_plan_id = plan_id;
+
+        self.emit_installment_plan_created_event(
+            plan_id,
+            contract_address,
+            token_id,
+            caller,
+            installments,
+        );
+        self.emit_installment_paid_event(plan_id, amount);
+        Ok(())
+    }
+
+    /// Pays the next installment of a payment plan.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn pay_installment(&mut self, plan_id: u128) -> Result<(), MarketplaceError> {
+        let mut plan = self
+            .data::<Data>()
+            .installment_plans
+            .get(&plan_id)
+            .ok_or(MarketplaceError::InstallmentPlanNotFound)?;
+        ensure!(
+            Self::env().caller() == plan.buyer,
+            MarketplaceError::NotBuyer
+        );
+        ensure!(
+            Self::env().block_timestamp() <= plan.next_due,
+            MarketplaceError::InstallmentOverdue
+        );
+
+        let amount = self.next_installment_amount(&plan);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == amount,
+            MarketplaceError::BadBuyValue {
+                expected: amount,
+                received,
+            }
+        );
+        plan.paid += amount;
+        plan.installments_paid += 1;
+        self.emit_installment_paid_event(plan_id, amount);
+
+        if plan.installments_paid == plan.installments {
+            self.data::<Data>().installment_plans.remove(&plan_id);
+            self.unlock_funds(plan.paid - amount);
+            return self.settle_sale(
+                plan.contract_address,
+                plan.token_id,
+                plan.seller,
+                plan.buyer,
+                plan.price,
+                Currency::Native,
+                SaleAttribution::default(),
+            )
+        }
+
+        self.lock_funds(amount);
+        plan.next_due = plan
+            .next_due
+            .saturating_add(self.data::<Data>().v1.installment_interval);
+        self.data::<Data>()
+            .installment_plans
+            .insert(&plan_id, &plan);
+        Ok(())
+    }
+
+    /// Returns the NFT of an overdue payment plan to the seller.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_defaulted_installments(
+        &mut self,
+        plan_id: u128,
+    ) -> Result<(), MarketplaceError> {
+        let plan = self
+            .data::<Data>()
+            .installment_plans
+            .get(&plan_id)
+            .ok_or(MarketplaceError::InstallmentPlanNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() > plan.next_due,
+            MarketplaceError::InstallmentNotOverdue
+        );
+        self.data::<Data>().installment_plans.remove(&plan_id);
+        self.unlock_funds(plan.paid);
+
+        let penalty = plan
+            .paid
+            .checked_mul(self.data::<Data>().v1.installment_penalty as u128)
+            .unwrap_or_default()
+            / 10_000;
+
+        self.release_custody(plan.contract_address, plan.seller, plan.token_id)?;
+        self.transfer_funds(
+            Currency::Native,
+            plan.seller,
+            penalty,
+            MarketplaceError::TransferToOwnerFailed,
+        )?;
+        self.transfer_funds(
+            Currency::Native,
+            plan.buyer,
+            plan.paid - penalty,
+            MarketplaceError::RefundFailed,
+        )?;
+
+        self.emit_installment_plan_defaulted_event(plan_id);
+        Ok(())
+    }
+
+    /// Gets payment plan.
+    default fn get_installment_plan(&self, plan_id: u128) -> Option<InstallmentPlan> {
+        self.data::<Data>().installment_plans.get(&plan_id)
+    }
+}
+
+impl<T> MarketplaceInstallmentsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_installment_plan_created_event(
+        &self,
+        _plan_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _buyer: AccountId,
+        _installments: u8,
+    ) {
+    }
+
+    default fn emit_installment_paid_event(&self, _plan_id: u128, _amount: Balance) {}
+
+    default fn emit_installment_plan_defaulted_event(&self, _plan_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn next_installment_amount(&self, plan: &InstallmentPlan) -> Balance {
+        if plan.installments_paid + 1 == plan.installments {
+            return plan.price - plan.paid
+        }
+        plan.price / plan.installments as u128
+    }
+}
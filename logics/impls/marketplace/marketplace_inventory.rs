@@ -0,0 +1,92 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Inventory of NFTs an account can list.
+//!
+//! Tokens are enumerated through PSP34Enumerable `owners_token_by_index` of the collection and
+//! annotated with marketplace state, so wallets can show what is listed and what still needs
+//! an approval from chain reads alone.
+
+use super::{
+    contract_calls,
+    marketplace_flash_sales::Internal as FlashSalesInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, InventoryToken, NftContractType},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::inventory::MarketplaceInventory,
+};
+use ink::prelude::vec::Vec;
+use openbrush::traits::{AccountId, Storage};
+
+impl<T> MarketplaceInventory for T
+where
+    T: Storage<Data>,
+{
+    default fn get_owned_tokens(
+        &self,
+        contract_address: AccountId,
+        owner: AccountId,
+        page: u32,
+    ) -> Result<Vec<InventoryToken>, MarketplaceError> {
+        let collection = self
+            .data::<Data>()
+            .registered_collections
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NotRegisteredContract)?;
+        ensure!(
+            collection.contract_type != NftContractType::Erc721Xvm,
+            MarketplaceError::CollectionNotEnumerable
+        );
+
+        let page_size = self.data::<Data>().v1.max_batch_size as u128;
+        let start = (page as u128).saturating_mul(page_size);
+        let end = (contract_calls::balance_of(contract_address, owner) as u128)
+            .min(start.saturating_add(page_size));
+        let tokens = (start..end)
+            .filter_map(|index| {
+                contract_calls::owners_token_by_index(contract_address, owner, index)
+            })
+            .map(|token_id| {
+                let listing = self
+                    .data::<Data>()
+                    .items
+                    .get(&(contract_address, token_key(&token_id)))
+                    .filter(|item| item.owner == owner)
+                    .map(|item| {
+                        (
+                            self.flash_sale_price(contract_address, &item),
+                            self.listing_currency(contract_address, &token_id),
+                        )
+                    });
+                InventoryToken {
+                    approved: self.token_approved(contract_address, owner, &token_id),
+                    token_id,
+                    listing,
+                }
+            })
+            .collect();
+
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,527 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Loan, LoanOffer, StandingLoanOffer},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::lending::MarketplaceLending,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Length of a year in milliseconds.
+pub const YEAR: u64 = 31_536_000_000;
+
+pub trait Internal {
+    /// Adds amount to the native deposit of an account.
+    fn credit_deposit(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Takes principal from the lender's deposit until the offer is accepted or cancelled.
+    fn lock_principal(
+        &mut self,
+        lender: AccountId,
+        principal: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets interest on principal at annual rate `apr` (in basis points) for `duration`.
+    fn loan_interest(&self, principal: Balance, apr: u16, duration: u64) -> Balance;
+
+    /// Removes closed loan together with its collection index entry.
+    fn remove_loan(&mut self, loan_id: u128, loan: &Loan);
+
+    /// Takes the caller's NFT as collateral and pays out locked principal to the caller.
+    fn start_loan(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        lender: AccountId,
+        principal: Balance,
+        interest: Balance,
+        duration: u64,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceLendingEvents {
+    fn emit_loan_offer_made_event(
+        &self,
+        offer_id: u128,
+        contract: AccountId,
+        lender: AccountId,
+        principal: Balance,
+        interest: Balance,
+        duration: u64,
+    );
+    fn emit_loan_offer_cancelled_event(&self, offer_id: u128);
+    fn emit_standing_loan_offer_made_event(
+        &self,
+        offer_id: u128,
+        contract: AccountId,
+        lender: AccountId,
+        max_principal: Balance,
+        apr: u16,
+        duration: u64,
+    );
+    fn emit_standing_loan_offer_cancelled_event(&self, offer_id: u128);
+    fn emit_loan_started_event(
+        &self,
+        loan_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        borrower: AccountId,
+        lender: AccountId,
+        due: u64,
+    );
+    fn emit_loan_repaid_event(&self, loan_id: u128, amount: Balance);
+    fn emit_loan_defaulted_event(&self, loan_id: u128);
+}
+
+impl<T> MarketplaceLending for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Offers a loan against NFT.
+    #[modifiers(when_not_taking_custody)]
+    default fn make_loan_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        principal: Balance,
+        interest: Balance,
+        duration: u64,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.registered_collection(contract_address)?;
+        self.lock_principal(caller, principal)?;
+
+        let offer_id = self.data::<Data>().v1.last_loan_offer_id + 1;
+        self.data::<Data>().loan_offers.insert(
+            &offer_id,
+            &LoanOffer {
+                lender: caller,
+                contract_address,
+                token_id,
+                principal,
+                interest,
+                duration,
+            },
+        );
+        self.data::<Data>().v1.last_loan_offer_id = offer_id;
+
+        self.emit_loan_offer_made_event(
+            offer_id,
+            contract_address,
+            caller,
+            principal,
+            interest,
+            duration,
+        );
+        Ok(())
+    }
+
+    /// Cancels loan offer and returns principal to the lender's deposit.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_loan_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .loan_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::LoanOfferNotFound)?;
+        ensure!(
+            Self::env().caller() == offer.lender,
+            MarketplaceError::NotLender
+        );
+
+        self.data::<Data>().loan_offers.remove(&offer_id);
+        self.unlock_funds(offer.principal);
+        self.credit_deposit(offer.lender, offer.principal)?;
+
+        self.emit_loan_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    /// Accepts loan offer.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn accept_loan_offer(
+        &mut self,
+        offer_id: u128,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .loan_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::LoanOfferNotFound)?;
+        if let Some(offer_token_id) = offer.token_id.clone() {
+            ensure!(offer_token_id == token_id, MarketplaceError::OfferNotMatch);
+        }
+
+        self.data::<Data>().loan_offers.remove(&offer_id);
+        self.start_loan(
+            offer.contract_address,
+            token_id,
+            offer.lender,
+            offer.principal,
+            offer.interest,
+            offer.duration,
+        )
+    }
+
+    /// Posts a standing loan offer against any NFT of a collection.
+    #[modifiers(when_not_taking_custody)]
+    default fn make_standing_loan_offer(
+        &mut self,
+        contract_address: AccountId,
+        max_principal: Balance,
+        apr: u16,
+        duration: u64,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.registered_collection(contract_address)?;
+        ensure!(max_principal > 0, MarketplaceError::InvalidPrincipal);
+        self.lock_principal(caller, max_principal)?;
+
+        let offer_id = self.data::<Data>().v1.last_standing_loan_offer_id + 1;
+        self.data::<Data>().standing_loan_offers.insert(
+            &offer_id,
+            &StandingLoanOffer {
+                lender: caller,
+                contract_address,
+                available: max_principal,
+                apr,
+                duration,
+            },
+        );
+        self.data::<Data>().v1.last_standing_loan_offer_id = offer_id;
+
+        self.emit_standing_loan_offer_made_event(
+            offer_id,
+            contract_address,
+            caller,
+            max_principal,
+            apr,
+            duration,
+        );
+        Ok(())
+    }
+
+    /// Cancels standing loan offer and returns principal left to the lender's deposit.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_standing_loan_offer(
+        &mut self,
+        offer_id: u128,
+    ) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .standing_loan_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::LoanOfferNotFound)?;
+        ensure!(
+            Self::env().caller() == offer.lender,
+            MarketplaceError::NotLender
+        );
+
+        self.data::<Data>().standing_loan_offers.remove(&offer_id);
+        self.unlock_funds(offer.available);
+        self.credit_deposit(offer.lender, offer.available)?;
+
+        self.emit_standing_loan_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    /// Borrows from a standing loan offer.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn accept_standing_loan_offer(
+        &mut self,
+        offer_id: u128,
+        token_id: Id,
+        principal: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let mut offer = self
+            .data::<Data>()
+            .standing_loan_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::LoanOfferNotFound)?;
+        ensure!(
+            principal > 0 && principal <= offer.available,
+            MarketplaceError::InvalidPrincipal
+        );
+
+        offer.available -= principal;
+        if offer.available == 0 {
+            self.data::<Data>().standing_loan_offers.remove(&offer_id);
+        } else {
+            self.data::<Data>()
+                .standing_loan_offers
+                .insert(&offer_id, &offer);
+        }
+
+        let interest = self.loan_interest(principal, offer.apr, offer.duration);
+        self.start_loan(
+            offer.contract_address,
+            token_id,
+            offer.lender,
+            principal,
+            interest,
+            offer.duration,
+        )
+    }
+
+    /// Repays principal and interest from the borrower's deposit and returns the NFT.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn repay_loan(&mut self, loan_id: u128) -> Result<(), MarketplaceError> {
+        let loan = self
+            .data::<Data>()
+            .loans
+            .get(&loan_id)
+            .ok_or(MarketplaceError::LoanNotFound)?;
+        ensure!(
+            Self::env().caller() == loan.borrower,
+            MarketplaceError::NotBorrower
+        );
+        ensure!(
+            Self::env().block_timestamp() <= loan.due,
+            MarketplaceError::LoanOverdue
+        );
+
+        let amount = loan
+            .principal
+            .checked_add(loan.interest)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let deposit = self.get_deposit_internal(loan.borrower);
+        ensure!(deposit >= amount, MarketplaceError::BalanceInsufficient);
+
+        self.remove_loan(loan_id, &loan);
+        self.set_currency_deposit(loan.borrower, Currency::Native, deposit - amount);
+        self.credit_deposit(loan.lender, amount)?;
+        self.release_custody(loan.contract_address, loan.borrower, loan.token_id)?;
+
+        self.emit_loan_repaid_event(loan_id, amount);
+        Ok(())
+    }
+
+    /// Transfers collateral NFT of a loan that was not repaid in time to the lender.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_defaulted_loan(&mut self, loan_id: u128) -> Result<(), MarketplaceError> {
+        let loan = self
+            .data::<Data>()
+            .loans
+            .get(&loan_id)
+            .ok_or(MarketplaceError::LoanNotFound)?;
+        ensure!(
+            Self::env().caller() == loan.lender,
+            MarketplaceError::NotLender
+        );
+        ensure!(
+            Self::env().block_timestamp() > loan.due,
+            MarketplaceError::LoanNotOverdue
+        );
+
+        self.remove_loan(loan_id, &loan);
+        self.release_custody(loan.contract_address, loan.lender, loan.token_id)?;
+
+        self.emit_loan_defaulted_event(loan_id);
+        Ok(())
+    }
+
+    /// Gets loan offer.
+    default fn get_loan_offer(&self, offer_id: u128) -> Option<LoanOffer> {
+        self.data::<Data>().loan_offers.get(&offer_id)
+    }
+
+    /// Gets standing loan offer.
+    default fn get_standing_loan_offer(&self, offer_id: u128) -> Option<StandingLoanOffer> {
+        self.data::<Data>().standing_loan_offers.get(&offer_id)
+    }
+
+    /// Gets loan.
+    default fn get_loan(&self, loan_id: u128) -> Option<Loan> {
+        self.data::<Data>().loans.get(&loan_id)
+    }
+
+    /// Gets open loans against NFT of a collection.
+    default fn get_loans_by_collection(
+        &self,
+        contract_address: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(u128, Loan)> {
+        let loans = &self.data::<Data>().collection_loan_index;
+        (from as u128..loans.count(&contract_address))
+            .take(limit as usize)
+            .filter_map(|index| loans.get_value(&contract_address, &index))
+            .filter_map(|loan_id| {
+                self.data::<Data>()
+                    .loans
+                    .get(&loan_id)
+                    .map(|loan| (loan_id, loan))
+            })
+            .collect()
+    }
+}
+
+impl<T> MarketplaceLendingEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_loan_offer_made_event(
+        &self,
+        _offer_id: u128,
+        _contract: AccountId,
+        _lender: AccountId,
+        _principal: Balance,
+        _interest: Balance,
+        _duration: u64,
+    ) {
+    }
+
+    default fn emit_loan_offer_cancelled_event(&self, _offer_id: u128) {}
+
+    default fn emit_standing_loan_offer_made_event(
+        &self,
+        _offer_id: u128,
+        _contract: AccountId,
+        _lender: AccountId,
+        _max_principal: Balance,
+        _apr: u16,
+        _duration: u64,
+    ) {
+    }
+
+    default fn emit_standing_loan_offer_cancelled_event(&self, _offer_id: u128) {}
+
+    default fn emit_loan_started_event(
+        &self,
+        _loan_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _borrower: AccountId,
+        _lender: AccountId,
+        _due: u64,
+    ) {
+    }
+
+    default fn emit_loan_repaid_event(&self, _loan_id: u128, _amount: Balance) {}
+
+    default fn emit_loan_defaulted_event(&self, _loan_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn credit_deposit(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let deposit = self
+            .get_deposit_internal(account)
+            .checked_add(amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        self.set_currency_deposit(account, Currency::Native, deposit);
+        Ok(())
+    }
+    default fn lock_principal(
+        &mut self,
+        lender: AccountId,
+        principal: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let deposit = self.get_deposit_internal(lender);
+        ensure!(deposit >= principal, MarketplaceError::BalanceInsufficient);
+
+        self.set_currency_deposit(lender, Currency::Native, deposit - principal);
+        self.lock_funds(principal);
+        Ok(())
+    }
+
+    default fn loan_interest(&self, principal: Balance, apr: u16, duration: u64) -> Balance {
+        principal
+            .saturating_mul(apr as u128)
+            .saturating_mul(duration as u128)
+            / (10_000 * YEAR as u128)
+    }
+
+    default fn remove_loan(&mut self, loan_id: u128, loan: &Loan) {
+        self.data::<Data>().loans.remove(&loan_id);
+        self.data::<Data>()
+            .collection_loan_index
+            .remove_value(&loan.contract_address, &loan_id);
+    }
+
+    default fn start_loan(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        lender: AccountId,
+        principal: Balance,
+        interest: Balance,
+        duration: u64,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(lender)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_token_allowance(contract_address, &token_id)?;
+
+        self.take_custody(contract_address, token_id.clone())?;
+        self.unlock_funds(principal);
+        self.credit_deposit(caller, principal)?;
+
+        let due = Self::env().block_timestamp().saturating_add(duration);
+        let loan_id = self.data::<Data>().v1.last_loan_id + 1;
+        self.data::<Data>().loans.insert(
+            &loan_id,
+            &Loan {
+                contract_address,
+                token_id: token_id.clone(),
+                borrower: caller,
+                lender,
+                principal,
+                interest,
+                due,
+            },
+        );
+        self.data::<Data>().v1.last_loan_id = loan_id;
+        self.data::<Data>()
+            .collection_loan_index
+            .insert(&contract_address, &loan_id);
+
+        self.emit_loan_started_event(loan_id, contract_address, token_id, caller, lender, due);
+        Ok(())
+    }
+}
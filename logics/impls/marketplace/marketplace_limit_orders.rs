@@ -0,0 +1,222 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_lending::Internal as LendingInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, LimitOrder, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::limit_orders::MarketplaceLimitOrders,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets value of the tokens left to buy by an order.
+    fn limit_order_value(&self, order: &LimitOrder) -> Result<Balance, MarketplaceError>;
+
+    /// Removes a limit order together with its enumerable entry.
+    fn remove_limit_order(&mut self, order_id: u128, order: &LimitOrder);
+}
+
+pub trait MarketplaceLimitOrdersEvents {
+    fn emit_limit_order_placed_event(
+        &self,
+        order_id: u128,
+        contract: AccountId,
+        buyer: AccountId,
+        quantity: u64,
+        price: Balance,
+    );
+    fn emit_limit_order_cancelled_event(&self, order_id: u128);
+    fn emit_limit_order_filled_event(&self, order_id: u128, seller: AccountId, token_id: Id);
+}
+
+impl<T> MarketplaceLimitOrders for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Places a standing order to buy tokens of a collection.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn place_limit_order(
+        &mut self,
+        contract_address: AccountId,
+        quantity: u64,
+        price: Balance,
+    ) -> Result<u128, MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(
+            quantity > 0 && price > 0,
+            MarketplaceError::InvalidLimitOrder
+        );
+        self.check_tick_size(price)?;
+
+        let order = LimitOrder {
+            buyer: caller,
+            contract_address,
+            price,
+            quantity,
+        };
+        let value = self.limit_order_value(&order)?;
+        let deposit = self.get_deposit_internal(caller);
+        ensure!(deposit >= value, MarketplaceError::BalanceInsufficient);
+        self.set_currency_deposit(caller, Currency::Native, deposit - value);
+        self.lock_funds(value);
+
+        let order_id = self.data::<Data>().v1.last_limit_order_id + 1;
+        self.data::<Data>().limit_orders.insert(&order_id, &order);
+        self.data::<Data>()
+            .collection_limit_orders
+            .insert(&contract_address, &order_id);
+        self.data::<Data>().v1.last_limit_order_id = order_id;
+
+        self.emit_limit_order_placed_event(order_id, contract_address, caller, quantity, price);
+        Ok(order_id)
+    }
+
+    /// Cancels limit order.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_limit_order(&mut self, order_id: u128) -> Result<(), MarketplaceError> {
+        let order = self
+            .data::<Data>()
+            .limit_orders
+            .get(&order_id)
+            .ok_or(MarketplaceError::LimitOrderNotFound)?;
+        ensure!(
+            Self::env().caller() == order.buyer,
+            MarketplaceError::NotOwner
+        );
+
+        let value = self.limit_order_value(&order)?;
+        self.remove_limit_order(order_id, &order);
+        self.unlock_funds(value);
+        self.credit_deposit(order.buyer, value)?;
+
+        self.emit_limit_order_cancelled_event(order_id);
+        Ok(())
+    }
+
+    /// Sells a token into a limit order.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn sell_into_order(
+        &mut self,
+        order_id: u128,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let mut order = self
+            .data::<Data>()
+            .limit_orders
+            .get(&order_id)
+            .ok_or(MarketplaceError::LimitOrderNotFound)?;
+        let caller = Self::env().caller();
+        self.check_token_owner(order.contract_address, &token_id)?;
+        self.check_token_allowance(order.contract_address, &token_id)?;
+
+        order.quantity -= 1;
+        if order.quantity == 0 {
+            self.remove_limit_order(order_id, &order);
+        } else {
+            self.data::<Data>().limit_orders.insert(&order_id, &order);
+        }
+        self.unlock_funds(order.price);
+
+        self.emit_limit_order_filled_event(order_id, caller, token_id.clone());
+        self.settle_sale(
+            order.contract_address,
+            token_id,
+            caller,
+            order.buyer,
+            order.price,
+            Currency::Native,
+            SaleAttribution::default(),
+        )
+    }
+
+    /// Gets limit order.
+    default fn get_limit_order(&self, order_id: u128) -> Option<LimitOrder> {
+        self.data::<Data>().limit_orders.get(&order_id)
+    }
+
+    /// Gets open limit orders of a collection.
+    default fn get_limit_orders(&self, contract_address: AccountId) -> Vec<u128> {
+        let orders = &self.data::<Data>().collection_limit_orders;
+        (0..orders.count(&contract_address))
+            .filter_map(|index| orders.get_value(&contract_address, &index))
+            .collect()
+    }
+}
+
+impl<T> MarketplaceLimitOrdersEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_limit_order_placed_event(
+        &self,
+        _order_id: u128,
+        _contract: AccountId,
+        _buyer: AccountId,
+        _quantity: u64,
+        _price: Balance,
+    ) {
+    }
+
+    default fn emit_limit_order_cancelled_event(&self, _order_id: u128) {}
+
+    default fn emit_limit_order_filled_event(
+        &self,
+        _order_id: u128,
+        _seller: AccountId,
+        _token_id: Id,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn limit_order_value(&self, order: &LimitOrder) -> Result<Balance, MarketplaceError> {
+        order
+            .price
+            .checked_mul(order.quantity as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)
+    }
+
+    default fn remove_limit_order(&mut self, order_id: u128, order: &LimitOrder) {
+        self.data::<Data>().limit_orders.remove(&order_id);
+        self.data::<Data>()
+            .collection_limit_orders
+            .remove_value(&order.contract_address, &order_id);
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    batch::{check_batch_size, DEFAULT_MAX_BATCH_SIZE},
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, STORAGE_VERSION},
+};
+use crate::{
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::migration::MarketplaceMigration,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{access_control, ownable::*, psp34::Id},
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Runs a batch of the migration step from `version` to `version + 1`. Entries can be
+    /// tracked with `migration_cursor`. Returns `true` once the step is complete.
+    fn migrate_step(&mut self, version: u32, batch_size: u32) -> Result<bool, MarketplaceError>;
+
+    /// Moves offers of the version 0 layout to the offer indexes, at most `batch_size` of them.
+    /// Returns `true` once all offers are moved.
+    fn migrate_offers(&mut self, batch_size: u32) -> Result<bool, MarketplaceError>;
+}
+
+pub trait MarketplaceMigrationEvents {
+    fn emit_storage_migrated_event(&self, version: u32);
+}
+
+impl<T> MarketplaceMigration for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<access_control::Data>,
+{
+    /// Migrates storage written by older code.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn migrate(&mut self, batch_size: u32) -> Result<bool, MarketplaceError> {
+        while self.data::<Data>().v1.storage_version < STORAGE_VERSION {
+            let version = self.data::<Data>().v1.storage_version;
+            if !self.migrate_step(version, batch_size)? {
+                return Ok(false)
+            }
+
+            self.data::<Data>().v1.storage_version = version + 1;
+            self.data::<Data>().v1.migration_cursor = 0;
+            self.emit_storage_migrated_event(version + 1);
+        }
+        Ok(true)
+    }
+
+    /// Indexes listings of the version 0 layout.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn migrate_listings(
+        &mut self,
+        listings: Vec<(AccountId, Id)>,
+    ) -> Result<(), MarketplaceError> {
+        check_batch_size(listings.len(), self.data::<Data>().v1.max_batch_size)?;
+
+        for (contract_address, token_id) in listings {
+            let key = token_key(&token_id);
+            // long ids were used as they are
+            if key != token_id {
+                let legacy_key = (contract_address, token_id.clone());
+                if let Some(item) = self.data::<Data>().items.get(&legacy_key) {
+                    self.data::<Data>().items.remove(&legacy_key);
+                    self.data::<Data>()
+                        .items
+                        .insert(&(contract_address, key.clone()), &item);
+                }
+            }
+            let item = match self
+                .data::<Data>()
+                .items
+                .get(&(contract_address, key.clone()))
+            {
+                Some(item) => item,
+                None => continue,
+            };
+            if self
+                .data::<Data>()
+                .account_listing_index
+                .contains_value(&item.owner, &(contract_address, key))
+            {
+                continue
+            }
+
+            // listings of the version 0 layout are all priced in the native currency
+            self.update_listing_prices(contract_address, &token_id, None, Some(item.price));
+            self.update_listing_indexes(item.owner, contract_address, &token_id, true);
+            let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+            stats.active_listings += 1;
+            self.data::<Data>().stats.set(&stats);
+        }
+        Ok(())
+    }
+
+    /// Gets version of the storage layout.
+    default fn get_storage_version(&self) -> u32 {
+        self.data::<Data>().v1.storage_version
+    }
+}
+
+impl<T> MarketplaceMigrationEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_storage_migrated_event(&self, _version: u32) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<access_control::Data>,
+{
+    default fn migrate_step(
+        &mut self,
+        version: u32,
+        batch_size: u32,
+    ) -> Result<bool, MarketplaceError> {
+        match version {
+            0 => {
+                // root cell fields added in version 1 were decoded as defaults
+                if self.data::<Data>().v1.migration_cursor == 0 {
+                    self.data::<Data>().v1.max_batch_size = DEFAULT_MAX_BATCH_SIZE;
+                    let owner = self.owner();
+                    access_control::Internal::_init_with_admin(self, owner);
+                }
+                self.migrate_offers(batch_size)
+            }
+            _ => Err(MarketplaceError::UnknownStorageVersion),
+        }
+    }
+
+    default fn migrate_offers(&mut self, batch_size: u32) -> Result<bool, MarketplaceError> {
+        let last_offer_id = self.data::<Data>().last_offer_id;
+        let first = self.data::<Data>().v1.migration_cursor + 1;
+        let end = last_offer_id.min(first.saturating_add(batch_size as u128).saturating_sub(1));
+
+        let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+        for offer_id in first..=end {
+            let data = self.data::<Data>();
+            let offer = match data.offer_items.get(&offer_id) {
+                Some(offer) => offer,
+                None => continue,
+            };
+            let legacy_key = (offer.contract_address, offer.token_id.clone());
+            data.offer_items_per_contract_token_id.remove(&legacy_key);
+            let key = (
+                offer.contract_address,
+                offer.token_id.as_ref().map(token_key),
+            );
+            data.token_offer_index.insert(&key, &offer_id);
+            data.account_offer_index.insert(&offer.bidder_id, &offer_id);
+            stats.active_offers += 1;
+        }
+        self.data::<Data>().stats.set(&stats);
+
+        if end >= first {
+            self.data::<Data>().v1.migration_cursor = end;
+        }
+        Ok(end == last_offer_id)
+    }
+}
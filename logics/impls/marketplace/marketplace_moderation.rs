@@ -0,0 +1,148 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_multisig::Internal as MultisigInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, MODERATOR},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::moderation::MarketplaceModeration,
+};
+use openbrush::{
+    contracts::{access_control::*, ownable::*, pausable, psp34::Id},
+    modifiers,
+    traits::{AccountId, Storage, String},
+};
+
+pub trait Internal {
+    /// Checks that caller is the owner or a moderator.
+    fn check_moderator(&self) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceModerationEvents {
+    fn emit_blacklist_updated_event(&self, account: AccountId, blacklisted: bool);
+    fn emit_listing_removed_by_moderator_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        moderator: AccountId,
+        reason: String,
+    );
+}
+
+impl<T> MarketplaceModeration for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<access_control::Data>
+        + Storage<pausable::Data>,
+{
+    /// Blocks an account from trading.
+    #[modifiers(when_not_taking_custody)]
+    default fn add_to_blacklist(&mut self, account: AccountId) -> Result<(), MarketplaceError> {
+        self.check_moderator()?;
+        self.ensure_no_multisig()?;
+        self.data::<Data>().blacklist.insert(&account, &());
+
+        self.emit_blacklist_updated_event(account, true);
+        Ok(())
+    }
+
+    /// Unblocks a blacklisted account.
+    #[modifiers(when_not_taking_custody)]
+    default fn remove_from_blacklist(
+        &mut self,
+        account: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        self.check_moderator()?;
+        self.ensure_no_multisig()?;
+        self.data::<Data>().blacklist.remove(&account);
+
+        self.emit_blacklist_updated_event(account, false);
+        Ok(())
+    }
+
+    /// Checks if an account is blacklisted.
+    default fn is_blacklisted(&self, account: AccountId) -> bool {
+        self.data::<Data>().blacklist.contains(&account)
+    }
+
+    /// Removes a malicious listing.
+    #[modifiers(when_not_taking_custody)]
+    default fn admin_unlist(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reason: String,
+    ) -> Result<(), MarketplaceError> {
+        self.check_moderator()?;
+        ensure!(
+            self.data::<Data>()
+                .items
+                .contains(&(contract_address, token_key(&token_id))),
+            MarketplaceError::ItemNotListedForSale
+        );
+        self.remove_item(contract_address, &token_id);
+
+        self.emit_listing_removed_by_moderator_event(
+            contract_address,
+            token_id,
+            Self::env().caller(),
+            reason,
+        );
+        Ok(())
+    }
+}
+
+impl<T> MarketplaceModerationEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_blacklist_updated_event(&self, _account: AccountId, _blacklisted: bool) {}
+
+    default fn emit_listing_removed_by_moderator_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _moderator: AccountId,
+        _reason: String,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<access_control::Data>,
+{
+    default fn check_moderator(&self) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            caller == self.owner() || self.has_role(MODERATOR, caller),
+            MarketplaceError::AccessControlError(AccessControlError::MissingRole)
+        );
+
+        Ok(())
+    }
+}
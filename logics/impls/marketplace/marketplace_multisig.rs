@@ -0,0 +1,312 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_moderation::MarketplaceModerationEvents,
+    marketplace_timelock::Internal as TimelockInternal,
+    types::{AdminOperation, AdminProposal},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::multisig::MarketplaceMultisig,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{access_control::*, ownable::*, pausable},
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Fails if admin operations have to go through the signers.
+    fn ensure_no_multisig(&self) -> Result<(), MarketplaceError>;
+
+    /// Checks that caller is a signer.
+    fn check_signer(&self) -> Result<AccountId, MarketplaceError>;
+
+    /// Validates and stores signers and threshold.
+    fn update_signers(
+        &mut self,
+        signers: Vec<AccountId>,
+        threshold: u8,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Applies a confirmed admin operation.
+    fn apply_admin_operation(&mut self, operation: AdminOperation) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceMultisigEvents {
+    fn emit_signers_updated_event(&self, signers: Vec<AccountId>, threshold: u8);
+    fn emit_admin_operation_proposed_event(
+        &self,
+        proposal_id: u128,
+        proposer: AccountId,
+        operation: AdminOperation,
+    );
+    fn emit_admin_operation_confirmed_event(&self, proposal_id: u128, signer: AccountId);
+    fn emit_admin_operation_executed_event(&self, proposal_id: u128);
+}
+
+impl<T> MarketplaceMultisig for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<access_control::Data>,
+{
+    /// Sets signers which have to confirm sensitive admin operations.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_signers(
+        &mut self,
+        signers: Vec<AccountId>,
+        threshold: u8,
+    ) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.update_signers(signers, threshold)
+    }
+
+    /// Gets signers.
+    default fn get_signers(&self) -> Vec<AccountId> {
+        self.data::<Data>().signers.get().unwrap_or_default()
+    }
+
+    /// Gets number of confirmations admin operations need.
+    default fn get_signer_threshold(&self) -> u8 {
+        self.data::<Data>().v1.signer_threshold
+    }
+
+    /// Proposes an admin operation.
+    #[modifiers(when_not_taking_custody)]
+    default fn propose_admin_operation(
+        &mut self,
+        operation: AdminOperation,
+    ) -> Result<u128, MarketplaceError> {
+        let caller = self.check_signer()?;
+
+        let proposal_id = self.data::<Data>().v1.last_admin_proposal_id + 1;
+        self.data::<Data>().admin_proposals.insert(
+            &proposal_id,
+            &AdminProposal {
+                operation: operation.clone(),
+                confirmations: 1,
+                executed: false,
+            },
+        );
+        self.data::<Data>()
+            .admin_confirmations
+            .insert(&(proposal_id, caller), &());
+        self.data::<Data>().v1.last_admin_proposal_id = proposal_id;
+
+        self.emit_admin_operation_proposed_event(proposal_id, caller, operation);
+        self.emit_admin_operation_confirmed_event(proposal_id, caller);
+        Ok(proposal_id)
+    }
+
+    /// Confirms an admin proposal.
+    #[modifiers(when_not_taking_custody)]
+    default fn confirm_admin_operation(
+        &mut self,
+        proposal_id: u128,
+    ) -> Result<(), MarketplaceError> {
+        let caller = self.check_signer()?;
+        let mut proposal = self
+            .data::<Data>()
+            .admin_proposals
+            .get(&proposal_id)
+            .ok_or(MarketplaceError::AdminProposalNotFound)?;
+        ensure!(!proposal.executed, MarketplaceError::AdminProposalExecuted);
+        ensure!(
+            !self
+                .data::<Data>()
+                .admin_confirmations
+                .contains(&(proposal_id, caller)),
+            MarketplaceError::AlreadyConfirmed
+        );
+
+        proposal.confirmations += 1;
+        self.data::<Data>()
+            .admin_proposals
+            .insert(&proposal_id, &proposal);
+        self.data::<Data>()
+            .admin_confirmations
+            .insert(&(proposal_id, caller), &());
+
+        self.emit_admin_operation_confirmed_event(proposal_id, caller);
+        Ok(())
+    }
+
+    /// Executes an admin proposal confirmed by enough signers.
+    #[modifiers(when_not_taking_custody)]
+    default fn execute_admin_operation(
+        &mut self,
+        proposal_id: u128,
+    ) -> Result<(), MarketplaceError> {
+        self.check_signer()?;
+        let mut proposal = self
+            .data::<Data>()
+            .admin_proposals
+            .get(&proposal_id)
+            .ok_or(MarketplaceError::AdminProposalNotFound)?;
+        ensure!(!proposal.executed, MarketplaceError::AdminProposalExecuted);
+        ensure!(
+            proposal.confirmations >= self.data::<Data>().v1.signer_threshold,
+            MarketplaceError::NotEnoughConfirmations
+        );
+
+        proposal.executed = true;
+        self.data::<Data>()
+            .admin_proposals
+            .insert(&proposal_id, &proposal);
+        self.apply_admin_operation(proposal.operation)?;
+
+        self.emit_admin_operation_executed_event(proposal_id);
+        Ok(())
+    }
+
+    /// Gets admin proposal.
+    default fn get_admin_proposal(&self, proposal_id: u128) -> Option<AdminProposal> {
+        self.data::<Data>().admin_proposals.get(&proposal_id)
+    }
+}
+
+impl<T> MarketplaceMultisigEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_signers_updated_event(&self, _signers: Vec<AccountId>, _threshold: u8) {}
+
+    default fn emit_admin_operation_proposed_event(
+        &self,
+        _proposal_id: u128,
+        _proposer: AccountId,
+        _operation: AdminOperation,
+    ) {
+    }
+
+    default fn emit_admin_operation_confirmed_event(&self, _proposal_id: u128, _signer: AccountId) {
+    }
+
+    default fn emit_admin_operation_executed_event(&self, _proposal_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data> + Storage<pausable::Data> + Storage<access_control::Data>,
+{
+    default fn ensure_no_multisig(&self) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().v1.signer_threshold == 0,
+            MarketplaceError::MultisigRequired
+        );
+        Ok(())
+    }
+
+    default fn check_signer(&self) -> Result<AccountId, MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            self.data::<Data>().v1.signer_threshold > 0
+                && self
+                    .data::<Data>()
+                    .signers
+                    .get()
+                    .unwrap_or_default()
+                    .contains(&caller),
+            MarketplaceError::NotSigner
+        );
+        Ok(caller)
+    }
+
+    default fn update_signers(
+        &mut self,
+        mut signers: Vec<AccountId>,
+        threshold: u8,
+    ) -> Result<(), MarketplaceError> {
+        signers.sort();
+        signers.dedup();
+        ensure!(
+            threshold as usize <= signers.len(),
+            MarketplaceError::InvalidThreshold
+        );
+
+        self.data::<Data>().signers.set(&signers);
+        self.data::<Data>().v1.signer_threshold = threshold;
+        self.emit_signers_updated_event(signers, threshold);
+        Ok(())
+    }
+
+    default fn apply_admin_operation(
+        &mut self,
+        operation: AdminOperation,
+    ) -> Result<(), MarketplaceError> {
+        match operation {
+            AdminOperation::ParameterChange(change) => {
+                self.queue_parameter_change(change)?;
+                Ok(())
+            }
+            AdminOperation::AddToBlacklist(account) => {
+                self.data::<Data>().blacklist.insert(&account, &());
+                self.emit_blacklist_updated_event(account, true);
+                Ok(())
+            }
+            AdminOperation::RemoveFromBlacklist(account) => {
+                self.data::<Data>().blacklist.remove(&account);
+                self.emit_blacklist_updated_event(account, false);
+                Ok(())
+            }
+            AdminOperation::Upgrade(code_hash) => Self::env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| MarketplaceError::UpgradeFailed),
+            AdminOperation::SetSigners(signers, threshold) => {
+                self.update_signers(signers, threshold)
+            }
+            AdminOperation::CancelParameterChange(change_id) => {
+                self.remove_parameter_change(change_id)
+            }
+            AdminOperation::Pause => pausable::Internal::_pause(self),
+            AdminOperation::Unpause => pausable::Internal::_unpause(self),
+            AdminOperation::SetPriceOracle(price_oracle, pair, max_price_age) => {
+                self.data::<Data>().v1.price_oracle = Some(price_oracle);
+                self.data::<Data>().oracle_pair.set(&pair);
+                self.data::<Data>().v1.max_price_age = max_price_age;
+                Ok(())
+            }
+            AdminOperation::SetComplianceRegistry(registry) => {
+                self.data::<Data>().v1.compliance_registry = registry;
+                Ok(())
+            }
+            AdminOperation::GrantRole(role, account) => {
+                ensure!(
+                    !self.has_role(role, account),
+                    MarketplaceError::AccessControlError(AccessControlError::RoleRedundant)
+                );
+                access_control::Internal::_setup_role(self, role, account);
+                Ok(())
+            }
+            AdminOperation::RevokeRole(role, account) => {
+                access_control::Internal::_check_role(self, role, account)?;
+                access_control::Internal::_do_revoke_role(self, role, account);
+                Ok(())
+            }
+        }
+    }
+}
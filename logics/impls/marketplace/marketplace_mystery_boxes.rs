@@ -0,0 +1,334 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    batch,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, MysteryBox, MysteryBoxPurchase},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::mystery_boxes::MarketplaceMysteryBoxes,
+};
+use ink::{env::hash::Blake2x256, prelude::vec::Vec};
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Time (in milliseconds) after a mystery box purchase only the buyer can reveal it.
+pub const MYSTERY_BOX_REVEAL_PERIOD: u64 = 86_400_000;
+
+pub trait Internal {
+    /// Gets index of the NFT drawn from a pool of `tokens` NFTs by a random value.
+    fn mystery_box_draw(&self, random: [u8; 32], tokens: usize) -> usize;
+
+    /// Pays price of a draw to the creator less the marketplace fee.
+    fn pay_mystery_box_creator(
+        &mut self,
+        creator: AccountId,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceMysteryBoxesEvents {
+    fn emit_mystery_box_created_event(
+        &self,
+        box_id: u128,
+        contract: AccountId,
+        creator: AccountId,
+        price: Balance,
+        tokens: u32,
+    );
+    fn emit_mystery_box_bought_event(&self, purchase_id: u128, box_id: u128, buyer: AccountId);
+    fn emit_mystery_box_revealed_event(&self, purchase_id: u128, buyer: AccountId, token_id: Id);
+    fn emit_mystery_box_closed_event(&self, box_id: u128);
+}
+
+impl<T> MarketplaceMysteryBoxes for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Creates a mystery box of caller's NFTs.
+    #[modifiers(when_not_taking_custody)]
+    default fn create_mystery_box(
+        &mut self,
+        contract_address: AccountId,
+        token_ids: Vec<Id>,
+        price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(!token_ids.is_empty(), MarketplaceError::MysteryBoxSoldOut);
+        batch::check_batch_size(token_ids.len(), self.data::<Data>().v1.max_batch_size)?;
+        for token_id in token_ids.iter() {
+            self.check_token_owner(contract_address, token_id)?;
+            self.check_token_allowance(contract_address, token_id)?;
+            self.take_custody(contract_address, token_id.clone())?;
+        }
+
+        let box_id = self.data::<Data>().v1.last_mystery_box_id + 1;
+        let tokens = token_ids.len() as u32;
+        let mystery_box = MysteryBox {
+            creator: caller,
+            contract_address,
+            price,
+            tokens: token_ids,
+            pending: 0,
+            seed: [0; 32],
+        };
+        self.data::<Data>()
+            .mystery_boxes
+            .insert(&box_id, &mystery_box);
+        self.data::<Data>().v1.last_mystery_box_id = box_id;
+
+        self.emit_mystery_box_created_event(box_id, contract_address, caller, price, tokens);
+        Ok(())
+    }
+
+    /// Buys a draw from a mystery box.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn buy_mystery_box(
+        &mut self,
+        box_id: u128,
+        commitment: [u8; 32],
+    ) -> Result<(), MarketplaceError> {
+        let mut mystery_box = self
+            .data::<Data>()
+            .mystery_boxes
+            .get(&box_id)
+            .ok_or(MarketplaceError::MysteryBoxNotFound)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(
+            caller != mystery_box.creator,
+            MarketplaceError::AlreadyOwner
+        );
+        ensure!(
+            mystery_box.tokens.len() > mystery_box.pending as usize,
+            MarketplaceError::MysteryBoxSoldOut
+        );
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == mystery_box.price,
+            MarketplaceError::BadBuyValue {
+                expected: mystery_box.price,
+                received,
+            }
+        );
+
+        self.pay_mystery_box_creator(mystery_box.creator, mystery_box.price)?;
+        let block = Self::env().block_number();
+        let timestamp = Self::env().block_timestamp();
+        let purchase_id = self.data::<Data>().v1.last_mystery_box_purchase_id + 1;
+        self.data::<Data>().mystery_box_purchases.insert(
+            &purchase_id,
+            &MysteryBoxPurchase {
+                box_id,
+                buyer: caller,
+                commitment,
+                block,
+                timestamp,
+            },
+        );
+        self.data::<Data>().v1.last_mystery_box_purchase_id = purchase_id;
+        mystery_box.pending += 1;
+        mystery_box.seed = Self::env().hash_encoded::<Blake2x256, _>(&(
+            mystery_box.seed,
+            caller,
+            commitment,
+            block,
+            timestamp,
+        ));
+        self.data::<Data>()
+            .mystery_boxes
+            .insert(&box_id, &mystery_box);
+
+        self.emit_mystery_box_bought_event(purchase_id, box_id, caller);
+        Ok(())
+    }
+
+    /// Draws the NFT of a mystery box purchase.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn reveal_mystery_box(
+        &mut self,
+        purchase_id: u128,
+        secret: Option<[u8; 32]>,
+    ) -> Result<(), MarketplaceError> {
+        let purchase = self
+            .data::<Data>()
+            .mystery_box_purchases
+            .get(&purchase_id)
+            .ok_or(MarketplaceError::MysteryBoxPurchaseNotFound)?;
+        let mut mystery_box = self
+            .data::<Data>()
+            .mystery_boxes
+            .get(&purchase.box_id)
+            .ok_or(MarketplaceError::MysteryBoxNotFound)?;
+        // the draw depends on the seed after the purchase block
+        ensure!(
+            Self::env().block_number() > purchase.block,
+            MarketplaceError::MysteryBoxRevealTooEarly
+        );
+
+        let random = if Self::env().block_timestamp()
+            <= purchase.timestamp.saturating_add(MYSTERY_BOX_REVEAL_PERIOD)
+        {
+            let secret = secret.ok_or(MarketplaceError::MysteryBoxRevealPending)?;
+            ensure!(
+                Self::env().caller() == purchase.buyer,
+                MarketplaceError::MysteryBoxRevealPending
+            );
+            ensure!(
+                Self::env().hash_bytes::<Blake2x256>(&secret) == purchase.commitment,
+                MarketplaceError::InvalidMysteryBoxSecret
+            );
+            Self::env().hash_encoded::<Blake2x256, _>(&(secret, mystery_box.seed, purchase_id))
+        } else {
+            Self::env().hash_encoded::<Blake2x256, _>(&(mystery_box.seed, purchase_id))
+        };
+
+        let index = self.mystery_box_draw(random, mystery_box.tokens.len());
+        let token_id = mystery_box.tokens.swap_remove(index);
+        mystery_box.pending -= 1;
+        self.data::<Data>()
+            .mystery_boxes
+            .insert(&purchase.box_id, &mystery_box);
+        self.data::<Data>()
+            .mystery_box_purchases
+            .remove(&purchase_id);
+        self.release_custody(
+            mystery_box.contract_address,
+            purchase.buyer,
+            token_id.clone(),
+        )?;
+
+        self.emit_mystery_box_revealed_event(purchase_id, purchase.buyer, token_id);
+        Ok(())
+    }
+
+    /// Closes mystery box and returns unsold NFTs to the creator.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn close_mystery_box(&mut self, box_id: u128) -> Result<(), MarketplaceError> {
+        let mystery_box = self
+            .data::<Data>()
+            .mystery_boxes
+            .get(&box_id)
+            .ok_or(MarketplaceError::MysteryBoxNotFound)?;
+        ensure!(
+            Self::env().caller() == mystery_box.creator,
+            MarketplaceError::NotOwner
+        );
+        ensure!(
+            mystery_box.pending == 0,
+            MarketplaceError::MysteryBoxPurchasesPending
+        );
+
+        self.data::<Data>().mystery_boxes.remove(&box_id);
+        for token_id in mystery_box.tokens {
+            self.release_custody(mystery_box.contract_address, mystery_box.creator, token_id)?;
+        }
+
+        self.emit_mystery_box_closed_event(box_id);
+        Ok(())
+    }
+
+    /// Gets mystery box.
+    default fn get_mystery_box(&self, box_id: u128) -> Option<MysteryBox> {
+        self.data::<Data>().mystery_boxes.get(&box_id)
+    }
+
+    /// Gets mystery box purchase.
+    default fn get_mystery_box_purchase(&self, purchase_id: u128) -> Option<MysteryBoxPurchase> {
+        self.data::<Data>().mystery_box_purchases.get(&purchase_id)
+    }
+}
+
+impl<T> MarketplaceMysteryBoxesEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_mystery_box_created_event(
+        &self,
+        _box_id: u128,
+        _contract: AccountId,
+        _creator: AccountId,
+        _price: Balance,
+        _tokens: u32,
+    ) {
+    }
+
+    default fn emit_mystery_box_bought_event(
+        &self,
+        _purchase_id: u128,
+        _box_id: u128,
+        _buyer: AccountId,
+    ) {
+    }
+
+    default fn emit_mystery_box_revealed_event(
+        &self,
+        _purchase_id: u128,
+        _buyer: AccountId,
+        _token_id: Id,
+    ) {
+    }
+
+    default fn emit_mystery_box_closed_event(&self, _box_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn mystery_box_draw(&self, random: [u8; 32], tokens: usize) -> usize {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&random[..16]);
+        (u128::from_le_bytes(bytes) % tokens as u128) as usize
+    }
+
+    default fn pay_mystery_box_creator(
+        &mut self,
+        creator: AccountId,
+        price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let marketplace_fee = price
+            .checked_mul(self.data::<Data>().fee as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+        self.transfer_funds(
+            Currency::Native,
+            self.fee_recipient()?,
+            marketplace_fee,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+        self.transfer_funds(
+            Currency::Native,
+            creator,
+            price - marketplace_fee,
+            MarketplaceError::TransferToOwnerFailed,
+        )
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Listings of RMRK tokens with nested children.
+//!
+//! Children of a listed token are recorded when it is listed so buyers know what is included,
+//! and a sale fails if they changed since or if a child is equipped into a checked slot.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, ChildNft, NftContractType, RegisteredCollection},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::{
+        nested_listings::MarketplaceNestedListings,
+        rmrk::{EquippableRef, NestingRef},
+    },
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, psp34::Id},
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Checks if NFT contract is a registered RMRK collection.
+    fn is_rmrk_collection(&self, contract_address: AccountId) -> bool;
+
+    /// Records children nested in a listed RMRK token.
+    fn record_listing_children(&mut self, contract_address: AccountId, token_id: &Id);
+
+    /// Checks that children nested in an RMRK token did not change since it was listed and
+    /// none is equipped into a checked slot.
+    fn check_nested_token(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceNestedListingsEvents {
+    fn emit_listing_children_recorded_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        children: Vec<ChildNft>,
+    );
+}
+
+impl<T> MarketplaceNestedListings for T
+where
+    T: Storage<Data> + Storage<ownable::Data>,
+{
+    /// Sets slots of an RMRK collection checked before a sale.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_equip_slots(
+        &mut self,
+        contract_address: AccountId,
+        slots: Vec<u32>,
+    ) -> Result<(), MarketplaceError> {
+        self.check_collection_owner(self.data::<ownable::Data>().owner, contract_address)?;
+        ensure!(
+            self.is_rmrk_collection(contract_address),
+            MarketplaceError::NotRmrkCollection
+        );
+
+        if slots.is_empty() {
+            self.data::<Data>().equip_slots.remove(&contract_address);
+        } else {
+            self.data::<Data>()
+                .equip_slots
+                .insert(&contract_address, &slots);
+        }
+        Ok(())
+    }
+
+    /// Gets slots of an RMRK collection checked before a sale.
+    default fn get_equip_slots(&self, contract_address: AccountId) -> Vec<u32> {
+        self.data::<Data>()
+            .equip_slots
+            .get(&contract_address)
+            .unwrap_or_default()
+    }
+
+    /// Gets children currently nested in an RMRK token.
+    default fn get_nested_children(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Vec<ChildNft> {
+        if !self.is_rmrk_collection(contract_address) {
+            return Vec::new()
+        }
+        NestingRef::get_accepted_children(&contract_address, token_id)
+    }
+
+    /// Gets children nested in an RMRK token when it was listed.
+    default fn get_listing_children(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Vec<ChildNft> {
+        self.data::<Data>()
+            .listing_children
+            .get(&(contract_address, token_key(&token_id)))
+            .unwrap_or_default()
+    }
+}
+
+impl<T> MarketplaceNestedListingsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_listing_children_recorded_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _children: Vec<ChildNft>,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn is_rmrk_collection(&self, contract_address: AccountId) -> bool {
+        matches!(
+            self.data::<Data>()
+                .registered_collections
+                .get(&contract_address),
+            Some(RegisteredCollection {
+                contract_type: NftContractType::Rmrk,
+                ..
+            })
+        )
+    }
+
+    default fn record_listing_children(&mut self, contract_address: AccountId, token_id: &Id) {
+        if !self.is_rmrk_collection(contract_address) {
+            return
+        }
+
+        let children = NestingRef::get_accepted_children(&contract_address, token_id.clone());
+        self.data::<Data>()
+            .listing_children
+            .insert(&(contract_address, token_key(token_id)), &children);
+        self.emit_listing_children_recorded_event(contract_address, token_id.clone(), children);
+    }
+
+    default fn check_nested_token(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError> {
+        if !self.is_rmrk_collection(contract_address) {
+            return Ok(())
+        }
+
+        if let Some(listing_children) = self
+            .data::<Data>()
+            .listing_children
+            .get(&(contract_address, token_key(token_id)))
+        {
+            ensure!(
+                NestingRef::get_accepted_children(&contract_address, token_id.clone())
+                    == listing_children,
+                MarketplaceError::NestedChildrenChanged
+            );
+        }
+
+        let slots = self
+            .data::<Data>()
+            .equip_slots
+            .get(&contract_address)
+            .unwrap_or_default();
+        for slot in slots {
+            ensure!(
+                EquippableRef::get_equipment(&contract_address, token_id.clone(), slot).is_none(),
+                MarketplaceError::TokenEquipped
+            );
+        }
+        Ok(())
+    }
+}
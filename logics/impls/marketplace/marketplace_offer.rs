@@ -0,0 +1,370 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::{Internal as SaleInternal, MarketplaceSaleEvents},
+    types::{token_key, Currency, OfferBond, OfferItem, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::{admin::MarketplaceAdmin, offer::MarketplaceOffer},
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+impl<T> MarketplaceOffer for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+
+        let offer = self
+            .data::<Data>()
+            .offer_items
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferDoesNotExist)?;
+
+        if offer.bidder_id != caller {
+            return Err(MarketplaceError::NotOwner);
+        }
+
+        let age = self.offer_age(offer_id);
+        ensure!(
+            age >= self.data::<Data>().v1.min_offer_duration,
+            MarketplaceError::OfferTooRecent
+        );
+
+        self.remove_offer(offer_id, &offer);
+
+        // offers cancelled within the bond period forfeit their bond
+        let forfeit = age < self.data::<Data>().v1.offer_bond_period;
+        self.release_offer_bond(offer_id, caller, forfeit)?;
+
+        self.emit_cancel_offer_event(offer_id);
+
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody)]
+    default fn remove_unfunded_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .offer_items
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferDoesNotExist)?;
+        ensure!(
+            !self.get_offer_active(offer_id),
+            MarketplaceError::OfferFunded
+        );
+        // the bidder can't use this to get around the minimum offer duration
+        if Self::env().caller() == offer.bidder_id {
+            ensure!(
+                self.offer_age(offer_id) >= self.data::<Data>().v1.min_offer_duration,
+                MarketplaceError::OfferTooRecent
+            );
+        }
+
+        self.remove_offer(offer_id, &offer);
+        self.release_offer_bond(offer_id, offer.bidder_id, true)?;
+
+        self.emit_offer_auto_cancelled_event(offer_id, offer.bidder_id);
+        Ok(())
+    }
+
+    /// Sets the bond taken from the bidder's deposit for every new offer.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_offer_bond(&mut self, bond: Balance) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.offer_bond = bond;
+        Ok(())
+    }
+
+    /// Gets the offer bond.
+    default fn get_offer_bond(&self) -> Balance {
+        self.data::<Data>().v1.offer_bond
+    }
+
+    /// Sets time (in milliseconds) within which a cancelled offer forfeits its bond.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_offer_bond_period(&mut self, duration: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.offer_bond_period = duration;
+        Ok(())
+    }
+
+    /// Gets the offer bond period in milliseconds.
+    default fn get_offer_bond_period(&self) -> u64 {
+        self.data::<Data>().v1.offer_bond_period
+    }
+
+    /// Sets time (in milliseconds) an offer has to stay open before it can be cancelled.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_min_offer_duration(&mut self, duration: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.min_offer_duration = duration;
+        Ok(())
+    }
+
+    /// Gets minimum offer duration in milliseconds.
+    default fn get_min_offer_duration(&self) -> u64 {
+        self.data::<Data>().v1.min_offer_duration
+    }
+
+    default fn get_offer_active(&self, offer_id: u128) -> bool {
+        let offer = self.data::<Data>().offer_items.get(&offer_id);
+
+        if let Some(offer) = offer {
+            let deposit = self.get_currency_deposit(offer.bidder_id, self.offer_currency(offer_id));
+            if let Some(total_amount) = (offer.quantity as u128).checked_mul(offer.price_per_item) {
+                return deposit >= total_amount
+            }
+        }
+        return false;
+    }
+
+    /// Checks if several offers are active.
+    default fn get_offers_active(&self, offer_ids: Vec<u128>) -> Vec<bool> {
+        offer_ids
+            .into_iter()
+            .take(self.data::<Data>().v1.max_batch_size as usize)
+            .map(|offer_id| self.get_offer_active(offer_id))
+            .collect()
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn accept_offer(
+        &mut self,
+        offer_id: u128,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let mut offer = self
+            .data::<Data>()
+            .offer_items
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferDoesNotExist)?;
+        if let Some(token_id_offer) = offer.token_id.clone() {
+            if token_id_offer != token_id {
+                return Err(MarketplaceError::OfferNotMatch);
+            }
+        }
+
+        // check owner and allowance
+        self.check_token_owner(offer.contract_address, &token_id)?;
+        self.check_token_allowance(offer.contract_address, &token_id)?;
+
+        // check if bidder's balance sufficient
+        let currency = self.offer_currency(offer_id);
+        let deposit = self.get_currency_deposit(offer.bidder_id, currency);
+
+        if deposit < offer.price_per_item {
+            return Err(MarketplaceError::BalanceInsufficient);
+        }
+
+        // update offer state
+        let filled = offer.quantity == 1;
+        if filled {
+            self.remove_offer(offer_id, &offer);
+        } else {
+            offer.quantity -= 1;
+            self.data::<Data>().offer_items.insert(&offer_id, &offer);
+        }
+
+        // update bidder state
+        self.set_currency_deposit(offer.bidder_id, currency, deposit - offer.price_per_item);
+        if filled {
+            self.release_offer_bond(offer_id, offer.bidder_id, false)?;
+        }
+
+        self.emit_accept_offer_event(offer_id);
+
+        self.settle_sale(
+            offer.contract_address,
+            token_id,
+            Self::env().caller(),
+            offer.bidder_id,
+            offer.price_per_item,
+            currency,
+            SaleAttribution::default(),
+        )
+    }
+
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn fulfill_offer(
+        &mut self,
+        _offer_id: u128,
+        _token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        // TO DO: will be used for accepting offer with extra
+        Ok(())
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn make_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        quantity: u64,
+        price_per_item: Balance,
+        extra: String,
+    ) -> Result<u128, MarketplaceError> {
+        self.make_offer_with_currency(
+            contract_address,
+            token_id,
+            quantity,
+            price_per_item,
+            Currency::Native,
+            extra,
+        )
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn make_offer_with_currency(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        quantity: u64,
+        price_per_item: Balance,
+        currency: Currency,
+        extra: String,
+    ) -> Result<u128, MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        match currency {
+            Currency::Native => self.check_tick_size(price_per_item)?,
+            Currency::Psp22(currency) => ensure!(
+                self.is_supported_currency(currency),
+                MarketplaceError::CurrencyNotSupported
+            ),
+            Currency::Asset(asset_id) => ensure!(
+                self.is_supported_asset(asset_id),
+                MarketplaceError::CurrencyNotSupported
+            ),
+        }
+
+        let total_amount = (quantity as u128)
+            .checked_mul(price_per_item)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+
+        // the bond is always taken from the native deposit
+        let native_deposit = self.get_deposit_internal(caller);
+        let bond = self.data::<Data>().v1.offer_bond;
+        let (deposit, required_amount) = if currency == Currency::Native {
+            let required_amount = total_amount
+                .checked_add(bond)
+                .ok_or(MarketplaceError::ArithmeticOverflow)?;
+            (native_deposit, required_amount)
+        } else {
+            ensure!(
+                native_deposit >= bond,
+                MarketplaceError::BalanceInsufficient
+            );
+            (self.get_currency_deposit(caller, currency), total_amount)
+        };
+
+        if deposit < required_amount {
+            return Err(MarketplaceError::BalanceInsufficient);
+        }
+
+        let current_offer_id = self.data::<Data>().last_offer_id + 1;
+
+        self.data::<Data>().last_offer_id = current_offer_id;
+
+        if currency != Currency::Native {
+            self.data::<Data>()
+                .offer_currencies
+                .insert(&current_offer_id, &currency);
+        }
+        if bond > 0 {
+            self.set_currency_deposit(caller, Currency::Native, native_deposit - bond);
+            self.lock_funds(bond);
+            self.data::<Data>()
+                .offer_bonds
+                .insert(&current_offer_id, &OfferBond { amount: bond });
+        }
+
+        self.data::<Data>()
+            .offer_created_at
+            .insert(&current_offer_id, &Self::env().block_timestamp());
+        let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+        stats.active_offers += 1;
+        self.data::<Data>().stats.set(&stats);
+
+        self.data::<Data>().offer_items.insert(
+            &current_offer_id,
+            &OfferItem {
+                bidder_id: caller,
+                contract_address,
+                token_id: token_id.clone(),
+                quantity,
+                price_per_item,
+                extra: extra.clone(),
+            },
+        );
+
+        let key = (contract_address, token_id.as_ref().map(token_key));
+        self.data::<Data>()
+            .token_offer_index
+            .insert(&key, &current_offer_id);
+        self.data::<Data>()
+            .account_offer_index
+            .insert(&caller, &current_offer_id);
+
+        // Emit event
+        self.emit_make_offer_event(
+            caller,
+            contract_address,
+            token_id,
+            quantity,
+            price_per_item,
+            extra,
+            current_offer_id,
+        );
+        Ok(current_offer_id)
+    }
+
+    default fn get_offer_currency(&self, offer_id: u128) -> Option<Currency> {
+        if !self.data::<Data>().offer_items.contains(&offer_id) {
+            return None
+        }
+        Some(self.offer_currency(offer_id))
+    }
+
+    default fn get_offer_for_token(
+        &self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+    ) -> Result<Vec<u128>, MarketplaceError> {
+        let offers = &self.data::<Data>().token_offer_index;
+        let key = (contract_address, token_id.as_ref().map(token_key));
+        Ok((0..offers.count(&key))
+            .filter_map(|index| offers.get_value(&key, &index))
+            .collect())
+    }
+}
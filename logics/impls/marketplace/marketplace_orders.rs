@@ -0,0 +1,225 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    contract_calls,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, SaleAttribution, SignedOrder},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::orders::MarketplaceOrders,
+};
+use ink::env::hash::Blake2x256;
+use openbrush::{
+    contracts::{pausable::*, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Gets hash of a signed order bound to this marketplace.
+    fn order_hash(&self, order: &SignedOrder) -> [u8; 32];
+
+    /// Checks that the order is signed by its seller.
+    fn check_order_signature(
+        &self,
+        order: &SignedOrder,
+        signature: &[u8; 65],
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if a signed order nonce of an account has been fulfilled or cancelled.
+    fn order_nonce_used(&self, account: AccountId, nonce: u64) -> bool;
+
+    /// Marks signed order nonce of an account as used or fails if it has been used or
+    /// cancelled already.
+    fn use_order_nonce(&mut self, account: AccountId, nonce: u64) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceOrdersEvents {
+    fn emit_signed_order_fulfilled_event(&self, seller: AccountId, nonce: u64, buyer: AccountId);
+    fn emit_signed_order_cancelled_event(&self, seller: AccountId, nonce: u64);
+    fn emit_signed_orders_cancelled_event(&self, seller: AccountId, min_nonce: u64);
+}
+
+impl<T> MarketplaceOrders for T
+where
+    T: Storage<Data> + Storage<pausable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Buys NFT with a sale order signed by the seller.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn fulfill_signed_order(
+        &mut self,
+        order: SignedOrder,
+        signature: [u8; 65],
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            Self::env().block_timestamp() <= order.expiry,
+            MarketplaceError::OrderExpired
+        );
+        self.check_order_signature(&order, &signature)?;
+        self.use_order_nonce(order.seller, order.nonce)?;
+        self.registered_collection(order.contract_address)?;
+
+        let token_owner = contract_calls::owner_of(order.contract_address, &order.token_id)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        ensure!(token_owner == order.seller, MarketplaceError::NotOwner);
+        let caller = Self::env().caller();
+        ensure!(caller != order.seller, MarketplaceError::AlreadyOwner);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == order.price,
+            MarketplaceError::BadBuyValue {
+                expected: order.price,
+                received,
+            }
+        );
+
+        self.settle_sale(
+            order.contract_address,
+            order.token_id,
+            order.seller,
+            caller,
+            order.price,
+            Currency::Native,
+            SaleAttribution::default(),
+        )?;
+
+        self.emit_signed_order_fulfilled_event(order.seller, order.nonce, caller);
+        Ok(())
+    }
+
+    /// Cancels caller's signed order.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_signed_order(&mut self, nonce: u64) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.use_order_nonce(caller, nonce)?;
+
+        self.emit_signed_order_cancelled_event(caller, nonce);
+        Ok(())
+    }
+
+    /// Cancels caller's signed orders with a lower nonce.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_signed_orders(&mut self, min_nonce: u64) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            min_nonce > self.get_min_order_nonce(caller),
+            MarketplaceError::OrderNonceUsed
+        );
+        self.data::<Data>()
+            .min_order_nonces
+            .insert(&caller, &min_nonce);
+
+        self.emit_signed_orders_cancelled_event(caller, min_nonce);
+        Ok(())
+    }
+
+    /// Gets hash of a signed order.
+    default fn get_order_hash(&self, order: SignedOrder) -> [u8; 32] {
+        self.order_hash(&order)
+    }
+
+    /// Gets lowest nonce of valid signed orders of an account.
+    default fn get_min_order_nonce(&self, account: AccountId) -> u64 {
+        self.data::<Data>()
+            .min_order_nonces
+            .get(&account)
+            .unwrap_or_default()
+    }
+
+    /// Checks if a signed order nonce of an account has been fulfilled or cancelled.
+    default fn is_order_nonce_used(&self, account: AccountId, nonce: u64) -> bool {
+        self.order_nonce_used(account, nonce)
+    }
+}
+
+impl<T> MarketplaceOrdersEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_signed_order_fulfilled_event(
+        &self,
+        _seller: AccountId,
+        _nonce: u64,
+        _buyer: AccountId,
+    ) {
+    }
+
+    default fn emit_signed_order_cancelled_event(&self, _seller: AccountId, _nonce: u64) {}
+
+    default fn emit_signed_orders_cancelled_event(&self, _seller: AccountId, _min_nonce: u64) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn order_hash(&self, order: &SignedOrder) -> [u8; 32] {
+        Self::env().hash_encoded::<Blake2x256, _>(&(Self::env().account_id(), order))
+    }
+
+    default fn check_order_signature(
+        &self,
+        order: &SignedOrder,
+        signature: &[u8; 65],
+    ) -> Result<(), MarketplaceError> {
+        let public_key = Self::env()
+            .ecdsa_recover(signature, &self.order_hash(order))
+            .map_err(|_| MarketplaceError::InvalidSignature)?;
+        // account id of an ECDSA key is the hash of the compressed public key
+        let signer = AccountId::from(Self::env().hash_bytes::<Blake2x256>(&public_key));
+        ensure!(signer == order.seller, MarketplaceError::InvalidSignature);
+
+        Ok(())
+    }
+
+    default fn order_nonce_used(&self, account: AccountId, nonce: u64) -> bool {
+        nonce
+            < self
+                .data::<Data>()
+                .min_order_nonces
+                .get(&account)
+                .unwrap_or_default()
+            || self
+                .data::<Data>()
+                .used_order_nonces
+                .contains(&(account, nonce))
+    }
+
+    default fn use_order_nonce(
+        &mut self,
+        account: AccountId,
+        nonce: u64,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self.order_nonce_used(account, nonce),
+            MarketplaceError::OrderNonceUsed
+        );
+        self.data::<Data>()
+            .used_order_nonces
+            .insert(&(account, nonce), &());
+
+        Ok(())
+    }
+}
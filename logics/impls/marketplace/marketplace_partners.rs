@@ -0,0 +1,334 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Whitelabel partner frontends.
+//!
+//! Sales made through a partner pay the partner a share of the marketplace fee and can be
+//! charged a partner specific marketplace fee. The partner is passed to `settle_sale` in the
+//! `SaleAttribution` of the purchase, so all sale paths, escrowed ones included, reuse the same
+//! settlement. Partner earnings accrue in the marketplace and are claimed per currency.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Partner, SaleAttribution},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::partners::MarketplacePartners,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Checks fee share and fee override of a partner.
+    fn check_partner_fees(
+        &self,
+        fee_share: u16,
+        fee_override: Option<u16>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Attributes a purchase to an active partner.
+    fn partner_attribution(&self, partner_id: u32) -> Result<SaleAttribution, MarketplaceError>;
+
+    /// Accrues partner's share of the marketplace fee. Returns the share.
+    fn accrue_partner_fee(
+        &mut self,
+        partner_id: u32,
+        partner: &Partner,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance;
+
+    /// Pays `amount` of partner earnings in a currency to the partner account.
+    fn pay_partner_earnings(
+        &mut self,
+        partner_id: u32,
+        account: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplacePartnersEvents {
+    fn emit_partner_registered_event(&self, partner_id: u32, account: AccountId);
+    fn emit_partner_fee_accrued_event(&self, partner_id: u32, currency: Currency, amount: Balance);
+    fn emit_partner_earnings_claimed_event(
+        &self,
+        partner_id: u32,
+        currency: Currency,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplacePartners for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Registers a whitelabel partner frontend.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn register_partner(
+        &mut self,
+        account: AccountId,
+        fee_share: u16,
+        fee_override: Option<u16>,
+        metadata: String,
+    ) -> Result<u32, MarketplaceError> {
+        self.check_partner_fees(fee_share, fee_override)?;
+
+        let partner_id = self.data::<Data>().v1.last_partner_id + 1;
+        self.data::<Data>().partners.insert(
+            &partner_id,
+            &Partner {
+                account,
+                fee_share,
+                fee_override,
+                metadata,
+                active: true,
+            },
+        );
+        self.data::<Data>().v1.last_partner_id = partner_id;
+
+        self.emit_partner_registered_event(partner_id, account);
+        Ok(partner_id)
+    }
+
+    /// Updates fee share, fee override and branding metadata of a partner.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn update_partner(
+        &mut self,
+        partner_id: u32,
+        fee_share: u16,
+        fee_override: Option<u16>,
+        metadata: String,
+    ) -> Result<(), MarketplaceError> {
+        let mut partner = self
+            .data::<Data>()
+            .partners
+            .get(&partner_id)
+            .ok_or(MarketplaceError::PartnerNotFound)?;
+        self.check_partner_fees(fee_share, fee_override)?;
+
+        partner.fee_share = fee_share;
+        partner.fee_override = fee_override;
+        partner.metadata = metadata;
+        self.data::<Data>().partners.insert(&partner_id, &partner);
+        Ok(())
+    }
+
+    /// Activates or deactivates a partner.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_partner_active(
+        &mut self,
+        partner_id: u32,
+        active: bool,
+    ) -> Result<(), MarketplaceError> {
+        let mut partner = self
+            .data::<Data>()
+            .partners
+            .get(&partner_id)
+            .ok_or(MarketplaceError::PartnerNotFound)?;
+        partner.active = active;
+        self.data::<Data>().partners.insert(&partner_id, &partner);
+        Ok(())
+    }
+
+    /// Buys NFT item through a partner frontend.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_via_partner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        partner_id: u32,
+    ) -> Result<(), MarketplaceError> {
+        let attribution = self.partner_attribution(partner_id)?;
+        self.buy_item(contract_address, token_id, attribution)
+    }
+
+    /// Buys NFT item through a partner frontend paying with caller's credit and deposit.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_deposit_via_partner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        partner_id: u32,
+    ) -> Result<(), MarketplaceError> {
+        let attribution = self.partner_attribution(partner_id)?;
+        self.buy_from_deposit(
+            Self::env().caller(),
+            contract_address,
+            token_id,
+            true,
+            attribution,
+        )
+    }
+
+    /// Pays partner earnings in a currency to the partner account.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_partner_earnings(
+        &mut self,
+        partner_id: u32,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError> {
+        let partner = self
+            .data::<Data>()
+            .partners
+            .get(&partner_id)
+            .ok_or(MarketplaceError::PartnerNotFound)?;
+        ensure!(
+            Self::env().caller() == partner.account,
+            MarketplaceError::NotPartner
+        );
+        let amount = self.get_partner_earnings(partner_id, currency);
+        ensure!(amount > 0, MarketplaceError::NoPartnerEarnings);
+
+        self.pay_partner_earnings(partner_id, partner.account, currency, amount)
+    }
+
+    /// Gets partner.
+    default fn get_partner(&self, partner_id: u32) -> Option<Partner> {
+        self.data::<Data>().partners.get(&partner_id)
+    }
+
+    /// Gets unclaimed earnings of a partner in a currency.
+    default fn get_partner_earnings(&self, partner_id: u32, currency: Currency) -> Balance {
+        self.data::<Data>()
+            .partner_earnings
+            .get(&(partner_id, currency))
+            .unwrap_or_default()
+    }
+}
+
+impl<T> MarketplacePartnersEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_partner_registered_event(&self, _partner_id: u32, _account: AccountId) {}
+
+    default fn emit_partner_fee_accrued_event(
+        &self,
+        _partner_id: u32,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_partner_earnings_claimed_event(
+        &self,
+        _partner_id: u32,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_partner_fees(
+        &self,
+        fee_share: u16,
+        fee_override: Option<u16>,
+    ) -> Result<(), MarketplaceError> {
+        self.check_fee(fee_share, 10_000)?;
+        if let Some(fee) = fee_override {
+            self.check_fee(fee, self.data::<Data>().max_fee)?;
+        }
+        Ok(())
+    }
+
+    default fn partner_attribution(
+        &self,
+        partner_id: u32,
+    ) -> Result<SaleAttribution, MarketplaceError> {
+        let partner = self
+            .data::<Data>()
+            .partners
+            .get(&partner_id)
+            .ok_or(MarketplaceError::PartnerNotFound)?;
+        ensure!(partner.active, MarketplaceError::PartnerInactive);
+
+        Ok(SaleAttribution {
+            partner: Some(partner_id),
+            ..Default::default()
+        })
+    }
+
+    default fn accrue_partner_fee(
+        &mut self,
+        partner_id: u32,
+        partner: &Partner,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance {
+        let amount = marketplace_fee.saturating_mul(partner.fee_share as u128) / 10_000;
+        if amount == 0 {
+            return 0
+        }
+
+        let earnings = self
+            .data::<Data>()
+            .partner_earnings
+            .get(&(partner_id, currency))
+            .unwrap_or_default();
+        self.data::<Data>()
+            .partner_earnings
+            .insert(&(partner_id, currency), &earnings.saturating_add(amount));
+        if currency == Currency::Native {
+            self.accrue_fees(amount);
+        }
+        self.emit_partner_fee_accrued_event(partner_id, currency, amount);
+        amount
+    }
+
+    default fn pay_partner_earnings(
+        &mut self,
+        partner_id: u32,
+        account: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>()
+            .partner_earnings
+            .remove(&(partner_id, currency));
+        if currency == Currency::Native {
+            self.pay_accrued_fees(amount);
+        }
+        self.transfer_funds(
+            currency,
+            account,
+            amount,
+            MarketplaceError::TransferToPartnerFailed,
+        )?;
+
+        self.emit_partner_earnings_claimed_event(partner_id, currency, amount);
+        Ok(())
+    }
+}
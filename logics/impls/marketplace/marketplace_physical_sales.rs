@@ -0,0 +1,250 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Sales of NFTs backed by physical items.
+//!
+//! Physical-backed sales are escrowed sales whose release time is the delivery period set
+//! by the seller. The buyer confirms delivery to release the price early, and either party
+//! can dispute the delivery through the dispute module before the period ends. Besides
+//! releasing or refunding the sale, an arbiter can find the item partially delivered, which
+//! refunds the share of the price agreed in the listing and completes the sale.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_disputes::Internal as DisputesInternal,
+    marketplace_escrow::{Internal as EscrowInternal, MarketplaceEscrowEvents},
+    marketplace_sale::Internal as SaleInternal,
+    types::{
+        token_key, Currency, DeliveryOutcome, DisputeSubject, EscrowItem, PhysicalTerms, ARBITER,
+    },
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::physical_sales::MarketplacePhysicalSales,
+};
+use openbrush::{
+    contracts::{access_control::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Refunds the partial refund share of the price to the buyer and completes the sale.
+    fn settle_partial_delivery(
+        &mut self,
+        escrow_id: u128,
+        escrow: EscrowItem,
+        partial_refund: u16,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplacePhysicalSalesEvents {
+    fn emit_physical_item_listed_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        delivery_period: u64,
+        partial_refund: u16,
+    );
+    fn emit_delivery_confirmed_event(&self, escrow_id: u128);
+}
+
+impl<T> MarketplacePhysicalSales for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data> + Storage<access_control::Data>,
+{
+    /// Lists a NFT backed by a physical item.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_physical_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        delivery_period: u64,
+        partial_refund: u16,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(delivery_period > 0, MarketplaceError::InvalidDeliveryPeriod);
+        self.check_fee(partial_refund, 10_000)?;
+        self.list_item(
+            contract_address,
+            token_id.clone(),
+            price,
+            Currency::Native,
+            false,
+            true,
+        )?;
+
+        self.data::<Data>().physical_listings.insert(
+            &(contract_address, token_key(&token_id)),
+            &PhysicalTerms {
+                delivery_period,
+                partial_refund,
+            },
+        );
+        self.emit_physical_item_listed_event(
+            contract_address,
+            token_id,
+            delivery_period,
+            partial_refund,
+        );
+        Ok(())
+    }
+
+    /// Confirms delivery of a physical-backed purchase.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn confirm_delivery(&mut self, escrow_id: u128) -> Result<(), MarketplaceError> {
+        let escrow = self
+            .data::<Data>()
+            .escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::EscrowNotFound)?;
+        ensure!(
+            self.data::<Data>().physical_escrows.contains(&escrow_id),
+            MarketplaceError::NotPhysicalSale
+        );
+        ensure!(
+            Self::env().caller() == escrow.buyer,
+            MarketplaceError::NotBuyer
+        );
+        ensure!(!escrow.disputed, MarketplaceError::EscrowDisputed);
+
+        self.close_escrow(escrow_id, escrow, false)?;
+        self.emit_delivery_confirmed_event(escrow_id);
+        self.emit_escrow_released_event(escrow_id);
+        Ok(())
+    }
+
+    /// Resolves a dispute of a physical-backed sale by the delivery outcome.
+    #[modifiers(when_not_taking_custody, only_role(ARBITER), non_reentrant)]
+    default fn rule_on_delivery(
+        &mut self,
+        dispute_id: u128,
+        outcome: DeliveryOutcome,
+        rationale: String,
+    ) -> Result<(), MarketplaceError> {
+        let dispute = self
+            .data::<Data>()
+            .disputes
+            .get(&dispute_id)
+            .ok_or(MarketplaceError::DisputeNotFound)?;
+        let escrow_id = match dispute.subject {
+            DisputeSubject::Escrow(escrow_id) => escrow_id,
+            _ => return Err(MarketplaceError::NotPhysicalSale),
+        };
+        let terms = self
+            .data::<Data>()
+            .physical_escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::NotPhysicalSale)?;
+        let escrow = self
+            .data::<Data>()
+            .escrows
+            .get(&escrow_id)
+            .ok_or(MarketplaceError::EscrowNotFound)?;
+
+        // any outcome but a full delivery is in the buyer's favour
+        let claimant_wins =
+            (outcome != DeliveryOutcome::Delivered) == (dispute.claimant == escrow.buyer);
+        self.record_ruling(dispute_id, claimant_wins, rationale)?;
+        match outcome {
+            DeliveryOutcome::Delivered => self.close_escrow(escrow_id, escrow, false)?,
+            DeliveryOutcome::PartiallyDelivered => {
+                self.settle_partial_delivery(escrow_id, escrow, terms.partial_refund)?
+            }
+            DeliveryOutcome::NotDelivered => self.close_escrow(escrow_id, escrow, true)?,
+        }
+
+        self.emit_dispute_resolved_event(escrow_id, outcome == DeliveryOutcome::NotDelivered);
+        Ok(())
+    }
+
+    /// Gets delivery terms of a listed NFT.
+    default fn get_physical_terms(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<PhysicalTerms> {
+        self.data::<Data>()
+            .physical_listings
+            .get(&(contract_address, token_key(&token_id)))
+    }
+
+    /// Gets delivery terms of an escrowed physical-backed sale.
+    default fn get_escrow_physical_terms(&self, escrow_id: u128) -> Option<PhysicalTerms> {
+        self.data::<Data>().physical_escrows.get(&escrow_id)
+    }
+}
+
+impl<T> MarketplacePhysicalSalesEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_physical_item_listed_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _delivery_period: u64,
+        _partial_refund: u16,
+    ) {
+    }
+
+    default fn emit_delivery_confirmed_event(&self, _escrow_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn settle_partial_delivery(
+        &mut self,
+        escrow_id: u128,
+        escrow: EscrowItem,
+        partial_refund: u16,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().escrows.remove(&escrow_id);
+        self.data::<Data>().physical_escrows.remove(&escrow_id);
+        if escrow.currency == Currency::Native {
+            self.release_escrowed_funds(escrow.price);
+        }
+
+        let refund = escrow.price.saturating_mul(partial_refund as u128) / 10_000;
+        self.settle_sale(
+            escrow.contract_address,
+            escrow.token_id,
+            escrow.seller,
+            escrow.buyer,
+            escrow.price - refund,
+            escrow.currency,
+            escrow.attribution,
+        )?;
+        if refund > 0 {
+            self.transfer_funds(
+                escrow.currency,
+                escrow.buyer,
+                refund,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
+        Ok(())
+    }
+}
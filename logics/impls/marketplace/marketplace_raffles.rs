@@ -0,0 +1,442 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Raffle, RaffleEntry, RaffleStatus},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::raffles::MarketplaceRaffles,
+};
+use ink::{env::hash::Blake2x256, prelude::vec::Vec};
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Time (in milliseconds) after the raffle deadline the owner's secret has to be revealed in.
+/// Raffles not drawn by then fail and buyers are refunded.
+pub const RAFFLE_REVEAL_PERIOD: u64 = 86_400_000;
+
+/// Most tickets a raffle can sell.
+pub const MAX_RAFFLE_TICKETS: u32 = 10_000;
+
+pub trait Internal {
+    /// Mixes a ticket purchase into the raffle seed.
+    fn mix_raffle_seed(&self, seed: [u8; 32], buyer: AccountId, tickets: u32) -> [u8; 32];
+
+    /// Gets holder of the ticket selected by a random value.
+    fn raffle_winner(
+        &self,
+        raffle_id: u128,
+        raffle: &Raffle,
+        random: [u8; 32],
+    ) -> Option<AccountId>;
+
+    /// Pays ticket proceeds to the raffle owner less the marketplace fee.
+    fn pay_raffle_proceeds(&mut self, raffle: &Raffle) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceRafflesEvents {
+    fn emit_raffle_created_event(
+        &self,
+        raffle_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        owner: AccountId,
+        ticket_price: Balance,
+        max_tickets: u32,
+        deadline: u64,
+    );
+    fn emit_raffle_tickets_bought_event(&self, raffle_id: u128, buyer: AccountId, tickets: u32);
+    fn emit_raffle_drawn_event(&self, raffle_id: u128, winner: AccountId);
+    fn emit_raffle_failed_event(&self, raffle_id: u128);
+    fn emit_raffle_refunded_event(&self, raffle_id: u128, buyer: AccountId, amount: Balance);
+}
+
+impl<T> MarketplaceRaffles for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Raffles caller's NFT.
+    #[modifiers(when_not_taking_custody)]
+    default fn create_raffle(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        ticket_price: Balance,
+        max_tickets: u32,
+        min_tickets: u32,
+        deadline: u64,
+        commitment: [u8; 32],
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_token_allowance(contract_address, &token_id)?;
+        ensure!(
+            max_tickets > 0
+                && max_tickets <= MAX_RAFFLE_TICKETS
+                && min_tickets <= max_tickets
+                && deadline > Self::env().block_timestamp(),
+            MarketplaceError::InvalidRaffle
+        );
+
+        self.take_custody(contract_address, token_id.clone())?;
+        let raffle_id = self.data::<Data>().v1.last_raffle_id + 1;
+        let raffle = Raffle {
+            owner: caller,
+            contract_address,
+            token_id: token_id.clone(),
+            ticket_price,
+            max_tickets,
+            min_tickets,
+            deadline,
+            commitment,
+            seed: [0; 32],
+            tickets_sold: 0,
+            entries: 0,
+            status: RaffleStatus::Open,
+        };
+        self.data::<Data>().raffles.insert(&raffle_id, &raffle);
+        self.data::<Data>().v1.last_raffle_id = raffle_id;
+
+        self.emit_raffle_created_event(
+            raffle_id,
+            contract_address,
+            token_id,
+            caller,
+            ticket_price,
+            max_tickets,
+            deadline,
+        );
+        Ok(())
+    }
+
+    /// Buys raffle tickets.
+    #[modifiers(when_not_taking_custody)]
+    default fn buy_raffle_tickets(
+        &mut self,
+        raffle_id: u128,
+        tickets: u32,
+    ) -> Result<(), MarketplaceError> {
+        let mut raffle = self
+            .data::<Data>()
+            .raffles
+            .get(&raffle_id)
+            .ok_or(MarketplaceError::RaffleNotFound)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        ensure!(caller != raffle.owner, MarketplaceError::AlreadyOwner);
+        ensure!(
+            raffle.status == RaffleStatus::Open,
+            MarketplaceError::RaffleNotOpen
+        );
+        ensure!(
+            Self::env().block_timestamp() <= raffle.deadline,
+            MarketplaceError::RaffleEnded
+        );
+        ensure!(
+            tickets > 0 && raffle.max_tickets - raffle.tickets_sold >= tickets,
+            MarketplaceError::RaffleSoldOut
+        );
+        let expected = raffle
+            .ticket_price
+            .checked_mul(tickets as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == expected,
+            MarketplaceError::BadBuyValue { expected, received }
+        );
+
+        self.escrow_funds(received);
+        self.data::<Data>().raffle_entries.insert(
+            &(raffle_id, raffle.entries),
+            &RaffleEntry {
+                buyer: caller,
+                tickets,
+                first_ticket: raffle.tickets_sold,
+            },
+        );
+        let held = self.get_raffle_tickets(raffle_id, caller);
+        self.data::<Data>()
+            .raffle_tickets
+            .insert(&(raffle_id, caller), &(held + tickets));
+        raffle.seed = self.mix_raffle_seed(raffle.seed, caller, tickets);
+        raffle.tickets_sold += tickets;
+        raffle.entries += 1;
+        self.data::<Data>().raffles.insert(&raffle_id, &raffle);
+
+        self.emit_raffle_tickets_bought_event(raffle_id, caller, tickets);
+        Ok(())
+    }
+
+    /// Draws the winner of a raffle.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn draw_raffle(
+        &mut self,
+        raffle_id: u128,
+        secret: Option<[u8; 32]>,
+    ) -> Result<(), MarketplaceError> {
+        let mut raffle = self
+            .data::<Data>()
+            .raffles
+            .get(&raffle_id)
+            .ok_or(MarketplaceError::RaffleNotFound)?;
+        ensure!(
+            raffle.status == RaffleStatus::Open,
+            MarketplaceError::RaffleNotOpen
+        );
+        let now = Self::env().block_timestamp();
+        ensure!(now > raffle.deadline, MarketplaceError::RaffleNotEnded);
+        ensure!(
+            raffle.tickets_sold > 0 && raffle.tickets_sold >= raffle.min_tickets,
+            MarketplaceError::RaffleMinimumNotMet
+        );
+
+        // a raffle not drawn in time fails, see `end_failed_raffle`
+        ensure!(
+            now <= raffle.deadline.saturating_add(RAFFLE_REVEAL_PERIOD),
+            MarketplaceError::RaffleRevealExpired
+        );
+        let secret = secret.ok_or(MarketplaceError::RaffleRevealPending)?;
+        ensure!(
+            Self::env().hash_bytes::<Blake2x256>(&secret) == raffle.commitment,
+            MarketplaceError::InvalidRaffleSecret
+        );
+        let random = Self::env().hash_encoded::<Blake2x256, _>(&(secret, raffle.seed));
+        let winner = self
+            .raffle_winner(raffle_id, &raffle, random)
+            .ok_or(MarketplaceError::RaffleMinimumNotMet)?;
+
+        raffle.status = RaffleStatus::Drawn;
+        self.data::<Data>().raffles.insert(&raffle_id, &raffle);
+        self.release_custody(raffle.contract_address, winner, raffle.token_id.clone())?;
+        self.pay_raffle_proceeds(&raffle)?;
+
+        self.emit_raffle_drawn_event(raffle_id, winner);
+        Ok(())
+    }
+
+    /// Returns NFT of a failed raffle to its owner.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn end_failed_raffle(&mut self, raffle_id: u128) -> Result<(), MarketplaceError> {
+        let mut raffle = self
+            .data::<Data>()
+            .raffles
+            .get(&raffle_id)
+            .ok_or(MarketplaceError::RaffleNotFound)?;
+        ensure!(
+            raffle.status == RaffleStatus::Open,
+            MarketplaceError::RaffleNotOpen
+        );
+        let now = Self::env().block_timestamp();
+        ensure!(now > raffle.deadline, MarketplaceError::RaffleNotEnded);
+        // the owner not revealing the secret in time forfeits the ticket proceeds
+        ensure!(
+            raffle.tickets_sold == 0
+                || raffle.tickets_sold < raffle.min_tickets
+                || now > raffle.deadline.saturating_add(RAFFLE_REVEAL_PERIOD),
+            MarketplaceError::RaffleMinimumMet
+        );
+
+        raffle.status = RaffleStatus::Failed;
+        self.data::<Data>().raffles.insert(&raffle_id, &raffle);
+        self.release_custody(raffle.contract_address, raffle.owner, raffle.token_id)?;
+
+        self.emit_raffle_failed_event(raffle_id);
+        Ok(())
+    }
+
+    /// Refunds caller's tickets of a failed raffle.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_raffle_refund(&mut self, raffle_id: u128) -> Result<(), MarketplaceError> {
+        let raffle = self
+            .data::<Data>()
+            .raffles
+            .get(&raffle_id)
+            .ok_or(MarketplaceError::RaffleNotFound)?;
+        ensure!(
+            raffle.status == RaffleStatus::Failed,
+            MarketplaceError::RaffleNotFailed
+        );
+        let caller = Self::env().caller();
+        let tickets = self.get_raffle_tickets(raffle_id, caller);
+        ensure!(tickets > 0, MarketplaceError::NoRaffleTickets);
+
+        self.data::<Data>()
+            .raffle_tickets
+            .remove(&(raffle_id, caller));
+        let amount = raffle.ticket_price.saturating_mul(tickets as u128);
+        self.release_escrowed_funds(amount);
+        self.transfer_funds(
+            Currency::Native,
+            caller,
+            amount,
+            MarketplaceError::RefundFailed,
+        )?;
+
+        self.emit_raffle_refunded_event(raffle_id, caller, amount);
+        Ok(())
+    }
+
+    /// Gets raffle.
+    default fn get_raffle(&self, raffle_id: u128) -> Option<Raffle> {
+        self.data::<Data>().raffles.get(&raffle_id)
+    }
+
+    /// Gets ticket purchases of a raffle.
+    default fn get_raffle_entries(
+        &self,
+        raffle_id: u128,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(AccountId, u32)> {
+        (from..self
+            .get_raffle(raffle_id)
+            .map_or(0, |raffle| raffle.entries))
+            .take(limit.min(self.data::<Data>().v1.max_batch_size) as usize)
+            .filter_map(|index| self.data::<Data>().raffle_entries.get(&(raffle_id, index)))
+            .map(|entry| (entry.buyer, entry.tickets))
+            .collect()
+    }
+
+    /// Gets tickets of a raffle held by an account and not refunded.
+    default fn get_raffle_tickets(&self, raffle_id: u128, account: AccountId) -> u32 {
+        self.data::<Data>()
+            .raffle_tickets
+            .get(&(raffle_id, account))
+            .unwrap_or(0)
+    }
+}
+
+impl<T> MarketplaceRafflesEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_raffle_created_event(
+        &self,
+        _raffle_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _owner: AccountId,
+        _ticket_price: Balance,
+        _max_tickets: u32,
+        _deadline: u64,
+    ) {
+    }
+
+    default fn emit_raffle_tickets_bought_event(
+        &self,
+        _raffle_id: u128,
+        _buyer: AccountId,
+        _tickets: u32,
+    ) {
+    }
+
+    default fn emit_raffle_drawn_event(&self, _raffle_id: u128, _winner: AccountId) {}
+
+    default fn emit_raffle_failed_event(&self, _raffle_id: u128) {}
+
+    default fn emit_raffle_refunded_event(
+        &self,
+        _raffle_id: u128,
+        _buyer: AccountId,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn mix_raffle_seed(&self, seed: [u8; 32], buyer: AccountId, tickets: u32) -> [u8; 32] {
+        Self::env().hash_encoded::<Blake2x256, _>(&(
+            seed,
+            buyer,
+            tickets,
+            Self::env().block_number(),
+            Self::env().block_timestamp(),
+        ))
+    }
+
+    default fn raffle_winner(
+        &self,
+        raffle_id: u128,
+        raffle: &Raffle,
+        random: [u8; 32],
+    ) -> Option<AccountId> {
+        if raffle.tickets_sold == 0 {
+            return None
+        }
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&random[..16]);
+        let ticket = (u128::from_le_bytes(bytes) % raffle.tickets_sold as u128) as u32;
+
+        // binary search for the last purchase starting at or before the ticket
+        let entries = &self.data::<Data>().raffle_entries;
+        let (mut low, mut high) = (0, raffle.entries);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            let entry = entries.get(&(raffle_id, middle))?;
+            if entry.first_ticket <= ticket {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        entries
+            .get(&(raffle_id, low))
+            .filter(|entry| ticket - entry.first_ticket < entry.tickets)
+            .map(|entry| entry.buyer)
+    }
+
+    default fn pay_raffle_proceeds(&mut self, raffle: &Raffle) -> Result<(), MarketplaceError> {
+        let proceeds = raffle
+            .ticket_price
+            .saturating_mul(raffle.tickets_sold as u128);
+        let marketplace_fee = proceeds
+            .checked_mul(self.data::<Data>().fee as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+        self.release_escrowed_funds(proceeds);
+        self.transfer_funds(
+            Currency::Native,
+            self.fee_recipient()?,
+            marketplace_fee,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+        self.transfer_funds(
+            Currency::Native,
+            raffle.owner,
+            proceeds - marketplace_fee,
+            MarketplaceError::TransferToOwnerFailed,
+        )
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_escrow::Internal as EscrowInternal,
+    marketplace_sale::Internal as SaleInternal,
+    marketplace_trade::Internal as TradeInternal,
+    types::{Currency, HeldAsset, PendingRecovery},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::recovery::MarketplaceRecovery,
+};
+use openbrush::{
+    contracts::{ownable::*, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::Storage,
+};
+
+pub trait Internal {
+    /// Checks that the held asset exists.
+    fn check_held_asset(&self, asset: &HeldAsset) -> Result<(), MarketplaceError>;
+
+    /// Returns held NFT to the seller and held funds to the buyer, or the maker for trades.
+    fn recover_asset(&mut self, asset: HeldAsset) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceRecoveryEvents {
+    fn emit_recovery_queued_event(&self, recovery_id: u128, asset: HeldAsset, eta: u64);
+    fn emit_recovery_executed_event(&self, recovery_id: u128, asset: HeldAsset);
+    fn emit_recovery_cancelled_event(&self, recovery_id: u128);
+}
+
+impl<T> MarketplaceRecovery for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Queues return of a held asset to its owners.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn queue_recovery(&mut self, asset: HeldAsset) -> Result<u128, MarketplaceError> {
+        self.check_held_asset(&asset)?;
+
+        let recovery_id = self.data::<Data>().v1.last_recovery_id + 1;
+        let eta = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().timelock_delay.get().unwrap_or_default());
+        self.data::<Data>()
+            .recoveries
+            .insert(&recovery_id, &PendingRecovery { asset, eta });
+        self.data::<Data>().v1.last_recovery_id = recovery_id;
+
+        self.emit_recovery_queued_event(recovery_id, asset, eta);
+        Ok(recovery_id)
+    }
+
+    /// Returns held NFTs to sellers and held funds to buyers.
+    #[modifiers(when_not_taking_custody, only_owner, non_reentrant)]
+    default fn execute_recovery(&mut self, recovery_id: u128) -> Result<(), MarketplaceError> {
+        let pending = self
+            .data::<Data>()
+            .recoveries
+            .get(&recovery_id)
+            .ok_or(MarketplaceError::RecoveryNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() >= pending.eta,
+            MarketplaceError::TimelockNotExpired
+        );
+
+        self.data::<Data>().recoveries.remove(&recovery_id);
+        self.recover_asset(pending.asset)?;
+        self.emit_recovery_executed_event(recovery_id, pending.asset);
+        Ok(())
+    }
+
+    /// Cancels a queued recovery.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn cancel_recovery(&mut self, recovery_id: u128) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().recoveries.contains(&recovery_id),
+            MarketplaceError::RecoveryNotFound
+        );
+
+        self.data::<Data>().recoveries.remove(&recovery_id);
+        self.emit_recovery_cancelled_event(recovery_id);
+        Ok(())
+    }
+
+    /// Gets a queued recovery.
+    default fn get_recovery(&self, recovery_id: u128) -> Option<PendingRecovery> {
+        self.data::<Data>().recoveries.get(&recovery_id)
+    }
+}
+
+impl<T> MarketplaceRecoveryEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_recovery_queued_event(&self, _recovery_id: u128, _asset: HeldAsset, _eta: u64) {
+    }
+
+    default fn emit_recovery_executed_event(&self, _recovery_id: u128, _asset: HeldAsset) {}
+
+    default fn emit_recovery_cancelled_event(&self, _recovery_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_held_asset(&self, asset: &HeldAsset) -> Result<(), MarketplaceError> {
+        match asset {
+            HeldAsset::Escrow(escrow_id) => {
+                ensure!(
+                    self.data::<Data>().escrows.contains(escrow_id),
+                    MarketplaceError::EscrowNotFound
+                );
+            }
+            HeldAsset::InstallmentPlan(plan_id) => {
+                ensure!(
+                    self.data::<Data>().installment_plans.contains(plan_id),
+                    MarketplaceError::InstallmentPlanNotFound
+                );
+            }
+            HeldAsset::Trade(trade_id) => {
+                ensure!(
+                    self.data::<Data>().trades.contains(trade_id),
+                    MarketplaceError::TradeNotFound
+                );
+            }
+        }
+        Ok(())
+    }
+
+    default fn recover_asset(&mut self, asset: HeldAsset) -> Result<(), MarketplaceError> {
+        match asset {
+            HeldAsset::Escrow(escrow_id) => {
+                let escrow = self
+                    .data::<Data>()
+                    .escrows
+                    .get(&escrow_id)
+                    .ok_or(MarketplaceError::EscrowNotFound)?;
+                self.close_escrow(escrow_id, escrow, true)
+            }
+            HeldAsset::InstallmentPlan(plan_id) => {
+                let plan = self
+                    .data::<Data>()
+                    .installment_plans
+                    .get(&plan_id)
+                    .ok_or(MarketplaceError::InstallmentPlanNotFound)?;
+                self.data::<Data>().installment_plans.remove(&plan_id);
+                self.unlock_funds(plan.paid);
+
+                self.release_custody(plan.contract_address, plan.seller, plan.token_id)?;
+                self.transfer_funds(
+                    Currency::Native,
+                    plan.buyer,
+                    plan.paid,
+                    MarketplaceError::RefundFailed,
+                )
+            }
+            HeldAsset::Trade(trade_id) => {
+                let trade = self
+                    .data::<Data>()
+                    .trades
+                    .get(&trade_id)
+                    .ok_or(MarketplaceError::TradeNotFound)?;
+                self.data::<Data>().trades.remove(&trade_id);
+
+                let amount = self.native_amount(&trade.offered);
+                self.unlock_funds(amount);
+                self.transfer_funds(
+                    Currency::Native,
+                    trade.maker,
+                    amount,
+                    MarketplaceError::RefundFailed,
+                )
+            }
+        }
+    }
+}
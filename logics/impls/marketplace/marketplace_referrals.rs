@@ -0,0 +1,264 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Referral codes.
+//!
+//! Accounts register a short code that buyers pass to `buy_with_referral` instead of the
+//! referrer address. Referred sales pay the referrer a share of the marketplace fee left after
+//! the partner share. The referrer is passed to `settle_sale` in the `SaleAttribution` of the
+//! purchase, which escrowed purchases keep until they are settled. Earnings accrue in the
+//! marketplace and are claimed per currency.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, SaleAttribution, MAX_REFERRAL_CODE_LEN},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::referrals::MarketplaceReferrals,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::Id, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage, String},
+};
+
+pub trait Internal {
+    /// Accrues referrer's share of the marketplace fee. Returns the share.
+    fn accrue_referral_fee(
+        &mut self,
+        referrer: AccountId,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance;
+
+    /// Pays `amount` of referral earnings of an account in a currency.
+    fn pay_referral_earnings(
+        &mut self,
+        referrer: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceReferralsEvents {
+    fn emit_referral_code_registered_event(&self, account: AccountId, code: String);
+    fn emit_referral_fee_accrued_event(
+        &self,
+        referrer: AccountId,
+        currency: Currency,
+        amount: Balance,
+    );
+    fn emit_referral_earnings_claimed_event(
+        &self,
+        referrer: AccountId,
+        currency: Currency,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceReferrals for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Registers a referral code for the caller.
+    #[modifiers(when_not_taking_custody)]
+    default fn register_referral_code(&mut self, code: String) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            !code.is_empty()
+                && code.len() <= MAX_REFERRAL_CODE_LEN
+                && code.iter().all(u8::is_ascii_alphanumeric),
+            MarketplaceError::InvalidReferralCode
+        );
+        ensure!(
+            !self.data::<Data>().referral_codes.contains(&code),
+            MarketplaceError::ReferralCodeTaken
+        );
+
+        if let Some(old_code) = self.data::<Data>().account_referral_codes.get(&caller) {
+            self.data::<Data>().referral_codes.remove(&old_code);
+        }
+        self.data::<Data>().referral_codes.insert(&code, &caller);
+        self.data::<Data>()
+            .account_referral_codes
+            .insert(&caller, &code);
+
+        self.emit_referral_code_registered_event(caller, code);
+        Ok(())
+    }
+
+    /// Gets account a referral code is registered to.
+    default fn get_referral_code_owner(&self, code: String) -> Option<AccountId> {
+        self.data::<Data>().referral_codes.get(&code)
+    }
+
+    /// Gets referral code of an account.
+    default fn get_referral_code(&self, account: AccountId) -> Option<String> {
+        self.data::<Data>().account_referral_codes.get(&account)
+    }
+
+    /// Sets share of the marketplace fee paid to referrers.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_referral_fee_share(&mut self, fee_share: u16) -> Result<(), MarketplaceError> {
+        self.check_fee(fee_share, 10_000)?;
+        self.data::<Data>().v1.referral_fee_share = fee_share;
+        Ok(())
+    }
+
+    /// Gets share of the marketplace fee paid to referrers.
+    default fn get_referral_fee_share(&self) -> u16 {
+        self.data::<Data>().v1.referral_fee_share
+    }
+
+    /// Buys NFT item referred by the owner of a referral code.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_referral(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        code: String,
+    ) -> Result<(), MarketplaceError> {
+        let referrer = self
+            .data::<Data>()
+            .referral_codes
+            .get(&code)
+            .ok_or(MarketplaceError::ReferralCodeNotFound)?;
+        ensure!(
+            referrer != Self::env().caller(),
+            MarketplaceError::SelfReferral
+        );
+
+        self.buy_item(
+            contract_address,
+            token_id,
+            SaleAttribution {
+                referrer: Some(referrer),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Pays referral earnings of the caller in a currency.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_referral_earnings(
+        &mut self,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let amount = self.get_referral_earnings(caller, currency);
+        ensure!(amount > 0, MarketplaceError::NoReferralEarnings);
+
+        self.pay_referral_earnings(caller, currency, amount)
+    }
+
+    /// Gets unclaimed referral earnings of an account in a currency.
+    default fn get_referral_earnings(&self, account: AccountId, currency: Currency) -> Balance {
+        self.data::<Data>()
+            .referral_earnings
+            .get(&(account, currency))
+            .unwrap_or_default()
+    }
+}
+
+impl<T> MarketplaceReferralsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_referral_code_registered_event(&self, _account: AccountId, _code: String) {}
+
+    default fn emit_referral_fee_accrued_event(
+        &self,
+        _referrer: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_referral_earnings_claimed_event(
+        &self,
+        _referrer: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn accrue_referral_fee(
+        &mut self,
+        referrer: AccountId,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance {
+        let fee_share = self.data::<Data>().v1.referral_fee_share;
+        let amount = marketplace_fee.saturating_mul(fee_share as u128) / 10_000;
+        if amount == 0 {
+            return 0
+        }
+
+        let earnings = self
+            .data::<Data>()
+            .referral_earnings
+            .get(&(referrer, currency))
+            .unwrap_or_default();
+        self.data::<Data>()
+            .referral_earnings
+            .insert(&(referrer, currency), &earnings.saturating_add(amount));
+        if currency == Currency::Native {
+            self.accrue_fees(amount);
+        }
+        self.emit_referral_fee_accrued_event(referrer, currency, amount);
+        amount
+    }
+
+    default fn pay_referral_earnings(
+        &mut self,
+        referrer: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>()
+            .referral_earnings
+            .remove(&(referrer, currency));
+        if currency == Currency::Native {
+            self.pay_accrued_fees(amount);
+        }
+        self.transfer_funds(
+            currency,
+            referrer,
+            amount,
+            MarketplaceError::TransferToReferrerFailed,
+        )?;
+
+        self.emit_referral_earnings_claimed_event(referrer, currency, amount);
+        Ok(())
+    }
+}
@@ -0,0 +1,854 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    contract_calls,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_disputes::Internal as DisputesInternal,
+    marketplace_lending::Internal as LendingInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, Rental, RentalListing},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::rentals::MarketplaceRentals,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Length of a rental day in milliseconds.
+pub const DAY: u64 = 86_400_000;
+
+pub trait Internal {
+    /// Lists NFT of the caller for rent.
+    fn list_rental(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        listing: RentalListing,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets rent for a number of days or fails if the owner does not allow such a term.
+    fn rental_price(
+        &self,
+        daily_price: Balance,
+        max_days: u32,
+        days: u32,
+    ) -> Result<Balance, MarketplaceError>;
+
+    /// Pays rent to the owner except for the refundable share held until the rental is
+    /// closed. Returns the held amount.
+    fn pay_rent(
+        &mut self,
+        owner: AccountId,
+        rent: Balance,
+        early_return_refund: u16,
+    ) -> Result<Balance, MarketplaceError>;
+
+    /// Caps rent at the part of the purchase price not paid yet.
+    fn rent_due(&self, rent: Balance, purchase_price: Option<Balance>, paid: Balance) -> Balance;
+
+    /// Stores rental or, once paid rent reaches the purchase price, transfers the NFT to the
+    /// renter and closes the rental.
+    fn update_rental(&mut self, rental_id: u128, rental: Rental) -> Result<(), MarketplaceError>;
+
+    /// Removes closed rental together with its entries in the rental indexes.
+    fn remove_rental(&mut self, rental_id: u128, rental: &Rental);
+
+    /// Adds rental to or removes it from the open rentals of an account.
+    fn update_account_rentals(&mut self, account: AccountId, rental_id: u128, open: bool);
+
+    /// Gets refund of rent for whole days left in the rental term.
+    fn rental_refund(&self, rental: &Rental) -> Balance;
+
+    /// Pays rent streamed since the last settlement from the renter's deposit to the owner's
+    /// deposit. Returns false if the renter's deposit did not cover all of it.
+    fn settle_stream(
+        &mut self,
+        rental_id: u128,
+        rental: &mut Rental,
+    ) -> Result<bool, MarketplaceError>;
+
+    /// Pays held rent out to the renter (refund) and the owner (the rest). Returns the refund.
+    fn settle_held_rent(&mut self, rental: &Rental) -> Result<Balance, MarketplaceError>;
+
+    /// Returns rented NFT to its owner and refunds collateral and unused rent to the renter.
+    /// Returns the rent refund.
+    fn close_rental(
+        &mut self,
+        rental_id: u128,
+        rental: Rental,
+    ) -> Result<Balance, MarketplaceError>;
+
+    /// Pays collateral and held rent of a rental to the owner. A NFT held by the marketplace
+    /// is returned to the owner.
+    fn forfeit_collateral(
+        &mut self,
+        rental_id: u128,
+        rental: Rental,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceRentalsEvents {
+    fn emit_rental_listed_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        owner: AccountId,
+        daily_price: Balance,
+        collateral: Balance,
+        custodial: bool,
+        purchase_price: Option<Balance>,
+    );
+    fn emit_rental_listing_cancelled_event(&self, contract: AccountId, token_id: Id);
+    fn emit_rental_started_event(
+        &self,
+        rental_id: u128,
+        contract: AccountId,
+        token_id: Id,
+        renter: AccountId,
+        end_time: u64,
+    );
+    fn emit_rental_extended_event(&self, rental_id: u128, end_time: u64);
+    fn emit_rental_ended_event(&self, rental_id: u128, refund: Balance);
+    fn emit_rent_to_own_completed_event(&self, rental_id: u128, renter: AccountId);
+    fn emit_rental_defaulted_event(&self, rental_id: u128);
+    fn emit_rent_stream_settled_event(&self, rental_id: u128, amount: Balance, settled_until: u64);
+}
+
+impl<T> MarketplaceRentals for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Lists NFT for rent.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_for_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        collateral: Balance,
+        max_days: u32,
+        early_return_refund: u16,
+    ) -> Result<(), MarketplaceError> {
+        self.list_rental(
+            contract_address,
+            token_id,
+            RentalListing {
+                owner: Self::env().caller(),
+                daily_price,
+                collateral,
+                max_days,
+                early_return_refund,
+                custodial: false,
+                purchase_price: None,
+                streamed: false,
+            },
+        )
+    }
+
+    /// Lists NFT for rent without collateral.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_for_custodial_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        max_days: u32,
+        early_return_refund: u16,
+    ) -> Result<(), MarketplaceError> {
+        self.list_rental(
+            contract_address,
+            token_id,
+            RentalListing {
+                owner: Self::env().caller(),
+                daily_price,
+                collateral: 0,
+                max_days,
+                early_return_refund,
+                custodial: true,
+                purchase_price: None,
+                streamed: false,
+            },
+        )
+    }
+
+    /// Lists NFT for rent to own.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_for_rent_to_own(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        max_days: u32,
+        purchase_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.list_rental(
+            contract_address,
+            token_id,
+            RentalListing {
+                owner: Self::env().caller(),
+                daily_price,
+                collateral: 0,
+                max_days,
+                early_return_refund: 0,
+                custodial: true,
+                purchase_price: Some(purchase_price),
+                streamed: false,
+            },
+        )
+    }
+
+    /// Lists NFT for rent streamed from the renter's deposit.
+    #[modifiers(when_not_taking_custody)]
+    default fn list_for_streamed_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.list_rental(
+            contract_address,
+            token_id,
+            RentalListing {
+                owner: Self::env().caller(),
+                daily_price,
+                collateral: 0,
+                max_days: 0,
+                early_return_refund: 0,
+                custodial: true,
+                purchase_price: None,
+                streamed: true,
+            },
+        )
+    }
+
+    /// Removes NFT from rental listings.
+    #[modifiers(when_not_taking_custody)]
+    default fn cancel_rental_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let key = (contract_address, token_key(&token_id));
+        let listing = self
+            .data::<Data>()
+            .rental_listings
+            .get(&key)
+            .ok_or(MarketplaceError::RentalNotListed)?;
+        ensure!(
+            Self::env().caller() == listing.owner,
+            MarketplaceError::NotOwner
+        );
+
+        self.data::<Data>().rental_listings.remove(&key);
+        self.emit_rental_listing_cancelled_event(contract_address, token_id);
+        Ok(())
+    }
+
+    /// Rents NFT for a number of days.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        days: u32,
+    ) -> Result<(), MarketplaceError> {
+        let key = (contract_address, token_key(&token_id));
+        let listing = self
+            .data::<Data>()
+            .rental_listings
+            .get(&key)
+            .ok_or(MarketplaceError::RentalNotListed)?;
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(listing.owner)?;
+        ensure!(caller != listing.owner, MarketplaceError::AlreadyOwner);
+        ensure!(
+            contract_calls::owner_of(contract_address, &token_id) == Some(listing.owner),
+            MarketplaceError::NotOwner
+        );
+
+        let rent = if listing.streamed {
+            0
+        } else {
+            let rent = self.rental_price(listing.daily_price, listing.max_days, days)?;
+            self.rent_due(rent, listing.purchase_price, 0)
+        };
+        let expected = rent
+            .checked_add(listing.collateral)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == expected,
+            MarketplaceError::BadBuyValue { expected, received }
+        );
+
+        let holder = if listing.custodial {
+            Self::env().account_id()
+        } else {
+            caller
+        };
+        contract_calls::transfer(contract_address, holder, token_id.clone())?;
+        self.data::<Data>().rental_listings.remove(&key);
+        // rented token can't be sold by the owner while it is rented out
+        self.remove_item(contract_address, &token_id);
+        let held_rent = self.pay_rent(listing.owner, rent, listing.early_return_refund)?;
+        self.lock_funds(listing.collateral);
+
+        let now = Self::env().block_timestamp();
+        let end_time = if listing.streamed {
+            u64::MAX
+        } else {
+            now.saturating_add((days as u64).saturating_mul(DAY))
+        };
+        let rental_id = self.data::<Data>().v1.last_rental_id + 1;
+        let rental = Rental {
+            contract_address,
+            token_id: token_id.clone(),
+            owner: listing.owner,
+            renter: caller,
+            daily_price: listing.daily_price,
+            max_days: listing.max_days,
+            collateral: listing.collateral,
+            early_return_refund: listing.early_return_refund,
+            held_rent,
+            end_time,
+            custodial: listing.custodial,
+            purchase_price: listing.purchase_price,
+            paid: rent,
+            settled_until: listing.streamed.then_some(now),
+        };
+        self.data::<Data>().rented_tokens.insert(&key, &rental_id);
+        self.update_account_rentals(caller, rental_id, true);
+        self.update_account_rentals(listing.owner, rental_id, true);
+        self.data::<Data>().v1.last_rental_id = rental_id;
+
+        self.emit_rental_started_event(rental_id, contract_address, token_id, caller, end_time);
+        self.update_rental(rental_id, rental)
+    }
+
+    /// Extends an active rental by a number of days.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn extend_rental(
+        &mut self,
+        rental_id: u128,
+        days: u32,
+    ) -> Result<(), MarketplaceError> {
+        let mut rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(
+            Self::env().caller() == rental.renter,
+            MarketplaceError::NotRenter
+        );
+        ensure!(
+            rental.settled_until.is_none(),
+            MarketplaceError::StreamedRental
+        );
+        ensure!(
+            Self::env().block_timestamp() <= rental.end_time,
+            MarketplaceError::RentalEnded
+        );
+
+        let rent = self.rental_price(rental.daily_price, rental.max_days, days)?;
+        let rent = self.rent_due(rent, rental.purchase_price, rental.paid);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == rent,
+            MarketplaceError::BadBuyValue {
+                expected: rent,
+                received,
+            }
+        );
+
+        let held_rent = self.pay_rent(rental.owner, rent, rental.early_return_refund)?;
+        rental.held_rent = rental.held_rent.saturating_add(held_rent);
+        rental.paid = rental.paid.saturating_add(rent);
+        rental.end_time = rental
+            .end_time
+            .saturating_add((days as u64).saturating_mul(DAY));
+
+        self.emit_rental_extended_event(rental_id, rental.end_time);
+        self.update_rental(rental_id, rental)
+    }
+
+    /// Returns rented NFT to its owner and refunds collateral and unused rent to the renter.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn return_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError> {
+        let mut rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(
+            Self::env().caller() == rental.renter,
+            MarketplaceError::NotRenter
+        );
+        self.check_not_disputed(rental.contract_address, &rental.token_id)?;
+        if !rental.custodial {
+            self.check_token_allowance(rental.contract_address, &rental.token_id)?;
+        }
+        if rental.settled_until.is_some() {
+            ensure!(
+                self.settle_stream(rental_id, &mut rental)?,
+                MarketplaceError::BalanceInsufficient
+            );
+        }
+
+        let refund = self.close_rental(rental_id, rental)?;
+        self.emit_rental_ended_event(rental_id, refund);
+        Ok(())
+    }
+
+    /// Returns NFT held by the marketplace to its owner once the rental term has ended.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn end_custodial_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError> {
+        let rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(rental.custodial, MarketplaceError::NotCustodialRental);
+        ensure!(
+            Self::env().block_timestamp() > rental.end_time,
+            MarketplaceError::RentalNotEnded
+        );
+        self.check_not_disputed(rental.contract_address, &rental.token_id)?;
+
+        let refund = self.close_rental(rental_id, rental)?;
+        self.emit_rental_ended_event(rental_id, refund);
+        Ok(())
+    }
+
+    /// Pays rent accrued since the last settlement of a streamed rental.
+    #[modifiers(when_not_taking_custody)]
+    default fn settle_rent_stream(&mut self, rental_id: u128) -> Result<(), MarketplaceError> {
+        let mut rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(
+            rental.settled_until.is_some(),
+            MarketplaceError::NotStreamedRental
+        );
+
+        self.settle_stream(rental_id, &mut rental)?;
+        self.data::<Data>().rentals.insert(&rental_id, &rental);
+        Ok(())
+    }
+
+    /// Returns NFT of a streamed rental the renter no longer pays for to its owner.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn terminate_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError> {
+        let mut rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(
+            rental.settled_until.is_some(),
+            MarketplaceError::NotStreamedRental
+        );
+        ensure!(
+            Self::env().caller() == rental.owner,
+            MarketplaceError::NotOwner
+        );
+        self.check_not_disputed(rental.contract_address, &rental.token_id)?;
+        ensure!(
+            !self.settle_stream(rental_id, &mut rental)?,
+            MarketplaceError::RentStreamFunded
+        );
+
+        self.close_rental(rental_id, rental)?;
+        self.emit_rental_defaulted_event(rental_id);
+        Ok(())
+    }
+
+    /// Pays collateral of a rental that was not returned by the end of the term to the owner.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_rental_collateral(&mut self, rental_id: u128) -> Result<(), MarketplaceError> {
+        let rental = self
+            .data::<Data>()
+            .rentals
+            .get(&rental_id)
+            .ok_or(MarketplaceError::RentalNotFound)?;
+        ensure!(!rental.custodial, MarketplaceError::CustodialRental);
+        ensure!(
+            Self::env().caller() == rental.owner,
+            MarketplaceError::NotOwner
+        );
+        ensure!(
+            Self::env().block_timestamp() > rental.end_time,
+            MarketplaceError::RentalNotEnded
+        );
+        self.check_not_disputed(rental.contract_address, &rental.token_id)?;
+
+        self.forfeit_collateral(rental_id, rental)?;
+        self.emit_rental_defaulted_event(rental_id);
+        Ok(())
+    }
+
+    /// Gets rental listing of NFT.
+    default fn get_rental_listing(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<RentalListing> {
+        self.data::<Data>()
+            .rental_listings
+            .get(&(contract_address, token_key(&token_id)))
+    }
+
+    /// Gets rental.
+    default fn get_rental(&self, rental_id: u128) -> Option<Rental> {
+        self.data::<Data>().rentals.get(&rental_id)
+    }
+
+    /// Gets open rentals an account rents or rents out.
+    default fn get_active_rentals_by_account(
+        &self,
+        account: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(u128, Rental)> {
+        let rentals = &self.data::<Data>().account_rental_index;
+        (from as u128..rentals.count(&account))
+            .take(limit as usize)
+            .filter_map(|index| rentals.get_value(&account, &index))
+            .filter_map(|rental_id| {
+                self.data::<Data>()
+                    .rentals
+                    .get(&rental_id)
+                    .map(|rental| (rental_id, rental))
+            })
+            .collect()
+    }
+
+    /// Gets renter allowed to use NFT until the end of the rental term.
+    default fn user_of(&self, contract_address: AccountId, token_id: Id) -> Option<AccountId> {
+        self.data::<Data>()
+            .rented_tokens
+            .get(&(contract_address, token_key(&token_id)))
+            .and_then(|rental_id| self.data::<Data>().rentals.get(&rental_id))
+            .filter(|rental| Self::env().block_timestamp() <= rental.end_time)
+            .map(|rental| rental.renter)
+    }
+}
+
+impl<T> MarketplaceRentalsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_rental_listed_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _owner: AccountId,
+        _daily_price: Balance,
+        _collateral: Balance,
+        _custodial: bool,
+        _purchase_price: Option<Balance>,
+    ) {
+    }
+
+    default fn emit_rental_listing_cancelled_event(&self, _contract: AccountId, _token_id: Id) {}
+
+    default fn emit_rental_started_event(
+        &self,
+        _rental_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _renter: AccountId,
+        _end_time: u64,
+    ) {
+    }
+
+    default fn emit_rental_extended_event(&self, _rental_id: u128, _end_time: u64) {}
+
+    default fn emit_rental_ended_event(&self, _rental_id: u128, _refund: Balance) {}
+
+    default fn emit_rent_to_own_completed_event(&self, _rental_id: u128, _renter: AccountId) {}
+
+    default fn emit_rental_defaulted_event(&self, _rental_id: u128) {}
+
+    default fn emit_rent_stream_settled_event(
+        &self,
+        _rental_id: u128,
+        _amount: Balance,
+        _settled_until: u64,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn list_rental(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        listing: RentalListing,
+    ) -> Result<(), MarketplaceError> {
+        self.check_not_blacklisted(listing.owner)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_not_disputed(contract_address, &token_id)?;
+        self.check_token_allowance(contract_address, &token_id)?;
+        self.check_fee(listing.early_return_refund, 10_000)?;
+        ensure!(
+            listing.streamed || listing.max_days > 0,
+            MarketplaceError::InvalidRentalDuration
+        );
+
+        self.data::<Data>()
+            .rental_listings
+            .insert(&(contract_address, token_key(&token_id)), &listing);
+
+        self.emit_rental_listed_event(
+            contract_address,
+            token_id,
+            listing.owner,
+            listing.daily_price,
+            listing.collateral,
+            listing.custodial,
+            listing.purchase_price,
+        );
+        Ok(())
+    }
+
+    default fn rental_price(
+        &self,
+        daily_price: Balance,
+        max_days: u32,
+        days: u32,
+    ) -> Result<Balance, MarketplaceError> {
+        ensure!(
+            days > 0 && days <= max_days,
+            MarketplaceError::InvalidRentalDuration
+        );
+        daily_price
+            .checked_mul(days as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)
+    }
+
+    default fn pay_rent(
+        &mut self,
+        owner: AccountId,
+        rent: Balance,
+        early_return_refund: u16,
+    ) -> Result<Balance, MarketplaceError> {
+        let held_rent = rent
+            .checked_mul(early_return_refund as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+        self.escrow_funds(held_rent);
+        self.transfer_funds(
+            Currency::Native,
+            owner,
+            rent - held_rent,
+            MarketplaceError::TransferToOwnerFailed,
+        )?;
+        Ok(held_rent)
+    }
+
+    default fn rent_due(
+        &self,
+        rent: Balance,
+        purchase_price: Option<Balance>,
+        paid: Balance,
+    ) -> Balance {
+        match purchase_price {
+            Some(purchase_price) => rent.min(purchase_price.saturating_sub(paid)),
+            None => rent,
+        }
+    }
+
+    default fn update_rental(
+        &mut self,
+        rental_id: u128,
+        rental: Rental,
+    ) -> Result<(), MarketplaceError> {
+        if !rental
+            .purchase_price
+            .map_or(false, |purchase_price| rental.paid >= purchase_price)
+        {
+            self.data::<Data>().rentals.insert(&rental_id, &rental);
+            return Ok(())
+        }
+
+        self.remove_rental(rental_id, &rental);
+        self.release_custody(rental.contract_address, rental.renter, rental.token_id)?;
+
+        self.emit_rent_to_own_completed_event(rental_id, rental.renter);
+        Ok(())
+    }
+
+    default fn remove_rental(&mut self, rental_id: u128, rental: &Rental) {
+        self.data::<Data>().rentals.remove(&rental_id);
+        self.data::<Data>()
+            .rented_tokens
+            .remove(&(rental.contract_address, token_key(&rental.token_id)));
+        self.update_account_rentals(rental.renter, rental_id, false);
+        self.update_account_rentals(rental.owner, rental_id, false);
+    }
+
+    default fn update_account_rentals(&mut self, account: AccountId, rental_id: u128, open: bool) {
+        if open {
+            self.data::<Data>()
+                .account_rental_index
+                .insert(&account, &rental_id);
+        } else {
+            self.data::<Data>()
+                .account_rental_index
+                .remove_value(&account, &rental_id);
+        }
+    }
+
+    default fn rental_refund(&self, rental: &Rental) -> Balance {
+        let unused_days = rental
+            .end_time
+            .saturating_sub(Self::env().block_timestamp())
+            / DAY;
+        let refund = rental
+            .daily_price
+            .saturating_mul(unused_days as u128)
+            .saturating_mul(rental.early_return_refund as u128)
+            / 10_000;
+        refund.min(rental.held_rent)
+    }
+
+    default fn settle_stream(
+        &mut self,
+        rental_id: u128,
+        rental: &mut Rental,
+    ) -> Result<bool, MarketplaceError> {
+        let settled_until = rental.settled_until.unwrap_or_default();
+        let now = Self::env().block_timestamp();
+        let due = rental
+            .daily_price
+            .saturating_mul(now.saturating_sub(settled_until) as u128)
+            / DAY as u128;
+        let deposit = self.get_deposit_internal(rental.renter);
+        let amount = due.min(deposit);
+
+        if amount > 0 {
+            self.set_currency_deposit(rental.renter, Currency::Native, deposit - amount);
+            self.credit_deposit(rental.owner, amount)?;
+            rental.paid = rental.paid.saturating_add(amount);
+        }
+        // unpaid rent keeps accruing from the time paid rent covers
+        let funded = amount == due;
+        rental.settled_until = Some(if funded {
+            now
+        } else {
+            settled_until
+                .saturating_add((amount.saturating_mul(DAY as u128) / rental.daily_price) as u64)
+        });
+
+        self.emit_rent_stream_settled_event(
+            rental_id,
+            amount,
+            rental.settled_until.unwrap_or_default(),
+        );
+        Ok(funded)
+    }
+
+    default fn settle_held_rent(&mut self, rental: &Rental) -> Result<Balance, MarketplaceError> {
+        let refund = self.rental_refund(rental);
+        self.release_escrowed_funds(rental.held_rent);
+        if refund > 0 {
+            self.transfer_funds(
+                Currency::Native,
+                rental.renter,
+                refund,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
+        if rental.held_rent > refund {
+            self.transfer_funds(
+                Currency::Native,
+                rental.owner,
+                rental.held_rent - refund,
+                MarketplaceError::TransferToOwnerFailed,
+            )?;
+        }
+        Ok(refund)
+    }
+
+    default fn close_rental(
+        &mut self,
+        rental_id: u128,
+        rental: Rental,
+    ) -> Result<Balance, MarketplaceError> {
+        self.remove_rental(rental_id, &rental);
+        self.release_custody(
+            rental.contract_address,
+            rental.owner,
+            rental.token_id.clone(),
+        )?;
+        let refund = self.settle_held_rent(&rental)?;
+
+        if rental.collateral > 0 {
+            self.unlock_funds(rental.collateral);
+            self.transfer_funds(
+                Currency::Native,
+                rental.renter,
+                rental.collateral,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
+        Ok(refund)
+    }
+
+    default fn forfeit_collateral(
+        &mut self,
+        rental_id: u128,
+        rental: Rental,
+    ) -> Result<(), MarketplaceError> {
+        self.remove_rental(rental_id, &rental);
+        if rental.custodial {
+            self.release_custody(
+                rental.contract_address,
+                rental.owner,
+                rental.token_id.clone(),
+            )?;
+        }
+        self.settle_held_rent(&rental)?;
+        self.unlock_funds(rental.collateral);
+        self.transfer_funds(
+            Currency::Native,
+            rental.owner,
+            rental.collateral,
+            MarketplaceError::TransferToOwnerFailed,
+        )
+    }
+}
@@ -19,32 +19,68 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use super::types::{NftContractType, OfferItem, RegisteredCollection};
+use super::{
+    assets, batch, contract_calls,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_circuit_breaker::Internal as CircuitBreakerInternal,
+    marketplace_credits::Internal as CreditsInternal,
+    marketplace_curation::Internal as CurationInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_disputes::Internal as DisputesInternal,
+    marketplace_escrow::Internal as EscrowInternal,
+    marketplace_flash_sales::Internal as FlashSalesInternal,
+    marketplace_nested_listings::Internal as NestedListingsInternal,
+    marketplace_partners::Internal as PartnersInternal,
+    marketplace_referrals::Internal as ReferralsInternal,
+    marketplace_staking::Internal as StakingInternal,
+    marketplace_subscriptions::Internal as SubscriptionsInternal,
+    marketplace_wash_trading::Internal as WashTradingInternal,
+    types::{
+        price_bucket, token_key, AccountActivity, AssetId, Currency, CurrencyMetadata, Earnings,
+        ListingStatus, MarketplaceStats, NftContractType, OfferItem, RegisteredCollection,
+        SaleAttribution, MAX_KEY_ID_LEN, NATIVE_UNIT,
+    },
+    xvm,
+};
 use crate::{
     ensure,
     impls::marketplace::types::{Data, Item, MarketplaceError},
-    traits::marketplace::MarketplaceSale,
+    traits::{
+        admin::MarketplaceAdmin, compliance_registry::ComplianceRegistryRef,
+        deposit::MarketplaceDeposit, marketplace::MarketplaceSale, price_oracle::PriceOracleRef,
+        psp22_permit::Psp22PermitRef, royalty_registry::RoyaltyRegistryRef, wnative::WnativeRef,
+    },
 };
 use ink::prelude::vec::Vec;
 use nft::nft::NFTSeriesRef;
 use openbrush::{
-    contracts::{ownable::*, psp34::*, reentrancy_guard::*},
+    contracts::{ownable::*, pausable::*, psp22::PSP22Ref, psp34::*, reentrancy_guard::*},
     modifiers,
     traits::{AccountId, Balance, Hash, Storage, String},
 };
 
 pub trait Internal {
+    /// Checks that account is not blacklisted from trading.
+    fn check_not_blacklisted(&self, account: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Checks that account is allowed by the compliance registry, if one is set.
+    fn check_compliance(&self, account: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets the marketplace fee recipient or fails if it is not configured.
+    fn fee_recipient(&self) -> Result<AccountId, MarketplaceError>;
+
     /// Checks if contract caller is an token owner
     fn check_token_owner(
         &self,
         contract_address: AccountId,
-        token_id: Id,
+        token_id: &Id,
     ) -> Result<(), MarketplaceError>;
 
     fn check_token_allowance(
         &self,
         contract_address: AccountId,
-        token_id: Id,
+        token_id: &Id,
     ) -> Result<(), MarketplaceError>;
 
     /// Checks token price.
@@ -57,12 +93,206 @@ pub trait Internal {
     /// Checks fee
     fn check_fee(&self, fee: u16, max_fee: u16) -> Result<(), MarketplaceError>;
 
+    /// Checks that native price is a multiple of the tick size, if one is set.
+    fn check_tick_size(&self, price: Balance) -> Result<(), MarketplaceError>;
+
+    /// Checks that native price of the caller is not further below the collection floor than
+    /// their floor guard allows, unless confirmed.
+    fn check_floor_guard(
+        &self,
+        contract_address: AccountId,
+        price: Balance,
+        confirm_below_floor: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks that buyer holds a token of the collection a listing is gated by, if any.
+    fn check_listing_gate(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+        buyer: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if caller is the marketplace owner or owner of the NFT contract.
+    fn check_collection_owner(
+        &self,
+        marketplace_owner: AccountId,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks collection royalty and pairs it with its receiver.
+    fn collection_royalty(
+        &self,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+    ) -> Result<Option<(AccountId, u16)>, MarketplaceError>;
+
+    /// Gets registered collection or fails if the collection is not registered.
+    fn registered_collection(
+        &self,
+        contract_address: AccountId,
+    ) -> Result<RegisteredCollection, MarketplaceError>;
+
     /// Checks if token is listed for sale on the marketplace.
-    fn is_token_listed(&self, contract_address: AccountId, token_id: Id) -> bool;
+    fn is_token_listed(&self, contract_address: AccountId, token_id: &Id) -> bool;
+
+    /// Gets currency of a listing, native currency unless listed for another one.
+    fn listing_currency(&self, contract_address: AccountId, token_id: &Id) -> Currency;
+
+    /// Checks if a listing is priced in USD.
+    fn is_usd_listing(&self, contract_address: AccountId, token_id: &Id) -> bool;
+
+    /// Checks if sale proceeds of a listing are held in escrow.
+    fn is_escrowed_listing(&self, contract_address: AccountId, token_id: &Id) -> bool;
+
+    /// Removes listing of a token if there is one.
+    fn remove_item(&mut self, contract_address: AccountId, token_id: &Id);
+
+    /// Adds a listed token to or removes it from the listings of its owner and collection, and
+    /// records the id of a hashed token key.
+    fn update_listing_indexes(
+        &mut self,
+        owner: AccountId,
+        contract_address: AccountId,
+        token_id: &Id,
+        listed: bool,
+    );
+
+    /// Replaces native currency price of a listing in the collection listing prices used for
+    /// the floor price.
+    fn update_listing_prices(
+        &mut self,
+        contract_address: AccountId,
+        token_id: &Id,
+        old_price: Option<Balance>,
+        new_price: Option<Balance>,
+    );
+
+    /// Gets the first non-empty listing price bucket of a collection from `from` on.
+    fn next_price_bucket(&self, contract_address: AccountId, from: u16) -> Option<u16>;
+
+    /// Gets native listings (price, token key) of a price bucket in ascending price order.
+    fn bucket_listings(&self, contract_address: AccountId, bucket: u16) -> Vec<(Balance, Id)>;
+
+    /// Gets token id of a token key found in an index.
+    fn listed_token_id(&self, contract_address: AccountId, key: Id) -> Option<Id>;
+
+    /// Records a settled sale in the marketplace statistics.
+    fn record_sale_stats(&mut self, price: Balance, marketplace_fee: Balance, currency: Currency);
+
+    /// Creates a NFT item sale in the given currency.
+    fn list_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        currency: Currency,
+        usd_denominated: bool,
+        escrowed: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Converts USD price to native currency using the price oracle.
+    fn usd_to_native(&self, usd_price: Balance) -> Result<Balance, MarketplaceError>;
+
+    /// Gets royalty receiver and royalty amount for a sale price.
+    fn get_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+        price: Balance,
+    ) -> Result<(AccountId, Balance), MarketplaceError>;
+
+    /// Buys a listing priced in native or wrapped native currency with the transferred value.
+    fn buy_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys a listing priced in `currency`, or in native currency if `currency` is the wrapped
+    /// native token, pulling the price from the caller.
+    fn buy_item_with_psp22(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys a listing for `buyer` paying from buyer's deposit in the listing currency. Native
+    /// listings are paid with buyer's credit first if `spend_credit` is set.
+    fn buy_from_deposit(
+        &mut self,
+        buyer: AccountId,
+        contract_address: AccountId,
+        token_id: Id,
+        spend_credit: bool,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Completes purchase of a listed item, escrowing it if required by the listing.
+    fn settle_purchase(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Adds a PSP22 token to the accepted currencies or updates its metadata.
+    fn insert_supported_currency(&mut self, currency: AccountId, metadata: CurrencyMetadata);
+
+    /// Removes a PSP22 token from the accepted currencies.
+    fn remove_currency(&mut self, currency: AccountId);
+
+    /// Checks if NFT contract is an EVM ERC-721 collection traded through XVM.
+    fn is_xvm_collection(&self, contract_address: AccountId) -> bool;
+
+    /// Gets token owner. EVM tokens are owned by H160 addresses, so the owner of a token of
+    /// an XVM collection is `account` if its EVM address owns the token.
+    fn token_owner(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+        account: AccountId,
+    ) -> Option<AccountId>;
+
+    /// Checks if the marketplace is allowed to transfer token of `owner`.
+    fn token_approved(&self, contract_address: AccountId, owner: AccountId, token_id: &Id) -> bool;
+
+    /// Transfers token of `from` to `to`.
+    fn transfer_nft(
+        &mut self,
+        contract_address: AccountId,
+        from: AccountId,
+        to: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers a listed NFT to the marketplace and removes the listing.
+    fn take_custody(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Splits sale price to seller, marketplace and royalty receiver and transfers token.
+    fn settle_sale(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Currency,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError>;
 
     /// Transfers token.
     fn transfer_token(
-        &self,
+        &mut self,
         contract_address: AccountId,
         token_id: Id,
         token_owner: AccountId,
@@ -72,8 +302,32 @@ pub trait Internal {
         royalty_receiver: AccountId,
         author_royalty: Balance,
         token_price: Balance,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers funds held by the marketplace.
+    fn transfer_funds(
+        &self,
+        currency: Currency,
+        to: AccountId,
+        amount: Balance,
+        error: MarketplaceError,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers sale proceeds to seller in the currency preferred by the seller.
+    fn transfer_proceeds(
+        &mut self,
+        currency: Currency,
+        seller: AccountId,
+        amount: Balance,
     ) -> Result<(), MarketplaceError>;
 
+    /// Wraps marketplace's native balance into wrapped native token.
+    fn wrap_native(&self, amount: Balance) -> Result<(), MarketplaceError>;
+
+    /// Unwraps marketplace's wrapped native token into native balance.
+    fn unwrap_native(&self, amount: Balance) -> Result<(), MarketplaceError>;
+
     /// Get NFT contract hash needed for factory method
     fn get_nft_contract_hash(
         &self,
@@ -81,10 +335,72 @@ pub trait Internal {
     ) -> Result<Hash, MarketplaceError>;
 
     fn get_deposit_internal(&self, account_id: AccountId) -> Balance;
+
+    /// Sum of offer bonds held for open offers of an account.
+    fn get_locked_deposit(&self, account_id: AccountId) -> Balance;
+
+    /// Largest open offer of an account in `currency`, the deposit has to keep covering it.
+    fn get_offer_locked_deposit(&self, account_id: AccountId, currency: Currency) -> Balance;
+
+    /// Removes an offer together with its enumerable entry.
+    fn remove_offer(&mut self, offer_id: u128, offer: &OfferItem);
+
+    /// Time (in milliseconds) since an offer was made.
+    fn offer_age(&self, offer_id: u128) -> u64;
+
+    /// Gets currency the bidder's deposit is spent in when an offer is accepted.
+    fn offer_currency(&self, offer_id: u128) -> Currency;
+
+    /// Returns offer bond to the bidder's deposit or forfeits it to the fee recipient.
+    fn release_offer_bond(
+        &mut self,
+        offer_id: u128,
+        bidder: AccountId,
+        forfeit: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets deposited balance of an account.
+    fn get_currency_deposit(&self, account_id: AccountId, currency: Currency) -> Balance;
+
+    /// Sets deposited balance of an account.
+    fn set_currency_deposit(&mut self, account_id: AccountId, currency: Currency, amount: Balance);
+
+    /// Withdraws caller's deposited balance to `recipient`.
+    fn withdraw_currency(
+        &mut self,
+        currency: Currency,
+        recipient: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Records creator earnings of a sale settled in native currency.
+    fn record_earnings(
+        &mut self,
+        contract_address: AccountId,
+        seller: AccountId,
+        seller_fee: Balance,
+        royalty_receiver: AccountId,
+        author_royalty: Balance,
+    );
 }
 
 pub trait MarketplaceSaleEvents {
     fn emit_token_listed_event(&self, contract: AccountId, token_id: Id, price: Option<Balance>);
+    fn emit_token_unlisted_event(&self, contract: AccountId, token_id: Id);
+    fn emit_listing_updated_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        old_price: Balance,
+        new_price: Balance,
+        currency: Currency,
+    );
+    fn emit_listing_gate_updated_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        gate_collection: Option<AccountId>,
+    );
     fn emit_make_offer_event(
         &self,
         bidder_id: AccountId,
@@ -97,578 +413,850 @@ pub trait MarketplaceSaleEvents {
     );
 
     fn emit_cancel_offer_event(&self, offer_id: u128);
+    fn emit_offer_auto_cancelled_event(&self, offer_id: u128, bidder: AccountId);
+    fn emit_offer_bond_forfeited_event(&self, offer_id: u128, amount: Balance);
     fn emit_accept_offer_event(&self, offer_id: u128);
     fn emit_token_bought_event(
         &self,
+        sale_id: u128,
         contract: AccountId,
         token_id: Id,
         price: Balance,
-        from: AccountId,
-        to: AccountId,
+        seller: AccountId,
+        buyer: AccountId,
+        marketplace_fee: Balance,
+        royalty_receiver: AccountId,
+        royalty: Balance,
+        currency: Currency,
     );
     fn emit_collection_registered_event(&self, contract: AccountId);
-    fn emit_deposit_event(&self, account_id: AccountId, amount: Balance);
-    fn emit_withdraw_event(&self, account_id: AccountId, amount: Balance);
+    fn emit_collection_royalty_updated_event(
+        &self,
+        contract: AccountId,
+        old_royalty: Option<(AccountId, u16)>,
+        new_royalty: Option<(AccountId, u16)>,
+    );
+    fn emit_collection_metadata_updated_event(
+        &self,
+        contract: AccountId,
+        old_metadata: Option<String>,
+        new_metadata: String,
+    );
+    fn emit_collection_verification_updated_event(
+        &self,
+        contract: AccountId,
+        old_verified: bool,
+        new_verified: bool,
+    );
+    fn emit_collection_unregistered_event(
+        &self,
+        contract: AccountId,
+        royalty: Option<(AccountId, u16)>,
+        verified: bool,
+    );
+    fn emit_factory_instantiated_event(
+        &self,
+        creator: AccountId,
+        contract: AccountId,
+        contract_type: NftContractType,
+        code_hash: Hash,
+    );
+    fn emit_deposit_event(
+        &self,
+        account_id: AccountId,
+        currency: Currency,
+        amount: Balance,
+        new_balance: Balance,
+    );
+    fn emit_withdraw_event(
+        &self,
+        account_id: AccountId,
+        currency: Currency,
+        amount: Balance,
+        new_balance: Balance,
+    );
+    fn emit_deposit_transferred_event(&self, from: AccountId, to: AccountId, amount: Balance);
+    fn emit_spender_approved_event(
+        &self,
+        owner: AccountId,
+        operator: AccountId,
+        allowance: Balance,
+    );
 }
 
 impl<T> MarketplaceSale for T
 where
-    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
 {
-    /// Sets a hash of a Shiden34 contract to be instantiated by factory call.
-    #[modifiers(only_owner)]
-    default fn set_nft_contract_hash(
+    /// Creates a NFT item sale on the marketplace.
+    #[modifiers(when_not_taking_custody)]
+    default fn list(
         &mut self,
-        contract_type: NftContractType,
-        contract_hash: Hash,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
     ) -> Result<(), MarketplaceError> {
-        self.data::<Data>()
-            .nft_contract_hash
-            .insert(&contract_type, &contract_hash);
+        self.list_with_confirmation(contract_address, token_id, price, false)
+    }
+
+    /// Creates a NFT item sale on the marketplace, confirming a price below the floor guard.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn list_with_confirmation(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        confirm_below_floor: bool,
+    ) -> Result<(), MarketplaceError> {
+        self.check_floor_guard(contract_address, price, confirm_below_floor)?;
+        self.list_item(
+            contract_address,
+            token_id,
+            price,
+            Currency::Native,
+            false,
+            false,
+        )
+    }
+
+    /// Sets floor guard of the caller.
+    #[modifiers(when_not_taking_custody)]
+    default fn set_floor_guard(
+        &mut self,
+        max_below_floor: Option<u16>,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        match max_below_floor {
+            Some(max_below_floor) => {
+                self.check_fee(max_below_floor, 10_000)?;
+                self.data::<Data>()
+                    .floor_guards
+                    .insert(&caller, &max_below_floor);
+            }
+            None => self.data::<Data>().floor_guards.remove(&caller),
+        }
         Ok(())
     }
 
-    /// Gets a NFT contract hash.
-    default fn nft_contract_hash(&self, contract_type: NftContractType) -> Hash {
-        self.get_nft_contract_hash(&contract_type).unwrap()
+    /// Gets floor guard of an account.
+    default fn get_floor_guard(&self, account: AccountId) -> Option<u16> {
+        self.data::<Data>().floor_guards.get(&account)
     }
 
-    /// Creates a NFT item sale on the marketplace.
-    default fn list(
+    /// Creates a NFT item sale priced in a PSP22 currency on the marketplace.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn list_with_psp22(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
         price: Balance,
+        currency: AccountId,
     ) -> Result<(), MarketplaceError> {
-        self.check_token_owner(contract_address, token_id.clone())?;
-        self.data::<Data>().items.insert(
-            &(contract_address, token_id.clone()),
-            &Item {
-                owner: Self::env().caller(),
-                price,
-            },
+        ensure!(
+            self.is_supported_currency(currency),
+            MarketplaceError::CurrencyNotSupported
         );
-        self.emit_token_listed_event(contract_address, token_id, Some(price));
-        Ok(())
+        self.list_item(
+            contract_address,
+            token_id,
+            price,
+            Currency::Psp22(currency),
+            false,
+            false,
+        )
     }
 
-    /// Removes a NFT from the marketplace sale.
-    default fn unlist(
+    /// Creates a NFT item sale priced in a pallet-assets asset on the marketplace.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn list_with_asset(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
+        price: Balance,
+        asset_id: AssetId,
     ) -> Result<(), MarketplaceError> {
         ensure!(
-            self.is_token_listed(contract_address, token_id.clone()),
-            MarketplaceError::ItemNotListedForSale
+            self.is_supported_asset(asset_id),
+            MarketplaceError::CurrencyNotSupported
         );
-        self.check_token_owner(contract_address, token_id.clone())?;
+        self.list_item(
+            contract_address,
+            token_id,
+            price,
+            Currency::Asset(asset_id),
+            false,
+            false,
+        )
+    }
 
-        self.data::<Data>()
-            .items
-            .remove(&(contract_address, token_id.clone()));
-        self.emit_token_listed_event(contract_address, token_id, None);
-        Ok(())
+    /// Creates a NFT item sale priced in USD and settled in native currency.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn list_with_usd_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        usd_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().v1.price_oracle.is_some(),
+            MarketplaceError::OracleNotSet
+        );
+        self.list_item(
+            contract_address,
+            token_id,
+            usd_price,
+            Currency::Native,
+            true,
+            false,
+        )
     }
 
-    /// Buys NFT item from the marketplace.
-    #[modifiers(non_reentrant)]
-    default fn buy(
+    /// Restricts a listing to holders of a token of another collection.
+    #[modifiers(when_not_taking_custody, when_not_paused)]
+    default fn set_listing_gate(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
+        gate_collection: Option<AccountId>,
     ) -> Result<(), MarketplaceError> {
+        let key = (contract_address, token_key(&token_id));
         let item = self
             .data::<Data>()
             .items
-            .get(&(contract_address, token_id.clone()))
+            .get(&key)
             .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            item.owner == Self::env().caller(),
+            MarketplaceError::NotOwner
+        );
 
-        let token_owner = PSP34Ref::owner_of(&contract_address, token_id.clone())
-            .ok_or(MarketplaceError::TokenDoesNotExist)?;
-        let caller = Self::env().caller();
-        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
-
-        let value = Self::env().transferred_value();
-        self.check_price(value, item.price)?;
-
-        let collection = self
-            .data::<Data>()
-            .registered_collections
-            .get(&contract_address)
-            .unwrap();
-
-        let marketplace_fee = value
-            .checked_mul(self.data::<Data>().fee as u128)
-            .unwrap_or_default()
-            / 10_000;
+        if let Some(gate_collection) = gate_collection {
+            self.data::<Data>()
+                .listing_gates
+                .insert(&key, &gate_collection);
+        } else {
+            self.data::<Data>().listing_gates.remove(&key);
+        }
+        self.emit_listing_gate_updated_event(contract_address, token_id, gate_collection);
+        Ok(())
+    }
 
-        let author_address;
-        let author_royalty = if let Some(royalty) = collection.royalty {
-            author_address = royalty.0;
+    /// Gets collection buyers of a listing have to hold a token of.
+    default fn get_listing_gate(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<AccountId> {
+        self.data::<Data>()
+            .listing_gates
+            .get(&(contract_address, token_key(&token_id)))
+    }
 
-            value.checked_mul(royalty.1 as u128).unwrap_or_default() / 10_000
-        } else {
-            let token_id_number = match token_id {
-                Id::U64(x) => x,
-                _ => panic!(),
-            };
-
-            // only support 1 for now
-            if collection.contract_type == NftContractType::NFTSeries {
-                match NFTSeriesRef::royalty_info(&contract_address, token_id_number, value) {
-                    Ok(payouts) => {
-                        if let Some(payout) = payouts.get(0) {
-                            author_address = payout.0;
-                            payout.1
-                        } else {
-                            author_address = contract_address;
-                            0
-                        }
-                    }
-                    Err(_) => {
-                        author_address = contract_address;
-                        0
-                    }
-                }
-            } else {
-                author_address = contract_address;
-                0
-            }
-        };
+    /// Removes a NFT from the marketplace sale.
+    #[modifiers(when_not_taking_custody)]
+    default fn unlist(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_token_listed(contract_address, &token_id),
+            MarketplaceError::ItemNotListedForSale
+        );
+        self.check_token_owner(contract_address, &token_id)?;
 
-        let seller_fee = value
-            .checked_sub(marketplace_fee)
-            .unwrap_or_default()
-            .checked_sub(author_royalty)
-            .unwrap_or_default();
+        self.remove_item(contract_address, &token_id);
+        self.emit_token_unlisted_event(contract_address, token_id);
+        Ok(())
+    }
 
-        self.transfer_token(
+    /// Buys NFT item from the marketplace.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        self.buy_item(contract_address, token_id, SaleAttribution::default())
+    }
+
+    /// Buys NFT item from the marketplace paying with caller's credit and deposit.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_deposit(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        self.buy_from_deposit(
+            Self::env().caller(),
             contract_address,
             token_id,
-            token_owner,
-            caller,
-            seller_fee,
-            marketplace_fee,
-            author_address,
-            author_royalty,
-            value,
+            true,
+            SaleAttribution::default(),
         )
     }
 
-    /// Registers NFT collection to the marketplace.
-    default fn register(
+    /// Buys NFT item for `owner` paying from owner's deposit. Owner's credit is not spent, so
+    /// the allowance is charged with what the deposit pays.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_deposit_of(
         &mut self,
+        owner: AccountId,
         contract_address: AccountId,
-        royalty_receiver: Option<AccountId>,
-        royalty: Option<u16>,
-        contract_type: NftContractType,
+        token_id: Id,
     ) -> Result<(), MarketplaceError> {
         let caller = Self::env().caller();
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        // allowances are approved in native currency
+        ensure!(
+            self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
+        let price = self.flash_sale_price(contract_address, &item);
+        let allowance = self.get_spender_allowance(owner, caller);
+        ensure!(allowance >= price, MarketplaceError::AllowanceExceeded);
+        self.data::<Data>()
+            .deposit_allowances
+            .insert(&(owner, caller), &(allowance - price));
 
-        // Check if caller is Marketplace owner of NFT owner.
-        if self.data::<ownable::Data>().owner != caller
-            && OwnableRef::owner(&contract_address) != caller
-        {
-            return Err(MarketplaceError::NotOwner);
-        }
-
-        if royalty.is_some() {
-            let max_fee = self.data::<Data>().max_fee;
-            self.check_fee(royalty.unwrap(), max_fee)?;
-            self.data::<Data>().registered_collections.insert(
-                &contract_address,
-                &RegisteredCollection {
-                    royalty: Some((royalty_receiver.unwrap(), royalty.unwrap())),
-                    contract_type,
-                },
-            );
-        } else {
-            self.data::<Data>().registered_collections.insert(
-                &contract_address,
-                &RegisteredCollection {
-                    royalty: None,
-                    contract_type,
-                },
-            );
-        }
-        self.emit_collection_registered_event(contract_address);
-        Ok(())
+        self.buy_from_deposit(
+            owner,
+            contract_address,
+            token_id,
+            false,
+            SaleAttribution::default(),
+        )
     }
 
-    /// Gets registered collection.
-    default fn get_registered_collection(
-        &self,
+    /// Buys NFT item listed for a PSP22 currency from the marketplace.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_psp22(
+        &mut self,
         contract_address: AccountId,
-    ) -> Option<RegisteredCollection> {
-        self.data::<Data>()
-            .registered_collections
-            .get(&contract_address)
+        token_id: Id,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        self.buy_item_with_psp22(contract_address, token_id, currency)
     }
 
-    /// Sets the marketplace fee.
-    #[modifiers(only_owner)]
-    default fn set_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError> {
-        let max_fee = self.data::<Data>().max_fee;
-        self.check_fee(fee, max_fee)?;
-        self.data::<Data>().fee = fee;
+    /// Buys NFT item listed for a PSP22 currency with a signed permit.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_psp22_permit(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        currency: AccountId,
+        deadline: u64,
+        signature: [u8; 65],
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_supported_currency(currency),
+            MarketplaceError::CurrencyNotSupported
+        );
+        ensure!(
+            Self::env().block_timestamp() <= deadline,
+            MarketplaceError::PermitExpired
+        );
+        let price = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .map(|item| self.flash_sale_price(contract_address, &item))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
 
-        Ok(())
-    }
+        Psp22PermitRef::permit(
+            &currency,
+            Self::env().caller(),
+            Self::env().account_id(),
+            price,
+            deadline,
+            signature,
+        )
+        .map_err(|_| MarketplaceError::PermitFailed)?;
 
-    /// Gets the marketplace fee.
-    default fn get_marketplace_fee(&self) -> u16 {
-        self.data::<Data>().fee
+        self.buy_item_with_psp22(contract_address, token_id, currency)
     }
 
-    /// Gets max fee that can be applied to an item price.
-    default fn get_max_fee(&self) -> u16 {
-        self.data::<Data>().max_fee
-    }
+    /// Buys NFT item listed for a pallet-assets asset from the marketplace.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_asset(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        asset_id: AssetId,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_supported_asset(asset_id),
+            MarketplaceError::CurrencyNotSupported
+        );
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            self.listing_currency(contract_address, &token_id) == Currency::Asset(asset_id),
+            MarketplaceError::CurrencyMismatch
+        );
 
-    /// Checks if NFT token is listed on the marketplace and returns token price.
-    default fn get_price(&self, contract_address: AccountId, token_id: Id) -> Option<Balance> {
-        match self.data::<Data>().items.get(&(contract_address, token_id)) {
-            Some(item) => Some(item.price),
-            _ => None,
-        }
-    }
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let caller = Self::env().caller();
+        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+
+        let price = self.flash_sale_price(contract_address, &item);
+        assets::transfer_approved(asset_id, caller, Self::env().account_id(), price)?;
 
-    /// Gets the marketplace fee recipient.
-    default fn get_fee_recipient(&self) -> AccountId {
-        self.data::<Data>().market_fee_recipient.unwrap()
+        self.settle_purchase(
+            contract_address,
+            token_id,
+            token_owner,
+            caller,
+            price,
+            SaleAttribution::default(),
+        )
     }
 
-    /// Sets the marketplace fee recipient.
-    #[modifiers(only_owner)]
-    default fn set_fee_recipient(
+    /// Buys NFT item priced in USD.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_with_usd_price(
         &mut self,
-        fee_recipient: AccountId,
+        contract_address: AccountId,
+        token_id: Id,
+        max_slippage: u16,
     ) -> Result<(), MarketplaceError> {
-        self.data::<Data>().market_fee_recipient = Option::Some(fee_recipient);
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            self.is_usd_listing(contract_address, &token_id),
+            MarketplaceError::CurrencyMismatch
+        );
+
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let caller = Self::env().caller();
+        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+
+        let native_price = self.usd_to_native(self.flash_sale_price(contract_address, &item))?;
+        let value = Self::env().transferred_value();
+        self.check_price(value, native_price)?;
+        ensure!(
+            value <= native_price.saturating_mul(10_000 + max_slippage as u128) / 10_000,
+            MarketplaceError::SlippageExceeded
+        );
 
+        self.settle_purchase(
+            contract_address,
+            token_id,
+            token_owner,
+            caller,
+            native_price,
+            SaleAttribution::default(),
+        )?;
+        if value > native_price {
+            self.transfer_funds(
+                Currency::Native,
+                caller,
+                value - native_price,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
         Ok(())
     }
 
-    default fn deposit(&mut self) -> Result<(), MarketplaceError> {
+    /// Buys the cheapest native currency listings of a collection within a budget.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn sweep_floor(
+        &mut self,
+        contract_address: AccountId,
+        max_items: u32,
+        max_total: Balance,
+    ) -> Result<u32, MarketplaceError> {
+        let max_batch_size = self.data::<Data>().v1.max_batch_size;
+        batch::check_batch_size(max_items as usize, max_batch_size)?;
         let caller = Self::env().caller();
         let value = Self::env().transferred_value();
+        let budget = max_total.min(value);
+
+        let mut bought = 0;
+        // skipped listings count too, so stale listings can't make the sweep unbounded
+        let mut visited = 0;
+        let mut spent: Balance = 0;
+        let mut next_bucket = self.next_price_bucket(contract_address, 0);
+        'sweep: while let Some(bucket) = next_bucket {
+            // purchases remove listings from the bucket, so walk a snapshot of it
+            for (_, key) in self.bucket_listings(contract_address, bucket) {
+                if bought == max_items || visited == max_batch_size {
+                    break 'sweep
+                }
+                visited += 1;
+                let token_id = match self.listed_token_id(contract_address, key) {
+                    Some(token_id) => token_id,
+                    None => continue,
+                };
+                let item = match self
+                    .data::<Data>()
+                    .items
+                    .get(&(contract_address, token_key(&token_id)))
+                {
+                    Some(item) => item,
+                    None => continue,
+                };
+                let price = self.flash_sale_price(contract_address, &item);
+                if spent.saturating_add(price) > budget {
+                    break 'sweep
+                }
+                // skip stale listings instead of failing the whole sweep
+                let token_owner = match self.token_owner(contract_address, &token_id, item.owner) {
+                    Some(token_owner) if token_owner != caller => token_owner,
+                    _ => continue,
+                };
+                if !self.token_approved(contract_address, token_owner, &token_id)
+                    || self
+                        .check_listing_gate(contract_address, &token_id, caller)
+                        .is_err()
+                {
+                    continue
+                }
 
-        let current_balance = self.data::<Data>().deposit.get(&caller).unwrap_or(0);
-        self.data::<Data>()
-            .deposit
-            .insert(&caller, &(value + current_balance));
+                self.settle_purchase(
+                    contract_address,
+                    token_id,
+                    token_owner,
+                    caller,
+                    price,
+                    SaleAttribution::default(),
+                )?;
+                spent += price;
+                bought += 1;
+            }
+            next_bucket = bucket
+                .checked_add(1)
+                .and_then(|from| self.next_price_bucket(contract_address, from));
+        }
 
-        self.emit_deposit_event(caller, value);
-        Ok(())
+        if value > spent {
+            self.transfer_funds(
+                Currency::Native,
+                caller,
+                value - spent,
+                MarketplaceError::RefundFailed,
+            )?;
+        }
+        Ok(bought)
     }
 
-    default fn withdraw(&mut self, amount: Balance) -> Result<(), MarketplaceError> {
-        let caller = Self::env().caller();
-        let current_balance = self.data::<Data>().deposit.get(&caller).unwrap_or(0);
+    /// Gets current price of a listed NFT token in native currency.
+    default fn get_native_price(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<Balance, MarketplaceError> {
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
 
-        if current_balance < amount {
-            return Err(MarketplaceError::BalanceInsufficient);
+        let price = self.flash_sale_price(contract_address, &item);
+        if self.is_usd_listing(contract_address, &token_id) {
+            self.usd_to_native(price)
         } else {
-            self.data::<Data>()
-                .deposit
-                .insert(&caller, &(current_balance - amount));
-            Self::env()
-                .transfer(caller, amount)
-                .map_err(|_| MarketplaceError::TransferToOwnerFailed)?;
-
-            self.emit_withdraw_event(caller, amount);
-            Ok(())
+            Ok(price)
         }
     }
 
-    default fn get_deposit(&self, account_id: AccountId) -> Balance {
-        self.get_deposit_internal(account_id)
+    /// Checks if NFT token is listed on the marketplace and returns token price.
+    default fn get_price(&self, contract_address: AccountId, token_id: Id) -> Option<Balance> {
+        match self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+        {
+            Some(item) => Some(self.flash_sale_price(contract_address, &item)),
+            _ => None,
+        }
     }
 
-    default fn cancel_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError> {
-        let caller = Self::env().caller();
-
-        let offer = self.data::<Data>().offer_items.get(&offer_id).unwrap();
-
-        if offer.bidder_id != caller {
-            return Err(MarketplaceError::NotOwner);
+    /// Gets the currency of a listed NFT token.
+    default fn get_currency(&self, contract_address: AccountId, token_id: Id) -> Option<Currency> {
+        if !self.is_token_listed(contract_address, &token_id) {
+            return None
         }
+        Some(self.listing_currency(contract_address, &token_id))
+    }
 
-        self.data::<Data>().offer_items.remove(&offer_id);
-
-        // remove offer from enumerable
-        let mut offer_ids = self
-            .data::<Data>()
-            .offer_items_per_contract_token_id
-            .get(&(offer.contract_address, offer.token_id.clone()))
-            .unwrap();
+    /// Gets listings of several NFT tokens.
+    default fn get_listings(&self, tokens: Vec<(AccountId, Id)>) -> Vec<Option<Item>> {
+        tokens
+            .iter()
+            .take(self.data::<Data>().v1.max_batch_size as usize)
+            .map(|(contract_address, token_id)| {
+                self.data::<Data>()
+                    .items
+                    .get(&(*contract_address, token_key(token_id)))
+            })
+            .collect()
+    }
 
-        offer_ids.swap_remove(offer_ids.binary_search(&offer_id).ok().unwrap());
+    /// Gets prices of several NFT tokens.
+    default fn get_prices(&self, tokens: Vec<(AccountId, Id)>) -> Vec<Option<Balance>> {
+        tokens
+            .iter()
+            .take(self.data::<Data>().v1.max_batch_size as usize)
+            .map(|(contract_address, token_id)| {
+                self.data::<Data>()
+                    .items
+                    .get(&(*contract_address, token_key(token_id)))
+                    .map(|item| self.flash_sale_price(*contract_address, &item))
+            })
+            .collect()
+    }
 
+    /// Gets lifetime native currency earnings (primary sales and royalties) of a creator.
+    default fn get_creator_earnings(&self, account: AccountId) -> Earnings {
         self.data::<Data>()
-            .offer_items_per_contract_token_id
-            .insert(
-                &(offer.contract_address, offer.token_id.clone()),
-                &offer_ids,
-            );
-
-        self.emit_cancel_offer_event(offer_id);
-
-        Ok(())
+            .creator_earnings
+            .get(&account)
+            .unwrap_or_default()
     }
 
-    default fn get_offer_active(&self, offer_id: u128) -> bool {
-        let offer = self.data::<Data>().offer_items.get(&offer_id);
-
-        if let Some(offer) = offer {
-            let deposit = self.get_deposit_internal(offer.bidder_id);
-            let total_amount = offer.quantity as u128 * offer.price_per_item;
+    /// Gets lifetime native currency creator earnings generated by a collection.
+    default fn get_collection_earnings(&self, contract_address: AccountId) -> Earnings {
+        self.data::<Data>()
+            .collection_earnings
+            .get(&contract_address)
+            .unwrap_or_default()
+    }
 
-            if deposit >= total_amount {
-                return true;
-            }
-        }
-        return false;
+    /// Gets the lowest native currency listing price of a collection.
+    default fn get_floor_price(&self, contract_address: AccountId) -> Option<Balance> {
+        self.data::<Data>().floor_prices.get(&contract_address)
     }
 
-    #[modifiers(non_reentrant)]
-    default fn accept_offer(
-        &mut self,
-        offer_id: u128,
+    default fn check_listing_valid(
+        &self,
+        contract_address: AccountId,
         token_id: Id,
-    ) -> Result<(), MarketplaceError> {
-        let offer_wrapped = self.data::<Data>().offer_items.get(&offer_id);
-        if offer_wrapped.is_none() {
-            return Err(MarketplaceError::OfferDoesNotExist);
-        }
-        let mut offer = offer_wrapped.unwrap();
-        if let Some(token_id_offer) = offer.token_id.clone() {
-            if token_id_offer != token_id {
-                return Err(MarketplaceError::OfferNotMatch);
-            }
-        }
-
-        let collection = self
+    ) -> Result<ListingStatus, MarketplaceError> {
+        let item = self
             .data::<Data>()
-            .registered_collections
-            .get(&offer.contract_address)
-            .unwrap();
-
-        // check owner and allowance
-        self.check_token_owner(offer.contract_address, token_id.clone())?;
-        self.check_token_allowance(offer.contract_address, token_id.clone())?;
-
-        // check if bidder's balance sufficient
-        let deposit = self.get_deposit_internal(offer.bidder_id);
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
 
-        if deposit < offer.price_per_item {
-            return Err(MarketplaceError::BalanceInsufficient);
+        match self.token_owner(contract_address, &token_id, item.owner) {
+            Some(token_owner) if token_owner == item.owner => {}
+            _ => return Ok(ListingStatus::OwnerChanged),
         }
 
-        // update offer state
-        if offer.quantity == 1 {
-            self.data::<Data>().offer_items.remove(&offer_id);
-        } else {
-            offer.quantity -= 1;
-            self.data::<Data>().offer_items.insert(&offer_id, &offer);
+        if !self.token_approved(contract_address, item.owner, &token_id) {
+            return Ok(ListingStatus::ApprovalRevoked);
         }
 
-        // update bidder state
-        self.data::<Data>()
-            .deposit
-            .insert(&offer.bidder_id, &(deposit - offer.price_per_item));
-
-        // remove from enumerable
-        let mut offer_ids = self
-            .data::<Data>()
-            .offer_items_per_contract_token_id
-            .get(&(offer.contract_address, offer.token_id.clone()))
-            .unwrap();
-
-        offer_ids.swap_remove(offer_ids.binary_search(&offer_id).ok().unwrap());
-
-        self.data::<Data>()
-            .offer_items_per_contract_token_id
-            .insert(
-                &(offer.contract_address, offer.token_id.clone()),
-                &offer_ids,
-            );
-
-        let marketplace_fee = offer
-            .price_per_item
-            .checked_mul(self.data::<Data>().fee as u128)
-            .unwrap_or_default()
-            / 10_000;
-
-        let author_address;
-        let author_royalty = if let Some(royalty) = collection.royalty {
-            author_address = royalty.0;
+        Ok(ListingStatus::Valid)
+    }
 
-            offer
-                .price_per_item
-                .checked_mul(royalty.1 as u128)
-                .unwrap_or_default()
-                / 10_000
-        } else {
-            let token_id_number = match token_id {
-                Id::U64(x) => x,
-                _ => panic!(),
-            };
-
-            // only support 1 for now
-            if collection.contract_type == NftContractType::NFTSeries {
-                match NFTSeriesRef::royalty_info(
-                    &offer.contract_address,
-                    token_id_number,
-                    offer.price_per_item,
-                ) {
-                    Ok(payouts) => {
-                        if let Some(payout) = payouts.get(0) {
-                            author_address = payout.0;
-                            payout.1
-                        } else {
-                            author_address = offer.contract_address;
-                            0
-                        }
-                    }
-                    Err(_) => {
-                        author_address = offer.contract_address;
-                        0
+    /// Gets listings, offers and deposit of an account.
+    default fn get_account_activity(&self, account: AccountId) -> AccountActivity {
+        let data = self.data::<Data>();
+        let listings: Vec<(AccountId, Id)> = (0..data.account_listing_index.count(&account))
+            .filter_map(|index| data.account_listing_index.get_value(&account, &index))
+            .collect();
+        let offers_made = (0..data.account_offer_index.count(&account))
+            .filter_map(|index| data.account_offer_index.get_value(&account, &index))
+            .collect();
+
+        let mut offers_received = Vec::new();
+        for (contract_address, token_id) in listings.iter() {
+            // listing index already holds token keys
+            for key in [
+                (*contract_address, Some(token_id.clone())),
+                (*contract_address, None),
+            ] {
+                let offers = &data.token_offer_index;
+                for offer_id in
+                    (0..offers.count(&key)).filter_map(|index| offers.get_value(&key, &index))
+                {
+                    if !offers_received.contains(&offer_id) {
+                        offers_received.push(offer_id);
                     }
                 }
-            } else {
-                author_address = offer.contract_address;
-                0
             }
-        };
+        }
 
-        let seller_fee = offer
-            .price_per_item
-            .checked_sub(marketplace_fee)
-            .unwrap_or_default()
-            .checked_sub(author_royalty)
-            .unwrap_or_default();
+        AccountActivity {
+            listings: listings
+                .into_iter()
+                .filter_map(|(contract_address, key)| {
+                    self.listed_token_id(contract_address, key)
+                        .map(|token_id| (contract_address, token_id))
+                })
+                .collect(),
+            offers_made,
+            offers_received,
+            deposit: self.get_deposit_internal(account),
+            locked: self.get_locked_deposit(account),
+        }
+    }
 
-        self.emit_accept_offer_event(offer_id);
+    /// Gets listings of a collection.
+    default fn get_listings_by_collection(
+        &self,
+        contract_address: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(Id, Item)> {
+        let listings = &self.data::<Data>().collection_listing_index;
+        (from as u128..listings.count(&contract_address))
+            .take(limit.min(self.data::<Data>().v1.max_batch_size) as usize)
+            .filter_map(|index| listings.get_value(&contract_address, &index))
+            .filter_map(|key| {
+                let item = self
+                    .data::<Data>()
+                    .items
+                    .get(&(contract_address, key.clone()))?;
+                Some((self.listed_token_id(contract_address, key)?, item))
+            })
+            .collect()
+    }
 
-        self.transfer_token(
-            offer.contract_address,
-            token_id,
-            Self::env().caller(),
-            offer.bidder_id,
-            seller_fee,
-            marketplace_fee,
-            author_address,
-            author_royalty,
-            offer.price_per_item,
-        )
+    /// Gets listings of an account.
+    default fn get_listings_by_account(
+        &self,
+        account: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(AccountId, Id, Item)> {
+        let listings = &self.data::<Data>().account_listing_index;
+        (from as u128..listings.count(&account))
+            .take(limit.min(self.data::<Data>().v1.max_batch_size) as usize)
+            .filter_map(|index| listings.get_value(&account, &index))
+            .filter_map(|(contract_address, key)| {
+                let item = self
+                    .data::<Data>()
+                    .items
+                    .get(&(contract_address, key.clone()))?;
+                Some((
+                    contract_address,
+                    self.listed_token_id(contract_address, key)?,
+                    item,
+                ))
+            })
+            .collect()
     }
 
-    default fn fulfill_offer(
-        &mut self,
-        _offer_id: u128,
+    /// Gets aggregate marketplace statistics.
+    default fn get_marketplace_stats(&self) -> MarketplaceStats {
+        self.data::<Data>().stats.get().unwrap_or_default()
+    }
+}
+
+impl<T> MarketplaceSaleEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_token_listed_event(
+        &self,
+        _contract: AccountId,
         _token_id: Id,
-    ) -> Result<(), MarketplaceError> {
-        // TO DO: will be used for accepting offer with extra
-        Ok(())
+        _price: Option<Balance>,
+    ) {
     }
 
-    default fn make_offer(
-        &mut self,
-        contract_address: AccountId,
-        token_id: Option<Id>,
-        quantity: u64,
-        price_per_item: Balance,
-        extra: String,
-    ) -> Result<u128, MarketplaceError> {
-        let caller = Self::env().caller();
-
-        let total_amount = quantity as u128 * price_per_item;
-
-        let deposit = self.get_deposit_internal(caller);
+    default fn emit_token_unlisted_event(&self, _contract: AccountId, _token_id: Id) {}
 
-        if deposit < total_amount {
-            return Err(MarketplaceError::BalanceInsufficient);
-        }
-
-        let current_offer_id = self.data::<Data>().last_offer_id + 1;
-
-        self.data::<Data>().last_offer_id = current_offer_id;
-
-        self.data::<Data>().offer_items.insert(
-            &current_offer_id,
-            &OfferItem {
-                bidder_id: caller,
-                contract_address,
-                token_id: token_id.clone(),
-                quantity,
-                price_per_item,
-                extra: extra.clone(),
-            },
-        );
+    default fn emit_listing_updated_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _old_price: Balance,
+        _new_price: Balance,
+        _currency: Currency,
+    ) {
+    }
 
-        let mut offer_ids = self
-            .data::<Data>()
-            .offer_items_per_contract_token_id
-            .get(&(contract_address, token_id.clone()))
-            .unwrap_or_default();
+    default fn emit_listing_gate_updated_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _gate_collection: Option<AccountId>,
+    ) {
+    }
 
-        offer_ids.push(current_offer_id);
+    default fn emit_token_bought_event(
+        &self,
+        _sale_id: u128,
+        _contract: AccountId,
+        _token_id: Id,
+        _price: Balance,
+        _seller: AccountId,
+        _buyer: AccountId,
+        _marketplace_fee: Balance,
+        _royalty_receiver: AccountId,
+        _royalty: Balance,
+        _currency: Currency,
+    ) {
+    }
 
-        self.data::<Data>()
-            .offer_items_per_contract_token_id
-            .insert(&(contract_address, token_id.clone()), &offer_ids);
+    default fn emit_collection_registered_event(&self, _contract: AccountId) {}
 
-        // Emit event
-        self.emit_make_offer_event(
-            caller,
-            contract_address,
-            token_id,
-            quantity,
-            price_per_item,
-            extra,
-            current_offer_id,
-        );
-        Ok(1)
+    default fn emit_collection_royalty_updated_event(
+        &self,
+        _contract: AccountId,
+        _old_royalty: Option<(AccountId, u16)>,
+        _new_royalty: Option<(AccountId, u16)>,
+    ) {
     }
 
-    default fn get_offer_for_token(
+    default fn emit_collection_metadata_updated_event(
         &self,
-        contract_address: AccountId,
-        token_id: Option<Id>,
-    ) -> Result<Vec<u128>, MarketplaceError> {
-        Ok(self
-            .data::<Data>()
-            .offer_items_per_contract_token_id
-            .get(&(contract_address, token_id))
-            .unwrap_or_default())
+        _contract: AccountId,
+        _old_metadata: Option<String>,
+        _new_metadata: String,
+    ) {
     }
-}
 
-impl<T> MarketplaceSaleEvents for T
-where
-    T: Storage<Data>,
-{
-    default fn emit_token_listed_event(
+    default fn emit_collection_verification_updated_event(
         &self,
         _contract: AccountId,
-        _token_id: Id,
-        _price: Option<Balance>,
+        _old_verified: bool,
+        _new_verified: bool,
     ) {
     }
 
-    default fn emit_token_bought_event(
+    default fn emit_collection_unregistered_event(
         &self,
         _contract: AccountId,
-        _token_id: Id,
-        _price: Balance,
-        _from: AccountId,
-        _to: AccountId,
+        _royalty: Option<(AccountId, u16)>,
+        _verified: bool,
     ) {
     }
 
-    default fn emit_collection_registered_event(&self, _contract: AccountId) {}
+    default fn emit_factory_instantiated_event(
+        &self,
+        _creator: AccountId,
+        _contract: AccountId,
+        _contract_type: NftContractType,
+        _code_hash: Hash,
+    ) {
+    }
 
     default fn emit_make_offer_event(
         &self,
@@ -682,20 +1270,79 @@ where
     ) {
     }
     default fn emit_cancel_offer_event(&self, _offer_id: u128) {}
+
+    default fn emit_offer_auto_cancelled_event(&self, _offer_id: u128, _bidder: AccountId) {}
+
+    default fn emit_offer_bond_forfeited_event(&self, _offer_id: u128, _amount: Balance) {}
     default fn emit_accept_offer_event(&self, _offer_id: u128) {}
 
-    default fn emit_deposit_event(&self, _account_id: AccountId, _amount: Balance) {}
-    default fn emit_withdraw_event(&self, _account_id: AccountId, _amount: Balance) {}
+    default fn emit_deposit_event(
+        &self,
+        _account_id: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+        _new_balance: Balance,
+    ) {
+    }
+    default fn emit_withdraw_event(
+        &self,
+        _account_id: AccountId,
+        _currency: Currency,
+        _amount: Balance,
+        _new_balance: Balance,
+    ) {
+    }
+    default fn emit_deposit_transferred_event(
+        &self,
+        _from: AccountId,
+        _to: AccountId,
+        _amount: Balance,
+    ) {
+    }
+    default fn emit_spender_approved_event(
+        &self,
+        _owner: AccountId,
+        _operator: AccountId,
+        _allowance: Balance,
+    ) {
+    }
 }
 
 impl<T> Internal for T
 where
     T: Storage<Data>,
 {
+    default fn check_not_blacklisted(&self, account: AccountId) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self.data::<Data>().blacklist.contains(&account),
+            MarketplaceError::Blacklisted
+        );
+
+        Ok(())
+    }
+
+    default fn fee_recipient(&self) -> Result<AccountId, MarketplaceError> {
+        self.data::<Data>()
+            .market_fee_recipient
+            .ok_or(MarketplaceError::FeeRecipientNotSet)
+    }
+
+    default fn check_compliance(&self, account: AccountId) -> Result<(), MarketplaceError> {
+        if let Some(registry) = self.data::<Data>().v1.compliance_registry {
+            ensure!(
+                account == Self::env().account_id()
+                    || ComplianceRegistryRef::is_allowed(&registry, account),
+                MarketplaceError::NotCompliant
+            );
+        }
+
+        Ok(())
+    }
+
     default fn check_token_owner(
         &self,
         contract_address: AccountId,
-        token_id: Id,
+        token_id: &Id,
     ) -> Result<(), MarketplaceError> {
         if !self
             .data::<Data>()
@@ -706,7 +1353,7 @@ where
         }
 
         let caller = Self::env().caller();
-        match PSP34Ref::owner_of(&contract_address, token_id) {
+        match self.token_owner(contract_address, token_id, caller) {
             Some(token_owner) => {
                 ensure!(caller == token_owner, MarketplaceError::NotOwner);
                 Ok(())
@@ -718,16 +1365,9 @@ where
     default fn check_token_allowance(
         &self,
         contract_address: AccountId,
-        token_id: Id,
+        token_id: &Id,
     ) -> Result<(), MarketplaceError> {
-        let caller = Self::env().caller();
-        let current_contract_id = Self::env().account_id();
-        match PSP34Ref::allowance(
-            &contract_address,
-            caller,
-            current_contract_id,
-            Some(token_id),
-        ) {
+        match self.token_approved(contract_address, Self::env().caller(), token_id) {
             false => Err(MarketplaceError::TokenNotApproved),
             true => Ok(()),
         }
@@ -738,74 +1378,1235 @@ where
         transferred_value: Balance,
         price: Balance,
     ) -> Result<(), MarketplaceError> {
-        ensure!(transferred_value >= price, MarketplaceError::BadBuyValue);
+        ensure!(
+            transferred_value >= price,
+            MarketplaceError::BadBuyValue {
+                expected: price,
+                received: transferred_value,
+            }
+        );
 
         Ok(())
     }
 
-    default fn check_fee(&self, fee: u16, max_fee: u16) -> Result<(), MarketplaceError> {
-        ensure!(fee <= max_fee, MarketplaceError::FeeTooHigh);
+    default fn check_collection_owner(
+        &self,
+        marketplace_owner: AccountId,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        // Check if caller is Marketplace owner of NFT owner.
+        if marketplace_owner != caller
+            && (self.is_xvm_collection(contract_address)
+                || contract_calls::collection_owner(contract_address) != caller)
+        {
+            return Err(MarketplaceError::NotOwner)
+        }
 
         Ok(())
     }
 
-    default fn is_token_listed(&self, contract_address: AccountId, token_id: Id) -> bool {
+    default fn collection_royalty(
+        &self,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+    ) -> Result<Option<(AccountId, u16)>, MarketplaceError> {
+        if let Some(royalty) = royalty {
+            self.check_fee(royalty, self.data::<Data>().max_fee)?;
+            let royalty_receiver =
+                royalty_receiver.ok_or(MarketplaceError::RoyaltyReceiverNotSet)?;
+            return Ok(Some((royalty_receiver, royalty)))
+        }
+
+        Ok(None)
+    }
+
+    default fn registered_collection(
+        &self,
+        contract_address: AccountId,
+    ) -> Result<RegisteredCollection, MarketplaceError> {
         self.data::<Data>()
-            .items
-            .get(&(contract_address, token_id))
-            .is_some()
+            .registered_collections
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NotRegisteredContract)
     }
 
-    default fn transfer_token(
+    default fn check_fee(&self, fee: u16, max_fee: u16) -> Result<(), MarketplaceError> {
+        ensure!(
+            fee <= max_fee,
+            MarketplaceError::FeeTooHigh {
+                max: max_fee,
+                got: fee,
+            }
+        );
+
+        Ok(())
+    }
+
+    default fn check_listing_gate(
         &self,
         contract_address: AccountId,
-        token_id: Id,
-        token_owner: AccountId,
+        token_id: &Id,
         buyer: AccountId,
-        seller_fee: Balance,
-        marketplace_fee: Balance,
-        royalty_receiver: AccountId,
-        author_royalty: Balance,
-        token_price: Balance,
     ) -> Result<(), MarketplaceError> {
-        match PSP34Ref::transfer(&contract_address, buyer, token_id.clone(), Vec::new()) {
-            Ok(()) => {
-                Self::env()
-                    .transfer(token_owner, seller_fee)
-                    .map_err(|_| MarketplaceError::TransferToOwnerFailed)?;
-                Self::env()
-                    .transfer(
-                        self.data::<Data>().market_fee_recipient.unwrap(),
-                        marketplace_fee,
-                    )
-                    .map_err(|_| MarketplaceError::TransferToMarketplaceFailed)?;
-                Self::env()
-                    .transfer(royalty_receiver, author_royalty)
-                    .map_err(|_| MarketplaceError::TransferToAuthorFailed)?;
-                self.emit_token_bought_event(
-                    contract_address,
-                    token_id,
-                    token_price,
-                    token_owner,
-                    buyer,
-                );
-                Ok(())
+        if let Some(gate_collection) = self
+            .data::<Data>()
+            .listing_gates
+            .get(&(contract_address, token_key(token_id)))
+        {
+            ensure!(
+                contract_calls::balance_of(gate_collection, buyer) > 0,
+                MarketplaceError::GateTokenRequired
+            );
+        }
+
+        Ok(())
+    }
+
+    default fn check_tick_size(&self, price: Balance) -> Result<(), MarketplaceError> {
+        let tick_size = self.data::<Data>().v1.tick_size;
+        ensure!(
+            tick_size == 0 || price % tick_size == 0,
+            MarketplaceError::PriceNotOnTick { tick_size }
+        );
+
+        Ok(())
+    }
+
+    default fn check_floor_guard(
+        &self,
+        contract_address: AccountId,
+        price: Balance,
+        confirm_below_floor: bool,
+    ) -> Result<(), MarketplaceError> {
+        let max_below_floor = match self.data::<Data>().floor_guards.get(&Self::env().caller()) {
+            Some(max_below_floor) if !confirm_below_floor => max_below_floor,
+            _ => return Ok(()),
+        };
+        if let Some(floor) = self.data::<Data>().floor_prices.get(&contract_address) {
+            ensure!(
+                price >= floor - floor.saturating_mul(max_below_floor as u128) / 10_000,
+                MarketplaceError::PriceBelowFloor { floor }
+            );
+        }
+
+        Ok(())
+    }
+
+    default fn remove_item(&mut self, contract_address: AccountId, token_id: &Id) {
+        let key = (contract_address, token_key(token_id));
+        if let Some(item) = self.data::<Data>().items.get(&key) {
+            self.data::<Data>().items.remove(&key);
+            self.data::<Data>().listing_children.remove(&key);
+            self.data::<Data>().physical_listings.remove(&key);
+            self.data::<Data>().listing_gates.remove(&key);
+            self.remove_featured_listing(contract_address, token_id);
+            // only native currency prices are comparable
+            let native = self.listing_currency(contract_address, token_id) == Currency::Native
+                && !self.is_usd_listing(contract_address, token_id);
+            self.update_listing_prices(
+                contract_address,
+                token_id,
+                native.then_some(item.price),
+                None,
+            );
+            self.data::<Data>().listing_currencies.remove(&key);
+            self.data::<Data>().usd_listings.remove(&key);
+            self.data::<Data>().escrowed_listings.remove(&key);
+            self.update_listing_indexes(item.owner, contract_address, token_id, false);
+            let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+            stats.active_listings = stats.active_listings.saturating_sub(1);
+            self.data::<Data>().stats.set(&stats);
+        }
+    }
+
+    default fn update_listing_indexes(
+        &mut self,
+        owner: AccountId,
+        contract_address: AccountId,
+        token_id: &Id,
+        listed: bool,
+    ) {
+        let listing = (contract_address, token_key(token_id));
+        let data = self.data::<Data>();
+        // listings in any currency resolve their hashed ids
+        if listing.1 != *token_id {
+            if listed {
+                data.hashed_token_ids.insert(&listing, token_id);
+            } else {
+                data.hashed_token_ids.remove(&listing);
             }
-            Err(_) => Err(MarketplaceError::UnableToTransferToken),
+        }
+        if data.account_listing_index.contains_value(&owner, &listing) == listed {
+            return
+        }
+
+        if listed {
+            data.account_listing_index.insert(&owner, &listing);
+            data.collection_listing_index
+                .insert(&contract_address, &listing.1);
+        } else {
+            data.account_listing_index.remove_value(&owner, &listing);
+            data.collection_listing_index
+                .remove_value(&contract_address, &listing.1);
         }
     }
 
-    default fn get_nft_contract_hash(
+    default fn update_listing_prices(
+        &mut self,
+        contract_address: AccountId,
+        token_id: &Id,
+        old_price: Option<Balance>,
+        new_price: Option<Balance>,
+    ) {
+        if old_price.is_none() && new_price.is_none() {
+            return
+        }
+
+        let key = token_key(token_id);
+        let data = self.data::<Data>();
+        let mut bitmap = data
+            .listing_price_bitmaps
+            .get(&contract_address)
+            .unwrap_or_default();
+        if let Some(old_price) = old_price {
+            let bucket = price_bucket(old_price);
+            data.listing_price_buckets
+                .remove_value(&(contract_address, bucket), &(old_price, key.clone()));
+            if data
+                .listing_price_buckets
+                .count(&(contract_address, bucket))
+                == 0
+            {
+                bitmap[bucket as usize / 128] &= !(1 << (bucket % 128));
+            }
+        }
+        if let Some(new_price) = new_price {
+            let bucket = price_bucket(new_price);
+            data.listing_price_buckets
+                .insert(&(contract_address, bucket), &(new_price, key.clone()));
+            bitmap[bucket as usize / 128] |= 1 << (bucket % 128);
+        }
+        if bitmap == Default::default() {
+            data.listing_price_bitmaps.remove(&contract_address);
+        } else {
+            data.listing_price_bitmaps
+                .insert(&contract_address, &bitmap);
+        }
+
+        // the floor only has to be searched for when its listing goes away
+        let floor = data.floor_prices.get(&contract_address);
+        let floor = if old_price.is_some() && old_price == floor {
+            self.next_price_bucket(contract_address, 0)
+                .and_then(|bucket| {
+                    self.bucket_listings(contract_address, bucket)
+                        .first()
+                        .map(|(price, _)| *price)
+                })
+        } else {
+            match (floor, new_price) {
+                (Some(floor), Some(new_price)) => Some(floor.min(new_price)),
+                (floor, new_price) => floor.or(new_price),
+            }
+        };
+        if let Some(floor) = floor {
+            self.data::<Data>()
+                .floor_prices
+                .insert(&contract_address, &floor);
+        } else {
+            self.data::<Data>().floor_prices.remove(&contract_address);
+        }
+    }
+
+    default fn next_price_bucket(&self, contract_address: AccountId, from: u16) -> Option<u16> {
+        let bitmap = self
+            .data::<Data>()
+            .listing_price_bitmaps
+            .get(&contract_address)?;
+        let mut word = from as usize / 128;
+        // bits of buckets below `from` are masked out of its word
+        let mut bits = bitmap.get(word)? & (u128::MAX << (from % 128));
+        loop {
+            if bits != 0 {
+                return Some((word * 128) as u16 + bits.trailing_zeros() as u16)
+            }
+            word += 1;
+            bits = *bitmap.get(word)?;
+        }
+    }
+
+    default fn bucket_listings(
         &self,
-        contract_type: &NftContractType,
-    ) -> Result<Hash, MarketplaceError> {
+        contract_address: AccountId,
+        bucket: u16,
+    ) -> Vec<(Balance, Id)> {
+        let listings = &self.data::<Data>().listing_price_buckets;
+        let key = (contract_address, bucket);
+        let mut prices: Vec<(Balance, Id)> = (0..listings.count(&key))
+            .filter_map(|index| listings.get_value(&key, &index))
+            .collect();
+        prices.sort_by(|(a, _), (b, _)| a.cmp(b));
+        prices
+    }
+
+    default fn listed_token_id(&self, contract_address: AccountId, key: Id) -> Option<Id> {
+        match &key {
+            Id::Bytes(bytes) if bytes.len() > MAX_KEY_ID_LEN => self
+                .data::<Data>()
+                .hashed_token_ids
+                .get(&(contract_address, key)),
+            _ => Some(key),
+        }
+    }
+
+    default fn record_sale_stats(
+        &mut self,
+        price: Balance,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) {
+        let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+        stats.total_sales = stats.total_sales.saturating_add(1);
+        if currency == Currency::Native {
+            stats.total_volume = stats.total_volume.saturating_add(price);
+            stats.total_fees = stats.total_fees.saturating_add(marketplace_fee);
+        }
+        self.data::<Data>().stats.set(&stats);
+    }
+
+    default fn is_token_listed(&self, contract_address: AccountId, token_id: &Id) -> bool {
         self.data::<Data>()
-            .nft_contract_hash
-            .get(&contract_type)
-            .ok_or(MarketplaceError::NftContractHashNotSet)
+            .items
+            .contains(&(contract_address, token_key(token_id)))
     }
 
-    default fn get_deposit_internal(&self, account_id: AccountId) -> Balance {
-        self.data::<Data>().deposit.get(&account_id).unwrap_or(0)
+    default fn listing_currency(&self, contract_address: AccountId, token_id: &Id) -> Currency {
+        self.data::<Data>()
+            .listing_currencies
+            .get(&(contract_address, token_key(token_id)))
+            .unwrap_or(Currency::Native)
+    }
+
+    default fn is_usd_listing(&self, contract_address: AccountId, token_id: &Id) -> bool {
+        self.data::<Data>()
+            .usd_listings
+            .contains(&(contract_address, token_key(token_id)))
+    }
+
+    default fn is_escrowed_listing(&self, contract_address: AccountId, token_id: &Id) -> bool {
+        self.data::<Data>()
+            .escrowed_listings
+            .contains(&(contract_address, token_key(token_id)))
+    }
+
+    default fn list_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        currency: Currency,
+        usd_denominated: bool,
+        escrowed: bool,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_token_owner(contract_address, &token_id)?;
+        self.check_not_disputed(contract_address, &token_id)?;
+        if self.data::<Data>().v1.curated {
+            self.registered_collection(contract_address)?;
+            ensure!(
+                self.data::<Data>()
+                    .verified_collections
+                    .contains(&contract_address),
+                MarketplaceError::CollectionNotVerified
+            );
+        }
+        if currency == Currency::Native && !usd_denominated {
+            self.check_tick_size(price)?;
+        }
+
+        // relisting by the same owner updates the listing
+        let key = (contract_address, token_key(&token_id));
+        let old_item = self.data::<Data>().items.get(&key);
+        // only native currency prices are comparable
+        let old_native_price = old_item
+            .as_ref()
+            .filter(|_| {
+                self.listing_currency(contract_address, &token_id) == Currency::Native
+                    && !self.is_usd_listing(contract_address, &token_id)
+            })
+            .map(|item| item.price);
+        let new_native_price = (currency == Currency::Native && !usd_denominated).then_some(price);
+        self.update_listing_prices(
+            contract_address,
+            &token_id,
+            old_native_price,
+            new_native_price,
+        );
+        if old_item.is_none() {
+            let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+            stats.active_listings += 1;
+            self.data::<Data>().stats.set(&stats);
+        }
+        if let Some(old_item) = old_item.as_ref() {
+            self.update_listing_indexes(old_item.owner, contract_address, &token_id, false);
+        }
+        self.update_listing_indexes(caller, contract_address, &token_id, true);
+        let old_price = old_item
+            .filter(|item| item.owner == caller)
+            .map(|item| item.price);
+        self.data::<Data>().items.insert(
+            &key,
+            &Item {
+                owner: caller,
+                price,
+            },
+        );
+        if currency == Currency::Native {
+            self.data::<Data>().listing_currencies.remove(&key);
+        } else {
+            self.data::<Data>()
+                .listing_currencies
+                .insert(&key, &currency);
+        }
+        if usd_denominated {
+            self.data::<Data>().usd_listings.insert(&key, &());
+        } else {
+            self.data::<Data>().usd_listings.remove(&key);
+        }
+        if escrowed {
+            self.data::<Data>().escrowed_listings.insert(&key, &());
+        } else {
+            self.data::<Data>().escrowed_listings.remove(&key);
+        }
+        // delivery terms and gate are set again when relisted
+        self.data::<Data>().physical_listings.remove(&key);
+        self.data::<Data>().listing_gates.remove(&key);
+        self.record_listing_children(contract_address, &token_id);
+
+        match old_price {
+            Some(old_price) => self.emit_listing_updated_event(
+                contract_address,
+                token_id,
+                old_price,
+                price,
+                currency,
+            ),
+            None => self.emit_token_listed_event(contract_address, token_id, Some(price)),
+        }
+        Ok(())
+    }
+
+    default fn usd_to_native(&self, usd_price: Balance) -> Result<Balance, MarketplaceError> {
+        let price_oracle = self
+            .data::<Data>()
+            .v1
+            .price_oracle
+            .ok_or(MarketplaceError::OracleNotSet)?;
+        let (timestamp, price) = PriceOracleRef::get_latest_price(
+            &price_oracle,
+            self.data::<Data>().oracle_pair.get().unwrap_or_default(),
+        )
+        .ok_or(MarketplaceError::OraclePriceUnavailable)?;
+        ensure!(price > 0, MarketplaceError::OraclePriceUnavailable);
+        ensure!(
+            Self::env().block_timestamp().saturating_sub(timestamp)
+                <= self.data::<Data>().v1.max_price_age,
+            MarketplaceError::OraclePriceStale
+        );
+
+        // Oracle price is USD for one unit of native currency with the same decimals as USD prices.
+        Ok(usd_price
+            .checked_mul(NATIVE_UNIT)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / price)
+    }
+
+    default fn get_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+        price: Balance,
+    ) -> Result<(AccountId, Balance), MarketplaceError> {
+        let collection = self
+            .data::<Data>()
+            .registered_collections
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NotRegisteredContract)?;
+
+        // royalty from the shared registry is capped at the marketplace max fee
+        let registry_royalty = self
+            .data::<Data>()
+            .v1
+            .royalty_registry
+            .and_then(|registry| RoyaltyRegistryRef::get_royalty(&registry, contract_address))
+            .map(|(receiver, royalty)| (receiver, royalty.min(self.data::<Data>().max_fee)));
+        if let Some(royalty) = registry_royalty.or(collection.royalty) {
+            return Ok((
+                royalty.0,
+                price
+                    .checked_mul(royalty.1 as u128)
+                    .ok_or(MarketplaceError::ArithmeticOverflow)?
+                    / 10_000,
+            ))
+        }
+
+        // only support 1 for now
+        if let (NftContractType::NFTSeries, Id::U64(token_id_number)) =
+            (collection.contract_type, token_id)
+        {
+            if let Ok(payouts) =
+                NFTSeriesRef::royalty_info(&contract_address, *token_id_number, price)
+            {
+                if let Some(payout) = payouts.get(0) {
+                    return Ok((payout.0, payout.1))
+                }
+            }
+        }
+        Ok((contract_address, 0))
+    }
+
+    default fn buy_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError> {
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        // Listings priced in wrapped native token can be bought with native balance too.
+        let wrapped_native = self.data::<Data>().v1.wrapped_native.map(Currency::Psp22);
+        let currency = self.listing_currency(contract_address, &token_id);
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && (currency == Currency::Native || Some(currency) == wrapped_native),
+            MarketplaceError::CurrencyMismatch
+        );
+
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let caller = Self::env().caller();
+        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+
+        let value = Self::env().transferred_value();
+        self.check_price(value, self.flash_sale_price(contract_address, &item))?;
+        if currency != Currency::Native {
+            self.wrap_native(value)?;
+        }
+
+        self.settle_purchase(
+            contract_address,
+            token_id,
+            token_owner,
+            caller,
+            value,
+            attribution,
+        )
+    }
+
+    default fn buy_item_with_psp22(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().supported_currencies.contains(&currency),
+            MarketplaceError::CurrencyNotSupported
+        );
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        // Native listings can be bought with wrapped native token too.
+        let listing_currency = self.listing_currency(contract_address, &token_id);
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && (listing_currency == Currency::Psp22(currency)
+                    || (listing_currency == Currency::Native
+                        && self.data::<Data>().v1.wrapped_native == Some(currency))),
+            MarketplaceError::CurrencyMismatch
+        );
+
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let caller = Self::env().caller();
+        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+
+        let price = self.flash_sale_price(contract_address, &item);
+        PSP22Ref::transfer_from(
+            &currency,
+            caller,
+            Self::env().account_id(),
+            price,
+            Vec::new(),
+        )
+        .map_err(|_| MarketplaceError::TransferFromBuyerFailed)?;
+        if listing_currency == Currency::Native {
+            self.unwrap_native(price)?;
+        }
+
+        self.settle_purchase(
+            contract_address,
+            token_id,
+            token_owner,
+            caller,
+            price,
+            SaleAttribution::default(),
+        )
+    }
+
+    default fn buy_from_deposit(
+        &mut self,
+        buyer: AccountId,
+        contract_address: AccountId,
+        token_id: Id,
+        spend_credit: bool,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError> {
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id),
+            MarketplaceError::CurrencyMismatch
+        );
+
+        let token_owner = self
+            .token_owner(contract_address, &token_id, item.owner)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        ensure!(token_owner != buyer, MarketplaceError::AlreadyOwner);
+
+        let price = self.flash_sale_price(contract_address, &item);
+        // credits are issued in native currency
+        let currency = self.listing_currency(contract_address, &token_id);
+        let from_credit = if spend_credit && currency == Currency::Native {
+            self.consume_credit(buyer, price)
+        } else {
+            0
+        };
+        let from_deposit = price - from_credit;
+        let deposit = self.get_currency_deposit(buyer, currency);
+        ensure!(
+            deposit >= from_deposit,
+            MarketplaceError::BalanceInsufficient
+        );
+        self.set_currency_deposit(buyer, currency, deposit - from_deposit);
+
+        self.settle_purchase(
+            contract_address,
+            token_id,
+            token_owner,
+            buyer,
+            price,
+            attribution,
+        )
+    }
+
+    default fn settle_purchase(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError> {
+        self.check_listing_gate(contract_address, &token_id, buyer)?;
+        let currency = self.listing_currency(contract_address, &token_id);
+        if self.is_escrowed_listing(contract_address, &token_id) {
+            return self.open_escrow(
+                contract_address,
+                token_id,
+                token_owner,
+                buyer,
+                price,
+                currency,
+                attribution,
+            )
+        }
+
+        self.settle_sale(
+            contract_address,
+            token_id,
+            token_owner,
+            buyer,
+            price,
+            currency,
+            attribution,
+        )
+    }
+
+    default fn insert_supported_currency(
+        &mut self,
+        currency: AccountId,
+        metadata: CurrencyMetadata,
+    ) {
+        if !self.data::<Data>().supported_currencies.contains(&currency) {
+            let mut currencies = self
+                .data::<Data>()
+                .supported_currency_list
+                .get()
+                .unwrap_or_default();
+            currencies.push(currency);
+            self.data::<Data>().supported_currency_list.set(&currencies);
+        }
+        self.data::<Data>()
+            .supported_currencies
+            .insert(&currency, &metadata);
+    }
+
+    default fn remove_currency(&mut self, currency: AccountId) {
+        self.data::<Data>().supported_currencies.remove(&currency);
+        let mut currencies = self
+            .data::<Data>()
+            .supported_currency_list
+            .get()
+            .unwrap_or_default();
+        currencies.retain(|supported| *supported != currency);
+        self.data::<Data>().supported_currency_list.set(&currencies);
+    }
+
+    default fn is_xvm_collection(&self, contract_address: AccountId) -> bool {
+        matches!(
+            self.data::<Data>()
+                .registered_collections
+                .get(&contract_address),
+            Some(RegisteredCollection {
+                contract_type: NftContractType::Erc721Xvm,
+                ..
+            })
+        )
+    }
+
+    default fn token_owner(
+        &self,
+        contract_address: AccountId,
+        token_id: &Id,
+        account: AccountId,
+    ) -> Option<AccountId> {
+        if !self.is_xvm_collection(contract_address) {
+            return contract_calls::owner_of(contract_address, token_id)
+        }
+
+        match xvm::owner_of(contract_address, token_id) {
+            Ok(owner) if owner == xvm::evm_address(&account) => Some(account),
+            _ => None,
+        }
+    }
+
+    default fn token_approved(
+        &self,
+        contract_address: AccountId,
+        owner: AccountId,
+        token_id: &Id,
+    ) -> bool {
+        let operator = Self::env().account_id();
+        if !self.is_xvm_collection(contract_address) {
+            return contract_calls::allowance(contract_address, owner, operator, token_id)
+        }
+
+        xvm::is_approved(
+            contract_address,
+            xvm::evm_address(&owner),
+            xvm::evm_address(&operator),
+            token_id,
+        )
+        .unwrap_or(false)
+    }
+
+    default fn transfer_nft(
+        &mut self,
+        contract_address: AccountId,
+        from: AccountId,
+        to: AccountId,
+        token_id: &Id,
+    ) -> Result<(), MarketplaceError> {
+        if !self.is_xvm_collection(contract_address) {
+            // tokens sold out of custody (escrow, installments) leave the custody ledger too
+            return self.release_custody(contract_address, to, token_id.clone())
+        }
+
+        xvm::transfer_from(
+            contract_address,
+            xvm::evm_address(&from),
+            xvm::evm_address(&to),
+            token_id,
+        )
+    }
+
+    default fn take_custody(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self.is_xvm_collection(contract_address),
+            MarketplaceError::XvmCustodyNotSupported
+        );
+        self.remove_item(contract_address, &token_id);
+        self.data::<Data>().custody_transfer.set(&true);
+        let result = contract_calls::transfer(contract_address, Self::env().account_id(), token_id);
+        self.data::<Data>().custody_transfer.set(&false);
+        result?;
+        Ok(())
+    }
+
+    default fn settle_sale(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Currency,
+        attribution: SaleAttribution,
+    ) -> Result<(), MarketplaceError> {
+        self.check_not_blacklisted(token_owner)?;
+        self.check_not_blacklisted(buyer)?;
+        self.check_compliance(token_owner)?;
+        self.check_compliance(buyer)?;
+        self.fee_recipient()?;
+        self.check_nested_token(contract_address, &token_id)?;
+        self.check_not_disputed(contract_address, &token_id)?;
+        self.check_collection_not_paused(contract_address)?;
+
+        let curator = attribution.curator.or_else(|| {
+            self.data::<Data>()
+                .featured_listings
+                .get(&(contract_address, token_key(&token_id)))
+        });
+        // sold token is no longer for sale
+        self.remove_item(contract_address, &token_id);
+
+        let partner = attribution.partner.and_then(|partner_id| {
+            self.data::<Data>()
+                .partners
+                .get(&partner_id)
+                .map(|partner| (partner_id, partner))
+        });
+        let fee = partner
+            .as_ref()
+            .and_then(|(_, partner)| partner.fee_override)
+            .map_or(self.data::<Data>().fee, |fee| {
+                fee.min(self.data::<Data>().max_fee)
+            });
+        // subscribed sellers pay the fee of their tier when it is lower
+        let fee = self
+            .subscription_fee(token_owner)
+            .map_or(fee, |subscription_fee| subscription_fee.min(fee));
+        let marketplace_fee = price
+            .checked_mul(fee as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?
+            / 10_000;
+
+        let (author_address, author_royalty) =
+            self.get_royalty(contract_address, &token_id, price)?;
+
+        let seller_fee = price
+            .checked_sub(marketplace_fee)
+            .and_then(|amount| amount.checked_sub(author_royalty))
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let partner_fee = match partner {
+            Some((partner_id, partner)) => {
+                self.accrue_partner_fee(partner_id, &partner, marketplace_fee, currency)
+            }
+            None => 0,
+        };
+        let curator_fee = match curator {
+            Some(curator) => {
+                self.accrue_curator_fee(curator, marketplace_fee - partner_fee, currency)
+            }
+            None => 0,
+        };
+        let referral_fee = match attribution.referrer {
+            Some(referrer) => self.accrue_referral_fee(
+                referrer,
+                marketplace_fee - partner_fee - curator_fee,
+                currency,
+            ),
+            None => 0,
+        };
+        let shared_fee = partner_fee + curator_fee + referral_fee;
+        let staking_fee = self.accrue_staking_fee(marketplace_fee - shared_fee, currency);
+
+        self.transfer_token(
+            contract_address,
+            token_id,
+            token_owner,
+            buyer,
+            seller_fee,
+            marketplace_fee - shared_fee - staking_fee,
+            author_address,
+            author_royalty,
+            price,
+            currency,
+        )?;
+        self.record_sale_volume(contract_address, price, currency);
+        Ok(())
+    }
+
+    default fn transfer_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        seller_fee: Balance,
+        marketplace_fee: Balance,
+        royalty_receiver: AccountId,
+        author_royalty: Balance,
+        token_price: Balance,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError> {
+        self.transfer_nft(contract_address, token_owner, buyer, &token_id)?;
+        self.transfer_proceeds(currency, token_owner, seller_fee)?;
+        self.transfer_funds(
+            currency,
+            self.fee_recipient()?,
+            marketplace_fee,
+            MarketplaceError::TransferToMarketplaceFailed,
+        )?;
+        self.transfer_funds(
+            currency,
+            royalty_receiver,
+            author_royalty,
+            MarketplaceError::TransferToAuthorFailed,
+        )?;
+        let sale_id = self.data::<Data>().v1.last_sale_id + 1;
+        self.data::<Data>().v1.last_sale_id = sale_id;
+
+        let excluded = self.track_sale(
+            sale_id,
+            contract_address,
+            &token_id,
+            token_owner,
+            buyer,
+            seller_fee,
+            royalty_receiver,
+            author_royalty,
+            currency,
+        );
+        if !excluded {
+            self.record_sale_stats(token_price, marketplace_fee, currency);
+        }
+        // earnings are kept in native currency only, like the sales volume
+        if !excluded && currency == Currency::Native {
+            self.record_earnings(
+                contract_address,
+                token_owner,
+                seller_fee,
+                royalty_receiver,
+                author_royalty,
+            );
+        }
+        self.emit_token_bought_event(
+            sale_id,
+            contract_address,
+            token_id,
+            token_price,
+            token_owner,
+            buyer,
+            marketplace_fee,
+            royalty_receiver,
+            author_royalty,
+            currency,
+        );
+        Ok(())
+    }
+
+    default fn transfer_funds(
+        &self,
+        currency: Currency,
+        to: AccountId,
+        amount: Balance,
+        error: MarketplaceError,
+    ) -> Result<(), MarketplaceError> {
+        match currency {
+            Currency::Native => Self::env().transfer(to, amount).map_err(|_| error),
+            Currency::Psp22(currency) => {
+                PSP22Ref::transfer(&currency, to, amount, Vec::new()).map_err(|_| error)
+            }
+            Currency::Asset(asset_id) => assets::transfer(asset_id, to, amount).map_err(|_| error),
+        }
+    }
+
+    default fn transfer_proceeds(
+        &mut self,
+        currency: Currency,
+        seller: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        if let Some(wrapped_native) = self.data::<Data>().v1.wrapped_native {
+            if currency == Currency::Native
+                && self.data::<Data>().receive_wrapped_native.contains(&seller)
+            {
+                self.wrap_native(amount)?;
+                return self.transfer_funds(
+                    Currency::Psp22(wrapped_native),
+                    seller,
+                    amount,
+                    MarketplaceError::TransferToOwnerFailed,
+                )
+            }
+        }
+        self.transfer_funds(
+            currency,
+            seller,
+            amount,
+            MarketplaceError::TransferToOwnerFailed,
+        )
+    }
+
+    default fn wrap_native(&self, amount: Balance) -> Result<(), MarketplaceError> {
+        let wrapped_native = self
+            .data::<Data>()
+            .v1
+            .wrapped_native
+            .ok_or(MarketplaceError::WrappedNativeNotSet)?;
+
+        WnativeRef::deposit_builder(&wrapped_native)
+            .transferred_value(amount)
+            .try_invoke()
+            .map_err(|_| MarketplaceError::WrapFailed)?
+            .map_err(|_| MarketplaceError::WrapFailed)?
+            .map_err(|_| MarketplaceError::WrapFailed)
+    }
+
+    default fn unwrap_native(&self, amount: Balance) -> Result<(), MarketplaceError> {
+        let wrapped_native = self
+            .data::<Data>()
+            .v1
+            .wrapped_native
+            .ok_or(MarketplaceError::WrappedNativeNotSet)?;
+
+        WnativeRef::withdraw(&wrapped_native, amount).map_err(|_| MarketplaceError::UnwrapFailed)
+    }
+
+    default fn get_nft_contract_hash(
+        &self,
+        contract_type: &NftContractType,
+    ) -> Result<Hash, MarketplaceError> {
+        self.data::<Data>()
+            .nft_contract_hash
+            .get(&contract_type)
+            .ok_or(MarketplaceError::NftContractHashNotSet)
+    }
+
+    default fn remove_offer(&mut self, offer_id: u128, offer: &OfferItem) {
+        self.data::<Data>().offer_items.remove(&offer_id);
+        self.data::<Data>().offer_created_at.remove(&offer_id);
+        self.data::<Data>().offer_currencies.remove(&offer_id);
+        let mut stats = self.data::<Data>().stats.get().unwrap_or_default();
+        stats.active_offers = stats.active_offers.saturating_sub(1);
+        self.data::<Data>().stats.set(&stats);
+
+        // remove offer from enumerable
+        let key = (
+            offer.contract_address,
+            offer.token_id.as_ref().map(token_key),
+        );
+        self.data::<Data>()
+            .token_offer_index
+            .remove_value(&key, &offer_id);
+        self.data::<Data>()
+            .account_offer_index
+            .remove_value(&offer.bidder_id, &offer_id);
+    }
+
+    default fn offer_age(&self, offer_id: u128) -> u64 {
+        let created_at = self
+            .data::<Data>()
+            .offer_created_at
+            .get(&offer_id)
+            .unwrap_or_default();
+        Self::env().block_timestamp().saturating_sub(created_at)
+    }
+
+    default fn release_offer_bond(
+        &mut self,
+        offer_id: u128,
+        bidder: AccountId,
+        forfeit: bool,
+    ) -> Result<(), MarketplaceError> {
+        let bond = match self.data::<Data>().offer_bonds.get(&offer_id) {
+            Some(bond) => bond,
+            None => return Ok(()),
+        };
+        self.data::<Data>().offer_bonds.remove(&offer_id);
+        self.unlock_funds(bond.amount);
+
+        let recipient = if forfeit {
+            self.fee_recipient()?
+        } else {
+            bidder
+        };
+        let balance = self
+            .get_deposit_internal(recipient)
+            .checked_add(bond.amount)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        self.set_currency_deposit(recipient, Currency::Native, balance);
+
+        if forfeit {
+            self.emit_offer_bond_forfeited_event(offer_id, bond.amount);
+        }
+        Ok(())
+    }
+
+    default fn offer_currency(&self, offer_id: u128) -> Currency {
+        self.data::<Data>()
+            .offer_currencies
+            .get(&offer_id)
+            .unwrap_or(Currency::Native)
+    }
+
+    default fn get_deposit_internal(&self, account_id: AccountId) -> Balance {
+        self.get_currency_deposit(account_id, Currency::Native)
+    }
+
+    default fn get_locked_deposit(&self, account_id: AccountId) -> Balance {
+        let offers = &self.data::<Data>().account_offer_index;
+        (0..offers.count(&account_id))
+            .filter_map(|index| offers.get_value(&account_id, &index))
+            .filter_map(|offer_id| self.data::<Data>().offer_bonds.get(&offer_id))
+            .fold(0, |locked: Balance, bond| {
+                locked.saturating_add(bond.amount)
+            })
+    }
+
+    default fn get_offer_locked_deposit(
+        &self,
+        account_id: AccountId,
+        currency: Currency,
+    ) -> Balance {
+        let offers = &self.data::<Data>().account_offer_index;
+        (0..offers.count(&account_id))
+            .filter_map(|index| offers.get_value(&account_id, &index))
+            .filter(|offer_id| self.offer_currency(*offer_id) == currency)
+            .filter_map(|offer_id| self.data::<Data>().offer_items.get(&offer_id))
+            .map(|offer| (offer.quantity as u128).saturating_mul(offer.price_per_item))
+            .max()
+            .unwrap_or(0)
+    }
+
+    default fn get_currency_deposit(&self, account_id: AccountId, currency: Currency) -> Balance {
+        match currency {
+            Currency::Native => self.data::<Data>().deposit.get(&account_id).unwrap_or(0),
+            Currency::Psp22(currency) => self
+                .data::<Data>()
+                .psp22_deposit
+                .get(&(account_id, currency))
+                .unwrap_or(0),
+            Currency::Asset(asset_id) => self
+                .data::<Data>()
+                .asset_deposit
+                .get(&(account_id, asset_id))
+                .unwrap_or(0),
+        }
+    }
+
+    default fn set_currency_deposit(
+        &mut self,
+        account_id: AccountId,
+        currency: Currency,
+        amount: Balance,
+    ) {
+        match currency {
+            Currency::Native => {
+                let old_amount = self.get_deposit_internal(account_id);
+                self.update_deposits(old_amount, amount);
+                self.data::<Data>().deposit.insert(&account_id, &amount)
+            }
+            Currency::Psp22(currency) => self
+                .data::<Data>()
+                .psp22_deposit
+                .insert(&(account_id, currency), &amount),
+            Currency::Asset(asset_id) => self
+                .data::<Data>()
+                .asset_deposit
+                .insert(&(account_id, asset_id), &amount),
+        }
+    }
+
+    default fn withdraw_currency(
+        &mut self,
+        currency: Currency,
+        recipient: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(recipient)?;
+        self.check_compliance(caller)?;
+        self.check_compliance(recipient)?;
+        let current_balance = self.get_currency_deposit(caller, currency);
+        ensure!(
+            current_balance >= amount,
+            MarketplaceError::BalanceInsufficient
+        );
+
+        let new_balance = current_balance - amount;
+        ensure!(
+            new_balance >= self.get_offer_locked_deposit(caller, currency),
+            MarketplaceError::DepositLockedByOffer
+        );
+        self.set_currency_deposit(caller, currency, new_balance);
+        self.transfer_funds(
+            currency,
+            recipient,
+            amount,
+            MarketplaceError::TransferToOwnerFailed,
+        )?;
+
+        self.emit_withdraw_event(caller, currency, amount, new_balance);
+        Ok(())
+    }
+
+    default fn record_earnings(
+        &mut self,
+        contract_address: AccountId,
+        seller: AccountId,
+        seller_fee: Balance,
+        royalty_receiver: AccountId,
+        author_royalty: Balance,
+    ) {
+        // A sale by the royalty receiver is treated as a primary sale by the creator.
+        let is_primary_sale = seller == royalty_receiver;
+        if !is_primary_sale && author_royalty == 0 {
+            return
+        }
+
+        let mut creator = self
+            .data::<Data>()
+            .creator_earnings
+            .get(&royalty_receiver)
+            .unwrap_or_default();
+        let mut collection = self
+            .data::<Data>()
+            .collection_earnings
+            .get(&contract_address)
+            .unwrap_or_default();
+
+        if is_primary_sale {
+            creator.primary_sales = creator.primary_sales.saturating_add(seller_fee);
+            collection.primary_sales = collection.primary_sales.saturating_add(seller_fee);
+        }
+        creator.royalties = creator.royalties.saturating_add(author_royalty);
+        collection.royalties = collection.royalties.saturating_add(author_royalty);
+
+        self.data::<Data>()
+            .creator_earnings
+            .insert(&royalty_receiver, &creator);
+        self.data::<Data>()
+            .collection_earnings
+            .insert(&contract_address, &collection);
     }
 }
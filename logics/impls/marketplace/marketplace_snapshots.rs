@@ -0,0 +1,74 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Holder snapshots of factory created collections.
+//!
+//! Collections created by the marketplace factory implement PSP34Enumerable, so their holders
+//! can be read page by page through cross-contract calls, without an indexer.
+
+use super::{batch, contract_calls};
+use crate::{
+    ensure,
+    impls::marketplace::types::{BatchProgress, Data, MarketplaceError, NftContractType},
+    traits::snapshots::MarketplaceSnapshots,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Storage},
+};
+
+impl<T> MarketplaceSnapshots for T
+where
+    T: Storage<Data>,
+{
+    default fn snapshot_holders(
+        &self,
+        contract_address: AccountId,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<(Vec<(Id, AccountId)>, BatchProgress), MarketplaceError> {
+        let collection = self
+            .data::<Data>()
+            .registered_collections
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NotRegisteredContract)?;
+        ensure!(
+            collection.contract_type == NftContractType::NFTSeries,
+            MarketplaceError::CollectionNotEnumerable
+        );
+
+        let total_supply = contract_calls::total_supply(contract_address);
+        let (range, progress) = batch::next_batch(
+            total_supply.min(u32::MAX as u128) as usize,
+            cursor,
+            limit.min(self.data::<Data>().v1.max_batch_size),
+        )?;
+        let holders = range
+            .filter_map(|index| contract_calls::token_by_index(contract_address, index as u128))
+            .filter_map(|token_id| {
+                contract_calls::owner_of(contract_address, &token_id).map(|owner| (token_id, owner))
+            })
+            .collect();
+
+        Ok((holders, progress))
+    }
+}
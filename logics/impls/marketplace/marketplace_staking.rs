@@ -0,0 +1,319 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Fee-sharing staking for a governance token.
+//!
+//! Holders of the configured PSP22 governance token stake it in the marketplace to earn a
+//! share of native marketplace fees. Fees accrue per epoch and are distributed pro rata to
+//! the stake active during that epoch when the epoch ends. New stake becomes active from the
+//! next epoch, so staking right before an epoch ends does not earn the fees of that epoch.
+
+use super::{
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Stake},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::staking::MarketplaceStaking,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp22::PSP22Ref, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+/// Length of a staking epoch in milliseconds.
+pub const EPOCH_LENGTH: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Scale of rewards per staked token.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+pub trait Internal {
+    /// Ends the current epoch if it is over, distributing its rewards to active stake.
+    fn advance_epoch(&mut self);
+
+    /// Activates pending stake of an account and accrues its rewards.
+    fn update_stake(&self, account: AccountId) -> Stake;
+
+    /// Pays staking rewards of an account. Returns the amount paid.
+    fn pay_staking_rewards(&mut self, account: AccountId) -> Result<Balance, MarketplaceError>;
+
+    /// Accrues stakers' share of the marketplace fee. Returns the share.
+    fn accrue_staking_fee(&mut self, marketplace_fee: Balance, currency: Currency) -> Balance;
+}
+
+pub trait MarketplaceStakingEvents {
+    fn emit_staked_event(&self, account: AccountId, amount: Balance);
+    fn emit_unstaked_event(&self, account: AccountId, amount: Balance);
+    fn emit_staking_rewards_claimed_event(&self, account: AccountId, amount: Balance);
+}
+
+impl<T> MarketplaceStaking for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Sets PSP22 governance token stakers stake.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_staking_token(&mut self, token: AccountId) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().v1.total_staked == 0 && self.data::<Data>().v1.pending_staked == 0,
+            MarketplaceError::StakesOutstanding
+        );
+        self.data::<Data>().v1.staking_token = Some(token);
+        Ok(())
+    }
+
+    /// Gets staking token.
+    default fn get_staking_token(&self) -> Option<AccountId> {
+        self.data::<Data>().v1.staking_token
+    }
+
+    /// Sets share of native marketplace fees paid to stakers.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_staking_reward_share(&mut self, share: u16) -> Result<(), MarketplaceError> {
+        self.check_fee(share, 10_000)?;
+        self.data::<Data>().v1.staking_reward_share = share;
+        Ok(())
+    }
+
+    /// Gets share of native marketplace fees paid to stakers.
+    default fn get_staking_reward_share(&self) -> u16 {
+        self.data::<Data>().v1.staking_reward_share
+    }
+
+    /// Stakes governance tokens of the caller.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn stake(&mut self, amount: Balance) -> Result<(), MarketplaceError> {
+        let token = self
+            .data::<Data>()
+            .v1
+            .staking_token
+            .ok_or(MarketplaceError::StakingNotConfigured)?;
+        let caller = Self::env().caller();
+
+        PSP22Ref::transfer_from(&token, caller, Self::env().account_id(), amount, Vec::new())
+            .map_err(|_| MarketplaceError::StakeTransferFailed)?;
+
+        self.advance_epoch();
+        let mut stake = self.update_stake(caller);
+        stake.pending = stake.pending.saturating_add(amount);
+        stake.pending_epoch = self.data::<Data>().v1.staking_epoch + 1;
+        self.data::<Data>().stakes.insert(&caller, &stake);
+        self.data::<Data>().v1.pending_staked =
+            self.data::<Data>().v1.pending_staked.saturating_add(amount);
+
+        self.emit_staked_event(caller, amount);
+        Ok(())
+    }
+
+    /// Returns staked governance tokens to the caller once proposals it voted on ended.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn unstake(&mut self, amount: Balance) -> Result<(), MarketplaceError> {
+        let token = self
+            .data::<Data>()
+            .v1
+            .staking_token
+            .ok_or(MarketplaceError::StakingNotConfigured)?;
+        let caller = Self::env().caller();
+        ensure!(
+            Self::env().block_timestamp()
+                >= self
+                    .data::<Data>()
+                    .vote_locks
+                    .get(&caller)
+                    .unwrap_or_default(),
+            MarketplaceError::StakeLocked
+        );
+
+        self.advance_epoch();
+        let mut stake = self.update_stake(caller);
+        ensure!(
+            amount <= stake.pending.saturating_add(stake.amount),
+            MarketplaceError::InsufficientStake
+        );
+
+        let from_pending = amount.min(stake.pending);
+        let from_active = amount - from_pending;
+        stake.pending -= from_pending;
+        stake.amount -= from_active;
+        self.data::<Data>().v1.pending_staked -= from_pending;
+        self.data::<Data>().v1.total_staked -= from_active;
+        self.data::<Data>().stakes.insert(&caller, &stake);
+
+        PSP22Ref::transfer(&token, caller, amount, Vec::new())
+            .map_err(|_| MarketplaceError::StakeTransferFailed)?;
+
+        self.emit_unstaked_event(caller, amount);
+        Ok(())
+    }
+
+    /// Pays staking rewards of the caller.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_rewards(&mut self) -> Result<(), MarketplaceError> {
+        let amount = self.pay_staking_rewards(Self::env().caller())?;
+        ensure!(amount > 0, MarketplaceError::NoStakingRewards);
+        Ok(())
+    }
+
+    /// Gets stake of an account.
+    default fn get_stake(&self, account: AccountId) -> Stake {
+        self.data::<Data>().stakes.get(&account).unwrap_or_default()
+    }
+
+    /// Gets current staking epoch and its start.
+    default fn get_staking_epoch(&self) -> (u32, u64) {
+        (
+            self.data::<Data>().v1.staking_epoch,
+            self.data::<Data>().v1.staking_epoch_start,
+        )
+    }
+
+    /// Gets tokens earning rewards in the current epoch.
+    default fn get_total_staked(&self) -> Balance {
+        self.data::<Data>().v1.total_staked
+    }
+}
+
+impl<T> MarketplaceStakingEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_staked_event(&self, _account: AccountId, _amount: Balance) {}
+
+    default fn emit_unstaked_event(&self, _account: AccountId, _amount: Balance) {}
+
+    default fn emit_staking_rewards_claimed_event(&self, _account: AccountId, _amount: Balance) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn advance_epoch(&mut self) {
+        let now = Self::env().block_timestamp();
+        let data = self.data::<Data>();
+        if now < data.v1.staking_epoch_start.saturating_add(EPOCH_LENGTH) {
+            return
+        }
+
+        if data.v1.total_staked > 0 {
+            let increment =
+                data.v1.epoch_rewards.saturating_mul(REWARD_PRECISION) / data.v1.total_staked;
+            let distributed = increment.saturating_mul(data.v1.total_staked) / REWARD_PRECISION;
+            data.v1.reward_per_token = data.v1.reward_per_token.saturating_add(increment);
+            // rounding dust is carried over to the next epoch
+            data.v1.epoch_rewards -= distributed;
+        }
+
+        data.v1.staking_epoch += 1;
+        data.v1.staking_epoch_start = now;
+        data.v1.total_staked = data.v1.total_staked.saturating_add(data.v1.pending_staked);
+        data.v1.pending_staked = 0;
+        let (epoch, reward_per_token) = (data.v1.staking_epoch, data.v1.reward_per_token);
+        data.epoch_reward_per_token
+            .insert(&epoch, &reward_per_token);
+    }
+
+    default fn update_stake(&self, account: AccountId) -> Stake {
+        let mut stake = self.data::<Data>().stakes.get(&account).unwrap_or_default();
+        let earned = |stake: &Stake, reward_per_token: u128| {
+            stake
+                .amount
+                .saturating_mul(reward_per_token.saturating_sub(stake.reward_per_token_paid))
+                / REWARD_PRECISION
+        };
+
+        if stake.pending > 0 && stake.pending_epoch <= self.data::<Data>().v1.staking_epoch {
+            let activated_at = self
+                .data::<Data>()
+                .epoch_reward_per_token
+                .get(&stake.pending_epoch)
+                .unwrap_or_default();
+            stake.rewards = stake.rewards.saturating_add(earned(&stake, activated_at));
+            stake.reward_per_token_paid = activated_at;
+            stake.amount = stake.amount.saturating_add(stake.pending);
+            stake.pending = 0;
+        }
+
+        let reward_per_token = self.data::<Data>().v1.reward_per_token;
+        stake.rewards = stake
+            .rewards
+            .saturating_add(earned(&stake, reward_per_token));
+        stake.reward_per_token_paid = reward_per_token;
+        stake
+    }
+
+    default fn pay_staking_rewards(
+        &mut self,
+        account: AccountId,
+    ) -> Result<Balance, MarketplaceError> {
+        self.advance_epoch();
+        let mut stake = self.update_stake(account);
+        let amount = stake.rewards;
+        if amount == 0 {
+            return Ok(0)
+        }
+
+        stake.rewards = 0;
+        self.data::<Data>().stakes.insert(&account, &stake);
+        self.pay_accrued_fees(amount);
+        self.transfer_funds(
+            Currency::Native,
+            account,
+            amount,
+            MarketplaceError::TransferToStakerFailed,
+        )?;
+
+        self.emit_staking_rewards_claimed_event(account, amount);
+        Ok(amount)
+    }
+
+    default fn accrue_staking_fee(
+        &mut self,
+        marketplace_fee: Balance,
+        currency: Currency,
+    ) -> Balance {
+        let data = self.data::<Data>();
+        if currency != Currency::Native
+            || data.v1.staking_token.is_none()
+            || data.v1.total_staked.saturating_add(data.v1.pending_staked) == 0
+        {
+            return 0
+        }
+        let amount = marketplace_fee.saturating_mul(data.v1.staking_reward_share as u128) / 10_000;
+        if amount == 0 {
+            return 0
+        }
+
+        self.advance_epoch();
+        self.data::<Data>().v1.epoch_rewards =
+            self.data::<Data>().v1.epoch_rewards.saturating_add(amount);
+        self.accrue_fees(amount);
+        amount
+    }
+}
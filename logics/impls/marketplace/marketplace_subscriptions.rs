@@ -0,0 +1,217 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Seller subscriptions.
+//!
+//! Sellers pay for a subscription tier to sell at the reduced marketplace fee of the tier
+//! until the subscription expires. The fee of the tier is read at settlement, so plan updates
+//! apply to running subscriptions.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Subscription, SubscriptionPlan},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::{admin::MarketplaceAdmin, subscriptions::MarketplaceSubscriptions},
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp22::PSP22Ref, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Gets marketplace fee of the active subscription of an account, if any.
+    fn subscription_fee(&self, account: AccountId) -> Option<u16>;
+}
+
+pub trait MarketplaceSubscriptionsEvents {
+    fn emit_subscription_plan_updated_event(&self, tier: u32, plan: Option<SubscriptionPlan>);
+    fn emit_subscribed_event(
+        &self,
+        account: AccountId,
+        tier: u32,
+        expires_at: u64,
+        currency: Currency,
+        amount: Balance,
+    );
+}
+
+impl<T> MarketplaceSubscriptions for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_subscription_plan(
+        &mut self,
+        tier: u32,
+        plan: Option<SubscriptionPlan>,
+    ) -> Result<(), MarketplaceError> {
+        match &plan {
+            Some(plan) => {
+                self.check_fee(plan.fee, self.data::<Data>().max_fee)?;
+                ensure!(
+                    plan.price > 0
+                        && plan.period > 0
+                        && match plan.currency {
+                            Currency::Native => true,
+                            Currency::Psp22(currency) => self.is_supported_currency(currency),
+                            Currency::Asset(_) => false,
+                        },
+                    MarketplaceError::InvalidSubscriptionPlan
+                );
+                self.data::<Data>().subscription_plans.insert(&tier, plan);
+            }
+            None => self.data::<Data>().subscription_plans.remove(&tier),
+        }
+
+        self.emit_subscription_plan_updated_event(tier, plan);
+        Ok(())
+    }
+
+    default fn get_subscription_plan(&self, tier: u32) -> Option<SubscriptionPlan> {
+        self.data::<Data>().subscription_plans.get(&tier)
+    }
+
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn subscribe(&mut self, tier: u32, periods: u32) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_not_blacklisted(caller)?;
+        let plan = self
+            .data::<Data>()
+            .subscription_plans
+            .get(&tier)
+            .ok_or(MarketplaceError::SubscriptionPlanNotFound)?;
+        ensure!(periods > 0, MarketplaceError::InvalidSubscriptionPlan);
+
+        let now = Self::env().block_timestamp();
+        let starts_at = match self.data::<Data>().subscriptions.get(&caller) {
+            Some(subscription) if subscription.expires_at > now => {
+                ensure!(
+                    subscription.tier == tier,
+                    MarketplaceError::SubscriptionActive
+                );
+                subscription.expires_at
+            }
+            _ => now,
+        };
+        let amount = plan
+            .price
+            .checked_mul(periods as u128)
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+        let expires_at = plan
+            .period
+            .checked_mul(periods as u64)
+            .and_then(|duration| starts_at.checked_add(duration))
+            .ok_or(MarketplaceError::ArithmeticOverflow)?;
+
+        let value = Self::env().transferred_value();
+        let fee_recipient = self.fee_recipient()?;
+        match plan.currency {
+            Currency::Psp22(currency) => {
+                ensure!(
+                    value == 0,
+                    MarketplaceError::BadBuyValue {
+                        expected: 0,
+                        received: value,
+                    }
+                );
+                PSP22Ref::transfer_from(&currency, caller, fee_recipient, amount, Vec::new())
+                    .map_err(|_| MarketplaceError::TransferFromBuyerFailed)?;
+            }
+            _ => {
+                ensure!(
+                    value == amount,
+                    MarketplaceError::BadBuyValue {
+                        expected: amount,
+                        received: value,
+                    }
+                );
+                self.transfer_funds(
+                    Currency::Native,
+                    fee_recipient,
+                    amount,
+                    MarketplaceError::TransferToMarketplaceFailed,
+                )?;
+            }
+        }
+
+        self.data::<Data>()
+            .subscriptions
+            .insert(&caller, &Subscription { tier, expires_at });
+
+        self.emit_subscribed_event(caller, tier, expires_at, plan.currency, amount);
+        Ok(())
+    }
+
+    default fn get_subscription(&self, account: AccountId) -> Option<Subscription> {
+        self.data::<Data>()
+            .subscriptions
+            .get(&account)
+            .filter(|subscription| subscription.expires_at > Self::env().block_timestamp())
+    }
+}
+
+impl<T> MarketplaceSubscriptionsEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_subscription_plan_updated_event(
+        &self,
+        _tier: u32,
+        _plan: Option<SubscriptionPlan>,
+    ) {
+    }
+
+    default fn emit_subscribed_event(
+        &self,
+        _account: AccountId,
+        _tier: u32,
+        _expires_at: u64,
+        _currency: Currency,
+        _amount: Balance,
+    ) {
+    }
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn subscription_fee(&self, account: AccountId) -> Option<u16> {
+        let subscription = self.data::<Data>().subscriptions.get(&account)?;
+        if subscription.expires_at <= Self::env().block_timestamp() {
+            return None
+        }
+
+        self.data::<Data>()
+            .subscription_plans
+            .get(&subscription.tier)
+            .map(|plan| plan.fee)
+    }
+}
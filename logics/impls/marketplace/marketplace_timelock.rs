@@ -0,0 +1,231 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_multisig::Internal as MultisigInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{ParameterChange, PendingParameterChange},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::timelock::MarketplaceTimelock,
+};
+use openbrush::{
+    contracts::{access_control, ownable::*, pausable},
+    modifiers,
+    traits::Storage,
+};
+
+pub trait Internal {
+    /// Queues a parameter change, applying it right away if there is no timelock delay.
+    /// Returns id of the queued change.
+    fn queue_parameter_change(
+        &mut self,
+        change: ParameterChange,
+    ) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Checks a parameter change against the current parameters.
+    fn check_parameter_change(&self, change: &ParameterChange) -> Result<(), MarketplaceError>;
+
+    /// Validates and applies a parameter change.
+    fn apply_parameter_change(&mut self, change: ParameterChange) -> Result<(), MarketplaceError>;
+
+    /// Drops a queued parameter change.
+    fn remove_parameter_change(&mut self, change_id: u128) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceTimelockEvents {
+    fn emit_parameter_change_queued_event(
+        &self,
+        change_id: u128,
+        change: ParameterChange,
+        eta: u64,
+    );
+    fn emit_parameter_change_executed_event(&self, change_id: u128);
+    fn emit_parameter_change_cancelled_event(&self, change_id: u128);
+}
+
+impl<T> MarketplaceTimelock for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<access_control::Data>,
+{
+    /// Queues a change of the timelock delay.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_timelock_delay(&mut self, delay: u64) -> Result<Option<u128>, MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.queue_parameter_change(ParameterChange::TimelockDelay(delay))
+    }
+
+    /// Gets the timelock delay.
+    default fn get_timelock_delay(&self) -> u64 {
+        self.data::<Data>().timelock_delay.get().unwrap_or_default()
+    }
+
+    /// Applies a queued parameter change once its delay has passed.
+    #[modifiers(when_not_taking_custody)]
+    default fn execute_parameter_change(
+        &mut self,
+        change_id: u128,
+    ) -> Result<(), MarketplaceError> {
+        let pending = self
+            .data::<Data>()
+            .parameter_changes
+            .get(&change_id)
+            .ok_or(MarketplaceError::ParameterChangeNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() >= pending.eta,
+            MarketplaceError::TimelockNotExpired
+        );
+
+        self.data::<Data>().parameter_changes.remove(&change_id);
+        self.apply_parameter_change(pending.change)?;
+        self.emit_parameter_change_executed_event(change_id);
+        Ok(())
+    }
+
+    /// Cancels a queued parameter change.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn cancel_parameter_change(&mut self, change_id: u128) -> Result<(), MarketplaceError> {
+        self.ensure_no_multisig()?;
+        self.remove_parameter_change(change_id)
+    }
+
+    /// Gets a queued parameter change.
+    default fn get_parameter_change(&self, change_id: u128) -> Option<PendingParameterChange> {
+        self.data::<Data>().parameter_changes.get(&change_id)
+    }
+}
+
+impl<T> MarketplaceTimelockEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_parameter_change_queued_event(
+        &self,
+        _change_id: u128,
+        _change: ParameterChange,
+        _eta: u64,
+    ) {
+    }
+
+    default fn emit_parameter_change_executed_event(&self, _change_id: u128) {}
+
+    default fn emit_parameter_change_cancelled_event(&self, _change_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn queue_parameter_change(
+        &mut self,
+        change: ParameterChange,
+    ) -> Result<Option<u128>, MarketplaceError> {
+        self.check_parameter_change(&change)?;
+        let delay = self.data::<Data>().timelock_delay.get().unwrap_or_default();
+        if delay == 0 {
+            self.apply_parameter_change(change)?;
+            return Ok(None)
+        }
+
+        let change_id = self.data::<Data>().v1.last_parameter_change_id + 1;
+        let eta = Self::env().block_timestamp().saturating_add(delay);
+        self.data::<Data>().parameter_changes.insert(
+            &change_id,
+            &PendingParameterChange {
+                change: change.clone(),
+                eta,
+            },
+        );
+        self.data::<Data>().v1.last_parameter_change_id = change_id;
+
+        self.emit_parameter_change_queued_event(change_id, change, eta);
+        Ok(Some(change_id))
+    }
+
+    default fn check_parameter_change(
+        &self,
+        change: &ParameterChange,
+    ) -> Result<(), MarketplaceError> {
+        match change {
+            ParameterChange::MarketplaceFee(fee) => {
+                self.check_fee(*fee, self.data::<Data>().max_fee)
+            }
+            ParameterChange::MaxFee(max_fee) => {
+                self.check_fee(*max_fee, 10_000)?;
+                // the marketplace fee has to be lowered first
+                self.check_fee(self.data::<Data>().fee, *max_fee)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    default fn apply_parameter_change(
+        &mut self,
+        change: ParameterChange,
+    ) -> Result<(), MarketplaceError> {
+        // parameters may have changed while the change was queued
+        self.check_parameter_change(&change)?;
+        match change {
+            ParameterChange::MarketplaceFee(fee) => {
+                self.data::<Data>().fee = fee;
+            }
+            ParameterChange::FeeRecipient(fee_recipient) => {
+                self.data::<Data>().market_fee_recipient = Some(fee_recipient);
+            }
+            ParameterChange::MaxFee(max_fee) => {
+                self.data::<Data>().max_fee = max_fee;
+            }
+            ParameterChange::TimelockDelay(delay) => {
+                self.data::<Data>().timelock_delay.set(&delay);
+            }
+            ParameterChange::AddSupportedCurrency(currency, metadata) => {
+                self.insert_supported_currency(currency, metadata);
+            }
+            ParameterChange::RemoveSupportedCurrency(currency) => {
+                self.remove_currency(currency);
+            }
+            ParameterChange::CuratedMode(curated) => {
+                self.data::<Data>().v1.curated = curated;
+            }
+            ParameterChange::RoyaltyRegistry(registry) => {
+                self.data::<Data>().v1.royalty_registry = registry;
+            }
+        }
+        Ok(())
+    }
+
+    default fn remove_parameter_change(&mut self, change_id: u128) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().parameter_changes.contains(&change_id),
+            MarketplaceError::ParameterChangeNotFound
+        );
+
+        self.data::<Data>().parameter_changes.remove(&change_id);
+        self.emit_parameter_change_cancelled_event(change_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    batch, contract_calls,
+    marketplace_accounting::Internal as AccountingInternal,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_sale::Internal as SaleInternal,
+    types::{Currency, Trade, TradeAsset},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::trade::MarketplaceTrade,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{psp22::PSP22Ref, psp34::*, psp37::PSP37Ref, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Sums native currency of a trade side.
+    fn native_amount(&self, assets: &[TradeAsset]) -> Balance;
+
+    /// Transfers trade side assets. Native currency is paid out of the marketplace balance.
+    fn transfer_trade_assets(
+        &mut self,
+        assets: &[TradeAsset],
+        from: AccountId,
+        to: AccountId,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceTradeEvents {
+    fn emit_trade_proposed_event(&self, trade_id: u128, maker: AccountId, taker: Option<AccountId>);
+    fn emit_trade_accepted_event(&self, trade_id: u128, taker: AccountId);
+    fn emit_trade_cancelled_event(&self, trade_id: u128);
+}
+
+impl<T> MarketplaceTrade for T
+where
+    T: Storage<Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Proposes a trade of offered assets for requested ones.
+    #[modifiers(when_not_taking_custody)]
+    default fn propose_trade(
+        &mut self,
+        taker: Option<AccountId>,
+        offered: Vec<TradeAsset>,
+        requested: Vec<TradeAsset>,
+    ) -> Result<u128, MarketplaceError> {
+        self.check_not_blacklisted(Self::env().caller())?;
+        ensure!(
+            !offered.is_empty() && !requested.is_empty(),
+            MarketplaceError::EmptyTrade
+        );
+        // both sides are transferred when the trade is accepted
+        batch::check_batch_size(
+            offered.len() + requested.len(),
+            self.data::<Data>().v1.max_batch_size,
+        )?;
+        let expected = self.native_amount(&offered);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == expected,
+            MarketplaceError::BadBuyValue { expected, received }
+        );
+
+        let maker = Self::env().caller();
+        let trade_id = self.data::<Data>().v1.last_trade_id + 1;
+        self.data::<Data>().trades.insert(
+            &trade_id,
+            &Trade {
+                maker,
+                taker,
+                offered,
+                requested,
+            },
+        );
+        self.data::<Data>().v1.last_trade_id = trade_id;
+        self.lock_funds(Self::env().transferred_value());
+
+        self.emit_trade_proposed_event(trade_id, maker, taker);
+        Ok(trade_id)
+    }
+
+    /// Accepts a trade, atomically exchanging assets of both sides.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn accept_trade(&mut self, trade_id: u128) -> Result<(), MarketplaceError> {
+        let trade = self
+            .data::<Data>()
+            .trades
+            .get(&trade_id)
+            .ok_or(MarketplaceError::TradeNotFound)?;
+        let caller = Self::env().caller();
+        ensure!(
+            trade.taker.unwrap_or(caller) == caller,
+            MarketplaceError::NotTradeTaker
+        );
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(trade.maker)?;
+        let expected = self.native_amount(&trade.requested);
+        let received = Self::env().transferred_value();
+        ensure!(
+            received == expected,
+            MarketplaceError::BadBuyValue { expected, received }
+        );
+
+        self.data::<Data>().trades.remove(&trade_id);
+        self.unlock_funds(self.native_amount(&trade.offered));
+        self.transfer_trade_assets(&trade.offered, trade.maker, caller)?;
+        self.transfer_trade_assets(&trade.requested, caller, trade.maker)?;
+
+        self.emit_trade_accepted_event(trade_id, caller);
+        Ok(())
+    }
+
+    /// Cancels a trade and refunds native currency offered.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn cancel_trade(&mut self, trade_id: u128) -> Result<(), MarketplaceError> {
+        let trade = self
+            .data::<Data>()
+            .trades
+            .get(&trade_id)
+            .ok_or(MarketplaceError::TradeNotFound)?;
+        ensure!(
+            Self::env().caller() == trade.maker,
+            MarketplaceError::NotOwner
+        );
+
+        self.data::<Data>().trades.remove(&trade_id);
+        let amount = self.native_amount(&trade.offered);
+        self.unlock_funds(amount);
+        self.transfer_funds(
+            Currency::Native,
+            trade.maker,
+            amount,
+            MarketplaceError::RefundFailed,
+        )?;
+
+        self.emit_trade_cancelled_event(trade_id);
+        Ok(())
+    }
+
+    /// Gets trade.
+    default fn get_trade(&self, trade_id: u128) -> Option<Trade> {
+        self.data::<Data>().trades.get(&trade_id)
+    }
+}
+
+impl<T> MarketplaceTradeEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_trade_proposed_event(
+        &self,
+        _trade_id: u128,
+        _maker: AccountId,
+        _taker: Option<AccountId>,
+    ) {
+    }
+
+    default fn emit_trade_accepted_event(&self, _trade_id: u128, _taker: AccountId) {}
+
+    default fn emit_trade_cancelled_event(&self, _trade_id: u128) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn native_amount(&self, assets: &[TradeAsset]) -> Balance {
+        assets
+            .iter()
+            .map(|asset| match asset {
+                TradeAsset::Native(amount) => *amount,
+                _ => 0,
+            })
+            .fold(0, Balance::saturating_add)
+    }
+
+    default fn transfer_trade_assets(
+        &mut self,
+        assets: &[TradeAsset],
+        from: AccountId,
+        to: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        for asset in assets {
+            match asset {
+                TradeAsset::Psp34(contract_address, token_id) => {
+                    ensure!(
+                        contract_calls::owner_of(*contract_address, token_id) == Some(from),
+                        MarketplaceError::NotOwner
+                    );
+                    contract_calls::transfer(*contract_address, to, token_id.clone())
+                        .map_err(|_| MarketplaceError::TradeTransferFailed)?;
+                }
+                TradeAsset::Psp37(contract_address, token_id, amount) => {
+                    PSP37Ref::transfer_from(
+                        contract_address,
+                        from,
+                        to,
+                        token_id.clone(),
+                        *amount,
+                        Vec::new(),
+                    )
+                    .map_err(|_| MarketplaceError::TradeTransferFailed)?;
+                }
+                TradeAsset::Psp22(currency, amount) => {
+                    PSP22Ref::transfer_from(currency, from, to, *amount, Vec::new())
+                        .map_err(|_| MarketplaceError::TradeTransferFailed)?;
+                }
+                TradeAsset::Native(amount) => {
+                    self.transfer_funds(
+                        Currency::Native,
+                        to,
+                        *amount,
+                        MarketplaceError::TradeTransferFailed,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::{
+    marketplace_custody::when_not_taking_custody,
+    marketplace_moderation::Internal as ModerationInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, WashTrade},
+};
+use crate::{
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::wash_trading::MarketplaceWashTrading,
+};
+use openbrush::{
+    contracts::{access_control::*, ownable::*, psp34::Id},
+    modifiers,
+    traits::{AccountId, Balance, Storage},
+};
+
+pub trait Internal {
+    /// Records a sale and flags it if the token was already sold between the same accounts
+    /// within the wash trade window. Returns `true` if the sale is left out of stats and earnings.
+    fn track_sale(
+        &mut self,
+        sale_id: u128,
+        contract_address: AccountId,
+        token_id: &Id,
+        seller: AccountId,
+        buyer: AccountId,
+        seller_fee: Balance,
+        royalty_receiver: AccountId,
+        author_royalty: Balance,
+        currency: Currency,
+    ) -> bool;
+}
+
+pub trait MarketplaceWashTradingEvents {
+    fn emit_wash_trade_flagged_event(&self, wash_trade_id: u128, wash_trade: &WashTrade);
+    fn emit_wash_trade_cleared_event(&self, wash_trade_id: u128, moderator: AccountId);
+}
+
+impl<T> MarketplaceWashTrading for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<access_control::Data>,
+{
+    /// Sets wash trade window in milliseconds.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_wash_trade_window(&mut self, window: u64) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.wash_trade_window = window;
+        Ok(())
+    }
+
+    /// Gets wash trade window in milliseconds.
+    default fn get_wash_trade_window(&self) -> u64 {
+        self.data::<Data>().v1.wash_trade_window
+    }
+
+    /// Sets whether flagged sales are left out of earnings.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_exclude_wash_trades(&mut self, exclude: bool) -> Result<(), MarketplaceError> {
+        self.data::<Data>().v1.exclude_wash_trades = exclude;
+        Ok(())
+    }
+
+    /// Checks if flagged sales are left out of earnings.
+    default fn get_exclude_wash_trades(&self) -> bool {
+        self.data::<Data>().v1.exclude_wash_trades
+    }
+
+    /// Clears a wash trade flag.
+    #[modifiers(when_not_taking_custody)]
+    default fn clear_wash_trade(&mut self, wash_trade_id: u128) -> Result<(), MarketplaceError> {
+        self.check_moderator()?;
+        let wash_trade = self
+            .data::<Data>()
+            .wash_trades
+            .get(&wash_trade_id)
+            .ok_or(MarketplaceError::WashTradeNotFound)?;
+
+        self.data::<Data>().wash_trades.remove(&wash_trade_id);
+        if wash_trade.excluded {
+            self.record_earnings(
+                wash_trade.contract_address,
+                wash_trade.seller,
+                wash_trade.seller_fee,
+                wash_trade.royalty_receiver,
+                wash_trade.author_royalty,
+            );
+        }
+
+        self.emit_wash_trade_cleared_event(wash_trade_id, Self::env().caller());
+        Ok(())
+    }
+
+    /// Gets flagged wash trade.
+    default fn get_wash_trade(&self, wash_trade_id: u128) -> Option<WashTrade> {
+        self.data::<Data>().wash_trades.get(&wash_trade_id)
+    }
+}
+
+impl<T> MarketplaceWashTradingEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_wash_trade_flagged_event(&self, _wash_trade_id: u128, _wash_trade: &WashTrade) {
+    }
+
+    default fn emit_wash_trade_cleared_event(&self, _wash_trade_id: u128, _moderator: AccountId) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    default fn track_sale(
+        &mut self,
+        sale_id: u128,
+        contract_address: AccountId,
+        token_id: &Id,
+        seller: AccountId,
+        buyer: AccountId,
+        seller_fee: Balance,
+        royalty_receiver: AccountId,
+        author_royalty: Balance,
+        currency: Currency,
+    ) -> bool {
+        let window = self.data::<Data>().v1.wash_trade_window;
+        if window == 0 {
+            return false
+        }
+
+        // sales in either direction count for the same pair
+        let (first, second) = if seller < buyer {
+            (seller, buyer)
+        } else {
+            (buyer, seller)
+        };
+        let key = (contract_address, token_key(token_id), first, second);
+        let now = Self::env().block_timestamp();
+        let last_sale = self.data::<Data>().pair_sales.get(&key);
+        self.data::<Data>().pair_sales.insert(&key, &now);
+
+        match last_sale {
+            Some(last_sale) if now < last_sale.saturating_add(window) => {}
+            _ => return false,
+        }
+
+        let excluded = self.data::<Data>().v1.exclude_wash_trades;
+        let wash_trade_id = self.data::<Data>().v1.last_wash_trade_id + 1;
+        let wash_trade = WashTrade {
+            sale_id,
+            contract_address,
+            token_id: token_id.clone(),
+            seller,
+            buyer,
+            seller_fee,
+            royalty_receiver,
+            author_royalty,
+            // only native sales are counted in earnings, so clearing others adds nothing back
+            excluded: excluded && currency == Currency::Native,
+        };
+        self.data::<Data>()
+            .wash_trades
+            .insert(&wash_trade_id, &wash_trade);
+        self.data::<Data>().v1.last_wash_trade_id = wash_trade_id;
+
+        self.emit_wash_trade_flagged_event(wash_trade_id, &wash_trade);
+        excluded
+    }
+}
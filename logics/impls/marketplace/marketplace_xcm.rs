@@ -0,0 +1,213 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Purchases initiated from sibling parachains through XCM `Transact`.
+//!
+//! Purchases are only accepted when the `xcm` feature is enabled, otherwise they fail with
+//! `MarketplaceError::XcmNotSupported` so the contract can be deployed on runtimes without
+//! XCM support.
+
+use super::{
+    contract_calls,
+    marketplace_custody::when_not_taking_custody,
+    marketplace_custody::Internal as CustodyInternal,
+    marketplace_flash_sales::Internal as FlashSalesInternal,
+    marketplace_sale::Internal as SaleInternal,
+    types::{token_key, Currency, SaleAttribution, XcmReservation},
+};
+use crate::{
+    ensure,
+    impls::marketplace::types::{Data, MarketplaceError},
+    traits::xcm::MarketplaceXcm,
+};
+use openbrush::{
+    contracts::{ownable::*, pausable::*, psp34::*, reentrancy_guard::*},
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait Internal {
+    /// Checks that account is an allowed XCM origin.
+    fn check_xcm_origin(&self, origin: AccountId) -> Result<(), MarketplaceError>;
+}
+
+pub trait MarketplaceXcmEvents {
+    fn emit_xcm_purchase_reserved_event(
+        &self,
+        reservation_id: u128,
+        origin: AccountId,
+        beneficiary: AccountId,
+    );
+    fn emit_xcm_reservation_claimed_event(&self, reservation_id: u128, to: AccountId);
+}
+
+impl<T> MarketplaceXcm for T
+where
+    T: Storage<Data>
+        + Storage<ownable::Data>
+        + Storage<pausable::Data>
+        + Storage<reentrancy_guard::Data>,
+{
+    /// Allows or disallows a parachain sovereign account to buy through XCM.
+    #[modifiers(when_not_taking_custody, only_owner)]
+    default fn set_xcm_origin(
+        &mut self,
+        origin: AccountId,
+        allowed: bool,
+    ) -> Result<(), MarketplaceError> {
+        if allowed {
+            self.data::<Data>().xcm_origins.insert(&origin, &());
+        } else {
+            self.data::<Data>().xcm_origins.remove(&origin);
+        }
+        Ok(())
+    }
+
+    /// Checks if a parachain sovereign account is allowed to buy through XCM.
+    default fn is_xcm_origin(&self, origin: AccountId) -> bool {
+        self.data::<Data>().xcm_origins.contains(&origin)
+    }
+
+    /// Buys NFT item for a beneficiary on another chain.
+    #[modifiers(when_not_taking_custody, when_not_paused, non_reentrant)]
+    default fn buy_via_xcm(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        beneficiary: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        self.check_xcm_origin(caller)?;
+        self.check_not_blacklisted(caller)?;
+        self.check_not_blacklisted(beneficiary)?;
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_key(&token_id)))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            !self.is_usd_listing(contract_address, &token_id)
+                && self.listing_currency(contract_address, &token_id) == Currency::Native,
+            MarketplaceError::CurrencyMismatch
+        );
+        ensure!(
+            !self.is_escrowed_listing(contract_address, &token_id),
+            MarketplaceError::EscrowRequired
+        );
+        self.check_listing_gate(contract_address, &token_id, beneficiary)?;
+
+        let token_owner = contract_calls::owner_of(contract_address, &token_id)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        let price = self.flash_sale_price(contract_address, &item);
+        let deposit = self.get_deposit_internal(caller);
+        ensure!(deposit >= price, MarketplaceError::BalanceInsufficient);
+        self.set_currency_deposit(caller, Currency::Native, deposit - price);
+
+        // the marketplace buys the NFT and holds it for the beneficiary
+        self.settle_sale(
+            contract_address,
+            token_id.clone(),
+            token_owner,
+            Self::env().account_id(),
+            price,
+            Currency::Native,
+            SaleAttribution::default(),
+        )?;
+        let reservation_id = self.data::<Data>().v1.last_xcm_reservation_id + 1;
+        self.data::<Data>().xcm_reservations.insert(
+            &reservation_id,
+            &XcmReservation {
+                origin: caller,
+                beneficiary,
+                contract_address,
+                token_id,
+            },
+        );
+        self.data::<Data>().v1.last_xcm_reservation_id = reservation_id;
+
+        self.emit_xcm_purchase_reserved_event(reservation_id, caller, beneficiary);
+        Ok(())
+    }
+
+    /// Transfers reserved NFT.
+    #[modifiers(when_not_taking_custody, non_reentrant)]
+    default fn claim_xcm_reservation(
+        &mut self,
+        reservation_id: u128,
+        to: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let reservation = self
+            .data::<Data>()
+            .xcm_reservations
+            .get(&reservation_id)
+            .ok_or(MarketplaceError::XcmReservationNotFound)?;
+        let caller = Self::env().caller();
+        ensure!(
+            caller == reservation.origin || caller == reservation.beneficiary,
+            MarketplaceError::NotOwner
+        );
+
+        self.data::<Data>().xcm_reservations.remove(&reservation_id);
+        self.release_custody(reservation.contract_address, to, reservation.token_id)?;
+
+        self.emit_xcm_reservation_claimed_event(reservation_id, to);
+        Ok(())
+    }
+
+    /// Gets XCM reservation.
+    default fn get_xcm_reservation(&self, reservation_id: u128) -> Option<XcmReservation> {
+        self.data::<Data>().xcm_reservations.get(&reservation_id)
+    }
+}
+
+impl<T> MarketplaceXcmEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_xcm_purchase_reserved_event(
+        &self,
+        _reservation_id: u128,
+        _origin: AccountId,
+        _beneficiary: AccountId,
+    ) {
+    }
+
+    default fn emit_xcm_reservation_claimed_event(&self, _reservation_id: u128, _to: AccountId) {}
+}
+
+impl<T> Internal for T
+where
+    T: Storage<Data>,
+{
+    #[cfg(feature = "xcm")]
+    default fn check_xcm_origin(&self, origin: AccountId) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>().xcm_origins.contains(&origin),
+            MarketplaceError::XcmOriginNotAllowed
+        );
+        Ok(())
+    }
+
+    #[cfg(not(feature = "xcm"))]
+    default fn check_xcm_origin(&self, _origin: AccountId) -> Result<(), MarketplaceError> {
+        Err(MarketplaceError::XcmNotSupported)
+    }
+}
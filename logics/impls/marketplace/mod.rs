@@ -1,2 +1,48 @@
+pub mod assets;
+pub mod batch;
+pub mod contract_calls;
+pub mod marketplace_accounting;
+pub mod marketplace_admin;
+pub mod marketplace_auctions;
+pub mod marketplace_circuit_breaker;
+pub mod marketplace_claims;
+pub mod marketplace_coupons;
+pub mod marketplace_credits;
+pub mod marketplace_curation;
+pub mod marketplace_custody;
+pub mod marketplace_deposit;
+pub mod marketplace_disputes;
+pub mod marketplace_editions;
+pub mod marketplace_escrow;
+pub mod marketplace_factory;
+pub mod marketplace_flash_sales;
+pub mod marketplace_fractions;
+pub mod marketplace_governance;
+pub mod marketplace_group_purchase;
+pub mod marketplace_installments;
+pub mod marketplace_inventory;
+pub mod marketplace_lending;
+pub mod marketplace_limit_orders;
+pub mod marketplace_migration;
+pub mod marketplace_moderation;
+pub mod marketplace_multisig;
+pub mod marketplace_mystery_boxes;
+pub mod marketplace_nested_listings;
+pub mod marketplace_offer;
+pub mod marketplace_orders;
+pub mod marketplace_partners;
+pub mod marketplace_physical_sales;
+pub mod marketplace_raffles;
+pub mod marketplace_recovery;
+pub mod marketplace_referrals;
+pub mod marketplace_rentals;
 pub mod marketplace_sale;
+pub mod marketplace_snapshots;
+pub mod marketplace_staking;
+pub mod marketplace_subscriptions;
+pub mod marketplace_timelock;
+pub mod marketplace_trade;
+pub mod marketplace_wash_trading;
+pub mod marketplace_xcm;
 pub mod types;
+pub mod xvm;
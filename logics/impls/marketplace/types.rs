@@ -1,18 +1,92 @@
-use ink::prelude::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use ink::{
+    env::hash::Blake2x256,
+    prelude::vec::Vec,
+    storage::{
+        traits::{AutoKey, Storable, StorableHint, StorageKey},
+        Lazy,
+    },
+};
 use openbrush::{
-    contracts::{ownable::OwnableError, psp34::Id, reentrancy_guard::ReentrancyGuardError},
-    storage::Mapping,
+    contracts::{
+        access_control::{AccessControlError, RoleType},
+        ownable::OwnableError,
+        pausable::PausableError,
+        psp34::{Id, PSP34Error},
+        reentrancy_guard::ReentrancyGuardError,
+    },
+    storage::{Mapping, MultiMapping},
     traits::{AccountId, Balance, Hash, String},
 };
 use scale::{Decode, Encode};
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 
+/// Version of the storage layout written by this code. Version 0 is the layout of the first
+/// deployment.
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Max marketplace fee (in basis points) set at deployment.
+pub const DEFAULT_MAX_FEE: u16 = 1000;
+
+/// One unit of the native currency.
+pub const NATIVE_UNIT: Balance = 1_000_000_000_000_000_000;
+
+/// Id of a pallet-assets asset.
+pub type AssetId = u128;
+
+/// RMRK child NFT as (contract, token id).
+pub type ChildNft = (AccountId, Id);
+
+/// Longest `Id::Bytes` value used as is in storage keys.
+pub const MAX_KEY_ID_LEN: usize = 32;
+
+/// Prefix of a hashed `Id::Bytes` value in storage keys.
+pub const HASHED_ID_PREFIX: u8 = 0xff;
+
+/// Number of `u128` words of a collection's bitmap of non-empty listing price buckets.
+pub const PRICE_BUCKET_WORDS: usize = 8;
+
+/// Longest referral code.
+pub const MAX_REFERRAL_CODE_LEN: usize = 16;
+
+/// Role allowed to resolve disputes.
+pub const ARBITER: RoleType = ink::selector_id!("ARBITER");
+
+/// Role allowed to grant marketplace credits besides the owner.
+pub const CREDIT_ISSUER: RoleType = ink::selector_id!("CREDIT_ISSUER");
+
+/// Role allowed to upgrade the marketplace code besides the owner.
+pub const UPGRADER: RoleType = ink::selector_id!("UPGRADER");
+
+/// Role allowed to moderate accounts besides the owner.
+pub const MODERATOR: RoleType = ink::selector_id!("MODERATOR");
+
+/// Role allowed to feature listings besides the owner.
+pub const CURATOR: RoleType = ink::selector_id!("CURATOR");
+
+/// Marketplace storage.
+///
+/// `Mapping` and `Lazy` fields live in their own cells under keys derived from `STORAGE_KEY`
+/// and the field name. Other fields are packed into the root cell, which is loaded and decoded
+/// as a whole on every call, so fields only read by admin messages and queries or that can grow
+/// are kept in `Lazy` cells. Root cell fields of version 0, the layout of the first deployment,
+/// come first and fields added in version 1 follow in `v1`. Later versions add fields as
+/// `Mapping` or `Lazy` cells, so the root cell still decodes when `migrate` runs. Enumerable
+/// indexes are `MultiMapping`s and hold token ids in their `token_key` form.
 #[derive(Default, Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
     pub registered_collections: Mapping<AccountId, RegisteredCollection>,
+    /// Registered collections marked as verified, including those created by the factory.
+    pub verified_collections: Mapping<AccountId, ()>,
     pub items: Mapping<(AccountId, Id), Item>,
+    /// Currency of listings not priced in native currency.
+    pub listing_currencies: Mapping<(AccountId, Id), Currency>,
+    /// Listings priced in USD (with price oracle decimals) and settled in native currency.
+    pub usd_listings: Mapping<(AccountId, Id), ()>,
+    /// Listings whose sale proceeds are held in escrow for the dispute window.
+    pub escrowed_listings: Mapping<(AccountId, Id), ()>,
     pub fee: u16,
     pub max_fee: u16,
     pub market_fee_recipient: Option<AccountId>,
@@ -20,8 +94,286 @@ pub struct Data {
     pub nonce: u64,
     pub deposit: Mapping<AccountId, Balance>,
     pub offer_items: Mapping<u128, OfferItem>,
+    /// Offer index of the version 0 layout, emptied by the migration to version 1.
     pub offer_items_per_contract_token_id: Mapping<(AccountId, Option<Id>), Vec<u128>>,
+    pub token_offer_index: MultiMapping<(AccountId, Option<Id>), u128>,
     pub last_offer_id: u128,
+    /// Root cell fields added in storage version 1.
+    pub v1: Appended<DataV1>,
+    pub creator_earnings: Mapping<AccountId, Earnings>,
+    pub collection_earnings: Mapping<AccountId, Earnings>,
+    pub supported_currencies: Mapping<AccountId, CurrencyMetadata>,
+    pub supported_currency_list: Lazy<Vec<AccountId>>,
+    pub psp22_deposit: Mapping<(AccountId, AccountId), Balance>,
+    pub receive_wrapped_native: Mapping<AccountId, ()>,
+    pub oracle_pair: Lazy<String>,
+    pub supported_assets: Mapping<AssetId, ()>,
+    pub supported_asset_list: Lazy<Vec<AssetId>>,
+    pub asset_deposit: Mapping<(AccountId, AssetId), Balance>,
+    pub escrows: Mapping<u128, EscrowItem>,
+    pub installment_plans: Mapping<u128, InstallmentPlan>,
+    pub group_purchases: Mapping<u128, GroupPurchase>,
+    pub group_contributions: Mapping<(u128, AccountId), Balance>,
+    pub trades: Mapping<u128, Trade>,
+    pub credits: Mapping<AccountId, Credit>,
+    pub deposit_allowances: Mapping<(AccountId, AccountId), Balance>,
+    pub timelock_delay: Lazy<u64>,
+    pub parameter_changes: Mapping<u128, PendingParameterChange>,
+    pub blacklist: Mapping<AccountId, ()>,
+    pub recoveries: Mapping<u128, PendingRecovery>,
+    pub offer_bonds: Mapping<u128, OfferBond>,
+    pub balance_sheet: Lazy<BalanceSheet>,
+    pub signers: Lazy<Vec<AccountId>>,
+    pub admin_proposals: Mapping<u128, AdminProposal>,
+    pub admin_confirmations: Mapping<(u128, AccountId), ()>,
+    pub offer_created_at: Mapping<u128, u64>,
+    /// Currency of offers not made in native currency.
+    pub offer_currencies: Mapping<u128, Currency>,
+    /// Time of the last sale of a token between an (ordered) pair of accounts.
+    pub pair_sales: Mapping<(AccountId, Id, AccountId, AccountId), u64>,
+    pub wash_trades: Mapping<u128, WashTrade>,
+    pub collection_metadata: Mapping<AccountId, String>,
+    pub stats: Lazy<MarketplaceStats>,
+    /// Native currency listings (price, token key) of a collection by `price_bucket`.
+    pub listing_price_buckets: MultiMapping<(AccountId, u16), (Balance, Id)>,
+    /// Non-empty price buckets of a collection, one bit per bucket.
+    pub listing_price_bitmaps: Mapping<AccountId, [u128; PRICE_BUCKET_WORDS]>,
+    /// Lowest native currency listing price of a collection.
+    pub floor_prices: Mapping<AccountId, Balance>,
+    /// Byte ids of listed tokens whose token key is hashed.
+    pub hashed_token_ids: Mapping<(AccountId, Id), Id>,
+    pub account_listing_index: MultiMapping<AccountId, (AccountId, Id)>,
+    pub collection_listing_index: MultiMapping<AccountId, Id>,
+    pub account_offer_index: MultiMapping<AccountId, u128>,
+    pub rental_listings: Mapping<(AccountId, Id), RentalListing>,
+    pub rentals: Mapping<u128, Rental>,
+    /// Rental id of a rented NFT.
+    pub rented_tokens: Mapping<(AccountId, Id), u128>,
+    /// Open rentals of renters and owners.
+    pub account_rental_index: MultiMapping<AccountId, u128>,
+    pub loan_offers: Mapping<u128, LoanOffer>,
+    pub loans: Mapping<u128, Loan>,
+    pub collection_loan_index: MultiMapping<AccountId, u128>,
+    pub standing_loan_offers: Mapping<u128, StandingLoanOffer>,
+    /// Signed orders of an account with a lower nonce are cancelled.
+    pub min_order_nonces: Mapping<AccountId, u64>,
+    /// Nonces of fulfilled or cancelled signed orders.
+    pub used_order_nonces: Mapping<(AccountId, u64), ()>,
+    pub edition_orders: Mapping<u128, EditionOrder>,
+    /// Price levels of an edition side in ascending order.
+    pub edition_levels: Mapping<(AccountId, Id, EditionSide), Vec<EditionLevel>>,
+    pub raffles: Mapping<u128, Raffle>,
+    /// Ticket purchases of a raffle by purchase index.
+    pub raffle_entries: Mapping<(u128, u32), RaffleEntry>,
+    /// Tickets of a raffle held by an account, removed once refunded.
+    pub raffle_tickets: Mapping<(u128, AccountId), u32>,
+    pub mystery_boxes: Mapping<u128, MysteryBox>,
+    pub mystery_box_purchases: Mapping<u128, MysteryBoxPurchase>,
+    pub fraction_vaults: Mapping<u128, FractionVault>,
+    /// Sovereign accounts of parachains allowed to buy through XCM.
+    pub xcm_origins: Mapping<AccountId, ()>,
+    pub xcm_reservations: Mapping<u128, XcmReservation>,
+    /// Children nested in a listed RMRK token when it was listed.
+    pub listing_children: Mapping<(AccountId, Id), Vec<ChildNft>>,
+    /// Slots of an RMRK collection that have to be empty for a sale.
+    pub equip_slots: Mapping<AccountId, Vec<u32>>,
+    /// Accounts NFTs held in custody were received from.
+    pub custody_depositors: Mapping<(AccountId, Id), AccountId>,
+    /// Set while a NFT is transferred into custody. Written to its own cell, so the
+    /// collection's `before_received` callback sees it.
+    pub custody_transfer: Lazy<bool>,
+    pub disputes: Mapping<u128, Dispute>,
+    /// Open dispute of an escrow or a rental.
+    pub subject_disputes: Mapping<DisputeSubject, u128>,
+    /// Open dispute freezing trading of a NFT.
+    pub disputed_tokens: Mapping<(AccountId, Id), u128>,
+    /// Delivery terms of listed physical-backed NFTs.
+    pub physical_listings: Mapping<(AccountId, Id), PhysicalTerms>,
+    /// Delivery terms of escrowed physical-backed sales.
+    pub physical_escrows: Mapping<u128, PhysicalTerms>,
+    pub partners: Mapping<u32, Partner>,
+    /// Unclaimed fee share of a partner per currency.
+    pub partner_earnings: Mapping<(u32, Currency), Balance>,
+    /// Rewards per staked token at the start of an epoch.
+    pub epoch_reward_per_token: Mapping<u32, u128>,
+    pub stakes: Mapping<AccountId, Stake>,
+    /// Minimum stake voting on a proposal for it to pass.
+    pub governance_quorum: Lazy<Balance>,
+    /// Share of votes (in basis points) in favour a proposal must exceed to pass.
+    pub governance_threshold: Lazy<u16>,
+    /// Time (in milliseconds) proposals are open for voting. Zero disables governance.
+    pub voting_period: Lazy<u64>,
+    pub proposals: Mapping<u128, Proposal>,
+    pub proposal_votes: Mapping<(u128, AccountId), ()>,
+    /// Time until which stake of a voter cannot be withdrawn.
+    pub vote_locks: Mapping<AccountId, u64>,
+    pub limit_orders: Mapping<u128, LimitOrder>,
+    /// Open limit orders of a collection.
+    pub collection_limit_orders: MultiMapping<AccountId, u128>,
+    pub referral_codes: Mapping<String, AccountId>,
+    pub account_referral_codes: Mapping<AccountId, String>,
+    /// Unclaimed fee share of a referrer per currency.
+    pub referral_earnings: Mapping<(AccountId, Currency), Balance>,
+    /// Curator that featured a listing.
+    pub featured_listings: Mapping<(AccountId, Id), AccountId>,
+    /// Featured listings of a collection.
+    pub featured_listing_index: MultiMapping<AccountId, Id>,
+    /// Share of the marketplace fee (in basis points) paid to curators of featured listings.
+    pub curator_fee_share: Lazy<u16>,
+    /// Unclaimed fee share of a curator per currency.
+    pub curator_earnings: Mapping<(AccountId, Currency), Balance>,
+    pub auctions: Mapping<u128, Auction>,
+    /// Curator of a featured auction.
+    pub auction_curators: Mapping<u128, AccountId>,
+    /// Featured auctions a curator schedules, ordered by start time.
+    pub auction_calendars: Mapping<AccountId, Vec<u128>>,
+    /// Collection buyers of a listing have to hold a token of.
+    pub listing_gates: Mapping<(AccountId, Id), AccountId>,
+    /// Coupons by the hash of their code.
+    pub coupons: Mapping<[u8; 32], Coupon>,
+    pub coupon_redemptions: Mapping<([u8; 32], AccountId), ()>,
+    /// Flash sales of collections (no seller) and of sellers in collections.
+    pub flash_sales: Mapping<(AccountId, Option<AccountId>), FlashSale>,
+    /// Rate limits of collections and the global rate limit (no collection).
+    pub rate_limits: Mapping<Option<AccountId>, RateLimit>,
+    pub sale_volumes: Mapping<Option<AccountId>, SaleVolume>,
+    /// Collections paused by the circuit breaker.
+    pub paused_collections: Mapping<AccountId, ()>,
+    /// Max share (in basis points) of the collection floor accounts can list below unconfirmed.
+    pub floor_guards: Mapping<AccountId, u16>,
+    pub subscription_plans: Mapping<u32, SubscriptionPlan>,
+    pub subscriptions: Mapping<AccountId, Subscription>,
+}
+
+/// Root cell fields of `Data` added in storage version 1. Root cells of version 0 end
+/// before them, so they are decoded as `Appended` and set by the migration.
+#[derive(Encode, Decode, Debug, Default)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DataV1 {
+    pub wrapped_native: Option<AccountId>,
+    pub price_oracle: Option<AccountId>,
+    pub max_price_age: u64,
+    pub escrow_period: u64,
+    pub last_escrow_id: u128,
+    pub installment_penalty: u16,
+    pub installment_interval: u64,
+    pub last_installment_plan_id: u128,
+    pub last_group_purchase_id: u128,
+    pub last_trade_id: u128,
+    pub last_parameter_change_id: u128,
+    pub storage_version: u32,
+    /// Progress of the running migration step, reset once the step completes.
+    pub migration_cursor: u128,
+    pub last_recovery_id: u128,
+    pub offer_bond: Balance,
+    /// Time (in milliseconds) after which an offer can be cancelled without losing its bond.
+    pub offer_bond_period: u64,
+    /// Number of signer confirmations admin operations need. Zero disables the multisig.
+    pub signer_threshold: u8,
+    pub last_admin_proposal_id: u128,
+    pub compliance_registry: Option<AccountId>,
+    /// Time (in milliseconds) an offer has to stay open before the bidder can cancel or remove it.
+    pub min_offer_duration: u64,
+    /// Time (in milliseconds) within which a repeated sale between the same accounts is
+    /// flagged as a wash trade. Zero disables the tracking.
+    pub wash_trade_window: u64,
+    /// Excludes flagged sales from creator and collection earnings.
+    pub exclude_wash_trades: bool,
+    pub last_wash_trade_id: u128,
+    /// Sequence number of the last settled sale.
+    pub last_sale_id: u128,
+    pub last_rental_id: u128,
+    pub last_loan_offer_id: u128,
+    pub last_loan_id: u128,
+    pub last_standing_loan_offer_id: u128,
+    pub last_edition_order_id: u128,
+    pub last_raffle_id: u128,
+    pub last_mystery_box_id: u128,
+    pub last_mystery_box_purchase_id: u128,
+    pub last_fraction_vault_id: u128,
+    pub last_xcm_reservation_id: u128,
+    /// Royalty registry shared with other marketplaces.
+    pub royalty_registry: Option<AccountId>,
+    pub last_dispute_id: u128,
+    /// Time (in milliseconds) after the end of a rental during which it can be disputed.
+    pub rental_dispute_window: u64,
+    pub last_partner_id: u32,
+    /// PSP22 governance token staked for a share of marketplace fees.
+    pub staking_token: Option<AccountId>,
+    /// Share of native marketplace fees (in basis points) paid to stakers.
+    pub staking_reward_share: u16,
+    pub staking_epoch: u32,
+    pub staking_epoch_start: u64,
+    /// Stake earning rewards in the current epoch.
+    pub total_staked: Balance,
+    /// Stake added in the current epoch, earning rewards from the next one.
+    pub pending_staked: Balance,
+    /// Fees accrued in the current epoch, distributed when it ends.
+    pub epoch_rewards: Balance,
+    /// Rewards per staked token accumulated over all epochs, scaled by `REWARD_PRECISION`.
+    pub reward_per_token: u128,
+    /// Only verified collections can be listed in curated mode.
+    pub curated: bool,
+    pub last_proposal_id: u128,
+    /// Max number of items a batch message handles in one call.
+    pub max_batch_size: u32,
+    /// Native prices must be a multiple of the tick size. Zero disables the check.
+    pub tick_size: Balance,
+    pub last_limit_order_id: u128,
+    /// Share of the marketplace fee (in basis points) paid to referrers.
+    pub referral_fee_share: u16,
+    pub last_auction_id: u128,
+}
+
+/// Root cell field appended after the first deployment. Root cells written before the field
+/// was added end before it, in which case it is decoded as the default value.
+#[derive(Default, Debug)]
+pub struct Appended<T>(pub T);
+
+impl<T> Deref for Appended<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Appended<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Storable + Default> Storable for Appended<T> {
+    fn encode<O: scale::Output + ?Sized>(&self, dest: &mut O) {
+        self.0.encode(dest)
+    }
+
+    fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
+        if input.remaining_len()? == Some(0) {
+            return Ok(Self::default())
+        }
+        T::decode(input).map(Self)
+    }
+}
+
+impl<T: Storable + Default, Key: StorageKey> StorableHint<Key> for Appended<T> {
+    type Type = Self;
+    type PreferredKey = AutoKey;
+}
+
+impl<T> StorageKey for Appended<T> {
+    const KEY: ink::primitives::Key = 0;
+}
+
+#[cfg(feature = "std")]
+impl<T: ink::storage::traits::StorageLayout> ink::storage::traits::StorageLayout for Appended<T> {
+    fn layout(key: &ink::primitives::Key) -> ink::metadata::layout::Layout {
+        T::layout(key)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -29,6 +381,10 @@ pub struct Data {
 pub enum MarketplaceError {
     /// Caller is not a marketplace owner.
     OwnableError(OwnableError),
+    /// Caller is missing a required marketplace role.
+    AccessControlError(AccessControlError),
+    /// Marketplace is paused or not paused.
+    PausableError(PausableError),
     /// Caller is trying to make second call while 1st one is still executing.
     ReentrancyError(ReentrancyGuardError),
     /// Caller is not an NFT owner.
@@ -40,7 +396,10 @@ pub enum MarketplaceError {
     /// NFT contract is not registered to the marketplace.
     NotRegisteredContract,
     /// Value send to buy method is invalid
-    BadBuyValue,
+    BadBuyValue {
+        expected: Balance,
+        received: Balance,
+    },
     /// Fee transfer to the marketplace failed.
     TransferToMarketplaceFailed,
     /// Fee transfer to the marketplace failed.
@@ -50,9 +409,9 @@ pub enum MarketplaceError {
     /// Contract has been already registered to the marketplace
     ContractAlreadyRegistered,
     /// Fee required is too high.
-    FeeTooHigh,
+    FeeTooHigh { max: u16, got: u16 },
     /// Unable to transfer token to a new owner.
-    UnableToTransferToken,
+    PSP34Error(PSP34Error),
     /// PSP23 contract hash was not set
     NftContractHashNotSet,
     /// Factory method was unable to initiate NFT smart contract.
@@ -71,6 +430,407 @@ pub enum MarketplaceError {
     OfferNotMatch,
     /// For offer, if details do not match
     OfferDoesNotExist,
+    /// PSP22 currency is not accepted by the marketplace.
+    CurrencyNotSupported,
+    /// Item is listed for a different currency.
+    CurrencyMismatch,
+    /// Unable to transfer PSP22 payment from the buyer.
+    TransferFromBuyerFailed,
+    /// Unable to transfer PSP22 deposit from the depositor.
+    TransferFromDepositorFailed,
+    /// Wrapped native token was not set.
+    WrappedNativeNotSet,
+    /// Unable to wrap native balance.
+    WrapFailed,
+    /// Unable to unwrap wrapped native token.
+    UnwrapFailed,
+    /// Price oracle was not set.
+    OracleNotSet,
+    /// Price oracle has no price for the configured pair.
+    OraclePriceUnavailable,
+    /// Price oracle price is older than allowed.
+    OraclePriceStale,
+    /// Value sent exceeds the price by more than allowed slippage.
+    SlippageExceeded,
+    /// Marketplace was built without pallet-assets chain extension support.
+    AssetsNotSupported,
+    /// Asset transfer through the chain extension failed.
+    AssetTransferFailed,
+    /// Escrow does not exist.
+    EscrowNotFound,
+    /// Caller is not the buyer of the escrowed item.
+    NotBuyer,
+    /// Escrow is disputed and waits for the arbiter.
+    EscrowDisputed,
+    /// Escrow is not disputed.
+    EscrowNotDisputed,
+    /// Dispute window of the escrow is still open.
+    DisputeWindowOpen,
+    /// Dispute window of the escrow is already closed.
+    DisputeWindowClosed,
+    /// Refund to the buyer failed.
+    RefundFailed,
+    /// Number of installments must be at least two.
+    InvalidInstallments,
+    /// Payment plan does not exist.
+    InstallmentPlanNotFound,
+    /// Payment plan is not overdue.
+    InstallmentNotOverdue,
+    /// Payment plan is overdue.
+    InstallmentOverdue,
+    /// Group purchase does not exist.
+    GroupPurchaseNotFound,
+    /// Group purchase is completed or expired.
+    GroupPurchaseClosed,
+    /// Contribution would exceed the group purchase target.
+    ContributionExceedsTarget,
+    /// Group purchase has not raised its target yet.
+    TargetNotReached,
+    /// Contribution can be refunded only if the group purchase expired or its listing changed.
+    RefundNotAvailable,
+    /// Trade does not exist.
+    TradeNotFound,
+    /// Trade is reserved for a different taker.
+    NotTradeTaker,
+    /// Trade must exchange assets on both sides.
+    EmptyTrade,
+    /// Unable to transfer a traded asset.
+    TradeTransferFailed,
+    /// Permit deadline has passed.
+    PermitExpired,
+    /// PSP22 token rejected the permit.
+    PermitFailed,
+    /// Caller is neither the marketplace owner nor a credit issuer.
+    NotCreditIssuer,
+    /// Credit expiry must be in the future.
+    InvalidCreditExpiry,
+    /// Account has expired credit that has to be reclaimed first.
+    CreditExpired,
+    /// Account has no expired credit.
+    CreditNotExpired,
+    /// Purchase exceeds deposit allowance of the spender.
+    AllowanceExceeded,
+    /// Queued parameter change does not exist.
+    ParameterChangeNotFound,
+    /// Queued parameter change delay has not passed yet.
+    TimelockNotExpired,
+    /// Unable to switch the marketplace code hash.
+    UpgradeFailed,
+    /// Storage version is not known to this code.
+    UnknownStorageVersion,
+    /// Account is blacklisted from trading on the marketplace.
+    Blacklisted,
+    /// Arithmetic operation overflowed.
+    ArithmeticOverflow,
+    /// Queued recovery does not exist.
+    RecoveryNotFound,
+    /// Offer is still covered by the bidder's deposit.
+    OfferFunded,
+    /// Operation has to be proposed to and confirmed by the signers.
+    MultisigRequired,
+    /// Caller is not a signer.
+    NotSigner,
+    /// Threshold must not exceed the number of distinct signers.
+    InvalidThreshold,
+    /// Admin proposal does not exist.
+    AdminProposalNotFound,
+    /// Signer has already confirmed the admin proposal.
+    AlreadyConfirmed,
+    /// Admin proposal has already been executed.
+    AdminProposalExecuted,
+    /// Admin proposal does not have enough confirmations yet.
+    NotEnoughConfirmations,
+    /// Account is not allowed to trade by the compliance registry.
+    NotCompliant,
+    /// Marketplace fee recipient is not configured.
+    FeeRecipientNotSet,
+    /// Royalty is set without a royalty receiver.
+    RoyaltyReceiverNotSet,
+    /// Offer has not been open for the minimum offer duration yet.
+    OfferTooRecent,
+    /// Flagged wash trade does not exist.
+    WashTradeNotFound,
+    /// NFT is not listed for rent.
+    RentalNotListed,
+    /// Rental does not exist.
+    RentalNotFound,
+    /// Rental duration is zero or longer than the owner allows.
+    InvalidRentalDuration,
+    /// Rental term has not ended yet.
+    RentalNotEnded,
+    /// Rental term has already ended.
+    RentalEnded,
+    /// Caller is not the renter of the NFT.
+    NotRenter,
+    /// Rented NFT is held by the marketplace and the rental has no collateral.
+    CustodialRental,
+    /// Rented NFT is held by the renter.
+    NotCustodialRental,
+    /// Rent of the rental is streamed from the renter's deposit.
+    StreamedRental,
+    /// Rent of the rental is paid upfront.
+    NotStreamedRental,
+    /// Renter's deposit still covers streamed rent.
+    RentStreamFunded,
+    /// Loan offer does not exist.
+    LoanOfferNotFound,
+    /// Loan does not exist.
+    LoanNotFound,
+    /// Caller is not the lender.
+    NotLender,
+    /// Caller is not the borrower.
+    NotBorrower,
+    /// Loan was not repaid in time.
+    LoanOverdue,
+    /// Loan can still be repaid.
+    LoanNotOverdue,
+    /// Principal is zero or exceeds what is left of the loan offer.
+    InvalidPrincipal,
+    /// Signature does not match the signer.
+    InvalidSignature,
+    /// Signed order has expired.
+    OrderExpired,
+    /// Signed order has been fulfilled or cancelled.
+    OrderNonceUsed,
+    /// Edition order does not exist.
+    EditionOrderNotFound,
+    /// Edition order has zero price or amount.
+    InvalidEditionOrder,
+    /// Transfer of edition units failed.
+    EditionTransferFailed,
+    /// Raffle does not exist.
+    RaffleNotFound,
+    /// Raffle has been drawn or has failed.
+    RaffleNotOpen,
+    /// Raffle deadline has passed.
+    RaffleEnded,
+    /// Raffle deadline has not passed yet.
+    RaffleNotEnded,
+    /// Raffle has no tickets left.
+    RaffleSoldOut,
+    /// Raffle has not sold the minimum number of tickets.
+    RaffleMinimumNotMet,
+    /// Raffle has sold the minimum number of tickets and can still be drawn.
+    RaffleMinimumMet,
+    /// Raffle has not failed, so tickets can't be refunded.
+    RaffleNotFailed,
+    /// Raffle has invalid ticket limits or deadline.
+    InvalidRaffle,
+    /// Secret does not match the raffle commitment.
+    InvalidRaffleSecret,
+    /// Raffle secret has to be revealed to draw the winner.
+    RaffleRevealPending,
+    /// Caller has no tickets to refund.
+    NoRaffleTickets,
+    /// Mystery box does not exist.
+    MysteryBoxNotFound,
+    /// Mystery box has no NFTs left to draw.
+    MysteryBoxSoldOut,
+    /// Mystery box has purchases waiting for their draw to be revealed.
+    MysteryBoxPurchasesPending,
+    /// Mystery box purchase does not exist.
+    MysteryBoxPurchaseNotFound,
+    /// Mystery box purchase can be revealed only in a later block.
+    MysteryBoxRevealTooEarly,
+    /// Only the buyer can reveal the purchase until the reveal period ends.
+    MysteryBoxRevealPending,
+    /// Secret does not match the purchase commitment.
+    InvalidMysteryBoxSecret,
+    /// Fraction vault does not exist.
+    FractionVaultNotFound,
+    /// Share token supply is zero or lower than the shares for sale.
+    InvalidFractionShares,
+    /// Vault has fewer shares for sale.
+    FractionSharesSoldOut,
+    /// Transfer of fraction shares failed.
+    ShareTransferFailed,
+    /// Buyout bid is below the reserve price or the current bid.
+    BuyoutBidTooLow,
+    /// Vault has no buyout bid.
+    BuyoutNotStarted,
+    /// Buyout can no longer be outbid.
+    BuyoutEnded,
+    /// Buyout can still be outbid.
+    BuyoutNotEnded,
+    /// Vault has been bought out.
+    VaultBoughtOut,
+    /// Vault has not been bought out.
+    VaultNotBoughtOut,
+    /// Marketplace is built without XCM support.
+    XcmNotSupported,
+    /// Caller is not an allowed XCM origin.
+    XcmOriginNotAllowed,
+    /// XCM reservation does not exist.
+    XcmReservationNotFound,
+    /// Listing can only be bought through escrow.
+    EscrowRequired,
+    /// Marketplace is built without XVM support.
+    XvmNotSupported,
+    /// XVM call of an EVM contract failed.
+    XvmCallFailed,
+    /// Account is not an H160 address padded with zeros.
+    InvalidEvmAddress,
+    /// Token id cannot be encoded as an EVM token id.
+    InvalidEvmTokenId,
+    /// Marketplace does not take custody of EVM NFTs.
+    XvmCustodyNotSupported,
+    /// NFT contract is not a registered RMRK collection.
+    NotRmrkCollection,
+    /// Children nested in the token changed since it was listed.
+    NestedChildrenChanged,
+    /// Token has a child equipped into a checked slot.
+    TokenEquipped,
+    /// Marketplace only accepts NFTs it transfers into custody itself.
+    UnsolicitedTransfer,
+    /// Dispute does not exist.
+    DisputeNotFound,
+    /// Caller is neither party of the disputed trade.
+    NotDisputeParty,
+    /// Dispute has already been resolved.
+    DisputeAlreadyResolved,
+    /// NFT is frozen by an open dispute.
+    TokenDisputed,
+    /// Delivery period has to be longer than zero.
+    InvalidDeliveryPeriod,
+    /// Escrow is not a physical-backed sale.
+    NotPhysicalSale,
+    /// Partner does not exist.
+    PartnerNotFound,
+    /// Partner has been deactivated.
+    PartnerInactive,
+    /// Caller is not the partner account.
+    NotPartner,
+    /// Partner has no earnings in the currency.
+    NoPartnerEarnings,
+    /// Transfer of partner earnings failed.
+    TransferToPartnerFailed,
+    /// Staking token is not set.
+    StakingNotConfigured,
+    /// Staking token cannot be changed while tokens are staked.
+    StakesOutstanding,
+    /// Account has not staked enough tokens.
+    InsufficientStake,
+    /// Account has no staking rewards.
+    NoStakingRewards,
+    /// Transfer of staked tokens failed.
+    StakeTransferFailed,
+    /// Transfer of staking rewards failed.
+    TransferToStakerFailed,
+    /// Collection must be verified to be listed in curated mode.
+    CollectionNotVerified,
+    /// Governance voting period is not set.
+    GovernanceNotConfigured,
+    /// Quorum, threshold or voting period is invalid.
+    InvalidGovernanceParameters,
+    /// Only accounts with active stake can propose and vote.
+    NotStaker,
+    /// Proposal does not exist.
+    ProposalNotFound,
+    /// Proposal is already finalized.
+    ProposalNotActive,
+    /// Voting period of the proposal has ended.
+    VotingClosed,
+    /// Voting period of the proposal has not ended yet.
+    VotingNotEnded,
+    /// Account already voted on the proposal.
+    AlreadyVoted,
+    /// Stake is locked until proposals the account voted on end.
+    StakeLocked,
+    /// Batch has more items than can be handled in one call.
+    BatchTooLarge,
+    /// Batch cursor is past the end of the batch.
+    InvalidBatchCursor,
+    /// Max batch size must be greater than zero.
+    InvalidBatchSize,
+    /// Price is not a multiple of the tick size.
+    PriceNotOnTick { tick_size: Balance },
+    /// Limit order does not exist.
+    LimitOrderNotFound,
+    /// Limit order has zero price or quantity.
+    InvalidLimitOrder,
+    /// Referral code is empty, too long or has characters other than ASCII letters and digits.
+    InvalidReferralCode,
+    /// Referral code is registered to another account.
+    ReferralCodeTaken,
+    /// Referral code is not registered.
+    ReferralCodeNotFound,
+    /// Buyer cannot use their own referral code.
+    SelfReferral,
+    /// Referrer has no earnings in the currency.
+    NoReferralEarnings,
+    /// Transfer of referral earnings failed.
+    TransferToReferrerFailed,
+    /// Buyer does not hold a token of the collection the listing is gated by.
+    GateTokenRequired,
+    /// Coupon does not exist.
+    CouponNotFound,
+    /// Coupon with the code hash already exists.
+    CouponExists,
+    /// Coupon has no discount, no uses, an expiry in the past or a pool it does not need.
+    InvalidCoupon,
+    /// Coupon has expired.
+    CouponExpired,
+    /// Coupon has no uses or pool left.
+    CouponExhausted,
+    /// Coupon does not apply to the listing.
+    CouponNotApplicable,
+    /// Account has already redeemed the coupon.
+    CouponAlreadyRedeemed,
+    /// Flash sale has no discount, a discount of the whole price or an invalid window.
+    InvalidFlashSale,
+    /// Flash sale does not exist.
+    FlashSaleNotFound,
+    /// Collection was not created by the marketplace factory and cannot be enumerated.
+    CollectionNotEnumerable,
+    /// Rate limit of zero.
+    InvalidRateLimit,
+    /// Trading of the collection is paused by the circuit breaker.
+    CollectionPaused,
+    /// Trading of the collection is not paused.
+    CollectionNotPaused,
+    /// Price is further below the collection floor than the floor guard of the seller allows.
+    PriceBelowFloor { floor: Balance },
+    /// Subscription plan has no price or period, a fee over the max fee or an unsupported
+    /// currency, or a subscription is for no periods.
+    InvalidSubscriptionPlan,
+    /// Subscription tier does not exist.
+    SubscriptionPlanNotFound,
+    /// Account has an active subscription to another tier.
+    SubscriptionActive,
+    /// Account has no earnings or rewards to claim.
+    NothingToClaim,
+    /// Listing is already featured by a curator.
+    ListingAlreadyFeatured,
+    /// Listing is not featured.
+    ListingNotFeatured,
+    /// Account has no curator earnings in the currency.
+    NoCuratorEarnings,
+    /// Transfer of curator earnings failed.
+    TransferToCuratorFailed,
+    /// Deposit would no longer cover an open offer.
+    DepositLockedByOffer,
+    /// Only `before_received` can be called while a NFT is transferred into custody.
+    CustodyTransferInProgress,
+    /// Raffle was not drawn within the reveal period and can only fail.
+    RaffleRevealExpired,
+    /// Auction does not exist.
+    AuctionNotFound,
+    /// Auction ends before it starts or has already ended.
+    InvalidAuction,
+    /// Auction does not accept bids yet.
+    AuctionNotStarted,
+    /// Auction no longer accepts bids.
+    AuctionEnded,
+    /// Auction still accepts bids.
+    AuctionNotEnded,
+    /// Bid is below the reserve price or not higher than the highest bid.
+    BidTooLow,
+    /// Auction with bids can't be cancelled.
+    AuctionHasBids,
+    /// Auction is already featured by a curator.
+    AuctionAlreadyFeatured,
+    /// Auction is not featured.
+    AuctionNotFeatured,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -82,6 +842,8 @@ pub enum NftContractType {
     Psp34,
     Rmrk,
     NFTSeries,
+    /// EVM ERC-721 collection traded through XVM.
+    Erc721Xvm,
 }
 
 #[derive(Encode, Decode, Debug)]
@@ -104,6 +866,137 @@ pub struct Item {
     pub price: Balance,
 }
 
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct AccountActivity {
+    /// Listed NFTs as (contract, token id) pairs.
+    pub listings: Vec<(AccountId, Id)>,
+    pub offers_made: Vec<u128>,
+    /// Offers on listed NFTs of the account and on their collections.
+    pub offers_received: Vec<u128>,
+    pub deposit: Balance,
+    /// Offer bonds held for offers of the account.
+    pub locked: Balance,
+}
+
+/// Earnings and rewards an account can claim from the marketplace.
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ClaimableBalances {
+    /// Escrowed sale proceeds of the account past their dispute window as (escrow id, currency,
+    /// sale price). Claiming releases the escrow and settles the sale.
+    pub escrow_proceeds: Vec<(u128, Currency, Balance)>,
+    /// Refundable contributions to expired or unavailable group purchases as (group id, amount).
+    pub group_refunds: Vec<(u128, Balance)>,
+    /// Unclaimed earnings of partners paying to the account as (partner id, currency, amount).
+    pub partner_earnings: Vec<(u32, Currency, Balance)>,
+    /// Unclaimed referral earnings per currency.
+    pub referral_earnings: Vec<(Currency, Balance)>,
+    /// Unclaimed curator earnings per currency.
+    pub curator_earnings: Vec<(Currency, Balance)>,
+    /// Unclaimed staking rewards in native currency.
+    pub staking_rewards: Balance,
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct DepositBreakdown {
+    /// Deposited balance, the same as `get_deposit`.
+    pub total: Balance,
+    /// Part of the deposit that has to keep covering open offers.
+    pub locked: Balance,
+    /// Balance that can be withdrawn.
+    pub available: Balance,
+    /// Offer bonds held until offers are accepted or cancelled, not part of the deposit.
+    pub bonds: Balance,
+}
+
+/// Whether a listed NFT can still be bought.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ListingStatus {
+    Valid,
+    /// NFT is no longer owned by the seller.
+    OwnerChanged,
+    /// Marketplace is no longer approved to transfer the NFT.
+    ApprovalRevoked,
+}
+
+/// NFT of an account annotated with what the account needs to list it.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct InventoryToken {
+    pub token_id: Id,
+    /// Current price and currency of the listing by the account, if the token is listed. Prices
+    /// of USD denominated listings are in USD.
+    pub listing: Option<(Balance, Currency)>,
+    /// Marketplace is approved to transfer the token.
+    pub approved: bool,
+}
+
+/// Marketplace features deployments can be queried for.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum FeatureId {
+    Psp22Payments,
+    AssetPayments,
+    UsdPricing,
+    WrappedNative,
+    Escrow,
+    Installments,
+    GroupPurchases,
+    Trades,
+    Credits,
+    OfferBonds,
+    Multisig,
+    Rentals,
+    Loans,
+    SignedOrders,
+    EditionOrders,
+    Raffles,
+    MysteryBoxes,
+    Fractions,
+    XcmPurchases,
+    Erc721Xvm,
+    RmrkNesting,
+    Disputes,
+    PhysicalSales,
+    Partners,
+    Staking,
+    Governance,
+    Auctions,
+    LimitOrders,
+    Referrals,
+    Coupons,
+    FlashSales,
+    Subscriptions,
+    Curation,
+}
+
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum Currency {
+    /// Native currency of the chain.
+    Native,
+    /// PSP22 token contract.
+    Psp22(AccountId),
+    /// pallet-assets asset transferred through the chain extension.
+    Asset(AssetId),
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct CurrencyMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
 #[derive(Encode, Decode, Debug)]
 #[cfg_attr(
     feature = "std",
@@ -118,12 +1011,903 @@ pub struct OfferItem {
     pub extra: String,
 }
 
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct OfferBond {
+    pub amount: Balance,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct EscrowItem {
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub seller: AccountId,
+    pub buyer: AccountId,
+    pub price: Balance,
+    pub currency: Currency,
+    /// Timestamp after which anyone can release the escrow.
+    pub release_time: u64,
+    pub disputed: bool,
+    /// Accounts sharing the marketplace fee once the sale is settled.
+    pub attribution: SaleAttribution,
+}
+
+/// Accounts a sale is attributed to, paid a share of the marketplace fee when it is settled.
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SaleAttribution {
+    /// Partner frontend the sale was made through.
+    pub partner: Option<u32>,
+    /// Account that referred the buyer.
+    pub referrer: Option<AccountId>,
+    /// Curator of the featured auction the sale settles.
+    pub curator: Option<AccountId>,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RentalListing {
+    pub owner: AccountId,
+    pub daily_price: Balance,
+    pub collateral: Balance,
+    /// Longest rental term in days.
+    pub max_days: u32,
+    /// Share (in basis points) of rent for unused days refunded on early return.
+    pub early_return_refund: u16,
+    /// NFT is held by the marketplace for the term instead of being transferred to the renter.
+    pub custodial: bool,
+    /// NFT is transferred to the renter once paid rent reaches this price.
+    pub purchase_price: Option<Balance>,
+    /// Rent is streamed from the renter's deposit for an open-ended term.
+    pub streamed: bool,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Rental {
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub owner: AccountId,
+    pub renter: AccountId,
+    pub daily_price: Balance,
+    /// Longest extension in days.
+    pub max_days: u32,
+    pub collateral: Balance,
+    pub early_return_refund: u16,
+    /// Refundable share of paid rent held by the marketplace until the rental is closed.
+    pub held_rent: Balance,
+    /// Timestamp after which the owner can claim collateral of a rental that was not returned.
+    pub end_time: u64,
+    pub custodial: bool,
+    pub purchase_price: Option<Balance>,
+    /// Rent paid so far.
+    pub paid: Balance,
+    /// Timestamp up to which rent streamed from the renter's deposit has been paid.
+    pub settled_until: Option<u64>,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct LoanOffer {
+    pub lender: AccountId,
+    pub contract_address: AccountId,
+    /// Loan is offered against any NFT of the collection if not set.
+    pub token_id: Option<Id>,
+    pub principal: Balance,
+    pub interest: Balance,
+    /// Time (in milliseconds) the borrower has to repay the loan.
+    pub duration: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct StandingLoanOffer {
+    pub lender: AccountId,
+    pub contract_address: AccountId,
+    /// Principal left to be borrowed against the offer.
+    pub available: Balance,
+    /// Annual interest rate in basis points.
+    pub apr: u16,
+    /// Time (in milliseconds) the borrower has to repay the loan.
+    pub duration: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Loan {
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub borrower: AccountId,
+    pub lender: AccountId,
+    pub principal: Balance,
+    pub interest: Balance,
+    /// Timestamp until which the loan has to be repaid.
+    pub due: u64,
+}
+
+/// Sale order signed off-chain by the seller and fulfilled on-chain by the buyer.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct SignedOrder {
+    pub seller: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    /// Price in native currency.
+    pub price: Balance,
+    /// Timestamp after which the order can't be fulfilled.
+    pub expiry: u64,
+    /// Seller's order number, used to cancel the order.
+    pub nonce: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum EditionSide {
+    Bid,
+    Ask,
+}
+
+/// Seller subscription tier.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SubscriptionPlan {
+    /// Marketplace fee of subscribers' sales in basis points.
+    pub fee: u16,
+    /// Price of one period.
+    pub price: Balance,
+    pub currency: Currency,
+    /// Length of one period (in milliseconds).
+    pub period: u64,
+}
+
+/// Subscription of a seller to a tier.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Subscription {
+    pub tier: u32,
+    pub expires_at: u64,
+}
+
+/// Limits of trading before the circuit breaker pauses a collection.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RateLimit {
+    pub max_sales_per_block: Option<u32>,
+    /// Max volume of sales in native currency per hour.
+    pub max_volume_per_hour: Option<Balance>,
+}
+
+/// Sales counted against a rate limit.
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SaleVolume {
+    pub block: u32,
+    pub block_sales: u32,
+    /// Hour since the Unix epoch.
+    pub hour: u64,
+    pub hour_volume: Balance,
+}
+
+/// Discount applied to listings for a time window.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct FlashSale {
+    pub start: u64,
+    pub end: u64,
+    /// Discount in basis points.
+    pub discount: u16,
+}
+
+/// Discount of a coupon.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum CouponDiscount {
+    /// Share of the price in basis points.
+    Percent(u16),
+    /// Amount of native currency.
+    Flat(Balance),
+}
+
+/// Who pays the discount of a coupon.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum CouponFunding {
+    /// Seller receives the discounted price.
+    Seller,
+    /// Discount is paid from the coupon pool and the seller receives the full price.
+    Pool,
+}
+
+/// Discount coupon redeemable at checkout.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Coupon {
+    pub issuer: AccountId,
+    /// Collection the coupon applies to. `None` applies to all collections.
+    pub contract_address: Option<AccountId>,
+    pub discount: CouponDiscount,
+    pub funding: CouponFunding,
+    /// Funds left to pay discounts of a pool funded coupon.
+    pub pool: Balance,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: u64,
+}
+
+/// Maker order for units of a PSP37 edition priced per unit in native currency.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct EditionOrder {
+    pub maker: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub side: EditionSide,
+    pub price: Balance,
+    /// Units left to fill.
+    pub amount: Balance,
+}
+
+/// Standing order to buy tokens of a collection at a price in native currency.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct LimitOrder {
+    pub buyer: AccountId,
+    pub contract_address: AccountId,
+    pub price: Balance,
+    /// Tokens left to buy.
+    pub quantity: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RaffleStatus {
+    Open,
+    Drawn,
+    /// Raffle ended without selling the minimum number of tickets or without being drawn
+    /// within the reveal period.
+    Failed,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Raffle {
+    pub owner: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub ticket_price: Balance,
+    pub max_tickets: u32,
+    /// Tickets to sell for the raffle to be drawn. Buyers are refunded otherwise.
+    pub min_tickets: u32,
+    /// Timestamp until which tickets can be bought.
+    pub deadline: u64,
+    /// Hash of the owner's secret revealed to draw the winner.
+    pub commitment: [u8; 32],
+    /// Entropy collected from ticket purchases.
+    pub seed: [u8; 32],
+    pub tickets_sold: u32,
+    /// Number of ticket purchases.
+    pub entries: u32,
+    pub status: RaffleStatus,
+}
+
+/// NFT auctioned by its seller, held by the marketplace until the auction is settled or
+/// cancelled.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Auction {
+    pub seller: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    /// Lowest accepted bid, in the native currency.
+    pub reserve_price: Balance,
+    /// Timestamp from which bids are accepted.
+    pub start_time: u64,
+    /// Timestamp until which bids are accepted.
+    pub end_time: u64,
+    /// Highest bid as (bidder, amount).
+    pub highest_bid: Option<(AccountId, Balance)>,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RaffleEntry {
+    pub buyer: AccountId,
+    pub tickets: u32,
+    /// Number of tickets sold before the purchase.
+    pub first_ticket: u32,
+}
+
+/// Pool of NFTs of a collection sold in random draws at a fixed price.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct MysteryBox {
+    pub creator: AccountId,
+    pub contract_address: AccountId,
+    pub price: Balance,
+    /// NFTs held by the marketplace that have not been drawn yet.
+    pub tokens: Vec<Id>,
+    /// Purchases waiting for their draw to be revealed.
+    pub pending: u32,
+    /// Entropy collected from purchases.
+    pub seed: [u8; 32],
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct MysteryBoxPurchase {
+    pub box_id: u128,
+    pub buyer: AccountId,
+    /// Hash of the buyer's secret revealed to draw the NFT.
+    pub commitment: [u8; 32],
+    pub block: u32,
+    pub timestamp: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Buyout {
+    pub bidder: AccountId,
+    pub bid: Balance,
+    /// Timestamp until which the buyout can be outbid.
+    pub end_time: u64,
+}
+
+/// NFT held by the marketplace and co-owned by holders of a PSP22 share token.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct FractionVault {
+    pub owner: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub share_token: AccountId,
+    /// Total supply of the share token when the NFT was fractionalized.
+    pub total_shares: Balance,
+    /// Shares held by the marketplace for sale to contributors.
+    pub shares_for_sale: Balance,
+    /// Price of a share unit.
+    pub share_price: Balance,
+    /// Lowest bid to buy the NFT out.
+    pub reserve_price: Balance,
+    pub buyout: Option<Buyout>,
+    /// NFT has been transferred to the buyout winner and shares redeem the winning bid.
+    pub bought_out: bool,
+}
+
+/// NFT bought through XCM and held by the marketplace for a beneficiary on another chain.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct XcmReservation {
+    /// Sovereign account of the parachain the purchase came from.
+    pub origin: AccountId,
+    pub beneficiary: AccountId,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+}
+
+/// Trade a dispute is opened for.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum DisputeSubject {
+    Escrow(u128),
+    Rental(u128),
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Dispute {
+    pub subject: DisputeSubject,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    /// Party that opened the dispute.
+    pub claimant: AccountId,
+    pub respondent: AccountId,
+    pub opened_at: u64,
+    /// Set once the arbiter resolved the dispute.
+    pub ruling: Option<DisputeRuling>,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DisputeRuling {
+    pub claimant_wins: bool,
+    pub rationale: String,
+    pub resolved_at: u64,
+}
+
+/// Whitelabel frontend earning a share of the marketplace fee on sales made through it.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Partner {
+    /// Account receiving the partner's earnings.
+    pub account: AccountId,
+    /// Share of the marketplace fee (in basis points) paid to the partner.
+    pub fee_share: u16,
+    /// Marketplace fee charged on sales made through the partner instead of the default one.
+    pub fee_override: Option<u16>,
+    /// Branding metadata of the partner frontend.
+    pub metadata: String,
+    pub active: bool,
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Stake {
+    /// Stake earning rewards.
+    pub amount: Balance,
+    /// Stake earning rewards from `pending_epoch`.
+    pub pending: Balance,
+    pub pending_epoch: u32,
+    /// Rewards per staked token already accounted for in `rewards`.
+    pub reward_per_token_paid: u128,
+    /// Unclaimed rewards.
+    pub rewards: Balance,
+}
+
+/// Delivery terms of a physical-backed sale.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct PhysicalTerms {
+    /// Time (in milliseconds) after the purchase the buyer has to confirm or dispute delivery.
+    pub delivery_period: u64,
+    /// Share of the price (in basis points) refunded when the item is only partially delivered.
+    pub partial_refund: u16,
+}
+
+/// Arbiter's finding on the delivery of a physical-backed sale.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum DeliveryOutcome {
+    /// Sale is released to the seller.
+    Delivered,
+    /// Buyer keeps the NFT and is refunded the partial refund share of the price.
+    PartiallyDelivered,
+    /// Buyer is refunded and the NFT is returned to the seller.
+    NotDelivered,
+}
+
+/// RMRK child equipped into a slot of its parent.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Equipment {
+    pub asset_id: u32,
+    pub child_asset_id: u32,
+    pub child_nft: ChildNft,
+}
+
+/// Orders of an edition at the same price.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct EditionLevel {
+    pub price: Balance,
+    /// Units left to fill at the price.
+    pub amount: Balance,
+    /// Orders at the price in the order they are filled.
+    pub order_ids: Vec<u128>,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct InstallmentPlan {
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub seller: AccountId,
+    pub buyer: AccountId,
+    pub price: Balance,
+    pub paid: Balance,
+    pub installments: u8,
+    pub installments_paid: u8,
+    /// Timestamp until which the next installment has to be paid.
+    pub next_due: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct GroupPurchase {
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    /// Listing price at the time the group purchase was created.
+    pub target: Balance,
+    pub raised: Balance,
+    pub expires_at: u64,
+    pub completed: bool,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum TradeAsset {
+    /// PSP34 token of a contract.
+    Psp34(AccountId, Id),
+    /// Amount of a PSP37 token of a contract.
+    Psp37(AccountId, Id, Balance),
+    /// Amount of a PSP22 token.
+    Psp22(AccountId, Balance),
+    /// Amount of native currency.
+    Native(Balance),
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Trade {
+    pub maker: AccountId,
+    /// Only account allowed to accept the trade, anyone if not set.
+    pub taker: Option<AccountId>,
+    pub offered: Vec<TradeAsset>,
+    pub requested: Vec<TradeAsset>,
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Credit {
+    /// Native balance backing the credit, spendable only on marketplace purchases.
+    pub amount: Balance,
+    pub expires_at: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ParameterChange {
+    MarketplaceFee(u16),
+    FeeRecipient(AccountId),
+    /// Cap of the marketplace fee and collection royalties.
+    MaxFee(u16),
+    TimelockDelay(u64),
+    AddSupportedCurrency(AccountId, CurrencyMetadata),
+    RemoveSupportedCurrency(AccountId),
+    CuratedMode(bool),
+    RoyaltyRegistry(Option<AccountId>),
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct PendingParameterChange {
+    pub change: ParameterChange,
+    /// Timestamp after which the change can be executed.
+    pub eta: u64,
+}
+
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ProposalStatus {
+    Active,
+    /// Proposal passed and its change was queued in the timelock.
+    Passed,
+    Rejected,
+    /// Proposal passed but its change failed validation, e.g. a fee above the current max fee.
+    Invalid,
+}
+
+/// Parameter change proposed by a staker.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Proposal {
+    pub proposer: AccountId,
+    pub change: ParameterChange,
+    pub votes_for: Balance,
+    pub votes_against: Balance,
+    /// Timestamp voting ends at.
+    pub end_time: u64,
+    pub status: ProposalStatus,
+}
+
+/// Sensitive operation executed once confirmed by the signers.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum AdminOperation {
+    ParameterChange(ParameterChange),
+    AddToBlacklist(AccountId),
+    RemoveFromBlacklist(AccountId),
+    Upgrade(Hash),
+    /// New signers and threshold.
+    SetSigners(Vec<AccountId>, u8),
+    /// Id of the queued parameter change.
+    CancelParameterChange(u128),
+    Pause,
+    Unpause,
+    /// Price oracle, quoted pair and max price age.
+    SetPriceOracle(AccountId, String, u64),
+    SetComplianceRegistry(Option<AccountId>),
+    GrantRole(RoleType, AccountId),
+    RevokeRole(RoleType, AccountId),
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct AdminProposal {
+    pub operation: AdminOperation,
+    pub confirmations: u8,
+    pub executed: bool,
+}
+
+/// Native currency the marketplace owes to its users.
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct BalanceSheet {
+    /// Deposits available to their owners.
+    pub deposits: Balance,
+    /// Funds locked in offer bonds, credits, payment plans, trades, group purchases and
+    /// auction bids.
+    pub locked: Balance,
+    /// Sale proceeds held in escrow.
+    pub escrowed: Balance,
+    /// Marketplace fee shares accrued to partners, referrers, curators and stakers.
+    pub accrued_fees: Balance,
+}
+
+/// Assets held by the marketplace which can be returned to their owners by the admin.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum HeldAsset {
+    Escrow(u128),
+    InstallmentPlan(u128),
+    Trade(u128),
+}
+
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct PendingRecovery {
+    pub asset: HeldAsset,
+    /// Timestamp after which the recovery can be executed.
+    pub eta: u64,
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Earnings {
+    /// Proceeds of tokens sold by the creator themselves.
+    pub primary_sales: Balance,
+    /// Royalties received from secondary sales.
+    pub royalties: Balance,
+}
+
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct MarketplaceStats {
+    /// Volume of sales settled in native currency.
+    pub total_volume: Balance,
+    pub total_sales: u128,
+    pub active_listings: u64,
+    pub active_offers: u64,
+    /// Marketplace fees of sales settled in native currency.
+    pub total_fees: Balance,
+}
+
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct WashTrade {
+    pub sale_id: u128,
+    pub contract_address: AccountId,
+    pub token_id: Id,
+    pub seller: AccountId,
+    pub buyer: AccountId,
+    pub seller_fee: Balance,
+    pub royalty_receiver: AccountId,
+    pub author_royalty: Balance,
+    /// Whether the sale was left out of creator and collection earnings.
+    pub excluded: bool,
+}
+
+/// Progress of a batch message handled over several calls.
+#[derive(Encode, Decode, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct BatchProgress {
+    /// Number of items handled in this call.
+    pub processed: u32,
+    /// Cursor to continue the batch from, `None` once the batch is complete.
+    pub next: Option<u32>,
+}
+
+/// Gets token id used in storage keys. Byte ids longer than `MAX_KEY_ID_LEN` are replaced
+/// by their prefixed Blake2x256 hash, which is longer than any id used as is.
+pub fn token_key(token_id: &Id) -> Id {
+    match token_id {
+        Id::Bytes(bytes) if bytes.len() > MAX_KEY_ID_LEN => {
+            let mut hash = [0; 32];
+            ink::env::hash_bytes::<Blake2x256>(bytes, &mut hash);
+            let mut key = Vec::with_capacity(1 + hash.len());
+            key.push(HASHED_ID_PREFIX);
+            key.extend_from_slice(&hash);
+            Id::Bytes(key)
+        }
+        _ => token_id.clone(),
+    }
+}
+
+/// Gets bucket of a native listing price. Buckets keep the order of prices and each spans at
+/// most an eighth of its lowest price, so there are fewer than `PRICE_BUCKET_WORDS * 128`.
+pub fn price_bucket(price: Balance) -> u16 {
+    let bits = 128 - price.leading_zeros();
+    if bits <= 4 {
+        return price as u16
+    }
+    // 4 leading bits of the price, the first of which is always set
+    let top = (price >> (bits - 4)) as u32;
+    ((bits - 4) * 8 + top) as u16
+}
+
 impl From<OwnableError> for MarketplaceError {
     fn from(error: OwnableError) -> Self {
         MarketplaceError::OwnableError(error)
     }
 }
 
+impl From<AccessControlError> for MarketplaceError {
+    fn from(error: AccessControlError) -> Self {
+        MarketplaceError::AccessControlError(error)
+    }
+}
+
+impl From<PSP34Error> for MarketplaceError {
+    fn from(error: PSP34Error) -> Self {
+        MarketplaceError::PSP34Error(error)
+    }
+}
+
+impl From<PausableError> for MarketplaceError {
+    fn from(error: PausableError) -> Self {
+        MarketplaceError::PausableError(error)
+    }
+}
+
 impl From<ReentrancyGuardError> for MarketplaceError {
     fn from(error: ReentrancyGuardError) -> Self {
         MarketplaceError::ReentrancyError(error)
@@ -0,0 +1,206 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Calls of EVM ERC-721 contracts through the Astar XVM chain extension.
+//!
+//! An EVM collection is registered under its H160 address padded with zeros to an `AccountId`
+//! and Substrate accounts act in EVM as the H160 address made of their first 20 bytes, as XVM
+//! maps callers. The chain extension is only called when the `xvm` feature is enabled,
+//! otherwise every call fails with `MarketplaceError::XvmNotSupported`.
+
+use crate::impls::marketplace::types::MarketplaceError;
+#[cfg(feature = "xvm")]
+use ink::prelude::vec::Vec;
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+/// Function id of XVM `xvm_call` (extension 1, function 1 on Astar).
+pub const XVM_CALL_FUNC_ID: u32 = 0x0001_0001;
+/// Id of the EVM in XVM calls.
+pub const EVM_ID: u8 = 0x0F;
+
+/// ERC-721 `ownerOf(uint256)` selector.
+pub const OWNER_OF_SELECTOR: [u8; 4] = [0x63, 0x52, 0x21, 0x1e];
+/// ERC-721 `getApproved(uint256)` selector.
+pub const GET_APPROVED_SELECTOR: [u8; 4] = [0x08, 0x18, 0x12, 0xfc];
+/// ERC-721 `isApprovedForAll(address,address)` selector.
+pub const IS_APPROVED_FOR_ALL_SELECTOR: [u8; 4] = [0xe9, 0x85, 0xe9, 0xc5];
+/// ERC-721 `transferFrom(address,address,uint256)` selector.
+pub const TRANSFER_FROM_SELECTOR: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+/// Non-zero status code returned by the chain extension.
+#[derive(Debug, PartialEq, Eq)]
+pub struct XvmStatusCode(pub u32);
+
+impl ink::env::chain_extension::FromStatusCode for XvmStatusCode {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            code => Err(XvmStatusCode(code)),
+        }
+    }
+}
+
+/// Gets EVM address an account acts as.
+pub fn evm_address(account: &AccountId) -> [u8; 20] {
+    let mut address = [0; 20];
+    address.copy_from_slice(&AsRef::<[u8]>::as_ref(account)[..20]);
+    address
+}
+
+/// Checks if account is an H160 address padded with zeros.
+pub fn is_evm_address(account: &AccountId) -> bool {
+    AsRef::<[u8]>::as_ref(account)[20..]
+        .iter()
+        .all(|byte| *byte == 0)
+}
+
+/// Encodes token id as an ABI `uint256` word.
+pub fn token_id_word(token_id: &Id) -> Result<[u8; 32], MarketplaceError> {
+    let id = match token_id {
+        Id::U8(id) => *id as u128,
+        Id::U16(id) => *id as u128,
+        Id::U32(id) => *id as u128,
+        Id::U64(id) => *id as u128,
+        Id::U128(id) => *id,
+        _ => return Err(MarketplaceError::InvalidEvmTokenId),
+    };
+    let mut word = [0; 32];
+    word[16..].copy_from_slice(&id.to_be_bytes());
+    Ok(word)
+}
+
+/// Gets EVM address owning token.
+#[cfg(feature = "xvm")]
+pub fn owner_of(contract_address: AccountId, token_id: &Id) -> Result<[u8; 20], MarketplaceError> {
+    let output = call(
+        contract_address,
+        OWNER_OF_SELECTOR,
+        &[token_id_word(token_id)?],
+    )?;
+    address_from_word(&output)
+}
+
+/// Checks if `operator` may transfer token of `owner`, either for the token or all tokens.
+#[cfg(feature = "xvm")]
+pub fn is_approved(
+    contract_address: AccountId,
+    owner: [u8; 20],
+    operator: [u8; 20],
+    token_id: &Id,
+) -> Result<bool, MarketplaceError> {
+    let output = call(
+        contract_address,
+        GET_APPROVED_SELECTOR,
+        &[token_id_word(token_id)?],
+    )?;
+    if address_from_word(&output)? == operator {
+        return Ok(true)
+    }
+
+    let output = call(
+        contract_address,
+        IS_APPROVED_FOR_ALL_SELECTOR,
+        &[address_word(owner), address_word(operator)],
+    )?;
+    Ok(output.get(31) == Some(&1))
+}
+
+/// Transfers token from `from` to `to`, the marketplace must be approved by `from`.
+#[cfg(feature = "xvm")]
+pub fn transfer_from(
+    contract_address: AccountId,
+    from: [u8; 20],
+    to: [u8; 20],
+    token_id: &Id,
+) -> Result<(), MarketplaceError> {
+    call(
+        contract_address,
+        TRANSFER_FROM_SELECTOR,
+        &[
+            address_word(from),
+            address_word(to),
+            token_id_word(token_id)?,
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(feature = "xvm")]
+fn call(
+    contract_address: AccountId,
+    selector: [u8; 4],
+    arguments: &[[u8; 32]],
+) -> Result<Vec<u8>, MarketplaceError> {
+    let mut input = Vec::with_capacity(4 + 32 * arguments.len());
+    input.extend_from_slice(&selector);
+    for argument in arguments {
+        input.extend_from_slice(argument);
+    }
+    ink::env::chain_extension::ChainExtensionMethod::build(XVM_CALL_FUNC_ID)
+        .input::<(u8, Vec<u8>, Vec<u8>)>()
+        .output::<Vec<u8>, false>()
+        .handle_error_code::<XvmStatusCode>()
+        .call(&(EVM_ID, evm_address(&contract_address).to_vec(), input))
+        .map_err(|_| MarketplaceError::XvmCallFailed)
+}
+
+#[cfg(feature = "xvm")]
+fn address_word(address: [u8; 20]) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[12..].copy_from_slice(&address);
+    word
+}
+
+#[cfg(feature = "xvm")]
+fn address_from_word(output: &[u8]) -> Result<[u8; 20], MarketplaceError> {
+    let word = output.get(..32).ok_or(MarketplaceError::XvmCallFailed)?;
+    let mut address = [0; 20];
+    address.copy_from_slice(&word[12..]);
+    Ok(address)
+}
+
+#[cfg(not(feature = "xvm"))]
+pub fn owner_of(
+    _contract_address: AccountId,
+    _token_id: &Id,
+) -> Result<[u8; 20], MarketplaceError> {
+    Err(MarketplaceError::XvmNotSupported)
+}
+
+#[cfg(not(feature = "xvm"))]
+pub fn is_approved(
+    _contract_address: AccountId,
+    _owner: [u8; 20],
+    _operator: [u8; 20],
+    _token_id: &Id,
+) -> Result<bool, MarketplaceError> {
+    Err(MarketplaceError::XvmNotSupported)
+}
+
+#[cfg(not(feature = "xvm"))]
+pub fn transfer_from(
+    _contract_address: AccountId,
+    _from: [u8; 20],
+    _to: [u8; 20],
+    _token_id: &Id,
+) -> Result<(), MarketplaceError> {
+    Err(MarketplaceError::XvmNotSupported)
+}
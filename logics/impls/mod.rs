@@ -1 +1,2 @@
 pub mod marketplace;
+pub mod royalty_registry;
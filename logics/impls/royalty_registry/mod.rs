@@ -0,0 +1,2 @@
+pub mod royalty_registry;
+pub mod types;
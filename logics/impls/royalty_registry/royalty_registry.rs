@@ -0,0 +1,113 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::types::{Data, MAX_ROYALTY};
+use crate::{
+    ensure, impls::marketplace::types::MarketplaceError, traits::royalty_registry::RoyaltyRegistry,
+};
+use openbrush::{
+    contracts::ownable::*,
+    modifiers,
+    traits::{AccountId, Storage},
+};
+
+pub trait RoyaltyRegistryEvents {
+    fn emit_royalty_set_event(
+        &self,
+        contract_address: AccountId,
+        royalty: Option<(AccountId, u16)>,
+    );
+}
+
+impl<T> RoyaltyRegistry for T
+where
+    T: Storage<Data> + Storage<ownable::Data>,
+{
+    /// Sets royalty of a collection.
+    default fn set_royalty(
+        &mut self,
+        contract_address: AccountId,
+        royalty: Option<(AccountId, u16)>,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(
+            self.data::<Data>().writers.contains(&caller)
+                || OwnableRef::owner(&contract_address) == caller,
+            MarketplaceError::NotOwner
+        );
+
+        match royalty {
+            Some(royalty) => {
+                ensure!(
+                    royalty.1 <= MAX_ROYALTY,
+                    MarketplaceError::FeeTooHigh {
+                        max: MAX_ROYALTY,
+                        got: royalty.1,
+                    }
+                );
+                self.data::<Data>()
+                    .royalties
+                    .insert(&contract_address, &royalty);
+            }
+            None => self.data::<Data>().royalties.remove(&contract_address),
+        }
+
+        self.emit_royalty_set_event(contract_address, royalty);
+        Ok(())
+    }
+
+    /// Gets royalty of a collection.
+    default fn get_royalty(&self, contract_address: AccountId) -> Option<(AccountId, u16)> {
+        self.data::<Data>().royalties.get(&contract_address)
+    }
+
+    /// Allows or disallows an account to set royalty of any collection.
+    #[modifiers(only_owner)]
+    default fn set_writer(
+        &mut self,
+        writer: AccountId,
+        allowed: bool,
+    ) -> Result<(), MarketplaceError> {
+        if allowed {
+            self.data::<Data>().writers.insert(&writer, &());
+        } else {
+            self.data::<Data>().writers.remove(&writer);
+        }
+        Ok(())
+    }
+
+    /// Checks if an account is allowed to set royalty of any collection.
+    default fn is_writer(&self, writer: AccountId) -> bool {
+        self.data::<Data>().writers.contains(&writer)
+    }
+}
+
+impl<T> RoyaltyRegistryEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_royalty_set_event(
+        &self,
+        _contract_address: AccountId,
+        _royalty: Option<(AccountId, u16)>,
+    ) {
+    }
+}
@@ -0,0 +1,14 @@
+use openbrush::{storage::Mapping, traits::AccountId};
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+/// Highest royalty (in basis points) the registry accepts.
+pub const MAX_ROYALTY: u16 = 10_000;
+
+#[derive(Default, Debug)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    pub royalties: Mapping<AccountId, (AccountId, u16)>,
+    /// Accounts allowed to set royalty of any collection.
+    pub writers: Mapping<AccountId, ()>,
+}
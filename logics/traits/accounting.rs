@@ -0,0 +1,12 @@
+use crate::impls::marketplace::types::BalanceSheet;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceAccounting {
+    /// Gets native currency the marketplace owes to its users.
+    #[ink(message)]
+    fn get_balance_sheet(&self) -> BalanceSheet;
+
+    /// Checks that the marketplace native balance covers all its liabilities.
+    #[ink(message)]
+    fn solvency_check(&self) -> bool;
+}
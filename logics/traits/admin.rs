@@ -0,0 +1,159 @@
+use crate::impls::marketplace::types::{AssetId, CurrencyMetadata, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::traits::{AccountId, Balance, String};
+
+/// Admin messages. Messages that were part of `MarketplaceSale` keep their selectors.
+#[openbrush::trait_definition]
+pub trait MarketplaceAdmin {
+    /// Halts trading on the marketplace.
+    #[ink(message, selector = 0xf76d10d2)]
+    fn pause(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Resumes trading on the marketplace.
+    #[ink(message, selector = 0xc21e16b5)]
+    fn unpause(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Sets price oracle, quoted pair and max age (in milliseconds) of an accepted price.
+    #[ink(message, selector = 0x9aadcf98)]
+    fn set_price_oracle(
+        &mut self,
+        price_oracle: AccountId,
+        pair: String,
+        max_price_age: u64,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets price oracle.
+    #[ink(message, selector = 0xd4b7eacd)]
+    fn get_price_oracle(&self) -> Option<AccountId>;
+
+    /// Sets the registry buyers, sellers and withdrawing accounts have to be allowed by.
+    /// `None` disables compliance checks.
+    #[ink(message, selector = 0xe3210ad2)]
+    fn set_compliance_registry(
+        &mut self,
+        registry: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets compliance registry.
+    #[ink(message, selector = 0xcdd8c0e3)]
+    fn get_compliance_registry(&self) -> Option<AccountId>;
+
+    /// Sets the royalty registry shared with other marketplaces. Royalty set in the registry
+    /// takes precedence over royalty of a registered collection and collection royalty updates
+    /// are written to it. `None` disables the registry. The change is queued if a timelock delay
+    /// is set, returns id of the queued change.
+    #[ink(message, selector = 0x8e078a50)]
+    fn set_royalty_registry(
+        &mut self,
+        registry: Option<AccountId>,
+    ) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Gets royalty registry.
+    #[ink(message, selector = 0x88fb6144)]
+    fn get_royalty_registry(&self) -> Option<AccountId>;
+
+    /// Sets the marketplace fee. The change is queued if a timelock delay is set, returns id of
+    /// the queued change.
+    #[ink(message, selector = 0x56e9ac75)]
+    fn set_marketplace_fee(&mut self, fee: u16) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Gets the marketplace fee.
+    #[ink(message, selector = 0xd670d2c9)]
+    fn get_marketplace_fee(&self) -> u16;
+
+    /// Sets max fee that can be applied to an item price. Max fee can't be lower than the
+    /// marketplace fee. The change is queued if a timelock delay is set, returns id of the
+    /// queued change.
+    #[ink(message, selector = 0xf48012a5)]
+    fn set_max_fee(&mut self, max_fee: u16) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Gets max fee that can be applied to an item price.
+    #[ink(message, selector = 0x65f6e5cb)]
+    fn get_max_fee(&self) -> u16;
+
+    /// Sets max number of items a batch message handles in one call.
+    #[ink(message)]
+    fn set_max_batch_size(&mut self, max_batch_size: u32) -> Result<(), MarketplaceError>;
+
+    /// Gets max number of items a batch message handles in one call.
+    #[ink(message)]
+    fn get_max_batch_size(&self) -> u32;
+
+    /// Sets tick size native listing, offer and bid prices must be a multiple of. Zero
+    /// disables the check.
+    #[ink(message)]
+    fn set_tick_size(&mut self, tick_size: Balance) -> Result<(), MarketplaceError>;
+
+    /// Gets tick size native prices must be a multiple of.
+    #[ink(message)]
+    fn get_tick_size(&self) -> Balance;
+
+    /// Gets the marketplace fee recipient.
+    #[ink(message, selector = 0xa8f2f9a7)]
+    fn get_fee_recipient(&self) -> Option<AccountId>;
+
+    /// Sets the marketplace fee recipient. The change is queued if a timelock delay is set,
+    /// returns id of the queued change.
+    #[ink(message, selector = 0x1d09a9b4)]
+    fn set_fee_recipient(
+        &mut self,
+        fee_recipient: AccountId,
+    ) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Sets curated mode in which only verified collections can be listed. The change is
+    /// queued if a timelock delay is set, returns id of the queued change.
+    #[ink(message, selector = 0xe2532765)]
+    fn set_curated_mode(&mut self, curated: bool) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Checks if only verified collections can be listed.
+    #[ink(message, selector = 0xc86e155c)]
+    fn is_curated_mode(&self) -> bool;
+
+    /// Adds a PSP22 token to the currencies accepted by the marketplace.
+    #[ink(message, selector = 0xdf69a718)]
+    fn add_supported_currency(
+        &mut self,
+        currency: AccountId,
+        metadata: CurrencyMetadata,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes a PSP22 token from the currencies accepted by the marketplace.
+    #[ink(message, selector = 0x889ba31f)]
+    fn remove_supported_currency(&mut self, currency: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Checks if a PSP22 token is accepted by the marketplace.
+    #[ink(message, selector = 0xecc38d98)]
+    fn is_supported_currency(&self, currency: AccountId) -> bool;
+
+    /// Gets PSP22 tokens accepted by the marketplace.
+    #[ink(message, selector = 0x440d6dad)]
+    fn get_supported_currencies(&self) -> Vec<AccountId>;
+
+    /// Gets metadata of a PSP22 token accepted by the marketplace.
+    #[ink(message, selector = 0x1e012d8e)]
+    fn get_currency_metadata(&self, currency: AccountId) -> Option<CurrencyMetadata>;
+
+    /// Adds a pallet-assets asset to the currencies accepted by the marketplace.
+    #[ink(message, selector = 0x35c4a44b)]
+    fn add_supported_asset(&mut self, asset_id: AssetId) -> Result<(), MarketplaceError>;
+
+    /// Removes a pallet-assets asset from the currencies accepted by the marketplace.
+    #[ink(message, selector = 0x36844e4b)]
+    fn remove_supported_asset(&mut self, asset_id: AssetId) -> Result<(), MarketplaceError>;
+
+    /// Checks if a pallet-assets asset is accepted by the marketplace.
+    #[ink(message, selector = 0x58569e94)]
+    fn is_supported_asset(&self, asset_id: AssetId) -> bool;
+
+    /// Gets pallet-assets assets accepted by the marketplace.
+    #[ink(message)]
+    fn get_supported_assets(&self) -> Vec<AssetId>;
+
+    /// Sets the wrapped native token (e.g. WASTR) and accepts it as a currency.
+    #[ink(message, selector = 0x3fe47605)]
+    fn set_wrapped_native(&mut self, wrapped_native: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets the wrapped native token.
+    #[ink(message, selector = 0x7a0477a6)]
+    fn get_wrapped_native(&self) -> Option<AccountId>;
+}
@@ -0,0 +1,40 @@
+use crate::impls::marketplace::types::{Auction, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceAuctions {
+    /// Auctions caller's NFT held by the marketplace until the auction is settled or
+    /// cancelled. Bids in the native currency are accepted from `start_time` until `end_time`.
+    /// Returns id of the new auction.
+    #[ink(message)]
+    fn create_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Bids the transferred value on an auction. The bid has to reach the reserve price and
+    /// exceed the highest bid, which is returned to the deposit of its bidder.
+    #[ink(message, payable)]
+    fn place_bid(&mut self, auction_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Sells the NFT of an ended auction to the highest bidder, or returns it to the seller if
+    /// there were no bids. The curator of a featured auction is paid the curator fee share of
+    /// the marketplace fee. Anyone can settle an auction.
+    #[ink(message)]
+    fn settle_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Returns the NFT of an auction without bids to its seller. Only the seller can cancel.
+    #[ink(message)]
+    fn cancel_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets auction that is not settled or cancelled.
+    #[ink(message)]
+    fn get_auction(&self, auction_id: u128) -> Option<Auction>;
+}
@@ -0,0 +1,28 @@
+use crate::impls::marketplace::types::{MarketplaceError, RateLimit};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceCircuitBreaker {
+    /// Sets rate limit of a collection, or the global rate limit when `contract_address` is
+    /// `None`. A sale exceeding a limit pauses trading of its collection. `None` removes the
+    /// limit.
+    #[ink(message)]
+    fn set_rate_limit(
+        &mut self,
+        contract_address: Option<AccountId>,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets rate limit of a collection, or the global rate limit when `contract_address` is
+    /// `None`.
+    #[ink(message)]
+    fn get_rate_limit(&self, contract_address: Option<AccountId>) -> Option<RateLimit>;
+
+    /// Resumes trading of a collection paused by the circuit breaker.
+    #[ink(message)]
+    fn resume_collection(&mut self, contract_address: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Checks if trading of a collection is paused by the circuit breaker.
+    #[ink(message)]
+    fn is_collection_paused(&self, contract_address: AccountId) -> bool;
+}
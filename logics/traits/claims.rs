@@ -0,0 +1,18 @@
+use crate::impls::marketplace::types::{ClaimableBalances, MarketplaceError};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceClaims {
+    /// Gets escrowed sale proceeds past their dispute window, refundable group purchase
+    /// contributions, partner, curator and referral earnings in the supported currencies and
+    /// staking rewards an account can claim. Rewards of an ended staking epoch are counted once
+    /// the epoch is advanced by a stake, unstake or claim.
+    #[ink(message)]
+    fn get_claimable(&self, account: AccountId) -> ClaimableBalances;
+
+    /// Releases escrowed sale proceeds past their dispute window, refunds group purchase
+    /// contributions and claims partner, curator and referral earnings in the supported
+    /// currencies and staking rewards of the caller.
+    #[ink(message)]
+    fn claim_all(&mut self) -> Result<(), MarketplaceError>;
+}
@@ -0,0 +1,12 @@
+use openbrush::traits::AccountId;
+
+#[openbrush::wrapper]
+pub type ComplianceRegistryRef = dyn ComplianceRegistry;
+
+/// Interface of an external allowlist registry run by a KYC/compliance provider.
+#[openbrush::trait_definition]
+pub trait ComplianceRegistry {
+    /// Checks if the account is allowed to trade.
+    #[ink(message)]
+    fn is_allowed(&self, account: AccountId) -> bool;
+}
@@ -0,0 +1,46 @@
+use crate::impls::marketplace::types::{Coupon, CouponDiscount, CouponFunding, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, String},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceCoupons {
+    /// Issues a coupon committed to by the Blake2x256 hash of its code. The marketplace owner
+    /// can issue coupons for any collection and collection owners for their collection.
+    /// Seller funded coupons only apply to listings of the issuer. Pool funded coupons are
+    /// paid from the transferred value, which is returned to the issuer when the coupon is
+    /// cancelled.
+    #[ink(message, payable)]
+    fn create_coupon(
+        &mut self,
+        code_hash: [u8; 32],
+        contract_address: Option<AccountId>,
+        discount: CouponDiscount,
+        funding: CouponFunding,
+        max_uses: u32,
+        expires_at: u64,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels coupon and returns the rest of its pool to the issuer.
+    #[ink(message)]
+    fn cancel_coupon(&mut self, code_hash: [u8; 32]) -> Result<(), MarketplaceError>;
+
+    /// Buys NFT item listed for native currency with a discount coupon. Transferred value has
+    /// to cover the discounted price. Every account can redeem a coupon once.
+    #[ink(message, payable)]
+    fn buy_with_coupon(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        code: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets coupon.
+    #[ink(message)]
+    fn get_coupon(&self, code_hash: [u8; 32]) -> Option<Coupon>;
+
+    /// Checks if an account has redeemed a coupon.
+    #[ink(message)]
+    fn is_coupon_redeemed(&self, code_hash: [u8; 32], account: AccountId) -> bool;
+}
@@ -0,0 +1,19 @@
+use crate::impls::marketplace::types::{Credit, MarketplaceError};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceCredits {
+    /// Grants transferred value as credit spendable on marketplace purchases until `expires_at`.
+    /// Only the owner and accounts with the `CREDIT_ISSUER` role can grant credits.
+    #[ink(message, payable)]
+    fn grant_credit(&mut self, account: AccountId, expires_at: u64)
+        -> Result<(), MarketplaceError>;
+
+    /// Moves expired credit of an account to the marketplace fee recipient.
+    #[ink(message)]
+    fn reclaim_expired_credit(&mut self, account: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets credit of an account, `None` if the account has no credit or it expired.
+    #[ink(message)]
+    fn get_credit(&self, account: AccountId) -> Option<Credit>;
+}
@@ -0,0 +1,76 @@
+use crate::impls::marketplace::types::{Auction, Currency, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceCuration {
+    /// Features a listing. The owner and accounts with the `CURATOR` role can feature listings.
+    /// The curator is paid the curator fee share of the marketplace fee when the listing sells,
+    /// except for escrowed listings.
+    #[ink(message)]
+    fn feature_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Stops featuring a listing. Only the curator that featured it and the owner can.
+    #[ink(message)]
+    fn unfeature_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets curator of a featured listing.
+    #[ink(message)]
+    fn get_listing_curator(&self, contract_address: AccountId, token_id: Id) -> Option<AccountId>;
+
+    /// Gets featured listings of a collection. At most max batch size listings are returned.
+    #[ink(message)]
+    fn get_featured_listings(&self, contract_address: AccountId, from: u32, limit: u32) -> Vec<Id>;
+
+    /// Features an auction that is not settled or cancelled. The owner and accounts with the
+    /// `CURATOR` role can feature auctions. The curator is paid the curator fee share of the
+    /// marketplace fee when the auction is settled with a bid.
+    #[ink(message)]
+    fn feature_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Stops featuring an auction. Only the curator that featured it and the owner can.
+    #[ink(message)]
+    fn unfeature_auction(&mut self, auction_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets curator of a featured auction.
+    #[ink(message)]
+    fn get_auction_curator(&self, auction_id: u128) -> Option<AccountId>;
+
+    /// Sets the auction calendar of the caller to auctions it features, ordered by start time.
+    /// At most max batch size auctions can be scheduled, an empty list clears the calendar.
+    #[ink(message)]
+    fn set_auction_calendar(&mut self, auction_ids: Vec<u128>) -> Result<(), MarketplaceError>;
+
+    /// Gets auction calendar of a curator. Auctions that were settled, cancelled or are no
+    /// longer featured by the curator are skipped.
+    #[ink(message)]
+    fn get_auction_calendar(&self, curator: AccountId) -> Vec<(u128, Auction)>;
+
+    /// Sets share of the marketplace fee (in basis points) paid to curators of featured
+    /// listings and auctions.
+    #[ink(message)]
+    fn set_curator_fee_share(&mut self, fee_share: u16) -> Result<(), MarketplaceError>;
+
+    /// Gets share of the marketplace fee (in basis points) paid to curators.
+    #[ink(message)]
+    fn get_curator_fee_share(&self) -> u16;
+
+    /// Pays curator earnings of the caller in a currency.
+    #[ink(message)]
+    fn claim_curator_earnings(&mut self, currency: Currency) -> Result<(), MarketplaceError>;
+
+    /// Gets unclaimed curator earnings of an account in a currency.
+    #[ink(message)]
+    fn get_curator_earnings(&self, account: AccountId, currency: Currency) -> Balance;
+}
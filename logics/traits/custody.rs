@@ -0,0 +1,9 @@
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceCustody {
+    /// Gets account that deposited a NFT held in custody by the marketplace.
+    #[ink(message)]
+    fn get_custody_depositor(&self, contract_address: AccountId, token_id: Id)
+        -> Option<AccountId>;
+}
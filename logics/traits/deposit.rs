@@ -0,0 +1,99 @@
+use crate::impls::marketplace::types::{AssetId, DepositBreakdown, MarketplaceError};
+use openbrush::traits::{AccountId, Balance};
+
+/// Deposit messages. Messages that were part of `MarketplaceSale` keep their selectors.
+#[openbrush::trait_definition]
+pub trait MarketplaceDeposit {
+    /// Sets if caller receives proceeds of native sales in wrapped native token.
+    #[ink(message, selector = 0xfeaf8fb4)]
+    fn set_receive_wrapped_native(&mut self, enabled: bool) -> Result<(), MarketplaceError>;
+
+    /// Checks if account receives proceeds of native sales in wrapped native token.
+    #[ink(message, selector = 0x4e8e48cb)]
+    fn is_receiving_wrapped_native(&self, account: AccountId) -> bool;
+
+    /// Deposit balance for offer
+    #[ink(message, payable, selector = 0x92dcb50f)]
+    fn deposit(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Withdraw balance for offer. Fails if the remaining deposit doesn't cover an open offer.
+    #[ink(message, selector = 0xfdd4f3ba)]
+    fn withdraw(&mut self, amount: Balance) -> Result<(), MarketplaceError>;
+
+    /// Withdraw whole deposited balance except what open offers lock. The deposit keeps covering
+    /// the largest open offer and offer bonds stay locked until their offers are closed.
+    #[ink(message, selector = 0x3bd390cf)]
+    fn withdraw_all(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Withdraw deposited balance to another account. Fails if the remaining deposit doesn't
+    /// cover an open offer.
+    #[ink(message, selector = 0x13888234)]
+    fn withdraw_to(
+        &mut self,
+        recipient: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    // Get deposited balance
+    #[ink(message, selector = 0x07da792d)]
+    fn get_deposit(&self, account_id: AccountId) -> Balance;
+
+    /// Gets deposited balance split into withdrawable and locked amounts, together with offer
+    /// bonds held outside of the deposit.
+    #[ink(message, selector = 0x2f7cff66)]
+    fn get_deposit_breakdown(&self, account_id: AccountId) -> DepositBreakdown;
+
+    /// Allow operator to buy with caller's deposit up to allowance
+    #[ink(message, selector = 0xbfabb659)]
+    fn approve_spender(
+        &mut self,
+        operator: AccountId,
+        allowance: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Get deposit allowance of operator
+    #[ink(message, selector = 0x72eb53a9)]
+    fn get_spender_allowance(&self, owner: AccountId, operator: AccountId) -> Balance;
+
+    /// Transfer deposited balance to another account. Open offers lock the deposit as for
+    /// `withdraw`.
+    #[ink(message, selector = 0x11a941b6)]
+    fn transfer_deposit(&mut self, to: AccountId, amount: Balance) -> Result<(), MarketplaceError>;
+
+    /// Deposit PSP22 balance, the marketplace must be approved to spend `amount`
+    #[ink(message, selector = 0x7dbe800e)]
+    fn deposit_psp22(
+        &mut self,
+        currency: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Withdraw deposited PSP22 balance
+    #[ink(message, selector = 0xcc43de32)]
+    fn withdraw_psp22(
+        &mut self,
+        currency: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    // Get deposited PSP22 balance
+    #[ink(message, selector = 0x122921fb)]
+    fn get_psp22_deposit(&self, account_id: AccountId, currency: AccountId) -> Balance;
+
+    /// Deposit pallet-assets balance, the marketplace must be approved to transfer `amount`
+    #[ink(message, selector = 0x34c9f671)]
+    fn deposit_asset(&mut self, asset_id: AssetId, amount: Balance)
+        -> Result<(), MarketplaceError>;
+
+    /// Withdraw deposited pallet-assets balance
+    #[ink(message, selector = 0x0f9823bd)]
+    fn withdraw_asset(
+        &mut self,
+        asset_id: AssetId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    // Get deposited pallet-assets balance
+    #[ink(message, selector = 0x39685eac)]
+    fn get_asset_deposit(&self, account_id: AccountId, asset_id: AssetId) -> Balance;
+}
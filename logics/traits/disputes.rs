@@ -0,0 +1,40 @@
+use crate::impls::marketplace::types::{Dispute, DisputeSubject, MarketplaceError};
+use openbrush::traits::String;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceDisputes {
+    /// Sets how long (in milliseconds) after its end a rental can be disputed.
+    #[ink(message)]
+    fn set_rental_dispute_window(&mut self, window: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets rental dispute window in milliseconds.
+    #[ink(message)]
+    fn get_rental_dispute_window(&self) -> u64;
+
+    /// Opens a dispute of an escrowed sale or a rental. Either party can open it within the
+    /// dispute window. Trading of the NFT is frozen until the arbiter resolves the dispute.
+    #[ink(message)]
+    fn open_dispute(&mut self, subject: DisputeSubject) -> Result<u128, MarketplaceError>;
+
+    /// Resolves a dispute for or against the claimant and settles the disputed trade. Only
+    /// an arbiter can resolve disputes.
+    ///
+    /// An escrowed sale is refunded to the buyer or released to the seller. A rental is closed
+    /// like a returned rental if the renter wins, and its collateral is paid to the owner if
+    /// the owner wins.
+    #[ink(message)]
+    fn rule_on_dispute(
+        &mut self,
+        dispute_id: u128,
+        claimant_wins: bool,
+        rationale: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets dispute.
+    #[ink(message)]
+    fn get_dispute(&self, dispute_id: u128) -> Option<Dispute>;
+
+    /// Gets id of the open dispute of an escrowed sale or a rental.
+    #[ink(message)]
+    fn get_open_dispute(&self, subject: DisputeSubject) -> Option<u128>;
+}
@@ -0,0 +1,62 @@
+use crate::impls::marketplace::types::{EditionOrder, EditionSide, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceEditions {
+    /// Posts a maker order for `amount` units of a PSP37 edition at a unit `price`. Asks
+    /// need the marketplace to be approved to transfer the units. Bids take the order value
+    /// from the maker's deposit until the order is filled or cancelled.
+    #[ink(message)]
+    fn make_edition_order(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        side: EditionSide,
+        price: Balance,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels edition order and returns the rest of a bid to the maker's deposit.
+    #[ink(message)]
+    fn cancel_edition_order(&mut self, order_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Buys up to `amount` units of an edition from the lowest asks priced at most
+    /// `max_price`. Transferred value has to cover the filled units and the rest is refunded.
+    /// Asks the makers no longer back are cancelled.
+    #[ink(message, payable)]
+    fn buy_editions(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        amount: Balance,
+        max_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Sells up to `amount` units of an edition to the highest bids priced at least
+    /// `min_price`. The marketplace has to be approved to transfer the units.
+    #[ink(message)]
+    fn sell_editions(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        amount: Balance,
+        min_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets edition order.
+    #[ink(message)]
+    fn get_edition_order(&self, order_id: u128) -> Option<EditionOrder>;
+
+    /// Gets (price, units) levels of an edition side in ascending price order.
+    #[ink(message)]
+    fn get_edition_levels(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        side: EditionSide,
+    ) -> Vec<(Balance, Balance)>;
+}
@@ -0,0 +1,47 @@
+use crate::impls::marketplace::types::{EscrowItem, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceEscrow {
+    /// Sets how long (in milliseconds) escrowed sale proceeds are held before release.
+    #[ink(message)]
+    fn set_escrow_period(&mut self, period: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets escrow period in milliseconds.
+    #[ink(message)]
+    fn get_escrow_period(&self) -> u64;
+
+    /// Creates a NFT item sale whose proceeds are held in escrow after purchase.
+    #[ink(message)]
+    fn list_with_escrow(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Releases escrowed proceeds to the seller and the NFT to the buyer. The buyer can
+    /// release any time, anyone else once the dispute window is closed.
+    #[ink(message)]
+    fn release_escrow(&mut self, escrow_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Disputes an escrowed purchase. Only the buyer can dispute within the dispute window.
+    #[ink(message)]
+    fn dispute(&mut self, escrow_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Resolves a disputed escrow either by refunding the buyer and returning the NFT to
+    /// the seller or by releasing it. The ruling is recorded without a rationale.
+    #[ink(message)]
+    fn resolve_dispute(
+        &mut self,
+        escrow_id: u128,
+        refund_buyer: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets escrowed purchase.
+    #[ink(message)]
+    fn get_escrow(&self, escrow_id: u128) -> Option<EscrowItem>;
+}
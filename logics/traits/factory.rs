@@ -0,0 +1,87 @@
+use crate::impls::marketplace::types::{MarketplaceError, NftContractType, RegisteredCollection};
+use ink::prelude::vec::Vec;
+use openbrush::traits::{AccountId, Hash, String};
+
+/// Collection factory and registry messages. Messages that were part of `MarketplaceSale`
+/// keep their selectors.
+#[openbrush::trait_definition]
+pub trait MarketplaceFactory {
+    /// Sets a hash of a Shiden34 contract to be instantiated by factory call.
+    #[ink(message, selector = 0xe4d9f02e)]
+    fn set_nft_contract_hash(
+        &mut self,
+        contract_type: NftContractType,
+        contract_hash: Hash,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets a NFT contract hash.
+    #[ink(message, selector = 0x2a0d765f)]
+    fn nft_contract_hash(&self, contract_type: NftContractType) -> Result<Hash, MarketplaceError>;
+
+    /// Gets NFT contract types with a configured contract hash.
+    #[ink(message, selector = 0x79aea0b4)]
+    fn get_nft_contract_types(&self) -> Vec<(NftContractType, Hash)>;
+
+    /// Registers NFT collection to the marketplace.
+    #[ink(message, selector = 0x8b3b40f3)]
+    fn register(
+        &mut self,
+        contract_address: AccountId,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+        contract_type: NftContractType,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates a new NFT Series collection and registers it to the marketplace.
+    #[ink(message, selector = 0xdf6fda9f)]
+    fn create_collection(
+        &mut self,
+        name: String,
+        symbol: String,
+        royalty: Option<u16>,
+    ) -> Result<AccountId, MarketplaceError>;
+
+    /// Updates royalty of a registered collection.
+    #[ink(message, selector = 0x2515aca4)]
+    fn set_collection_royalty(
+        &mut self,
+        contract_address: AccountId,
+        royalty_receiver: Option<AccountId>,
+        royalty: Option<u16>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Sets metadata of a registered collection.
+    #[ink(message, selector = 0x4b48e403)]
+    fn set_collection_metadata(
+        &mut self,
+        contract_address: AccountId,
+        metadata: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets metadata of a registered collection.
+    #[ink(message, selector = 0x707b4de0)]
+    fn get_collection_metadata(&self, contract_address: AccountId) -> Option<String>;
+
+    /// Marks a registered collection as verified or unverified.
+    #[ink(message, selector = 0x690641a7)]
+    fn set_collection_verified(
+        &mut self,
+        contract_address: AccountId,
+        verified: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if a registered collection is verified.
+    #[ink(message, selector = 0x70bc1ab3)]
+    fn is_collection_verified(&self, contract_address: AccountId) -> bool;
+
+    /// Removes NFT collection from the marketplace.
+    #[ink(message, selector = 0x9117899a)]
+    fn unregister(&mut self, contract_address: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets registered collection.
+    #[ink(message, selector = 0x300696d9)]
+    fn get_registered_collection(
+        &self,
+        contract_address: AccountId,
+    ) -> Option<RegisteredCollection>;
+}
@@ -0,0 +1,35 @@
+use crate::impls::marketplace::types::{FlashSale, MarketplaceError};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceFlashSales {
+    /// Schedules a discount in basis points between `start` and `end`. A collection wide sale
+    /// can be scheduled by the collection or marketplace owner and applies to every listing of
+    /// the collection. Otherwise the sale applies to listings of the caller. Replaces the
+    /// previous sale of the same scope.
+    #[ink(message)]
+    fn schedule_flash_sale(
+        &mut self,
+        contract_address: AccountId,
+        collection_wide: bool,
+        start: u64,
+        end: u64,
+        discount: u16,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels flash sale scheduled by the caller.
+    #[ink(message)]
+    fn cancel_flash_sale(
+        &mut self,
+        contract_address: AccountId,
+        collection_wide: bool,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets flash sale of a collection or of a seller in a collection.
+    #[ink(message)]
+    fn get_flash_sale(
+        &self,
+        contract_address: AccountId,
+        seller: Option<AccountId>,
+    ) -> Option<FlashSale>;
+}
@@ -0,0 +1,55 @@
+use crate::impls::marketplace::types::{FractionVault, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceFractions {
+    /// Fractionalizes caller's NFT held by the marketplace into units of a PSP22 share token
+    /// whose whole supply the caller holds. `shares_for_sale` units are transferred to the
+    /// marketplace and sold to contributors at `share_price`. Anyone can start a buyout
+    /// bidding at least `reserve_price`.
+    #[ink(message)]
+    fn fractionalize(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        share_token: AccountId,
+        shares_for_sale: Balance,
+        share_price: Balance,
+        reserve_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys shares of a vault. Transferred value has to cover the shares and is paid to the
+    /// vault owner less the marketplace fee.
+    #[ink(message, payable)]
+    fn buy_fraction_shares(
+        &mut self,
+        vault_id: u128,
+        shares: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Bids to buy the NFT of a vault out. The first bid starts the buyout, which can be
+    /// outbid until it ends. Outbid bids are refunded. Bids must be a multiple of the tick size.
+    #[ink(message, payable)]
+    fn bid_buyout(&mut self, vault_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Transfers the NFT to the buyout winner once the buyout has ended. Shares still for
+    /// sale are redeemed for the vault owner.
+    #[ink(message)]
+    fn end_buyout(&mut self, vault_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Redeems caller's shares of a bought out vault for their pro-rata part of the winning
+    /// bid. The marketplace has to be approved to transfer the shares.
+    #[ink(message)]
+    fn redeem_fraction_shares(
+        &mut self,
+        vault_id: u128,
+        shares: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets fraction vault.
+    #[ink(message)]
+    fn get_fraction_vault(&self, vault_id: u128) -> Option<FractionVault>;
+}
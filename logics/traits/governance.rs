@@ -0,0 +1,43 @@
+use crate::impls::marketplace::types::{MarketplaceError, ParameterChange, Proposal};
+use openbrush::traits::{AccountId, Balance};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceGovernance {
+    /// Sets minimum stake voting on a proposal for it to pass, share of votes (in basis points)
+    /// in favour a proposal must exceed to pass and voting period (in milliseconds). Zero voting
+    /// period disables governance.
+    #[ink(message)]
+    fn set_governance_parameters(
+        &mut self,
+        quorum: Balance,
+        threshold: u16,
+        voting_period: u64,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets governance quorum, threshold and voting period.
+    #[ink(message)]
+    fn get_governance_parameters(&self) -> (Balance, u16, u64);
+
+    /// Proposes a parameter change. Only accounts with active stake can propose.
+    #[ink(message)]
+    fn propose(&mut self, change: ParameterChange) -> Result<u128, MarketplaceError>;
+
+    /// Votes on a proposal with the active stake of the caller. The stake cannot be
+    /// withdrawn until voting ends.
+    #[ink(message)]
+    fn vote(&mut self, proposal_id: u128, support: bool) -> Result<(), MarketplaceError>;
+
+    /// Counts votes of a proposal once voting ended. Changes of passed proposals are queued
+    /// in the timelock, or applied right away if there is no timelock delay. Passed proposals
+    /// whose change fails validation are finalized as `Invalid`.
+    #[ink(message)]
+    fn finalize_proposal(&mut self, proposal_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets proposal.
+    #[ink(message)]
+    fn get_proposal(&self, proposal_id: u128) -> Option<Proposal>;
+
+    /// Checks if an account voted on a proposal.
+    #[ink(message)]
+    fn has_voted(&self, proposal_id: u128, account: AccountId) -> bool;
+}
@@ -0,0 +1,42 @@
+use crate::impls::marketplace::types::{GroupPurchase, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceGroupPurchase {
+    /// Creates a vault pooling native currency toward buying a listed NFT.
+    #[ink(message)]
+    fn create_group_purchase(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        expires_at: u64,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Contributes transferred value to a group purchase.
+    #[ink(message, payable)]
+    fn contribute(&mut self, group_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Buys the NFT once a group purchase raised its target. The marketplace holds the NFT on
+    /// behalf of the contributors.
+    #[ink(message)]
+    fn execute_group_purchase(&mut self, group_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Refunds caller's contribution if a group purchase expired or its listing changed.
+    #[ink(message)]
+    fn refund_contribution(&mut self, group_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets group purchase.
+    #[ink(message)]
+    fn get_group_purchase(&self, group_id: u128) -> Option<GroupPurchase>;
+
+    /// Gets account contribution to a group purchase.
+    #[ink(message)]
+    fn get_contribution(&self, group_id: u128, account_id: AccountId) -> Balance;
+
+    /// Gets account share (in basis points) of a group purchase.
+    #[ink(message)]
+    fn get_group_share(&self, group_id: u128, account_id: AccountId) -> u16;
+}
@@ -0,0 +1,44 @@
+use crate::impls::marketplace::types::{InstallmentPlan, MarketplaceError};
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceInstallments {
+    /// Sets share (in basis points) of paid installments the seller keeps on default.
+    #[ink(message)]
+    fn set_installment_penalty(&mut self, penalty: u16) -> Result<(), MarketplaceError>;
+
+    /// Gets installment default penalty in basis points.
+    #[ink(message)]
+    fn get_installment_penalty(&self) -> u16;
+
+    /// Sets time (in milliseconds) the buyer has to pay each installment.
+    #[ink(message)]
+    fn set_installment_interval(&mut self, interval: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets installment interval in milliseconds.
+    #[ink(message)]
+    fn get_installment_interval(&self) -> u64;
+
+    /// Buys NFT item listed for native currency in `installments` payments. Transferred value
+    /// is the first payment, the NFT is held by the marketplace until the final one.
+    #[ink(message, payable)]
+    fn buy_in_installments(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        installments: u8,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Pays the next installment of a payment plan.
+    #[ink(message, payable)]
+    fn pay_installment(&mut self, plan_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Returns the NFT of an overdue payment plan to the seller together with the penalty and
+    /// refunds the rest of the paid amount to the buyer.
+    #[ink(message)]
+    fn claim_defaulted_installments(&mut self, plan_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets payment plan.
+    #[ink(message)]
+    fn get_installment_plan(&self, plan_id: u128) -> Option<InstallmentPlan>;
+}
@@ -0,0 +1,17 @@
+use crate::impls::marketplace::types::{InventoryToken, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceInventory {
+    /// Gets a page of NFTs an account owns in a PSP34Enumerable collection, with their listing
+    /// and approval status. Pages hold `max_batch_size` tokens, an empty page means there are
+    /// no more tokens.
+    #[ink(message)]
+    fn get_owned_tokens(
+        &self,
+        contract_address: AccountId,
+        owner: AccountId,
+        page: u32,
+    ) -> Result<Vec<InventoryToken>, MarketplaceError>;
+}
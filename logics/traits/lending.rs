@@ -0,0 +1,87 @@
+use crate::impls::marketplace::types::{Loan, LoanOffer, MarketplaceError, StandingLoanOffer};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceLending {
+    /// Offers a loan against NFT of a collection or against a specific NFT if `token_id` is
+    /// set. Principal is taken from the lender's deposit until the offer is accepted or
+    /// cancelled.
+    #[ink(message)]
+    fn make_loan_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        principal: Balance,
+        interest: Balance,
+        duration: u64,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels loan offer and returns principal to the lender's deposit.
+    #[ink(message)]
+    fn cancel_loan_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Accepts loan offer. The NFT is held by the marketplace as collateral and principal
+    /// is added to the borrower's deposit.
+    #[ink(message)]
+    fn accept_loan_offer(&mut self, offer_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+
+    /// Posts a standing loan offer against any NFT of a collection. Up to `max_principal` is
+    /// taken from the lender's deposit and can be borrowed by any number of holders at the
+    /// annual rate `apr` (in basis points).
+    #[ink(message)]
+    fn make_standing_loan_offer(
+        &mut self,
+        contract_address: AccountId,
+        max_principal: Balance,
+        apr: u16,
+        duration: u64,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels standing loan offer and returns principal left to the lender's deposit.
+    #[ink(message)]
+    fn cancel_standing_loan_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Borrows `principal` from a standing loan offer against NFT held by the marketplace as
+    /// collateral.
+    #[ink(message)]
+    fn accept_standing_loan_offer(
+        &mut self,
+        offer_id: u128,
+        token_id: Id,
+        principal: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Repays principal and interest from the borrower's deposit and returns the NFT.
+    #[ink(message)]
+    fn repay_loan(&mut self, loan_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Transfers collateral NFT of a loan that was not repaid in time to the lender.
+    #[ink(message)]
+    fn claim_defaulted_loan(&mut self, loan_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets loan offer.
+    #[ink(message)]
+    fn get_loan_offer(&self, offer_id: u128) -> Option<LoanOffer>;
+
+    /// Gets standing loan offer.
+    #[ink(message)]
+    fn get_standing_loan_offer(&self, offer_id: u128) -> Option<StandingLoanOffer>;
+
+    /// Gets loan.
+    #[ink(message)]
+    fn get_loan(&self, loan_id: u128) -> Option<Loan>;
+
+    /// Gets open loans against NFT of a collection, skipping the first `from` ones and
+    /// returning at most `limit`.
+    #[ink(message)]
+    fn get_loans_by_collection(
+        &self,
+        contract_address: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(u128, Loan)>;
+}
@@ -0,0 +1,37 @@
+use crate::impls::marketplace::types::{LimitOrder, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceLimitOrders {
+    /// Places a standing order to buy up to `quantity` tokens of a collection at `price` each.
+    /// The order value is taken from the buyer's deposit until the order is filled or
+    /// cancelled. Returns id of the order.
+    #[ink(message)]
+    fn place_limit_order(
+        &mut self,
+        contract_address: AccountId,
+        quantity: u64,
+        price: Balance,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Cancels limit order and returns the rest of its value to the buyer's deposit.
+    #[ink(message)]
+    fn cancel_limit_order(&mut self, order_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Sells a token of the order collection to the buyer at the order price, paid from the
+    /// order value. The marketplace has to be approved to transfer the token.
+    #[ink(message)]
+    fn sell_into_order(&mut self, order_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+
+    /// Gets limit order.
+    #[ink(message)]
+    fn get_limit_order(&self, order_id: u128) -> Option<LimitOrder>;
+
+    /// Gets ids of open limit orders for a collection.
+    #[ink(message)]
+    fn get_limit_orders(&self, contract_address: AccountId) -> Vec<u128>;
+}
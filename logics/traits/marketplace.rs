@@ -1,31 +1,71 @@
-use crate::impls::marketplace::types::{MarketplaceError, NftContractType, RegisteredCollection};
+use crate::impls::marketplace::types::{
+    AccountActivity, AssetId, Currency, Earnings, Item, ListingStatus, MarketplaceError,
+    MarketplaceStats,
+};
 use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::psp34::Id,
-    traits::{AccountId, Balance, Hash, String},
+    traits::{AccountId, Balance},
 };
 
 #[openbrush::trait_definition]
 pub trait MarketplaceSale {
-    /// Sets a hash of a Shiden34 contract to be instantiated by factory call.
+    /// Creates a NFT item sale on the marketplace. Price must be a multiple of the tick size.
+    #[ink(message)]
+    fn list(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates a NFT item sale on the marketplace. With `confirm_below_floor` the seller
+    /// confirms a price further below the collection floor than their floor guard allows.
     #[ink(message)]
-    fn set_nft_contract_hash(
+    fn list_with_confirmation(
         &mut self,
-        contract_type: NftContractType,
-        contract_hash: Hash,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        confirm_below_floor: bool,
     ) -> Result<(), MarketplaceError>;
 
-    /// Gets a NFT contract hash.
+    /// Rejects native currency listings of the caller priced more than `max_below_floor` (in
+    /// basis points) below the collection floor unless confirmed. `None` turns the guard off.
     #[ink(message)]
-    fn nft_contract_hash(&self, contract_type: NftContractType) -> Hash;
+    fn set_floor_guard(&mut self, max_below_floor: Option<u16>) -> Result<(), MarketplaceError>;
 
-    /// Creates a NFT item sale on the marketplace.
+    /// Gets floor guard of an account.
     #[ink(message)]
-    fn list(
+    fn get_floor_guard(&self, account: AccountId) -> Option<u16>;
+
+    /// Creates a NFT item sale priced in a PSP22 currency on the marketplace.
+    #[ink(message)]
+    fn list_with_psp22(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
         price: Balance,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates a NFT item sale priced in a pallet-assets asset on the marketplace.
+    #[ink(message)]
+    fn list_with_asset(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        asset_id: AssetId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates a NFT item sale priced in USD and settled in native currency.
+    #[ink(message)]
+    fn list_with_usd_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        usd_price: Balance,
     ) -> Result<(), MarketplaceError>;
 
     /// Removes a NFT from the marketplace sale.
@@ -33,94 +73,168 @@ pub trait MarketplaceSale {
     fn unlist(&mut self, contract_address: AccountId, token_id: Id)
         -> Result<(), MarketplaceError>;
 
+    /// Restricts a listing to buyers holding a token of `gate_collection`. `None` lifts the
+    /// restriction. Relisting the token lifts the restriction too.
+    #[ink(message)]
+    fn set_listing_gate(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        gate_collection: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets collection buyers of a listing have to hold a token of.
+    #[ink(message)]
+    fn get_listing_gate(&self, contract_address: AccountId, token_id: Id) -> Option<AccountId>;
+
     /// Buys NFT item from the marketplace.
     #[ink(message, payable)]
     fn buy(&mut self, contract_address: AccountId, token_id: Id) -> Result<(), MarketplaceError>;
 
-    /// Registers NFT collection to the marketplace.
+    /// Buys NFT item from the marketplace paying from caller's deposit in the listing currency.
+    /// Native listings are paid with caller's credit first.
     #[ink(message)]
-    fn register(
+    fn buy_with_deposit(
         &mut self,
         contract_address: AccountId,
-        royalty_receiver: Option<AccountId>,
-        royalty: Option<u16>,
-        contract_type: NftContractType,
+        token_id: Id,
     ) -> Result<(), MarketplaceError>;
 
-    /// Gets registered collection.
+    /// Buys NFT item listed in native currency for `owner` paying from owner's deposit, up to
+    /// the allowance the owner approved to the caller. Owner's credit is not spent.
     #[ink(message)]
-    fn get_registered_collection(
-        &self,
+    fn buy_with_deposit_of(
+        &mut self,
+        owner: AccountId,
         contract_address: AccountId,
-    ) -> Option<RegisteredCollection>;
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
 
-    /// Sets the marketplace fee.
+    /// Buys NFT item listed for a PSP22 currency from the marketplace.
     #[ink(message)]
-    fn set_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError>;
+    fn buy_with_psp22(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        currency: AccountId,
+    ) -> Result<(), MarketplaceError>;
 
-    /// Gets the marketplace fee.
+    /// Buys NFT item listed for a PSP22 currency, approving the payment with a signed permit
+    /// instead of a separate approval transaction.
     #[ink(message)]
-    fn get_marketplace_fee(&self) -> u16;
+    fn buy_with_psp22_permit(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        currency: AccountId,
+        deadline: u64,
+        signature: [u8; 65],
+    ) -> Result<(), MarketplaceError>;
 
-    /// Gets max fee that can be applied to an item price.
+    /// Buys NFT item listed for a pallet-assets asset from the marketplace. The marketplace
+    /// must be approved to transfer the price through pallet-assets `approve_transfer`.
     #[ink(message)]
-    fn get_max_fee(&self) -> u16;
+    fn buy_with_asset(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        asset_id: AssetId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys NFT item priced in USD. Transferred value must cover the oracle price and
+    /// can exceed it by at most `max_slippage` (in basis points). The item is sold at the oracle
+    /// price and the rest of the transferred value is refunded.
+    #[ink(message, payable)]
+    fn buy_with_usd_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        max_slippage: u16,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys the cheapest native currency listings of a collection, up to `max_items` tokens
+    /// and `max_total` spent in total. Listings the caller owns or that can no longer be
+    /// transferred are skipped. At most max batch size listings are visited, skipped ones
+    /// included. Transferred value has to cover the bought tokens and the rest is refunded.
+    /// Returns number of bought tokens.
+    #[ink(message, payable)]
+    fn sweep_floor(
+        &mut self,
+        contract_address: AccountId,
+        max_items: u32,
+        max_total: Balance,
+    ) -> Result<u32, MarketplaceError>;
+
+    /// Gets current price of a listed NFT token in native currency.
+    #[ink(message)]
+    fn get_native_price(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<Balance, MarketplaceError>;
 
     /// Checks if NFT token is listed on the marketplace and returns token price.
     #[ink(message)]
     fn get_price(&self, contract_address: AccountId, token_id: Id) -> Option<Balance>;
 
-    /// Gets the marketplace fee recipient.
+    /// Gets the currency of a listed NFT token.
     #[ink(message)]
-    fn get_fee_recipient(&self) -> AccountId;
+    fn get_currency(&self, contract_address: AccountId, token_id: Id) -> Option<Currency>;
 
-    /// Sets the marketplace fee recipient.
+    /// Gets listings of several NFT tokens, `None` for tokens that are not listed. At most max
+    /// batch size tokens are looked up.
     #[ink(message)]
-    fn set_fee_recipient(&mut self, fee_recipient: AccountId) -> Result<(), MarketplaceError>;
+    fn get_listings(&self, tokens: Vec<(AccountId, Id)>) -> Vec<Option<Item>>;
 
-    /// Deposit balance for offer
-    #[ink(message, payable)]
-    fn deposit(&mut self) -> Result<(), MarketplaceError>;
-
-    /// Withdraw balance for offer
+    /// Gets prices of several NFT tokens, `None` for tokens that are not listed. At most max
+    /// batch size tokens are looked up.
     #[ink(message)]
-    fn withdraw(&mut self, amount: Balance) -> Result<(), MarketplaceError>;
+    fn get_prices(&self, tokens: Vec<(AccountId, Id)>) -> Vec<Option<Balance>>;
 
-    // Get deposited balance
+    /// Gets lifetime native currency earnings (primary sales and royalties) of a creator.
     #[ink(message)]
-    fn get_deposit(&self, account_id: AccountId) -> Balance;
+    fn get_creator_earnings(&self, account: AccountId) -> Earnings;
 
-    // Make offer to a specific contract and/or token_id. Returns offer_id, so duplicate offer is possible
+    /// Gets lifetime native currency creator earnings generated by a collection.
     #[ink(message)]
-    fn make_offer(
-        &mut self,
-        contract_address: AccountId,
-        token_id: Option<Id>,
-        quantity: u64,
-        price_per_item: u128,
-        extra: String,
-    ) -> Result<u128, MarketplaceError>;
+    fn get_collection_earnings(&self, contract_address: AccountId) -> Earnings;
 
-    // Cancel a specific offer
+    /// Gets the lowest price a collection NFT is listed for in native currency.
     #[ink(message)]
-    fn cancel_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError>;
+    fn get_floor_price(&self, contract_address: AccountId) -> Option<Balance>;
 
+    /// Checks that the seller of a listed NFT still owns it and the marketplace is still approved to transfer it.
     #[ink(message)]
-    fn get_offer_for_token(
+    fn check_listing_valid(
         &self,
         contract_address: AccountId,
-        token_id: Option<Id>,
-    ) -> Result<Vec<u128>, MarketplaceError>;
+        token_id: Id,
+    ) -> Result<ListingStatus, MarketplaceError>;
 
-    // Check offer is active, balance >= quantity * amount
+    /// Gets listings, offers made and received, deposit and locked balance of an account.
     #[ink(message)]
-    fn get_offer_active(&self, offer_id: u128) -> bool;
+    fn get_account_activity(&self, account: AccountId) -> AccountActivity;
 
-    // Accept offer
+    /// Gets listings of a collection. `limit` is capped at the max batch size.
     #[ink(message)]
-    fn accept_offer(&mut self, offer_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+    fn get_listings_by_collection(
+        &self,
+        contract_address: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(Id, Item)>;
+
+    /// Gets listings of an account as (contract, token id, listing). `limit` is capped at the
+    /// max batch size.
+    #[ink(message)]
+    fn get_listings_by_account(
+        &self,
+        account: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(AccountId, Id, Item)>;
 
-    // Accept offer for admin, for the ones with extras
+    /// Gets aggregate sales, listing and offer statistics of the marketplace.
     #[ink(message)]
-    fn fulfill_offer(&mut self, offer_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+    fn get_marketplace_stats(&self) -> MarketplaceStats;
 }
@@ -0,0 +1,22 @@
+use crate::impls::marketplace::types::MarketplaceError;
+use ink::prelude::vec::Vec;
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceMigration {
+    /// Migrates storage written by older code, transforming at most `batch_size` entries.
+    /// Returns `true` once storage is at the current version, otherwise it has to be called
+    /// again.
+    #[ink(message)]
+    fn migrate(&mut self, batch_size: u32) -> Result<bool, MarketplaceError>;
+
+    /// Adds listings of the first deployment, as (contract, token id) pairs, to the listing
+    /// indexes, floor prices and stats. Listings are not enumerable in its layout, so they are
+    /// passed in batches of at most `max_batch_size`. Listings already indexed are skipped.
+    #[ink(message)]
+    fn migrate_listings(&mut self, listings: Vec<(AccountId, Id)>) -> Result<(), MarketplaceError>;
+
+    /// Gets version of the storage layout.
+    #[ink(message)]
+    fn get_storage_version(&self) -> u32;
+}
@@ -1 +1,49 @@
+pub mod accounting;
+pub mod admin;
+pub mod auctions;
+pub mod circuit_breaker;
+pub mod claims;
+pub mod compliance_registry;
+pub mod coupons;
+pub mod credits;
+pub mod curation;
+pub mod custody;
+pub mod deposit;
+pub mod disputes;
+pub mod editions;
+pub mod escrow;
+pub mod factory;
+pub mod flash_sales;
+pub mod fractions;
+pub mod governance;
+pub mod group_purchase;
+pub mod installments;
+pub mod inventory;
+pub mod lending;
+pub mod limit_orders;
 pub mod marketplace;
+pub mod migration;
+pub mod moderation;
+pub mod multisig;
+pub mod mystery_boxes;
+pub mod nested_listings;
+pub mod offer;
+pub mod orders;
+pub mod partners;
+pub mod physical_sales;
+pub mod price_oracle;
+pub mod psp22_permit;
+pub mod raffles;
+pub mod recovery;
+pub mod referrals;
+pub mod rentals;
+pub mod rmrk;
+pub mod royalty_registry;
+pub mod snapshots;
+pub mod staking;
+pub mod subscriptions;
+pub mod timelock;
+pub mod trade;
+pub mod wash_trading;
+pub mod wnative;
+pub mod xcm;
@@ -0,0 +1,31 @@
+use crate::impls::marketplace::types::MarketplaceError;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, String},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceModeration {
+    /// Blocks an account from listing, buying, offering and withdrawing. Only the owner and
+    /// accounts with the `MODERATOR` role can manage the blacklist.
+    #[ink(message)]
+    fn add_to_blacklist(&mut self, account: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Unblocks a blacklisted account.
+    #[ink(message)]
+    fn remove_from_blacklist(&mut self, account: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Checks if an account is blacklisted.
+    #[ink(message)]
+    fn is_blacklisted(&self, account: AccountId) -> bool;
+
+    /// Removes a malicious listing. Only the owner and accounts with the `MODERATOR` role can
+    /// remove listings.
+    #[ink(message)]
+    fn admin_unlist(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reason: String,
+    ) -> Result<(), MarketplaceError>;
+}
@@ -0,0 +1,42 @@
+use crate::impls::marketplace::types::{AdminOperation, AdminProposal, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceMultisig {
+    /// Sets signers which have to confirm fee changes, upgrades and blacklist changes. Once
+    /// configured, signers can be changed only by an admin proposal.
+    #[ink(message)]
+    fn set_signers(
+        &mut self,
+        signers: Vec<AccountId>,
+        threshold: u8,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets signers.
+    #[ink(message)]
+    fn get_signers(&self) -> Vec<AccountId>;
+
+    /// Gets number of confirmations admin operations need.
+    #[ink(message)]
+    fn get_signer_threshold(&self) -> u8;
+
+    /// Proposes an admin operation. The proposal counts as the proposer's confirmation.
+    #[ink(message)]
+    fn propose_admin_operation(
+        &mut self,
+        operation: AdminOperation,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Confirms an admin proposal.
+    #[ink(message)]
+    fn confirm_admin_operation(&mut self, proposal_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Executes an admin proposal confirmed by enough signers.
+    #[ink(message)]
+    fn execute_admin_operation(&mut self, proposal_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets admin proposal.
+    #[ink(message)]
+    fn get_admin_proposal(&self, proposal_id: u128) -> Option<AdminProposal>;
+}
@@ -0,0 +1,51 @@
+use crate::impls::marketplace::types::{MarketplaceError, MysteryBox, MysteryBoxPurchase};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceMysteryBoxes {
+    /// Creates a mystery box selling random draws from caller's NFTs at a fixed price. The
+    /// marketplace holds the NFTs until they are drawn or the box is closed. A box holds at most
+    /// max batch size NFTs.
+    #[ink(message)]
+    fn create_mystery_box(
+        &mut self,
+        contract_address: AccountId,
+        token_ids: Vec<Id>,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys a draw from a mystery box. `commitment` is the hash of a secret the buyer reveals
+    /// in a later block to draw the NFT. Transferred value has to cover the price, which is
+    /// paid to the creator less the marketplace fee.
+    #[ink(message, payable)]
+    fn buy_mystery_box(
+        &mut self,
+        box_id: u128,
+        commitment: [u8; 32],
+    ) -> Result<(), MarketplaceError>;
+
+    /// Draws the NFT of a purchase and transfers it to the buyer. The buyer reveals with the
+    /// committed secret; once the reveal period is over anyone can reveal without it.
+    #[ink(message)]
+    fn reveal_mystery_box(
+        &mut self,
+        purchase_id: u128,
+        secret: Option<[u8; 32]>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Closes mystery box with no pending purchases and returns unsold NFTs to the creator.
+    #[ink(message)]
+    fn close_mystery_box(&mut self, box_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets mystery box.
+    #[ink(message)]
+    fn get_mystery_box(&self, box_id: u128) -> Option<MysteryBox>;
+
+    /// Gets mystery box purchase waiting for its draw to be revealed.
+    #[ink(message)]
+    fn get_mystery_box_purchase(&self, purchase_id: u128) -> Option<MysteryBoxPurchase>;
+}
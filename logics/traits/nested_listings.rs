@@ -0,0 +1,28 @@
+use crate::impls::marketplace::types::{ChildNft, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceNestedListings {
+    /// Sets slots of an RMRK collection checked before a sale. Tokens with a child equipped
+    /// into any of the slots cannot be sold, no slots allow all sales.
+    #[ink(message)]
+    fn set_equip_slots(
+        &mut self,
+        contract_address: AccountId,
+        slots: Vec<u32>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets slots of an RMRK collection checked before a sale.
+    #[ink(message)]
+    fn get_equip_slots(&self, contract_address: AccountId) -> Vec<u32>;
+
+    /// Gets children currently nested in an RMRK token.
+    #[ink(message)]
+    fn get_nested_children(&self, contract_address: AccountId, token_id: Id) -> Vec<ChildNft>;
+
+    /// Gets children nested in an RMRK token when it was listed. A listed token can only be
+    /// sold with the same children.
+    #[ink(message)]
+    fn get_listing_children(&self, contract_address: AccountId, token_id: Id) -> Vec<ChildNft>;
+}
@@ -0,0 +1,96 @@
+use crate::impls::marketplace::types::{Currency, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance, String},
+};
+
+/// Offer messages. Messages that were part of `MarketplaceSale` keep their selectors.
+#[openbrush::trait_definition]
+pub trait MarketplaceOffer {
+    // Make offer to a specific contract and/or token_id. Returns offer_id, so duplicate offer is possible
+    // Price per item must be a multiple of the tick size.
+    #[ink(message, selector = 0xa584ff56)]
+    fn make_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        quantity: u64,
+        price_per_item: u128,
+        extra: String,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Makes offer paid from the bidder's deposit in `currency`. The offer bond is still taken
+    /// from the native deposit.
+    #[ink(message)]
+    fn make_offer_with_currency(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+        quantity: u64,
+        price_per_item: u128,
+        currency: Currency,
+        extra: String,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Gets currency of an offer.
+    #[ink(message)]
+    fn get_offer_currency(&self, offer_id: u128) -> Option<Currency>;
+
+    // Cancel a specific offer
+    #[ink(message, selector = 0x01588357)]
+    fn cancel_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError>;
+
+    #[ink(message, selector = 0xf75a8688)]
+    fn get_offer_for_token(
+        &self,
+        contract_address: AccountId,
+        token_id: Option<Id>,
+    ) -> Result<Vec<u128>, MarketplaceError>;
+
+    // Check offer is active, balance >= quantity * amount
+    #[ink(message, selector = 0xcc37e9b3)]
+    fn get_offer_active(&self, offer_id: u128) -> bool;
+
+    /// Checks if several offers are active. At most max batch size offers are checked.
+    #[ink(message, selector = 0xa052e49e)]
+    fn get_offers_active(&self, offer_ids: Vec<u128>) -> Vec<bool>;
+
+    // Accept offer
+    #[ink(message, selector = 0x36a3de09)]
+    fn accept_offer(&mut self, offer_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+
+    // Accept offer for admin, for the ones with extras
+    #[ink(message, selector = 0x44dcfa14)]
+    fn fulfill_offer(&mut self, offer_id: u128, token_id: Id) -> Result<(), MarketplaceError>;
+
+    /// Removes an offer no longer covered by the bidder's deposit. The offer bond is forfeited
+    /// to the marketplace fee recipient.
+    #[ink(message, selector = 0x92d59005)]
+    fn remove_unfunded_offer(&mut self, offer_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Sets the bond taken from the bidder's deposit for every new offer.
+    #[ink(message, selector = 0x3595f13c)]
+    fn set_offer_bond(&mut self, bond: Balance) -> Result<(), MarketplaceError>;
+
+    /// Gets the offer bond.
+    #[ink(message, selector = 0xbf9bc375)]
+    fn get_offer_bond(&self) -> Balance;
+
+    /// Sets time (in milliseconds) within which a cancelled offer forfeits its bond.
+    #[ink(message, selector = 0x30650911)]
+    fn set_offer_bond_period(&mut self, duration: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets the offer bond period in milliseconds.
+    #[ink(message, selector = 0x851e5389)]
+    fn get_offer_bond_period(&self) -> u64;
+
+    /// Sets time (in milliseconds) an offer has to stay open before the bidder can cancel or
+    /// remove it.
+    #[ink(message, selector = 0x1b10eb14)]
+    fn set_min_offer_duration(&mut self, duration: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets minimum offer duration in milliseconds.
+    #[ink(message, selector = 0x85beddf2)]
+    fn get_min_offer_duration(&self) -> u64;
+}
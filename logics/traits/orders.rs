@@ -0,0 +1,35 @@
+use crate::impls::marketplace::types::{MarketplaceError, SignedOrder};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceOrders {
+    /// Buys NFT with a sale order signed off-chain by the seller, so sellers pay for listing
+    /// only when the NFT is sold. `signature` is the seller's ECDSA signature of the order
+    /// hash. Transferred value has to cover the order price.
+    #[ink(message, payable)]
+    fn fulfill_signed_order(
+        &mut self,
+        order: SignedOrder,
+        signature: [u8; 65],
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels caller's signed order with a nonce.
+    #[ink(message)]
+    fn cancel_signed_order(&mut self, nonce: u64) -> Result<(), MarketplaceError>;
+
+    /// Cancels all caller's signed orders with a nonce lower than `min_nonce`.
+    #[ink(message)]
+    fn cancel_signed_orders(&mut self, min_nonce: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets hash of a signed order the seller signs.
+    #[ink(message)]
+    fn get_order_hash(&self, order: SignedOrder) -> [u8; 32];
+
+    /// Gets lowest nonce of valid signed orders of an account.
+    #[ink(message)]
+    fn get_min_order_nonce(&self, account: AccountId) -> u64;
+
+    /// Checks if a signed order nonce of an account has been fulfilled or cancelled.
+    #[ink(message)]
+    fn is_order_nonce_used(&self, account: AccountId, nonce: u64) -> bool;
+}
@@ -0,0 +1,72 @@
+use crate::impls::marketplace::types::{Currency, MarketplaceError, Partner};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance, String},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplacePartners {
+    /// Registers a whitelabel partner frontend. `fee_share` is the share of the marketplace
+    /// fee (in basis points) paid to the partner and `fee_override` replaces the marketplace
+    /// fee on sales made through the partner. Returns the partner id.
+    #[ink(message)]
+    fn register_partner(
+        &mut self,
+        account: AccountId,
+        fee_share: u16,
+        fee_override: Option<u16>,
+        metadata: String,
+    ) -> Result<u32, MarketplaceError>;
+
+    /// Updates fee share, fee override and branding metadata of a partner.
+    #[ink(message)]
+    fn update_partner(
+        &mut self,
+        partner_id: u32,
+        fee_share: u16,
+        fee_override: Option<u16>,
+        metadata: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Activates or deactivates a partner. Deactivated partners can still claim their
+    /// earnings.
+    #[ink(message)]
+    fn set_partner_active(&mut self, partner_id: u32, active: bool)
+        -> Result<(), MarketplaceError>;
+
+    /// Buys NFT item through a partner frontend. The partner of an escrowed listing is paid
+    /// when the escrow is released.
+    #[ink(message, payable)]
+    fn buy_via_partner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        partner_id: u32,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys NFT item through a partner frontend paying with caller's credit and deposit.
+    #[ink(message)]
+    fn buy_with_deposit_via_partner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        partner_id: u32,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Pays partner earnings in a currency to the partner account. Only the partner account
+    /// can claim.
+    #[ink(message)]
+    fn claim_partner_earnings(
+        &mut self,
+        partner_id: u32,
+        currency: Currency,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets partner.
+    #[ink(message)]
+    fn get_partner(&self, partner_id: u32) -> Option<Partner>;
+
+    /// Gets unclaimed earnings of a partner in a currency.
+    #[ink(message)]
+    fn get_partner_earnings(&self, partner_id: u32, currency: Currency) -> Balance;
+}
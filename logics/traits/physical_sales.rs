@@ -0,0 +1,48 @@
+use crate::impls::marketplace::types::{DeliveryOutcome, MarketplaceError, PhysicalTerms};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance, String},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplacePhysicalSales {
+    /// Lists a NFT backed by a physical item. The price is held in escrow until the buyer
+    /// confirms delivery or the delivery period passes. `partial_refund` is the share of the
+    /// price (in basis points) refunded to the buyer if the item is only partially delivered.
+    #[ink(message)]
+    fn list_physical_item(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        delivery_period: u64,
+        partial_refund: u16,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Confirms delivery of a physical-backed purchase and releases the price to the seller.
+    /// Only the buyer can confirm delivery.
+    #[ink(message)]
+    fn confirm_delivery(&mut self, escrow_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Resolves a dispute of a physical-backed sale by the delivery outcome. Only an arbiter
+    /// can resolve disputes.
+    #[ink(message)]
+    fn rule_on_delivery(
+        &mut self,
+        dispute_id: u128,
+        outcome: DeliveryOutcome,
+        rationale: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets delivery terms of a listed NFT.
+    #[ink(message)]
+    fn get_physical_terms(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<PhysicalTerms>;
+
+    /// Gets delivery terms of an escrowed physical-backed sale.
+    #[ink(message)]
+    fn get_escrow_physical_terms(&self, escrow_id: u128) -> Option<PhysicalTerms>;
+}
@@ -0,0 +1,12 @@
+use openbrush::traits::{Balance, String};
+
+#[openbrush::wrapper]
+pub type PriceOracleRef = dyn PriceOracle;
+
+/// Interface of an on-chain price oracle (e.g. DIA).
+#[openbrush::trait_definition]
+pub trait PriceOracle {
+    /// Gets the latest `(timestamp, price)` of a pair like `ASTR/USD`.
+    #[ink(message)]
+    fn get_latest_price(&self, pair: String) -> Option<(u64, Balance)>;
+}
@@ -0,0 +1,22 @@
+use openbrush::{
+    contracts::psp22::PSP22Error,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::wrapper]
+pub type Psp22PermitRef = dyn Psp22Permit;
+
+/// Interface of PSP22 tokens supporting signed approvals (EIP-2612 style permit).
+#[openbrush::trait_definition]
+pub trait Psp22Permit {
+    /// Verifies `owner` signature and approves `spender` to transfer `value` of owner tokens.
+    #[ink(message)]
+    fn permit(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        value: Balance,
+        deadline: u64,
+        signature: [u8; 65],
+    ) -> Result<(), PSP22Error>;
+}
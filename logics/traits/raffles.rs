@@ -0,0 +1,62 @@
+use crate::impls::marketplace::types::{MarketplaceError, Raffle};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceRaffles {
+    /// Raffles caller's NFT held by the marketplace until the raffle is drawn or fails.
+    /// `commitment` is the hash of a secret the owner reveals to draw the winner. A raffle
+    /// sells at most `MAX_RAFFLE_TICKETS` tickets.
+    #[ink(message)]
+    fn create_raffle(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        ticket_price: Balance,
+        max_tickets: u32,
+        min_tickets: u32,
+        deadline: u64,
+        commitment: [u8; 32],
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys raffle tickets before the deadline. Transferred value has to cover the tickets.
+    #[ink(message, payable)]
+    fn buy_raffle_tickets(&mut self, raffle_id: u128, tickets: u32)
+        -> Result<(), MarketplaceError>;
+
+    /// Draws the winner of a raffle that sold the minimum number of tickets once the
+    /// deadline has passed. The winner gets the NFT and the owner gets ticket proceeds less
+    /// the marketplace fee. Anyone can draw with the committed secret until the reveal period
+    /// is over.
+    #[ink(message)]
+    fn draw_raffle(
+        &mut self,
+        raffle_id: u128,
+        secret: Option<[u8; 32]>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Returns NFT of a raffle that did not sell the minimum number of tickets by the
+    /// deadline, or was not drawn by the end of the reveal period, to its owner, letting
+    /// buyers claim refunds.
+    #[ink(message)]
+    fn end_failed_raffle(&mut self, raffle_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Refunds caller's tickets of a failed raffle.
+    #[ink(message)]
+    fn claim_raffle_refund(&mut self, raffle_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets raffle.
+    #[ink(message)]
+    fn get_raffle(&self, raffle_id: u128) -> Option<Raffle>;
+
+    /// Gets ticket purchases (buyer, tickets) of a raffle in purchase order.
+    #[ink(message)]
+    fn get_raffle_entries(&self, raffle_id: u128, from: u32, limit: u32) -> Vec<(AccountId, u32)>;
+
+    /// Gets tickets of a raffle held by an account and not refunded.
+    #[ink(message)]
+    fn get_raffle_tickets(&self, raffle_id: u128, account: AccountId) -> u32;
+}
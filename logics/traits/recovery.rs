@@ -0,0 +1,21 @@
+use crate::impls::marketplace::types::{HeldAsset, MarketplaceError, PendingRecovery};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceRecovery {
+    /// Queues return of an asset held by the marketplace to its owners in case its settlement
+    /// is broken. The recovery can be executed once the timelock delay has passed.
+    #[ink(message)]
+    fn queue_recovery(&mut self, asset: HeldAsset) -> Result<u128, MarketplaceError>;
+
+    /// Returns held NFTs to sellers and held funds to buyers.
+    #[ink(message)]
+    fn execute_recovery(&mut self, recovery_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Cancels a queued recovery.
+    #[ink(message)]
+    fn cancel_recovery(&mut self, recovery_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets a queued recovery.
+    #[ink(message)]
+    fn get_recovery(&self, recovery_id: u128) -> Option<PendingRecovery>;
+}
@@ -0,0 +1,47 @@
+use crate::impls::marketplace::types::{Currency, MarketplaceError};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance, String},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceReferrals {
+    /// Registers a referral code for the caller. Codes are 1 to `MAX_REFERRAL_CODE_LEN`
+    /// ASCII letters and digits. A previous code of the caller is released.
+    #[ink(message)]
+    fn register_referral_code(&mut self, code: String) -> Result<(), MarketplaceError>;
+
+    /// Gets account a referral code is registered to.
+    #[ink(message)]
+    fn get_referral_code_owner(&self, code: String) -> Option<AccountId>;
+
+    /// Gets referral code of an account.
+    #[ink(message)]
+    fn get_referral_code(&self, account: AccountId) -> Option<String>;
+
+    /// Sets share of the marketplace fee (in basis points) paid to referrers.
+    #[ink(message)]
+    fn set_referral_fee_share(&mut self, fee_share: u16) -> Result<(), MarketplaceError>;
+
+    /// Gets share of the marketplace fee (in basis points) paid to referrers.
+    #[ink(message)]
+    fn get_referral_fee_share(&self) -> u16;
+
+    /// Buys NFT item referred by the owner of a referral code. The referrer of an escrowed
+    /// listing is paid when the escrow is released.
+    #[ink(message, payable)]
+    fn buy_with_referral(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        code: String,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Pays referral earnings of the caller in a currency.
+    #[ink(message)]
+    fn claim_referral_earnings(&mut self, currency: Currency) -> Result<(), MarketplaceError>;
+
+    /// Gets unclaimed referral earnings of an account in a currency.
+    #[ink(message)]
+    fn get_referral_earnings(&self, account: AccountId, currency: Currency) -> Balance;
+}
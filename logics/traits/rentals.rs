@@ -0,0 +1,131 @@
+use crate::impls::marketplace::types::{MarketplaceError, Rental, RentalListing};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{AccountId, Balance},
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceRentals {
+    /// Lists NFT for rent. Renters pay daily price for each rented day and leave
+    /// collateral the owner can claim if the NFT is not returned in time. On early return
+    /// the renter gets `early_return_refund` basis points of the rent for unused days back.
+    #[ink(message)]
+    fn list_for_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        collateral: Balance,
+        max_days: u32,
+        early_return_refund: u16,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Lists NFT for rent without collateral. The marketplace holds the NFT for the term and
+    /// records the renter as its user.
+    #[ink(message)]
+    fn list_for_custodial_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        max_days: u32,
+        early_return_refund: u16,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Lists NFT for rent to own. The marketplace holds the NFT for the term and transfers it
+    /// to the renter once paid rent reaches `purchase_price`. Otherwise the owner keeps paid
+    /// rent and gets the NFT back at the end of the term.
+    #[ink(message)]
+    fn list_for_rent_to_own(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+        max_days: u32,
+        purchase_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Lists NFT for an open-ended rent streamed from the renter's deposit. The marketplace
+    /// holds the NFT for the term and the owner can terminate the rental once the renter's
+    /// deposit no longer covers accrued rent.
+    #[ink(message)]
+    fn list_for_streamed_rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        daily_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes NFT from rental listings.
+    #[ink(message)]
+    fn cancel_rental_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Rents NFT for a number of days. Transferred value has to cover rent and collateral.
+    /// Streamed rentals are open-ended, so `days` is ignored and no value is transferred.
+    #[ink(message, payable)]
+    fn rent(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        days: u32,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Extends an active rental by a number of days. Transferred value has to cover the rent,
+    /// which is capped at the rest of the purchase price for rent to own.
+    #[ink(message, payable)]
+    fn extend_rental(&mut self, rental_id: u128, days: u32) -> Result<(), MarketplaceError>;
+
+    /// Returns rented NFT to its owner and refunds collateral and the owner's share of rent
+    /// for unused days to the renter. Streamed rent has to be paid up to now.
+    #[ink(message)]
+    fn return_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Returns NFT held by the marketplace to its owner once the rental term has ended.
+    #[ink(message)]
+    fn end_custodial_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Pays rent accrued since the last settlement of a streamed rental from the renter's
+    /// deposit to the owner's deposit.
+    #[ink(message)]
+    fn settle_rent_stream(&mut self, rental_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Returns NFT of a streamed rental to its owner once the renter's deposit no longer
+    /// covers accrued rent.
+    #[ink(message)]
+    fn terminate_rental(&mut self, rental_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Pays collateral of a rental that was not returned by the end of the term to the owner.
+    #[ink(message)]
+    fn claim_rental_collateral(&mut self, rental_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets rental listing of NFT.
+    #[ink(message)]
+    fn get_rental_listing(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<RentalListing>;
+
+    /// Gets rental.
+    #[ink(message)]
+    fn get_rental(&self, rental_id: u128) -> Option<Rental>;
+
+    /// Gets open rentals an account rents or rents out, skipping the first `from` ones and
+    /// returning at most `limit`.
+    #[ink(message)]
+    fn get_active_rentals_by_account(
+        &self,
+        account: AccountId,
+        from: u32,
+        limit: u32,
+    ) -> Vec<(u128, Rental)>;
+
+    /// Gets renter allowed to use NFT until the end of the rental term.
+    #[ink(message)]
+    fn user_of(&self, contract_address: AccountId, token_id: Id) -> Option<AccountId>;
+}
@@ -0,0 +1,25 @@
+use crate::impls::marketplace::types::{ChildNft, Equipment};
+use ink::prelude::vec::Vec;
+use openbrush::contracts::psp34::Id;
+
+#[openbrush::wrapper]
+pub type NestingRef = dyn Nesting;
+
+#[openbrush::wrapper]
+pub type EquippableRef = dyn Equippable;
+
+/// Interface of RMRK ink! nesting, children are NFTs owned by a parent NFT.
+#[openbrush::trait_definition]
+pub trait Nesting {
+    /// Gets children accepted by a parent token.
+    #[ink(message)]
+    fn get_accepted_children(&self, parent_token_id: Id) -> Vec<ChildNft>;
+}
+
+/// Interface of RMRK ink! equippable, children equipped into slots of a parent NFT.
+#[openbrush::trait_definition]
+pub trait Equippable {
+    /// Gets child equipped into a slot of a token.
+    #[ink(message)]
+    fn get_equipment(&self, token_id: Id, slot_part_id: u32) -> Option<Equipment>;
+}
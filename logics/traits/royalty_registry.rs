@@ -0,0 +1,31 @@
+use crate::impls::marketplace::types::MarketplaceError;
+use openbrush::traits::AccountId;
+
+#[openbrush::wrapper]
+pub type RoyaltyRegistryRef = dyn RoyaltyRegistry;
+
+/// Interface of a royalty registry shared by marketplaces, so creators configure royalty of
+/// a collection once.
+#[openbrush::trait_definition]
+pub trait RoyaltyRegistry {
+    /// Sets royalty receiver and royalty (in basis points) of a collection. `None` removes the
+    /// royalty. Callable by the owner of the NFT contract or an allowed writer.
+    #[ink(message)]
+    fn set_royalty(
+        &mut self,
+        contract_address: AccountId,
+        royalty: Option<(AccountId, u16)>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets royalty receiver and royalty (in basis points) of a collection.
+    #[ink(message)]
+    fn get_royalty(&self, contract_address: AccountId) -> Option<(AccountId, u16)>;
+
+    /// Allows or disallows an account (e.g. a marketplace) to set royalty of any collection.
+    #[ink(message)]
+    fn set_writer(&mut self, writer: AccountId, allowed: bool) -> Result<(), MarketplaceError>;
+
+    /// Checks if an account is allowed to set royalty of any collection.
+    #[ink(message)]
+    fn is_writer(&self, writer: AccountId) -> bool;
+}
@@ -0,0 +1,17 @@
+use crate::impls::marketplace::types::{BatchProgress, MarketplaceError};
+use ink::prelude::vec::Vec;
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceSnapshots {
+    /// Gets holders of up to `limit` tokens of a factory created collection, starting at token
+    /// index `cursor`. Pass the returned cursor to get the next page. Holders are current as of
+    /// the block the query runs at.
+    #[ink(message)]
+    fn snapshot_holders(
+        &self,
+        contract_address: AccountId,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<(Vec<(Id, AccountId)>, BatchProgress), MarketplaceError>;
+}
@@ -0,0 +1,46 @@
+use crate::impls::marketplace::types::{MarketplaceError, Stake};
+use openbrush::traits::{AccountId, Balance};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceStaking {
+    /// Sets PSP22 governance token stakers stake. Cannot be changed while tokens are staked.
+    #[ink(message)]
+    fn set_staking_token(&mut self, token: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets staking token.
+    #[ink(message)]
+    fn get_staking_token(&self) -> Option<AccountId>;
+
+    /// Sets share of native marketplace fees (in basis points) paid to stakers.
+    #[ink(message)]
+    fn set_staking_reward_share(&mut self, share: u16) -> Result<(), MarketplaceError>;
+
+    /// Gets share of native marketplace fees paid to stakers in basis points.
+    #[ink(message)]
+    fn get_staking_reward_share(&self) -> u16;
+
+    /// Stakes governance tokens of the caller. The stake earns rewards from the next epoch.
+    #[ink(message)]
+    fn stake(&mut self, amount: Balance) -> Result<(), MarketplaceError>;
+
+    /// Returns staked governance tokens to the caller. Stake still waiting for its first
+    /// epoch is returned first. Stake is locked while proposals the caller voted on are open.
+    #[ink(message)]
+    fn unstake(&mut self, amount: Balance) -> Result<(), MarketplaceError>;
+
+    /// Pays staking rewards of the caller.
+    #[ink(message)]
+    fn claim_rewards(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Gets stake of an account as of the last epoch it was updated in.
+    #[ink(message)]
+    fn get_stake(&self, account: AccountId) -> Stake;
+
+    /// Gets current staking epoch and the time (in milliseconds) it started.
+    #[ink(message)]
+    fn get_staking_epoch(&self) -> (u32, u64);
+
+    /// Gets tokens earning rewards in the current epoch.
+    #[ink(message)]
+    fn get_total_staked(&self) -> Balance;
+}
@@ -0,0 +1,28 @@
+use crate::impls::marketplace::types::{MarketplaceError, Subscription, SubscriptionPlan};
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceSubscriptions {
+    /// Sets plan of a subscription tier. `None` removes the tier, sales of its subscribers pay
+    /// the regular fee from then on.
+    #[ink(message)]
+    fn set_subscription_plan(
+        &mut self,
+        tier: u32,
+        plan: Option<SubscriptionPlan>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets plan of a subscription tier.
+    #[ink(message)]
+    fn get_subscription_plan(&self, tier: u32) -> Option<SubscriptionPlan>;
+
+    /// Subscribes the caller to a tier for a number of periods, or extends their subscription
+    /// to the tier. Native currency plans are paid with the transferred value, PSP22 plans
+    /// with an allowance to the marketplace.
+    #[ink(message, payable)]
+    fn subscribe(&mut self, tier: u32, periods: u32) -> Result<(), MarketplaceError>;
+
+    /// Gets active subscription of an account.
+    #[ink(message)]
+    fn get_subscription(&self, account: AccountId) -> Option<Subscription>;
+}
@@ -0,0 +1,25 @@
+use crate::impls::marketplace::types::{MarketplaceError, PendingParameterChange};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceTimelock {
+    /// Queues a change of the delay (in milliseconds) parameter changes wait before execution.
+    /// Returns id of the queued change, `None` if there is no delay and it is applied at once.
+    #[ink(message)]
+    fn set_timelock_delay(&mut self, delay: u64) -> Result<Option<u128>, MarketplaceError>;
+
+    /// Gets the delay (in milliseconds) parameter changes wait before execution.
+    #[ink(message)]
+    fn get_timelock_delay(&self) -> u64;
+
+    /// Applies a queued parameter change once its delay has passed.
+    #[ink(message)]
+    fn execute_parameter_change(&mut self, change_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Cancels a queued parameter change.
+    #[ink(message)]
+    fn cancel_parameter_change(&mut self, change_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets a queued parameter change.
+    #[ink(message)]
+    fn get_parameter_change(&self, change_id: u128) -> Option<PendingParameterChange>;
+}
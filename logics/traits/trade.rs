@@ -0,0 +1,31 @@
+use crate::impls::marketplace::types::{MarketplaceError, Trade, TradeAsset};
+use ink::prelude::vec::Vec;
+use openbrush::traits::AccountId;
+
+#[openbrush::trait_definition]
+pub trait MarketplaceTrade {
+    /// Proposes a trade of offered assets for requested ones. Native currency offered has to be
+    /// transferred with the call, other offered assets have to be approved to the marketplace.
+    /// Both sides together hold at most max batch size assets.
+    #[ink(message, payable)]
+    fn propose_trade(
+        &mut self,
+        taker: Option<AccountId>,
+        offered: Vec<TradeAsset>,
+        requested: Vec<TradeAsset>,
+    ) -> Result<u128, MarketplaceError>;
+
+    /// Accepts a trade, atomically exchanging assets of both sides. Native currency requested
+    /// has to be transferred with the call, other requested assets have to be approved to the
+    /// marketplace.
+    #[ink(message, payable)]
+    fn accept_trade(&mut self, trade_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Cancels a trade and refunds native currency offered.
+    #[ink(message)]
+    fn cancel_trade(&mut self, trade_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets trade.
+    #[ink(message)]
+    fn get_trade(&self, trade_id: u128) -> Option<Trade>;
+}
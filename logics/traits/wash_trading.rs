@@ -0,0 +1,29 @@
+use crate::impls::marketplace::types::{MarketplaceError, WashTrade};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceWashTrading {
+    /// Sets time (in milliseconds) within which a repeated sale of a token between the same
+    /// accounts is flagged as a wash trade. Zero disables the tracking.
+    #[ink(message)]
+    fn set_wash_trade_window(&mut self, window: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets wash trade window in milliseconds.
+    #[ink(message)]
+    fn get_wash_trade_window(&self) -> u64;
+
+    /// Sets whether flagged sales are left out of creator and collection earnings.
+    #[ink(message)]
+    fn set_exclude_wash_trades(&mut self, exclude: bool) -> Result<(), MarketplaceError>;
+
+    /// Checks if flagged sales are left out of creator and collection earnings.
+    #[ink(message)]
+    fn get_exclude_wash_trades(&self) -> bool;
+
+    /// Clears a wash trade flag raised by mistake. An excluded sale is added to the earnings.
+    #[ink(message)]
+    fn clear_wash_trade(&mut self, wash_trade_id: u128) -> Result<(), MarketplaceError>;
+
+    /// Gets flagged wash trade.
+    #[ink(message)]
+    fn get_wash_trade(&self, wash_trade_id: u128) -> Option<WashTrade>;
+}
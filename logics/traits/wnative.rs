@@ -0,0 +1,16 @@
+use openbrush::{contracts::psp22::PSP22Error, traits::Balance};
+
+#[openbrush::wrapper]
+pub type WnativeRef = dyn Wnative;
+
+/// Interface of the wrapped native token (e.g. WASTR) contract.
+#[openbrush::trait_definition]
+pub trait Wnative {
+    /// Wraps transferred native balance into the wrapped token.
+    #[ink(message, payable)]
+    fn deposit(&mut self) -> Result<(), PSP22Error>;
+
+    /// Burns `amount` of wrapped token and transfers the native balance back to the caller.
+    #[ink(message)]
+    fn withdraw(&mut self, amount: Balance) -> Result<(), PSP22Error>;
+}
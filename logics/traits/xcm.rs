@@ -0,0 +1,37 @@
+use crate::impls::marketplace::types::{MarketplaceError, XcmReservation};
+use openbrush::{contracts::psp34::Id, traits::AccountId};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceXcm {
+    /// Allows or disallows a parachain sovereign account to buy through XCM.
+    #[ink(message)]
+    fn set_xcm_origin(&mut self, origin: AccountId, allowed: bool) -> Result<(), MarketplaceError>;
+
+    /// Checks if a parachain sovereign account is allowed to buy through XCM.
+    #[ink(message)]
+    fn is_xcm_origin(&self, origin: AccountId) -> bool;
+
+    /// Buys NFT item for a beneficiary on another chain. Called through XCM `Transact` by an
+    /// allowed sovereign account paying from its pre-funded deposit. The marketplace holds the
+    /// NFT until the reservation is claimed.
+    #[ink(message)]
+    fn buy_via_xcm(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        beneficiary: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers reserved NFT to `to`. Callable by the origin or the beneficiary of the
+    /// reservation.
+    #[ink(message)]
+    fn claim_xcm_reservation(
+        &mut self,
+        reservation_id: u128,
+        to: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets XCM reservation.
+    #[ink(message)]
+    fn get_xcm_reservation(&self, reservation_id: u128) -> Option<XcmReservation>;
+}
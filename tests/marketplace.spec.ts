@@ -217,10 +217,9 @@ describe("Marketplace tests", () => {
         { gasLimit: getEstimatedGas(gasRequired) }
       );
     expect(unlistResult.result?.isError).to.be.false;
-    checkIfEventIsEmitted(unlistResult, "TokenListed", {
+    checkIfEventIsEmitted(unlistResult, "TokenUnlisted", {
       contract: psp34.address,
       id: { u64: 1 },
-      price: null,
     });
 
     // Check if the token is actually unlisted.
@@ -325,11 +324,16 @@ describe("Marketplace tests", () => {
 
     expect(buyResult.result?.isError).to.be.false;
     checkIfEventIsEmitted(buyResult, "TokenBought", {
+      saleId: BigInt(1),
       contract: psp34.address,
       id: { u64: 1 },
       price: BigInt("100000000000000000"),
-      from: charlie.address,
-      to: bob.address,
+      seller: charlie.address,
+      buyer: bob.address,
+      marketplaceFee: BigInt("1000000000000000"),
+      royaltyReceiver: deployer.address,
+      royalty: BigInt("1000000000000000"),
+      currency: { native: null },
     });
 
     // Balances check.
@@ -414,11 +418,16 @@ describe("Marketplace tests", () => {
 
     expect(buyResult.result?.isError).to.be.false;
     checkIfEventIsEmitted(buyResult, "TokenBought", {
+      saleId: BigInt(1),
       contract: rmrk.address,
       id: { u64: 1 },
       price: BigInt("100000000000000000"),
-      from: charlie.address,
-      to: bob.address,
+      seller: charlie.address,
+      buyer: bob.address,
+      marketplaceFee: BigInt("1000000000000000"),
+      royaltyReceiver: deployer.address,
+      royalty: BigInt("1000000000000000"),
+      currency: { native: null },
     });
 
     // Balances check.
@@ -503,11 +512,16 @@ describe("Marketplace tests", () => {
 
     expect(buyResult.result?.isError).to.be.false;
     checkIfEventIsEmitted(buyResult, "TokenBought", {
+      saleId: BigInt(1),
       contract: nftSeries.address,
       id: { u64: 1 },
       price: BigInt("100000000000000000"),
-      from: charlie.address,
-      to: bob.address,
+      seller: charlie.address,
+      buyer: bob.address,
+      marketplaceFee: BigInt("1000000000000000"),
+      royaltyReceiver: nftSeries.address,
+      royalty: BigInt("0"),
+      currency: { native: null },
     });
 
     // Balances check.
@@ -570,7 +584,7 @@ describe("Marketplace tests", () => {
     const hashValue = await marketplace.query.nftContractHash(
       NftContractType.psp34
     );
-    expect(hashValue.value.unwrap()).to.be.equal(toHex(hash));
+    expect(hashValue.value.unwrap().ok).to.be.equal(toHex(hash));
   });
 
   it("setNftContractHash fails if not an owner", async () => {
@@ -876,11 +890,16 @@ describe("Marketplace tests", () => {
 
     expect(buyResult.result?.isError).to.be.false;
     checkIfEventIsEmitted(buyResult, "TokenBought", {
+      saleId: BigInt(1),
       contract: psp34.address,
       id: { u64: 1 },
       price: BigInt("100000000000000000"),
-      from: charlie.address,
-      to: bob.address,
+      seller: charlie.address,
+      buyer: bob.address,
+      marketplaceFee: BigInt("1000000000000000"),
+      royaltyReceiver: deployer.address,
+      royalty: BigInt("1000000000000000"),
+      currency: { native: null },
     });
 
     // Balances check.